@@ -0,0 +1,67 @@
+//! Compares the size of the LLVM IR generated for the
+//! [example programs](mini_rust_compiler_components::examples) with and without promoting
+//! the local variables to SSA registers using
+//! [mem2reg](mini_rust_compiler_components::codegen::CodeGenOptions::mem2reg).
+//!
+//! Run with `cargo bench --bench ir_size`.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use inkwell::context::Context;
+use mini_rust_compiler_components::codegen::stats::IrStats;
+use mini_rust_compiler_components::codegen::{CodeGenOptions, CodeGenRequest};
+use mini_rust_compiler_components::examples;
+use mini_rust_compiler_components::parser::Parser;
+
+/// Returns the statistics of the IR generated for the given file with the given options.
+fn ir_stats(path: &Path, options: CodeGenOptions) -> IrStats {
+    let context = Context::create();
+    let crt = Parser::new(path).unwrap().parse().unwrap();
+    let (module, _) = crt
+        .code_gen_with(&context, CodeGenRequest::new().with_options(options))
+        .unwrap();
+    IrStats::of(&module)
+}
+
+/// Returns the relative change from `before` to `after` in percents.
+fn change(before: usize, after: usize) -> f64 {
+    (after as f64 - before as f64) / before as f64 * 100.0
+}
+
+fn main() {
+    let dir = env::temp_dir().join("mrs_bench_ir_size");
+    fs::create_dir_all(&dir).unwrap();
+
+    println!("Instructions of the generated IR:");
+    println!(
+        "{:<12} {:>12} {:>12} {:>9}",
+        "example", "no mem2reg", "mem2reg", "change"
+    );
+    let (mut total_before, mut total_after) = (0, 0);
+    for example in examples::all() {
+        let path = example.write_to(&dir).unwrap();
+        let before = ir_stats(&path, CodeGenOptions::new().with_mem2reg(false));
+        let after = ir_stats(&path, CodeGenOptions::new().with_mem2reg(true));
+        fs::remove_file(&path).unwrap();
+
+        println!(
+            "{:<12} {:>12} {:>12} {:>8.1}%",
+            example.name(),
+            before.instructions(),
+            after.instructions(),
+            change(before.instructions(), after.instructions())
+        );
+        total_before += before.instructions();
+        total_after += after.instructions();
+    }
+    println!(
+        "{:<12} {:>12} {:>12} {:>8.1}%",
+        "total",
+        total_before,
+        total_after,
+        change(total_before, total_after)
+    );
+    fs::remove_dir(&dir).unwrap();
+}
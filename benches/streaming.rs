@@ -0,0 +1,115 @@
+//! Compares the peak memory used by compiling a large generated file as a whole crate
+//! and in the [streaming mode](mini_rust_compiler_components::codegen::stream).
+//!
+//! Only the Rust heap is measured (by a counting global allocator), i.e. mostly the tokens
+//! and the AST, since LLVM allocates the module with its own allocator.
+//!
+//! Run with `cargo bench --bench streaming [-- <number of functions>]`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use inkwell::context::Context;
+use mini_rust_compiler_components::codegen::stream;
+use mini_rust_compiler_components::codegen::{CodeGenOptions, CodeGenRequest};
+use mini_rust_compiler_components::parser::Parser;
+
+/// The default number of the generated functions.
+const DEFAULT_FUNCTIONS: usize = 20_000;
+
+/// A global allocator that tracks the current and the peak number of allocated bytes.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Generates a source file with the given number of functions, each calling the next one.
+fn generate_source(functions: usize) -> String {
+    let mut source = String::from("static SEED: i32 = 7;\n");
+    for i in 0..functions {
+        let next = if i + 1 < functions {
+            format!("f{}(x - 1)", i + 1)
+        } else {
+            "SEED".to_owned()
+        };
+        writeln!(
+            source,
+            "pub fn f{i}(x: i32) -> i32 {{\n    \
+                let mut y: i32 = x * 2;\n    \
+                while y > 100 {{ y = y / 2 + 1; }}\n    \
+                if x > 0 {{ return y + {next}; }} else {{ return y - {i}; }}\n\
+            }}"
+        )
+        .unwrap();
+    }
+    source
+}
+
+/// Runs `f` and returns the peak number of bytes allocated during it (above the baseline).
+fn measure(name: &str, f: impl FnOnce()) {
+    let baseline = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let start = Instant::now();
+    f();
+    let elapsed = start.elapsed();
+    let peak = PEAK.load(Ordering::Relaxed) - baseline;
+    println!(
+        "{:<10} peak heap: {:>8.2} MiB, time: {:>8.2?}",
+        name,
+        peak as f64 / (1024.0 * 1024.0),
+        elapsed
+    );
+}
+
+fn main() {
+    let functions = env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_FUNCTIONS);
+    let path = env::temp_dir().join("mrs_bench_streaming.mrs");
+    fs::write(&path, generate_source(functions)).unwrap();
+    println!(
+        "{} functions, {} bytes",
+        functions,
+        fs::metadata(&path).unwrap().len()
+    );
+
+    let options = CodeGenOptions::default();
+    measure("crate", || {
+        let context = Context::create();
+        let crt = Parser::new(&path).unwrap().parse().unwrap();
+        let (module, _) = crt
+            .code_gen_with(&context, CodeGenRequest::new().with_options(options))
+            .unwrap();
+        assert!(module.get_function("f0").is_some());
+    });
+    measure("streaming", || {
+        let context = Context::create();
+        let (module, _) = stream::code_gen_file(&context, &path, options).unwrap();
+        assert!(module.get_function("f0").is_some());
+    });
+    fs::remove_file(&path).unwrap();
+}
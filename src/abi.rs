@@ -0,0 +1,82 @@
+//! A module containing the ABIs supported in extern blocks.
+//!
+//! This is the single source of truth for the ABIs: the [parser](crate::parser::Parser)
+//! reports the ones that are not listed here, and the code generation uses
+//! the [calling conventions](Abi::calling_convention) of the listed ones.
+
+use std::fmt;
+
+/// An ABI of the items in an extern block, i.e. `extern "C" { ... }`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Abi {
+    /// The default ABI of the C compiler of the target (`"C"`).
+    #[default]
+    C,
+    /// The ABI of the system libraries of the target (`"system"`).
+    ///
+    /// It is the same as [`Abi::C`] on all the targets, except for the 32-bit Windows on x86,
+    /// where it is `stdcall`.
+    System,
+}
+
+/// The LLVM `ccc` calling convention.
+const C_CALL_CONV: u32 = 0;
+
+/// The LLVM `x86_stdcallcc` calling convention.
+const X86_STDCALL_CALL_CONV: u32 = 64;
+
+impl Abi {
+    /// All the supported ABIs.
+    pub const SUPPORTED: [Abi; 2] = [Abi::C, Abi::System];
+
+    /// Returns the ABI with the given name (as written in the source code), if it is supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::abi::Abi;
+    ///
+    /// assert_eq!(Abi::from_name("C"), Some(Abi::C));
+    /// assert_eq!(Abi::from_name("stdcall"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Abi> {
+        Abi::SUPPORTED.into_iter().find(|abi| abi.name() == name)
+    }
+
+    /// Returns the name of the ABI as written in the source code.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Abi::C => "C",
+            Abi::System => "system",
+        }
+    }
+
+    /// Returns the LLVM calling convention of the functions with this ABI on the host target.
+    pub fn calling_convention(&self) -> u32 {
+        match self {
+            Abi::System if cfg!(all(windows, target_arch = "x86")) => X86_STDCALL_CALL_CONV,
+            Abi::C | Abi::System => C_CALL_CONV,
+        }
+    }
+}
+
+impl fmt::Display for Abi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        for abi in Abi::SUPPORTED {
+            assert_eq!(Abi::from_name(abi.name()), Some(abi));
+            assert_eq!(abi.to_string(), abi.name());
+        }
+        assert_eq!(Abi::from_name("c"), None);
+        assert_eq!(Abi::from_name("Rust"), None);
+    }
+}
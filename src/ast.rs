@@ -19,34 +19,44 @@
 // - Literal
 // - Variable & underscore expression
 // - Function call
+// - Tuple (+ tuple index)
 // - Assignment
 // - Operator
 //   - Arithmetic or logical
 //   - Comparison
 //   - Negation
 //   - Lazy boolean
+//   - Borrow & dereference
 // - Type cast
+// - Layout query (size & alignment of a type)
 // - Loop
 //   - Infinite loop
 //   - While loop
-// - If
+//   - Do-while loop
+//   - For loop
+// - If (+ else branch)
 // - Unsafe block
 // - Return
 
 use std::fmt;
 use std::fmt::Debug;
+use std::path::Path;
+use std::rc::Rc;
 
 use debug_tree::{TreeBuilder, TreeConfig, TreeSymbols};
-use inkwell::context::Context;
-use inkwell::module::Module;
 
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::ast::error::SemanticError;
+use crate::init::InitChecker;
+use crate::metrics::AstStats;
+use crate::pretty::SourcePrinter;
+use crate::resolve::{ResolutionMap, Resolver};
+use crate::token::Span;
+use crate::unsafety::{UnsafeBlock, UnsafetyChecker};
 
 pub use self::crt::*;
 pub use self::expr::*;
 pub use self::item::*;
+pub use self::lower::*;
 use self::node::*;
 pub use self::node::{ASTChildIterator, ASTNode, AsASTNode};
 pub use self::r#type::*;
@@ -56,35 +66,189 @@ mod crt;
 pub mod error;
 mod expr;
 mod item;
+mod lower;
 mod stmt;
 mod r#type;
 
+/// Returns the number of nodes in the subtree of the given node, including the node itself.
+pub(crate) fn node_count(node: &dyn ASTNode) -> usize {
+    1 + node
+        .children()
+        .map_or(0, |children| children.map(node_count).sum())
+}
+
+/// Returns the span of the first node in the given subtree for which `uses` returns `true`,
+/// if there is one. Besides the node, `uses` is given the type declared by the node
+/// (e.g. the type of a let statement, a parameter or a type cast), if it declares one.
+///
+/// The backends which cannot represent some constructs use it to reject them up front.
+pub(crate) fn find_usage(
+    node: &dyn ASTNode,
+    uses: &dyn Fn(&dyn ASTNode, Option<Type>) -> bool,
+) -> Option<Span> {
+    let any = node.as_any();
+    if let Some(item) = any.downcast_ref::<ItemASTNode>() {
+        let item: &dyn ASTNode = match item {
+            ItemASTNode::Func(func) => func.as_ref(),
+            ItemASTNode::Extern(ext) => ext.as_ref(),
+            ItemASTNode::Static(stat) => stat.as_ref(),
+        };
+        return find_usage(item, uses);
+    }
+
+    let declared = if let Some(let_node) = any.downcast_ref::<LetASTNode>() {
+        let_node.ty()
+    } else if let Some(param) = any.downcast_ref::<ParamASTNode>() {
+        Some(param.ty().ty())
+    } else if let Some(proto) = any.downcast_ref::<FuncProtoASTNode>() {
+        Some(proto.return_type().ty())
+    } else if let Some(stat) = any.downcast_ref::<StaticASTNode>() {
+        Some(stat.ty().ty())
+    } else {
+        any.downcast_ref::<TypeCastASTNode>().map(|cast| cast.ty())
+    };
+    if uses(node, declared) {
+        return Some(node.span());
+    }
+    node.children()?.find_map(|child| find_usage(child, uses))
+}
+
 /// The AST for the μRust language.
 #[derive(Debug)]
 pub struct Crate {
     root: Box<CrateASTNode>,
+    source_path: Option<Rc<Path>>,
 }
 
 impl Crate {
     /// Creates a new `Crate` with the given root node.
     pub fn new(root: Box<CrateASTNode>) -> Crate {
-        Crate { root }
+        Crate {
+            root,
+            source_path: None,
+        }
+    }
+
+    /// Sets the path of the source file of this crate.
+    pub fn with_source_path(self, path: Rc<Path>) -> Crate {
+        Crate {
+            source_path: Some(path),
+            ..self
+        }
+    }
+
+    /// Returns the path of the source file of this crate, if it is known
+    /// (i.e. the crate was parsed from a file rather than e.g. [decoded](Crate::from_bytes)).
+    pub fn source_path(&self) -> Option<&Path> {
+        self.source_path.as_deref()
     }
 
-    /// Generates the LLVM IR for this crate given the context.
-    pub fn code_gen<'ctx>(&self, context: &'ctx Context) -> codegen::Result<Module<'ctx>> {
-        let module_name = self.root.name();
-        let mut state = CodeGenState::new(context, module_name);
+    /// Returns the root node of this crate.
+    pub fn root(&self) -> &CrateASTNode {
+        &self.root
+    }
 
-        self.root.collect_symbols(&mut state)?;
-        self.root.code_gen(&mut state)?;
+    /// Merges the items (and the imports) of the `other` crate into this one, e.g. to accumulate
+    /// the items parsed by a REPL into one logical crate, which can then be checked and
+    /// [compiled item by item](CrateASTNode::code_gen_item).
+    ///
+    /// The name and the source path of this crate are kept. The spans of the merged items
+    /// still refer to the source of the `other` crate.
+    ///
+    /// # Errors
+    ///
+    /// If `other` declares a name that is already declared, [`SemanticError::DuplicateItem`]
+    /// is returned and this crate is not changed (see [`CrateASTNode::merge`]).
+    pub fn merge(&mut self, other: Crate) -> Result<(), SemanticError> {
+        self.root.merge(*other.root)
+    }
+
+    /// Returns the top-level items of this crate.
+    ///
+    /// See also [`Crate::functions`], [`Crate::statics`] and [`Crate::extern_blocks`]
+    /// for the items of a specific kind.
+    pub fn items(&self) -> &[ItemASTNode] {
+        self.root.items()
+    }
+
+    /// Returns an iterator over the functions defined in this crate.
+    ///
+    /// See [`CrateASTNode::functions`].
+    pub fn functions(&self) -> impl Iterator<Item = &FuncASTNode> {
+        self.root.functions()
+    }
+
+    /// Returns an iterator over the static items defined in this crate.
+    ///
+    /// See [`CrateASTNode::statics`].
+    pub fn statics(&self) -> impl Iterator<Item = &StaticASTNode> {
+        self.root.statics()
+    }
+
+    /// Returns an iterator over the extern blocks in this crate.
+    ///
+    /// See [`CrateASTNode::extern_blocks`].
+    pub fn extern_blocks(&self) -> impl Iterator<Item = &ExternASTNode> {
+        self.root.extern_blocks()
+    }
+
+    /// Returns an iterator over the names of the items exported from this crate.
+    ///
+    /// See [`CrateASTNode::exports`].
+    pub fn exports(&self) -> impl Iterator<Item = &str> {
+        self.root.exports()
+    }
+
+    /// Returns the number of nodes in the AST of this crate, including the root.
+    pub fn node_count(&self) -> usize {
+        node_count(self.root.as_ref())
+    }
+
+    /// Returns the statistics of the AST of this crate, e.g. the numbers of the nodes
+    /// by their kind and the complexity of the functions.
+    pub fn stats(&self) -> AstStats {
+        AstStats::of(&self.root)
+    }
+
+    /// Resolves all the names in this crate and returns the index of their declarations
+    /// and references.
+    pub fn resolve_names(&self) -> ResolutionMap {
+        let mut resolver = Resolver::new();
+        self.root.resolve_names(&mut resolver);
+        resolver.finish()
+    }
+
+    /// Prints this crate back as μRust source code.
+    ///
+    /// The parentheses of [grouped expressions](GroupedExprASTNode) are kept, and any other
+    /// parentheses needed to preserve the structure of the AST are added
+    /// (see [`SourcePrinter`]).
+    pub fn to_source(&self) -> String {
+        let mut printer = SourcePrinter::new();
+        self.root.print_source(&mut printer);
+        printer.finish()
+    }
 
-        let module = state.take_module();
-        module
-            .verify()
-            .map_err(CodeGenError::ModuleVerificationFailed)?;
+    /// Checks that the local variables are definitely initialized before they are used.
+    ///
+    /// Returns [`SemanticError::UninitializedVariable`] for every read
+    /// of a possibly-uninitialized variable.
+    pub fn check_initialization(&self) -> Vec<SemanticError> {
+        let mut checker = InitChecker::new();
+        self.root.check_init(&mut checker);
+        checker.finish()
+    }
 
-        Ok(module)
+    /// Checks which `unsafe` blocks are actually required by the operations inside them.
+    ///
+    /// Returns all the `unsafe` blocks in this crate, ordered by their position.
+    /// The unused ones are reported as [`SemanticWarning::UnusedUnsafe`]
+    /// by [`Crate::code_gen_with`].
+    pub fn check_unsafety(&self) -> Vec<UnsafeBlock> {
+        let map = self.resolve_names();
+        let mut checker = UnsafetyChecker::new(self.root.items(), &map);
+        self.root.check_unsafety(&mut checker);
+        checker.finish()
     }
 }
 
@@ -101,24 +265,38 @@ impl fmt::Display for Crate {
 
 /// A submodule containing the common AST node interface and utilities.
 mod node {
+    use std::any::Any;
     use std::fmt;
 
     use debug_tree::TreeBuilder;
 
-    use crate::codegen::CodeGen;
+    use crate::init::InitChecker;
+    use crate::pretty::SourcePrinter;
+    use crate::resolve::Resolver;
+    use crate::serialize::Encoder;
     use crate::token::Span;
+    use crate::unsafety::UnsafetyChecker;
 
     /// A type alias for an iterator over the children of an AST node.
     pub type ASTChildIterator<'a> = Box<dyn Iterator<Item = &'a dyn ASTNode> + 'a>;
 
     //TODO Add examples to all the doc comments.
     /// A trait defining the common interface for all AST nodes.
-    pub trait ASTNode: AsASTNode + for<'ctx> CodeGen<'ctx, ()> + fmt::Debug + fmt::Display {
+    pub trait ASTNode: AsASTNode + fmt::Debug + fmt::Display {
         /// Returns the span that defines the location of this AST node.
         fn span(&self) -> Span;
 
         /// Returns an iterator over the children of this AST node, if any.
-        fn children(&self) -> Option<ASTChildIterator>;
+        fn children(&self) -> Option<ASTChildIterator<'_>>;
+
+        /// Prints this AST node (and its descendants) as μRust source code.
+        fn print_source(&self, printer: &mut SourcePrinter);
+
+        /// Encodes this AST node (and its descendants) into the binary format.
+        ///
+        /// The node has to write its [tag](crate::serialize::NodeTag) first, and the fields in the order
+        /// expected by the [`Decoder`](crate::serialize::Decoder).
+        fn encode(&self, encoder: &mut Encoder);
 
         /// Adds this AST node to the given tree builder.
         ///
@@ -143,20 +321,101 @@ mod node {
                 }
             }
         }
+
+        /// Resolves the names declared and referenced in this AST node (and its descendants).
+        ///
+        /// The default implementation resolves the names in all the [children] of the node.
+        /// It should be overridden by the nodes that declare or reference a name, or open a new scope.
+        ///
+        /// [children]: ASTNode::children
+        fn resolve_names(&self, resolver: &mut Resolver) {
+            if let Some(children) = self.children() {
+                for child in children {
+                    child.resolve_names(resolver);
+                }
+            }
+        }
+
+        /// Checks that the local variables used in this AST node (and its descendants)
+        /// are definitely initialized.
+        ///
+        /// The default implementation checks all the [children] of the node in order,
+        /// i.e. it assumes that they are all evaluated unconditionally. It should be overridden
+        /// by the nodes that declare, read or write a local variable, open a new scope,
+        /// or branch the control flow.
+        ///
+        /// [children]: ASTNode::children
+        fn check_init(&self, checker: &mut InitChecker) {
+            if let Some(children) = self.children() {
+                for child in children {
+                    child.check_init(checker);
+                }
+            }
+        }
+
+        /// Checks which `unsafe` blocks are required by the operations in this AST node
+        /// (and its descendants).
+        ///
+        /// The default implementation checks all the [children] of the node.
+        /// It should be overridden by the nodes that use a path or are an `unsafe` block.
+        ///
+        /// [children]: ASTNode::children
+        fn check_unsafety(&self, checker: &mut UnsafetyChecker) {
+            if let Some(children) = self.children() {
+                for child in children {
+                    child.check_unsafety(checker);
+                }
+            }
+        }
     }
 
-    /// An auto-trait for converting a type into a reference to a `dyn ASTNode`.
+    /// An auto-trait for converting a type into a reference to a `dyn ASTNode` or a `dyn Any`.
     ///
     /// It is automatically implemented for all types that implement [`ASTNode`].
     pub trait AsASTNode {
         /// Returns a reference to this AST node as a `dyn ASTNode`.
         fn as_ast(&self) -> &dyn ASTNode;
+
+        /// Returns a reference to this AST node as a `dyn Any`,
+        /// so that it can be downcast to its concrete type.
+        ///
+        /// See also the `is` and `downcast_ref` methods of `dyn ASTNode`.
+        fn as_any(&self) -> &dyn Any;
     }
 
-    impl<T: ASTNode> AsASTNode for T {
+    impl<T: ASTNode + 'static> AsASTNode for T {
         fn as_ast(&self) -> &dyn ASTNode {
             self
         }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    impl dyn ASTNode {
+        /// Returns whether this AST node is of type `T`.
+        pub fn is<T: ASTNode + 'static>(&self) -> bool {
+            self.as_any().is::<T>()
+        }
+
+        /// Returns a reference to this AST node as a `T`, if it is of type `T`.
+        ///
+        /// # Example
+        /// ```
+        /// # use mini_rust_compiler_components::ast::{ASTNode, LiteralASTNode, PathASTNode};
+        /// # use mini_rust_compiler_components::token::{Position, Span};
+        /// let span = Span::new(Position::new(), Position::new());
+        /// let literal = LiteralASTNode::<i128>::new(42, span);
+        /// let node: &dyn ASTNode = &literal;
+        ///
+        /// let literal = node.downcast_ref::<LiteralASTNode<i128>>().unwrap();
+        /// assert_eq!(*literal.value(), 42);
+        /// assert!(node.downcast_ref::<PathASTNode>().is_none());
+        /// ```
+        pub fn downcast_ref<T: ASTNode + 'static>(&self) -> Option<&T> {
+            self.as_any().downcast_ref::<T>()
+        }
     }
 
     /// A macro that can be used as a shorthand for implementing [`ASTNode::span`].
@@ -165,6 +424,7 @@ mod node {
     /// ```ignore
     /// use debug_tree::TreeBuilder;
     /// use crate::ast::{as_ast, ast_defaults, ASTNode, ASTChildIterator};
+    /// use crate::pretty::SourcePrinter;
     /// use crate::token::Span;
     ///
     /// struct MyNode {
@@ -178,6 +438,14 @@ mod node {
     ///         // Your implementation for `children`.
     ///     }
     ///
+    ///     fn print_source(&self, printer: &mut SourcePrinter) {
+    ///         // Your implementation for `print_source`.
+    ///     }
+    ///
+    ///     fn encode(&self, encoder: &mut Encoder) {
+    ///         // Your implementation for `encode`.
+    ///     }
+    ///
     ///     fn add_to_tree_string(&self, builder: &mut TreeBuilder) {
     ///         // Your implementation for `add_to_tree_string`
     ///         // (or just use the trait's base implementation).
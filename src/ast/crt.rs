@@ -1,25 +1,42 @@
 //! A module containing the Crate AST node implementation.
 
+use std::collections::HashMap;
 use std::fmt;
 use std::rc::Rc;
 
-use crate::ast::{ast_defaults, ASTChildIterator, ASTNode, AsASTNode, ItemASTNode};
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::ast::error::SemanticError;
+use crate::ast::{
+    ast_defaults, ASTChildIterator, ASTNode, AsASTNode, ExternASTNode, FuncASTNode, ImportASTNode,
+    ItemASTNode, ItemMeta, StaticASTNode,
+};
+use crate::pretty::SourcePrinter;
+use crate::resolve::Resolver;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing a crate.
 #[derive(Debug)]
 pub struct CrateASTNode {
     name: Rc<str>,
+    imports: Vec<ImportASTNode>,
     items: Vec<ItemASTNode>,
     span: Span,
 }
 
 impl CrateASTNode {
-    /// Creates a new `CrateASTNode` with the given name, items and span.
-    pub fn new(name: Rc<str>, items: Vec<ItemASTNode>, span: Span) -> CrateASTNode {
-        CrateASTNode { name, items, span }
+    /// Creates a new `CrateASTNode` with the given name, imports, items and span.
+    pub fn new(
+        name: Rc<str>,
+        imports: Vec<ImportASTNode>,
+        items: Vec<ItemASTNode>,
+        span: Span,
+    ) -> CrateASTNode {
+        CrateASTNode {
+            name,
+            imports,
+            items,
+            span,
+        }
     }
 
     /// Returns the name of the crate.
@@ -27,27 +44,148 @@ impl CrateASTNode {
         &self.name
     }
 
+    /// Returns the imports of the functions from other μRust files.
+    pub fn imports(&self) -> &[ImportASTNode] {
+        &self.imports
+    }
+
     /// Returns the items in the crate.
     pub fn items(&self) -> &[ItemASTNode] {
         &self.items
     }
+
+    /// Returns an iterator over the functions defined in the crate.
+    ///
+    /// The functions declared in extern blocks are not included.
+    pub fn functions(&self) -> impl Iterator<Item = &FuncASTNode> {
+        self.items.iter().filter_map(|item| match item {
+            ItemASTNode::Func(func) => Some(func.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the static items defined in the crate.
+    ///
+    /// The statics declared in extern blocks are not included.
+    pub fn statics(&self) -> impl Iterator<Item = &StaticASTNode> {
+        self.items.iter().filter_map(|item| match item {
+            ItemASTNode::Static(stat) => Some(stat.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the extern blocks in the crate.
+    pub fn extern_blocks(&self) -> impl Iterator<Item = &ExternASTNode> {
+        self.items.iter().filter_map(|item| match item {
+            ItemASTNode::Extern(ext) => Some(ext.as_ref()),
+            _ => None,
+        })
+    }
+
+    /// Returns the function or static item with the given name, if any.
+    ///
+    /// The items declared in extern blocks are not included.
+    pub fn find_item(&self, name: &str) -> Option<&ItemASTNode> {
+        self.items.iter().find(|item| item.name() == Some(name))
+    }
+
+    /// Returns an iterator over the names declared at the top level of the crate
+    /// (i.e. by the imports, the items and the items of the extern blocks),
+    /// together with the spans of the names.
+    fn declared_names(&self) -> impl Iterator<Item = (&str, Span)> {
+        let imports = self
+            .imports
+            .iter()
+            .map(|import| (import.name(), import.name_span()));
+        let items = self.items.iter().flat_map(|item| match item {
+            ItemASTNode::Func(func) => vec![(func.proto().name(), func.proto().name_span())],
+            ItemASTNode::Static(stat) => vec![(stat.name(), stat.name_span())],
+            ItemASTNode::Extern(ext) => ext
+                .items()
+                .iter()
+                .map(|item| (item.name(), item.name_span()))
+                .collect(),
+        });
+        imports.chain(items)
+    }
+
+    /// Appends the imports and the items of the `other` crate to this one.
+    ///
+    /// # Errors
+    ///
+    /// If any name declared in `other` is already declared in this crate (or earlier
+    /// in `other`), [`SemanticError::DuplicateItem`] is returned and this crate is not changed.
+    pub fn merge(&mut self, other: CrateASTNode) -> Result<(), SemanticError> {
+        let mut declared: HashMap<&str, Span> = HashMap::new();
+        for (name, span) in self.declared_names() {
+            declared.entry(name).or_insert(span);
+        }
+        for (name, span) in other.declared_names() {
+            if let Some(&first_span) = declared.get(name) {
+                return Err(SemanticError::DuplicateItem {
+                    name: name.into(),
+                    first_span,
+                    span,
+                });
+            }
+            declared.insert(name, span);
+        }
+
+        self.imports.extend(other.imports);
+        self.items.extend(other.items);
+        Ok(())
+    }
+
+    /// Returns an iterator over the names of the items exported from the crate,
+    /// i.e. the `pub` functions and statics.
+    pub fn exports(&self) -> impl Iterator<Item = &str> {
+        self.items
+            .iter()
+            .filter(|item| item.visibility().is_public())
+            .filter_map(|item| item.name())
+    }
 }
 
 impl ASTNode for CrateASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
-        let iter = self.items.iter().map(|i| i.as_ast());
-        Some(Box::new(iter))
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        let imports = self.imports.iter().map(|i| i.as_ast());
+        let items = self.items.iter().map(|i| i.inner_ast());
+        Some(Box::new(imports.chain(items)))
     }
-}
 
-impl<'ctx> CodeGen<'ctx, ()> for CrateASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        for import in &self.imports {
+            import.resolve_names(resolver);
+        }
+        resolver.declare_items(&self.items);
         for item in &self.items {
-            item.code_gen(state)?;
+            item.resolve_names(resolver);
         }
-        Ok(())
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        for import in &self.imports {
+            import.print_source(printer);
+            printer.new_line();
+        }
+        for (i, item) in self.items.iter().enumerate() {
+            // The items (and the imports) are separated by an empty line
+            if i > 0 || !self.imports.is_empty() {
+                printer.new_line();
+            }
+            item.print_source(printer);
+            printer.new_line();
+        }
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Crate);
+        encoder.write_str(&self.name);
+        encoder.write_list(self.imports.iter().map(|i| i.as_ast()));
+        encoder.write_list(self.items.iter().map(|i| i.inner_ast()));
+        encoder.write_span(self.span);
     }
 }
 
@@ -56,3 +194,109 @@ impl fmt::Display for CrateASTNode {
         write!(f, "Crate")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::abi::Abi;
+    use crate::ast::{ItemKind, Visibility};
+    use crate::test_util::parse;
+    use crate::token::Position;
+
+    use super::*;
+
+    #[test]
+    fn test_item_queries() {
+        let crt = parse(
+            "extern \"C\" {\n\
+                fn print(n: i32);\n\
+                static mut X: i32;\n\
+            }\n\
+            pub static Y: f64 = 0.5;\n\
+            fn f(a: i32, b: bool) -> i32 { return a; }\n\
+            pub fn main() { return; }\n",
+        );
+
+        assert_eq!(crt.items().len(), 4);
+        let functions: Vec<&str> = crt.functions().map(|func| func.proto().name()).collect();
+        assert_eq!(functions, vec!["f", "main"]);
+        let statics: Vec<&str> = crt.statics().map(StaticASTNode::name).collect();
+        assert_eq!(statics, vec!["Y"]);
+        assert_eq!(crt.extern_blocks().count(), 1);
+        assert_eq!(crt.extern_blocks().next().unwrap().abi(), Abi::C);
+
+        let metadata: Vec<_> = crt
+            .items()
+            .iter()
+            .map(|item| (item.kind(), item.name(), item.visibility()))
+            .collect();
+        assert_eq!(
+            metadata,
+            vec![
+                (ItemKind::Extern, None, Visibility::Private),
+                (ItemKind::Static, Some("Y"), Visibility::Public),
+                (ItemKind::Func, Some("f"), Visibility::Private),
+                (ItemKind::Func, Some("main"), Visibility::Public),
+            ]
+        );
+        assert_eq!(crt.exports().collect::<Vec<_>>(), vec!["Y", "main"]);
+
+        let main = &crt.items()[3];
+        assert_eq!(main.span(), main.inner_ast().span());
+        assert_eq!(main.inner_ast().to_string(), main.to_string());
+        assert!(main.inner_ast().as_any().is::<FuncASTNode>());
+        assert!(main.as_ast().as_any().is::<ItemASTNode>());
+        let extern_items: Vec<_> = crt
+            .extern_blocks()
+            .flat_map(|ext| ext.items())
+            .map(|item| (item.name(), item.name_span().start().line()))
+            .collect();
+        assert_eq!(extern_items, vec![("print", 2), ("X", 3)]);
+        let block = crt.extern_blocks().next().unwrap();
+        assert!(block
+            .items()
+            .iter()
+            .all(|item| block.span().contains(item.span())));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut crt = parse("extern \"C\" { fn print(n: i32); }\nfn f() -> i32 { return 1; }\n");
+        crt.merge(parse(
+            "static X: i32 = 2;\npub fn g() -> i32 { return f() + X; }\n",
+        ))
+        .unwrap();
+        let names: Vec<_> = crt.items().iter().filter_map(|item| item.name()).collect();
+        assert_eq!(names, vec!["f", "X", "g"]);
+        assert_eq!(crt.root().name(), "main.mrs");
+        assert!(crt.root().find_item("g").is_some());
+
+        let duplicates = [
+            ("fn f() {}\n", "f", (2, 4), (1, 4)),
+            ("static g: i32 = 1;\n", "g", (2, 8), (1, 8)),
+            (
+                "extern \"C\" { fn print(n: i32); }\n",
+                "print",
+                (1, 17),
+                (1, 17),
+            ),
+            ("fn h() {}\nfn h() {}\n", "h", (1, 4), (2, 4)),
+        ];
+        for (source, name, first, second) in duplicates {
+            let position = |(line, column)| Position::new_at(line, column);
+            match crt.merge(parse(source)) {
+                Err(SemanticError::DuplicateItem {
+                    name: duplicate,
+                    first_span,
+                    span,
+                }) => {
+                    assert_eq!(&*duplicate, name);
+                    assert_eq!(first_span.start(), position(first));
+                    assert_eq!(span.start(), position(second));
+                }
+                result => panic!("Expected a duplicate `{name}`, got {result:?}"),
+            }
+        }
+        // The failed merges don't change the crate
+        assert_eq!(crt.items().len(), 4);
+    }
+}
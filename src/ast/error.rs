@@ -3,6 +3,8 @@
 use std::error::Error;
 use std::fmt;
 
+use crate::ast::Type;
+use crate::suggestion::Suggestion;
 use crate::token::Span;
 
 /// An error that can occur during semantic analysis on the AST.
@@ -33,6 +35,152 @@ pub enum SemanticError {
         /// The span of the function body.
         span: Span,
     },
+    /// An error that occurs when a function is declared without a body
+    /// outside an extern block.
+    FunctionWithoutBody {
+        /// The span of the function prototype.
+        span: Span,
+    },
+    /// An error that occurs when a visibility modifier is used on an item
+    /// that does not accept one (e.g. an extern block).
+    VisibilityNotPermitted {
+        /// The span of the visibility modifier.
+        span: Span,
+    },
+    /// An error that occurs when a function has multiple parameters with the same name.
+    DuplicateParameter {
+        /// The name of the parameter.
+        name: Box<str>,
+        /// The span of the first parameter with the name.
+        first_span: Span,
+        /// The span of the duplicate parameter.
+        span: Span,
+    },
+    /// An error that occurs when multiple items (or imports) are declared with the same name,
+    /// e.g. when [merging crates](crate::ast::Crate::merge).
+    DuplicateItem {
+        /// The name of the item.
+        name: Box<str>,
+        /// The span of the name of the first item with the name.
+        first_span: Span,
+        /// The span of the name of the duplicate item.
+        span: Span,
+    },
+    /// An error that occurs when a function is called with a wrong number of arguments.
+    ArgumentCountMismatch {
+        /// The name of the function.
+        name: Box<str>,
+        /// The number of the parameters of the function.
+        expected: usize,
+        /// The number of the arguments of the call.
+        actual: usize,
+        /// The span of the call.
+        span: Span,
+    },
+    /// An error that occurs when a local variable is read
+    /// before it is definitely initialized.
+    UninitializedVariable {
+        /// The name of the variable.
+        name: Box<str>,
+        /// The span of the variable declaration.
+        decl_span: Span,
+        /// The span of the read.
+        span: Span,
+    },
+    /// An error that occurs when an immutable local variable is assigned
+    /// while it is possibly initialized already.
+    ///
+    /// The `mut` keyword should be added at the start of the declaration.
+    ImmutableAssignedTwice {
+        /// The name of the variable.
+        name: Box<str>,
+        /// The span of the variable declaration (without the `let` keyword).
+        decl_span: Span,
+        /// The span of the assignment.
+        span: Span,
+    },
+    /// An error that occurs when the condition of an `if` or `while` is an assignment,
+    /// which is most likely a mistyped comparison (e.g. `if x = 1`).
+    AssignmentInCondition {
+        /// The comparison that can be used instead.
+        suggestion: Box<str>,
+        /// The span of the condition.
+        span: Span,
+    },
+    /// An error that occurs when the initializers of static items refer to each other in a cycle.
+    CyclicStaticInitializers {
+        /// The names of the statics in the cycle, starting and ending with the same one.
+        cycle: Box<[Box<str>]>,
+        /// The span of the reference closing the cycle.
+        span: Span,
+    },
+    /// An error that occurs when a qualified path does not refer to any builtin constant.
+    UnknownAssociatedConstant {
+        /// The qualified path.
+        path: Box<str>,
+        /// The span of the path.
+        span: Span,
+    },
+    /// An error that occurs when a function other than the
+    /// [layout queries](crate::ast::LayoutQuery) is called with a type argument.
+    UnknownGenericFunction {
+        /// The name of the function.
+        name: Box<str>,
+        /// The span of the name.
+        span: Span,
+    },
+    /// An error that occurs when a tuple index refers to an element that doesn't exist
+    /// (or the indexed value is not a tuple).
+    NoTupleField {
+        /// The type of the indexed value.
+        ty: Type,
+        /// The index.
+        index: u32,
+        /// The span of the tuple index expression.
+        span: Span,
+    },
+    /// An error that occurs when an immutable local variable is borrowed mutably (`&mut x`).
+    ///
+    /// The `mut` keyword should be added at the start of the declaration.
+    ImmutableBorrowedAsMutable {
+        /// The name of the variable.
+        name: Box<str>,
+        /// The span of the variable declaration (without the `let` keyword).
+        decl_span: Span,
+        /// The span of the borrow.
+        span: Span,
+    },
+    /// An error that occurs when a value which is not a reference is dereferenced.
+    NotDereferenceable {
+        /// The type of the dereferenced value.
+        ty: Type,
+        /// The span of the dereference expression.
+        span: Span,
+    },
+    /// An error that occurs when a value behind a shared reference is assigned to
+    /// or borrowed mutably (e.g. `*r = 1` where `r` is a `&i32`).
+    MutationBehindSharedReference {
+        /// The type of the reference.
+        ty: Type,
+        /// The span of the dereference expression.
+        span: Span,
+    },
+    /// An error that occurs when a function returns a reference to its local variable
+    /// (or to a temporary value), which no longer exists once the function returns.
+    ReturnedLocalReference {
+        /// The span of the borrow expression.
+        span: Span,
+    },
+    /// An error that occurs when an integer literal (e.g. `256u8` or `let x: u8 = 256;`)
+    /// or the constant initializer of a static is out of the range of its type.
+    IntOutOfRange {
+        /// The value (negative for a negated literal, e.g. `-129i8`).
+        value: i128,
+        /// The type of the value.
+        ty: Type,
+        /// The span of the literal or the initializer.
+        span: Span,
+    },
 }
 
 impl fmt::Display for SemanticError {
@@ -58,8 +206,270 @@ impl fmt::Display for SemanticError {
                     span
                 )
             }
+            SemanticError::FunctionWithoutBody { span } => {
+                write!(
+                    f,
+                    "Function declared without a body at {} (add a body or move the declaration into an extern block)",
+                    span
+                )
+            }
+            SemanticError::VisibilityNotPermitted { span } => {
+                write!(f, "Visibility modifier is not permitted here at {}", span)
+            }
+            SemanticError::DuplicateParameter {
+                name,
+                first_span,
+                span,
+            } => {
+                write!(
+                    f,
+                    "Parameter `{}` (first declared at {}) is bound more than once at {}",
+                    name, first_span, span
+                )
+            }
+            SemanticError::DuplicateItem {
+                name,
+                first_span,
+                span,
+            } => {
+                write!(
+                    f,
+                    "Item `{}` (first declared at {}) is declared more than once at {}",
+                    name, first_span, span
+                )
+            }
+            SemanticError::ArgumentCountMismatch {
+                name,
+                expected,
+                actual,
+                span,
+            } => {
+                write!(
+                    f,
+                    "Function `{}` takes {} argument(s), but {} were supplied at {}",
+                    name, expected, actual, span
+                )
+            }
+            SemanticError::UninitializedVariable {
+                name,
+                decl_span,
+                span,
+            } => {
+                write!(
+                    f,
+                    "Variable `{}` (declared at {}) is possibly uninitialized when used at {}",
+                    name, decl_span, span
+                )
+            }
+            SemanticError::ImmutableAssignedTwice {
+                name,
+                decl_span,
+                span,
+            } => {
+                write!(
+                    f,
+                    "Cannot assign twice to immutable variable `{}` (declared at {}) at {} \
+                    -- consider adding `mut` at {}",
+                    name,
+                    decl_span,
+                    span,
+                    decl_span.start()
+                )
+            }
+            SemanticError::AssignmentInCondition { suggestion, span } => {
+                write!(
+                    f,
+                    "Expected a boolean condition, found an assignment at {} \
+                    (use `{}` to compare for equality)",
+                    span, suggestion
+                )
+            }
+            SemanticError::CyclicStaticInitializers { cycle, span } => {
+                let cycle: Vec<String> = cycle.iter().map(|name| format!("`{}`", name)).collect();
+                write!(
+                    f,
+                    "Cycle in the static initializers ({}) detected at {}",
+                    cycle.join(" -> "),
+                    span
+                )
+            }
+            SemanticError::UnknownAssociatedConstant { path, span } => {
+                write!(f, "Cannot find the constant `{}` at {}", path, span)
+            }
+            SemanticError::UnknownGenericFunction { name, span } => {
+                write!(
+                    f,
+                    "Cannot find the generic function `{}` at {} \
+                    (only `size_of` and `align_of` take a type argument)",
+                    name, span
+                )
+            }
+            SemanticError::NoTupleField { ty, index, span } => {
+                write!(f, "No field `{}` on type `{}` at {}", index, ty, span)
+            }
+            SemanticError::ImmutableBorrowedAsMutable {
+                name,
+                decl_span,
+                span,
+            } => {
+                write!(
+                    f,
+                    "Cannot borrow immutable variable `{}` (declared at {}) as mutable at {} \
+                    -- consider adding `mut` at {}",
+                    name,
+                    decl_span,
+                    span,
+                    decl_span.start()
+                )
+            }
+            SemanticError::NotDereferenceable { ty, span } => {
+                write!(f, "Type `{}` cannot be dereferenced at {}", ty, span)
+            }
+            SemanticError::MutationBehindSharedReference { ty, span } => {
+                write!(
+                    f,
+                    "Cannot mutate the value behind a shared reference of type `{}` at {}",
+                    ty, span
+                )
+            }
+            SemanticError::ReturnedLocalReference { span } => {
+                write!(
+                    f,
+                    "Cannot return a reference to a local value of the function at {}",
+                    span
+                )
+            }
+            SemanticError::IntOutOfRange { value, ty, span } => {
+                let range = ty.int_range().expect("Only integers can be out of range");
+                write!(
+                    f,
+                    "Integer `{}` is out of the range of `{}` ({}..={}) at {}",
+                    value,
+                    ty,
+                    range.start(),
+                    range.end(),
+                    span
+                )
+            }
+        }
+    }
+}
+
+impl SemanticError {
+    /// Returns the [`Suggestion`] fixing the error, if there is one.
+    pub fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            SemanticError::ImmutableAssignedTwice { decl_span, .. }
+            | SemanticError::ImmutableBorrowedAsMutable { decl_span, .. } => Some(
+                Suggestion::insert("make the variable mutable", decl_span.start(), "mut "),
+            ),
+            SemanticError::AssignmentInCondition { suggestion, span } => Some(Suggestion::replace(
+                "compare for equality",
+                *span,
+                suggestion.as_ref(),
+            )),
+            _ => None,
         }
     }
 }
 
 impl Error for SemanticError {}
+
+/// A warning that can occur during semantic analysis on the AST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SemanticWarning {
+    /// A warning that occurs when a branch of an `if` or `while` is never executed,
+    /// because its condition is a constant.
+    UnreachableBranch {
+        /// The constant value of the condition.
+        condition: bool,
+        /// The span of the eliminated branch.
+        span: Span,
+    },
+    /// A warning that occurs when nothing inside an `unsafe` block requires it
+    /// (including when it is nested in another `unsafe` block).
+    UnusedUnsafe {
+        /// The span of the `unsafe` block.
+        span: Span,
+    },
+    /// A warning that occurs when a boolean expression is compared with a boolean literal
+    /// using `==` or `!=` (e.g. `x == true`).
+    RedundantBoolComparison {
+        /// The expression that can be used instead, i.e. the operand or its negation.
+        suggestion: Box<str>,
+        /// The span of the comparison.
+        span: Span,
+    },
+    /// A warning that occurs when the code after a diverging expression (e.g. `return`)
+    /// is never executed.
+    UnreachableCode {
+        /// The span of the unreachable code.
+        span: Span,
+    },
+    /// A warning that occurs when the value of a float literal is not exactly representable
+    /// as `f64`, so it is rounded (only reported when
+    /// [enabled](crate::codegen::CodeGenOptions::with_inexact_float_lint)).
+    InexactFloatLiteral {
+        /// The text of the literal.
+        literal: Box<str>,
+        /// The span of the literal.
+        span: Span,
+    },
+    /// A warning that occurs when an `#[unroll(N)]` hint cannot be applied,
+    /// e.g. because the function contains no loops.
+    IgnoredUnrollHint {
+        /// The reason why the hint is ignored.
+        reason: &'static str,
+        /// The span of the attribute or the loop's body.
+        span: Span,
+    },
+}
+
+impl fmt::Display for SemanticWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SemanticWarning::UnreachableBranch { condition, span } => {
+                write!(
+                    f,
+                    "Unreachable branch at {} (the condition is always `{}`)",
+                    span, condition
+                )
+            }
+            SemanticWarning::UnusedUnsafe { span } => {
+                write!(f, "Unnecessary `unsafe` block at {}", span)
+            }
+            SemanticWarning::RedundantBoolComparison { suggestion, span } => {
+                write!(
+                    f,
+                    "Redundant comparison with a boolean literal at {} (use `{}` instead)",
+                    span, suggestion
+                )
+            }
+            SemanticWarning::UnreachableCode { span } => {
+                write!(f, "Unreachable code at {}", span)
+            }
+            SemanticWarning::InexactFloatLiteral { literal, span } => {
+                write!(
+                    f,
+                    "Float literal `{}` at {} is not exactly representable as `f64`",
+                    literal, span
+                )
+            }
+            SemanticWarning::IgnoredUnrollHint { reason, span } => {
+                write!(f, "Ignored unrolling hint at {} ({})", span, reason)
+            }
+        }
+    }
+}
+
+impl SemanticWarning {
+    /// Returns the [`Suggestion`] fixing the warning, if there is one.
+    pub fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            SemanticWarning::RedundantBoolComparison { suggestion, span } => Some(
+                Suggestion::replace("remove the comparison", *span, suggestion.as_ref()),
+            ),
+            _ => None,
+        }
+    }
+}
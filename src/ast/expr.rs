@@ -1,40 +1,56 @@
 //! A module containing all the expression-related AST nodes.
+//!
+//! # Evaluation order
+//!
+//! The operands of an expression are always evaluated from left to right,
+//! regardless of the precedence of the operators:
+//! - both operands of an [arithmetic](ArithExprASTNode) or a [comparison](CompExprASTNode)
+//!   expression are evaluated, the left-hand side first,
+//! - the right-hand side of a [lazy boolean](LazyBoolExprASTNode) expression is evaluated
+//!   only after (and only if) the left-hand side doesn't determine the result,
+//! - the arguments of a [function call](FunCallASTNode) are evaluated in order,
+//!   before the function is called,
+//! - the value of an [assignment](AssignASTNode) is evaluated before the value is stored.
 
 use std::rc::Rc;
 
-use inkwell::values::AnyValueEnum;
-
-use codegen::CodeGenState;
-
-use crate::ast::ASTNode;
-use crate::codegen;
-use crate::codegen::CodeGen;
+use crate::ast::{ASTNode, CoreExpr};
 
 pub use self::assign::*;
 pub use self::block::*;
 pub use self::cast::*;
+pub use self::constant::*;
 pub use self::fun_call::*;
 pub use self::grouped::*;
+pub use self::layout_of::*;
 pub use self::literal::*;
 pub use self::operator::*;
 pub use self::path::*;
+pub use self::precedence::*;
 pub use self::r#if::*;
 pub use self::r#loop::*;
 pub use self::r#return::*;
+pub use self::tuple::*;
+pub use self::tuple_index::*;
 pub use self::underscore::*;
 pub use self::unsafe_block::*;
 
 mod assign;
 mod block;
 mod cast;
+mod constant;
 mod fun_call;
 mod grouped;
 mod r#if;
+mod layout_of;
 mod literal;
 mod r#loop;
 mod operator;
 mod path;
+mod precedence;
 mod r#return;
+mod tuple;
+mod tuple_index;
 mod underscore;
 mod unsafe_block;
 
@@ -49,11 +65,11 @@ mod unsafe_block;
 /// # use std::rc::Rc;
 /// # use mini_rust_compiler_components::token::Span;
 /// # use crate::mini_rust_compiler_components::ast::{
-/// #     ASTNode, ASTChildIterator, ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
+/// #     ASTNode, ASTChildIterator, CoreExpr, ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
 /// #     AssigneeExprASTNode
 /// # };
-/// # use mini_rust_compiler_components::codegen;
-/// # use inkwell::values::AnyValueEnum;
+/// # use mini_rust_compiler_components::pretty::SourcePrinter;
+/// # use mini_rust_compiler_components::serialize::Encoder;
 ///
 /// # #[derive(Debug)]
 /// struct MyExprASTNode;
@@ -72,6 +88,9 @@ mod unsafe_block;
 ///         // so it is also implemented
 ///         Some(self)
 ///     }
+///
+///     // ...
+/// # fn lower(&self) -> CoreExpr<'_> { unimplemented!() }
 /// }
 ///
 /// impl PlaceExprASTNode for MyExprASTNode {}
@@ -84,11 +103,8 @@ mod unsafe_block;
 /// # impl ASTNode for MyExprASTNode {
 /// #     fn span(&self) -> Span { unimplemented!() }
 /// #     fn children(&self) -> Option<ASTChildIterator> { unimplemented!() }
-/// # }
-/// # impl<'ctx> codegen::CodeGen<'ctx, AnyValueEnum<'ctx>> for MyExprASTNode {
-/// #     fn code_gen<'a>(&self, state: &mut codegen::CodeGenState<'a>) -> codegen::Result<AnyValueEnum<'ctx>> {
-/// #         unimplemented!()
-/// #     }
+/// #     fn print_source(&self, printer: &mut SourcePrinter) { unimplemented!() }
+/// #     fn encode(&self, encoder: &mut Encoder) { unimplemented!() }
 /// # }
 /// # impl fmt::Display for MyExprASTNode {
 /// #    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { unimplemented!() }
@@ -109,9 +125,7 @@ mod unsafe_block;
 /// assert!(std::ptr::eq(assignee.unwrap(), &my_expr));
 /// # }
 /// ```
-pub trait ExprASTNode:
-    ASTNode + for<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> + AsExprASTNode
-{
+pub trait ExprASTNode: ASTNode + AsExprASTNode {
     /// Tries to convert the expression to a [`PlaceExprASTNode`].
     fn try_as_place(&self) -> Option<&dyn PlaceExprASTNode>;
 
@@ -120,6 +134,36 @@ pub trait ExprASTNode:
 
     /// Tries to convert the expression to an [`AssigneeExprASTNode`].
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode>;
+
+    /// Lowers the expression to the [core construct](CoreExpr) the code generation backends
+    /// generate the code for.
+    fn lower(&self) -> CoreExpr<'_>;
+
+    /// Tries to evaluate the expression at compile time.
+    ///
+    /// Returns `None` if the value of the expression is not known at compile time.
+    /// It is equivalent to [`const_eval_in`](ExprASTNode::const_eval_in) with no named constants,
+    /// for the [host](crate::target::TargetLayout::host) machine.
+    fn const_eval(&self) -> Option<ConstValue> {
+        self.const_eval_in(&ConstEnv::new())
+    }
+
+    /// Tries to evaluate the expression at compile time, where the paths can refer
+    /// to the named constants in `env`.
+    ///
+    /// Returns `None` if the value of the expression is not known at compile time
+    /// (which is the default).
+    fn const_eval_in(&self, _env: &ConstEnv) -> Option<ConstValue> {
+        None
+    }
+
+    /// Returns the precedence of the expression, used to decide where the parentheses
+    /// are needed when it is printed as an operand.
+    ///
+    /// The default is [`Precedence::Primary`], i.e. the expression never needs parentheses.
+    fn precedence(&self) -> Precedence {
+        Precedence::Primary
+    }
 }
 
 /// A trait for all [place expression](https://doc.rust-lang.org/reference/expressions.html#place-expressions-and-value-expressions)
@@ -160,9 +204,3 @@ impl<T: ExprASTNode> AsExprASTNode for T {
         self
     }
 }
-
-impl<'ctx, T: ExprASTNode> CodeGen<'ctx, ()> for T {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
-        CodeGen::<AnyValueEnum>::code_gen(self, state).map(|_| ())
-    }
-}
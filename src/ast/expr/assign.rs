@@ -3,16 +3,14 @@
 use std::{fmt, iter};
 
 use debug_tree::TreeBuilder;
-use inkwell::values::{AnyValueEnum, BasicValueEnum};
 
-use crate::ast::error::SemanticError;
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, LiteralASTNode,
-    PlaceExprASTNode, ValueExprASTNode,
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr, ExprASTNode,
+    PlaceExprASTNode, Precedence, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing an assignment.
@@ -38,12 +36,22 @@ impl AssignASTNode {
             span,
         }
     }
+
+    /// Returns the assignee (the left-hand side) of the assignment.
+    pub fn assignee(&self) -> &dyn ExprASTNode {
+        self.assignee.as_ref()
+    }
+
+    /// Returns the assigned value (the right-hand side).
+    pub fn value(&self) -> &dyn ExprASTNode {
+        self.value.as_ref()
+    }
 }
 
 impl ASTNode for AssignASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let assignee = iter::once(self.assignee.as_ast());
         let value = iter::once(self.value.as_ast());
         let iter = assignee.chain(value);
@@ -67,6 +75,31 @@ impl ASTNode for AssignASTNode {
         }
         branch.release();
     }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        self.value.check_init(checker);
+        // Assigning to a variable initializes it, so it is not a read
+        match self.assignee.try_as_assignee().and_then(|a| a.pattern()) {
+            Some(name) => checker.write(&name, self.assignee.span()),
+            None => self.assignee.check_init(checker),
+        }
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.print_bin_op(
+            self.assignee.as_ref(),
+            "=",
+            self.value.as_ref(),
+            Precedence::Assign,
+        );
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Assign);
+        encoder.write_node(self.assignee.as_ast());
+        encoder.write_node(self.value.as_ast());
+        encoder.write_span(self.span);
+    }
 }
 
 impl ExprASTNode for AssignASTNode {
@@ -81,69 +114,18 @@ impl ExprASTNode for AssignASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
-
-impl ValueExprASTNode for AssignASTNode {}
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for AssignASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        // Returns the result of the assignment, which is always a unit value.
-        let assignment_result = |state: &mut CodeGenState<'ctx>| {
-            let value = LiteralASTNode::<()>::new(self.value.span());
-            CodeGen::<AnyValueEnum>::code_gen(&value, state).unwrap()
-        };
-
-        let value = CodeGen::<AnyValueEnum>::code_gen(self.value.as_ref(), state)?;
-        let value =
-            BasicValueEnum::try_from(value).map_err(|_| CodeGenError::InvalidLLVMValueType {
-                message: "The RHS of the assignment must be a basic value".into(),
-                span: self.span,
-            })?;
-
-        let assignee = self
-            .assignee
-            .try_as_assignee()
-            .ok_or(SemanticError::WrongExpressionKind {
-                message: "Expected an assignee expression",
-                span: self.span,
-            })?
-            .pattern();
-
-        let pat = match assignee {
-            Some(pat) => pat,
-            None => return Ok(assignment_result(state)),
-        };
-        let ptr = state.symbol_table().get(pat.as_ref()).map_or_else(
-            || {
-                Err(CodeGenError::MissingSymbol {
-                    symbol: pat.to_string().into_boxed_str(),
-                    span: self.assignee.span(),
-                })
-            },
-            |s| match s.value() {
-                AnyValueEnum::PointerValue(p) => Ok(p),
-                _ => Err(CodeGenError::InvalidLLVMValueType {
-                    message: "Expected a pointer value".into(),
-                    span: self.assignee.span(),
-                }),
-            },
-        )?;
-
-        // If the value is a unit struct, we don't need to store it.
-        match value {
-            BasicValueEnum::StructValue(s) if s.count_fields() == 0 => {}
-            _ => {
-                state
-                    .builder()
-                    .build_store(ptr, value)
-                    .map_err(CodeGenError::from)?;
-            }
-        }
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Assign(self)
+    }
 
-        Ok(assignment_result(state))
+    fn precedence(&self) -> Precedence {
+        Precedence::Assign
     }
 }
 
+impl ValueExprASTNode for AssignASTNode {}
+
 impl fmt::Display for AssignASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Assignment {}", self.span)
@@ -2,14 +2,14 @@
 
 use std::fmt;
 
-use inkwell::values::AnyValueEnum;
-
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, PlaceExprASTNode,
-    StatementASTNode, ValueExprASTNode,
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ConstEnv, ConstValue, CoreExpr,
+    ExprASTNode, PlaceExprASTNode, StatementASTNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::resolve::Resolver;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// A type alias for a list of statements.
@@ -48,17 +48,75 @@ impl BlockASTNode {
             span,
         }
     }
+
+    /// Returns the statements of the block.
+    pub fn statements(&self) -> &[Box<dyn StatementASTNode>] {
+        &self.statements
+    }
+
+    /// Returns the final expression of the block (without a semicolon), if any.
+    pub fn return_expr(&self) -> Option<&dyn ExprASTNode> {
+        self.return_expr.as_deref()
+    }
 }
 
 impl ASTNode for BlockASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let statements = self.statements.iter().map(|s| s.as_ast());
         let return_expr = self.return_expr.iter().map(|e| e.as_ast());
         let iter = statements.chain(return_expr);
         Some(Box::new(iter))
     }
+
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        resolver.open_scope();
+        if let Some(children) = self.children() {
+            for child in children {
+                child.resolve_names(resolver);
+            }
+        }
+        resolver.close_scope();
+    }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        checker.open_scope();
+        if let Some(children) = self.children() {
+            for child in children {
+                child.check_init(checker);
+            }
+        }
+        checker.close_scope();
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        if self.statements.is_empty() && self.return_expr.is_none() {
+            printer.write("{}");
+            return;
+        }
+
+        printer.write("{");
+        printer.indent();
+        for stmt in &self.statements {
+            printer.new_line();
+            stmt.print_source(printer);
+        }
+        if let Some(expr) = &self.return_expr {
+            printer.new_line();
+            expr.print_source(printer);
+        }
+        printer.dedent();
+        printer.new_line();
+        printer.write("}");
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Block);
+        encoder.write_list(self.statements.iter().map(|s| s.as_ast()));
+        encoder.write_optional(self.return_expr.as_ref().map(|e| e.as_ast()));
+        encoder.write_span(self.span);
+    }
 }
 
 impl ExprASTNode for BlockASTNode {
@@ -73,32 +131,25 @@ impl ExprASTNode for BlockASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
-
-impl ValueExprASTNode for BlockASTNode {}
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for BlockASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        state.symbol_table().open_scope();
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Block(self)
+    }
 
-        for statement in &self.statements {
-            statement.code_gen(state).map_err(|e| {
-                state.symbol_table().close_scope();
-                e
-            })?;
+    fn const_eval_in(&self, env: &ConstEnv) -> Option<ConstValue> {
+        // Only a block without any statements can be a constant
+        if !self.statements.is_empty() {
+            return None;
+        }
+        match &self.return_expr {
+            Some(expr) => expr.const_eval_in(env),
+            None => Some(ConstValue::Unit),
         }
-
-        let ret_value = match &self.return_expr {
-            Some(expr) => CodeGen::<AnyValueEnum>::code_gen(expr.as_ref(), state),
-            None => Ok(state.build_unit_value(self.span.end())),
-        };
-
-        state.symbol_table().close_scope();
-
-        ret_value
     }
 }
 
+impl ValueExprASTNode for BlockASTNode {}
+
 impl fmt::Display for BlockASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Block {}", self.span)
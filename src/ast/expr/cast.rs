@@ -2,14 +2,12 @@
 
 use std::{fmt, iter};
 
-use inkwell::values::AnyValueEnum;
-
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, PlaceExprASTNode,
-    TypeASTMetaNode, ValueExprASTNode,
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr, ExprASTNode,
+    PlaceExprASTNode, Precedence, Type, TypeASTMetaNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing a type cast.
@@ -26,15 +24,43 @@ impl TypeCastASTNode {
     pub fn new(value: Box<dyn ExprASTNode>, ty: TypeASTMetaNode, span: Span) -> TypeCastASTNode {
         TypeCastASTNode { value, ty, span }
     }
+
+    /// Returns the cast value.
+    pub fn value(&self) -> &dyn ExprASTNode {
+        self.value.as_ref()
+    }
+
+    /// Returns the target type of the cast.
+    pub fn ty(&self) -> Type {
+        self.ty.ty()
+    }
+
+    /// Returns the node of the target type of the cast.
+    pub fn target_type(&self) -> &TypeASTMetaNode {
+        &self.ty
+    }
 }
 
 impl ASTNode for TypeCastASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let iter = iter::once(self.value.as_ast());
         Some(Box::new(iter))
     }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        let value = self.value.as_ref();
+        printer.print_operand(value, value.precedence() < Precedence::Cast);
+        printer.write(format_args!(" as {}", self.ty));
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Cast);
+        encoder.write_node(self.value.as_ast());
+        encoder.write_type(&self.ty);
+        encoder.write_span(self.span);
+    }
 }
 
 impl ExprASTNode for TypeCastASTNode {
@@ -49,16 +75,18 @@ impl ExprASTNode for TypeCastASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
 
-impl ValueExprASTNode for TypeCastASTNode {}
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Cast(self)
+    }
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for TypeCastASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        todo!()
+    fn precedence(&self) -> Precedence {
+        Precedence::Cast
     }
 }
 
+impl ValueExprASTNode for TypeCastASTNode {}
+
 impl fmt::Display for TypeCastASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Type Cast {} `{}`", self.span, self.ty)
@@ -0,0 +1,151 @@
+//! A module containing the values of expressions evaluated at compile time.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::Type;
+use crate::target::TargetLayout;
+
+/// A value of an expression that can be evaluated at compile time.
+///
+/// See [`ExprASTNode::const_eval`](crate::ast::ExprASTNode::const_eval).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstValue {
+    /// A constant of an integer type (by default [`Type::I32`]), wide enough
+    /// for the values of all the integer types.
+    Int(i128),
+    /// A constant of a floating-point type (by default [`Type::F64`]).
+    Float(f64),
+    /// A constant of type [`Type::Bool`].
+    Bool(bool),
+    /// The unit value.
+    Unit,
+}
+
+impl ConstValue {
+    /// Returns the default type of the constant.
+    pub fn ty(&self) -> Type {
+        match self {
+            ConstValue::Int(_) => Type::I32,
+            ConstValue::Float(_) => Type::F64,
+            ConstValue::Bool(_) => Type::Bool,
+            ConstValue::Unit => Type::Unit,
+        }
+    }
+
+    /// Returns whether the constant can be a value of the given type, i.e. whether it is
+    /// of the same kind (like the unsuffixed literals, which adopt the expected type).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::ast::{ConstValue, Type};
+    ///
+    /// assert!(ConstValue::Int(7).is_of_type(&Type::U8));
+    /// assert!(ConstValue::Float(0.5).is_of_type(&Type::F32));
+    /// assert!(!ConstValue::Int(1).is_of_type(&Type::F64));
+    /// assert!(ConstValue::Bool(true).is_of_type(&Type::Bool));
+    /// ```
+    pub fn is_of_type(&self, ty: &Type) -> bool {
+        match self {
+            ConstValue::Int(_) => ty.is_integer(),
+            ConstValue::Float(_) => ty.is_float(),
+            _ => self.ty() == *ty,
+        }
+    }
+
+    /// Returns the boolean value of the constant, if it is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ConstValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl From<i128> for ConstValue {
+    fn from(value: i128) -> Self {
+        ConstValue::Int(value)
+    }
+}
+
+impl From<f64> for ConstValue {
+    fn from(value: f64) -> Self {
+        ConstValue::Float(value)
+    }
+}
+
+impl From<bool> for ConstValue {
+    fn from(value: bool) -> Self {
+        ConstValue::Bool(value)
+    }
+}
+
+impl From<()> for ConstValue {
+    fn from(_: ()) -> Self {
+        ConstValue::Unit
+    }
+}
+
+/// The values of the named constants (i.e. the immutable statics) which the expressions
+/// evaluated at compile time can refer to, together with the [layout](TargetLayout)
+/// of the target machine (see [`LayoutOfASTNode`](crate::ast::LayoutOfASTNode)).
+///
+/// See [`ExprASTNode::const_eval_in`](crate::ast::ExprASTNode::const_eval_in).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConstEnv {
+    values: HashMap<Rc<str>, ConstValue>,
+    layout: TargetLayout,
+}
+
+impl ConstEnv {
+    /// Creates a new empty `ConstEnv` for the [host](TargetLayout::host) machine.
+    pub fn new() -> ConstEnv {
+        ConstEnv::with_layout(TargetLayout::host())
+    }
+
+    /// Creates a new empty `ConstEnv` for a target machine with the given layout.
+    pub fn with_layout(layout: TargetLayout) -> ConstEnv {
+        ConstEnv {
+            values: HashMap::new(),
+            layout,
+        }
+    }
+
+    /// Returns the layout of the target machine.
+    pub fn layout(&self) -> TargetLayout {
+        self.layout
+    }
+
+    /// Sets the value of the constant with the given name.
+    pub fn insert(&mut self, name: Rc<str>, value: ConstValue) {
+        self.values.insert(name, value);
+    }
+
+    /// Returns the value of the constant with the given name, if it is known.
+    pub fn get(&self, name: &str) -> Option<ConstValue> {
+        self.values.get(name).copied()
+    }
+
+    /// Returns the number of the known constants.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether there are no known constants.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Display for ConstValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstValue::Int(i) => write!(f, "{}", i),
+            ConstValue::Float(x) => write!(f, "{}", x),
+            ConstValue::Bool(b) => write!(f, "{}", b),
+            ConstValue::Unit => write!(f, "()"),
+        }
+    }
+}
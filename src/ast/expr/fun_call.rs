@@ -2,16 +2,15 @@
 
 use std::fmt;
 
-use inkwell::values::{AnyValue, AnyValueEnum, BasicMetadataValueEnum};
-
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, PathASTNode,
-    PlaceExprASTNode, ValueExprASTNode,
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr, ExprASTNode,
+    PathASTNode, PlaceExprASTNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::pretty::SourcePrinter;
+use crate::resolve::Resolver;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
+use crate::unsafety::UnsafetyChecker;
 
 /// An AST node representing a function call.
 #[derive(Debug)]
@@ -36,15 +35,48 @@ impl FunCallASTNode {
     pub fn path(&self) -> &str {
         self.path.path()
     }
+
+    /// Returns the arguments of the call.
+    pub fn args(&self) -> &[Box<dyn ExprASTNode>] {
+        &self.args
+    }
 }
 
 impl ASTNode for FunCallASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let iter = self.args.iter().map(|arg| arg.as_ast());
         Some(Box::new(iter))
     }
+
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        self.path.resolve_names(resolver);
+        for arg in &self.args {
+            arg.resolve_names(resolver);
+        }
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        self.path.print_source(printer);
+        printer.write("(");
+        printer.print_list(self.args.iter().map(|arg| arg.as_ast()));
+        printer.write(")");
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::FunCall);
+        encoder.write_node(self.path.as_ref());
+        encoder.write_list(self.args.iter().map(|arg| arg.as_ast()));
+        encoder.write_span(self.span);
+    }
+
+    fn check_unsafety(&self, checker: &mut UnsafetyChecker) {
+        self.path.check_unsafety(checker);
+        for arg in &self.args {
+            arg.check_unsafety(checker);
+        }
+    }
 }
 
 impl ExprASTNode for FunCallASTNode {
@@ -59,52 +91,14 @@ impl ExprASTNode for FunCallASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
 
-impl ValueExprASTNode for FunCallASTNode {}
-
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for FunCallASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        let fn_value = state.symbol_table().get(self.path()).map_or_else(
-            || {
-                Err(CodeGenError::MissingSymbol {
-                    symbol: self.path().into(),
-                    span: self.span,
-                })
-            },
-            |s| match s.value() {
-                AnyValueEnum::FunctionValue(f) => Ok(f),
-                _ => Err(CodeGenError::InvalidLLVMValueType {
-                    message: format!("`{}` is not a function", self.path()).into_boxed_str(),
-                    span: self.span,
-                }),
-            },
-        )?;
-
-        //TODO Validate passed arguments (probably on HIR->MIR conversion)
-        let args = self
-            .args
-            .iter()
-            .map(|arg| {
-                let arg = CodeGen::<AnyValueEnum>::code_gen(arg.as_ref(), state)?;
-                BasicMetadataValueEnum::try_from(arg).map_err(|_| {
-                    CodeGenError::InvalidLLVMValueType {
-                        message: "The expression is not a basic metadata value".into(),
-                        span: self.span,
-                    }
-                })
-            })
-            .collect::<Result<Vec<BasicMetadataValueEnum>, _>>()?;
-
-        let call_name = format!("call_{}", self.path());
-        state
-            .builder()
-            .build_call(fn_value, &args, call_name.as_str())
-            .map(|v| v.as_any_value_enum())
-            .map_err(CodeGenError::from)
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::FunCall(self)
     }
 }
 
+impl ValueExprASTNode for FunCallASTNode {}
+
 impl fmt::Display for FunCallASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Function Call {} \"{}\"", self.span, self.path())
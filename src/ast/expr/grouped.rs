@@ -2,13 +2,12 @@
 
 use std::{fmt, iter};
 
-use inkwell::values::AnyValueEnum;
-
 use crate::ast::{
-    ast_defaults, ASTNode, AssigneeExprASTNode, ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
+    ast_defaults, ASTNode, AssigneeExprASTNode, ConstEnv, ConstValue, CoreExpr, ExprASTNode,
+    PlaceExprASTNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing a grouped expression (i.e. an expression wrapped in parentheses).
@@ -24,15 +23,30 @@ impl GroupedExprASTNode {
     pub fn new(expr: Box<dyn ExprASTNode>, span: Span) -> GroupedExprASTNode {
         GroupedExprASTNode { expr, span }
     }
+
+    /// Returns the expression inside the parentheses.
+    pub fn expr(&self) -> &dyn ExprASTNode {
+        self.expr.as_ref()
+    }
 }
 
 impl ASTNode for GroupedExprASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<crate::ast::ASTChildIterator> {
+    fn children(&self) -> Option<crate::ast::ASTChildIterator<'_>> {
         let iter = iter::once(self.expr.as_ast());
         Some(Box::new(iter))
     }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.print_operand(self.expr.as_ref(), true);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Grouped);
+        encoder.write_node(self.expr.as_ast());
+        encoder.write_span(self.span);
+    }
 }
 
 impl ExprASTNode for GroupedExprASTNode {
@@ -47,16 +61,18 @@ impl ExprASTNode for GroupedExprASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
 
-impl ValueExprASTNode for GroupedExprASTNode {}
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Grouped(self)
+    }
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for GroupedExprASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        CodeGen::<AnyValueEnum>::code_gen(self.expr.as_ref(), state)
+    fn const_eval_in(&self, env: &ConstEnv) -> Option<ConstValue> {
+        self.expr.const_eval_in(env)
     }
 }
 
+impl ValueExprASTNode for GroupedExprASTNode {}
+
 impl fmt::Display for GroupedExprASTNode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Grouped {}", self.span)
@@ -3,14 +3,14 @@
 use std::{fmt, iter};
 
 use debug_tree::TreeBuilder;
-use inkwell::values::AnyValueEnum;
 
 use crate::ast::{
     ast_defaults, ASTChildIterator, ASTNode, AsASTNode, AssigneeExprASTNode, BlockASTNode,
-    ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
+    CoreExpr, ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing an if expression.
@@ -19,27 +19,78 @@ pub struct IfASTNode {
     /// The condition can be [any kind of expression](ExprASTNode).
     condition: Box<dyn ExprASTNode>,
     then_block: Box<BlockASTNode>,
-    else_node: ElseExpr,
+    else_node: Option<ElseASTNode>,
     span: Span,
 }
 
-/// An enum representing the possible else expressions in an if statement.
+/// An AST node representing the `else` branch of an if expression
+/// (spanning from the `else` keyword to the end of the branch).
+#[derive(Debug)]
+pub struct ElseASTNode {
+    branch: ElseExpr,
+    span: Span,
+}
+
+/// An enum representing the possible branches following an `else`.
 #[derive(Debug)]
 pub enum ElseExpr {
-    /// No else block.
-    None,
     /// An else block.
     Else(Box<BlockASTNode>),
     /// An else if block.
     ElseIf(Box<IfASTNode>),
 }
 
+impl ElseASTNode {
+    /// Creates a new `ElseASTNode` with the given branch and span.
+    pub fn new(branch: ElseExpr, span: Span) -> ElseASTNode {
+        ElseASTNode { branch, span }
+    }
+
+    /// Returns the branch following the `else`.
+    pub fn branch(&self) -> &ElseExpr {
+        &self.branch
+    }
+
+    /// Returns the branch following the `else` as an expression.
+    pub fn expr(&self) -> &dyn ExprASTNode {
+        match &self.branch {
+            ElseExpr::Else(block) => block.as_ref(),
+            ElseExpr::ElseIf(if_node) => if_node.as_ref(),
+        }
+    }
+}
+
+impl ASTNode for ElseASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        Some(Box::new(iter::once(self.expr().as_ast())))
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("else ");
+        self.expr().print_source(printer);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Else);
+        encoder.write_node(self.expr().as_ast());
+        encoder.write_span(self.span);
+    }
+}
+
+impl fmt::Display for ElseASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Else {}", self.span)
+    }
+}
+
 impl IfASTNode {
-    /// Creates a new `IfASTNode` with the given condition, then block, else expression and span.
+    /// Creates a new `IfASTNode` with the given condition, then block, else branch and span.
     pub fn new(
         condition: Box<dyn ExprASTNode>,
         then_block: Box<BlockASTNode>,
-        else_node: ElseExpr,
+        else_node: Option<ElseASTNode>,
         span: Span,
     ) -> IfASTNode {
         IfASTNode {
@@ -50,99 +101,86 @@ impl IfASTNode {
         }
     }
 
-    /// Collects all the else if nodes and the final else block (if any) in a flat vector.
-    fn flatten_else_ifs(&self) -> (Vec<&IfASTNode>, Option<&BlockASTNode>) {
-        let mut nodes = Vec::new();
-        let mut current = self;
-        while let ElseExpr::ElseIf(if_node) = &current.else_node {
-            nodes.push(if_node.as_ref());
-            current = if_node;
-        }
-        let else_block = match &current.else_node {
-            ElseExpr::None => None,
-            ElseExpr::Else(block) => Some(block.as_ref()),
-            ElseExpr::ElseIf(_) => unreachable!(),
-        };
-
-        (nodes, else_block)
-    }
-
-    /// Custom implementation for [`ASTNode::add_to_tree_string`] that properly
-    /// handles the else if case.
-    ///
-    /// In the case of an else if, the tree is flattened so that all the else ifs
-    /// and the final else (if any) are at the same level.
-    fn add_to_tree_string(&self, builder: &mut TreeBuilder, is_else_if: bool) {
-        // We add a new branch only if this is the main if node, not a nested else if
-        let branch = if !is_else_if {
-            Some(builder.add_branch(format!("{self}").as_str()))
-        } else {
-            None
-        };
+    /// Returns the condition of the `if`.
+    pub fn condition(&self) -> &dyn ExprASTNode {
+        self.condition.as_ref()
+    }
+
+    /// Returns the block executed when the condition is `true`.
+    pub fn then_block(&self) -> &BlockASTNode {
+        &self.then_block
+    }
+
+    /// Returns the `else` branch, if any.
+    pub fn else_node(&self) -> Option<&ElseASTNode> {
+        self.else_node.as_ref()
+    }
+}
+
+impl ASTNode for IfASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        let condition = iter::once(self.condition.as_ast());
+        let then_block = iter::once(self.then_block.as_ast());
+        let else_node = self.else_node.iter().map(|else_node| else_node.as_ast());
+        Some(Box::new(condition.chain(then_block).chain(else_node)))
+    }
+
+    fn add_to_tree_string(&self, builder: &mut TreeBuilder) {
+        let mut branch = builder.add_branch(format!("{self}").as_str());
 
         // Condition
         {
-            let condition = self.condition.as_ast();
             let mut branch = builder.add_branch("Condition");
-            condition.add_to_tree_string(builder);
+            self.condition.add_to_tree_string(builder);
             branch.release();
         }
 
         // Then block
         {
-            let then_block = self.then_block.as_ast();
             let mut branch = builder.add_branch("Then");
-            then_block.add_to_tree_string(builder);
+            self.then_block.add_to_tree_string(builder);
             branch.release();
         }
 
-        // The branch only exists if this is the main if node, not a nested else if
-        if let Some(mut branch) = branch {
-            let (else_ifs, else_block) = self.flatten_else_ifs();
+        if let Some(else_node) = &self.else_node {
+            else_node.add_to_tree_string(builder);
+        }
+        branch.release();
+    }
 
-            // Else if blocks
-            for else_if in else_ifs {
-                let mut branch = builder.add_branch(format!("Else {else_if}").as_str());
-                IfASTNode::add_to_tree_string(else_if, builder, true);
-                branch.release();
-            }
+    fn check_init(&self, checker: &mut InitChecker) {
+        self.condition.check_init(checker);
 
-            // Else block
-            if let Some(else_block) = else_block {
-                let mut branch = builder.add_branch("Else");
-                else_block.add_to_tree_string(builder);
-                branch.release();
-            }
+        let before = checker.snapshot();
+        self.then_block.check_init(checker);
+        let after_then = checker.snapshot();
 
-            branch.release();
+        checker.restore(before);
+        if let Some(else_node) = &self.else_node {
+            else_node.check_init(checker);
         }
+        checker.merge(after_then);
     }
-}
 
-impl ASTNode for IfASTNode {
-    ast_defaults!();
-
-    fn children(&self) -> Option<ASTChildIterator> {
-        let condition = iter::once(self.condition.as_ast());
-        let then_block = iter::once(self.then_block.as_ast());
-        let iter = condition.chain(then_block);
-        Some(match &self.else_node {
-            ElseExpr::None => Box::new(iter),
-            ElseExpr::Else(block) => {
-                let else_block = iter::once(block.as_ast());
-                let iter = iter.chain(else_block);
-                Box::new(iter)
-            }
-            ElseExpr::ElseIf(if_node) => {
-                let else_if = iter::once(if_node.as_ast());
-                let iter = iter.chain(else_if);
-                Box::new(iter)
-            }
-        })
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("if ");
+        self.condition.print_source(printer);
+        printer.write(" ");
+        self.then_block.print_source(printer);
+        if let Some(else_node) = &self.else_node {
+            printer.write(" ");
+            else_node.print_source(printer);
+        }
     }
 
-    fn add_to_tree_string(&self, builder: &mut TreeBuilder) {
-        IfASTNode::add_to_tree_string(self, builder, false);
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::If);
+        encoder.write_node(self.condition.as_ast());
+        encoder.write_node(self.then_block.as_ast());
+        encoder.write_optional(self.else_node.as_ref().map(|else_node| else_node.as_ast()));
+        encoder.write_span(self.span);
     }
 }
 
@@ -158,16 +196,14 @@ impl ExprASTNode for IfASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
 
-impl ValueExprASTNode for IfASTNode {}
-
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for IfASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        todo!()
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::If(self)
     }
 }
 
+impl ValueExprASTNode for IfASTNode {}
+
 impl fmt::Display for IfASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "If {}", self.span)
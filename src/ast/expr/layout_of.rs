@@ -0,0 +1,135 @@
+//! A module containing the Layout Query AST node implementation.
+
+use std::fmt;
+
+use crate::ast::{
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ConstEnv, ConstValue, CoreExpr,
+    ExprASTNode, PlaceExprASTNode, Type, TypeASTMetaNode, ValueExprASTNode,
+};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
+use crate::token::Span;
+
+/// A property of the layout of a type on the target machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayoutQuery {
+    /// `size_of::<T>()` -- the number of bytes occupied by a value of the type.
+    SizeOf,
+    /// `align_of::<T>()` -- the ABI alignment of the values of the type.
+    AlignOf,
+}
+
+impl LayoutQuery {
+    /// Returns the query called with the given name, if there is one.
+    pub fn from_name(name: &str) -> Option<LayoutQuery> {
+        match name {
+            "size_of" => Some(LayoutQuery::SizeOf),
+            "align_of" => Some(LayoutQuery::AlignOf),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the function performing the query.
+    pub fn name(self) -> &'static str {
+        match self {
+            LayoutQuery::SizeOf => "size_of",
+            LayoutQuery::AlignOf => "align_of",
+        }
+    }
+}
+
+/// An AST node representing a query about the layout of a type
+/// (`size_of::<T>()` or `align_of::<T>()`), which is resolved at compile time.
+///
+/// The result depends on the [layout](crate::target::TargetLayout) of the target machine,
+/// which the [const evaluator](ConstEnv) is given. Like an unsuffixed integer literal,
+/// the query adopts the expected integer type (by default `i32`).
+#[derive(Debug)]
+pub struct LayoutOfASTNode {
+    query: LayoutQuery,
+    ty: TypeASTMetaNode,
+    span: Span,
+}
+
+impl LayoutOfASTNode {
+    /// Creates a new `LayoutOfASTNode` with the given query, queried type and span.
+    pub fn new(query: LayoutQuery, ty: TypeASTMetaNode, span: Span) -> LayoutOfASTNode {
+        LayoutOfASTNode { query, ty, span }
+    }
+
+    /// Returns the query.
+    pub fn query(&self) -> LayoutQuery {
+        self.query
+    }
+
+    /// Returns the queried type.
+    pub fn ty(&self) -> Type {
+        self.ty.ty()
+    }
+
+    /// Returns the type of the result in a context which expected the given type,
+    /// i.e. the expected type if it is an integer type, or `i32` otherwise.
+    pub fn infer_type(&self, expected: Option<Type>) -> Type {
+        expected
+            .filter(|expected| expected.is_integer())
+            .unwrap_or(Type::I32)
+    }
+}
+
+impl ASTNode for LayoutOfASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        None
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write(format_args!("{}::<{}>()", self.query.name(), self.ty));
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::LayoutOf);
+        encoder.write_u8(self.query as u8);
+        encoder.write_type(&self.ty);
+        encoder.write_span(self.span);
+    }
+}
+
+impl ExprASTNode for LayoutOfASTNode {
+    fn try_as_place(&self) -> Option<&dyn PlaceExprASTNode> {
+        None
+    }
+
+    fn try_as_value(&self) -> Option<&dyn ValueExprASTNode> {
+        Some(self)
+    }
+
+    fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
+        None
+    }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::LayoutOf(self)
+    }
+
+    fn const_eval_in(&self, env: &ConstEnv) -> Option<ConstValue> {
+        let layout = env.layout();
+        let value = match self.query {
+            LayoutQuery::SizeOf => layout.size_of(&self.ty()),
+            LayoutQuery::AlignOf => layout.align_of(&self.ty()),
+        };
+        Some(ConstValue::Int(value as i128))
+    }
+}
+
+impl ValueExprASTNode for LayoutOfASTNode {}
+
+impl fmt::Display for LayoutOfASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let query = match self.query {
+            LayoutQuery::SizeOf => "Size Of",
+            LayoutQuery::AlignOf => "Align Of",
+        };
+        write!(f, "{} {} `{}`", query, self.span, self.ty)
+    }
+}
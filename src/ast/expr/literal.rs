@@ -1,11 +1,15 @@
 //! A module containing Literal AST node implementations.
 
+use std::rc::Rc;
+
 use crate::ast::Type;
+use crate::pretty::SourcePrinter;
 use crate::token::Span;
 
 mod bool;
 mod float;
 mod int;
+mod str;
 mod unit;
 
 /// A generic AST node representing a literal.
@@ -13,6 +17,8 @@ mod unit;
 pub struct LiteralASTNode<T> {
     value: T,
     ty: Type,
+    text: Option<Rc<str>>,
+    suffixed: bool,
     span: Span,
 }
 
@@ -25,14 +31,38 @@ impl<T> LiteralASTNode<T> {
     ///
     /// # Example
     /// ```ignore
-    /// impl LiteralASTNode<i32> {
-    ///     pub fn new(value: i32, span: Span) -> LiteralASTNode<i32> {
+    /// impl LiteralASTNode<i128> {
+    ///     pub fn new(value: i128, span: Span) -> LiteralASTNode<i128> {
     ///         LiteralASTNode::new_generic(value, Type::I32, span)
     ///     }
     /// }
     /// ```
     fn new_generic(value: T, ty: Type, span: Span) -> LiteralASTNode<T> {
-        LiteralASTNode { value, ty, span }
+        LiteralASTNode {
+            value,
+            ty,
+            text: None,
+            suffixed: false,
+            span,
+        }
+    }
+
+    /// Sets the text of the literal in the source code.
+    pub fn with_text(self, text: Rc<str>) -> LiteralASTNode<T> {
+        LiteralASTNode {
+            text: Some(text),
+            ..self
+        }
+    }
+
+    /// Marks the literal as written with the type suffix (e.g. `1i32`),
+    /// which fixes its type to `ty`.
+    pub fn with_suffix(self, ty: Type) -> LiteralASTNode<T> {
+        LiteralASTNode {
+            ty,
+            suffixed: true,
+            ..self
+        }
     }
 
     /// Returns a reference to the value of the literal.
@@ -42,14 +72,50 @@ impl<T> LiteralASTNode<T> {
 
     /// Returns the type of the literal.
     pub fn ty(&self) -> Type {
-        self.ty
+        self.ty.clone()
+    }
+
+    /// Returns the text of the literal in the source code, if it is known.
+    ///
+    /// It is kept for the float literals, whose value may be rounded.
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Returns the type given by the suffix of the literal, if it has one.
+    pub fn suffix(&self) -> Option<Type> {
+        self.suffixed.then(|| self.ty.clone())
+    }
+
+    /// Returns the type of the literal in a context which expects the `expected` type.
+    ///
+    /// A suffixed literal always has the type of its suffix. An unsuffixed number literal
+    /// adopts the expected type if it is of the same kind (an integer or a floating-point type),
+    /// and otherwise defaults to its [type](LiteralASTNode::ty) (i.e. `i32` or `f64`).
+    pub fn infer_type(&self, expected: Option<Type>) -> Type {
+        if self.suffixed {
+            return self.ty.clone();
+        }
+        expected
+            .filter(|expected| {
+                (self.ty.is_integer() && expected.is_integer())
+                    || (self.ty.is_float() && expected.is_float())
+            })
+            .unwrap_or_else(|| self.ty.clone())
     }
 }
 
+/// A trait for printing the value of a literal as μRust source code.
+trait PrintLiteral {
+    /// Prints the value in the form it is written in the source code.
+    fn print_literal(&self, printer: &mut SourcePrinter);
+}
+
 macro_rules! impl_ast {
-    ($ty:ty) => {
+    ($ty:ty, $lowered:path) => {
         impl_ast! {
             Type = $ty;
+            Lowered = $lowered;
 
             impl fmt::Display for LiteralASTNode<$ty> {
                 fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -60,20 +126,44 @@ macro_rules! impl_ast {
     };
     (
         Type = $ty:ty;
+        Lowered = $lowered:path;
         $display_impl:item
     ) => {
         use crate::ast::{
-            ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode,
-            PlaceExprASTNode, ValueExprASTNode,
+            ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ConstEnv, ConstValue,
+            CoreExpr, ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
         };
+        use crate::pretty::SourcePrinter;
+        use crate::serialize::{Encoder, NodeTag};
         use std::fmt;
 
         impl ASTNode for LiteralASTNode<$ty> {
             ast_defaults!();
 
-            fn children(&self) -> Option<ASTChildIterator> {
+            fn children(&self) -> Option<ASTChildIterator<'_>> {
                 None
             }
+
+            fn print_source(&self, printer: &mut SourcePrinter) {
+                match &self.text {
+                    Some(text) => printer.write(text),
+                    None => super::PrintLiteral::print_literal(&self.value, printer),
+                }
+                if let Some(suffix) = self.suffix() {
+                    printer.write(suffix);
+                }
+            }
+
+            fn encode(&self, encoder: &mut Encoder) {
+                encoder.write_tag(NodeTag::Literal);
+                encoder.write_literal(ConstValue::from(self.value));
+                encoder.write_bool(self.text.is_some());
+                if let Some(text) = &self.text {
+                    encoder.write_str(text);
+                }
+                encoder.write_suffix(self.suffix());
+                encoder.write_span(self.span);
+            }
         }
 
         impl ExprASTNode for LiteralASTNode<$ty> {
@@ -88,6 +178,14 @@ macro_rules! impl_ast {
             fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
                 None
             }
+
+            fn lower(&self) -> CoreExpr<'_> {
+                $lowered(self)
+            }
+
+            fn const_eval_in(&self, _: &ConstEnv) -> Option<ConstValue> {
+                Some(ConstValue::from(self.value))
+            }
         }
 
         impl ValueExprASTNode for LiteralASTNode<$ty> {}
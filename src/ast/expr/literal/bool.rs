@@ -1,13 +1,9 @@
 //! A concrete implementation of [LiteralASTNode] for booleans.
 
-use inkwell::values::{AnyValue, AnyValueEnum};
-
 use crate::ast::Type;
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
 use crate::token::Span;
 
-use super::{impl_ast, LiteralASTNode};
+use super::{impl_ast, LiteralASTNode, PrintLiteral};
 
 impl LiteralASTNode<bool> {
     /// Creates a new `LiteralASTNode<bool>` with the given value and span.
@@ -16,12 +12,10 @@ impl LiteralASTNode<bool> {
     }
 }
 
-impl_ast!(bool);
-
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LiteralASTNode<bool> {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        let bool_type = state.context().bool_type();
-        let value = bool_type.const_int(self.value as u64, false);
-        Ok(value.as_any_value_enum())
+impl PrintLiteral for bool {
+    fn print_literal(&self, printer: &mut SourcePrinter) {
+        printer.write(self);
     }
 }
+
+impl_ast!(bool, CoreExpr::Bool);
@@ -1,27 +1,56 @@
 //! A concrete implementation of [LiteralASTNode] for floating-point numbers.
 
-use inkwell::values::{AnyValue, AnyValueEnum};
-
 use crate::ast::Type;
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
 use crate::token::Span;
 
-use super::{impl_ast, LiteralASTNode};
+use super::{impl_ast, LiteralASTNode, PrintLiteral};
 
 impl LiteralASTNode<f64> {
     /// Creates a new `LiteralASTNode<f64>` with the given value and span.
     pub fn new(value: f64, span: Span) -> LiteralASTNode<f64> {
         LiteralASTNode::new_generic(value, Type::F64, span)
     }
+
+    /// Creates a new `LiteralASTNode<f64>` from the given builtin constant path
+    /// (e.g. `f64::INFINITY`), if there is such a constant.
+    ///
+    /// The constants are the only way to express the non-finite values,
+    /// as there are no literals for them.
+    pub fn builtin_constant(path: &str, span: Span) -> Option<LiteralASTNode<f64>> {
+        BUILTIN_CONSTANTS
+            .iter()
+            .find(|(name, _)| *name == path)
+            .map(|(_, value)| LiteralASTNode::<f64>::new(*value, span))
+    }
 }
 
-impl_ast!(f64);
+/// The builtin constants of type `f64` together with their values.
+const BUILTIN_CONSTANTS: [(&str, f64); 3] = [
+    ("f64::INFINITY", f64::INFINITY),
+    ("f64::NEG_INFINITY", f64::NEG_INFINITY),
+    ("f64::NAN", f64::NAN),
+];
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LiteralASTNode<f64> {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        let f64_type = state.context().f64_type();
-        let value = f64_type.const_float(self.value);
-        Ok(value.as_any_value_enum())
+impl PrintLiteral for f64 {
+    fn print_literal(&self, printer: &mut SourcePrinter) {
+        if !self.is_finite() {
+            let name = if self.is_nan() {
+                "f64::NAN"
+            } else if self.is_sign_positive() {
+                "f64::INFINITY"
+            } else {
+                "f64::NEG_INFINITY"
+            };
+            printer.write(name);
+            return;
+        }
+        // Float literals have to contain a `.`, otherwise they would be lexed as integers
+        let text = self.to_string();
+        printer.write(&text);
+        if !text.contains('.') {
+            printer.write(".0");
+        }
     }
 }
+
+impl_ast!(f64, CoreExpr::Float);
@@ -1,27 +1,57 @@
 //! A concrete implementation of [LiteralASTNode] for integers.
 
-use inkwell::values::AnyValueEnum;
-
+use crate::ast::error::SemanticError;
 use crate::ast::Type;
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
 use crate::token::Span;
 
-use super::{impl_ast, LiteralASTNode};
+use super::{impl_ast, LiteralASTNode, PrintLiteral};
 
-impl LiteralASTNode<i32> {
-    /// Creates a new `LiteralASTNode<i32>` with the given value and span.
-    pub fn new(value: i32, span: Span) -> LiteralASTNode<i32> {
+impl LiteralASTNode<i128> {
+    /// Creates a new `LiteralASTNode<i128>` with the given value and span.
+    ///
+    /// The value is not limited by the type of the literal (by default `i32`) yet,
+    /// it is only [checked](LiteralASTNode::check_range) once the type is known.
+    pub fn new(value: i128, span: Span) -> LiteralASTNode<i128> {
         LiteralASTNode::new_generic(value, Type::I32, span)
     }
-}
 
-impl_ast!(i32);
+    /// Returns the value of the literal (negated if it is the operand of a negation,
+    /// e.g. in `-128i8`) if it is in the range of `ty`, which is the type of the literal
+    /// (see [`infer_type`](LiteralASTNode::infer_type)).
+    ///
+    /// # Errors
+    ///
+    /// If the value is out of the range of `ty`, [`SemanticError::IntOutOfRange`] is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::ast::{LiteralASTNode, Type};
+    /// # use mini_rust_compiler_components::token::{Position, Span};
+    /// let span = Span::new(Position::new(), Position::new());
+    /// let literal = LiteralASTNode::<i128>::new(128, span);
+    ///
+    /// assert!(literal.check_range(&Type::I8, false).is_err());
+    /// assert_eq!(literal.check_range(&Type::I8, true), Ok(-128));
+    /// assert_eq!(literal.check_range(&Type::U8, false), Ok(128));
+    /// ```
+    pub fn check_range(&self, ty: &Type, negated: bool) -> Result<i128, SemanticError> {
+        let value = if negated { -self.value } else { self.value };
+        match ty.int_range() {
+            Some(range) if !range.contains(&value) => Err(SemanticError::IntOutOfRange {
+                value,
+                ty: ty.clone(),
+                span: self.span,
+            }),
+            _ => Ok(value),
+        }
+    }
+}
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LiteralASTNode<i32> {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        let i32_type = state.context().i32_type();
-        let value = i32_type.const_int(self.value as u64, true);
-        Ok(AnyValueEnum::IntValue(value))
+impl PrintLiteral for i128 {
+    fn print_literal(&self, printer: &mut SourcePrinter) {
+        printer.write(self);
     }
 }
+
+impl_ast!(i128, CoreExpr::Int);
@@ -0,0 +1,76 @@
+//! A concrete implementation of [LiteralASTNode] for strings.
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::{
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr, ExprASTNode,
+    PlaceExprASTNode, Type, ValueExprASTNode,
+};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
+use crate::token::Span;
+
+use super::{LiteralASTNode, PrintLiteral};
+
+impl LiteralASTNode<Rc<str>> {
+    /// Creates a new `LiteralASTNode<Rc<str>>` with the given (already unescaped) value and span.
+    pub fn new(value: Rc<str>, span: Span) -> LiteralASTNode<Rc<str>> {
+        LiteralASTNode::new_generic(value, Type::Str, span)
+    }
+}
+
+impl PrintLiteral for Rc<str> {
+    fn print_literal(&self, printer: &mut SourcePrinter) {
+        // The escape sequences of `Debug` are a subset of the ones supported by the lexer
+        printer.write(format_args!("{:?}", self));
+    }
+}
+
+// The strings are not `Copy` and cannot be evaluated at compile time,
+// so the node is not implemented with `impl_ast!`
+impl ASTNode for LiteralASTNode<Rc<str>> {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        None
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        self.value.print_literal(printer);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Literal);
+        encoder.write_str_literal(&self.value);
+        encoder.write_bool(false);
+        encoder.write_suffix(None);
+        encoder.write_span(self.span);
+    }
+}
+
+impl ExprASTNode for LiteralASTNode<Rc<str>> {
+    fn try_as_place(&self) -> Option<&dyn PlaceExprASTNode> {
+        None
+    }
+
+    fn try_as_value(&self) -> Option<&dyn ValueExprASTNode> {
+        Some(self)
+    }
+
+    fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
+        None
+    }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Str(self)
+    }
+}
+
+impl ValueExprASTNode for LiteralASTNode<Rc<str>> {}
+
+impl fmt::Display for LiteralASTNode<Rc<str>> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Literal {} `{}` {:?}", self.span, self.ty, self.value)
+    }
+}
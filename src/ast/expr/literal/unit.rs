@@ -1,13 +1,9 @@
 //! A concrete implementation of [LiteralASTNode] for unit.
 
-use inkwell::values::{AnyValue, AnyValueEnum};
-
 use crate::ast::Type;
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
-use crate::token::{Position, Span};
+use crate::token::Span;
 
-use super::{impl_ast, LiteralASTNode};
+use super::{impl_ast, LiteralASTNode, PrintLiteral};
 
 impl LiteralASTNode<()> {
     /// Creates a new `LiteralASTNode<()>` with the given span.
@@ -16,8 +12,15 @@ impl LiteralASTNode<()> {
     }
 }
 
+impl PrintLiteral for () {
+    fn print_literal(&self, printer: &mut SourcePrinter) {
+        printer.write("()");
+    }
+}
+
 impl_ast! {
     Type = ();
+    Lowered = CoreExpr::Unit;
 
     impl fmt::Display for LiteralASTNode<()> {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -25,24 +28,3 @@ impl_ast! {
         }
     }
 }
-
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LiteralASTNode<()> {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        let unit_type = state.context().struct_type(&[], false);
-        let value = unit_type.const_zero();
-        Ok(value.as_any_value_enum())
-    }
-}
-
-impl<'ctx> CodeGenState<'ctx> {
-    //TODO Add Examples to the documentation
-    /// Generates a new unit [`LLVM value`](AnyValueEnum) with the span that starts and ends at `end_pos`.
-    pub fn build_unit_value(
-        self: &mut CodeGenState<'ctx>,
-        end_pos: Position,
-    ) -> AnyValueEnum<'ctx> {
-        let span = Span::new(end_pos, end_pos);
-        let unit = LiteralASTNode::<()>::new(span);
-        CodeGen::<AnyValueEnum>::code_gen(&unit, self).unwrap()
-    }
-}
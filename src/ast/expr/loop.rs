@@ -1,7 +1,11 @@
 //! A module containing all the loop AST nodes.
 
+pub use self::do_while::*;
 pub use self::inf_loop::*;
+pub use self::r#for::*;
 pub use self::r#while::*;
 
+mod do_while;
+mod r#for;
 mod inf_loop;
 mod r#while;
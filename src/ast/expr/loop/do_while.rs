@@ -0,0 +1,151 @@
+//! A module containing the Do-While Loop AST node implementation.
+
+use std::{fmt, iter};
+
+use debug_tree::TreeBuilder;
+
+use crate::ast::{
+    ast_defaults, ASTChildIterator, ASTNode, AsASTNode, AssigneeExprASTNode, BlockASTNode,
+    CoreExpr, ExprASTNode, ExprStmtASTNode, PlaceExprASTNode, ValueExprASTNode, WhileASTNode,
+};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
+use crate::token::Span;
+
+/// An AST node representing a do-while loop expression (`do { ... } while condition`),
+/// i.e. a loop whose condition is checked after every iteration.
+///
+/// It is desugared (when it is created) into the equivalent [`WhileASTNode`]
+/// `while { { ... }; condition } {}`, which the code generation backends build instead,
+/// while the original form is kept for printing the source and the tree.
+#[derive(Debug)]
+pub struct DoWhileASTNode {
+    desugared: WhileASTNode,
+    span: Span,
+}
+
+impl DoWhileASTNode {
+    /// Creates a new `DoWhileASTNode` with the given body, condition and span.
+    pub fn new(
+        body: Box<BlockASTNode>,
+        condition: Box<dyn ExprASTNode>,
+        span: Span,
+    ) -> DoWhileASTNode {
+        let cond_span = Span::new(body.span().start(), condition.span().end());
+        let body_span = body.span();
+        let body = ExprStmtASTNode::new(body, body_span);
+        let cond = BlockASTNode::new_with_return(vec![Box::new(body)], condition, cond_span);
+        let empty = BlockASTNode::new(Vec::new(), Span::new(span.end(), span.end()));
+        let desugared = WhileASTNode::new(Box::new(cond), Box::new(empty), span);
+        DoWhileASTNode { desugared, span }
+    }
+
+    /// Returns the body of the loop.
+    pub fn body(&self) -> &BlockASTNode {
+        self.cond_block().statements()[0]
+            .as_any()
+            .downcast_ref::<ExprStmtASTNode>()
+            .and_then(|stmt| stmt.expr().as_any().downcast_ref::<BlockASTNode>())
+            .expect("The body should be the first statement of the condition block")
+    }
+
+    /// Returns the condition of the loop.
+    pub fn condition(&self) -> &dyn ExprASTNode {
+        self.cond_block()
+            .return_expr()
+            .expect("The condition should be the value of the condition block")
+    }
+
+    /// Returns the equivalent while loop, which runs the body as a part of its condition.
+    pub fn desugared(&self) -> &WhileASTNode {
+        &self.desugared
+    }
+
+    /// Returns the condition of the [desugared](DoWhileASTNode::desugared) loop,
+    /// i.e. the block containing both the body and the condition.
+    fn cond_block(&self) -> &BlockASTNode {
+        self.desugared
+            .condition()
+            .as_any()
+            .downcast_ref::<BlockASTNode>()
+            .expect("The condition of a desugared do-while loop should be a block")
+    }
+}
+
+impl ASTNode for DoWhileASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        let body = iter::once(self.body().as_ast());
+        let condition = iter::once(self.condition().as_ast());
+        let iter = body.chain(condition);
+        Some(Box::new(iter))
+    }
+
+    fn add_to_tree_string(&self, builder: &mut TreeBuilder) {
+        let body = self.body().as_ast();
+        let condition = self.condition().as_ast();
+
+        let mut branch = builder.add_branch(format!("{self}").as_str());
+        {
+            let mut branch = builder.add_branch("Body");
+            body.add_to_tree_string(builder);
+            branch.release()
+        }
+        {
+            let mut branch = builder.add_branch("Condition");
+            condition.add_to_tree_string(builder);
+            branch.release()
+        }
+        branch.release()
+    }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        // The body is always executed at least once, and the condition after every iteration
+        checker.check_loop_body(|checker| {
+            self.body().check_init(checker);
+            self.condition().check_init(checker);
+        });
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("do ");
+        self.body().print_source(printer);
+        printer.write(" while ");
+        self.condition().print_source(printer);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::DoWhile);
+        encoder.write_node(self.body().as_ast());
+        encoder.write_node(self.condition().as_ast());
+        encoder.write_span(self.span);
+    }
+}
+
+impl ExprASTNode for DoWhileASTNode {
+    fn try_as_place(&self) -> Option<&dyn PlaceExprASTNode> {
+        None
+    }
+
+    fn try_as_value(&self) -> Option<&dyn ValueExprASTNode> {
+        Some(self)
+    }
+
+    fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
+        None
+    }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::While(&self.desugared)
+    }
+}
+
+impl ValueExprASTNode for DoWhileASTNode {}
+
+impl fmt::Display for DoWhileASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Do While {}", self.span)
+    }
+}
@@ -0,0 +1,357 @@
+//! A module containing the For Loop AST node implementation.
+
+use std::rc::Rc;
+use std::{fmt, iter};
+
+use debug_tree::TreeBuilder;
+
+use crate::ast::{
+    ast_defaults, ASTChildIterator, ASTNode, ArithExprASTNode, ArithOperator, AsASTNode,
+    AssignASTNode, AssigneeExprASTNode, BlockASTNode, CompExprASTNode, CompOperator, CoreExpr,
+    ExprASTNode, ExprStmtASTNode, IfASTNode, LetASTNode, LiteralASTNode, PathASTNode,
+    PlaceExprASTNode, StatementASTNode, ValueExprASTNode, WhileASTNode,
+};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::resolve::{DeclKind, Resolver};
+use crate::serialize::{Encoder, NodeTag};
+use crate::token::Span;
+
+/// The hidden variables of the [desugared](ForASTNode::desugared) loop
+/// (`#` cannot appear in an identifier, so they cannot clash with the ones in the source).
+const COUNTER: &str = "#counter";
+const END: &str = "#end";
+const MORE: &str = "#more";
+
+/// An AST node representing a for loop expression over an integer range
+/// (`for i in start..end { ... }` or `for i in start..=end { ... }`).
+///
+/// It is desugared (when it is created) into the equivalent block with a [`WhileASTNode`],
+/// which the code generation backends build instead, while the original form is kept
+/// for printing the source and the tree:
+/// ```text
+/// {
+///     let mut #counter = start;
+///     let mut #end = #counter;
+///     #end = end;
+///     while #counter < #end {
+///         let i = #counter;
+///         #counter = #counter + 1;
+///         { ... };
+///     }
+/// }
+/// ```
+/// This way:
+/// - the end of the range is evaluated only once, before the first iteration,
+///   and it is of the type of the start,
+/// - the loop variable cannot be assigned in the body,
+/// - the counter is incremented before the body, so it is never incremented past the end.
+///
+/// An unsuffixed literal at the start adopts the type of the end (e.g. `0..n`) instead,
+/// so the end is evaluated first (a literal has no side effects). An inclusive range
+/// only increments the counter while it is less than the end, so that the range can end
+/// at the maximum value of its type:
+/// ```text
+/// let mut #more = #counter <= #end;
+/// while #more {
+///     let i = #counter;
+///     #more = #counter < #end;
+///     if #more { #counter = #counter + 1; }
+///     { ... };
+/// }
+/// ```
+///
+/// The range is of the type of its bounds, e.g. `0u8..n` iterates over `u8` values.
+#[derive(Debug)]
+pub struct ForASTNode {
+    desugared: BlockASTNode,
+    /// Whether the end of the range is evaluated before its start.
+    end_first: bool,
+    inclusive: bool,
+    span: Span,
+}
+
+impl ForASTNode {
+    /// Creates a new `ForASTNode` with the given pattern, bounds of the range,
+    /// its inclusiveness, body and span.
+    ///
+    /// The pattern is either a [path](PathASTNode) (the loop variable)
+    /// or an [underscore](crate::ast::UnderscoreASTNode).
+    pub fn new(
+        pattern: Box<dyn ExprASTNode>,
+        start: Box<dyn ExprASTNode>,
+        end: Box<dyn ExprASTNode>,
+        inclusive: bool,
+        body: Box<BlockASTNode>,
+        span: Span,
+    ) -> ForASTNode {
+        let range = Span::new(start.span().start(), end.span().end());
+        let end_first = start
+            .as_any()
+            .downcast_ref::<LiteralASTNode<i128>>()
+            .is_some_and(|literal| literal.suffix().is_none());
+        let (first, first_name, second, second_name) = match end_first {
+            true => (end, END, start, COUNTER),
+            false => (start, COUNTER, end, END),
+        };
+
+        let (first_span, second_span) = (first.span(), second.span());
+        let mut statements = vec![
+            declare(first_name, first, first_span),
+            declare(second_name, path(first_name, second_span), second_span),
+            assign(second_name, second, second_span),
+        ];
+        let pattern_span = pattern.span();
+        let binding = LetASTNode::new_inferred(pattern, path(COUNTER, range), None, pattern_span);
+        let mut body_statements: Vec<Box<dyn StatementASTNode>> = vec![Box::new(binding)];
+        let condition = match inclusive {
+            true => {
+                let more = compare(CompOperator::Le, range);
+                statements.push(declare(MORE, more, range));
+                body_statements.push(assign(MORE, compare(CompOperator::Lt, range), range));
+                let step = BlockASTNode::new(vec![step(range)], range);
+                let step = IfASTNode::new(path(MORE, range), Box::new(step), None, range);
+                body_statements.push(Box::new(ExprStmtASTNode::new(Box::new(step), range)));
+                path(MORE, range)
+            }
+            false => {
+                body_statements.push(step(range));
+                compare(CompOperator::Lt, range)
+            }
+        };
+
+        let body_span = body.span();
+        body_statements.push(Box::new(ExprStmtASTNode::new(body, body_span)));
+        let body = BlockASTNode::new(body_statements, body_span);
+        let while_node = WhileASTNode::new(condition, Box::new(body), span);
+        let desugared = BlockASTNode::new_with_return(statements, Box::new(while_node), span);
+        ForASTNode {
+            desugared,
+            end_first,
+            inclusive,
+            span,
+        }
+    }
+
+    /// Returns the pattern of the loop variable.
+    pub fn pattern(&self) -> &dyn ExprASTNode {
+        self.loop_body().statements()[0]
+            .as_any()
+            .downcast_ref::<LetASTNode>()
+            .expect("The loop variable should be declared at the start of the body")
+            .decl()
+    }
+
+    /// Returns the name of the loop variable, if the pattern binds one.
+    pub fn variable(&self) -> Option<Rc<str>> {
+        self.pattern().try_as_assignee().and_then(|a| a.pattern())
+    }
+
+    /// Returns the (inclusive) start of the range.
+    pub fn start(&self) -> &dyn ExprASTNode {
+        self.bound(!self.end_first)
+    }
+
+    /// Returns the end of the range.
+    pub fn end(&self) -> &dyn ExprASTNode {
+        self.bound(self.end_first)
+    }
+
+    /// Returns whether the end of the range is inclusive (`..=`).
+    pub fn is_inclusive(&self) -> bool {
+        self.inclusive
+    }
+
+    /// Returns the body of the loop.
+    pub fn body(&self) -> &BlockASTNode {
+        self.loop_body()
+            .statements()
+            .last()
+            .and_then(|statement| statement.as_any().downcast_ref::<ExprStmtASTNode>())
+            .and_then(|statement| statement.expr().as_any().downcast_ref::<BlockASTNode>())
+            .expect("The body should be the last statement of the while loop")
+    }
+
+    /// Returns the equivalent block with a while loop (see [`ForASTNode`]).
+    pub fn desugared(&self) -> &BlockASTNode {
+        &self.desugared
+    }
+
+    /// Returns the bound of the range evaluated first (the value of the first `let`)
+    /// or second (the value assigned in the third statement) by the desugared loop.
+    fn bound(&self, first: bool) -> &dyn ExprASTNode {
+        let statements = self.desugared.statements();
+        let bound = match first {
+            true => statements[0]
+                .as_any()
+                .downcast_ref::<LetASTNode>()
+                .and_then(LetASTNode::value),
+            false => statements[2]
+                .as_any()
+                .downcast_ref::<ExprStmtASTNode>()
+                .and_then(|statement| statement.expr().as_any().downcast_ref::<AssignASTNode>())
+                .map(AssignASTNode::value),
+        };
+        bound.expect("The bounds should be stored in the counter and the end")
+    }
+
+    /// Returns the body of the desugared while loop.
+    fn loop_body(&self) -> &BlockASTNode {
+        self.desugared
+            .return_expr()
+            .and_then(|expr| expr.as_any().downcast_ref::<WhileASTNode>())
+            .map(WhileASTNode::body)
+            .expect("The value of the desugared block should be a while loop")
+    }
+}
+
+/// Returns the path to the given hidden variable.
+fn path(name: &str, span: Span) -> Box<dyn ExprASTNode> {
+    Box::new(PathASTNode::new(name.into(), span))
+}
+
+/// Returns the declaration of the given mutable hidden variable initialized with the `value`.
+fn declare(name: &str, value: Box<dyn ExprASTNode>, span: Span) -> Box<dyn StatementASTNode> {
+    Box::new(LetASTNode::new_inferred(
+        path(name, span),
+        value,
+        Some(span),
+        span,
+    ))
+}
+
+/// Returns the assignment of the `value` to the given hidden variable.
+fn assign(name: &str, value: Box<dyn ExprASTNode>, span: Span) -> Box<dyn StatementASTNode> {
+    let assign = AssignASTNode::new(path(name, span), value, span);
+    Box::new(ExprStmtASTNode::new(Box::new(assign), span))
+}
+
+/// Returns the comparison of the counter with the end of the range.
+fn compare(operator: CompOperator, span: Span) -> Box<dyn ExprASTNode> {
+    let lhs = path(COUNTER, span);
+    Box::new(CompExprASTNode::new(operator, lhs, path(END, span), span))
+}
+
+/// Returns the increment of the counter.
+fn step(span: Span) -> Box<dyn StatementASTNode> {
+    let one = Box::new(LiteralASTNode::<i128>::new(1, span));
+    let next = ArithExprASTNode::new(ArithOperator::Add, path(COUNTER, span), one, span);
+    assign(COUNTER, Box::new(next), span)
+}
+
+impl ASTNode for ForASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        let pattern = iter::once(self.pattern().as_ast());
+        let start = iter::once(self.start().as_ast());
+        let end = iter::once(self.end().as_ast());
+        let body = iter::once(self.body().as_ast());
+        let iter = pattern.chain(start).chain(end).chain(body);
+        Some(Box::new(iter))
+    }
+
+    fn add_to_tree_string(&self, builder: &mut TreeBuilder) {
+        let pattern = self.pattern().as_ast();
+        let start = self.start().as_ast();
+        let end = self.end().as_ast();
+        let body = self.body().as_ast();
+
+        let mut branch = builder.add_branch(format!("{self}").as_str());
+        {
+            let mut branch = builder.add_branch("Pattern");
+            pattern.add_to_tree_string(builder);
+            branch.release()
+        }
+        {
+            let mut branch = builder.add_branch("Start");
+            start.add_to_tree_string(builder);
+            branch.release()
+        }
+        {
+            let mut branch = builder.add_branch("End");
+            end.add_to_tree_string(builder);
+            branch.release()
+        }
+        {
+            let mut branch = builder.add_branch("Body");
+            body.add_to_tree_string(builder);
+            branch.release()
+        }
+        branch.release()
+    }
+
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        // The loop variable is not visible in the range
+        self.start().resolve_names(resolver);
+        self.end().resolve_names(resolver);
+        resolver.open_scope();
+        if let Some(name) = self.variable() {
+            resolver.declare(name, DeclKind::Local, self.pattern().span());
+        }
+        self.body().resolve_names(resolver);
+        resolver.close_scope();
+    }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        self.start().check_init(checker);
+        self.end().check_init(checker);
+        // The body may not be executed at all
+        let state = checker.snapshot();
+        checker.open_scope();
+        if let Some(name) = self.variable() {
+            checker.declare(name, self.pattern().span(), None, true);
+        }
+        checker.check_loop_body(|checker| self.body().check_init(checker));
+        checker.close_scope();
+        checker.restore(state);
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("for ");
+        self.pattern().print_source(printer);
+        printer.write(" in ");
+        self.start().print_source(printer);
+        printer.write(if self.inclusive { "..=" } else { ".." });
+        self.end().print_source(printer);
+        printer.write(" ");
+        self.body().print_source(printer);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::For);
+        encoder.write_node(self.pattern().as_ast());
+        encoder.write_node(self.start().as_ast());
+        encoder.write_node(self.end().as_ast());
+        encoder.write_bool(self.inclusive);
+        encoder.write_node(self.body().as_ast());
+        encoder.write_span(self.span);
+    }
+}
+
+impl ExprASTNode for ForASTNode {
+    fn try_as_place(&self) -> Option<&dyn PlaceExprASTNode> {
+        None
+    }
+
+    fn try_as_value(&self) -> Option<&dyn ValueExprASTNode> {
+        Some(self)
+    }
+
+    fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
+        None
+    }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Block(&self.desugared)
+    }
+}
+
+impl ValueExprASTNode for ForASTNode {}
+
+impl fmt::Display for ForASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let range = if self.inclusive { "Inclusive " } else { "" };
+        write!(f, "For {}{}", range, self.span)
+    }
+}
@@ -2,15 +2,13 @@
 
 use std::{fmt, iter};
 
-use inkwell::values::AnyValueEnum;
-
 use crate::ast::{
     ast_defaults, ASTChildIterator, ASTNode, AsASTNode, AssigneeExprASTNode, BlockASTNode,
-    ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
+    CoreExpr, ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing an infinite loop expression.
@@ -25,15 +23,38 @@ impl InfLoopASTNode {
     pub fn new(block: Box<BlockASTNode>, span: Span) -> InfLoopASTNode {
         InfLoopASTNode { block, span }
     }
+
+    /// Returns the body of the loop.
+    pub fn body(&self) -> &BlockASTNode {
+        &self.block
+    }
 }
 
 impl ASTNode for InfLoopASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let iter = iter::once(self.block.as_ast());
         Some(Box::new(iter))
     }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        checker.check_loop_body(|checker| self.block.check_init(checker));
+        //TODO Update when `break` is supported
+        // The loop can only be exited with a `return`, so the code after it is unreachable
+        checker.diverge();
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("loop ");
+        self.block.print_source(printer);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::InfLoop);
+        encoder.write_node(self.block.as_ast());
+        encoder.write_span(self.span);
+    }
 }
 
 impl ExprASTNode for InfLoopASTNode {
@@ -48,38 +69,14 @@ impl ExprASTNode for InfLoopASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
 
-impl ValueExprASTNode for InfLoopASTNode {}
-
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for InfLoopASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        //TODO Type checking -> loop has type `!` (unless it has a `break` statement)
-        let parent_fn = state
-            .get_current_function()
-            .unwrap_or_else(|| panic!("Statement outside of function"));
-
-        //#region Label
-        let start_bb = state.context().append_basic_block(parent_fn, "loop");
-        state
-            .builder()
-            .build_unconditional_branch(start_bb)
-            .map_err(CodeGenError::from)?;
-        //#endregion
-
-        //#region Body
-        state.builder().position_at_end(start_bb);
-        self.block.code_gen(state)?;
-        state
-            .builder()
-            .build_unconditional_branch(start_bb)
-            .map_err(CodeGenError::from)?;
-        //#endregion
-
-        Ok(state.build_unit_value(self.span.end()))
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::InfLoop(self)
     }
 }
 
+impl ValueExprASTNode for InfLoopASTNode {}
+
 impl fmt::Display for InfLoopASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Loop {}", self.span)
@@ -3,15 +3,14 @@
 use std::{fmt, iter};
 
 use debug_tree::TreeBuilder;
-use inkwell::values::AnyValueEnum;
 
 use crate::ast::{
     ast_defaults, ASTChildIterator, ASTNode, AsASTNode, AssigneeExprASTNode, BlockASTNode,
-    ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
+    CoreExpr, ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing a while loop expression.
@@ -36,12 +35,22 @@ impl WhileASTNode {
             span,
         }
     }
+
+    /// Returns the condition of the loop.
+    pub fn condition(&self) -> &dyn ExprASTNode {
+        self.condition.as_ref()
+    }
+
+    /// Returns the body of the loop.
+    pub fn body(&self) -> &BlockASTNode {
+        &self.body
+    }
 }
 
 impl ASTNode for WhileASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let condition = iter::once(self.condition.as_ast());
         let body = iter::once(self.body.as_ast());
         let iter = condition.chain(body);
@@ -65,6 +74,28 @@ impl ASTNode for WhileASTNode {
         }
         branch.release()
     }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        self.condition.check_init(checker);
+        // The body may not be executed at all
+        let state = checker.snapshot();
+        checker.check_loop_body(|checker| self.body.check_init(checker));
+        checker.restore(state);
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("while ");
+        self.condition.print_source(printer);
+        printer.write(" ");
+        self.body.print_source(printer);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::While);
+        encoder.write_node(self.condition.as_ast());
+        encoder.write_node(self.body.as_ast());
+        encoder.write_span(self.span);
+    }
 }
 
 impl ExprASTNode for WhileASTNode {
@@ -79,51 +110,14 @@ impl ExprASTNode for WhileASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
 
-impl ValueExprASTNode for WhileASTNode {}
-
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for WhileASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        //TODO Type checking -> loop has type `!` (unless it has a `break` statement)
-        let parent_fn = state
-            .get_current_function()
-            .unwrap_or_else(|| panic!("Statement outside of function"));
-
-        //#region Labels
-        let cond_bb = state.context().append_basic_block(parent_fn, "cond");
-        let body_bb = state.context().append_basic_block(parent_fn, "body");
-        let end_bb = state.context().append_basic_block(parent_fn, "end");
-        state
-            .builder()
-            .build_unconditional_branch(cond_bb)
-            .map_err(CodeGenError::from)?;
-        //#endregion
-
-        //#region Condition
-        state.builder().position_at_end(cond_bb);
-        let cond = state.build_condition(self.condition.as_ref(), true)?;
-        state
-            .builder()
-            .build_conditional_branch(cond, body_bb, end_bb)
-            .map_err(CodeGenError::from)?;
-        //#endregion
-
-        //#region Body
-        state.builder().position_at_end(body_bb);
-        self.body.code_gen(state)?;
-        state
-            .builder()
-            .build_unconditional_branch(cond_bb)
-            .map_err(CodeGenError::from)?;
-        //#endregion
-
-        state.builder().position_at_end(end_bb);
-
-        Ok(state.build_unit_value(self.span.end()))
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::While(self)
     }
 }
 
+impl ValueExprASTNode for WhileASTNode {}
+
 impl fmt::Display for WhileASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "While {}", self.span)
@@ -1,13 +1,19 @@
 //! A module containing all the operator expression AST nodes.
 
 pub use self::arith::*;
+pub use self::borrow::*;
 pub use self::comp::*;
+pub use self::deref::*;
 pub use self::lazy_bool::*;
 use self::macros::*;
 pub use self::neg::*;
 
+use crate::ast::Precedence;
+
 mod arith;
+mod borrow;
 mod comp;
+mod deref;
 mod lazy_bool;
 mod neg;
 
@@ -15,15 +21,29 @@ mod neg;
 trait BinOperator {
     /// Returns a string representation of the operator.
     fn as_str(&self) -> &'static str;
+
+    /// Returns the precedence of the operator.
+    fn precedence(&self) -> Precedence;
+
+    /// Returns whether the right-hand side of the operator is evaluated conditionally.
+    fn is_lazy(&self) -> bool {
+        false
+    }
 }
 
 /// Macros for defining binary operator AST nodes.
 mod macros {
+    /// Defines a binary operator AST node.
+    ///
+    /// The node's [`const_eval`](crate::ast::ExprASTNode::const_eval_in) calls `fold(&self, env)`,
+    /// which has to be implemented for each node separately.
     macro_rules! bin_op_ast_node {
         (
             $( #[$doc:meta] )*
             $name:ident {
                 operator: $operator:ty,
+                tag: $tag:expr,
+                lowered: $lowered:path,
                 label: $label:expr $(,)?
             }
         ) => {
@@ -34,9 +54,12 @@ mod macros {
                 use std::{fmt, iter};
 
                 use crate::ast::{
-                    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, PlaceExprASTNode,
-                    ValueExprASTNode,
+                    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ConstEnv, ConstValue, CoreExpr,
+                    ExprASTNode, PlaceExprASTNode, Precedence, ValueExprASTNode,
                 };
+                use crate::init::InitChecker;
+                use crate::pretty::SourcePrinter;
+                use crate::serialize::{Encoder, NodeTag};
                 use crate::token::Span;
 
                 #[derive(Debug)]
@@ -65,17 +88,58 @@ mod macros {
                             span,
                         }
                     }
+
+                    /// Returns the operator.
+                    pub fn operator(&self) -> $operator {
+                        self.operator
+                    }
+
+                    /// Returns the left-hand side of the operator.
+                    pub fn lhs(&self) -> &dyn ExprASTNode {
+                        self.lhs.as_ref()
+                    }
+
+                    /// Returns the right-hand side of the operator.
+                    pub fn rhs(&self) -> &dyn ExprASTNode {
+                        self.rhs.as_ref()
+                    }
                 }
 
                 impl ASTNode for $name {
                     ast_defaults!();
 
-                    fn children(&self) -> Option<ASTChildIterator> {
+                    fn children(&self) -> Option<ASTChildIterator<'_>> {
                         let lhs = iter::once(self.lhs.as_ast());
                         let rhs = iter::once(self.rhs.as_ast());
                         let iter = lhs.chain(rhs);
                         Some(Box::new(iter))
                     }
+
+                    fn check_init(&self, checker: &mut InitChecker) {
+                        self.lhs.check_init(checker);
+                        let state = self.operator.is_lazy().then(|| checker.snapshot());
+                        self.rhs.check_init(checker);
+                        if let Some(state) = state {
+                            checker.restore(state);
+                        }
+                    }
+
+                    fn print_source(&self, printer: &mut SourcePrinter) {
+                        printer.print_bin_op(
+                            self.lhs.as_ref(),
+                            self.operator.as_str(),
+                            self.rhs.as_ref(),
+                            self.operator.precedence(),
+                        );
+                    }
+
+                    fn encode(&self, encoder: &mut Encoder) {
+                        encoder.write_tag($tag);
+                        encoder.write_u8(self.operator as u8);
+                        encoder.write_node(self.lhs.as_ast());
+                        encoder.write_node(self.rhs.as_ast());
+                        encoder.write_span(self.span);
+                    }
                 }
 
                 impl ExprASTNode for $name {
@@ -90,6 +154,18 @@ mod macros {
                     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
                         None
                     }
+
+                    fn lower(&self) -> CoreExpr<'_> {
+                        $lowered(self)
+                    }
+
+                    fn const_eval_in(&self, env: &ConstEnv) -> Option<ConstValue> {
+                        self.fold(env)
+                    }
+
+                    fn precedence(&self) -> Precedence {
+                        self.operator.precedence()
+                    }
                 }
 
                 impl ValueExprASTNode for $name {}
@@ -0,0 +1,116 @@
+//! A module containing Borrow operator AST node implementation.
+
+use std::{fmt, iter};
+
+use crate::ast::{
+    ast_defaults, find_usage, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr,
+    DerefExprASTNode, ExprASTNode, PlaceExprASTNode, Precedence, Type, ValueExprASTNode,
+};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
+use crate::token::Span;
+
+/// An AST node representing a borrow operator expression, i.e. `&expr` or `&mut expr`.
+#[derive(Debug)]
+pub struct BorrowExprASTNode {
+    mutable: bool,
+    /// The expression can be [any kind of expression](ExprASTNode).
+    expr: Box<dyn ExprASTNode>,
+    span: Span,
+}
+
+impl BorrowExprASTNode {
+    /// Creates a new [`BorrowExprASTNode`] with the given mutability, expression and span.
+    pub fn new(mutable: bool, expr: Box<dyn ExprASTNode>, span: Span) -> BorrowExprASTNode {
+        BorrowExprASTNode {
+            mutable,
+            expr,
+            span,
+        }
+    }
+
+    /// Returns whether the borrow is mutable (`&mut`).
+    pub fn is_mutable(&self) -> bool {
+        self.mutable
+    }
+
+    /// Returns the borrowed expression.
+    pub fn expr(&self) -> &dyn ExprASTNode {
+        self.expr.as_ref()
+    }
+}
+
+/// Returns the span of the first node in the given subtree which uses a reference (a borrow
+/// or a dereference expression, or a declaration with a reference type), if there is one.
+///
+/// The backends which cannot represent the references use it to reject them up front.
+pub fn find_reference(node: &dyn ASTNode) -> Option<Span> {
+    find_usage(node, &|node, declared| {
+        let any = node.as_any();
+        any.is::<BorrowExprASTNode>()
+            || any.is::<DerefExprASTNode>()
+            || matches!(declared, Some(Type::Ref { .. }))
+    })
+}
+
+impl ASTNode for BorrowExprASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        let iter = iter::once(self.expr.as_ast());
+        Some(Box::new(iter))
+    }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        let place = self.expr.try_as_place().and_then(|place| place.pattern());
+        match place {
+            Some(name) if self.mutable => checker.borrow_mut(&name, self.span),
+            _ => self.expr.check_init(checker),
+        }
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        let expr = self.expr.as_ref();
+        printer.write(if self.mutable { "&mut " } else { "&" });
+        printer.print_operand(expr, expr.precedence() < Precedence::Unary);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Borrow);
+        encoder.write_bool(self.mutable);
+        encoder.write_node(self.expr.as_ast());
+        encoder.write_span(self.span);
+    }
+}
+
+impl ExprASTNode for BorrowExprASTNode {
+    fn try_as_place(&self) -> Option<&dyn PlaceExprASTNode> {
+        None
+    }
+
+    fn try_as_value(&self) -> Option<&dyn ValueExprASTNode> {
+        Some(self)
+    }
+
+    fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
+        None
+    }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Borrow(self)
+    }
+
+    fn precedence(&self) -> Precedence {
+        Precedence::Unary
+    }
+}
+
+impl ValueExprASTNode for BorrowExprASTNode {}
+
+impl fmt::Display for BorrowExprASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operator = if self.mutable { "&mut" } else { "&" };
+        write!(f, "Operator (Borrow) {} `{}`", self.span, operator)
+    }
+}
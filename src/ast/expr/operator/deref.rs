@@ -0,0 +1,94 @@
+//! A module containing Dereference operator AST node implementation.
+
+use std::rc::Rc;
+use std::{fmt, iter};
+
+use crate::ast::{
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr, ExprASTNode,
+    PlaceExprASTNode, Precedence, ValueExprASTNode,
+};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
+use crate::token::Span;
+
+/// An AST node representing a dereference operator expression, i.e. `*expr`.
+///
+/// It is a [place expression](PlaceExprASTNode) referring to the referenced value,
+/// so it can be assigned to (e.g. `*r = 1`) or borrowed again.
+#[derive(Debug)]
+pub struct DerefExprASTNode {
+    /// The expression can be [any kind of expression](ExprASTNode).
+    expr: Box<dyn ExprASTNode>,
+    span: Span,
+}
+
+impl DerefExprASTNode {
+    /// Creates a new [`DerefExprASTNode`] with the given expression and span.
+    pub fn new(expr: Box<dyn ExprASTNode>, span: Span) -> DerefExprASTNode {
+        DerefExprASTNode { expr, span }
+    }
+
+    /// Returns the dereferenced expression.
+    pub fn expr(&self) -> &dyn ExprASTNode {
+        self.expr.as_ref()
+    }
+}
+
+impl ASTNode for DerefExprASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        let iter = iter::once(self.expr.as_ast());
+        Some(Box::new(iter))
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        let expr = self.expr.as_ref();
+        printer.write("*");
+        printer.print_operand(expr, expr.precedence() < Precedence::Unary);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Deref);
+        encoder.write_node(self.expr.as_ast());
+        encoder.write_span(self.span);
+    }
+}
+
+impl ExprASTNode for DerefExprASTNode {
+    fn try_as_place(&self) -> Option<&dyn PlaceExprASTNode> {
+        Some(self)
+    }
+
+    fn try_as_value(&self) -> Option<&dyn ValueExprASTNode> {
+        Some(self)
+    }
+
+    fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
+        Some(self)
+    }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Deref(self)
+    }
+
+    fn precedence(&self) -> Precedence {
+        Precedence::Unary
+    }
+}
+
+impl PlaceExprASTNode for DerefExprASTNode {}
+
+impl ValueExprASTNode for DerefExprASTNode {}
+
+impl AssigneeExprASTNode for DerefExprASTNode {
+    fn pattern(&self) -> Option<Rc<str>> {
+        None
+    }
+}
+
+impl fmt::Display for DerefExprASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Operator (Dereference) {}", self.span)
+    }
+}
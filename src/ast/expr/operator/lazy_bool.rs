@@ -1,10 +1,6 @@
 //! A module containing Lazy Boolean operator AST node implementation.
 
-use inkwell::values::{AnyValue, AnyValueEnum};
-
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::ast::{ConstEnv, ConstValue, Precedence};
 
 use super::{bin_op_ast_node, operator_display, BinOperator};
 
@@ -24,6 +20,17 @@ impl BinOperator for LazyBoolOperator {
             LazyBoolOperator::Or => "||",
         }
     }
+
+    fn precedence(&self) -> Precedence {
+        match self {
+            LazyBoolOperator::And => Precedence::LazyAnd,
+            LazyBoolOperator::Or => Precedence::LazyOr,
+        }
+    }
+
+    fn is_lazy(&self) -> bool {
+        true
+    }
 }
 
 operator_display!(LazyBoolOperator);
@@ -32,77 +39,21 @@ bin_op_ast_node! {
     /// An AST node representing a lazy boolean operator expression.
     LazyBoolExprASTNode {
         operator: LazyBoolOperator,
+        tag: NodeTag::LazyBool,
+        lowered: CoreExpr::LazyBool,
         label: "Lazy Boolean",
     }
 }
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LazyBoolExprASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        let no_bb_panic = || panic!("Builder not in a block");
-
-        let parent_fn = state
-            .get_current_function()
-            .unwrap_or_else(|| panic!("Expression outside of function"));
-
-        //#region Labels
-        let lhs_bb = state.context().append_basic_block(parent_fn, "lhs");
-        let rhs_bb = state.context().append_basic_block(parent_fn, "rhs");
-        let merge_bb = state.context().append_basic_block(parent_fn, "merge");
-        state
-            .builder()
-            .build_unconditional_branch(lhs_bb)
-            .map_err(CodeGenError::from)?;
-        //#endregion
-
-        //#region LHS
-        state.builder().position_at_end(lhs_bb);
-
-        let lhs = state.build_condition(
-            self.lhs.as_ref(),
-            match self.operator {
-                LazyBoolOperator::And => true,
-                LazyBoolOperator::Or => false,
-            },
-        )?;
-        state
-            .builder()
-            .build_conditional_branch(lhs, rhs_bb, merge_bb)
-            .map_err(CodeGenError::from)?;
-
-        // The block may have changed because LHS could have added its own blocks
-        let lhs_bb = state
-            .builder()
-            .get_insert_block()
-            .unwrap_or_else(no_bb_panic);
-        //#endregion
-
-        //#region RHS
-        state.builder().position_at_end(rhs_bb);
-
-        let rhs = state.build_bool(self.rhs.as_ref())?;
-        state
-            .builder()
-            .build_unconditional_branch(merge_bb)
-            .map_err(CodeGenError::from)?;
-
-        // The block may have changed because RHS could have added its own blocks
-        let rhs_bb = state
-            .builder()
-            .get_insert_block()
-            .unwrap_or_else(no_bb_panic);
-        //#endregion
-
-        //#region Merge
-        state.builder().position_at_end(merge_bb);
-
-        let ty = state.context().bool_type();
-        let phi = state
-            .builder()
-            .build_phi(ty, "lazybool")
-            .map_err(CodeGenError::from)?;
-        phi.add_incoming(&[(&lhs, lhs_bb), (&rhs, rhs_bb)]);
-        //#endregion
-
-        Ok(phi.as_any_value_enum())
+impl LazyBoolExprASTNode {
+    /// Evaluates the operator if both operands are boolean constants.
+    fn fold(&self, env: &ConstEnv) -> Option<ConstValue> {
+        let lhs = self.lhs.const_eval_in(env)?.as_bool()?;
+        let rhs = self.rhs.const_eval_in(env)?.as_bool()?;
+        let value = match self.operator {
+            LazyBoolOperator::And => lhs && rhs,
+            LazyBoolOperator::Or => lhs || rhs,
+        };
+        Some(ConstValue::Bool(value))
     }
 }
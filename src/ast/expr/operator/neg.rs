@@ -2,15 +2,12 @@
 
 use std::{fmt, iter};
 
-use inkwell::values::{AnyValue, AnyValueEnum};
-
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, PlaceExprASTNode,
-    Type, ValueExprASTNode,
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ConstEnv, ConstValue, CoreExpr,
+    ExprASTNode, LiteralASTNode, PlaceExprASTNode, Precedence, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 use super::{operator_display, BinOperator};
@@ -31,6 +28,10 @@ impl BinOperator for NegOperator {
             NegOperator::Not => "!",
         }
     }
+
+    fn precedence(&self) -> Precedence {
+        Precedence::Unary
+    }
 }
 
 operator_display!(NegOperator);
@@ -53,15 +54,47 @@ impl NegExprASTNode {
             span,
         }
     }
+
+    /// Returns the operator.
+    pub fn operator(&self) -> NegOperator {
+        self.operator
+    }
+
+    /// Returns the negated expression.
+    pub fn expr(&self) -> &dyn ExprASTNode {
+        self.expr.as_ref()
+    }
+
+    /// Returns the integer literal if this is its numerical negation (e.g. `-128i8`),
+    /// which has to be range-checked as a whole.
+    pub fn negated_int_literal(&self) -> Option<&LiteralASTNode<i128>> {
+        match self.operator {
+            NegOperator::Neg => self.expr.as_any().downcast_ref::<LiteralASTNode<i128>>(),
+            NegOperator::Not => None,
+        }
+    }
 }
 
 impl ASTNode for NegExprASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let iter = iter::once(self.expr.as_ast());
         Some(Box::new(iter))
     }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        let expr = self.expr.as_ref();
+        printer.write(self.operator.as_str());
+        printer.print_operand(expr, expr.precedence() < Precedence::Unary);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Neg);
+        encoder.write_u8(self.operator as u8);
+        encoder.write_node(self.expr.as_ast());
+        encoder.write_span(self.span);
+    }
 }
 
 impl ExprASTNode for NegExprASTNode {
@@ -76,50 +109,27 @@ impl ExprASTNode for NegExprASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
-
-impl ValueExprASTNode for NegExprASTNode {}
-
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for NegExprASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        //TODO Refactor to use traits
-        let expr = CodeGen::<AnyValueEnum>::code_gen(self.expr.as_ref(), state)?;
 
-        //TODO Split into HIR & MIR and run type checking on HIR->MIR
-        let expr_ty = Type::try_from_llvm_value(state.context(), expr, self.expr.span())?;
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Neg(self)
+    }
 
-        let builder = state.builder();
-        match self.operator {
-            NegOperator::Neg => match expr_ty {
-                Type::I32 => builder
-                    .build_int_neg(expr.into_int_value(), "neg")
-                    .map(|v| v.as_any_value_enum()),
-                Type::F64 => builder
-                    .build_float_neg(expr.into_float_value(), "neg")
-                    .map(|v| v.as_any_value_enum()),
-                _ => {
-                    return Err(CodeGenError::UnsupportedType {
-                        message: "Cannot perform numerical negation on non-numeric type".into(),
-                        span: self.span,
-                    });
-                }
-            },
-            NegOperator::Not => match expr_ty {
-                Type::Bool => builder
-                    .build_not(expr.into_int_value(), "not")
-                    .map(|v| v.as_any_value_enum()),
-                _ => {
-                    return Err(CodeGenError::UnsupportedType {
-                        message: "Cannot perform logical negation on non-boolean type".into(),
-                        span: self.span,
-                    });
-                }
-            },
+    fn const_eval_in(&self, env: &ConstEnv) -> Option<ConstValue> {
+        match (self.operator, self.expr.const_eval_in(env)?) {
+            (NegOperator::Neg, ConstValue::Int(i)) => i.checked_neg().map(ConstValue::Int),
+            (NegOperator::Neg, ConstValue::Float(x)) => Some(ConstValue::Float(-x)),
+            (NegOperator::Not, ConstValue::Bool(b)) => Some(ConstValue::Bool(!b)),
+            _ => None,
         }
-        .map_err(Into::<CodeGenError>::into)
+    }
+
+    fn precedence(&self) -> Precedence {
+        self.operator.precedence()
     }
 }
 
+impl ValueExprASTNode for NegExprASTNode {}
+
 impl fmt::Display for NegExprASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -3,16 +3,16 @@
 use std::fmt;
 use std::rc::Rc;
 
-use inkwell::values::AnyValueEnum;
-
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, PlaceExprASTNode,
-    ValueExprASTNode,
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ConstEnv, ConstValue, CoreExpr,
+    ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::resolve::Resolver;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
+use crate::unsafety::UnsafetyChecker;
 
 /// An AST node representing a path (i.e. a variable or item).
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -36,9 +36,31 @@ impl PathASTNode {
 impl ASTNode for PathASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         None
     }
+
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        resolver.reference(&self.path, self.span);
+    }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        checker.read(&self.path, self.span);
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write(&self.path);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Path);
+        encoder.write_str(&self.path);
+        encoder.write_span(self.span);
+    }
+
+    fn check_unsafety(&self, checker: &mut UnsafetyChecker) {
+        checker.use_path(self.span);
+    }
 }
 
 impl ExprASTNode for PathASTNode {
@@ -53,6 +75,14 @@ impl ExprASTNode for PathASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         Some(self)
     }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Path(self)
+    }
+
+    fn const_eval_in(&self, env: &ConstEnv) -> Option<ConstValue> {
+        env.get(&self.path)
+    }
 }
 
 impl PlaceExprASTNode for PathASTNode {}
@@ -65,19 +95,6 @@ impl AssigneeExprASTNode for PathASTNode {
     }
 }
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for PathASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        state
-            .symbol_table()
-            .get(self.path())
-            .ok_or_else(|| CodeGenError::MissingSymbol {
-                symbol: self.path().into(),
-                span: self.span,
-            })
-            .map(|s| s.value())
-    }
-}
-
 impl fmt::Display for PathASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Path {} \"{}\"", self.span, self.path)
@@ -0,0 +1,38 @@
+//! A module containing the precedence of expressions.
+
+/// The precedence of an expression, ordered from the loosest to the tightest binding.
+///
+/// It mirrors the order in which the parser handles the operators (see
+/// the [Rust reference](https://doc.rust-lang.org/reference/expressions.html#expression-precedence)).
+/// All the binary operators are left-associative.
+///
+/// See [`ExprASTNode::precedence`](crate::ast::ExprASTNode::precedence).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Precedence {
+    /// `return`
+    Return,
+    /// `=`
+    Assign,
+    /// `||`
+    LazyOr,
+    /// `&&`
+    LazyAnd,
+    /// `==`, `!=`, `<`, `>`, `<=`, `>=`
+    Comparison,
+    /// `|`
+    BitOr,
+    /// `^`
+    BitXor,
+    /// `&`
+    BitAnd,
+    /// `+`, `-`
+    Additive,
+    /// `*`, `/`, `%`
+    Multiplicative,
+    /// `as`
+    Cast,
+    /// Unary `-`, `!`, `&` and `*`
+    Unary,
+    /// Literals, paths, function calls, grouped expressions and expressions with a block.
+    Primary,
+}
@@ -2,15 +2,13 @@
 
 use std::{fmt, iter};
 
-use inkwell::values::{AnyValue, AnyValueEnum, BasicValue, BasicValueEnum};
-
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, PlaceExprASTNode,
-    ValueExprASTNode,
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr, ExprASTNode,
+    PlaceExprASTNode, Precedence, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing a return expression.
@@ -34,12 +32,17 @@ impl ReturnASTNode {
     pub fn empty(span: Span) -> ReturnASTNode {
         ReturnASTNode { value: None, span }
     }
+
+    /// Returns the returned value, if any.
+    pub fn value(&self) -> Option<&dyn ExprASTNode> {
+        self.value.as_deref()
+    }
 }
 
 impl ASTNode for ReturnASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         self.value
             .as_ref()
             .map(|v| v.as_ast())
@@ -47,6 +50,27 @@ impl ASTNode for ReturnASTNode {
             .map(Box::new)
             .map(|b| b as ASTChildIterator)
     }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        if let Some(value) = &self.value {
+            value.check_init(checker);
+        }
+        checker.diverge();
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("return");
+        if let Some(value) = &self.value {
+            printer.write(" ");
+            value.print_source(printer);
+        }
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Return);
+        encoder.write_optional(self.value.as_ref().map(|v| v.as_ast()));
+        encoder.write_span(self.span);
+    }
 }
 
 impl ExprASTNode for ReturnASTNode {
@@ -61,34 +85,18 @@ impl ExprASTNode for ReturnASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
 
-impl ValueExprASTNode for ReturnASTNode {}
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Return(self)
+    }
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for ReturnASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        let value = self
-            .value
-            .as_ref()
-            .map(|v| {
-                let v = CodeGen::<AnyValueEnum>::code_gen(v.as_ref(), state)?;
-                BasicValueEnum::try_from(v).map_err(|_| CodeGenError::InvalidLLVMValueType {
-                    message: "Return value must be a basic value".into(),
-                    span: self.span,
-                })
-            })
-            .transpose()?;
-
-        //TODO Type checking (probably on HIR->MIR conversion)
-        let value = value.as_ref().map(|v| v as &dyn BasicValue);
-        state
-            .builder()
-            .build_return(value)
-            .map(|v| v.as_any_value_enum())
-            .map_err(CodeGenError::from)
+    fn precedence(&self) -> Precedence {
+        Precedence::Return
     }
 }
 
+impl ValueExprASTNode for ReturnASTNode {}
+
 impl fmt::Display for ReturnASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Return {}", self.span)
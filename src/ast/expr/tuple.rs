@@ -0,0 +1,96 @@
+//! A module containing Tuple Expression AST node implementation.
+
+use std::fmt;
+
+use crate::ast::{
+    ast_defaults, find_usage, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr,
+    ExprASTNode, PlaceExprASTNode, TupleIndexASTNode, Type, ValueExprASTNode,
+};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
+use crate::token::Span;
+
+/// An AST node representing a tuple expression with at least one element, e.g. `(1, true)`
+/// or `(x,)` (the empty tuple is the [unit literal](crate::ast::LiteralASTNode)).
+#[derive(Debug)]
+pub struct TupleASTNode {
+    /// The elements can be [any kind of expression](ExprASTNode).
+    elements: Vec<Box<dyn ExprASTNode>>,
+    span: Span,
+}
+
+impl TupleASTNode {
+    /// Creates a new `TupleASTNode` with the given elements and span.
+    pub fn new(elements: Vec<Box<dyn ExprASTNode>>, span: Span) -> TupleASTNode {
+        TupleASTNode { elements, span }
+    }
+
+    /// Returns the elements of the tuple.
+    pub fn elements(&self) -> &[Box<dyn ExprASTNode>] {
+        &self.elements
+    }
+}
+
+/// Returns the span of the first node in the given subtree which uses a tuple (a tuple
+/// or a tuple index expression, or a declaration with a tuple type), if there is one.
+///
+/// The backends which cannot represent the tuples use it to reject them up front.
+pub fn find_tuple(node: &dyn ASTNode) -> Option<Span> {
+    find_usage(node, &|node, declared| {
+        let any = node.as_any();
+        any.is::<TupleASTNode>()
+            || any.is::<TupleIndexASTNode>()
+            || matches!(declared, Some(Type::Tuple(_)))
+    })
+}
+
+impl ASTNode for TupleASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        let iter = self.elements.iter().map(|element| element.as_ast());
+        Some(Box::new(iter))
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("(");
+        printer.print_list(self.elements.iter().map(|element| element.as_ast()));
+        // A single element without the trailing comma is a grouped expression
+        if self.elements.len() == 1 {
+            printer.write(",");
+        }
+        printer.write(")");
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Tuple);
+        encoder.write_list(self.elements.iter().map(|element| element.as_ast()));
+        encoder.write_span(self.span);
+    }
+}
+
+impl ExprASTNode for TupleASTNode {
+    fn try_as_place(&self) -> Option<&dyn PlaceExprASTNode> {
+        None
+    }
+
+    fn try_as_value(&self) -> Option<&dyn ValueExprASTNode> {
+        Some(self)
+    }
+
+    fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
+        None
+    }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Tuple(self)
+    }
+}
+
+impl ValueExprASTNode for TupleASTNode {}
+
+impl fmt::Display for TupleASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Tuple {}", self.span)
+    }
+}
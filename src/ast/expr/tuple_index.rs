@@ -0,0 +1,86 @@
+//! A module containing Tuple Index Expression AST node implementation.
+
+use std::{fmt, iter};
+
+use crate::ast::{
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr, ExprASTNode,
+    PlaceExprASTNode, Precedence, ValueExprASTNode,
+};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
+use crate::token::Span;
+
+/// An AST node representing an access to an element of a tuple, e.g. `t.0`.
+//TODO Make it a place expression, so that the elements of the tuple variables can be assigned to
+#[derive(Debug)]
+pub struct TupleIndexASTNode {
+    /// The tuple can be [any kind of expression](ExprASTNode).
+    tuple: Box<dyn ExprASTNode>,
+    index: u32,
+    span: Span,
+}
+
+impl TupleIndexASTNode {
+    /// Creates a new `TupleIndexASTNode` with the given tuple, index and span.
+    pub fn new(tuple: Box<dyn ExprASTNode>, index: u32, span: Span) -> TupleIndexASTNode {
+        TupleIndexASTNode { tuple, index, span }
+    }
+
+    /// Returns the indexed tuple.
+    pub fn tuple(&self) -> &dyn ExprASTNode {
+        self.tuple.as_ref()
+    }
+
+    /// Returns the index of the element.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl ASTNode for TupleIndexASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        let iter = iter::once(self.tuple.as_ast());
+        Some(Box::new(iter))
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        let tuple = self.tuple.as_ref();
+        printer.print_operand(tuple, tuple.precedence() < Precedence::Primary);
+        printer.write(format_args!(".{}", self.index));
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::TupleIndex);
+        encoder.write_node(self.tuple.as_ast());
+        encoder.write_usize(self.index as usize);
+        encoder.write_span(self.span);
+    }
+}
+
+impl ExprASTNode for TupleIndexASTNode {
+    fn try_as_place(&self) -> Option<&dyn PlaceExprASTNode> {
+        None
+    }
+
+    fn try_as_value(&self) -> Option<&dyn ValueExprASTNode> {
+        Some(self)
+    }
+
+    fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
+        None
+    }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::TupleIndex(self)
+    }
+}
+
+impl ValueExprASTNode for TupleIndexASTNode {}
+
+impl fmt::Display for TupleIndexASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Tuple Index {} {}", self.span, self.index)
+    }
+}
@@ -3,15 +3,12 @@
 use std::fmt;
 use std::rc::Rc;
 
-use inkwell::values::AnyValueEnum;
-
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, PlaceExprASTNode,
-    ValueExprASTNode,
+    ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, CoreExpr, ExprASTNode,
+    PlaceExprASTNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing an underscore.
@@ -30,9 +27,18 @@ impl UnderscoreASTNode {
 impl ASTNode for UnderscoreASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         None
     }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("_");
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Underscore);
+        encoder.write_span(self.span);
+    }
 }
 
 impl ExprASTNode for UnderscoreASTNode {
@@ -47,6 +53,10 @@ impl ExprASTNode for UnderscoreASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         Some(self)
     }
+
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::Underscore(self)
+    }
 }
 
 impl AssigneeExprASTNode for UnderscoreASTNode {
@@ -55,13 +65,6 @@ impl AssigneeExprASTNode for UnderscoreASTNode {
     }
 }
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for UnderscoreASTNode {
-    fn code_gen(&self, _: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        // Underscore cannot be used as a value; it should only be used as a pattern.
-        Err(CodeGenError::UnderscoreUsedAsValue { span: self.span })
-    }
-}
-
 impl fmt::Display for UnderscoreASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Underscore {}", self.span)
@@ -2,16 +2,21 @@
 
 use std::fmt;
 
-use inkwell::values::AnyValueEnum;
-
 use crate::ast::{
-    ASTNode, AssigneeExprASTNode, BlockASTNode, ExprASTNode, PlaceExprASTNode, ValueExprASTNode,
+    ASTNode, AssigneeExprASTNode, BlockASTNode, ConstEnv, ConstValue, CoreExpr, ExprASTNode,
+    PlaceExprASTNode, ValueExprASTNode,
 };
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::resolve::Resolver;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
+use crate::unsafety::UnsafetyChecker;
 
 /// An AST node representing an unsafe block expression.
+///
+/// Like any block, it is an expression whose value (and type) is the value of the block
+/// inside `unsafe`, e.g. `let x: i32 = unsafe { f() };`.
 #[derive(Debug)]
 pub struct UnsafeBlockASTNode {
     block: Box<BlockASTNode>,
@@ -23,6 +28,11 @@ impl UnsafeBlockASTNode {
     pub fn new(block: Box<BlockASTNode>, span: Span) -> UnsafeBlockASTNode {
         UnsafeBlockASTNode { block, span }
     }
+
+    /// Returns the block inside `unsafe`.
+    pub fn block(&self) -> &BlockASTNode {
+        &self.block
+    }
 }
 
 impl ASTNode for UnsafeBlockASTNode {
@@ -30,9 +40,34 @@ impl ASTNode for UnsafeBlockASTNode {
         self.span
     }
 
-    fn children(&self) -> Option<crate::ast::ASTChildIterator> {
+    fn children(&self) -> Option<crate::ast::ASTChildIterator<'_>> {
         self.block.children()
     }
+
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        self.block.resolve_names(resolver);
+    }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        self.block.check_init(checker);
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("unsafe ");
+        self.block.print_source(printer);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::UnsafeBlock);
+        encoder.write_node(self.block.as_ref());
+        encoder.write_span(self.span);
+    }
+
+    fn check_unsafety(&self, checker: &mut UnsafetyChecker) {
+        checker.enter_unsafe(self.span);
+        self.block.check_unsafety(checker);
+        checker.exit_unsafe();
+    }
 }
 
 impl ExprASTNode for UnsafeBlockASTNode {
@@ -47,16 +82,18 @@ impl ExprASTNode for UnsafeBlockASTNode {
     fn try_as_assignee(&self) -> Option<&dyn AssigneeExprASTNode> {
         None
     }
-}
 
-impl ValueExprASTNode for UnsafeBlockASTNode {}
+    fn lower(&self) -> CoreExpr<'_> {
+        CoreExpr::UnsafeBlock(self)
+    }
 
-impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for UnsafeBlockASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
-        CodeGen::<AnyValueEnum>::code_gen(self.block.as_ref(), state)
+    fn const_eval_in(&self, env: &ConstEnv) -> Option<ConstValue> {
+        self.block.const_eval_in(env)
     }
 }
 
+impl ValueExprASTNode for UnsafeBlockASTNode {}
+
 impl fmt::Display for UnsafeBlockASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Unsafe Block {}", self.span)
@@ -4,18 +4,25 @@ use std::fmt;
 
 use debug_tree::TreeBuilder;
 
-use crate::ast::{ASTChildIterator, ASTNode};
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::ast::{ASTChildIterator, ASTNode, AsASTNode};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::resolve::Resolver;
+use crate::serialize::Encoder;
 use crate::token::Span;
+use crate::unsafety::UnsafetyChecker;
 
+pub use self::attr::*;
 pub use self::func::*;
+pub use self::import::*;
 pub use self::proto::*;
 pub use self::r#extern::*;
 pub use self::r#static::*;
 
+mod attr;
 mod r#extern;
 mod func;
+mod import;
 mod proto;
 mod r#static;
 
@@ -41,23 +48,176 @@ pub enum ItemASTNode {
     Static(Box<StaticASTNode>),
 }
 
+impl ItemASTNode {
+    /// Returns the span of the item.
+    pub fn span(&self) -> Span {
+        delegate_ast!(self, span,)
+    }
+
+    /// Returns a reference to the contained item as a `dyn ASTNode`
+    /// (unlike [`as_ast`](AsASTNode::as_ast), which returns this node itself).
+    pub fn inner_ast(&self) -> &dyn ASTNode {
+        match self {
+            ItemASTNode::Func(func) => func.as_ast(),
+            ItemASTNode::Extern(ext) => ext.as_ast(),
+            ItemASTNode::Static(stat) => stat.as_ast(),
+        }
+    }
+}
+
+/// The visibility of an item.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Visibility {
+    /// The item is visible only inside the crate (the default).
+    #[default]
+    Private,
+    /// The item is declared with `pub` and is exported from the crate.
+    Public,
+}
+
+impl Visibility {
+    /// Returns whether the visibility is [`Visibility::Public`].
+    pub fn is_public(&self) -> bool {
+        matches!(self, Visibility::Public)
+    }
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Visibility::Private => Ok(()),
+            Visibility::Public => write!(f, "Pub"),
+        }
+    }
+}
+
+/// The kind of an item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    /// A function declaration.
+    Func,
+    /// An extern block.
+    Extern,
+    /// A static item.
+    Static,
+}
+
+impl fmt::Display for ItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ItemKind::Func => write!(f, "function"),
+            ItemKind::Extern => write!(f, "extern block"),
+            ItemKind::Static => write!(f, "static"),
+        }
+    }
+}
+
+/// A trait for the metadata shared by all the items.
+///
+/// The span of the item is provided by [`ASTNode::span`].
+pub trait ItemMeta: ASTNode {
+    /// Returns the name of the item, if it has one (extern blocks don't).
+    fn name(&self) -> Option<&str>;
+
+    /// Returns the kind of the item.
+    fn kind(&self) -> ItemKind;
+
+    /// Returns the visibility of the item.
+    fn visibility(&self) -> Visibility;
+}
+
+impl ItemMeta for FuncASTNode {
+    fn name(&self) -> Option<&str> {
+        Some(self.proto().name())
+    }
+
+    fn kind(&self) -> ItemKind {
+        ItemKind::Func
+    }
+
+    fn visibility(&self) -> Visibility {
+        FuncASTNode::visibility(self)
+    }
+}
+
+impl ItemMeta for ExternASTNode {
+    fn name(&self) -> Option<&str> {
+        None
+    }
+
+    fn kind(&self) -> ItemKind {
+        ItemKind::Extern
+    }
+
+    fn visibility(&self) -> Visibility {
+        // Extern blocks cannot be declared with `pub`
+        Visibility::Private
+    }
+}
+
+impl ItemMeta for StaticASTNode {
+    fn name(&self) -> Option<&str> {
+        Some(StaticASTNode::name(self))
+    }
+
+    fn kind(&self) -> ItemKind {
+        ItemKind::Static
+    }
+
+    fn visibility(&self) -> Visibility {
+        StaticASTNode::visibility(self)
+    }
+}
+
+impl ItemMeta for ItemASTNode {
+    fn name(&self) -> Option<&str> {
+        match self {
+            ItemASTNode::Func(func) => ItemMeta::name(func.as_ref()),
+            ItemASTNode::Extern(ext) => ItemMeta::name(ext.as_ref()),
+            ItemASTNode::Static(stat) => ItemMeta::name(stat.as_ref()),
+        }
+    }
+
+    fn kind(&self) -> ItemKind {
+        delegate_ast!(&self, kind,)
+    }
+
+    fn visibility(&self) -> Visibility {
+        delegate_ast!(&self, visibility,)
+    }
+}
+
 impl ASTNode for ItemASTNode {
     fn span(&self) -> Span {
-        delegate_ast!(&self, span,)
+        ItemASTNode::span(self)
     }
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         delegate_ast!(&self, children,)
     }
 
     fn add_to_tree_string(&self, builder: &mut TreeBuilder) {
         delegate_ast!(&self, add_to_tree_string, builder)
     }
-}
 
-impl<'ctx> CodeGen<'ctx, ()> for ItemASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
-        delegate_ast!(self, code_gen, state)
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        delegate_ast!(&self, resolve_names, resolver)
+    }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        delegate_ast!(&self, check_init, checker)
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        delegate_ast!(&self, print_source, printer)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        delegate_ast!(&self, encode, encoder)
+    }
+
+    fn check_unsafety(&self, checker: &mut UnsafetyChecker) {
+        delegate_ast!(&self, check_unsafety, checker)
     }
 }
 
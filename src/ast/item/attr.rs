@@ -0,0 +1,84 @@
+//! A module containing the attributes of items.
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::token::Span;
+
+/// An outer attribute of an item, i.e. `#[name]` or `#[name(arg, ...)]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute {
+    name: Rc<str>,
+    args: Vec<AttributeArg>,
+    span: Span,
+}
+
+impl Attribute {
+    /// The names of the attributes supported by the compiler.
+    pub const SUPPORTED: [&'static str; 2] = [Attribute::UNROLL, Attribute::TEST];
+
+    /// The name of the loop unrolling hint, i.e. `#[unroll(N)]`.
+    pub const UNROLL: &'static str = "unroll";
+
+    /// The name of the marker of the functions run by the [test runner](crate::harness),
+    /// i.e. `#[test]`.
+    pub const TEST: &'static str = "test";
+
+    /// Creates a new `Attribute` with the given name, arguments and span.
+    pub fn new(name: Rc<str>, args: Vec<AttributeArg>, span: Span) -> Attribute {
+        Attribute { name, args, span }
+    }
+
+    /// Returns the name of the attribute.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the arguments of the attribute.
+    pub fn args(&self) -> &[AttributeArg] {
+        &self.args
+    }
+
+    /// Returns the span of the attribute.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns whether the attribute is [supported](Attribute::SUPPORTED) by the compiler.
+    pub fn is_supported(&self) -> bool {
+        Attribute::SUPPORTED.contains(&self.name())
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#[{}", self.name)?;
+        if !self.args.is_empty() {
+            let args = self
+                .args
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>();
+            write!(f, "({})", args.join(", "))?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// An argument of an [`Attribute`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeArg {
+    /// An integer literal.
+    Int(i128),
+    /// An identifier.
+    Ident(Rc<str>),
+}
+
+impl fmt::Display for AttributeArg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttributeArg::Int(value) => write!(f, "{}", value),
+            AttributeArg::Ident(ident) => write!(f, "{}", ident),
+        }
+    }
+}
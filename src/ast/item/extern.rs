@@ -1,19 +1,19 @@
 //! A module containing the Extern Block AST node implementation.
 
 use std::fmt;
-use std::rc::Rc;
 
+use crate::abi::Abi;
 use crate::ast::{
     ast_defaults, ASTChildIterator, ASTNode, AsASTNode, FuncProtoASTNode, StaticASTNode,
 };
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing an extern block.
 #[derive(Debug)]
 pub struct ExternASTNode {
-    abi: Rc<str>,
+    abi: Abi,
     items: Vec<ExternItem>,
     span: Span,
 }
@@ -29,13 +29,26 @@ pub enum ExternItem {
 
 impl ExternASTNode {
     /// Creates a new `ExternASTNode` with the given ABI, items and span.
-    pub fn new(abi: Rc<str>, items: Vec<ExternItem>, span: Span) -> ExternASTNode {
+    pub fn new(abi: Abi, items: Vec<ExternItem>, span: Span) -> ExternASTNode {
         ExternASTNode { abi, items, span }
     }
 
+    /// Creates a new `ExternASTNode` with the single function declared
+    /// in the qualifier form, i.e. `extern "C" fn f();`.
+    pub fn new_qualified(proto: FuncProtoASTNode, abi: Abi, span: Span) -> ExternASTNode {
+        let proto = proto.with_abi(abi);
+        ExternASTNode::new(abi, vec![ExternItem::Func(Box::new(proto))], span)
+    }
+
+    /// Returns whether the block is a single function declared in the qualifier form
+    /// (see [`ExternASTNode::new_qualified`]).
+    pub fn is_qualified(&self) -> bool {
+        matches!(self.items.as_slice(), [ExternItem::Func(func)] if func.abi().is_some())
+    }
+
     /// Returns the used ABI.
-    pub fn abi(&self) -> &str {
-        &self.abi
+    pub fn abi(&self) -> Abi {
+        self.abi
     }
 
     /// Returns the items in the extern block.
@@ -47,15 +60,40 @@ impl ExternASTNode {
 impl ASTNode for ExternASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let iter = self.items.iter().map(|i| i.as_ast());
         Some(Box::new(iter))
     }
-}
 
-impl<'ctx> CodeGen<'ctx, ()> for ExternASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
-        todo!()
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        if self.is_qualified() {
+            // The prototype prints its ABI itself
+            self.items[0].as_ast().print_source(printer);
+            printer.write(";");
+            return;
+        }
+        printer.write(format_args!("extern \"{}\" {{", self.abi));
+        printer.indent();
+        for item in &self.items {
+            printer.new_line();
+            match item {
+                ExternItem::Func(func) => {
+                    func.print_source(printer);
+                    printer.write(";");
+                }
+                ExternItem::Static(stat) => stat.print_source(printer),
+            }
+        }
+        printer.dedent();
+        printer.new_line();
+        printer.write("}");
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Extern);
+        encoder.write_str(self.abi.name());
+        encoder.write_list(self.items.iter().map(|item| item.as_ast()));
+        encoder.write_span(self.span);
     }
 }
 
@@ -67,6 +105,30 @@ impl ExternItem {
             ExternItem::Static(stat) => stat.as_ast(),
         }
     }
+
+    /// Returns the span of the item.
+    pub fn span(&self) -> Span {
+        match self {
+            ExternItem::Func(func) => func.span(),
+            ExternItem::Static(stat) => stat.span(),
+        }
+    }
+
+    /// Returns the name of the item.
+    pub fn name(&self) -> &str {
+        match self {
+            ExternItem::Func(func) => func.name(),
+            ExternItem::Static(stat) => stat.name(),
+        }
+    }
+
+    /// Returns the span of the name of the item.
+    pub fn name_span(&self) -> Span {
+        match self {
+            ExternItem::Func(func) => func.name_span(),
+            ExternItem::Static(stat) => stat.name_span(),
+        }
+    }
 }
 
 impl fmt::Display for ExternASTNode {
@@ -3,14 +3,15 @@
 use std::{fmt, iter};
 
 use debug_tree::TreeBuilder;
-use inkwell::values::{AnyValue, AnyValueEnum};
 
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, AsASTNode, BlockASTNode, FuncProtoASTNode,
+    ast_defaults, ASTChildIterator, ASTNode, AsASTNode, Attribute, BlockASTNode, FuncProtoASTNode,
+    Visibility,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::resolve::Resolver;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node for a function declaration.
@@ -18,29 +19,72 @@ use crate::token::Span;
 pub struct FuncASTNode {
     proto: FuncProtoASTNode,
     body: Box<BlockASTNode>,
+    visibility: Visibility,
+    attributes: Vec<Attribute>,
     span: Span,
 }
 
 impl FuncASTNode {
-    /// Creates a new `FuncASTNode` with the given prototype, body and span.
-    pub fn new(proto: FuncProtoASTNode, body: BlockASTNode, span: Span) -> FuncASTNode {
+    /// The name of the function that is the entry point of the program.
+    pub const ENTRY_POINT: &'static str = "main";
+
+    /// Creates a new `FuncASTNode` with the given prototype, body, visibility and span.
+    pub fn new(
+        proto: FuncProtoASTNode,
+        body: BlockASTNode,
+        visibility: Visibility,
+        span: Span,
+    ) -> FuncASTNode {
         FuncASTNode {
             proto,
             body: Box::new(body),
+            visibility,
+            attributes: Vec::new(),
             span,
         }
     }
 
+    /// Sets the attributes of the function.
+    pub fn with_attributes(self, attributes: Vec<Attribute>) -> FuncASTNode {
+        FuncASTNode { attributes, ..self }
+    }
+
     /// Returns the prototype of the function.
     pub fn proto(&self) -> &FuncProtoASTNode {
         &self.proto
     }
+
+    /// Returns the body of the function.
+    pub fn body(&self) -> &BlockASTNode {
+        &self.body
+    }
+
+    /// Returns the visibility of the function.
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    /// Returns the attributes of the function.
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    /// Returns the attribute of the function with the given name, if any.
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|attr| attr.name() == name)
+    }
+
+    /// Returns whether the function is exported from the generated module,
+    /// i.e. it is either public or the [entry point](FuncASTNode::ENTRY_POINT).
+    pub fn is_exported(&self) -> bool {
+        self.visibility.is_public() || self.proto.name() == FuncASTNode::ENTRY_POINT
+    }
 }
 
 impl ASTNode for FuncASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let proto = iter::once(self.proto.as_ast());
         let body = iter::once(self.body.as_ast());
         let iter = proto.chain(body);
@@ -60,60 +104,56 @@ impl ASTNode for FuncASTNode {
         }
         branch.release();
     }
-}
 
-impl<'ctx> CodeGen<'ctx, ()> for FuncASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
-        state.symbol_table().open_scope();
-
-        let fn_value = state.symbol_table().get(self.proto().name()).map_or_else(
-            || panic!(
-                "Function \"{}\" could not be found in the symbol table. Did you forget to run the Collection Phase?",
-                self.proto.name()
-            ),
-            |s| match s.value() {
-                AnyValueEnum::FunctionValue(v) => v,
-                _ => panic!(
-                    "Function \"{}\" is not a function value.",
-                    self.proto.name()
-                ),
-            },
-        );
-
-        //#region Parameters
-        let param_iter = fn_value.get_param_iter().zip(self.proto().get_param_iter());
-        for (llvm_param, param) in param_iter {
-            let param = param.assignee().map_err(|e| {
-                state.symbol_table().close_scope();
-                CodeGenError::from(e)
-            })?;
-
-            if let Some(param) = param.pattern() {
-                let name = param.clone();
-                llvm_param.set_name(&name);
-                state
-                    .symbol_table()
-                    .insert(name, llvm_param.as_any_value_enum());
-            }
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        resolver.open_scope();
+        for param in self.proto.get_param_iter() {
+            param.resolve_names(resolver);
+        }
+        self.body.resolve_names(resolver);
+        resolver.close_scope();
+    }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        let state = checker.snapshot();
+        checker.open_scope();
+        for param in self.proto.get_param_iter() {
+            param.check_init(checker);
         }
-        //#endregion
-
-        //#region Body
-        let body = state.context().append_basic_block(fn_value, "start");
-        state.builder().position_at_end(body);
-        self.body.code_gen(state).map_err(|e| {
-            state.symbol_table().close_scope();
-            e
-        })?;
-        //#endregion
-
-        state.symbol_table().close_scope();
-        Ok(())
+        self.body.check_init(checker);
+        checker.close_scope();
+        checker.restore(state);
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        for attr in &self.attributes {
+            printer.write(attr);
+            printer.new_line();
+        }
+        if self.visibility.is_public() {
+            printer.write("pub ");
+        }
+        self.proto.print_source(printer);
+        printer.write(" ");
+        self.body.print_source(printer);
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Func);
+        encoder.write_node(&self.proto);
+        encoder.write_node(self.body.as_ast());
+        encoder.write_visibility(self.visibility);
+        encoder.write_attributes(&self.attributes);
+        encoder.write_span(self.span);
     }
 }
 
 impl fmt::Display for FuncASTNode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Function \"{}\" {}", self.proto.name(), self.span)
+        write!(f, "Function ")?;
+        if self.visibility.is_public() {
+            write!(f, "{} ", self.visibility)?;
+        }
+        write!(f, "\"{}\" {}", self.proto.name(), self.span)
     }
 }
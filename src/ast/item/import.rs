@@ -0,0 +1,92 @@
+//! A module containing the Import AST node implementation.
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::{ast_defaults, ASTChildIterator, ASTNode};
+use crate::pretty::SourcePrinter;
+use crate::resolve::{DeclKind, Resolver};
+use crate::serialize::{Encoder, NodeTag};
+use crate::token::Span;
+
+/// An AST node representing an import of a function from another μRust file,
+/// i.e. `use module::name;`.
+///
+/// The `module` is the name of the file (without the `.mrs` extension) in the same directory,
+/// and the `name` has to be a function exported from it (see [`CrateASTNode::exports`]).
+///
+/// [`CrateASTNode::exports`]: crate::ast::CrateASTNode::exports
+#[derive(Debug)]
+pub struct ImportASTNode {
+    module: Rc<str>,
+    name: Rc<str>,
+    name_span: Span,
+    span: Span,
+}
+
+impl ImportASTNode {
+    /// Creates a new `ImportASTNode` with the given module, name (and its span) and span.
+    pub fn new(module: Rc<str>, name: Rc<str>, name_span: Span, span: Span) -> ImportASTNode {
+        ImportASTNode {
+            module,
+            name,
+            name_span,
+            span,
+        }
+    }
+
+    /// Returns the name of the module the item is imported from.
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    /// Returns a shared strong reference to the name of the module the item is imported from.
+    pub fn module_owned(&self) -> Rc<str> {
+        self.module.clone()
+    }
+
+    /// Returns the name of the imported item.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns a shared strong reference to the name of the imported item.
+    pub fn name_owned(&self) -> Rc<str> {
+        self.name.clone()
+    }
+
+    /// Returns the span of the name of the imported item.
+    pub fn name_span(&self) -> Span {
+        self.name_span
+    }
+}
+
+impl ASTNode for ImportASTNode {
+    ast_defaults!();
+
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
+        None
+    }
+
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        resolver.declare(self.name.clone(), DeclKind::Function, self.name_span);
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write(format_args!("use {}::{};", self.module, self.name));
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Import);
+        encoder.write_str(&self.module);
+        encoder.write_str(&self.name);
+        encoder.write_span(self.name_span);
+        encoder.write_span(self.span);
+    }
+}
+
+impl fmt::Display for ImportASTNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Import {} \"{}::{}\"", self.span, self.module, self.name)
+    }
+}
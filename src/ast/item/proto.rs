@@ -3,11 +3,12 @@
 use std::fmt;
 use std::rc::Rc;
 
-use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType};
-
+use crate::abi::Abi;
 use crate::ast::{ast_defaults, ASTChildIterator, ASTNode, AsASTNode, Type, TypeASTMetaNode};
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::resolve::Resolver;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 pub use self::param::*;
@@ -18,27 +19,41 @@ mod param;
 #[derive(Debug)]
 pub struct FuncProtoASTNode {
     name: Rc<str>,
+    name_span: Span,
     params: Vec<ParamASTNode>,
     return_type: TypeASTMetaNode,
+    abi: Option<Abi>,
     span: Span,
 }
 
 impl FuncProtoASTNode {
-    /// Creates a new `FuncProtoASTNode` with the given name, parameters, return type and span.
+    /// Creates a new `FuncProtoASTNode` with the given name (and its span), parameters,
+    /// return type and span.
     pub fn new(
         name: Rc<str>,
+        name_span: Span,
         params: Vec<ParamASTNode>,
         return_type: TypeASTMetaNode,
         span: Span,
     ) -> FuncProtoASTNode {
         FuncProtoASTNode {
             name,
+            name_span,
             params,
             return_type,
+            abi: None,
             span,
         }
     }
 
+    /// Sets the ABI the function is qualified with, i.e. `extern "C" fn`.
+    pub fn with_abi(self, abi: Abi) -> FuncProtoASTNode {
+        FuncProtoASTNode {
+            abi: Some(abi),
+            ..self
+        }
+    }
+
     /// Returns the name.
     pub fn name(&self) -> &str {
         &self.name
@@ -49,6 +64,19 @@ impl FuncProtoASTNode {
         self.name.clone()
     }
 
+    /// Returns the span of the name.
+    pub fn name_span(&self) -> Span {
+        self.name_span
+    }
+
+    /// Returns the ABI the function is qualified with, if any.
+    ///
+    /// The functions declared in an extern block are not qualified,
+    /// as they use the [ABI of the block](crate::ast::ExternASTNode::abi).
+    pub fn abi(&self) -> Option<Abi> {
+        self.abi
+    }
+
     /// Returns the return type meta-node.
     pub fn return_type(&self) -> &TypeASTMetaNode {
         &self.return_type
@@ -58,36 +86,63 @@ impl FuncProtoASTNode {
     pub fn get_param_iter(&self) -> impl Iterator<Item = &ParamASTNode> {
         self.params.iter()
     }
+
+    /// Returns the number of the parameters.
+    pub fn param_count(&self) -> usize {
+        self.params.len()
+    }
+
+    /// Returns an iterator over the types of the parameters.
+    pub fn param_types(&self) -> impl Iterator<Item = Type> + '_ {
+        self.params.iter().map(|p| p.ty().ty())
+    }
 }
 
 impl ASTNode for FuncProtoASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let iter = self.params.iter().map(|p| p.as_ast());
         Some(Box::new(iter))
     }
-}
 
-impl<'ctx> CodeGen<'ctx, ()> for FuncProtoASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
-        CodeGen::<FunctionType>::code_gen(self, state).map(|_| ())
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        // The parameters of a prototype without a body are visible only in the prototype
+        resolver.open_scope();
+        for param in &self.params {
+            param.resolve_names(resolver);
+        }
+        resolver.close_scope();
+    }
+
+    fn check_init(&self, _checker: &mut InitChecker) {
+        // The parameters of a prototype without a body are never read
     }
-}
 
-impl<'ctx> CodeGen<'ctx, FunctionType<'ctx>> for FuncProtoASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<FunctionType<'ctx>> {
-        let params = self
-            .params
-            .iter()
-            .map(|p| CodeGen::<BasicMetadataTypeEnum>::code_gen(p, state))
-            .collect::<codegen::Result<Vec<_>>>()?;
-
-        Ok(match self.return_type.ty() {
-            Type::Unit => state.context().void_type().fn_type(&params, false),
-            _ => CodeGen::<BasicTypeEnum>::code_gen(&self.return_type, state)?
-                .fn_type(&params, false),
-        })
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        if let Some(abi) = self.abi {
+            printer.write(format_args!("extern \"{}\" ", abi));
+        }
+        printer.write(format_args!("fn {}(", self.name));
+        printer.print_list(self.params.iter().map(|p| p.as_ast()));
+        printer.write(")");
+        // The unit return type is implicit
+        if self.return_type.ty() != Type::Unit {
+            printer.write(format_args!(" -> {}", self.return_type));
+        }
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Proto);
+        encoder.write_str(&self.name);
+        encoder.write_span(self.name_span);
+        encoder.write_list(self.params.iter().map(|p| p.as_ast()));
+        encoder.write_type(&self.return_type);
+        encoder.write_bool(self.abi.is_some());
+        if let Some(abi) = self.abi {
+            encoder.write_str(abi.name());
+        }
+        encoder.write_span(self.span);
     }
 }
 
@@ -2,14 +2,14 @@
 
 use std::{fmt, iter};
 
-use inkwell::types::BasicMetadataTypeEnum;
-
 use crate::ast::error::SemanticError;
 use crate::ast::{
     ast_defaults, ASTChildIterator, ASTNode, AssigneeExprASTNode, ExprASTNode, TypeASTMetaNode,
 };
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::resolve::{DeclKind, Resolver};
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing a function parameter.
@@ -18,22 +18,26 @@ pub struct ParamASTNode {
     /// The assignee has to be an [assignee expression](AssigneeExprASTNode).
     assignee: Box<dyn ExprASTNode>,
     ty: TypeASTMetaNode,
-    mutable: bool,
+    /// The span of the `mut` keyword, if the parameter is mutable.
+    mut_span: Option<Span>,
     span: Span,
 }
 
 impl ParamASTNode {
-    /// Creates a new `ParamASTNode` with the given assignee, type, mutability and span.
+    /// Creates a new `ParamASTNode` with the given assignee, type, span of the `mut` keyword
+    /// (if the parameter is mutable) and span.
+    ///
+    /// The span should cover the whole parameter, i.e. `mut ident: Type`.
     pub fn new(
         assignee: Box<dyn ExprASTNode>,
         ty: TypeASTMetaNode,
-        mutable: bool,
+        mut_span: Option<Span>,
         span: Span,
     ) -> ParamASTNode {
         ParamASTNode {
             assignee,
             ty,
-            mutable,
+            mut_span,
             span,
         }
     }
@@ -53,44 +57,67 @@ impl ParamASTNode {
             })
     }
 
-    /// Returns the type.
-    pub fn ty(&self) -> TypeASTMetaNode {
-        self.ty
+    /// Returns the span of the assignee, i.e. the name of the parameter.
+    pub fn name_span(&self) -> Span {
+        self.assignee.span()
+    }
+
+    /// Returns the type (together with the span of the type annotation).
+    pub fn ty(&self) -> &TypeASTMetaNode {
+        &self.ty
     }
 
     /// Returns whether the parameter is mutable.
     pub fn is_mutable(&self) -> bool {
-        self.mutable
+        self.mut_span.is_some()
+    }
+
+    /// Returns the span of the `mut` keyword, if the parameter is mutable.
+    pub fn mut_span(&self) -> Option<Span> {
+        self.mut_span
     }
 }
 
 impl ASTNode for ParamASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let iter = iter::once(self.assignee.as_ast());
         Some(Box::new(iter))
     }
-}
 
-impl<'ctx> CodeGen<'ctx, ()> for ParamASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
-        CodeGen::<BasicMetadataTypeEnum>::code_gen(self, state).map(|_| ())
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        if let Ok(Some(name)) = self.assignee().map(|a| a.pattern()) {
+            resolver.declare(name, DeclKind::Param, self.assignee.span());
+        }
+    }
+
+    fn check_init(&self, checker: &mut InitChecker) {
+        if let Ok(Some(name)) = self.assignee().map(|a| a.pattern()) {
+            checker.declare(name, self.assignee.span(), self.mut_span, true);
+        }
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        if self.is_mutable() {
+            printer.write("mut ");
+        }
+        self.assignee.print_source(printer);
+        printer.write(format_args!(": {}", self.ty));
     }
-}
 
-impl<'ctx> CodeGen<'ctx, BasicMetadataTypeEnum<'ctx>> for ParamASTNode {
-    fn code_gen(
-        &self,
-        state: &mut CodeGenState<'ctx>,
-    ) -> codegen::Result<BasicMetadataTypeEnum<'ctx>> {
-        CodeGen::<BasicMetadataTypeEnum>::code_gen(&self.ty, state)
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Param);
+        encoder.write_node(self.assignee.as_ast());
+        encoder.write_type(&self.ty);
+        encoder.write_optional_span(self.mut_span);
+        encoder.write_span(self.span);
     }
 }
 
 impl fmt::Display for ParamASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mutability = if self.is_mutable() { "Mut" } else { "" };
-        write!(f, "Param {} {}", mutability, self.span)
+        let mutability = if self.is_mutable() { "Mut " } else { "" };
+        write!(f, "Param {}{} `{}`", mutability, self.span, self.ty)
     }
 }
@@ -3,54 +3,74 @@
 use std::fmt;
 use std::rc::Rc;
 
-use crate::ast::{ast_defaults, ASTChildIterator, ASTNode, ExprASTNode, TypeASTMetaNode};
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::ast::{
+    ast_defaults, ASTChildIterator, ASTNode, ExprASTNode, TypeASTMetaNode, Visibility,
+};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing a static item.
 #[derive(Debug)]
 pub struct StaticASTNode {
     name: Rc<str>,
+    name_span: Span,
     /// The value has to be a [value expression](crate::ast::ValueExprASTNode).
     value: Option<Box<dyn ExprASTNode>>,
     ty: TypeASTMetaNode,
-    mutable: bool,
+    /// The span of the `mut` keyword, if the item is mutable.
+    mut_span: Option<Span>,
+    visibility: Visibility,
     span: Span,
 }
 
 impl StaticASTNode {
-    /// Creates a new `StaticASTNode` with the given name, type, mutability and span.
-    pub fn new(name: Rc<str>, ty: TypeASTMetaNode, mutable: bool, span: Span) -> StaticASTNode {
+    /// Creates a new `StaticASTNode` with the given name (and its span), type,
+    /// span of the `mut` keyword (if the item is mutable), visibility and span.
+    pub fn new(
+        name: Rc<str>,
+        name_span: Span,
+        ty: TypeASTMetaNode,
+        mut_span: Option<Span>,
+        visibility: Visibility,
+        span: Span,
+    ) -> StaticASTNode {
         StaticASTNode {
             name,
+            name_span,
             value: None,
             ty,
-            mutable,
+            mut_span,
+            visibility,
             span,
         }
     }
 
-    /// Creates a new `StaticASTNode` with the given name, assigned value, type, mutability and span.
+    /// Creates a new `StaticASTNode` with the given name (and its span), assigned value, type,
+    /// span of the `mut` keyword (if the item is mutable), visibility and span.
     pub fn new_with_assignment(
         name: Rc<str>,
+        name_span: Span,
         value: Box<dyn ExprASTNode>,
         ty: TypeASTMetaNode,
-        mutable: bool,
+        mut_span: Option<Span>,
+        visibility: Visibility,
         span: Span,
     ) -> StaticASTNode {
         StaticASTNode {
             name,
+            name_span,
             value: Some(value),
             ty,
-            mutable,
+            mut_span,
+            visibility,
             span,
         }
     }
 
     /// Returns the type.
-    pub fn ty(&self) -> TypeASTMetaNode {
-        self.ty
+    pub fn ty(&self) -> &TypeASTMetaNode {
+        &self.ty
     }
 
     /// Returns the name.
@@ -63,9 +83,29 @@ impl StaticASTNode {
         self.name.clone()
     }
 
+    /// Returns the span of the name.
+    pub fn name_span(&self) -> Span {
+        self.name_span
+    }
+
     /// Returns whether the item is mutable.
     pub fn is_mutable(&self) -> bool {
-        self.mutable
+        self.mut_span.is_some()
+    }
+
+    /// Returns the span of the `mut` keyword, if the item is mutable.
+    pub fn mut_span(&self) -> Option<Span> {
+        self.mut_span
+    }
+
+    /// Returns the visibility of the item.
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    /// Returns the assigned value, if any.
+    pub fn value(&self) -> Option<&dyn ExprASTNode> {
+        self.value.as_deref()
     }
 
     /// Returns whether the item is initialized,
@@ -78,23 +118,48 @@ impl StaticASTNode {
 impl ASTNode for StaticASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let iter = self.value.iter().map(|v| v.as_ast());
         Some(Box::new(iter))
     }
-}
 
-impl<'ctx> CodeGen<'ctx, ()> for StaticASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
-        // Here we only generate the initializer, the declaration is done during Collection Phase.
-        todo!()
-        //TODO Add support for more complicated initializers (evaluated at compile time)
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        if self.visibility.is_public() {
+            printer.write("pub ");
+        }
+        printer.write("static ");
+        if self.is_mutable() {
+            printer.write("mut ");
+        }
+        printer.write(format_args!("{}: {}", self.name, self.ty));
+        if let Some(value) = &self.value {
+            printer.write(" = ");
+            value.print_source(printer);
+        }
+        printer.write(";");
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Static);
+        encoder.write_str(&self.name);
+        encoder.write_span(self.name_span);
+        encoder.write_optional(self.value.as_ref().map(|v| v.as_ast()));
+        encoder.write_type(&self.ty);
+        encoder.write_optional_span(self.mut_span);
+        encoder.write_visibility(self.visibility);
+        encoder.write_span(self.span);
     }
 }
 
 impl fmt::Display for StaticASTNode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mutability = if self.mutable { "Mut" } else { "" };
-        write!(f, "Static {} {} \"{}\"", mutability, self.span, self.name())
+        write!(f, "Static ")?;
+        if self.visibility.is_public() {
+            write!(f, "{} ", self.visibility)?;
+        }
+        if self.is_mutable() {
+            write!(f, "Mut ")?;
+        }
+        write!(f, "{} \"{}\"", self.span, self.name())
     }
 }
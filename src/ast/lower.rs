@@ -0,0 +1,83 @@
+//! A module containing the core constructs of the language, which the expressions and statements
+//! are [lowered](crate::ast::ExprASTNode::lower) to for the code generation backends.
+//!
+//! The backends don't depend on the concrete types of the nodes (and the nodes don't depend
+//! on the backends) -- they match on the lowered constructs instead, so every backend has to
+//! handle (or explicitly reject) every one of them. The syntactic sugar is lowered
+//! to its desugared form, so the backends support it for free.
+
+use std::rc::Rc;
+
+use crate::ast::{
+    ArithExprASTNode, AssignASTNode, BlockASTNode, BorrowExprASTNode, CompExprASTNode,
+    DerefExprASTNode, ExprStmtASTNode, FunCallASTNode, GroupedExprASTNode, IfASTNode,
+    InfLoopASTNode, LayoutOfASTNode, LazyBoolExprASTNode, LetASTNode, LiteralASTNode,
+    NegExprASTNode, PathASTNode, ReturnASTNode, TupleASTNode, TupleIndexASTNode, TypeCastASTNode,
+    UnderscoreASTNode, UnsafeBlockASTNode, WhileASTNode,
+};
+
+/// The core construct an [expression](crate::ast::ExprASTNode) is lowered to.
+#[derive(Debug, Clone, Copy)]
+pub enum CoreExpr<'a> {
+    /// An integer literal.
+    Int(&'a LiteralASTNode<i128>),
+    /// A floating-point literal.
+    Float(&'a LiteralASTNode<f64>),
+    /// A boolean literal.
+    Bool(&'a LiteralASTNode<bool>),
+    /// A string literal.
+    Str(&'a LiteralASTNode<Rc<str>>),
+    /// The unit literal.
+    Unit(&'a LiteralASTNode<()>),
+    /// A path to a variable, a static or a function.
+    Path(&'a PathASTNode),
+    /// An underscore expression (only valid as an assignee).
+    Underscore(&'a UnderscoreASTNode),
+    /// A parenthesized expression.
+    Grouped(&'a GroupedExprASTNode),
+    /// A block expression.
+    Block(&'a BlockASTNode),
+    /// An `unsafe` block expression.
+    UnsafeBlock(&'a UnsafeBlockASTNode),
+    /// A tuple expression.
+    Tuple(&'a TupleASTNode),
+    /// A tuple indexing expression.
+    TupleIndex(&'a TupleIndexASTNode),
+    /// A function call.
+    FunCall(&'a FunCallASTNode),
+    /// A query of the size or the alignment of a type.
+    LayoutOf(&'a LayoutOfASTNode),
+    /// An assignment.
+    Assign(&'a AssignASTNode),
+    /// An arithmetic or logical operator expression.
+    Arith(&'a ArithExprASTNode),
+    /// A comparison operator expression.
+    Comp(&'a CompExprASTNode),
+    /// A lazy boolean operator expression.
+    LazyBool(&'a LazyBoolExprASTNode),
+    /// A negation operator expression.
+    Neg(&'a NegExprASTNode),
+    /// A borrow expression.
+    Borrow(&'a BorrowExprASTNode),
+    /// A dereference expression.
+    Deref(&'a DerefExprASTNode),
+    /// A type cast expression.
+    Cast(&'a TypeCastASTNode),
+    /// An `if` expression.
+    If(&'a IfASTNode),
+    /// A `while` loop (a `do`-`while` loop is lowered to one).
+    While(&'a WhileASTNode),
+    /// An infinite loop.
+    InfLoop(&'a InfLoopASTNode),
+    /// A `return` expression.
+    Return(&'a ReturnASTNode),
+}
+
+/// The core construct a [statement](crate::ast::StatementASTNode) is lowered to.
+#[derive(Debug, Clone, Copy)]
+pub enum CoreStmt<'a> {
+    /// A `let` statement.
+    Let(&'a LetASTNode),
+    /// An expression statement.
+    Expr(&'a ExprStmtASTNode),
+}
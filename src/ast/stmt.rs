@@ -1,6 +1,6 @@
 //! A module containing all the statement-related AST nodes.
 
-use crate::ast::ASTNode;
+use crate::ast::{ASTNode, CoreStmt};
 
 pub use self::expr::*;
 pub use self::r#let::*;
@@ -9,4 +9,8 @@ mod expr;
 mod r#let;
 
 /// A trait for all statement-related AST nodes.
-pub trait StatementASTNode: ASTNode {}
+pub trait StatementASTNode: ASTNode {
+    /// Lowers the statement to the [core construct](CoreStmt) the code generation backends
+    /// generate the code for.
+    fn lower(&self) -> CoreStmt<'_>;
+}
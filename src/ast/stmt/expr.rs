@@ -2,9 +2,11 @@
 
 use std::{fmt, iter};
 
-use crate::ast::{ast_defaults, ASTChildIterator, ASTNode, ExprASTNode, StatementASTNode};
-use crate::codegen;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::ast::{
+    ast_defaults, ASTChildIterator, ASTNode, CoreStmt, ExprASTNode, StatementASTNode,
+};
+use crate::pretty::SourcePrinter;
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing an expression statement.
@@ -35,17 +37,26 @@ impl ExprStmtASTNode {
 impl ASTNode for ExprStmtASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let iter = iter::once(self.expr.as_ast());
         Some(Box::new(iter))
     }
-}
 
-impl StatementASTNode for ExprStmtASTNode {}
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        self.expr.print_source(printer);
+        printer.write(";");
+    }
+
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::ExprStmt);
+        encoder.write_node(self.expr.as_ast());
+        encoder.write_span(self.span);
+    }
+}
 
-impl<'ctx> CodeGen<'ctx, ()> for ExprStmtASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
-        self.expr.code_gen(state)
+impl StatementASTNode for ExprStmtASTNode {
+    fn lower(&self) -> CoreStmt<'_> {
+        CoreStmt::Expr(self)
     }
 }
 
@@ -3,16 +3,15 @@
 use std::{fmt, iter};
 
 use debug_tree::TreeBuilder;
-use inkwell::types::BasicTypeEnum;
-use inkwell::values::{AnyValue, AnyValueEnum, BasicValueEnum};
 
-use crate::ast::error::SemanticError;
 use crate::ast::{
-    ast_defaults, ASTChildIterator, ASTNode, ExprASTNode, StatementASTNode, Type, TypeASTMetaNode,
+    ast_defaults, ASTChildIterator, ASTNode, CoreStmt, ExprASTNode, StatementASTNode, Type,
+    TypeASTMetaNode,
 };
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
+use crate::init::InitChecker;
+use crate::pretty::SourcePrinter;
+use crate::resolve::{DeclKind, Resolver};
+use crate::serialize::{Encoder, NodeTag};
 use crate::token::Span;
 
 /// An AST node representing a let statement.
@@ -20,62 +19,101 @@ use crate::token::Span;
 pub struct LetASTNode {
     /// The declaration has to be an [assignee expression](crate::ast::AssigneeExprASTNode).
     decl: Box<dyn ExprASTNode>,
-    ty: TypeASTMetaNode,
+    /// The type is `None` if it is inferred from the value (see [`LetASTNode::new_inferred`]).
+    ty: Option<TypeASTMetaNode>,
     /// The value has to be a [value expression](crate::ast::ValueExprASTNode).
     value: Option<Box<dyn ExprASTNode>>,
-    mutable: bool,
+    /// The span of the `mut` keyword, if the variable is mutable.
+    mut_span: Option<Span>,
     span: Span,
 }
 
 impl LetASTNode {
-    /// Creates a new `LetASTNode` with the given declaration, type, mutability and span.
+    /// Creates a new `LetASTNode` with the given declaration, type, span of the `mut` keyword
+    /// (if the variable is mutable) and span.
     pub fn new(
         decl: Box<dyn ExprASTNode>,
         ty: TypeASTMetaNode,
-        mutable: bool,
+        mut_span: Option<Span>,
         span: Span,
     ) -> LetASTNode {
         LetASTNode {
             decl,
-            ty,
+            ty: Some(ty),
             value: None,
-            mutable,
+            mut_span,
             span,
         }
     }
 
-    /// Creates a new `LetASTNode` with the given declaration, type, assigned value, mutability and span.
+    /// Creates a new `LetASTNode` with the given declaration, type, assigned value,
+    /// span of the `mut` keyword (if the variable is mutable) and span.
     pub fn new_with_assignment(
         decl: Box<dyn ExprASTNode>,
         ty: TypeASTMetaNode,
         value: Box<dyn ExprASTNode>,
-        mutable: bool,
+        mut_span: Option<Span>,
         span: Span,
     ) -> LetASTNode {
         LetASTNode {
             decl,
-            ty,
+            ty: Some(ty),
             value: Some(value),
-            mutable,
+            mut_span,
             span,
         }
     }
 
-    /// Returns the type of the declaration
-    pub fn ty(&self) -> Type {
-        self.ty.ty()
+    /// Creates a new `LetASTNode` with the given declaration, assigned value (from which
+    /// the type is inferred), span of the `mut` keyword (if the variable is mutable) and span.
+    ///
+    /// The type inference is not supported in the source code yet, so these are only created
+    /// by desugaring (see [`ForASTNode`](crate::ast::ForASTNode)).
+    pub(crate) fn new_inferred(
+        decl: Box<dyn ExprASTNode>,
+        value: Box<dyn ExprASTNode>,
+        mut_span: Option<Span>,
+        span: Span,
+    ) -> LetASTNode {
+        LetASTNode {
+            decl,
+            ty: None,
+            value: Some(value),
+            mut_span,
+            span,
+        }
+    }
+
+    /// Returns the type of the declaration, or `None` if it is inferred from the value.
+    pub fn ty(&self) -> Option<Type> {
+        self.ty.as_ref().map(TypeASTMetaNode::ty)
     }
 
     /// Returns whether the variable is mutable.
     pub fn is_mutable(&self) -> bool {
-        self.mutable
+        self.mut_span.is_some()
+    }
+
+    /// Returns the span of the `mut` keyword, if the variable is mutable.
+    pub fn mut_span(&self) -> Option<Span> {
+        self.mut_span
+    }
+
+    /// Returns the declaration (the [assignee expression](crate::ast::AssigneeExprASTNode)).
+    pub fn decl(&self) -> &dyn ExprASTNode {
+        self.decl.as_ref()
+    }
+
+    /// Returns the assigned value, if any.
+    pub fn value(&self) -> Option<&dyn ExprASTNode> {
+        self.value.as_deref()
     }
 }
 
 impl ASTNode for LetASTNode {
     ast_defaults!();
 
-    fn children(&self) -> Option<ASTChildIterator> {
+    fn children(&self) -> Option<ASTChildIterator<'_>> {
         let decl = iter::once(self.decl.as_ast());
         let value = self.value.iter().map(|v| v.as_ast());
         let iter = decl.chain(value);
@@ -99,61 +137,67 @@ impl ASTNode for LetASTNode {
         }
         branch.release()
     }
-}
 
-impl StatementASTNode for LetASTNode {}
+    fn resolve_names(&self, resolver: &mut Resolver) {
+        // The declared variable is not visible in its own initializer
+        if let Some(value) = &self.value {
+            value.resolve_names(resolver);
+        }
+        if let Some(name) = self.decl.try_as_assignee().and_then(|a| a.pattern()) {
+            resolver.declare(name, DeclKind::Local, self.decl.span());
+        }
+    }
 
-impl<'ctx> CodeGen<'ctx, ()> for LetASTNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
-        let value = self
-            .value
-            .as_ref()
-            .map(|v| CodeGen::<AnyValueEnum>::code_gen(v.as_ref(), state))
-            .transpose()?;
-
-        let pat = self
-            .decl
-            .try_as_assignee()
-            .ok_or(SemanticError::WrongExpressionKind {
-                message: "Expected an assignee expression",
-                span: self.span,
-            })?
-            .pattern();
-
-        //TODO Type checking (probably on HIR->MIR conversion)
-
-        if let Some(pat) = pat.as_ref() {
-            let ty = CodeGen::<BasicTypeEnum>::code_gen(&self.ty, state)?;
-            let ptr = state
-                .builder()
-                .build_alloca(ty, pat.as_ref())
-                .map_err(CodeGenError::from)?;
-
-            //TODO Variable mutability?
-            state
-                .symbol_table()
-                .insert(pat.clone(), ptr.as_any_value_enum());
-
-            if let Some(value) = value {
-                let value = BasicValueEnum::try_from(value).map_err(|_| {
-                    CodeGenError::InvalidLLVMValueType {
-                        message: "Return value must be a basic value".into(),
-                        span: self.value.as_ref().unwrap().span(),
-                    }
-                })?;
-                state.builder().build_store(ptr, value)?;
-            }
+    fn check_init(&self, checker: &mut InitChecker) {
+        if let Some(value) = &self.value {
+            value.check_init(checker);
+        }
+        if let Some(name) = self.decl.try_as_assignee().and_then(|a| a.pattern()) {
+            checker.declare(name, self.decl.span(), self.mut_span, self.value.is_some());
+        }
+    }
+
+    fn print_source(&self, printer: &mut SourcePrinter) {
+        printer.write("let ");
+        if self.is_mutable() {
+            printer.write("mut ");
+        }
+        self.decl.print_source(printer);
+        if let Some(ty) = &self.ty {
+            printer.write(format_args!(": {}", ty));
+        }
+        if let Some(value) = &self.value {
+            printer.write(" = ");
+            value.print_source(printer);
         }
+        printer.write(";");
+    }
 
-        //TODO Unused value warning
+    fn encode(&self, encoder: &mut Encoder) {
+        encoder.write_tag(NodeTag::Let);
+        encoder.write_node(self.decl.as_ast());
+        // The desugared nodes are encoded in their source form,
+        // so only the lets written in the source (which are annotated) are encoded
+        let ty = self
+            .ty
+            .as_ref()
+            .expect("The encoded lets should be annotated");
+        encoder.write_type(ty);
+        encoder.write_optional(self.value.as_ref().map(|v| v.as_ast()));
+        encoder.write_optional_span(self.mut_span);
+        encoder.write_span(self.span);
+    }
+}
 
-        Ok(())
+impl StatementASTNode for LetASTNode {
+    fn lower(&self) -> CoreStmt<'_> {
+        CoreStmt::Let(self)
     }
 }
 
 impl fmt::Display for LetASTNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mutability = if self.mutable { "Mut" } else { "" };
+        let mutability = if self.is_mutable() { "Mut" } else { "" };
         write!(f, "Let {} {}", mutability, self.span)
     }
 }
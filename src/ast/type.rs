@@ -2,19 +2,13 @@
 //! This is a module containing the Type AST meta-node implementation.
 
 use std::fmt;
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
-use inkwell::context::Context;
-use inkwell::types::{AnyType, AnyTypeEnum, BasicMetadataTypeEnum, BasicType, BasicTypeEnum};
-use inkwell::values::AnyValueEnum;
-
-use crate::codegen;
-use crate::codegen::error::CodeGenError;
-use crate::codegen::{CodeGen, CodeGenState};
-use crate::token::Span;
+use crate::token::{NumericSuffix, Span};
 
 /// An AST meta-node representing a type.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TypeASTMetaNode {
     ty: Type,
     span: Span,
@@ -28,7 +22,7 @@ impl TypeASTMetaNode {
 
     /// Returns the type of this meta-node.
     pub fn ty(&self) -> Type {
-        self.ty
+        self.ty.clone()
     }
 
     /// Returns the span of this meta-node.
@@ -37,33 +31,6 @@ impl TypeASTMetaNode {
     }
 }
 
-impl<'ctx> CodeGen<'ctx, AnyTypeEnum<'ctx>> for TypeASTMetaNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyTypeEnum<'ctx>> {
-        CodeGen::<BasicTypeEnum>::code_gen(self, state).map(|bt| bt.as_any_type_enum())
-    }
-}
-
-impl<'ctx> CodeGen<'ctx, BasicTypeEnum<'ctx>> for TypeASTMetaNode {
-    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<BasicTypeEnum<'ctx>> {
-        let context = state.context();
-        Ok(match self.ty {
-            Type::I32 => context.i32_type().as_basic_type_enum(),
-            Type::F64 => context.f64_type().as_basic_type_enum(),
-            Type::Bool => context.bool_type().as_basic_type_enum(),
-            Type::Unit => context.struct_type(&[], false).as_basic_type_enum(),
-        })
-    }
-}
-
-impl<'ctx> CodeGen<'ctx, BasicMetadataTypeEnum<'ctx>> for TypeASTMetaNode {
-    fn code_gen(
-        &self,
-        state: &mut CodeGenState<'ctx>,
-    ) -> codegen::Result<BasicMetadataTypeEnum<'ctx>> {
-        CodeGen::<BasicTypeEnum>::code_gen(self, state).map(|bt| bt.into())
-    }
-}
-
 impl fmt::Display for TypeASTMetaNode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Display::fmt(&self.ty, f)
@@ -74,26 +41,87 @@ impl fmt::Display for TypeASTMetaNode {
 //TODO Add a link to Inkwell's `AnyTypeEnum` documentation.
 /// An enum representing a type.
 ///
-/// This enum is closely related to LLVM types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// This enum is closely related to LLVM types. The signed and unsigned integer types
+/// of the same width are the same LLVM type, so the signedness is only known
+/// from the types in the source code (see [`try_from_llvm_value`](Type::try_from_llvm_value)).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
-    /// A 32-bit integer type.
+    /// An 8-bit signed integer type.
+    I8,
+    /// A 16-bit signed integer type.
+    I16,
+    /// A 32-bit signed integer type.
     I32,
+    /// A 64-bit signed integer type.
+    I64,
+    /// An 8-bit unsigned integer type.
+    U8,
+    /// A 16-bit unsigned integer type.
+    U16,
+    /// A 32-bit unsigned integer type.
+    U32,
+    /// A 64-bit unsigned integer type.
+    U64,
+    /// A 32-bit floating-point type.
+    F32,
     /// A 64-bit floating-point type.
     F64,
     /// A boolean type.
     Bool,
     /// The unit type.
+    ///
+    /// The unit values (including the unit parameters and local variables) are represented
+    /// as an empty struct `{}` in LLVM. The only exception are the return types of functions,
+    /// where `void` is used instead -- a call to such a function evaluates to the unit value,
+    /// and returning the unit value from such a function returns `void`.
     Unit,
+    /// The string slice type `&str`.
+    ///
+    /// Its values are the string literals, represented as pointers to their null-terminated
+    /// UTF-8 contents in the [constant pool](crate::codegen::pool), so that they can be passed
+    /// to the C functions (e.g. `puts`).
+    Str,
+    /// A tuple type with at least one element, e.g. `(i32, bool)` (the empty tuple is
+    /// the [unit type](Type::Unit)).
+    ///
+    /// The tuples are represented as LLVM structs with the elements as the fields, in order.
+    Tuple(Box<[Type]>),
+    /// A reference type, e.g. `&i32` or `&mut (i32, bool)` (the references to the strings
+    /// are the [string slice type](Type::Str)).
+    ///
+    /// The references are represented as pointers to the referenced values.
+    Ref {
+        /// The type of the referenced value.
+        target: Box<Type>,
+        /// Whether the reference is mutable (`&mut T`).
+        mutable: bool,
+    },
 }
 
 impl fmt::Display for Type {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Type::I32 => write!(f, "i32"),
-            Type::F64 => write!(f, "f64"),
             Type::Bool => write!(f, "bool"),
             Type::Unit => write!(f, "()"),
+            Type::Str => write!(f, "&str"),
+            Type::Tuple(elements) if elements.len() == 1 => write!(f, "({},)", elements[0]),
+            Type::Tuple(elements) => {
+                let elements: Vec<String> = elements.iter().map(Type::to_string).collect();
+                write!(f, "({})", elements.join(", "))
+            }
+            Type::Ref {
+                target,
+                mutable: false,
+            } => write!(f, "&{}", target),
+            Type::Ref {
+                target,
+                mutable: true,
+            } => write!(f, "&mut {}", target),
+            ty => write!(
+                f,
+                "{}",
+                ty.numeric_suffix().expect("Numeric types have a suffix")
+            ),
         }
     }
 }
@@ -103,49 +131,187 @@ impl FromStr for Type {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "i32" => Ok(Type::I32),
-            "f64" => Ok(Type::F64),
             "bool" => Ok(Type::Bool),
             "()" => Ok(Type::Unit),
-            _ => Err(()),
+            "&str" => Ok(Type::Str),
+            s => NumericSuffix::from_name(s).map(Type::from).ok_or(()),
+        }
+    }
+}
+
+impl From<NumericSuffix> for Type {
+    fn from(suffix: NumericSuffix) -> Self {
+        match suffix {
+            NumericSuffix::I8 => Type::I8,
+            NumericSuffix::I16 => Type::I16,
+            NumericSuffix::I32 => Type::I32,
+            NumericSuffix::I64 => Type::I64,
+            NumericSuffix::U8 => Type::U8,
+            NumericSuffix::U16 => Type::U16,
+            NumericSuffix::U32 => Type::U32,
+            NumericSuffix::U64 => Type::U64,
+            NumericSuffix::F32 => Type::F32,
+            NumericSuffix::F64 => Type::F64,
         }
     }
 }
 
 impl Type {
-    /// Attempts to get the [`Type`] of an [`AnyValueEnum`].
-    ///
-    /// # Errors
-    ///
-    /// Returns a [`CodeGenError::UnsupportedType`] if the type is not supported.
-    pub fn try_from_llvm_value<'ctx>(
-        context: &'ctx Context,
-        value: AnyValueEnum<'ctx>,
-        span: Span,
-    ) -> codegen::Result<Type> {
-        match value.get_type() {
-            // Bool
-            AnyTypeEnum::IntType(i) if i.get_bit_width() == 1 => Ok(Type::Bool),
-            // I32
-            AnyTypeEnum::IntType(i) if i.get_bit_width() == 32 => Ok(Type::I32),
-            // F64
-            AnyTypeEnum::FloatType(f) if f == context.f64_type() => Ok(Type::F64),
-            // Unit
-            AnyTypeEnum::StructType(s) if s.count_fields() == 0 => Ok(Type::Unit),
-            // Unsupported ints
-            AnyTypeEnum::IntType(_) => Err(CodeGenError::UnsupportedType {
-                message: "Unsupported int type (only `i32` is supported)".into(),
-                span,
-            }),
-            // Unsupported floats
-            AnyTypeEnum::FloatType(_) => Err(CodeGenError::UnsupportedType {
-                message: "Unsupported float type (only `f64` is supported)".into(),
-                span,
-            }),
-            ty => Err(CodeGenError::UnsupportedType {
-                message: format!("Unsupported type: {}", ty).into_boxed_str(),
-                span,
-            }),
+    /// Returns the tuple type with the given element types, or the unit type
+    /// if there are no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::ast::Type;
+    ///
+    /// let pair = Type::tuple(vec![Type::I32, Type::Bool]);
+    /// assert_eq!(pair, Type::Tuple(Box::new([Type::I32, Type::Bool])));
+    /// assert_eq!(pair.to_string(), "(i32, bool)");
+    /// assert_eq!(Type::tuple(vec![Type::U8]).to_string(), "(u8,)");
+    /// assert_eq!(Type::tuple(vec![]), Type::Unit);
+    /// ```
+    pub fn tuple(elements: Vec<Type>) -> Type {
+        if elements.is_empty() {
+            return Type::Unit;
         }
+        Type::Tuple(elements.into_boxed_slice())
+    }
+
+    /// Returns the reference type to the given type, e.g. `&mut i32`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::ast::Type;
+    ///
+    /// let pair = Type::tuple(vec![Type::I32, Type::Bool]);
+    /// let reference = Type::reference(pair.clone(), true);
+    /// assert_eq!(reference.to_string(), "&mut (i32, bool)");
+    /// assert_eq!(reference.referenced_type(), Some(pair.clone()));
+    /// assert_eq!(Type::reference(pair.clone(), true), reference);
+    /// assert_ne!(Type::reference(pair, false), reference);
+    /// ```
+    pub fn reference(target: Type, mutable: bool) -> Type {
+        Type::Ref {
+            target: Box::new(target),
+            mutable,
+        }
+    }
+
+    /// Returns the type of the value referenced by a reference type,
+    /// or `None` for the other types.
+    pub fn referenced_type(&self) -> Option<Type> {
+        match self {
+            Type::Ref { target, .. } => Some(target.as_ref().clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the element types of a tuple type, or `None` for the other types.
+    pub fn tuple_elements(&self) -> Option<&[Type]> {
+        match self {
+            Type::Tuple(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the type is an integer type.
+    pub fn is_integer(&self) -> bool {
+        self.is_signed() || self.is_unsigned()
+    }
+
+    /// Returns whether the type is a signed integer type.
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Type::I8 | Type::I16 | Type::I32 | Type::I64)
+    }
+
+    /// Returns whether the type is an unsigned integer type.
+    pub fn is_unsigned(&self) -> bool {
+        matches!(self, Type::U8 | Type::U16 | Type::U32 | Type::U64)
+    }
+
+    /// Returns whether the type is a floating-point type.
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::F32 | Type::F64)
+    }
+
+    /// Returns the number of bits of a numeric type, or `None` for the other types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::ast::Type;
+    ///
+    /// assert_eq!(Type::U16.bit_width(), Some(16));
+    /// assert_eq!(Type::F32.bit_width(), Some(32));
+    /// assert_eq!(Type::Bool.bit_width(), None);
+    /// ```
+    pub fn bit_width(&self) -> Option<u32> {
+        match self {
+            Type::I8 | Type::U8 => Some(8),
+            Type::I16 | Type::U16 => Some(16),
+            Type::I32 | Type::U32 | Type::F32 => Some(32),
+            Type::I64 | Type::U64 | Type::F64 => Some(64),
+            Type::Bool | Type::Unit | Type::Str | Type::Tuple(_) | Type::Ref { .. } => None,
+        }
+    }
+
+    /// Returns the range of the values of an integer type, or `None` for the other types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::ast::Type;
+    ///
+    /// assert_eq!(Type::I8.int_range(), Some(-128..=127));
+    /// assert_eq!(Type::U64.int_range(), Some(0..=u64::MAX as i128));
+    /// assert_eq!(Type::F64.int_range(), None);
+    /// ```
+    pub fn int_range(&self) -> Option<RangeInclusive<i128>> {
+        let bits = self.bit_width().filter(|_| self.is_integer())?;
+        Some(match self.is_signed() {
+            true => -(1 << (bits - 1))..=(1 << (bits - 1)) - 1,
+            false => 0..=(1 << bits) - 1,
+        })
+    }
+
+    /// Returns the unsigned integer type of the same width as the given integer type,
+    /// or the type itself otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::ast::Type;
+    ///
+    /// assert_eq!(Type::I8.to_unsigned(), Type::U8);
+    /// assert_eq!(Type::U64.to_unsigned(), Type::U64);
+    /// assert_eq!(Type::F64.to_unsigned(), Type::F64);
+    /// ```
+    pub fn to_unsigned(self) -> Type {
+        match self {
+            Type::I8 => Type::U8,
+            Type::I16 => Type::U16,
+            Type::I32 => Type::U32,
+            Type::I64 => Type::U64,
+            ty => ty,
+        }
+    }
+
+    /// Returns the suffix of the number literals of a numeric type, or `None` for the other types.
+    pub fn numeric_suffix(&self) -> Option<NumericSuffix> {
+        Some(match self {
+            Type::I8 => NumericSuffix::I8,
+            Type::I16 => NumericSuffix::I16,
+            Type::I32 => NumericSuffix::I32,
+            Type::I64 => NumericSuffix::I64,
+            Type::U8 => NumericSuffix::U8,
+            Type::U16 => NumericSuffix::U16,
+            Type::U32 => NumericSuffix::U32,
+            Type::U64 => NumericSuffix::U64,
+            Type::F32 => NumericSuffix::F32,
+            Type::F64 => NumericSuffix::F64,
+            Type::Bool | Type::Unit | Type::Str | Type::Tuple(_) | Type::Ref { .. } => return None,
+        })
     }
 }
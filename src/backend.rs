@@ -0,0 +1,82 @@
+//! A module containing the abstraction over the code generators (backends),
+//! which turn a crate into the object code for the host machine.
+//!
+//! The rest of the pipeline (e.g. the [linker](crate::link::Linker)) only depends on
+//! the [`Backend`] trait, so it doesn't have to know which code generator is used,
+//! and it can be tested with a backend that doesn't generate any code at all.
+//! The available backends are:
+//! - LLVM, i.e. a [`CompilationSession`](crate::codegen::session::CompilationSession)
+//!   (with the `codegen` feature),
+//! - [Cranelift](crate::cranelift::CraneliftBackend) (with the `cranelift` feature).
+
+use std::error::Error;
+use std::fmt;
+
+use crate::ast::Crate;
+#[cfg(feature = "codegen")]
+use crate::codegen::error::CodeGenError;
+#[cfg(feature = "cranelift")]
+use crate::cranelift::CraneliftError;
+
+/// A code generator producing the object code of a crate.
+///
+/// The backends are shared between the threads (like the
+/// [sessions](crate::codegen::session::CompilationSession)), so they have to be `Send` and `Sync`.
+pub trait Backend: fmt::Debug + Send + Sync {
+    /// Returns the name of the backend (e.g. `llvm`).
+    fn name(&self) -> &'static str;
+
+    /// Compiles the given crate, and returns the contents of the object file
+    /// for the host machine.
+    ///
+    /// # Errors
+    ///
+    /// If the crate cannot be compiled, a [`BackendError`] is returned.
+    fn emit_object(&self, crt: &Crate) -> Result<Vec<u8>, BackendError>;
+}
+
+/// The type of error that can occur while compiling a crate with a [`Backend`].
+#[derive(Debug)]
+pub enum BackendError {
+    /// An error of the LLVM code generation.
+    #[cfg(feature = "codegen")]
+    CodeGen(CodeGenError),
+    /// An error of the Cranelift code generation.
+    #[cfg(feature = "cranelift")]
+    Cranelift(CraneliftError),
+    /// An error of a backend defined outside of this crate.
+    Custom {
+        /// The name of the backend.
+        backend: &'static str,
+        /// The description of the error.
+        message: Box<str>,
+    },
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "codegen")]
+            BackendError::CodeGen(err) => write!(f, "{:?}", err),
+            #[cfg(feature = "cranelift")]
+            BackendError::Cranelift(err) => write!(f, "{}", err),
+            BackendError::Custom { backend, message } => write!(f, "{} ({})", message, backend),
+        }
+    }
+}
+
+impl Error for BackendError {}
+
+#[cfg(feature = "codegen")]
+impl From<CodeGenError> for BackendError {
+    fn from(err: CodeGenError) -> BackendError {
+        BackendError::CodeGen(err)
+    }
+}
+
+#[cfg(feature = "cranelift")]
+impl From<CraneliftError> for BackendError {
+    fn from(err: CraneliftError) -> BackendError {
+        BackendError::Cranelift(err)
+    }
+}
@@ -0,0 +1,108 @@
+//! A module containing the built-in functions, which can be called without being declared.
+//!
+//! A built-in function is only used if there is no function with the same name in the crate,
+//! so declaring e.g. `fn min(...)` (or importing it from C) shadows the built-in one.
+
+use std::fmt;
+
+use crate::ast::Type;
+
+/// A built-in numeric function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    /// `min(a, b)` -- the smaller of two numbers of the same type.
+    ///
+    /// If one of the floats is `NaN`, the other one is returned (like [`f64::min`]).
+    Min,
+    /// `max(a, b)` -- the larger of two numbers of the same type.
+    ///
+    /// If one of the floats is `NaN`, the other one is returned (like [`f64::max`]).
+    Max,
+    /// `abs(x)` -- the absolute value of a signed integer or a float.
+    ///
+    /// The absolute value of the minimum value of an integer type wraps around to itself
+    /// (like [`i32::wrapping_abs`]).
+    Abs,
+    /// `sqrt(x)` -- the square root of a float (`NaN` for the negative numbers).
+    Sqrt,
+}
+
+impl Builtin {
+    /// All the built-in functions.
+    pub const ALL: [Builtin; 4] = [Builtin::Min, Builtin::Max, Builtin::Abs, Builtin::Sqrt];
+
+    /// Returns the built-in function with the given name, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::builtin::Builtin;
+    ///
+    /// assert_eq!(Builtin::from_name("sqrt"), Some(Builtin::Sqrt));
+    /// assert_eq!(Builtin::from_name("pow"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        Builtin::ALL
+            .into_iter()
+            .find(|builtin| builtin.name() == name)
+    }
+
+    /// Returns the name of the function.
+    pub fn name(self) -> &'static str {
+        match self {
+            Builtin::Min => "min",
+            Builtin::Max => "max",
+            Builtin::Abs => "abs",
+            Builtin::Sqrt => "sqrt",
+        }
+    }
+
+    /// Returns the number of the parameters of the function, which are all of the same type.
+    pub fn param_count(self) -> usize {
+        match self {
+            Builtin::Min | Builtin::Max => 2,
+            Builtin::Abs | Builtin::Sqrt => 1,
+        }
+    }
+
+    /// Returns whether the function accepts the arguments of the given type
+    /// (the result is always of the same type).
+    pub fn supports(self, ty: &Type) -> bool {
+        match self {
+            Builtin::Min | Builtin::Max => ty.is_integer() || ty.is_float(),
+            Builtin::Abs => ty.is_signed() || ty.is_float(),
+            Builtin::Sqrt => ty.is_float(),
+        }
+    }
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        for builtin in Builtin::ALL {
+            assert_eq!(Builtin::from_name(builtin.name()), Some(builtin));
+            assert_eq!(builtin.to_string(), builtin.name());
+        }
+        assert_eq!(Builtin::from_name("Min"), None);
+    }
+
+    #[test]
+    fn test_supported_types() {
+        assert!(Builtin::Min.supports(&Type::U8));
+        assert!(Builtin::Max.supports(&Type::F32));
+        assert!(!Builtin::Max.supports(&Type::Bool));
+        assert!(Builtin::Abs.supports(&Type::I64));
+        assert!(!Builtin::Abs.supports(&Type::U64));
+        assert!(Builtin::Sqrt.supports(&Type::F64));
+        assert!(!Builtin::Sqrt.supports(&Type::I32));
+    }
+}
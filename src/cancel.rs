@@ -0,0 +1,97 @@
+//! A module containing the cancellation of a compilation.
+//!
+//! A [`CancellationToken`] is checked at the safe points of the pipeline: before every token
+//! in the [lexer](crate::lexer::Lexer) and the [parser](crate::parser::Parser), and before every
+//! item in the code generation. Once the token is cancelled (or its deadline has passed),
+//! the current stage aborts with the corresponding [`Interrupted`] error.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The reason why a compilation was aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interrupted {
+    /// The compilation was [cancelled](CancellationToken::cancel).
+    Cancelled,
+    /// The deadline of the compilation has passed.
+    TimedOut,
+}
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Interrupted::Cancelled => write!(f, "The compilation was cancelled"),
+            Interrupted::TimedOut => write!(f, "The compilation timed out"),
+        }
+    }
+}
+
+impl Error for Interrupted {}
+
+/// A token used to abort a compilation, possibly from another thread.
+///
+/// The clones of a token share its state, so cancelling any of them cancels all of them.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    /// Creates a new `CancellationToken` without a deadline.
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Creates a new `CancellationToken` that times out after the given duration from now.
+    pub fn with_timeout(timeout: Duration) -> CancellationToken {
+        // A timeout too large to be represented never passes
+        let deadline = Instant::now().checked_add(timeout);
+        CancellationToken {
+            cancelled: Arc::default(),
+            deadline,
+        }
+    }
+
+    /// Creates a new `CancellationToken` that times out at the given instant.
+    pub fn with_deadline(deadline: Instant) -> CancellationToken {
+        CancellationToken {
+            cancelled: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Returns the deadline of the token, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Cancels the token (and all its clones).
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the token has been [cancelled](CancellationToken::cancel).
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Checks whether the compilation should be aborted.
+    ///
+    /// # Errors
+    ///
+    /// If the token has been cancelled, [`Interrupted::Cancelled`] is returned,
+    /// and if its deadline has passed, [`Interrupted::TimedOut`] is returned.
+    pub fn check(&self) -> Result<(), Interrupted> {
+        if self.is_cancelled() {
+            return Err(Interrupted::Cancelled);
+        }
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Err(Interrupted::TimedOut),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,21 +1,54 @@
 //! A module containing the types and traits used for code generation.
 
-use inkwell::builder::Builder;
+use std::collections::HashMap;
+use std::path::Path;
+
+use inkwell::basic_block::BasicBlock;
+use inkwell::builder::{Builder, BuilderError};
 use inkwell::context::Context;
 use inkwell::module::{Linkage, Module};
+use inkwell::passes::PassManager;
 use inkwell::types::{BasicTypeEnum, FunctionType};
-use inkwell::values::{AnyValue, AnyValueEnum, FunctionValue, IntValue};
+use inkwell::values::{
+    AnyValue, AnyValueEnum, BasicValue, FunctionValue, InstructionValue, IntValue,
+};
 use inkwell::IntPredicate;
 
+use crate::ast::error::SemanticWarning;
 use crate::ast::{
-    CrateASTNode, ExprASTNode, ExternItem, FuncProtoASTNode, ItemASTNode, StaticASTNode, Type,
+    ASTNode, BorrowExprASTNode, ConstEnv, CrateASTNode, ExprASTNode, ExternItem, FuncProtoASTNode,
+    ItemASTNode, StaticASTNode, Type,
 };
+use crate::cancel::CancellationToken;
+use crate::observer::{self, Diagnostic, SharedObserver};
+use crate::target::TargetLayout;
+use crate::token::{Position, Span};
 
+use self::debug_info::DebugInfo;
 use self::error::CodeGenError;
+use self::pool::ConstantPool;
+use self::registry::FunctionRegistry;
 use self::symbol_table::*;
 
+// The evaluation of the statics doesn't depend on LLVM, so it is a part of the front-end
+pub use crate::statics;
+
+pub mod asm;
+pub mod cache;
+pub mod coverage;
+mod debug_info;
 pub mod error;
+pub mod lower;
+mod metadata;
+pub mod pool;
+pub mod profile;
+pub mod registry;
+pub mod sanitizer;
+pub mod session;
+pub mod stats;
+pub mod stream;
 mod symbol_table;
+mod typing;
 
 /// The state of the code generation process.
 pub struct CodeGenState<'ctx> {
@@ -23,11 +56,37 @@ pub struct CodeGenState<'ctx> {
     module: Module<'ctx>,
     builder: Builder<'ctx>,
     symbol_table: SymbolTable<'ctx>,
+    functions: FunctionRegistry,
+    options: CodeGenOptions,
+    cancellation: Option<CancellationToken>,
+    observer: Option<SharedObserver>,
+    warnings: Vec<SemanticWarning>,
+    unroll_count: Option<u32>,
+    hinted_loops: usize,
+    constant_pool: ConstantPool<'ctx>,
+    expected_type: Option<Type>,
+    /// The types of the references generated by the borrow expressions, by the addresses
+    /// of the expressions (the LLVM pointers don't carry the referenced types, see the `typing`
+    /// module), since the spans of the nodes from different files can be the same.
+    reference_types: HashMap<*const BorrowExprASTNode, Type>,
+    static_values: ConstEnv,
+    debug_info: Option<DebugInfo<'ctx>>,
 }
 
 impl<'ctx> CodeGenState<'ctx> {
-    /// Creates a new code generation state with the `module_name` based on the given `context`.
+    /// Creates a new code generation state with the `module_name` based on the given `context`,
+    /// using the default [options](CodeGenOptions).
     pub fn new(context: &'ctx Context, module_name: &str) -> CodeGenState<'ctx> {
+        CodeGenState::with_options(context, module_name, CodeGenOptions::default())
+    }
+
+    /// Creates a new code generation state with the `module_name` based on the given `context`,
+    /// using the given `options`.
+    pub fn with_options(
+        context: &'ctx Context,
+        module_name: &str,
+        options: CodeGenOptions,
+    ) -> CodeGenState<'ctx> {
         let module = context.create_module(module_name);
         let builder = context.create_builder();
         let symbol_table = SymbolTable::new();
@@ -37,6 +96,18 @@ impl<'ctx> CodeGenState<'ctx> {
             module,
             builder,
             symbol_table,
+            functions: FunctionRegistry::new(),
+            options,
+            cancellation: None,
+            observer: None,
+            warnings: Vec::new(),
+            unroll_count: None,
+            hinted_loops: 0,
+            constant_pool: ConstantPool::new(),
+            expected_type: None,
+            reference_types: HashMap::new(),
+            static_values: ConstEnv::with_layout(options.target_layout()),
+            debug_info: None,
         }
     }
 
@@ -52,9 +123,52 @@ impl<'ctx> CodeGenState<'ctx> {
 
     /// Extracts the LLVM module that is being generated.
     pub fn take_module(self) -> Module<'ctx> {
+        // The debug info has to be complete before the module is verified
+        if let Some(debug_info) = &self.debug_info {
+            debug_info.finalize();
+        }
         self.module
     }
 
+    /// Returns the registry of the signatures of the functions declared in the crate.
+    pub fn functions(&self) -> &FunctionRegistry {
+        &self.functions
+    }
+
+    /// Returns the options of the code generation.
+    pub fn options(&self) -> CodeGenOptions {
+        self.options
+    }
+
+    /// Sets the token that aborts the code generation when it is cancelled or times out.
+    pub fn set_cancellation(&mut self, cancellation: CancellationToken) {
+        self.cancellation = Some(cancellation);
+    }
+
+    /// Checks whether the code generation should be aborted (see [`CancellationToken::check`]).
+    ///
+    /// It is checked before the code is generated for every item.
+    ///
+    /// # Errors
+    ///
+    /// If the token has been cancelled or has timed out, [`CodeGenError::Interrupted`] is returned.
+    pub fn check_cancellation(&self) -> Result<()> {
+        match &self.cancellation {
+            Some(cancellation) => cancellation.check().map_err(CodeGenError::from),
+            None => Ok(()),
+        }
+    }
+
+    /// Registers the observer notified about the progress of the code generation.
+    pub fn set_observer(&mut self, observer: SharedObserver) {
+        self.observer = Some(observer);
+    }
+
+    /// Notifies the observer (if any) that the code is about to be generated for the given item.
+    pub(crate) fn notify_item_codegen(&self, item: &ItemASTNode) {
+        observer::notify(&self.observer, |o| o.on_item_codegen(item));
+    }
+
     /// Returns the builder that is being used to generate LLVM IR.
     pub fn builder(&mut self) -> &mut Builder<'ctx> {
         &mut self.builder
@@ -65,6 +179,55 @@ impl<'ctx> CodeGenState<'ctx> {
         &mut self.symbol_table
     }
 
+    /// Runs `f` in a new scope of the [symbol table](CodeGenState::symbol_table).
+    ///
+    /// The scope is closed afterwards, even if `f` returns an error.
+    pub fn with_scope<T>(
+        &mut self,
+        f: impl FnOnce(&mut CodeGenState<'ctx>) -> Result<T>,
+    ) -> Result<T> {
+        self.symbol_table.open_scope();
+        let result = f(self);
+        self.symbol_table.close_scope();
+        result
+    }
+
+    /// Builds an instruction with the [builder](CodeGenState::builder),
+    /// converting its error into [`CodeGenError::BuilderError`].
+    pub fn emit<T>(
+        &mut self,
+        f: impl FnOnce(&Builder<'ctx>) -> std::result::Result<T, BuilderError>,
+    ) -> Result<T> {
+        f(&self.builder).map_err(CodeGenError::from)
+    }
+
+    /// Records a warning encountered during code generation.
+    pub fn push_warning(&mut self, warning: SemanticWarning) {
+        observer::notify(&self.observer, |o| {
+            o.on_diagnostic(Diagnostic::Warning(&warning))
+        });
+        self.warnings.push(warning);
+    }
+
+    /// Reports the error that aborted the code generation to the observer (if any).
+    pub(crate) fn report_error(&self, err: &CodeGenError) {
+        let diagnostic = match err {
+            CodeGenError::SemanticError(err) => Diagnostic::Error(err),
+            err => Diagnostic::CodeGen(err),
+        };
+        observer::notify(&self.observer, |o| o.on_diagnostic(diagnostic));
+    }
+
+    /// Returns the warnings encountered so far.
+    pub fn warnings(&self) -> &[SemanticWarning] {
+        &self.warnings
+    }
+
+    /// Extracts the warnings encountered so far.
+    pub fn take_warnings(&mut self) -> Vec<SemanticWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
     /// Returns the parent function of the basic block currently being used by the builder, if any.
     pub fn get_current_function(&mut self) -> Option<FunctionValue<'ctx>> {
         self.builder()
@@ -72,6 +235,121 @@ impl<'ctx> CodeGenState<'ctx> {
             .and_then(|bb| bb.get_parent())
     }
 
+    /// Returns whether the basic block currently being used by the builder already has
+    /// a terminator (e.g. after a `return`), so no more instructions can be added to it.
+    pub fn is_terminated(&self) -> bool {
+        self.builder
+            .get_insert_block()
+            .and_then(|bb| bb.get_terminator())
+            .is_some()
+    }
+
+    /// Builds an unconditional branch to the given basic block, unless the current one
+    /// is already [terminated](CodeGenState::is_terminated).
+    pub fn build_branch_if_unterminated(&mut self, dest: BasicBlock<'ctx>) -> Result<()> {
+        if !self.is_terminated() {
+            self.builder.build_unconditional_branch(dest)?;
+        }
+        Ok(())
+    }
+
+    /// Starts describing the generated code with the [debug info](CodeGenOptions::debug_info),
+    /// as if it was compiled from the source file at the given path.
+    pub(crate) fn enable_debug_info(&mut self, path: &str) {
+        self.debug_info = Some(DebugInfo::new(self.context, &self.module, path));
+    }
+
+    /// Attaches the debug info (if enabled) to the given function starting at `start`,
+    /// so that the following instructions are located in it.
+    pub(crate) fn begin_debug_function(
+        &mut self,
+        function: FunctionValue<'ctx>,
+        is_local: bool,
+        start: Position,
+    ) {
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.begin_function(function, is_local, start);
+            self.set_debug_location(start);
+        }
+    }
+
+    /// Stops locating the following instructions in the current function.
+    pub(crate) fn end_debug_function(&mut self) {
+        if let Some(debug_info) = &mut self.debug_info {
+            debug_info.end_function();
+            self.builder.unset_current_debug_location();
+        }
+    }
+
+    /// Sets the source position of the following instructions, if the debug info is enabled.
+    pub(crate) fn set_debug_location(&mut self, pos: Position) {
+        if let Some(location) = self.debug_info.as_ref().and_then(|d| d.location(pos)) {
+            self.builder.set_current_debug_location(location);
+        }
+    }
+
+    /// Requests the loops generated until [`CodeGenState::end_unroll_hint`]
+    /// to be unrolled the given number of times.
+    pub(crate) fn begin_unroll_hint(&mut self, count: u32) {
+        self.unroll_count = Some(count);
+        self.hinted_loops = 0;
+    }
+
+    /// Stops attaching the unrolling hint to the generated loops,
+    /// returning the number of loops generated since [`CodeGenState::begin_unroll_hint`].
+    pub(crate) fn end_unroll_hint(&mut self) -> usize {
+        self.unroll_count = None;
+        std::mem::take(&mut self.hinted_loops)
+    }
+
+    /// Builds the back edge of a loop, i.e. the branch from the end of the loop's `body`
+    /// to its `header`, unless the current basic block is already
+    /// [terminated](CodeGenState::is_terminated).
+    ///
+    /// If an [unrolling hint](CodeGenState::begin_unroll_hint) is active, it is attached
+    /// to the back edge as the loop metadata. A loop without a back edge cannot be unrolled,
+    /// so [`SemanticWarning::IgnoredUnrollHint`] is reported instead.
+    pub fn build_loop_back_edge(&mut self, header: BasicBlock<'ctx>, body: Span) -> Result<()> {
+        if !self.can_repeat_loop(body) {
+            return Ok(());
+        }
+
+        let back_edge = self.builder.build_unconditional_branch(header)?;
+        self.attach_unroll_hint(back_edge);
+        Ok(())
+    }
+
+    /// Returns whether the back edge of a loop can be built at the end of its `body`,
+    /// i.e. the current basic block is not [terminated](CodeGenState::is_terminated),
+    /// reporting the ignored [unrolling hint](CodeGenState::begin_unroll_hint) otherwise.
+    fn can_repeat_loop(&mut self, body: Span) -> bool {
+        if self.unroll_count.is_some() {
+            self.hinted_loops += 1;
+        }
+        if self.is_terminated() {
+            if self.unroll_count.is_some() {
+                self.push_warning(SemanticWarning::IgnoredUnrollHint {
+                    reason: "the loop never repeats",
+                    span: body,
+                });
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Attaches the active [unrolling hint](CodeGenState::begin_unroll_hint), if any,
+    /// to the back edge of a loop as the loop metadata.
+    fn attach_unroll_hint(&self, back_edge: InstructionValue<'ctx>) {
+        if let Some(count) = self.unroll_count {
+            let loop_id = metadata::loop_unroll_count(self.context, count);
+            let kind_id = self.context.get_kind_id(metadata::LOOP_KIND);
+            back_edge
+                .set_metadata(loop_id, kind_id)
+                .expect("A loop ID should be a metadata node.");
+        }
+    }
+
     /// Runs [`CodeGen::<AnyValueEnum>::code_gen`] on the given `expr`,
     /// checks if the result is a boolean value, and returns it.
     ///
@@ -79,10 +357,15 @@ impl<'ctx> CodeGenState<'ctx> {
     ///
     /// If the result is not a boolean value, [`CodeGenError::TypeMismatch`] is returned.
     pub fn build_bool(&mut self, expr: &dyn ExprASTNode) -> Result<IntValue<'ctx>> {
-        CodeGen::<AnyValueEnum>::code_gen(expr, self).and_then(|value| match value {
+        CodeGen::<AnyValueEnum>::code_gen(expr, self)
+            .and_then(|value| self.expect_bool(value, expr.span()))
+    }
+
+    /// Checks if the given `value` of an expression at `span` is a boolean value, and returns it.
+    fn expect_bool(&self, value: AnyValueEnum<'ctx>, span: Span) -> Result<IntValue<'ctx>> {
+        match value {
             AnyValueEnum::IntValue(i) if i.get_type().get_bit_width() == 1 => Ok(i),
             value => {
-                let span = expr.span();
                 let ty = Type::try_from_llvm_value(self.context(), value, span)?;
                 Err(CodeGenError::TypeMismatch {
                     expected: Type::Bool,
@@ -90,19 +373,98 @@ impl<'ctx> CodeGenState<'ctx> {
                     span,
                 })
             }
-        })
+        }
+    }
+
+    /// Runs [`CodeGen::<AnyValueEnum>::code_gen`] on the given `expr` whose type is expected
+    /// (e.g. by a type annotation) to be `ty`.
+    ///
+    /// The expected type is only a hint for the [literal type defaulting](CodeGenState::expected_type),
+    /// so the result still has to be checked against it.
+    pub fn build_expecting(
+        &mut self,
+        expr: &dyn ExprASTNode,
+        ty: Type,
+    ) -> Result<AnyValueEnum<'ctx>> {
+        let outer = self.expected_type.replace(ty);
+        let result = CodeGen::<AnyValueEnum>::code_gen(expr, self);
+        self.expected_type = outer;
+        result
+    }
+
+    /// Runs the given closure without the [expected type](CodeGenState::expected_type)
+    /// of the enclosing expression, e.g. for the statements of a block, which don't
+    /// produce the value of the block.
+    pub(crate) fn without_expected_type<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = self.expected_type.take();
+        let result = f(self);
+        self.expected_type = outer;
+        result
+    }
+
+    /// Returns the type expected from the expression being generated by
+    /// [`build_expecting`](CodeGenState::build_expecting), if there is one.
+    ///
+    /// The unsuffixed number literals adopt it when it is of their kind (an integer or
+    /// a floating-point type), just like in Rust, and default to `i32` or `f64` otherwise.
+    pub fn expected_type(&self) -> Option<Type> {
+        self.expected_type.clone()
+    }
+
+    /// Returns the values of the immutable statics of the crate, which the initializers
+    /// of the statics can refer to (see the [`statics`] module), together with the layout
+    /// of the target machine.
+    pub fn static_values(&self) -> &ConstEnv {
+        &self.static_values
     }
 
     /// Generates LLVM IR for the given `cond` expression and a comparison with the `expected_result`.
     /// The comparison is then returned as an integer value.
     ///
     /// The `cond` expression must be a boolean expression, see [`build_bool`](CodeGenState::build_bool).
+    /// If it is a constant, no comparison is emitted and the constant result is returned instead.
     pub fn build_condition(
         &mut self,
         cond: &dyn ExprASTNode,
         expected_result: bool,
     ) -> Result<IntValue<'ctx>> {
+        if let Some(value) = cond.const_eval().and_then(|c| c.as_bool()) {
+            let result = value == expected_result;
+            return Ok(self.context().bool_type().const_int(result as u64, false));
+        }
+
         let cond = self.build_bool(cond)?;
+        self.compare_condition(cond, expected_result)
+    }
+
+    /// Like [`build_condition`](CodeGenState::build_condition) expecting `true`, but for
+    /// the condition of a loop, which can contain the statements of the loop's body
+    /// (see [`DoWhileASTNode`](crate::ast::DoWhileASTNode)).
+    ///
+    /// If the condition diverges (e.g. it returns), the loop is never entered (nor repeated),
+    /// so `None` is returned instead, with the builder left in the terminated basic block.
+    pub fn build_loop_condition(
+        &mut self,
+        cond: &dyn ExprASTNode,
+    ) -> Result<Option<IntValue<'ctx>>> {
+        if cond.const_eval().is_some() {
+            return self.build_condition(cond, true).map(Some);
+        }
+
+        let value = CodeGen::<AnyValueEnum>::code_gen(cond, self)?;
+        if self.is_terminated() {
+            return Ok(None);
+        }
+        let cond = self.expect_bool(value, cond.span())?;
+        self.compare_condition(cond, true).map(Some)
+    }
+
+    /// Builds a comparison of the boolean value `cond` with the `expected_result`.
+    fn compare_condition(
+        &mut self,
+        cond: IntValue<'ctx>,
+        expected_result: bool,
+    ) -> Result<IntValue<'ctx>> {
         let pred = if expected_result {
             IntPredicate::NE
         } else {
@@ -110,9 +472,329 @@ impl<'ctx> CodeGenState<'ctx> {
         };
 
         let const_zero = self.context().bool_type().const_zero();
-        self.builder()
-            .build_int_compare(pred, cond, const_zero, "cond")
-            .map_err(CodeGenError::from)
+        self.emit(|builder| builder.build_int_compare(pred, cond, const_zero, "cond"))
+    }
+}
+
+/// The options of the code generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeGenOptions {
+    mem2reg: bool,
+    overflow_checks: bool,
+    inexact_float_lint: bool,
+    coverage: bool,
+    profiling: bool,
+    sanitizer: bool,
+    debug_info: bool,
+    module_naming: ModuleNaming,
+    target_layout: TargetLayout,
+}
+
+impl CodeGenOptions {
+    /// Creates the default `CodeGenOptions`, i.e. with [mem2reg](CodeGenOptions::mem2reg)
+    /// and [overflow checks](CodeGenOptions::overflow_checks) enabled,
+    /// and the [inexact float lint](CodeGenOptions::inexact_float_lint), the
+    /// [coverage](CodeGenOptions::coverage), the [profiling](CodeGenOptions::profiling),
+    /// the [sanitizer](CodeGenOptions::sanitizer) and the [debug info](CodeGenOptions::debug_info)
+    /// disabled, the modules [named](CodeGenOptions::module_naming) after the source file names,
+    /// and the [layout](CodeGenOptions::target_layout) of the host machine.
+    pub fn new() -> CodeGenOptions {
+        CodeGenOptions {
+            mem2reg: true,
+            overflow_checks: true,
+            inexact_float_lint: false,
+            coverage: false,
+            profiling: false,
+            sanitizer: false,
+            debug_info: false,
+            module_naming: ModuleNaming::FileName,
+            target_layout: TargetLayout::host(),
+        }
+    }
+
+    /// Sets whether the local variables should be promoted to SSA registers.
+    pub fn with_mem2reg(self, mem2reg: bool) -> CodeGenOptions {
+        CodeGenOptions { mem2reg, ..self }
+    }
+
+    /// Sets whether the integer operations should be checked for overflow.
+    pub fn with_overflow_checks(self, overflow_checks: bool) -> CodeGenOptions {
+        CodeGenOptions {
+            overflow_checks,
+            ..self
+        }
+    }
+
+    /// Sets whether the float literals that are not exactly representable should be reported.
+    pub fn with_inexact_float_lint(self, inexact_float_lint: bool) -> CodeGenOptions {
+        CodeGenOptions {
+            inexact_float_lint,
+            ..self
+        }
+    }
+
+    /// Sets whether the generated code should be instrumented with the coverage counters.
+    pub fn with_coverage(self, coverage: bool) -> CodeGenOptions {
+        CodeGenOptions { coverage, ..self }
+    }
+
+    /// Sets whether the generated code should be instrumented with the profiling hooks.
+    pub fn with_profiling(self, profiling: bool) -> CodeGenOptions {
+        CodeGenOptions { profiling, ..self }
+    }
+
+    /// Sets whether the memory accesses of the generated code should be checked at runtime.
+    pub fn with_sanitizer(self, sanitizer: bool) -> CodeGenOptions {
+        CodeGenOptions { sanitizer, ..self }
+    }
+
+    /// Sets whether the generated code should carry the source locations of the statements.
+    pub fn with_debug_info(self, debug_info: bool) -> CodeGenOptions {
+        CodeGenOptions { debug_info, ..self }
+    }
+
+    /// Sets how the generated modules should be named.
+    pub fn with_module_naming(self, module_naming: ModuleNaming) -> CodeGenOptions {
+        CodeGenOptions {
+            module_naming,
+            ..self
+        }
+    }
+
+    /// Sets the layout of the target machine the code is generated for.
+    pub fn with_target_layout(self, target_layout: TargetLayout) -> CodeGenOptions {
+        CodeGenOptions {
+            target_layout,
+            ..self
+        }
+    }
+
+    /// Returns whether the local variables (generated as stack allocations with loads and stores)
+    /// are promoted to SSA registers using LLVM's `mem2reg` pass.
+    pub fn mem2reg(&self) -> bool {
+        self.mem2reg
+    }
+
+    /// Returns whether the integer operations trap when they overflow.
+    ///
+    /// Currently, the checks are emitted for the integer division and remainder, which trap
+    /// when the divisor is zero, or when `i32::MIN` is divided by `-1`. Without the checks,
+    /// the latter wraps around (like [`i32::wrapping_div`] and [`i32::wrapping_rem`]),
+    /// and the division by zero is undefined behaviour.
+    pub fn overflow_checks(&self) -> bool {
+        self.overflow_checks
+    }
+
+    /// Returns whether [`SemanticWarning::InexactFloatLiteral`] is reported for the float literals
+    /// whose value is rounded to the nearest `f64`, e.g. `0.1`.
+    pub fn inexact_float_lint(&self) -> bool {
+        self.inexact_float_lint
+    }
+
+    /// Returns whether every basic block of the generated code counts how many times it was
+    /// entered (see [`coverage`](self::coverage) for reading the counts).
+    pub fn coverage(&self) -> bool {
+        self.coverage
+    }
+
+    /// Returns whether every function of the generated code reports when it is entered
+    /// and when it returns (see [`profile`](self::profile) for measuring the calls).
+    pub fn profiling(&self) -> bool {
+        self.profiling
+    }
+
+    /// Returns whether the invalid memory accesses of the generated code are reported
+    /// at runtime (see [`sanitizer`](self::sanitizer) for the checks).
+    pub fn sanitizer(&self) -> bool {
+        self.sanitizer
+    }
+
+    /// Returns whether the generated code carries the line tables of the DWARF debug info,
+    /// i.e. every function is described by a subprogram, and every statement by its location
+    /// in the source file (see [`asm`](self::asm) for the annotated assembly).
+    pub fn debug_info(&self) -> bool {
+        self.debug_info
+    }
+
+    /// Returns how the identifiers of the generated modules are derived from their crates
+    /// (see [`Crate::module_identifier`](crate::ast::Crate::module_identifier)).
+    ///
+    /// The source file name of a module is always the path of its source file, if it is known.
+    /// A [session](session::CompilationSession::with_module_name) can override the identifier.
+    pub fn module_naming(&self) -> ModuleNaming {
+        self.module_naming
+    }
+
+    /// Returns the layout of the target machine, which determines the values
+    /// of the [`size_of`](crate::ast::LayoutOfASTNode) expressions.
+    pub fn target_layout(&self) -> TargetLayout {
+        self.target_layout
+    }
+
+    /// Runs the enabled passes on the given (already verified) module.
+    ///
+    /// The coverage instrumentation runs last, so it counts the blocks of the final code.
+    pub(crate) fn run_passes(&self, module: &Module) {
+        if self.mem2reg {
+            let pass_manager = PassManager::create(());
+            pass_manager.add_promote_memory_to_register_pass();
+            pass_manager.run_on(module);
+        }
+        if self.profiling {
+            profile::instrument(module);
+        }
+        if self.coverage {
+            coverage::instrument(module);
+        }
+    }
+}
+
+/// The scheme of the identifiers of the generated modules.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ModuleNaming {
+    /// The name of the source file, e.g. `main.mrs`.
+    #[default]
+    FileName,
+    /// The name of the source file without the extension, e.g. `main`.
+    Stem,
+    /// The path of the source file as it was given to the parser, e.g. `src/main.mrs`
+    /// (or its name, if the path is not known).
+    Path,
+}
+
+impl ModuleNaming {
+    /// Returns the scheme with the given name (`file-name`, `stem` or `path`), if there is one.
+    pub fn from_name(name: &str) -> Option<ModuleNaming> {
+        match name {
+            "file-name" => Some(ModuleNaming::FileName),
+            "stem" => Some(ModuleNaming::Stem),
+            "path" => Some(ModuleNaming::Path),
+            _ => None,
+        }
+    }
+
+    /// Returns the identifier of the module generated from the source file
+    /// with the given name and path (if it is known).
+    pub fn identifier(self, file_name: &str, path: Option<&Path>) -> String {
+        match self {
+            ModuleNaming::FileName => file_name.to_owned(),
+            ModuleNaming::Stem => Path::new(file_name)
+                .file_stem()
+                .map_or(file_name.into(), |stem| stem.to_string_lossy())
+                .into_owned(),
+            ModuleNaming::Path => {
+                path.map_or(file_name.to_owned(), |path| path.display().to_string())
+            }
+        }
+    }
+}
+
+impl Default for CodeGenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The parameters of a single [code generation](crate::ast::Crate::code_gen_with) of a crate:
+/// its [options](CodeGenOptions), and how the code generation is set up.
+#[derive(Clone, Copy, Default)]
+pub struct CodeGenRequest<'a> {
+    options: CodeGenOptions,
+    cancellation: Option<&'a CancellationToken>,
+    observer: Option<&'a SharedObserver>,
+    module_name: Option<&'a str>,
+    imports: &'a [(&'a str, &'a FuncProtoASTNode)],
+    item: Option<&'a str>,
+}
+
+impl<'a> CodeGenRequest<'a> {
+    /// Creates a new `CodeGenRequest` of the whole crate with the default options.
+    pub fn new() -> CodeGenRequest<'a> {
+        CodeGenRequest::default()
+    }
+
+    /// Sets the options of the code generation.
+    pub fn with_options(self, options: CodeGenOptions) -> CodeGenRequest<'a> {
+        CodeGenRequest { options, ..self }
+    }
+
+    /// Sets the token that aborts the code generation with [`CodeGenError::Interrupted`]
+    /// when it is cancelled or times out.
+    pub fn with_cancellation(self, cancellation: &'a CancellationToken) -> CodeGenRequest<'a> {
+        CodeGenRequest {
+            cancellation: Some(cancellation),
+            ..self
+        }
+    }
+
+    /// Registers the observer notified about the diagnostics of the code generation.
+    pub fn with_observer(self, observer: &'a SharedObserver) -> CodeGenRequest<'a> {
+        CodeGenRequest {
+            observer: Some(observer),
+            ..self
+        }
+    }
+
+    /// Sets the identifier of the generated module,
+    /// overriding the [module naming](CodeGenOptions::module_naming) of the options.
+    pub fn with_module_name(self, module_name: &'a str) -> CodeGenRequest<'a> {
+        CodeGenRequest {
+            module_name: Some(module_name),
+            ..self
+        }
+    }
+
+    /// Declares the functions imported from other crates with the given prototypes
+    /// (paired with the names of their modules).
+    ///
+    /// See [`CrateASTNode::collect_imported_symbols`] and [`Project`](crate::driver::Project).
+    pub fn with_imports(
+        self,
+        imports: &'a [(&'a str, &'a FuncProtoASTNode)],
+    ) -> CodeGenRequest<'a> {
+        CodeGenRequest { imports, ..self }
+    }
+
+    /// Restricts the code generation to the function or static item with the given `name`
+    /// (and the declarations of its dependencies), reporting only the warnings encountered
+    /// in the item.
+    ///
+    /// See [`CrateASTNode::code_gen_item`].
+    pub fn with_item(self, name: &'a str) -> CodeGenRequest<'a> {
+        CodeGenRequest {
+            item: Some(name),
+            ..self
+        }
+    }
+
+    /// Returns the options of the code generation.
+    pub fn options(&self) -> CodeGenOptions {
+        self.options
+    }
+
+    /// Returns the identifier of the generated module, if it overrides the module naming.
+    pub fn module_name(&self) -> Option<&'a str> {
+        self.module_name
+    }
+
+    /// Returns the prototypes of the imported functions, paired with the names of their modules.
+    pub fn imports(&self) -> &'a [(&'a str, &'a FuncProtoASTNode)] {
+        self.imports
+    }
+
+    /// Returns the name of the only item to generate, if the code generation is restricted.
+    pub fn item(&self) -> Option<&'a str> {
+        self.item
+    }
+
+    /// Sets up the fresh code generation state according to this request.
+    pub(crate) fn configure(&self, state: &mut CodeGenState) {
+        if let Some(cancellation) = self.cancellation {
+            state.set_cancellation(cancellation.clone());
+        }
+        if let Some(observer) = self.observer {
+            state.set_observer(observer.clone());
+        }
     }
 }
 
@@ -130,12 +812,100 @@ impl CrateASTNode {
     ///
     /// It collects the global symbol declarations (functions, statics, externals, etc.)
     /// and adds them to the [`module`](CodeGenState::module) that is being generated.
+    /// The initializers of the statics are evaluated as well, in their
+    /// [dependency order](statics::initialization_order).
     ///
     /// __This method should be called before [generating the LLVM IR](CodeGen::code_gen) for the crate!__
     pub fn collect_symbols(&self, state: &mut CodeGenState) -> Result<()> {
         for item in self.items() {
             item.add_to_symbol_table(state)?;
         }
+        let statics: Vec<_> = self.statics().collect();
+        state.static_values = statics::evaluate_statics(&statics, state.options.target_layout())?;
+        Ok(())
+    }
+}
+
+impl CrateASTNode {
+    /// Declares the functions imported by the crate (see [`CrateASTNode::imports`])
+    /// in the symbol table, given the prototypes of the functions exported from the other crates,
+    /// paired with the names of their modules.
+    ///
+    /// __This method should be called before [generating the LLVM IR](CodeGen::code_gen) for the crate!__
+    ///
+    /// # Errors
+    ///
+    /// If there is no prototype for an imported function in its module,
+    /// [`CodeGenError::MissingSymbol`] is returned.
+    pub fn collect_imported_symbols(
+        &self,
+        prototypes: &[(&str, &FuncProtoASTNode)],
+        state: &mut CodeGenState,
+    ) -> Result<()> {
+        for import in self.imports() {
+            let (_, proto) = prototypes
+                .iter()
+                .find(|(module, proto)| *module == import.module() && proto.name() == import.name())
+                .ok_or_else(|| CodeGenError::MissingSymbol {
+                    symbol: import.name().into(),
+                    span: import.span(),
+                })?;
+            proto.add_to_symbol_table(state, Linkage::External)?;
+        }
+        Ok(())
+    }
+
+    /// Generates the LLVM IR only for the function or static item with the given `name`.
+    ///
+    /// The other items are left as declarations, and the ones that the item doesn't use
+    /// are removed from the [`module`](CodeGenState::module), so that it contains only
+    /// the item and its dependencies.
+    ///
+    /// __This method should be called after [collecting the symbols](CrateASTNode::collect_symbols)
+    /// of the crate!__
+    ///
+    /// # Errors
+    ///
+    /// If there is no such item, [`CodeGenError::MissingSymbol`] is returned.
+    pub fn code_gen_item(&self, name: &str, state: &mut CodeGenState) -> Result<()> {
+        let item = self
+            .find_item(name)
+            .ok_or_else(|| CodeGenError::MissingSymbol {
+                symbol: name.into(),
+                span: self.span(),
+            })?;
+        state.check_cancellation()?;
+        state.notify_item_codegen(item);
+        item.code_gen(state)?;
+
+        let module = state.module();
+        for function in module.get_functions() {
+            if function.count_basic_blocks() > 0 {
+                continue;
+            }
+            if function
+                .as_global_value()
+                .as_pointer_value()
+                .get_first_use()
+                .is_none()
+            {
+                // SAFETY: The declaration is not used anywhere (and not looked up afterwards)
+                unsafe { function.delete() };
+            } else {
+                function.set_linkage(Linkage::External);
+            }
+        }
+        for global in module.get_globals() {
+            if global.get_initializer().is_some() {
+                continue;
+            }
+            if global.as_pointer_value().get_first_use().is_none() {
+                // SAFETY: The declaration is not used anywhere (and not looked up afterwards)
+                unsafe { global.delete() };
+            } else {
+                global.set_linkage(Linkage::External);
+            }
+        }
         Ok(())
     }
 }
@@ -144,13 +914,32 @@ impl ItemASTNode {
     /// Adds the item declaration to the symbol table.
     fn add_to_symbol_table(&self, state: &mut CodeGenState) -> Result<()> {
         match &self {
-            ItemASTNode::Func(func) => func.proto().add_to_symbol_table(state)?,
-            ItemASTNode::Static(stat) => stat.add_to_symbol_table(state, false)?,
+            ItemASTNode::Func(func) => {
+                let linkage = if func.is_exported() {
+                    Linkage::External
+                } else {
+                    Linkage::Internal
+                };
+                func.proto().add_to_symbol_table(state, linkage)?;
+            }
+            ItemASTNode::Static(stat) => {
+                let linkage = if stat.visibility().is_public() {
+                    Linkage::External
+                } else {
+                    Linkage::Internal
+                };
+                stat.add_to_symbol_table(state, linkage)?;
+            }
             ItemASTNode::Extern(ext) => {
                 for item in ext.items() {
                     match item {
-                        ExternItem::Func(func) => func.add_to_symbol_table(state)?,
-                        ExternItem::Static(stat) => stat.add_to_symbol_table(state, true)?,
+                        ExternItem::Func(func) => {
+                            let fn_value = func.add_to_symbol_table(state, Linkage::External)?;
+                            fn_value.set_call_conventions(ext.abi().calling_convention());
+                        }
+                        ExternItem::Static(stat) => {
+                            stat.add_to_symbol_table(state, Linkage::External)?
+                        }
                     }
                 }
             }
@@ -160,37 +949,1605 @@ impl ItemASTNode {
 }
 
 impl FuncProtoASTNode {
-    /// Adds the function prototype to the symbol table.
-    fn add_to_symbol_table(&self, state: &mut CodeGenState) -> Result<()> {
+    /// Adds the function prototype with the given `linkage` to the symbol table,
+    /// returning the declared function.
+    fn add_to_symbol_table<'ctx>(
+        &self,
+        state: &mut CodeGenState<'ctx>,
+        linkage: Linkage,
+    ) -> Result<FunctionValue<'ctx>> {
         let name = self.name_owned();
 
         let fn_type = CodeGen::<FunctionType>::code_gen(self, state)?;
-        let fn_value = state.module().add_function(&name, fn_type, None);
+        let fn_value = state.module().add_function(&name, fn_type, Some(linkage));
+        if let Some(abi) = self.abi() {
+            fn_value.set_call_conventions(abi.calling_convention());
+        }
         //TODO Handle redeclarations
         state
             .symbol_table()
             .insert(name, fn_value.as_any_value_enum());
+        state.functions.insert(self.signature());
 
-        Ok(())
+        Ok(fn_value)
     }
 }
 
 impl StaticASTNode {
-    /// Adds the static item declaration to the symbol table.
-    fn add_to_symbol_table(&self, state: &mut CodeGenState, is_external: bool) -> Result<()> {
+    /// Adds the static item declaration with the given `linkage` to the symbol table.
+    fn add_to_symbol_table(&self, state: &mut CodeGenState, linkage: Linkage) -> Result<()> {
         let name = self.name_owned();
-        let linkage = if is_external {
-            Linkage::External
-        } else {
-            Linkage::Internal
-        };
 
-        let ty = CodeGen::<BasicTypeEnum>::code_gen(&self.ty(), state)?;
+        let ty = CodeGen::<BasicTypeEnum>::code_gen(self.ty(), state)?;
         let stat = state.module().add_global(ty, None, &name);
         stat.set_linkage(linkage);
 
         //TODO Handle redeclarations
-        state.symbol_table().insert(name, stat.as_any_value_enum());
+        state
+            .symbol_table()
+            .insert_variable(name, stat.as_pointer_value(), ty, self.ty().ty());
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use inkwell::OptimizationLevel;
+
+    use crate::ast::error::SemanticError;
+    use crate::ast::{ConstValue, PathASTNode};
+    use crate::test_util::parse;
+    use crate::token::{Position, Span};
+
+    use super::*;
+
+    #[test]
+    fn test_const_conditions() {
+        let crt = parse(
+            "fn main() -> i32 {\n    while false { return 2; };\n    if !(1 + 1 == 2) { return 3; } else { return 1; }\n}\n",
+        );
+
+        let context = Context::create();
+        let (module, warnings) = crt.code_gen_with(&context, CodeGenRequest::new()).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings
+            .iter()
+            .all(|w| matches!(w, SemanticWarning::UnreachableBranch { .. })));
+
+        let main = module.get_function("main").unwrap();
+        assert_eq!(main.count_basic_blocks(), 1);
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        let result = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn() -> i32>("main")
+                .unwrap()
+                .call()
+        };
+        assert_eq!(result, 1);
+    }
+
+    #[test]
+    fn test_constant_condition() {
+        use crate::ast::LiteralASTNode;
+        use crate::token::{Position, Span};
+
+        let context = Context::create();
+        let mut state = CodeGenState::new(&context, "test");
+        let fn_type = context.void_type().fn_type(&[], false);
+        let function = state.module().add_function("f", fn_type, None);
+        let block = context.append_basic_block(function, "start");
+        state.builder().position_at_end(block);
+
+        let span = Span::new(Position::new(), Position::new());
+        let literal = LiteralASTNode::<bool>::new(true, span);
+        let cond = state.build_condition(&literal, true).unwrap();
+        assert_eq!(cond.get_zero_extended_constant(), Some(1));
+        let cond = state.build_condition(&literal, false).unwrap();
+        assert_eq!(cond.get_zero_extended_constant(), Some(0));
+        assert!(block.get_first_instruction().is_none());
+    }
+
+    #[test]
+    fn test_redundant_bool_comparisons() {
+        let crt = parse(
+            "fn f(x: bool, y: i32) -> bool {\n\
+                let a: bool = x == true;\n\
+                let b: bool = false != x;\n\
+                let c: bool = y < 2 == false;\n\
+                let d: bool = true == false;\n\
+                return x != true;\n\
+            }\n",
+        );
+        let context = Context::create();
+        let (_, warnings) = crt.code_gen_with(&context, CodeGenRequest::new()).unwrap();
+
+        let suggestions: Vec<&str> = warnings
+            .iter()
+            .map(|w| match w {
+                SemanticWarning::RedundantBoolComparison { suggestion, .. } => suggestion.as_ref(),
+                w => panic!("Unexpected warning: {w}"),
+            })
+            .collect();
+        assert_eq!(suggestions, vec!["x", "x", "!(y < 2)", "!x"]);
+    }
+
+    #[test]
+    fn test_unit_comparisons() {
+        let crt = parse(
+            "fn f() { return; }\n\
+            fn main() -> bool {\n\
+                return f() == () && () <= f() && !(f() != f()) && !(() < ());\n\
+            }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        let result = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn() -> bool>("main")
+                .unwrap()
+                .call()
+        };
+        assert!(result);
+
+        let crt = parse("static X: bool = () == ();\nstatic Y: bool = () > ();\n");
+        let values: Vec<_> = crt
+            .root()
+            .items()
+            .iter()
+            .map(|item| match item {
+                ItemASTNode::Static(stat) => stat.value().unwrap().const_eval(),
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(
+            values,
+            vec![Some(ConstValue::Bool(true)), Some(ConstValue::Bool(false))]
+        );
+    }
+
+    #[test]
+    fn test_signed_division() {
+        let crt = parse(
+            "pub fn div(a: i32, b: i32) -> i32 { return a / b; }\n\
+            pub fn rem(a: i32, b: i32) -> i32 { return a % b; }\n",
+        );
+        type BinFn = unsafe extern "C" fn(i32, i32) -> i32;
+
+        for overflow_checks in [true, false] {
+            let context = Context::create();
+            let options = CodeGenOptions::new().with_overflow_checks(overflow_checks);
+            let (module, _) = crt
+                .code_gen_with(&context, CodeGenRequest::new().with_options(options))
+                .unwrap();
+            let ir = module.print_to_string().to_string();
+            assert_eq!(ir.contains("call void @llvm.trap()"), overflow_checks);
+
+            let engine = module
+                .create_jit_execution_engine(OptimizationLevel::None)
+                .unwrap();
+            let (div, rem) = unsafe {
+                (
+                    engine.get_function::<BinFn>("div").unwrap(),
+                    engine.get_function::<BinFn>("rem").unwrap(),
+                )
+            };
+
+            // Rounding towards zero, as in Rust
+            for (a, b) in [(7, 2), (-7, 2), (7, -2), (-7, -2), (i32::MAX, -1), (0, -5)] {
+                unsafe {
+                    assert_eq!(div.call(a, b), a / b, "{a} / {b}");
+                    assert_eq!(rem.call(a, b), a % b, "{a} % {b}");
+                }
+            }
+            if !overflow_checks {
+                unsafe {
+                    assert_eq!(div.call(i32::MIN, -1), i32::MIN.wrapping_div(-1));
+                    assert_eq!(rem.call(i32::MIN, -1), i32::MIN.wrapping_rem(-1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_float_comparisons_with_nan() {
+        let ops = [
+            ("eq", "=="),
+            ("ne", "!="),
+            ("lt", "<"),
+            ("gt", ">"),
+            ("le", "<="),
+            ("ge", ">="),
+        ];
+        let source: String = ops
+            .iter()
+            .map(|(name, op)| {
+                format!("pub fn {name}(a: f64, b: f64) -> bool {{ return a {op} b; }}\n")
+            })
+            .collect();
+        let crt = parse(&source);
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+
+        let expected: [fn(f64, f64) -> bool; 6] = [
+            |a, b| a == b,
+            |a, b| a != b,
+            |a, b| a < b,
+            |a, b| a > b,
+            |a, b| a <= b,
+            |a, b| a >= b,
+        ];
+        let values = [f64::NAN, 0.0, -1.5, f64::INFINITY];
+        for ((name, op), expected) in ops.iter().zip(expected) {
+            let function = unsafe {
+                engine
+                    .get_function::<unsafe extern "C" fn(f64, f64) -> bool>(name)
+                    .unwrap()
+            };
+            for a in values {
+                for b in values {
+                    let result = unsafe { function.call(a, b) };
+                    assert_eq!(result, expected(a, b), "{a} {op} {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_call_signatures() {
+        let source = "extern \"C\" { fn ext(x: f64); }\n\
+            fn f(a: i32, b: bool) -> i32 { return a; }\n";
+        let crt = parse(source);
+        let context = Context::create();
+        let mut state = CodeGenState::new(&context, "test");
+        crt.root().collect_symbols(&mut state).unwrap();
+
+        let f = state.functions().get("f").unwrap();
+        assert_eq!(f.param_types(), [Type::I32, Type::Bool]);
+        assert_eq!(*f.return_type(), Type::I32);
+        let ext = state.functions().get("ext").unwrap();
+        assert_eq!((ext.param_count(), ext.return_type()), (1, &Type::Unit));
+
+        let call = |body: &str| {
+            let crt = parse(&format!("{source}fn main() -> i32 {{ {body} }}\n"));
+            let context = Context::create();
+            crt.code_gen(&context).map(|_| ())
+        };
+        assert!(call("return f(1, true);").is_ok());
+        assert!(matches!(
+            call("return f(1);"),
+            Err(CodeGenError::SemanticError(
+                SemanticError::ArgumentCountMismatch {
+                    expected: 2,
+                    actual: 1,
+                    ..
+                }
+            ))
+        ));
+        assert!(matches!(
+            call("unsafe { ext(1); }; return 0;"),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::F64,
+                actual: Type::I32,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_evaluation_order() {
+        thread_local! {
+            static TRACE: std::cell::RefCell<Vec<i32>> = const { std::cell::RefCell::new(Vec::new()) };
+        }
+        extern "C" fn record(n: i32) -> i32 {
+            TRACE.with(|trace| trace.borrow_mut().push(n));
+            n
+        }
+        fn traced<T>(call: impl FnOnce() -> T) -> (T, Vec<i32>) {
+            TRACE.with(|trace| trace.borrow_mut().clear());
+            let result = call();
+            (result, TRACE.with(|trace| trace.take()))
+        }
+
+        let crt = parse(
+            "extern \"C\" { fn record(n: i32) -> i32; }\n\
+            fn r(n: i32) -> i32 { unsafe { return record(n); }; }\n\
+            fn f(a: i32, b: i32, c: i32) -> i32 { return a * 100 + b * 10 + c; }\n\
+            pub fn args() -> i32 { return f(r(1), r(2), r(3)); }\n\
+            pub fn nested() -> i32 { return f(r(1), f(r(2), r(3), r(4)), r(5)); }\n\
+            pub fn arith() -> i32 { return r(1) - r(2) * r(3) + r(4); }\n\
+            pub fn comp() -> bool { return (r(1) < r(2)) == (r(3) > r(4)); }\n\
+            pub fn lazy() -> bool { return r(1) > 5 || r(2) == 2 && r(3) > 5 || r(4) == 4; }\n\
+            pub fn short() -> bool { return r(1) == 1 || r(2) == 2; }\n\
+            pub fn assign() { let mut x: i32 = 0; x = f(r(1), r(2), r(3)); return; }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        engine.add_global_mapping(
+            &module.get_function("record").unwrap(),
+            record as extern "C" fn(i32) -> i32 as usize,
+        );
+
+        // Each function is called through its real signature
+        let (args, nested, arith) = unsafe {
+            (
+                engine
+                    .get_function::<unsafe extern "C" fn() -> i32>("args")
+                    .unwrap(),
+                engine
+                    .get_function::<unsafe extern "C" fn() -> i32>("nested")
+                    .unwrap(),
+                engine
+                    .get_function::<unsafe extern "C" fn() -> i32>("arith")
+                    .unwrap(),
+            )
+        };
+        let (comp, lazy, short) = unsafe {
+            (
+                engine
+                    .get_function::<unsafe extern "C" fn() -> bool>("comp")
+                    .unwrap(),
+                engine
+                    .get_function::<unsafe extern "C" fn() -> bool>("lazy")
+                    .unwrap(),
+                engine
+                    .get_function::<unsafe extern "C" fn() -> bool>("short")
+                    .unwrap(),
+            )
+        };
+        let assign = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn()>("assign")
+                .unwrap()
+        };
+
+        unsafe {
+            assert_eq!(traced(|| args.call()), (123, vec![1, 2, 3]));
+            assert_eq!(
+                traced(|| nested.call()),
+                (100 + 10 * 234 + 5, vec![1, 2, 3, 4, 5])
+            );
+            assert_eq!(traced(|| arith.call()), (-1, vec![1, 2, 3, 4]));
+            assert_eq!(traced(|| comp.call()), (false, vec![1, 2, 3, 4]));
+            assert_eq!(traced(|| lazy.call()), (true, vec![1, 2, 3, 4]));
+            assert_eq!(traced(|| short.call()), (true, vec![1]));
+            assert_eq!(traced(|| assign.call()), ((), vec![1, 2, 3]));
+        }
+    }
+
+    #[test]
+    fn test_uncollected_functions() {
+        let crt = parse("pub fn f() {}\n");
+        let context = Context::create();
+        let name_span = Span::new(Position::new_at(1, 8), Position::new_at(1, 9));
+
+        // The collection phase was skipped
+        let mut state = CodeGenState::new(&context, "uncollected");
+        assert_eq!(
+            crt.root().code_gen_item("f", &mut state),
+            Err(CodeGenError::MissingSymbol {
+                symbol: "f".into(),
+                span: name_span,
+            })
+        );
+
+        // The name of the function is bound to a different value
+        let value = context.i32_type().const_zero().as_any_value_enum();
+        state.symbol_table().insert("f".into(), value);
+        assert_eq!(
+            crt.root().code_gen_item("f", &mut state),
+            Err(CodeGenError::InvalidLLVMValueType {
+                message: "`f` is not a function".into(),
+                span: name_span,
+            })
+        );
+    }
+
+    #[test]
+    fn test_imported_symbols() {
+        let crt = parse("use a::f;\nfn g() -> i32 { return f(); }\n");
+        let other = parse("pub fn f() -> i32 { return 1; }\n");
+        let proto = match &other.root().items()[0] {
+            ItemASTNode::Func(func) => func.proto(),
+            _ => unreachable!(),
+        };
+        let context = Context::create();
+
+        // A function with the same name exported from a different module
+        let mut state = CodeGenState::new(&context, "imports");
+        assert_eq!(
+            crt.root()
+                .collect_imported_symbols(&[("b", proto)], &mut state),
+            Err(CodeGenError::MissingSymbol {
+                symbol: "f".into(),
+                span: crt.root().imports()[0].span(),
+            })
+        );
+
+        let mut state = CodeGenState::new(&context, "imports");
+        crt.root().collect_symbols(&mut state).unwrap();
+        crt.root()
+            .collect_imported_symbols(&[("b", proto), ("a", proto)], &mut state)
+            .unwrap();
+        crt.root().code_gen(&mut state).unwrap();
+        assert!(state.module().get_function("f").is_some());
+    }
+
+    #[test]
+    fn test_with_scope() {
+        let context = Context::create();
+        let mut state = CodeGenState::new(&context, "scopes");
+        let value = context.bool_type().const_zero().as_any_value_enum();
+        let result: Result<()> = state.with_scope(|state| {
+            state.symbol_table().insert("x".into(), value);
+            assert!(state.symbol_table().get("x").is_some());
+            state.emit(|builder| builder.build_return(None))?;
+            Ok(())
+        });
+
+        // The builder isn't positioned in any block, and the scope is closed despite the error
+        assert!(matches!(result, Err(CodeGenError::BuilderError(_))));
+        assert!(state.symbol_table().get("x").is_none());
+    }
+
+    #[test]
+    fn test_inexact_float_lint() {
+        use crate::codegen::session::CompilationSession;
+
+        let crt = parse("pub fn f() -> f64 { return 1.50 + 0.1 * 2.5e0; }\n");
+        // The literals are printed as they are written
+        assert!(crt.to_source().contains("1.50 + 0.1 * 2.5e0"));
+
+        let warnings = |options: CodeGenOptions| {
+            let session = CompilationSession::with_options(options);
+            session.compile(&crt).unwrap().warnings().to_vec()
+        };
+        assert!(warnings(CodeGenOptions::new()).is_empty());
+        assert_eq!(
+            warnings(CodeGenOptions::new().with_inexact_float_lint(true)),
+            [SemanticWarning::InexactFloatLiteral {
+                literal: "0.1".into(),
+                span: Span::new(Position::new_at(1, 35), Position::new_at(1, 38)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_extern_abis() {
+        use crate::abi::Abi;
+
+        let crt = parse(
+            "extern \"C\" { fn c(); }\n\
+            extern \"system\" { fn system(); }\n\
+            extern \"system\" fn qualified();\n\
+            pub extern \"system\" fn defined() {}\n\
+            pub fn f() { unsafe { c(); system(); qualified(); }; defined(); }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        for (name, abi) in [
+            ("c", Abi::C),
+            ("system", Abi::System),
+            ("qualified", Abi::System),
+            ("defined", Abi::System),
+        ] {
+            let function = module.get_function(name).unwrap();
+            assert_eq!(function.get_call_conventions(), abi.calling_convention());
+        }
+        assert!(module.verify().is_ok());
+    }
+
+    #[test]
+    fn test_non_finite_floats() {
+        let crt = parse(
+            "pub fn inf() -> f64 { return f64::INFINITY; }\n\
+            pub fn neg_inf() -> f64 { return f64::NEG_INFINITY; }\n\
+            pub fn nan() -> f64 { return f64::NAN; }\n\
+            pub fn overflow() -> f64 { return 1.0e400; }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        let call = |name: &str| unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn() -> f64>(name)
+                .unwrap()
+                .call()
+        };
+        assert_eq!(call("inf"), f64::INFINITY);
+        assert_eq!(call("neg_inf"), f64::NEG_INFINITY);
+        assert!(call("nan").is_nan());
+        assert_eq!(call("overflow"), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_literal_suffixes() {
+        let crt = parse(
+            "fn g(a: i32, b: f64) -> f64 { return 2.5f64; }\n\
+            pub fn f() -> f64 { let x: i32 = 1i32; return g(3i32, 2f64) + g(4, 1.0); }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        // SAFETY: The signature of `f` is known
+        let result = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn() -> f64>("f")
+                .unwrap()
+                .call()
+        };
+        assert_eq!(result, 5.0);
+
+        // An integer literal cannot adopt a floating-point type, even if it is expected
+        let crt = parse("pub fn f() { let x: f64 = 1; }\n");
+        let result = crt.code_gen(&context);
+        assert!(matches!(
+            result,
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::F64,
+                actual: Type::I32,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_implicit_unit_return() {
+        let crt = parse(
+            "fn empty() {}\n\
+            fn unit() -> () { let x: i32 = 1; }\n\
+            fn nested() { { let x: i32 = 1; }; }\n\
+            fn explicit() { return; }\n\
+            fn main() -> i32 { empty(); unit(); nested(); explicit(); return 7; }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        for name in ["empty", "unit", "nested", "explicit"] {
+            let fn_value = module.get_function(name).unwrap();
+            let terminators: Vec<_> = fn_value
+                .get_basic_block_iter()
+                .filter_map(|bb| bb.get_terminator())
+                .collect();
+            assert_eq!(terminators.len(), 1, "`{name}` should return exactly once");
+        }
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        let result = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn() -> i32>("main")
+                .unwrap()
+                .call()
+        };
+        assert_eq!(result, 7);
+
+        let crt = parse("fn f() -> i32 {}\n");
+        let context = Context::create();
+        assert!(crt.code_gen(&context).is_err());
+    }
+
+    #[test]
+    fn test_unroll_hints() {
+        let crt = parse(
+            "#[unroll(4)]\npub fn spin(n: i32) -> i32 { while n > 0 {} loop { return n; } }\n\
+            #[unroll(2)]\npub fn no_loops() {}\n\
+            #[unroll(x)]\npub fn bad_count() { loop {} }\n",
+        );
+        let context = Context::create();
+        let (module, warnings) = crt.code_gen_with(&context, CodeGenRequest::new()).unwrap();
+        assert!(module.verify().is_ok());
+        let span = |start: (usize, usize), end: (usize, usize)| {
+            Span::new(
+                Position::new_at(start.0, start.1),
+                Position::new_at(end.0, end.1),
+            )
+        };
+        assert_eq!(
+            warnings,
+            [
+                SemanticWarning::IgnoredUnrollHint {
+                    reason: "the loop never repeats",
+                    span: span((2, 50), (2, 63)),
+                },
+                SemanticWarning::IgnoredUnrollHint {
+                    reason: "the function contains no loops",
+                    span: span((3, 1), (3, 13)),
+                },
+                SemanticWarning::IgnoredUnrollHint {
+                    reason: "expected a positive loop count",
+                    span: span((5, 1), (5, 13)),
+                },
+            ]
+        );
+
+        // Only the back edge of the `while` loop gets the hint
+        let ir = module.print_to_string().to_string();
+        assert_eq!(ir.matches("!llvm.loop").count(), 1);
+        assert!(ir.contains("= distinct !{!0, !1}"));
+        assert!(ir.contains("!{!\"llvm.loop.unroll.count\", i32 4}"));
+    }
+
+    #[test]
+    fn test_unreachable_code() {
+        let crt = parse(
+            "pub fn early(x: i32) -> i32 {\n    { return x; };\n    let y: i32 = 2;\n    return y;\n}\n\
+            pub fn in_loop(n: i32) -> i32 { while n > 0 { return n; let y: i32 = 1; }; return 0; }\n\
+            pub fn diverging_let() -> i32 { let x: i32 = return 3; }\n",
+        );
+        let context = Context::create();
+        let (module, warnings) = crt.code_gen_with(&context, CodeGenRequest::new()).unwrap();
+        assert!(module.verify().is_ok());
+        let span = |start: (usize, usize), end: (usize, usize)| {
+            Span::new(
+                Position::new_at(start.0, start.1),
+                Position::new_at(end.0, end.1),
+            )
+        };
+        assert_eq!(
+            warnings,
+            [
+                SemanticWarning::UnreachableCode {
+                    span: span((3, 5), (4, 14)),
+                },
+                SemanticWarning::UnreachableCode {
+                    span: span((6, 57), (6, 72)),
+                },
+            ]
+        );
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let early = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("early")
+                .unwrap();
+            assert_eq!(early.call(5), 5);
+            let in_loop = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("in_loop")
+                .unwrap();
+            assert_eq!((in_loop.call(3), in_loop.call(0)), (3, 0));
+            let diverging_let = engine
+                .get_function::<unsafe extern "C" fn() -> i32>("diverging_let")
+                .unwrap();
+            assert_eq!(diverging_let.call(), 3);
+        }
+    }
+
+    #[test]
+    fn test_if_statements() {
+        let crt = parse(
+            "pub fn sign(x: i32) -> i32 {\n\
+                if x > 0 { return 1; } else if x < 0 { return -1 } else { true }\n\
+                return 0;\n\
+            }\n\
+            pub fn abs(x: i32) -> i32 { if x < 0 { return -x; } return x; }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        assert!(module.verify().is_ok());
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let sign = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("sign")
+                .unwrap();
+            assert_eq!((sign.call(7), sign.call(-7), sign.call(0)), (1, -1, 0));
+            let abs = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("abs")
+                .unwrap();
+            assert_eq!((abs.call(-3), abs.call(3)), (3, 3));
+        }
+    }
+
+    #[test]
+    fn test_if_values() {
+        let crt = parse(
+            "pub fn pick(c: bool) -> i32 { return if c { 1 } else { 2 }; }\n\
+            pub fn sign(x: i32) -> i32 { return if x > 0 { 1 } else if x < 0 { -1 } else { 0 }; }\n\
+            pub fn half(x: i32) -> f64 {\n\
+                let y: f64 = if x < 0 { return 0.0; } else { 0.5 };\n\
+                let z: () = if x > 9 { } else { };\n\
+                return if x > 1 { 2.0 } else { 1.0 };\n\
+            }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        assert!(module.verify().is_ok());
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let pick = engine
+                .get_function::<unsafe extern "C" fn(bool) -> i32>("pick")
+                .unwrap();
+            assert_eq!((pick.call(true), pick.call(false)), (1, 2));
+            let sign = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("sign")
+                .unwrap();
+            assert_eq!((sign.call(7), sign.call(-7), sign.call(0)), (1, -1, 0));
+            let half = engine
+                .get_function::<unsafe extern "C" fn(i32) -> f64>("half")
+                .unwrap();
+            assert_eq!((half.call(-1), half.call(1), half.call(5)), (0.0, 1.0, 2.0));
+        }
+
+        let crt = parse("pub fn f(c: bool) { let x: i32 = if c { 1 } else { true }; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::I32,
+                actual: Type::Bool,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_unit_calls() {
+        let crt = parse(
+            "fn f() { return; }\nfn main() -> i32 {\n    f();\n    let x: () = f();\n    return 0;\n}\n",
+        );
+        let context = Context::create();
+        assert!(crt.code_gen(&context).is_ok());
+
+        let crt = parse(
+            "fn f() { return; }\nfn main() -> i32 {\n    let y: i32 = f();\n    return y;\n}\n",
+        );
+        let context = Context::create();
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::I32,
+                actual: Type::Unit,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_void_externs() {
+        thread_local! {
+            static LOG: std::cell::RefCell<Vec<i32>> = const { std::cell::RefCell::new(Vec::new()) };
+        }
+        extern "C" fn log(n: i32) {
+            LOG.with(|log| log.borrow_mut().push(n));
+        }
+        extern "C" fn flush() {
+            LOG.with(|log| log.borrow_mut().push(0));
+        }
+
+        let crt = parse(
+            "extern \"C\" { fn log(n: i32); fn flush() -> (); }\n\
+            fn last(n: i32) { unsafe { return log(n); }; }\n\
+            pub fn run(n: i32) {\n\
+                unsafe { log(n); let u: () = flush(); };\n\
+                last(n + 1);\n\
+            }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("declare void @log(i32)"));
+        assert!(ir.contains("declare void @flush()"));
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        engine.add_global_mapping(
+            &module.get_function("log").unwrap(),
+            log as extern "C" fn(i32) as usize,
+        );
+        engine.add_global_mapping(
+            &module.get_function("flush").unwrap(),
+            flush as extern "C" fn() as usize,
+        );
+        unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn(i32)>("run")
+                .unwrap()
+                .call(5)
+        };
+        assert_eq!(LOG.with(|log| log.take()), [5, 0, 6]);
+    }
+
+    #[test]
+    fn test_unit_values() {
+        let crt = parse(
+            "fn f() { return (); }\n\
+            fn id(u: ()) -> () { return u; }\n\
+            fn g(u: (), x: i32) -> i32 { return x; }\n\
+            fn main() -> i32 {\n\
+                let u: () = f();\n\
+                let v: () = id(());\n\
+                id(f());\n\
+                return g((), 3);\n\
+            }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("define internal void @f()"));
+        assert!(ir.contains("define internal void @id({} %u)"));
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        let result = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn() -> i32>("main")
+                .unwrap()
+                .call()
+        };
+        assert_eq!(result, 3);
+    }
+
+    #[test]
+    fn test_same_scope_shadowing() {
+        // The initializer is generated before the new `x` shadows the parameter
+        let crt = parse(
+            "fn f(x: i32) {\n    let x: i32 = x + 1;\n    let x: bool = true;\n    return;\n}\n",
+        );
+        let context = Context::create();
+        let options = CodeGenOptions::new().with_mem2reg(false);
+        let (module, _) = crt
+            .code_gen_with(&context, CodeGenRequest::new().with_options(options))
+            .unwrap();
+
+        let ir = module
+            .get_function("f")
+            .unwrap()
+            .print_to_string()
+            .to_string();
+        assert!(ir.contains("add i32 %x, 1"));
+        assert!(ir.contains("%x1 = alloca i32"));
+        assert!(ir.contains("%x2 = alloca i1"));
+    }
+
+    #[test]
+    fn test_variable_reads() {
+        let crt = parse(
+            "static K: i32 = 40;\n\
+            static mut N: i32 = 1;\n\
+            pub fn f(x: i32) -> i32 {\n\
+                let y: i32 = x * 2;\n\
+                let y: i32 = y + 1;\n\
+                unsafe { N = N + y; }\n\
+                return unsafe { N } + K + x;\n\
+            }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        assert!(module.verify().is_ok());
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        let f = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("f")
+                .unwrap()
+        };
+        // N = 1 + 7, then 8 + 40 + 3
+        assert_eq!(unsafe { f.call(3) }, 51);
+        // N = 8 + 3
+        assert_eq!(unsafe { f.call(1) }, 52);
+
+        let mut state = CodeGenState::new(&context, "missing");
+        let span = Span::new(Position::new_at(1, 1), Position::new_at(1, 2));
+        let path = PathASTNode::new("z".into(), span);
+        assert_eq!(
+            CodeGen::<AnyValueEnum>::code_gen(&path, &mut state),
+            Err(CodeGenError::MissingSymbol {
+                symbol: "z".into(),
+                span,
+            })
+        );
+    }
+
+    #[test]
+    fn test_mem2reg_instruction_counts() {
+        let corpus = [
+            "fn f(a: i32, b: i32) -> i32 {\n    let x: i32 = a + b;\n    let y: i32 = a * b;\n    return a - b;\n}\n",
+            "fn g(c: bool) {\n    let mut n: i32 = 0;\n    n = 5;\n    return;\n}\n",
+            "fn main() -> i32 {\n    let k: bool = 1 < 2;\n    let m: f64 = 2.5;\n    return 0;\n}\n",
+        ];
+
+        for source in corpus {
+            let crt = parse(source);
+            let stats = |mem2reg| {
+                let context = Context::create();
+                let options = CodeGenOptions::new().with_mem2reg(mem2reg);
+                let (module, _) = crt
+                    .code_gen_with(&context, CodeGenRequest::new().with_options(options))
+                    .unwrap();
+                stats::IrStats::of(&module)
+            };
+
+            let before = stats(false);
+            let after = stats(true);
+            assert_eq!(before.functions(), after.functions());
+            assert!(
+                after.instructions() < before.instructions(),
+                "{before} -> {after}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_unsafe_block_values() {
+        let crt = parse(
+            "fn square(n: i32) -> i32 { return n * n; }\n\
+            pub fn f(n: i32) -> i32 {\n\
+                let a: i32 = unsafe { square(n) };\n\
+                let b: f64 = unsafe { let k: i32 = n; 1.5 };\n\
+                let c: bool = unsafe { n > 1 };\n\
+                return unsafe { let m: i32 = n; square(n) * 2 } + 1;\n\
+            }\n",
+        );
+        let context = Context::create();
+        let (module, warnings) = crt.code_gen_with(&context, CodeGenRequest::new()).unwrap();
+        // None of the blocks contains an unsafe operation
+        assert_eq!(warnings.len(), 4);
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        let result = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("f")
+                .unwrap()
+                .call(3)
+        };
+        assert_eq!(result, 19);
+
+        let crt = parse("pub fn f() { let x: i32 = unsafe { let y: i32 = 1; true }; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::I32,
+                actual: Type::Bool,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_integer_types() {
+        let crt = parse(
+            "pub fn udiv(a: u32, b: u32) -> u32 { return a / b; }\n\
+            pub fn ult(a: u8, b: u8) -> bool { return a < b; }\n\
+            pub fn widen(x: u8, y: i8) -> i64 { let z: u64 = 10; return x as i64 + y as i64 + z as i64; }\n\
+            pub fn sat(x: f64) -> u8 { return x as u8; }\n\
+            pub fn half(x: u16) -> f32 { return x as f32 + 0.5; }\n\
+            pub fn flag(c: bool) -> u8 { return c as u8; }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        assert!(module.verify().is_ok());
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let udiv = engine
+                .get_function::<unsafe extern "C" fn(u32, u32) -> u32>("udiv")
+                .unwrap();
+            assert_eq!(udiv.call(u32::MAX, 2), u32::MAX / 2);
+            let ult = engine
+                .get_function::<unsafe extern "C" fn(u8, u8) -> bool>("ult")
+                .unwrap();
+            assert_eq!((ult.call(200, 1), ult.call(1, 200)), (false, true));
+            let widen = engine
+                .get_function::<unsafe extern "C" fn(u8, i8) -> i64>("widen")
+                .unwrap();
+            assert_eq!(widen.call(200, -56), 154);
+            let sat = engine
+                .get_function::<unsafe extern "C" fn(f64) -> u8>("sat")
+                .unwrap();
+            assert_eq!(
+                (sat.call(300.0), sat.call(-1.0), sat.call(7.9)),
+                (255, 0, 7)
+            );
+            let half = engine
+                .get_function::<unsafe extern "C" fn(u16) -> f32>("half")
+                .unwrap();
+            assert_eq!(half.call(u16::MAX), 65535.5);
+            let flag = engine
+                .get_function::<unsafe extern "C" fn(bool) -> u8>("flag")
+                .unwrap();
+            assert_eq!((flag.call(true), flag.call(false)), (1, 0));
+        }
+
+        let crt = parse("pub fn f(x: u8) -> u8 { return -x; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::UnsupportedType { .. })
+        ));
+        let crt = parse("pub fn f(x: u8) { let y: i32 = x; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::I32,
+                actual: Type::U8,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_integer_literal_ranges() {
+        let crt = parse(
+            "pub fn max_i8() -> i8 { return 127i8; }\n\
+            pub fn min_i8() -> i8 { return -128i8; }\n\
+            pub fn max_u8() -> u8 { let x: u8 = 255; return x; }\n\
+            pub fn max_u64() -> u64 { return 18446744073709551615u64; }\n\
+            static MIN: i64 = -9223372036854775808;\n\
+            pub fn min_i64() -> i64 { return MIN; }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let max_i8 = engine
+                .get_function::<unsafe extern "C" fn() -> i8>("max_i8")
+                .unwrap();
+            let min_i8 = engine
+                .get_function::<unsafe extern "C" fn() -> i8>("min_i8")
+                .unwrap();
+            assert_eq!((max_i8.call(), min_i8.call()), (i8::MAX, i8::MIN));
+            let max_u8 = engine
+                .get_function::<unsafe extern "C" fn() -> u8>("max_u8")
+                .unwrap();
+            assert_eq!(max_u8.call(), u8::MAX);
+            let max_u64 = engine
+                .get_function::<unsafe extern "C" fn() -> u64>("max_u64")
+                .unwrap();
+            assert_eq!(max_u64.call(), u64::MAX);
+            let min_i64 = engine
+                .get_function::<unsafe extern "C" fn() -> i64>("min_i64")
+                .unwrap();
+            assert_eq!(min_i64.call(), i64::MIN);
+        }
+
+        for (source, expected_value, expected_ty) in [
+            ("pub fn f() -> i8 { return 128i8; }\n", 128, Type::I8),
+            ("pub fn f() -> i8 { return -129i8; }\n", -129, Type::I8),
+            ("pub fn f() -> u8 { return 256u8; }\n", 256, Type::U8),
+            ("pub fn f() { let x: u8 = 256; }\n", 256, Type::U8),
+            (
+                "pub fn f() { let x: i32 = 3000000000; }\n",
+                3000000000,
+                Type::I32,
+            ),
+            (
+                "pub fn f() { let x: u64 = 18446744073709551616; }\n",
+                1 << 64,
+                Type::U64,
+            ),
+            ("static S: u8 = 255 + 1;\n", 256, Type::U8),
+        ] {
+            let result = parse(source).code_gen(&context);
+            assert!(
+                matches!(
+                    &result,
+                    Err(CodeGenError::SemanticError(SemanticError::IntOutOfRange { value, ty, .. }))
+                        if *value == expected_value && *ty == expected_ty
+                ),
+                "{}: {:?}",
+                source,
+                result.err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_do_while_loops() {
+        let crt = parse(
+            "#[unroll(2)]\n\
+            pub fn count(n: i32) -> i32 { let mut i: i32 = 0; do { i = i + 1; } while i < n; return i; }\n\
+            pub fn first(n: i32) -> i32 { do { return n; } while n > 0; return 0; }\n",
+        );
+        let context = Context::create();
+        let (module, warnings) = crt.code_gen_with(&context, CodeGenRequest::new()).unwrap();
+        assert!(module.verify().is_ok());
+        // The loop in `first` never checks its condition, so both the condition
+        // and the code after the loop are unreachable
+        assert!(matches!(
+            warnings.as_slice(),
+            [
+                SemanticWarning::UnreachableCode { .. },
+                SemanticWarning::UnreachableCode { .. }
+            ]
+        ));
+
+        // The back edge of the desugared loop gets the hint
+        let ir = module.print_to_string().to_string();
+        assert_eq!(ir.matches("!llvm.loop").count(), 1);
+        assert!(ir.contains("!{!\"llvm.loop.unroll.count\", i32 2}"));
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let count = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("count")
+                .unwrap();
+            // The body is executed at least once
+            assert_eq!((count.call(0), count.call(5)), (1, 5));
+            let first = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("first")
+                .unwrap();
+            assert_eq!(first.call(-3), -3);
+        }
+    }
+
+    #[test]
+    fn test_for_loops() {
+        let crt = parse(
+            "pub fn sum(n: i32) -> i32 { let mut s: i32 = 0; for i in 0..n { s = s + i; } return s; }\n\
+            pub fn sum_incl(n: i32) -> i32 { let mut s: i32 = 0; for i in 1..=n { s = s + i; } return s; }\n\
+            pub fn count(a: u8, b: u8) -> u8 { let mut c: u8 = 0; for _ in a..=b { c = c + 1; } return c; }\n\
+            pub fn last() -> i32 { let mut c: i32 = 0; for _ in 2147483646..=2147483647 { c = c + 1; } return c; }\n",
+        );
+        let context = Context::create();
+        let (module, warnings) = crt.code_gen_with(&context, CodeGenRequest::new()).unwrap();
+        assert!(module.verify().is_ok());
+        assert!(warnings.is_empty());
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let sum = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("sum")
+                .unwrap();
+            assert_eq!((sum.call(0), sum.call(-5), sum.call(5)), (0, 0, 10));
+            let sum_incl = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("sum_incl")
+                .unwrap();
+            assert_eq!(
+                (sum_incl.call(0), sum_incl.call(1), sum_incl.call(100)),
+                (0, 1, 5050)
+            );
+            // The range over `u8` is compared as unsigned, and it can end at `u8::MAX`
+            let count = engine
+                .get_function::<unsafe extern "C" fn(u8, u8) -> u8>("count")
+                .unwrap();
+            assert_eq!(
+                (count.call(10, 20), count.call(200, 255), count.call(3, 2)),
+                (11, 56, 0)
+            );
+            // The counter of an inclusive range doesn't overflow after the last iteration
+            let last = engine
+                .get_function::<unsafe extern "C" fn() -> i32>("last")
+                .unwrap();
+            assert_eq!(last.call(), 2);
+        }
+
+        // The counter of the desugared loop is incremented by an integer
+        let crt = parse("pub fn f() { for _ in 0.5..2.0 {} }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::F64,
+                actual: Type::I32,
+                ..
+            })
+        ));
+        // The type of the start is not inferred from the end if it has a suffix
+        let crt = parse("pub fn f(n: u8) { for _ in 0i32..n {} }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::I32,
+                actual: Type::U8,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_string_literals() {
+        extern "C" fn length(s: *const std::ffi::c_char) -> u64 {
+            unsafe { std::ffi::CStr::from_ptr(s) }.to_bytes().len() as u64
+        }
+
+        let crt = parse(
+            "extern \"C\" { fn length(s: &str) -> u64; }\n\
+            fn id(s: &str) -> &str { return s; }\n\
+            pub fn literal() -> u64 { unsafe { return length(\"h\\u{e9}llo\\n\"); }; }\n\
+            pub fn variable() -> u64 { let s: &str = id(\"\"); unsafe { return length(s); }; }\n\
+            pub fn same() -> u64 { unsafe { return length(\"a\\\"b\") + length(\"a\\\"b\"); }; }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        assert!(module.verify().is_ok());
+        // The identical literals share the null-terminated constant
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("private unnamed_addr constant [8 x i8] c\"h\\C3\\A9llo\\0A\\00\""));
+        assert_eq!(ir.matches("c\"a\\22b\\00\"").count(), 1);
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        engine.add_global_mapping(
+            &module.get_function("length").unwrap(),
+            length as extern "C" fn(*const std::ffi::c_char) -> u64 as usize,
+        );
+        let call = |name: &str| unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn() -> u64>(name)
+                .unwrap()
+                .call()
+        };
+        assert_eq!((call("literal"), call("variable"), call("same")), (7, 0, 6));
+
+        let crt = parse("pub fn f() -> bool { return \"a\" == \"a\"; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::UnsupportedType { .. })
+        ));
+        let crt = parse("pub fn f() -> i32 { return 1 + \"a\"; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::UnsupportedType { .. })
+        ));
+        let crt = parse("pub fn f() { let x: i32 = \"a\"; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::I32,
+                actual: Type::Str,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_builtin_functions() {
+        let crt = parse(
+            "pub fn clamp(x: i32) -> i32 { return max(-5, min(x, 5)); }\n\
+            pub fn umax(a: u8, b: u8) -> u8 { return max(a, b); }\n\
+            pub fn dist(a: i64, b: i64) -> i64 { return abs(a - b); }\n\
+            pub fn smaller(a: f64, b: f64) -> f64 { return min(a, b); }\n\
+            pub fn hyp(a: f32, b: f32) -> f32 { return sqrt(a * a + b * b) + abs(-0f32); }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        assert!(module.verify().is_ok());
+        // The unsigned maximum is used for `u8`
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("@llvm.umax.i8"));
+        assert!(ir.contains("@llvm.sqrt.f32"));
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let clamp = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("clamp")
+                .unwrap();
+            assert_eq!((clamp.call(-9), clamp.call(3), clamp.call(9)), (-5, 3, 5));
+            let umax = engine
+                .get_function::<unsafe extern "C" fn(u8, u8) -> u8>("umax")
+                .unwrap();
+            assert_eq!(umax.call(200, 100), 200);
+            let dist = engine
+                .get_function::<unsafe extern "C" fn(i64, i64) -> i64>("dist")
+                .unwrap();
+            assert_eq!((dist.call(3, 10), dist.call(i64::MIN, 0)), (7, i64::MIN));
+            let smaller = engine
+                .get_function::<unsafe extern "C" fn(f64, f64) -> f64>("smaller")
+                .unwrap();
+            assert_eq!(
+                (smaller.call(1.5, -2.0), smaller.call(f64::NAN, 3.0)),
+                (-2.0, 3.0)
+            );
+            let hyp = engine
+                .get_function::<unsafe extern "C" fn(f32, f32) -> f32>("hyp")
+                .unwrap();
+            assert_eq!(hyp.call(3.0, 4.0), 5.0);
+        }
+
+        // The user-defined `sqrt` shadows the built-in one in the whole crate
+        let crt = parse(
+            "fn sqrt(x: i32) -> i32 { return x; }\n\
+            pub fn shadowed() -> i32 { return sqrt(9); }\n",
+        );
+        let module = crt.code_gen(&context).unwrap();
+        assert!(module.verify().is_ok());
+        assert!(!module.print_to_string().to_string().contains("@llvm.sqrt"));
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let shadowed = engine
+                .get_function::<unsafe extern "C" fn() -> i32>("shadowed")
+                .unwrap();
+            assert_eq!(shadowed.call(), 9);
+        }
+
+        let crt = parse("pub fn f(x: u32) -> u32 { return abs(x); }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::UnsupportedType { .. })
+        ));
+        let crt = parse("pub fn f(x: f64) -> f64 { return max(x, 1i32); }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::F64,
+                actual: Type::I32,
+                ..
+            })
+        ));
+        let crt = parse("pub fn f() -> i32 { return min(1); }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::SemanticError(
+                SemanticError::ArgumentCountMismatch { expected: 2, .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_layout_queries() {
+        let crt = parse(
+            "pub fn words(n: u64) -> u64 { return n * size_of::<&str>() + align_of::<f64>(); }\n\
+            pub fn unit() -> i32 { let x: i8 = size_of::<()>(); return x as i32 + size_of::<bool>(); }\n",
+        );
+        // i386 Linux, and the host
+        let i386 = "e-m:e-p:32:32-p270:32:32-p271:32:32-p272:64:64-f64:32:64-f80:32-n8:16:32-S128";
+        let i386 = TargetLayout::from_data_layout(i386).unwrap();
+        let host = TargetLayout::host();
+        for layout in [i386, host] {
+            let context = Context::create();
+            let options = CodeGenOptions::new().with_target_layout(layout);
+            let (module, _) = crt
+                .code_gen_with(&context, CodeGenRequest::new().with_options(options))
+                .unwrap();
+            assert!(module.verify().is_ok());
+
+            let engine = module
+                .create_jit_execution_engine(OptimizationLevel::None)
+                .unwrap();
+            let expected = 6 * layout.size_of(&Type::Str) + layout.align_of(&Type::F64);
+            unsafe {
+                let words = engine
+                    .get_function::<unsafe extern "C" fn(u64) -> u64>("words")
+                    .unwrap();
+                assert_eq!(words.call(6), expected as u64);
+                let unit = engine
+                    .get_function::<unsafe extern "C" fn() -> i32>("unit")
+                    .unwrap();
+                assert_eq!(unit.call(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tuples() {
+        let crt = parse(
+            "fn pair(a: u8, b: i32) -> (u8, i32) { return (a, b); }\n\
+            pub fn first(a: u8) -> u8 { let t: (u8, i32) = pair(a, 1); return t.0 / 2; }\n\
+            pub fn nested(x: i32) -> i32 {\n\
+                let t: ((i32, bool), (f64,)) = ((x, true), (2.5,));\n\
+                if (t.0).1 { return t.0.0 + t.1.0 as i32; }\n\
+                return 0;\n\
+            }\n\
+            pub fn literal() -> u32 { return (1u32, 2).0 + 5; }\n\
+            pub fn block(x: u8) -> bool { let t: (u8, u8) = { let y: u8 = x; (y, 200) }; return t.0 < t.1; }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        assert!(module.verify().is_ok());
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            // The elements keep their signedness, so `250u8 / 2` is not `-6i8 / 2`
+            let first = engine
+                .get_function::<unsafe extern "C" fn(u8) -> u8>("first")
+                .unwrap();
+            assert_eq!(first.call(250), 125);
+            let nested = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("nested")
+                .unwrap();
+            assert_eq!(nested.call(3), 5);
+            let literal = engine
+                .get_function::<unsafe extern "C" fn() -> u32>("literal")
+                .unwrap();
+            assert_eq!(literal.call(), 6);
+            let block = engine
+                .get_function::<unsafe extern "C" fn(u8) -> bool>("block")
+                .unwrap();
+            assert!(block.call(100));
+        }
+
+        let crt = parse("pub fn f(t: (i32, bool)) -> i32 { return t.2; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::SemanticError(SemanticError::NoTupleField {
+                index: 2,
+                ..
+            }))
+        ));
+        let crt = parse("pub fn f() { let t: (i32, bool) = (1, 2); }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                actual: Type::Tuple(elements),
+                ..
+            }) if *elements == [Type::I32, Type::I32]
+        ));
+        let crt = parse("pub fn f(t: (i32, i32)) -> bool { return t == t; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::UnsupportedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_references() {
+        let crt = parse(
+            "fn inc(r: &mut i32) { *r = *r + 1; }\n\
+            fn larger(a: &u8, b: &u8) -> &u8 { if *a > *b { return a; } return b; }\n\
+            pub fn counter(n: i32) -> i32 {\n\
+                let mut x: i32 = n;\n\
+                inc(&mut x);\n\
+                inc(&mut x);\n\
+                let r: &&mut i32 = &&mut x;\n\
+                return **r;\n\
+            }\n\
+            pub fn max(a: u8, b: u8) -> u8 { return *larger(&a, &b); }\n\
+            pub fn temporary() -> i64 { let r: &i64 = &(40 + 2); return *r; }\n\
+            pub fn param(mut p: f64) -> f64 { p = p * 2.0; let r: &mut f64 = &mut p; *r = *r + 0.5; return p; }\n",
+        );
+        let context = Context::create();
+        let module = crt.code_gen(&context).unwrap();
+        assert!(module.verify().is_ok());
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let counter = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("counter")
+                .unwrap();
+            assert_eq!(counter.call(5), 7);
+            // The referenced values keep their signedness, so `200u8` is not `-56i8`
+            let max = engine
+                .get_function::<unsafe extern "C" fn(u8, u8) -> u8>("max")
+                .unwrap();
+            assert_eq!(max.call(200, 100), 200);
+            let temporary = engine
+                .get_function::<unsafe extern "C" fn() -> i64>("temporary")
+                .unwrap();
+            assert_eq!(temporary.call(), 42);
+            let param = engine
+                .get_function::<unsafe extern "C" fn(f64) -> f64>("param")
+                .unwrap();
+            assert_eq!(param.call(1.0), 2.5);
+        }
+
+        let crt = parse("pub fn f() { let x: i32 = 1; let r: &i32 = &x; *r = 2; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::SemanticError(
+                SemanticError::MutationBehindSharedReference { .. }
+            ))
+        ));
+        let crt = parse("pub fn f(x: i32) -> i32 { return *x; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::SemanticError(
+                SemanticError::NotDereferenceable { ty: Type::I32, .. }
+            ))
+        ));
+        // The references to the locals and the temporaries cannot outlive the function
+        let crt = parse("fn f() -> &i32 { let x: i32 = 1; return &x; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::SemanticError(
+                SemanticError::ReturnedLocalReference { .. }
+            ))
+        ));
+        let crt = parse(
+            "fn f(x: i32, r: &i32) -> &i32 { return if x > 0 { r } else { let y: i32 = x; &(y) }; }\n",
+        );
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::SemanticError(
+                SemanticError::ReturnedLocalReference { .. }
+            ))
+        ));
+        let crt = parse("fn f() -> &i64 { return &(40 + 2); }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::SemanticError(
+                SemanticError::ReturnedLocalReference { .. }
+            ))
+        ));
+        let crt = parse("fn f(r: &i32) -> &i32 { return &*r; }\n");
+        assert!(crt.code_gen(&context).is_ok());
+        let crt = parse("pub fn f(x: i32) { let r: &u8 = &x; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch { .. })
+        ));
+        let crt = parse("pub fn f(r: &i32) -> bool { return r == r; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::UnsupportedType { .. })
+        ));
+    }
+
+    #[test]
+    fn test_assignment_types() {
+        let context = Context::create();
+        let crt = parse("pub fn f() -> u8 { let mut x: u8 = 0; x = 200; x = x + 1; return x; }\n");
+        assert!(crt.code_gen(&context).is_ok());
+
+        // The value assigned to a variable is not stored over the memory after it
+        let crt = parse("pub fn f() { let mut x: u8 = 0; x = 1.5; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::U8,
+                actual: Type::F64,
+                ..
+            })
+        ));
+        let crt = parse("pub fn f() { let mut x: u8 = 0; x = 70000i32; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::U8,
+                actual: Type::I32,
+                ..
+            })
+        ));
+        let crt = parse("pub fn f(mut x: u8, y: i64) { x = y; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::U8,
+                actual: Type::I64,
+                ..
+            })
+        ));
+        let crt = parse("pub fn f(r: &mut u8) { *r = 70000i32; }\n");
+        assert!(matches!(
+            crt.code_gen(&context),
+            Err(CodeGenError::TypeMismatch {
+                expected: Type::U8,
+                actual: Type::I32,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+//! A module containing the annotation of the generated assembly with the μRust source code.
+//!
+//! When the code is generated with the [debug info](crate::codegen::CodeGenOptions::debug_info),
+//! the assembly contains a `.loc` directive wherever the source line of the following instructions
+//! changes. [`annotate`] adds a comment with that line of the source file after the directive,
+//! so that the instructions generated for every statement can be found at a glance.
+
+/// Annotates the given assembly with the lines of the given source code.
+///
+/// Every `.loc` directive pointing at a different line than the previous one in the same function
+/// is followed by a comment (starting with the given `comment` token of the assembler,
+/// e.g. `#` for x86-64) containing the line number and the trimmed line of the source code.
+/// The lines missing from the source code are annotated only with their numbers.
+pub fn annotate(asm: &str, source: &str, comment: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut result = String::with_capacity(asm.len() * 2);
+    let mut last_line = None;
+    for asm_line in asm.lines() {
+        result.push_str(asm_line);
+        result.push('\n');
+
+        let directive = asm_line.trim_start();
+        if directive.starts_with(".cfi_startproc") {
+            last_line = None;
+            continue;
+        }
+        let Some(line) = location_line(directive) else {
+            continue;
+        };
+        if last_line == Some(line) {
+            continue;
+        }
+        last_line = Some(line);
+
+        result.push('\t');
+        result.push_str(comment);
+        match lines.get(line - 1).map(|text| text.trim()) {
+            Some(text) if !text.is_empty() => result.push_str(&format!(" {}: {}\n", line, text)),
+            _ => result.push_str(&format!(" {}\n", line)),
+        }
+    }
+    result
+}
+
+/// Returns the token starting the comments of the assembler for the given target triple.
+pub fn comment_token(triple: &str) -> &'static str {
+    if triple.starts_with("aarch64") || triple.starts_with("arm") {
+        "//"
+    } else {
+        "#"
+    }
+}
+
+/// Returns the (non-zero) source line of the given `.loc <file> <line> [<column>]` directive.
+fn location_line(directive: &str) -> Option<usize> {
+    let mut operands = directive.strip_prefix(".loc")?.split_whitespace();
+    let _file = operands.next()?;
+    operands
+        .next()?
+        .parse()
+        .ok()
+        .filter(|&line: &usize| line > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotate() {
+        let source = "fn main() -> i32 {\n    let x: i32 = 1;\n\n    return x + 2;\n}\n";
+        let asm = "\
+main:
+\t.cfi_startproc
+\t.loc\t1 1 0
+\tpushq\t%rbp
+\t.loc\t1 2 5 prologue_end
+\tmovl\t$1, %eax
+\t.loc\t1 2 18 is_stmt 0
+\taddl\t$2, %eax
+\t.loc\t1 4 5 is_stmt 1
+\tretq
+\t.loc\t1 0 0
+\t.loc\t1 9 1
+\t.cfi_endproc
+";
+        let annotated = annotate(asm, source, "#");
+        let expected = "\
+main:
+\t.cfi_startproc
+\t.loc\t1 1 0
+\t# 1: fn main() -> i32 {
+\tpushq\t%rbp
+\t.loc\t1 2 5 prologue_end
+\t# 2: let x: i32 = 1;
+\tmovl\t$1, %eax
+\t.loc\t1 2 18 is_stmt 0
+\taddl\t$2, %eax
+\t.loc\t1 4 5 is_stmt 1
+\t# 4: return x + 2;
+\tretq
+\t.loc\t1 0 0
+\t.loc\t1 9 1
+\t# 9
+\t.cfi_endproc
+";
+        assert_eq!(annotated, expected);
+
+        // The first line of every function is annotated again
+        let twice = annotate(&format!("{asm}{asm}"), source, "//");
+        assert_eq!(twice.matches("// 1: fn main() -> i32 {").count(), 2);
+        assert_eq!(comment_token("x86_64-unknown-linux-gnu"), "#");
+        assert_eq!(comment_token("aarch64-apple-darwin"), "//");
+    }
+}
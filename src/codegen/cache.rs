@@ -0,0 +1,377 @@
+//! A module containing the incremental build cache of the compilation artifacts.
+//!
+//! The cache stores the emitted LLVM IR and object code of every compiled file in a directory,
+//! keyed by the [hash](CacheKey) of the file's canonical path and contents and the [options](CodeGenOptions)
+//! of the compilation. A file that hasn't changed since its last compilation (with the same options)
+//! is not parsed or compiled again.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::ast::Type;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::session::{CompilationOutput, CompilationSession};
+use crate::codegen::CodeGenOptions;
+use crate::parser::error::ParserError;
+use crate::parser::Parser;
+use crate::serialize::checksum;
+
+/// The file extension of the cached LLVM IR.
+const IR_EXTENSION: &str = "ll";
+/// The file extension of the cached object code.
+const OBJECT_EXTENSION: &str = "o";
+
+/// The type of error that can occur while compiling a file through the cache.
+#[derive(Debug)]
+pub enum CacheError {
+    /// An error when reading the source file or accessing the cache directory.
+    Io(io::Error),
+    /// An error when parsing the source file.
+    Parser(ParserError),
+    /// An error when compiling the source file.
+    CodeGen(CodeGenError),
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Io(err) => write!(f, "I/O error: {}", err),
+            CacheError::Parser(err) => fmt::Display::fmt(err, f),
+            CacheError::CodeGen(err) => write!(f, "Code generation failed: {:?}", err),
+        }
+    }
+}
+
+impl Error for CacheError {}
+
+impl From<io::Error> for CacheError {
+    fn from(err: io::Error) -> Self {
+        CacheError::Io(err)
+    }
+}
+
+/// The key of a cache entry.
+///
+/// It is a hash of the canonical path and the contents of the source file, the options of the compilation
+/// and the version of the compiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CacheKey(u64);
+
+impl CacheKey {
+    /// Creates the key for the source file with the given (canonical) path and contents
+    /// compiled with the given options.
+    ///
+    /// The files with the same name in different directories have different keys,
+    /// since the path is embedded in the generated module.
+    pub fn new(path: &Path, source: &[u8], options: CodeGenOptions) -> CacheKey {
+        let path = path.to_string_lossy();
+        let mut data = Vec::with_capacity(source.len() + path.len() + 16);
+        data.extend_from_slice(env!("CARGO_PKG_VERSION").as_bytes());
+        data.push(0);
+        data.push(options.mem2reg() as u8);
+        data.push(options.overflow_checks() as u8);
+        data.push(options.inexact_float_lint() as u8);
+        data.push(options.coverage() as u8);
+        data.push(options.profiling() as u8);
+        data.push(options.sanitizer() as u8);
+        data.push(options.debug_info() as u8);
+        data.push(options.module_naming() as u8);
+        let layout = options.target_layout();
+        data.extend_from_slice(&layout.size_of(&Type::Str).to_le_bytes());
+        for ty in [
+            Type::Str,
+            Type::I8,
+            Type::I16,
+            Type::I32,
+            Type::I64,
+            Type::F32,
+            Type::F64,
+        ] {
+            data.extend_from_slice(&layout.align_of(&ty).to_le_bytes());
+        }
+        data.extend_from_slice(path.as_bytes());
+        data.push(0);
+        data.extend_from_slice(source);
+        CacheKey(checksum(&data))
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// The compilation artifacts stored in the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedArtifacts {
+    ir: String,
+    object: Vec<u8>,
+}
+
+impl CachedArtifacts {
+    /// Returns the textual representation of the generated LLVM IR.
+    pub fn ir(&self) -> &str {
+        &self.ir
+    }
+
+    /// Returns the contents of the object file for the host machine.
+    pub fn object(&self) -> &[u8] {
+        &self.object
+    }
+}
+
+impl From<&CompilationOutput> for CachedArtifacts {
+    fn from(output: &CompilationOutput) -> Self {
+        CachedArtifacts {
+            ir: output.ir().to_owned(),
+            object: output.object().to_vec(),
+        }
+    }
+}
+
+/// The statistics of a [`BuildCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    hits: usize,
+    misses: usize,
+}
+
+impl CacheStats {
+    /// Returns the number of lookups that found the artifacts in the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Returns the number of lookups that didn't find the artifacts in the cache.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+/// An incremental build cache stored in a directory.
+///
+/// The entries are written atomically, and an entry that cannot be read back
+/// (e.g. because it is incomplete) is treated as missing.
+#[derive(Debug)]
+pub struct BuildCache {
+    dir: PathBuf,
+    stats: CacheStats,
+}
+
+impl BuildCache {
+    /// Opens the cache in the given directory, creating the directory if it doesn't exist.
+    pub fn open<P: AsRef<Path>>(dir: P) -> io::Result<BuildCache> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(BuildCache {
+            dir,
+            stats: CacheStats::default(),
+        })
+    }
+
+    /// Returns the directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the statistics of the lookups since the cache was opened.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Returns the artifacts stored with the given key, if any.
+    pub fn get(&mut self, key: CacheKey) -> io::Result<Option<CachedArtifacts>> {
+        let artifacts = self.read(key)?;
+        match artifacts {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        Ok(artifacts)
+    }
+
+    /// Stores the given artifacts with the given key, replacing the previous ones.
+    pub fn insert(&mut self, key: CacheKey, artifacts: &CachedArtifacts) -> io::Result<()> {
+        // The object is written last, so it marks a complete entry
+        self.write(key, IR_EXTENSION, artifacts.ir.as_bytes())?;
+        self.write(key, OBJECT_EXTENSION, &artifacts.object)
+    }
+
+    /// Returns the number of entries in the cache.
+    pub fn len(&self) -> io::Result<usize> {
+        Ok(self.entry_files()?.filter(|path| is_object(path)).count())
+    }
+
+    /// Returns whether the cache has no entries.
+    pub fn is_empty(&self) -> io::Result<bool> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Removes all the entries from the cache.
+    pub fn clear(&mut self) -> io::Result<()> {
+        for path in self.entry_files()? {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    /// Compiles the file at the given path using the given session, unless the artifacts
+    /// of the same file compiled with the same options are already in the cache.
+    ///
+    /// On a cache miss the file is parsed and compiled, and the artifacts are stored in the cache.
+    pub fn compile<P: AsRef<Path>>(
+        &mut self,
+        session: &CompilationSession,
+        path: P,
+    ) -> Result<CachedArtifacts, CacheError> {
+        let path = path.as_ref();
+        let source = fs::read(path)?;
+        let key = CacheKey::new(&fs::canonicalize(path)?, &source, session.options());
+        if let Some(artifacts) = self.get(key)? {
+            return Ok(artifacts);
+        }
+
+        let parser = match session.cancellation() {
+            Some(cancellation) => Parser::with_cancellation(path, cancellation.clone())?,
+            None => Parser::new(path)?,
+        };
+        let crt = parser.parse().map_err(CacheError::Parser)?;
+        let output = session.compile(&crt).map_err(CacheError::CodeGen)?;
+        let artifacts = CachedArtifacts::from(&output);
+        self.insert(key, &artifacts)?;
+        Ok(artifacts)
+    }
+
+    fn entry_path(&self, key: CacheKey, extension: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{extension}"))
+    }
+
+    fn read(&self, key: CacheKey) -> io::Result<Option<CachedArtifacts>> {
+        let object = match fs::read(self.entry_path(key, OBJECT_EXTENSION)) {
+            Ok(object) => object,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let ir = match fs::read(self.entry_path(key, IR_EXTENSION)) {
+            Ok(ir) => String::from_utf8(ir).ok(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => return Err(err),
+        };
+        Ok(ir.map(|ir| CachedArtifacts { ir, object }))
+    }
+
+    fn write(&self, key: CacheKey, extension: &str, contents: &[u8]) -> io::Result<()> {
+        let path = self.entry_path(key, extension);
+        let temp_path = path.with_extension(format!("{extension}.tmp"));
+        fs::write(&temp_path, contents)?;
+        fs::rename(temp_path, path)
+    }
+
+    fn entry_files(&self) -> io::Result<impl Iterator<Item = PathBuf>> {
+        let paths = fs::read_dir(&self.dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(paths.into_iter().filter(|path| {
+            let extension = path.extension().and_then(|ext| ext.to_str());
+            matches!(extension, Some(IR_EXTENSION | OBJECT_EXTENSION | "tmp"))
+        }))
+    }
+}
+
+fn is_object(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some(OBJECT_EXTENSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::target::TargetLayout;
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_cache_hits() {
+        let dir = TempDir::new("cache_hits");
+        let source_path = dir.write("main.mrs", "pub fn f() -> i32 { return 1; }\n");
+
+        let mut cache = BuildCache::open(dir.join("cache")).unwrap();
+        let session = CompilationSession::new();
+        let first = cache.compile(&session, &source_path).unwrap();
+        let second = cache.compile(&session, &source_path).unwrap();
+        assert_eq!(first, second);
+        assert!(first.ir().contains("ret i32 1"));
+        assert_eq!((cache.stats().hits(), cache.stats().misses()), (1, 1));
+        assert_eq!(cache.len().unwrap(), 1);
+
+        // Changing the options or the source invalidates the entry
+        let session = CompilationSession::with_options(CodeGenOptions::new().with_mem2reg(false));
+        cache.compile(&session, &source_path).unwrap();
+        fs::write(&source_path, "pub fn f() -> i32 { return 2; }\n").unwrap();
+        let changed = cache.compile(&session, &source_path).unwrap();
+        assert!(changed.ir().contains("ret i32 2"));
+        assert_eq!((cache.stats().hits(), cache.stats().misses()), (1, 3));
+        assert_eq!(cache.len().unwrap(), 3);
+
+        // The entries persist across the instances of the cache
+        let mut reopened = BuildCache::open(dir.join("cache")).unwrap();
+        assert_eq!(reopened.compile(&session, &source_path).unwrap(), changed);
+        assert_eq!(reopened.stats().hits(), 1);
+
+        // A file with the same name and contents in another directory has its own entry
+        let other_dir = TempDir::new("cache_hits_other");
+        let other_path = other_dir.write("main.mrs", "pub fn f() -> i32 { return 2; }\n");
+        reopened.compile(&session, &other_path).unwrap();
+        assert_eq!((reopened.stats().hits(), reopened.stats().misses()), (1, 1));
+        let relative = dir.path().join(".").join("main.mrs");
+        reopened.compile(&session, relative).unwrap();
+        assert_eq!((reopened.stats().hits(), reopened.stats().misses()), (2, 1));
+
+        reopened.clear().unwrap();
+        assert!(reopened.is_empty().unwrap());
+        assert!(matches!(
+            reopened.compile(&session, dir.join("missing.mrs")),
+            Err(CacheError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_target_layout_key() {
+        let path = Path::new("main.mrs");
+        let source = b"pub fn f() -> u32 { return size_of::<&i64>(); }";
+        let host = CodeGenOptions::default();
+        let layout = TargetLayout::from_data_layout("e-p:32:32-i64:64").unwrap();
+        let target = host.with_target_layout(layout);
+        assert_eq!(
+            CacheKey::new(path, source, host),
+            CacheKey::new(path, source, host)
+        );
+        assert_ne!(
+            CacheKey::new(path, source, host),
+            CacheKey::new(path, source, target)
+        );
+    }
+
+    #[test]
+    fn test_incomplete_entry() {
+        let dir = TempDir::new("cache_incomplete");
+        let mut cache = BuildCache::open(dir.join("cache")).unwrap();
+        let key = CacheKey::new(
+            Path::new("a.mrs"),
+            b"fn main() {}",
+            CodeGenOptions::default(),
+        );
+        fs::write(cache.entry_path(key, OBJECT_EXTENSION), b"object").unwrap();
+        assert_eq!(cache.get(key).unwrap(), None);
+
+        let artifacts = CachedArtifacts {
+            ir: "ir".into(),
+            object: b"object".to_vec(),
+        };
+        cache.insert(key, &artifacts).unwrap();
+        assert_eq!(cache.get(key).unwrap(), Some(artifacts));
+        assert_eq!((cache.stats().hits(), cache.stats().misses()), (1, 1));
+    }
+}
@@ -0,0 +1,247 @@
+//! A module containing the code coverage instrumentation of the generated LLVM IR.
+//!
+//! When the [coverage](crate::codegen::CodeGenOptions::coverage) is enabled, every function
+//! with a body gets its own array of counters (a global named [`COUNTERS_PREFIX`] followed
+//! by the name of the function), with one counter per basic block incremented whenever
+//! the block is entered. After the module is run with the JIT, the counters can be read
+//! into a [`CoverageReport`].
+
+use std::ffi::CString;
+use std::fmt;
+use std::rc::Rc;
+
+use inkwell::execution_engine::ExecutionEngine;
+use inkwell::llvm_sys::execution_engine::LLVMGetGlobalValueAddress;
+use inkwell::module::{Linkage, Module};
+use inkwell::values::{FunctionValue, InstructionOpcode};
+
+/// The prefix of the names of the globals holding the counters of the functions.
+pub const COUNTERS_PREFIX: &str = "__mrs_coverage.";
+
+/// Instruments all the functions of the given module (which mustn't be changed afterwards)
+/// with the counters of their basic blocks.
+pub(crate) fn instrument(module: &Module) {
+    let context = module.get_context();
+    let counter_type = context.i64_type();
+    let builder = context.create_builder();
+    for function in module.get_functions() {
+        let blocks = function.get_basic_blocks();
+        if blocks.is_empty() {
+            continue;
+        }
+
+        let name = counters_name(function);
+        let array_type = counter_type.array_type(blocks.len() as u32);
+        let counters = module.add_global(array_type, None, &name);
+        counters.set_initializer(&array_type.const_zero());
+        // The counters have to be visible to the JIT
+        counters.set_linkage(Linkage::External);
+
+        for (index, block) in blocks.into_iter().enumerate() {
+            // The phi nodes have to stay at the beginning of the block
+            let mut first = block.get_first_instruction();
+            while let Some(instruction) = first {
+                if instruction.get_opcode() != InstructionOpcode::Phi {
+                    break;
+                }
+                first = instruction.get_next_instruction();
+            }
+            // The module has been verified, so every block ends with a terminator
+            let Some(first) = first else { continue };
+            builder.position_before(&first);
+
+            let indices = [
+                counter_type.const_zero(),
+                counter_type.const_int(index as u64, false),
+            ];
+            // SAFETY: The index is within the bounds of the array
+            let counter = unsafe {
+                builder.build_in_bounds_gep(array_type, counters.as_pointer_value(), &indices, "")
+            };
+            let result = counter.and_then(|counter| {
+                let count = builder.build_load(counter_type, counter, "")?;
+                let one = counter_type.const_int(1, false);
+                let count = builder.build_int_add(count.into_int_value(), one, "")?;
+                builder.build_store(counter, count)
+            });
+            // The builder is always positioned, so the instructions can always be built
+            debug_assert!(result.is_ok());
+        }
+    }
+}
+
+/// Returns the name of the global holding the counters of the given function.
+fn counters_name(function: FunctionValue) -> String {
+    format!(
+        "{}{}",
+        COUNTERS_PREFIX,
+        function.get_name().to_string_lossy()
+    )
+}
+
+/// The number of times the basic blocks of a function were entered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCoverage {
+    name: Rc<str>,
+    blocks: Vec<(Rc<str>, u64)>,
+}
+
+impl FunctionCoverage {
+    /// Returns the name of the function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the names of the basic blocks of the function together with their counts,
+    /// starting with the entry block.
+    pub fn blocks(&self) -> &[(Rc<str>, u64)] {
+        &self.blocks
+    }
+
+    /// Returns the number of times the function was called.
+    pub fn calls(&self) -> u64 {
+        self.blocks.first().map_or(0, |(_, count)| *count)
+    }
+
+    /// Returns the number of the basic blocks that were entered at least once.
+    pub fn covered_blocks(&self) -> usize {
+        self.blocks.iter().filter(|(_, count)| *count > 0).count()
+    }
+}
+
+impl fmt::Display for FunctionCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "fn {}: {} call(s), {}/{} blocks covered",
+            self.name,
+            self.calls(),
+            self.covered_blocks(),
+            self.blocks.len()
+        )?;
+        for (block, count) in &self.blocks {
+            write!(f, "\n    {}: {}", block, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// The coverage of all the instrumented functions of a module, in the order of their definitions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    functions: Vec<FunctionCoverage>,
+}
+
+impl CoverageReport {
+    /// Reads the counters of the given instrumented module from the execution engine running it.
+    ///
+    /// The functions without counters (e.g. when the coverage was disabled) are skipped.
+    pub fn collect(module: &Module, engine: &ExecutionEngine) -> CoverageReport {
+        let mut functions = Vec::new();
+        for function in module.get_functions() {
+            let name = counters_name(function);
+            if module.get_global(&name).is_none() {
+                continue;
+            }
+            let Ok(c_name) = CString::new(name) else {
+                continue;
+            };
+            // SAFETY: The engine and the name are valid for the duration of the call
+            let address =
+                unsafe { LLVMGetGlobalValueAddress(engine.as_mut_ptr(), c_name.as_ptr()) };
+            if address == 0 {
+                continue;
+            }
+            let blocks = function.get_basic_blocks();
+            // SAFETY: The global is an array of a `u64` counter per basic block
+            let counts = unsafe { std::slice::from_raw_parts(address as *const u64, blocks.len()) };
+            let blocks = blocks
+                .iter()
+                .zip(counts)
+                .map(|(block, count)| (block.get_name().to_string_lossy().into(), *count))
+                .collect();
+            functions.push(FunctionCoverage {
+                name: function.get_name().to_string_lossy().into(),
+                blocks,
+            });
+        }
+        CoverageReport { functions }
+    }
+
+    /// Returns the coverage of the individual functions.
+    pub fn functions(&self) -> &[FunctionCoverage] {
+        &self.functions
+    }
+
+    /// Returns the coverage of the function with the given name, if it was instrumented.
+    pub fn function(&self, name: &str) -> Option<&FunctionCoverage> {
+        self.functions.iter().find(|f| f.name() == name)
+    }
+}
+
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = self
+            .functions
+            .iter()
+            .map(|function| function.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::OptimizationLevel;
+
+    use crate::codegen::session::CompilationSession;
+    use crate::codegen::CodeGenOptions;
+    use crate::test_util::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_coverage() {
+        let crt = parse(
+            "pub fn sign(x: i32) -> i32 { if x < 0 { return -1; }; return 1; }\n\
+            pub fn main() -> i32 { return sign(2) + sign(3); }\n",
+        );
+
+        let session = CompilationSession::with_options(CodeGenOptions::new().with_coverage(true));
+        let report = session
+            .with_module(&crt, |module, _| {
+                let engine = module
+                    .create_jit_execution_engine(OptimizationLevel::None)
+                    .unwrap();
+                // SAFETY: The signature of `main` is known
+                let result = unsafe {
+                    engine
+                        .get_function::<unsafe extern "C" fn() -> i32>("main")
+                        .unwrap()
+                        .call()
+                };
+                assert_eq!(result, 2);
+                CoverageReport::collect(module, &engine)
+            })
+            .unwrap();
+        let sign = report.function("sign").unwrap();
+        assert_eq!(sign.calls(), 2);
+        assert_eq!(sign.covered_blocks(), 2);
+        assert_eq!(
+            report.to_string(),
+            "fn sign: 2 call(s), 2/3 blocks covered\n    start: 2\n    then: 0\n    end: 2\n\
+            fn main: 1 call(s), 1/1 blocks covered\n    start: 1"
+        );
+
+        // Without the instrumentation, there are no counters to read
+        let report = CompilationSession::new()
+            .with_module(&crt, |module, _| {
+                let engine = module
+                    .create_jit_execution_engine(OptimizationLevel::None)
+                    .unwrap();
+                CoverageReport::collect(module, &engine)
+            })
+            .unwrap();
+        assert!(report.functions().is_empty());
+    }
+}
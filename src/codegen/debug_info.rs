@@ -0,0 +1,124 @@
+//! A module containing the builder of the DWARF line tables of the generated code.
+//!
+//! Only the locations are described (i.e. the functions and the positions of their statements),
+//! not the types or the variables, which is enough to map the generated code back to the source.
+
+use std::path::Path;
+
+use inkwell::context::Context;
+use inkwell::debug_info::{
+    debug_metadata_version, AsDIScope, DICompileUnit, DIFlags, DIFlagsConstants, DILocation,
+    DIScope, DWARFEmissionKind, DWARFSourceLanguage, DebugInfoBuilder,
+};
+use inkwell::module::{FlagBehavior, Module};
+use inkwell::values::FunctionValue;
+
+use crate::token::Position;
+
+/// The name of the producer recorded in the compile unit.
+const PRODUCER: &str = concat!("mini-rust-compiler ", env!("CARGO_PKG_VERSION"));
+
+/// The debug info of a module, with the scope of the function that is being generated.
+pub(crate) struct DebugInfo<'ctx> {
+    context: &'ctx Context,
+    builder: DebugInfoBuilder<'ctx>,
+    unit: DICompileUnit<'ctx>,
+    scope: Option<DIScope<'ctx>>,
+}
+
+impl<'ctx> DebugInfo<'ctx> {
+    /// Creates the compile unit of the given module compiled from the source file at `path`.
+    pub(crate) fn new(
+        context: &'ctx Context,
+        module: &Module<'ctx>,
+        path: &str,
+    ) -> DebugInfo<'ctx> {
+        let path = Path::new(path);
+        let file_name = path
+            .file_name()
+            .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy());
+        let directory = path.parent().map_or("".into(), |dir| dir.to_string_lossy());
+
+        let version = context
+            .i32_type()
+            .const_int(debug_metadata_version() as u64, false);
+        module.add_basic_value_flag("Debug Info Version", FlagBehavior::Warning, version);
+
+        let (builder, unit) = module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::Rust,
+            &file_name,
+            &directory,
+            PRODUCER,
+            false,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::LineTablesOnly,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+        DebugInfo {
+            context,
+            builder,
+            unit,
+            scope: None,
+        }
+    }
+
+    /// Attaches a subprogram to the given function starting at `start`, and makes it
+    /// the scope of the following [locations](DebugInfo::location).
+    pub(crate) fn begin_function(
+        &mut self,
+        function: FunctionValue<'ctx>,
+        is_local: bool,
+        start: Position,
+    ) {
+        let file = self.unit.get_file();
+        let name = function.get_name().to_string_lossy();
+        let ty = self
+            .builder
+            .create_subroutine_type(file, None, &[], DIFlags::ZERO);
+        let line = start.line() as u32;
+        let subprogram = self.builder.create_function(
+            self.unit.as_debug_info_scope(),
+            &name,
+            None,
+            file,
+            line,
+            ty,
+            is_local,
+            true,
+            line,
+            DIFlags::ZERO,
+            false,
+        );
+        function.set_subprogram(subprogram);
+        self.scope = Some(subprogram.as_debug_info_scope());
+    }
+
+    /// Leaves the scope of the current function.
+    pub(crate) fn end_function(&mut self) {
+        self.scope = None;
+    }
+
+    /// Returns the location of the given position in the current function, if there is one.
+    pub(crate) fn location(&self, pos: Position) -> Option<DILocation<'ctx>> {
+        let scope = self.scope?;
+        Some(self.builder.create_debug_location(
+            self.context,
+            pos.line() as u32,
+            pos.column() as u32,
+            scope,
+            None,
+        ))
+    }
+
+    /// Constructs the deferred descriptors, so that the module can be verified.
+    pub(crate) fn finalize(&self) {
+        self.builder.finalize();
+    }
+}
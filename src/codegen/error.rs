@@ -1,10 +1,13 @@
 //! Error types for code generation.
 
+use std::fmt;
+
 use inkwell::builder::BuilderError;
 use inkwell::support::LLVMString;
 
 use crate::ast::error::SemanticError;
 use crate::ast::Type;
+use crate::cancel::Interrupted;
 use crate::token::Span;
 
 /// The type of error that can occur during code generation.
@@ -47,10 +50,58 @@ pub enum CodeGenError {
         /// The span where the error occurred.
         span: Span,
     },
+    /// An error when the initializer of a static item cannot be evaluated at compile time.
+    NonConstantInitializer {
+        /// The span of the initializer.
+        span: Span,
+    },
     /// An error returned by all `inkwell::builder::Builder::build_*` methods.
     BuilderError(BuilderError),
     /// An error returned by [`Module::verify`](inkwell::module::Module::verify).
     ModuleVerificationFailed(LLVMString),
+    /// An error when the host target cannot be initialized or the object code cannot be emitted.
+    TargetError(Box<str>),
+    /// An error when the code generation is aborted by a
+    /// [cancellation token](crate::cancel::CancellationToken).
+    Interrupted(Interrupted),
+}
+
+impl fmt::Display for CodeGenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeGenError::SemanticError(err) => fmt::Display::fmt(err, f),
+            CodeGenError::MissingSymbol { symbol, span } => {
+                write!(f, "Unknown symbol `{}` at {}", symbol, span)
+            }
+            CodeGenError::InvalidLLVMValueType { message, span } => {
+                write!(f, "Invalid LLVM value at {}: {}", span, message)
+            }
+            CodeGenError::UnderscoreUsedAsValue { span } => {
+                write!(f, "Underscore used as a value at {}", span)
+            }
+            CodeGenError::UnsupportedType { message, span } => {
+                write!(f, "Unsupported type at {}: {}", span, message)
+            }
+            CodeGenError::TypeMismatch {
+                expected,
+                actual,
+                span,
+            } => write!(
+                f,
+                "Mismatched types at {}: expected {}, got {}",
+                span, expected, actual
+            ),
+            CodeGenError::NonConstantInitializer { span } => {
+                write!(f, "Non-constant initializer of a static item at {}", span)
+            }
+            CodeGenError::BuilderError(err) => write!(f, "LLVM builder error: {}", err),
+            CodeGenError::ModuleVerificationFailed(message) => {
+                write!(f, "LLVM module verification failed: {}", message)
+            }
+            CodeGenError::TargetError(message) => write!(f, "Target error: {}", message),
+            CodeGenError::Interrupted(reason) => fmt::Display::fmt(reason, f),
+        }
+    }
 }
 
 impl From<SemanticError> for CodeGenError {
@@ -59,6 +110,12 @@ impl From<SemanticError> for CodeGenError {
     }
 }
 
+impl From<Interrupted> for CodeGenError {
+    fn from(reason: Interrupted) -> CodeGenError {
+        CodeGenError::Interrupted(reason)
+    }
+}
+
 impl From<BuilderError> for CodeGenError {
     fn from(err: BuilderError) -> CodeGenError {
         CodeGenError::BuilderError(err)
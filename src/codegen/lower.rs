@@ -0,0 +1,122 @@
+//! A module containing the generation of the LLVM IR for the AST nodes.
+//!
+//! The AST doesn't depend on LLVM -- the expressions and statements are [lowered](crate::ast::CoreExpr)
+//! to the core constructs of the language, whose code generation is implemented in the submodules
+//! (mirroring the [`ast`](crate::ast) module) using the accessors of the nodes.
+
+use inkwell::context::Context;
+use inkwell::module::Module;
+
+use crate::ast::error::SemanticWarning;
+use crate::ast::{Crate, FuncProtoASTNode};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::registry::FunctionSignature;
+use crate::codegen::{CodeGen, CodeGenRequest, CodeGenState, ModuleNaming};
+use crate::trace;
+
+pub use self::expr::{ExprCodeGen, PlaceCodeGen};
+
+mod crt;
+mod expr;
+mod item;
+mod stmt;
+mod r#type;
+
+impl Crate {
+    /// Returns an iterator over the signatures of all the functions in this crate.
+    ///
+    /// See [`CrateASTNode::signatures`](crate::ast::CrateASTNode::signatures).
+    pub fn signatures(&self) -> impl Iterator<Item = FunctionSignature> + '_ {
+        self.root().signatures()
+    }
+
+    /// Returns the identifier of the LLVM module generated for this crate,
+    /// named according to the given scheme.
+    pub fn module_identifier(&self, naming: ModuleNaming) -> String {
+        naming.identifier(self.root().name(), self.source_path())
+    }
+
+    /// Generates the LLVM IR for this crate given the context.
+    pub fn code_gen<'ctx>(&self, context: &'ctx Context) -> codegen::Result<Module<'ctx>> {
+        self.code_gen_with(context, CodeGenRequest::new())
+            .map(|(module, _)| module)
+    }
+
+    /// Generates the LLVM IR for this crate (or only its item) given the context,
+    /// as described by the given `request`, together with the warnings encountered
+    /// during code generation.
+    pub fn code_gen_with<'ctx>(
+        &self,
+        context: &'ctx Context,
+        request: CodeGenRequest,
+    ) -> codegen::Result<(Module<'ctx>, Vec<SemanticWarning>)> {
+        let options = request.options();
+        let item = request.item();
+        let module_name = request.module_name().map_or_else(
+            || self.module_identifier(options.module_naming()),
+            str::to_owned,
+        );
+        trace::span!(INFO, "codegen", module = %module_name, item);
+        let mut state = CodeGenState::with_options(context, &module_name, options);
+        // The source file name is the most precise path known, regardless of the module name
+        let source_file_name = self.module_identifier(ModuleNaming::Path);
+        state.module().set_source_file_name(&source_file_name);
+        if options.debug_info() {
+            state.enable_debug_info(&source_file_name);
+        }
+        request.configure(&mut state);
+        // Every error is reported to the observer, but only the first one is returned
+        let init_errors = self.check_initialization();
+        for err in &init_errors {
+            state.report_error(&err.clone().into());
+        }
+        if let Some(err) = init_errors.into_iter().next() {
+            return Err(err.into());
+        }
+        if let Err(err) = self.generate_module(&mut state, request.imports(), item) {
+            state.report_error(&err);
+            return Err(err);
+        }
+
+        if let Err(message) = state.module().verify() {
+            let err = CodeGenError::ModuleVerificationFailed(message);
+            state.report_error(&err);
+            return Err(err);
+        }
+        let warnings = state.take_warnings();
+        let module = state.take_module();
+        trace::event!(DEBUG, warnings = warnings.len(), "generated the module");
+        options.run_passes(&module);
+
+        Ok((module, warnings))
+    }
+
+    /// Generates the LLVM IR for this crate (or only the given item) into the configured state.
+    fn generate_module(
+        &self,
+        state: &mut CodeGenState,
+        imports: &[(&str, &FuncProtoASTNode)],
+        item: Option<&str>,
+    ) -> codegen::Result<()> {
+        state.check_cancellation()?;
+        let item_span = item
+            .and_then(|name| self.root().find_item(name))
+            .map(|item| item.span());
+        for block in self.check_unsafety() {
+            if item_span.is_some_and(|span| !span.contains(block.span())) {
+                continue;
+            }
+            if let Some(warning) = block.unused_warning() {
+                state.push_warning(warning);
+            }
+        }
+
+        self.root().collect_symbols(state)?;
+        self.root().collect_imported_symbols(imports, state)?;
+        match item {
+            Some(name) => self.root().code_gen_item(name, state),
+            None => self.root().code_gen(state),
+        }
+    }
+}
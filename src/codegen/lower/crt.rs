@@ -0,0 +1,69 @@
+//! The LLVM IR generation for the [`CrateASTNode`].
+
+use crate::ast::{CrateASTNode, ExternItem, ItemASTNode};
+use crate::codegen;
+use crate::codegen::registry::FunctionSignature;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl CrateASTNode {
+    /// Returns an iterator over the signatures of all the functions in the crate,
+    /// including the ones declared in extern blocks, in the order of their declarations.
+    pub fn signatures(&self) -> impl Iterator<Item = FunctionSignature> + '_ {
+        self.items().iter().flat_map(|item| {
+            let signatures: Vec<FunctionSignature> = match item {
+                ItemASTNode::Func(func) => vec![func.proto().signature()],
+                ItemASTNode::Static(_) => Vec::new(),
+                ItemASTNode::Extern(ext) => ext
+                    .items()
+                    .iter()
+                    .filter_map(|item| match item {
+                        ExternItem::Func(proto) => Some(proto.signature()),
+                        ExternItem::Static(_) => None,
+                    })
+                    .collect(),
+            };
+            signatures
+        })
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, ()> for CrateASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        for item in self.items() {
+            state.check_cancellation()?;
+            state.notify_item_codegen(item);
+            item.code_gen(state)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{ASTNode, Type};
+    use crate::codegen::registry::FunctionSignature;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_signatures() {
+        let crt = parse(
+            "extern \"C\" {\n\
+                fn print(n: i32);\n\
+                static mut X: i32;\n\
+            }\n\
+            pub static Y: f64 = 0.5;\n\
+            fn f(a: i32, b: bool) -> i32 { return a; }\n\
+            pub fn main() { return; }\n",
+        );
+
+        let signatures: Vec<FunctionSignature> = crt.signatures().collect();
+        let names: Vec<&str> = signatures.iter().map(FunctionSignature::name).collect();
+        assert_eq!(names, vec!["print", "f", "main"]);
+        assert_eq!(signatures[1].param_types(), [Type::I32, Type::Bool]);
+        assert_eq!(*signatures[1].return_type(), Type::I32);
+        assert_eq!(
+            signatures[2].span(),
+            crt.functions().nth(1).unwrap().proto().span()
+        );
+    }
+}
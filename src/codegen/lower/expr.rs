@@ -0,0 +1,120 @@
+//! The LLVM IR generation for the expression AST nodes.
+
+use inkwell::values::{AnyValueEnum, PointerValue};
+
+use crate::ast::{CoreExpr, ExprASTNode, PlaceExprASTNode, Type};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::{CodeGen, CodeGenState};
+
+mod assign;
+mod block;
+mod cast;
+mod fun_call;
+mod grouped;
+mod r#if;
+mod layout_of;
+mod literal;
+mod r#loop;
+mod operator;
+mod path;
+mod r#return;
+mod tuple;
+mod tuple_index;
+mod underscore;
+mod unsafe_block;
+
+/// A trait for the [core expressions](CoreExpr) that can [generate](CodeGen)
+/// the LLVM value of the expression.
+///
+/// It is implemented for `dyn ExprASTNode` by [lowering](ExprASTNode::lower) the expression.
+pub trait ExprCodeGen: for<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> {
+    /// Generates the code of the expression in a statement position, i.e. when its value
+    /// is discarded (e.g. in an [expression statement](crate::ast::ExprStmtASTNode)).
+    ///
+    /// By default, the value is generated and discarded, but the expressions with a block
+    /// (e.g. [`IfASTNode`](crate::ast::IfASTNode)) don't produce a value in a statement position,
+    /// so their branches don't have to be of the same type.
+    fn code_gen_stmt<'ctx>(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        CodeGen::<AnyValueEnum>::code_gen(self, state).map(|_| ())
+    }
+}
+
+/// A trait for the [core expressions](CoreExpr) that are [place expressions](PlaceExprASTNode)
+/// and can generate the pointer to the memory location they refer to.
+///
+/// It is implemented for `dyn PlaceExprASTNode` by [lowering](ExprASTNode::lower) the expression.
+pub trait PlaceCodeGen {
+    /// Generates the pointer to the memory location the expression refers to (instead of
+    /// reading the value stored there), e.g. to assign to it or to
+    /// [borrow](crate::ast::BorrowExprASTNode) it, and returns it together with the type
+    /// of the stored value.
+    ///
+    /// `mutable` is `true` if the location is going to be mutated.
+    fn code_gen_place<'ctx>(
+        &self,
+        state: &mut CodeGenState<'ctx>,
+        mutable: bool,
+    ) -> codegen::Result<(PointerValue<'ctx>, Type)>;
+}
+
+/// Returns the code generation of the core construct the expression is lowered to.
+fn lowered(expr: &dyn ExprASTNode) -> &dyn ExprCodeGen {
+    match expr.lower() {
+        CoreExpr::Int(node) => node,
+        CoreExpr::Float(node) => node,
+        CoreExpr::Bool(node) => node,
+        CoreExpr::Str(node) => node,
+        CoreExpr::Unit(node) => node,
+        CoreExpr::Path(node) => node,
+        CoreExpr::Underscore(node) => node,
+        CoreExpr::Grouped(node) => node,
+        CoreExpr::Block(node) => node,
+        CoreExpr::UnsafeBlock(node) => node,
+        CoreExpr::Tuple(node) => node,
+        CoreExpr::TupleIndex(node) => node,
+        CoreExpr::FunCall(node) => node,
+        CoreExpr::LayoutOf(node) => node,
+        CoreExpr::Assign(node) => node,
+        CoreExpr::Arith(node) => node,
+        CoreExpr::Comp(node) => node,
+        CoreExpr::LazyBool(node) => node,
+        CoreExpr::Neg(node) => node,
+        CoreExpr::Borrow(node) => node,
+        CoreExpr::Deref(node) => node,
+        CoreExpr::Cast(node) => node,
+        CoreExpr::If(node) => node,
+        CoreExpr::While(node) => node,
+        CoreExpr::InfLoop(node) => node,
+        CoreExpr::Return(node) => node,
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for dyn ExprASTNode + '_ {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        lowered(self).code_gen(state)
+    }
+}
+
+impl ExprCodeGen for dyn ExprASTNode + '_ {
+    fn code_gen_stmt<'ctx>(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        lowered(self).code_gen_stmt(state)
+    }
+}
+
+impl PlaceCodeGen for dyn PlaceExprASTNode + '_ {
+    fn code_gen_place<'ctx>(
+        &self,
+        state: &mut CodeGenState<'ctx>,
+        mutable: bool,
+    ) -> codegen::Result<(PointerValue<'ctx>, Type)> {
+        match self.lower() {
+            CoreExpr::Path(path) => path.code_gen_place(state, mutable),
+            CoreExpr::Deref(deref) => deref.code_gen_place(state, mutable),
+            _ => Err(CodeGenError::InvalidLLVMValueType {
+                message: "Expected a place expression".into(),
+                span: self.span(),
+            }),
+        }
+    }
+}
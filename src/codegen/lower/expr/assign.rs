@@ -0,0 +1,97 @@
+//! The LLVM IR generation for the [`AssignASTNode`].
+
+use inkwell::values::{AnyValueEnum, BasicValueEnum};
+
+use crate::ast::error::SemanticError;
+use crate::ast::{ASTNode, AssignASTNode, LiteralASTNode, Type};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::{ExprCodeGen, PlaceCodeGen};
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for AssignASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        // Returns the result of the assignment, which is always a unit value.
+        let assignment_result = |state: &mut CodeGenState<'ctx>| {
+            let value = LiteralASTNode::<()>::new(self.value().span());
+            CodeGen::<AnyValueEnum>::code_gen(&value, state).unwrap()
+        };
+
+        // An unsuffixed literal adopts the type of the assignee (e.g. `*r = 1`)
+        let expected = state.place_type(self.assignee());
+        let value = match &expected {
+            Some(ty) => state.build_expecting(self.value(), ty.clone())?,
+            None => state.without_expected_type(|state| {
+                CodeGen::<AnyValueEnum>::code_gen(self.value(), state)
+            })?,
+        };
+        let value_ty = state.type_of(self.value(), value, expected)?;
+        // The value has to be of the type stored at the assignee, otherwise a wider value
+        // would overwrite the memory after it
+        let check_type = |ty: Type| match value_ty == ty {
+            true => Ok(()),
+            false => Err(CodeGenError::TypeMismatch {
+                expected: ty,
+                actual: value_ty.clone(),
+                span: self.value().span(),
+            }),
+        };
+        let value =
+            BasicValueEnum::try_from(value).map_err(|_| CodeGenError::InvalidLLVMValueType {
+                message: "The RHS of the assignment must be a basic value".into(),
+                span: self.span(),
+            })?;
+
+        let assignee = self
+            .assignee()
+            .try_as_assignee()
+            .ok_or(SemanticError::WrongExpressionKind {
+                message: "Expected an assignee expression",
+                span: self.span(),
+            })?
+            .pattern();
+
+        let pat = match (assignee, self.assignee().try_as_place()) {
+            (Some(pat), _) => pat,
+            // A place without a name (e.g. `*r`) is generated after the value
+            (None, Some(place)) => {
+                let (ptr, ty) = place.code_gen_place(state, true)?;
+                check_type(ty)?;
+                state.build_checked_store(ptr, value, self.assignee().span())?;
+                return Ok(assignment_result(state));
+            }
+            // The value assigned to the underscore expression is discarded
+            (None, None) => return Ok(assignment_result(state)),
+        };
+        let (ptr, ty) = state.symbol_table().get(pat.as_ref()).map_or_else(
+            || {
+                Err(CodeGenError::MissingSymbol {
+                    symbol: pat.to_string().into_boxed_str(),
+                    span: self.assignee().span(),
+                })
+            },
+            |s| match s.value() {
+                AnyValueEnum::PointerValue(p) => Ok((p, s.ty().cloned())),
+                _ => Err(CodeGenError::InvalidLLVMValueType {
+                    message: "Expected a pointer value".into(),
+                    span: self.assignee().span(),
+                }),
+            },
+        )?;
+        if let Some(ty) = ty {
+            check_type(ty)?;
+        }
+
+        // If the value is a unit struct, we don't need to store it.
+        match value {
+            BasicValueEnum::StructValue(s) if s.count_fields() == 0 => {}
+            _ => {
+                state.build_checked_store(ptr, value, self.assignee().span())?;
+            }
+        }
+
+        Ok(assignment_result(state))
+    }
+}
+
+impl ExprCodeGen for AssignASTNode {}
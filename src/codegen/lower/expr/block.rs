@@ -0,0 +1,72 @@
+//! The LLVM IR generation for the [`BlockASTNode`].
+
+use inkwell::values::AnyValueEnum;
+
+use crate::ast::error::SemanticWarning;
+use crate::ast::{ASTNode, BlockASTNode};
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+use crate::token::Position;
+use crate::token::Span;
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for BlockASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        self.build(state, false)
+    }
+}
+
+impl ExprCodeGen for BlockASTNode {
+    fn code_gen_stmt<'ctx>(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        self.build(state, true).map(|_| ())
+    }
+}
+
+impl BlockASTNode {
+    /// Generates the code of the block. If `is_stmt` is `true`, the return expression
+    /// is in a statement position, and the value of the block is unit.
+    fn build<'ctx>(
+        &self,
+        state: &mut CodeGenState<'ctx>,
+        is_stmt: bool,
+    ) -> codegen::Result<AnyValueEnum<'ctx>> {
+        state.with_scope(|state| {
+            for statement in self.statements() {
+                if state.is_terminated() {
+                    self.warn_unreachable(state, statement.span().start());
+                    return Ok(state.build_unit_value(self.span().end()));
+                }
+                state.set_debug_location(statement.span().start());
+                // Only the return expression is the value of the block
+                state.without_expected_type(|state| statement.code_gen(state))?;
+            }
+
+            if let Some(expr) = self.return_expr() {
+                state.set_debug_location(expr.span().start());
+            }
+            match self.return_expr() {
+                Some(expr) if state.is_terminated() => {
+                    self.warn_unreachable(state, expr.span().start());
+                    Ok(state.build_unit_value(self.span().end()))
+                }
+                Some(expr) if is_stmt => expr
+                    .code_gen_stmt(state)
+                    .map(|_| state.build_unit_value(self.span().end())),
+                Some(expr) => CodeGen::<AnyValueEnum>::code_gen(expr, state),
+                None => Ok(state.build_unit_value(self.span().end())),
+            }
+        })
+    }
+
+    /// Warns about the code of the block from `start_pos` to its end,
+    /// which is not generated, because it follows a diverging expression.
+    fn warn_unreachable(&self, state: &mut CodeGenState, start_pos: Position) {
+        let end_pos = self
+            .children()
+            .and_then(|children| children.last())
+            .map_or(start_pos, |child| child.span().end());
+        state.push_warning(SemanticWarning::UnreachableCode {
+            span: Span::new(start_pos, end_pos),
+        });
+    }
+}
@@ -0,0 +1,90 @@
+//! The LLVM IR generation for the [`TypeCastASTNode`].
+
+use inkwell::intrinsics::Intrinsic;
+use inkwell::types::{BasicType, BasicTypeEnum};
+use inkwell::values::{AnyValue, AnyValueEnum};
+
+use crate::ast::{ASTNode, Type, TypeCastASTNode};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+/// Generates the cast of the value to the target type, which can be:
+/// - a numeric type, if the value is numeric or a boolean (`true` is `1`),
+///   except for the casts of booleans to floating-point types,
+/// - the type of the value itself.
+///
+/// The integers are truncated or extended (according to the signedness of the value),
+/// and the floats are rounded towards zero when cast to integers, saturating at the bounds
+/// of the target type (with `NaN` cast to `0`), as in Rust.
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for TypeCastASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        // The target type is not expected from the value, e.g. in `300 as u8` the literal is `i32`
+        let value = state.without_expected_type(|state| {
+            CodeGen::<AnyValueEnum>::code_gen(self.value(), state)
+        })?;
+        let from = state.type_of(self.value(), value, None)?;
+        let to = self.ty();
+        if from == to {
+            return Ok(value);
+        }
+
+        let target = CodeGen::<BasicTypeEnum>::code_gen(self.target_type(), state)?;
+        let value = match (&from, &to) {
+            (from, to) if (from.is_integer() || *from == Type::Bool) && to.is_integer() => {
+                let int = value.into_int_value();
+                let int_type = target.into_int_type();
+                state
+                    .emit(|b| b.build_int_cast_sign_flag(int, int_type, from.is_signed(), "cast"))?
+                    .as_any_value_enum()
+            }
+            (from, to) if from.is_integer() && to.is_float() => {
+                let int = value.into_int_value();
+                let float_type = target.into_float_type();
+                let float = if from.is_signed() {
+                    state.emit(|b| b.build_signed_int_to_float(int, float_type, "cast"))?
+                } else {
+                    state.emit(|b| b.build_unsigned_int_to_float(int, float_type, "cast"))?
+                };
+                float.as_any_value_enum()
+            }
+            (from, to) if from.is_float() && to.is_float() => {
+                let float = value.into_float_value();
+                let float_type = target.into_float_type();
+                state
+                    .emit(|b| b.build_float_cast(float, float_type, "cast"))?
+                    .as_any_value_enum()
+            }
+            (from, to) if from.is_float() && to.is_integer() => {
+                // The saturating intrinsics are overloaded on both the result and the operand type
+                let name = if to.is_signed() {
+                    "llvm.fptosi.sat"
+                } else {
+                    "llvm.fptoui.sat"
+                };
+                let float = value.into_float_value();
+                let operand_type = float.get_type().as_basic_type_enum();
+                let intrinsic = Intrinsic::find(name)
+                    .and_then(|i| i.get_declaration(state.module(), &[target, operand_type]))
+                    .expect("The saturating float to int intrinsics are always available");
+                let args = [float.into()];
+                state
+                    .emit(|b| b.build_call(intrinsic, &args, "cast"))?
+                    .try_as_basic_value()
+                    .left()
+                    .expect("The intrinsic should return a value")
+                    .as_any_value_enum()
+            }
+            _ => {
+                return Err(CodeGenError::UnsupportedType {
+                    message: format!("Cannot cast `{}` as `{}`", from, to).into_boxed_str(),
+                    span: self.span(),
+                });
+            }
+        };
+        Ok(value)
+    }
+}
+
+impl ExprCodeGen for TypeCastASTNode {}
@@ -0,0 +1,181 @@
+//! The LLVM IR generation for the [`FunCallASTNode`].
+
+use inkwell::intrinsics::Intrinsic;
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::{AnyValue, AnyValueEnum, BasicMetadataValueEnum, BasicValueEnum};
+
+use crate::ast::error::SemanticError;
+use crate::ast::{ASTNode, FunCallASTNode};
+use crate::builtin::Builtin;
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for FunCallASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        // The functions declared in the crate shadow the built-in ones
+        if state.symbol_table().get(self.path()).is_none() {
+            if let Some(builtin) = Builtin::from_name(self.path()) {
+                return self.build_builtin_call(builtin, state);
+            }
+        }
+
+        let fn_value = state.symbol_table().get(self.path()).map_or_else(
+            || {
+                Err(CodeGenError::MissingSymbol {
+                    symbol: self.path().into(),
+                    span: self.span(),
+                })
+            },
+            |s| match s.value() {
+                AnyValueEnum::FunctionValue(f) => Ok(f),
+                _ => Err(CodeGenError::InvalidLLVMValueType {
+                    message: format!("`{}` is not a function", self.path()).into_boxed_str(),
+                    span: self.span(),
+                }),
+            },
+        )?;
+
+        // The signature is registered for all the functions declared in the crate
+        let param_types = state
+            .functions()
+            .get(self.path())
+            .map(|sig| sig.param_types().to_vec());
+        if let Some(param_types) = &param_types {
+            if param_types.len() != self.args().len() {
+                return Err(SemanticError::ArgumentCountMismatch {
+                    name: self.path().into(),
+                    expected: param_types.len(),
+                    actual: self.args().len(),
+                    span: self.span(),
+                }
+                .into());
+            }
+        }
+
+        // The arguments are evaluated from left to right (see the `ast::expr` module)
+        let args = self
+            .args()
+            .iter()
+            .enumerate()
+            .map(|(i, arg_node)| {
+                let span = arg_node.span();
+                let expected = param_types.as_ref().map(|types| types[i].clone());
+                let arg = match &expected {
+                    Some(expected) => state.build_expecting(arg_node.as_ref(), expected.clone())?,
+                    None => CodeGen::<AnyValueEnum>::code_gen(arg_node.as_ref(), state)?,
+                };
+                if let Some(expected) = expected {
+                    let actual = state.type_of(arg_node.as_ref(), arg, Some(expected.clone()))?;
+                    if actual != expected {
+                        return Err(CodeGenError::TypeMismatch {
+                            expected,
+                            actual,
+                            span,
+                        });
+                    }
+                }
+                BasicMetadataValueEnum::try_from(arg).map_err(|_| {
+                    CodeGenError::InvalidLLVMValueType {
+                        message: "The expression is not a basic metadata value".into(),
+                        span: self.span(),
+                    }
+                })
+            })
+            .collect::<Result<Vec<BasicMetadataValueEnum>, _>>()?;
+
+        let call_name = format!("call_{}", self.path());
+        let call = state.emit(|builder| builder.build_call(fn_value, &args, call_name.as_str()))?;
+        call.set_call_convention(fn_value.get_call_conventions());
+
+        // A call to a function returning unit (i.e. `void` in LLVM) evaluates to the unit value
+        Ok(match call.try_as_basic_value().left() {
+            Some(value) => value.as_any_value_enum(),
+            None => state.build_unit_value(self.span().end()),
+        })
+    }
+}
+
+impl ExprCodeGen for FunCallASTNode {}
+
+impl FunCallASTNode {
+    /// Generates a call to the given [built-in function](crate::builtin), which is lowered
+    /// to the corresponding LLVM intrinsic.
+    ///
+    /// The arguments (and so the result) are of the type of the first one, which adopts
+    /// the expected type of the call (e.g. `let x: u8 = max(1, 2);`).
+    fn build_builtin_call<'ctx>(
+        &self,
+        builtin: Builtin,
+        state: &mut CodeGenState<'ctx>,
+    ) -> codegen::Result<AnyValueEnum<'ctx>> {
+        if self.args().len() != builtin.param_count() {
+            return Err(SemanticError::ArgumentCountMismatch {
+                name: self.path().into(),
+                expected: builtin.param_count(),
+                actual: self.args().len(),
+                span: self.span(),
+            }
+            .into());
+        }
+
+        //#region Arguments
+        // The arguments are evaluated from left to right (see the `ast::expr` module)
+        let expected = state.expected_type();
+        let first = CodeGen::<AnyValueEnum>::code_gen(self.args()[0].as_ref(), state)?;
+        let ty = state.type_of(self.args()[0].as_ref(), first, expected)?;
+        if !builtin.supports(&ty) {
+            return Err(CodeGenError::UnsupportedType {
+                message: format!("Function `{}` cannot be called with `{}`", builtin, ty).into(),
+                span: self.args()[0].span(),
+            });
+        }
+        let mut args = vec![BasicValueEnum::try_from(first).expect("Numbers are basic values")];
+        for arg in &self.args()[1..] {
+            let value = state.build_expecting(arg.as_ref(), ty.clone())?;
+            let actual = state.type_of(arg.as_ref(), value, Some(ty.clone()))?;
+            if actual != ty {
+                return Err(CodeGenError::TypeMismatch {
+                    expected: ty,
+                    actual,
+                    span: arg.span(),
+                });
+            }
+            args.push(BasicValueEnum::try_from(value).expect("Numbers are basic values"));
+        }
+        //#endregion
+
+        //#region Intrinsic
+        let name = match (builtin, &ty) {
+            (Builtin::Min, ty) if ty.is_float() => "llvm.minnum",
+            (Builtin::Max, ty) if ty.is_float() => "llvm.maxnum",
+            (Builtin::Min, ty) if ty.is_signed() => "llvm.smin",
+            (Builtin::Max, ty) if ty.is_signed() => "llvm.smax",
+            (Builtin::Min, _) => "llvm.umin",
+            (Builtin::Max, _) => "llvm.umax",
+            (Builtin::Abs, ty) if ty.is_float() => "llvm.fabs",
+            (Builtin::Abs, _) => {
+                // The minimum value is not poison, so that it wraps around to itself
+                let is_int_min_poison = state.context().bool_type().const_zero();
+                args.push(is_int_min_poison.into());
+                "llvm.abs"
+            }
+            (Builtin::Sqrt, _) => "llvm.sqrt",
+        };
+        // The intrinsics are overloaded on the type of the arguments
+        let arg_type: BasicTypeEnum = args[0].get_type();
+        let intrinsic = Intrinsic::find(name)
+            .and_then(|i| i.get_declaration(state.module(), &[arg_type]))
+            .expect("The numeric intrinsics are always available");
+        let args: Vec<BasicMetadataValueEnum> = args.into_iter().map(Into::into).collect();
+        let call = state.emit(|builder| builder.build_call(intrinsic, &args, builtin.name()))?;
+        //#endregion
+
+        Ok(call
+            .try_as_basic_value()
+            .left()
+            .expect("The intrinsic should return a value")
+            .as_any_value_enum())
+    }
+}
@@ -0,0 +1,16 @@
+//! The LLVM IR generation for the [`GroupedExprASTNode`].
+
+use inkwell::values::AnyValueEnum;
+
+use crate::ast::GroupedExprASTNode;
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for GroupedExprASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        CodeGen::<AnyValueEnum>::code_gen(self.expr(), state)
+    }
+}
+
+impl ExprCodeGen for GroupedExprASTNode {}
@@ -0,0 +1,192 @@
+//! The LLVM IR generation for the [`IfASTNode`] and the [`ElseASTNode`].
+
+use inkwell::values::{AnyValue, AnyValueEnum, BasicValueEnum};
+
+use crate::ast::error::SemanticWarning;
+use crate::ast::{ASTNode, ElseASTNode, ExprASTNode, IfASTNode, Type};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, ()> for ElseASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        self.expr().code_gen_stmt(state)
+    }
+}
+
+impl IfASTNode {
+    /// Returns the value of the condition if it is a constant,
+    /// warning about the branch that is never executed.
+    fn const_condition(&self, state: &mut CodeGenState) -> Option<bool> {
+        let condition = self.condition().const_eval().and_then(|c| c.as_bool())?;
+        let eliminated = if condition {
+            self.else_node().map(|else_node| else_node.span())
+        } else {
+            Some(self.then_block().span())
+        };
+        if let Some(span) = eliminated {
+            state.push_warning(SemanticWarning::UnreachableBranch { condition, span });
+        }
+        Some(condition)
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for IfASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        // A constant condition selects the branch at compile time
+        if let Some(condition) = self.const_condition(state) {
+            return match (condition, self.else_node()) {
+                (true, _) => CodeGen::<AnyValueEnum>::code_gen(self.then_block(), state),
+                (false, None) => Ok(state.build_unit_value(self.span().end())),
+                (false, Some(else_node)) => {
+                    CodeGen::<AnyValueEnum>::code_gen(else_node.expr(), state)
+                }
+            };
+        }
+
+        // Without an `else` branch, the value is always unit (like in a statement position)
+        let Some(else_node) = self.else_node() else {
+            self.code_gen_stmt(state)?;
+            return Ok(state.build_unit_value(self.span().end()));
+        };
+
+        let no_bb_panic = || panic!("Builder not in a block");
+
+        let parent_fn = state
+            .get_current_function()
+            .unwrap_or_else(|| panic!("Expression outside of function"));
+
+        //#region Labels
+        let then_bb = state.context().append_basic_block(parent_fn, "then");
+        let else_bb = state.context().append_basic_block(parent_fn, "else");
+        let end_bb = state.context().append_basic_block(parent_fn, "end");
+        //#endregion
+
+        //#region Condition
+        let cond =
+            state.without_expected_type(|state| state.build_condition(self.condition(), true))?;
+        state.emit(|builder| builder.build_conditional_branch(cond, then_bb, else_bb))?;
+        //#endregion
+
+        //#region Branches
+        // Only the branches that don't diverge flow into the end block with their values
+        let expected = state.expected_type();
+        let mut incoming: Vec<(_, &dyn ExprASTNode, _)> = Vec::with_capacity(2);
+
+        state.builder().position_at_end(then_bb);
+        let then_value = CodeGen::<AnyValueEnum>::code_gen(self.then_block(), state)?;
+        if !state.is_terminated() {
+            let bb = state
+                .builder()
+                .get_insert_block()
+                .unwrap_or_else(no_bb_panic);
+            incoming.push((then_value, self.then_block(), bb));
+            state.emit(|builder| builder.build_unconditional_branch(end_bb))?;
+        }
+
+        state.builder().position_at_end(else_bb);
+        let else_expr = else_node.expr();
+        let else_value = CodeGen::<AnyValueEnum>::code_gen(else_expr, state)?;
+        if !state.is_terminated() {
+            let bb = state
+                .builder()
+                .get_insert_block()
+                .unwrap_or_else(no_bb_panic);
+            incoming.push((else_value, else_expr, bb));
+            state.emit(|builder| builder.build_unconditional_branch(end_bb))?;
+        }
+        //#endregion
+
+        //#region End
+        state.builder().position_at_end(end_bb);
+        match incoming[..] {
+            // Both branches diverge, so the end is never reached
+            [] => {
+                state.emit(|builder| builder.build_unreachable())?;
+                Ok(state.build_unit_value(self.span().end()))
+            }
+            [(value, _, _)] => Ok(value),
+            [(then_value, then_expr, then_bb), (else_value, else_expr, else_bb)] => {
+                let (then_span, else_span) = (then_expr.span(), else_expr.span());
+                let then_ty = state.type_of(then_expr, then_value, expected.clone())?;
+                let else_ty = state.type_of(else_expr, else_value, expected)?;
+                if then_ty != else_ty {
+                    return Err(CodeGenError::TypeMismatch {
+                        expected: then_ty,
+                        actual: else_ty,
+                        span: else_span,
+                    });
+                }
+                if then_ty == Type::Unit {
+                    return Ok(state.build_unit_value(self.span().end()));
+                }
+
+                let basic = |value, span| {
+                    BasicValueEnum::try_from(value).map_err(|_| {
+                        CodeGenError::InvalidLLVMValueType {
+                            message: "Branch value must be a basic value".into(),
+                            span,
+                        }
+                    })
+                };
+                let then_value = basic(then_value, then_span)?;
+                let else_value = basic(else_value, else_span)?;
+                let phi =
+                    state.emit(|builder| builder.build_phi(then_value.get_type(), "ifvalue"))?;
+                phi.add_incoming(&[(&then_value, then_bb), (&else_value, else_bb)]);
+                Ok(phi.as_any_value_enum())
+            }
+            _ => unreachable!(),
+        }
+        //#endregion
+    }
+}
+
+impl ExprCodeGen for IfASTNode {
+    fn code_gen_stmt<'ctx>(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        if let Some(condition) = self.const_condition(state) {
+            return match (condition, self.else_node()) {
+                (true, _) => self.then_block().code_gen_stmt(state),
+                (false, None) => Ok(()),
+                (false, Some(else_node)) => else_node.code_gen(state),
+            };
+        }
+
+        let parent_fn = state
+            .get_current_function()
+            .unwrap_or_else(|| panic!("Statement outside of function"));
+
+        //#region Labels
+        let then_bb = state.context().append_basic_block(parent_fn, "then");
+        let else_bb = self
+            .else_node()
+            .as_ref()
+            .map(|_| state.context().append_basic_block(parent_fn, "else"));
+        let end_bb = state.context().append_basic_block(parent_fn, "end");
+        //#endregion
+
+        //#region Condition
+        let cond = state.build_condition(self.condition(), true)?;
+        state.emit(|builder| {
+            builder.build_conditional_branch(cond, then_bb, else_bb.unwrap_or(end_bb))
+        })?;
+        //#endregion
+
+        //#region Branches
+        // The values of the branches are discarded, so their types don't have to match
+        state.builder().position_at_end(then_bb);
+        self.then_block().code_gen_stmt(state)?;
+        state.build_branch_if_unterminated(end_bb)?;
+
+        if let (Some(else_bb), Some(else_node)) = (else_bb, self.else_node()) {
+            state.builder().position_at_end(else_bb);
+            else_node.code_gen(state)?;
+            state.build_branch_if_unterminated(end_bb)?;
+        }
+        //#endregion
+
+        state.builder().position_at_end(end_bb);
+        Ok(())
+    }
+}
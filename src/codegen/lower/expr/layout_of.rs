@@ -0,0 +1,27 @@
+//! The LLVM IR generation for the [`LayoutOfASTNode`].
+
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::AnyValueEnum;
+
+use crate::ast::{ASTNode, ConstValue, ExprASTNode, LayoutOfASTNode, TypeASTMetaNode};
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LayoutOfASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let value = self
+            .const_eval_in(state.static_values())
+            .expect("Layout queries are always evaluated at compile time");
+        let ConstValue::Int(value) = value else {
+            unreachable!("Layout queries evaluate to integers");
+        };
+        let ty = TypeASTMetaNode::new(self.infer_type(state.expected_type()), self.span());
+        let int_type = CodeGen::<BasicTypeEnum>::code_gen(&ty, state)?.into_int_type();
+        Ok(AnyValueEnum::IntValue(
+            int_type.const_int(value as u64, true),
+        ))
+    }
+}
+
+impl ExprCodeGen for LayoutOfASTNode {}
@@ -0,0 +1,7 @@
+//! The LLVM IR generation for the [literals](crate::ast::LiteralASTNode).
+
+mod bool;
+mod float;
+mod int;
+mod str;
+mod unit;
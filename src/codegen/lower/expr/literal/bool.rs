@@ -0,0 +1,18 @@
+//! The LLVM IR generation for the boolean [literals](LiteralASTNode).
+
+use inkwell::values::{AnyValue, AnyValueEnum};
+
+use crate::ast::LiteralASTNode;
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LiteralASTNode<bool> {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let bool_type = state.context().bool_type();
+        let value = bool_type.const_int(*self.value() as u64, false);
+        Ok(value.as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for LiteralASTNode<bool> {}
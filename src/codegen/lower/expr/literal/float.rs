@@ -0,0 +1,84 @@
+//! The LLVM IR generation for the floating-point [literals](LiteralASTNode).
+
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::{AnyValue, AnyValueEnum};
+
+use crate::ast::error::SemanticWarning;
+use crate::ast::{ASTNode, LiteralASTNode, Type, TypeASTMetaNode};
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LiteralASTNode<f64> {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let ty = self.infer_type(state.expected_type());
+        if let Some(text) = self.text() {
+            // An `f32` literal is rounded once more
+            let value = match ty {
+                Type::F32 => *self.value() as f32 as f64,
+                _ => *self.value(),
+            };
+            if state.options().inexact_float_lint() && !is_exact(text, value) {
+                state.push_warning(SemanticWarning::InexactFloatLiteral {
+                    literal: text.into(),
+                    span: self.span(),
+                });
+            }
+        }
+
+        let ty = TypeASTMetaNode::new(ty, self.span());
+        let float_type = CodeGen::<BasicTypeEnum>::code_gen(&ty, state)?.into_float_type();
+        let value = float_type.const_float(*self.value());
+        Ok(value.as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for LiteralASTNode<f64> {}
+
+/// Returns whether the decimal literal `text` is exactly equal to its parsed `value`.
+fn is_exact(text: &str, value: f64) -> bool {
+    // Every finite `f64` is a finite decimal with at most 1074 fractional digits
+    value.is_finite() && normalize(text) == normalize(&format!("{:.1074}", value))
+}
+
+/// Converts a decimal number (`digits[.digits][e[-]digits]`) into its significant digits
+/// (without leading and trailing zeros) and the exponent of the last one.
+fn normalize(text: &str) -> (String, i64) {
+    let (mantissa, mut exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse().unwrap_or(0)),
+        None => (text, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    exponent -= frac.len() as i64;
+
+    let digits = format!("{int}{frac}");
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+    if significant.is_empty() {
+        return (String::new(), 0);
+    }
+    exponent += (digits.len() - significant.len()) as i64;
+    (significant.to_owned(), exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exactness() {
+        for exact in [
+            "0.5",
+            "1.25",
+            "007.500",
+            "0.0",
+            "1.5e3",
+            "4503599627370497.0",
+        ] {
+            assert!(is_exact(exact, exact.parse().unwrap()), "{exact}");
+        }
+        for inexact in ["0.1", "3.14", "9007199254740993.0", "1.0e400"] {
+            assert!(!is_exact(inexact, inexact.parse().unwrap()), "{inexact}");
+        }
+    }
+}
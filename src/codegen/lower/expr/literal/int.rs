@@ -0,0 +1,21 @@
+//! The LLVM IR generation for the integer [literals](LiteralASTNode).
+
+use inkwell::values::AnyValueEnum;
+
+use crate::ast::LiteralASTNode;
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LiteralASTNode<i128> {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let ty = self.infer_type(state.expected_type());
+        let value = self.check_range(&ty, false)?;
+        let int_type = ty.llvm_type(state).into_int_type();
+        Ok(AnyValueEnum::IntValue(
+            int_type.const_int(value as u64, true),
+        ))
+    }
+}
+
+impl ExprCodeGen for LiteralASTNode<i128> {}
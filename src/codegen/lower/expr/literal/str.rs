@@ -0,0 +1,20 @@
+//! The LLVM IR generation for the string [literals](LiteralASTNode).
+
+use std::rc::Rc;
+
+use inkwell::values::{AnyValue, AnyValueEnum};
+
+use crate::ast::LiteralASTNode;
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::pool::PoolConstant;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LiteralASTNode<Rc<str>> {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let global = state.intern_constant(PoolConstant::c_string(self.value()));
+        Ok(global.as_pointer_value().as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for LiteralASTNode<Rc<str>> {}
@@ -0,0 +1,45 @@
+//! The LLVM IR generation for the unit [literal](LiteralASTNode).
+
+use inkwell::types::{AnyType, StructType};
+use inkwell::values::{AnyValue, AnyValueEnum};
+
+use crate::ast::LiteralASTNode;
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+use crate::token::Position;
+use crate::token::Span;
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LiteralASTNode<()> {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let value = state.unit_type().const_zero();
+        Ok(value.as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for LiteralASTNode<()> {}
+
+impl<'ctx> CodeGenState<'ctx> {
+    /// Returns the LLVM type of the unit values, i.e. an empty struct.
+    ///
+    /// See [`Type::Unit`](crate::ast::Type::Unit) for the representation of the unit type.
+    pub fn unit_type(&self) -> StructType<'ctx> {
+        self.context().struct_type(&[], false)
+    }
+
+    /// Returns whether the given value is a unit value.
+    pub fn is_unit_value(&self, value: AnyValueEnum<'ctx>) -> bool {
+        value.get_type() == self.unit_type().as_any_type_enum()
+    }
+
+    //TODO Add Examples to the documentation
+    /// Generates a new unit [`LLVM value`](AnyValueEnum) with the span that starts and ends at `end_pos`.
+    pub fn build_unit_value(
+        self: &mut CodeGenState<'ctx>,
+        end_pos: Position,
+    ) -> AnyValueEnum<'ctx> {
+        let span = Span::new(end_pos, end_pos);
+        let unit = LiteralASTNode::<()>::new(span);
+        CodeGen::<AnyValueEnum>::code_gen(&unit, self).unwrap()
+    }
+}
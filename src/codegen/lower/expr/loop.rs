@@ -0,0 +1,6 @@
+//! The LLVM IR generation for the loops.
+//!
+//! The `for` and `do`-`while` loops are lowered to their desugared forms.
+
+mod inf_loop;
+mod r#while;
@@ -0,0 +1,47 @@
+//! The LLVM IR generation for the [`InfLoopASTNode`].
+
+use inkwell::values::AnyValueEnum;
+
+use crate::ast::{ASTNode, BlockASTNode, InfLoopASTNode};
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+use crate::token::Position;
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for InfLoopASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        state.build_inf_loop(self.body(), self.span().end())
+    }
+}
+
+impl ExprCodeGen for InfLoopASTNode {}
+
+impl<'ctx> CodeGenState<'ctx> {
+    /// Generates an infinite loop with the given `body`,
+    /// returning a unit value with the span at `end_pos`.
+    ///
+    /// It is used for both `loop` and `while true`.
+    pub(crate) fn build_inf_loop(
+        &mut self,
+        body: &BlockASTNode,
+        end_pos: Position,
+    ) -> codegen::Result<AnyValueEnum<'ctx>> {
+        //TODO Type checking -> loop has type `!` (unless it has a `break` statement)
+        let parent_fn = self
+            .get_current_function()
+            .unwrap_or_else(|| panic!("Statement outside of function"));
+
+        //#region Label
+        let start_bb = self.context().append_basic_block(parent_fn, "loop");
+        self.emit(|builder| builder.build_unconditional_branch(start_bb))?;
+        //#endregion
+
+        //#region Body
+        self.builder().position_at_end(start_bb);
+        body.code_gen_stmt(self)?;
+        self.build_loop_back_edge(start_bb, body.span())?;
+        //#endregion
+
+        Ok(self.build_unit_value(end_pos))
+    }
+}
@@ -0,0 +1,58 @@
+//! The LLVM IR generation for the [`WhileASTNode`].
+
+use inkwell::values::AnyValueEnum;
+
+use crate::ast::error::SemanticWarning;
+use crate::ast::{ASTNode, WhileASTNode};
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for WhileASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        match self.condition().const_eval().and_then(|c| c.as_bool()) {
+            // The body is never executed, so the loop is not generated at all
+            Some(false) => {
+                state.push_warning(SemanticWarning::UnreachableBranch {
+                    condition: false,
+                    span: self.body().span(),
+                });
+                return Ok(state.build_unit_value(self.span().end()));
+            }
+            // The condition does not need to be checked, so this is just a `loop`
+            Some(true) => return state.build_inf_loop(self.body(), self.span().end()),
+            None => {}
+        }
+
+        //TODO Type checking -> loop has type `!` (unless it has a `break` statement)
+        let parent_fn = state
+            .get_current_function()
+            .unwrap_or_else(|| panic!("Statement outside of function"));
+
+        //#region Condition
+        let cond_bb = state.context().append_basic_block(parent_fn, "cond");
+        state.emit(|builder| builder.build_unconditional_branch(cond_bb))?;
+        state.builder().position_at_end(cond_bb);
+        let Some(cond) = state.build_loop_condition(self.condition())? else {
+            // The condition diverges, so the code after the loop is unreachable
+            state.build_loop_back_edge(cond_bb, self.body().span())?;
+            return Ok(state.build_unit_value(self.span().end()));
+        };
+        let body_bb = state.context().append_basic_block(parent_fn, "body");
+        let end_bb = state.context().append_basic_block(parent_fn, "end");
+        state.emit(|builder| builder.build_conditional_branch(cond, body_bb, end_bb))?;
+        //#endregion
+
+        //#region Body
+        state.builder().position_at_end(body_bb);
+        self.body().code_gen_stmt(state)?;
+        state.build_loop_back_edge(cond_bb, self.body().span())?;
+        //#endregion
+
+        state.builder().position_at_end(end_bb);
+
+        Ok(state.build_unit_value(self.span().end()))
+    }
+}
+
+impl ExprCodeGen for WhileASTNode {}
@@ -0,0 +1,8 @@
+//! The LLVM IR generation for the operator expressions.
+
+mod arith;
+mod borrow;
+mod comp;
+mod deref;
+mod lazy_bool;
+mod neg;
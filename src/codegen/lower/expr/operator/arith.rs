@@ -0,0 +1,229 @@
+//! The LLVM IR generation for the [`ArithExprASTNode`].
+
+use inkwell::builder::Builder;
+use inkwell::intrinsics::Intrinsic;
+use inkwell::values::{AnyValue, AnyValueEnum, IntValue};
+use inkwell::IntPredicate;
+
+use crate::ast::{ASTNode, ArithExprASTNode, ArithOperator, Type};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for ArithExprASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        //TODO Refactor to use traits
+        // The operands are evaluated from left to right (see the `ast::expr` module)
+        let expected = state.expected_type();
+        let lhs = CodeGen::<AnyValueEnum>::code_gen(self.lhs(), state)?;
+        let lhs_ty = state.type_of(self.lhs(), lhs, expected)?;
+        // An unsuffixed literal on the right adopts the type of the left operand (e.g. `x + 1`)
+        let rhs = state.build_expecting(self.rhs(), lhs_ty.clone())?;
+
+        //TODO Split into HIR & MIR and run type checking on HIR->MIR
+        //#region Type checking
+        let expr_type = {
+            let rhs_ty = state.type_of(self.rhs(), rhs, Some(lhs_ty.clone()))?;
+
+            if let (Type::Unit, _) | (_, Type::Unit) = (&lhs_ty, &rhs_ty) {
+                return Err(CodeGenError::UnsupportedType {
+                    message: "Cannot perform arithmetic operations on unit type".into(),
+                    span: self.span(),
+                });
+            }
+            if let (Type::Str, _) | (_, Type::Str) = (&lhs_ty, &rhs_ty) {
+                return Err(CodeGenError::UnsupportedType {
+                    message: "Cannot perform arithmetic operations on strings".into(),
+                    span: self.span(),
+                });
+            }
+            if let (Type::Tuple(_), _) | (_, Type::Tuple(_)) = (&lhs_ty, &rhs_ty) {
+                return Err(CodeGenError::UnsupportedType {
+                    message: "Cannot perform arithmetic operations on tuples".into(),
+                    span: self.span(),
+                });
+            }
+            if let (Type::Ref { .. }, _) | (_, Type::Ref { .. }) = (&lhs_ty, &rhs_ty) {
+                return Err(CodeGenError::UnsupportedType {
+                    message: "Cannot perform arithmetic operations on references \
+                    (dereference them first)"
+                        .into(),
+                    span: self.span(),
+                });
+            }
+            if lhs_ty != rhs_ty {
+                return Err(CodeGenError::TypeMismatch {
+                    expected: lhs_ty,
+                    actual: rhs_ty,
+                    span: self.rhs().span(),
+                });
+            }
+
+            lhs_ty
+        };
+        //#endregion
+
+        // The divisor of the integer division and remainder is guarded against the overflow
+        let rhs = match (&expr_type, self.operator()) {
+            (ty, ArithOperator::Div | ArithOperator::Rem) if ty.is_integer() => state
+                .build_guarded_divisor(lhs.into_int_value(), rhs.into_int_value(), ty.is_signed())?
+                .as_any_value_enum(),
+            _ => rhs,
+        };
+
+        //#region Type-specific code generation
+        let codegen_int = |builder: &mut Builder<'ctx>| {
+            let lhs = lhs.into_int_value();
+            let rhs = rhs.into_int_value();
+            let signed = expr_type.is_signed();
+
+            match self.operator() {
+                ArithOperator::Add => builder.build_int_add(lhs, rhs, "add"),
+                ArithOperator::Sub => builder.build_int_sub(lhs, rhs, "sub"),
+                ArithOperator::Mul => builder.build_int_mul(lhs, rhs, "mul"),
+                ArithOperator::Div if signed => builder.build_int_signed_div(lhs, rhs, "div"),
+                ArithOperator::Div => builder.build_int_unsigned_div(lhs, rhs, "div"),
+                ArithOperator::Rem if signed => builder.build_int_signed_rem(lhs, rhs, "rem"),
+                ArithOperator::Rem => builder.build_int_unsigned_rem(lhs, rhs, "rem"),
+                ArithOperator::BitAnd => builder.build_and(lhs, rhs, "and"),
+                ArithOperator::BitOr => builder.build_or(lhs, rhs, "or"),
+                ArithOperator::BitXor => builder.build_xor(lhs, rhs, "xor"),
+            }
+            .map(|v| v.as_any_value_enum())
+            .map_err(Into::<CodeGenError>::into)
+        };
+        let codegen_float = |builder: &mut Builder<'ctx>| {
+            let lhs = lhs.into_float_value();
+            let rhs = rhs.into_float_value();
+
+            match self.operator() {
+                ArithOperator::Add => builder.build_float_add(lhs, rhs, "add"),
+                ArithOperator::Sub => builder.build_float_sub(lhs, rhs, "sub"),
+                ArithOperator::Mul => builder.build_float_mul(lhs, rhs, "mul"),
+                ArithOperator::Div => builder.build_float_div(lhs, rhs, "div"),
+                ArithOperator::Rem => builder.build_float_rem(lhs, rhs, "rem"),
+                ArithOperator::BitAnd | ArithOperator::BitOr | ArithOperator::BitXor => {
+                    let message = "Bitwise operations are not supported on floating point operands";
+                    return Err(CodeGenError::UnsupportedType {
+                        message: message.into(),
+                        span: self.span(),
+                    });
+                }
+            }
+            .map(|v| v.as_any_value_enum())
+            .map_err(Into::<CodeGenError>::into)
+        };
+        let codegen_bool = |builder: &mut Builder<'ctx>| {
+            let lhs = lhs.into_int_value();
+            let rhs = rhs.into_int_value();
+
+            match self.operator() {
+                ArithOperator::BitAnd => builder.build_and(lhs, rhs, "and"),
+                ArithOperator::BitOr => builder.build_or(lhs, rhs, "or"),
+                ArithOperator::BitXor => builder.build_xor(lhs, rhs, "xor"),
+                ArithOperator::Add
+                | ArithOperator::Sub
+                | ArithOperator::Mul
+                | ArithOperator::Div
+                | ArithOperator::Rem => {
+                    let message = "Only bitwise operations are supported on boolean operands";
+                    return Err(CodeGenError::UnsupportedType {
+                        message: message.into(),
+                        span: self.span(),
+                    });
+                }
+            }
+            .map(|v| v.as_any_value_enum())
+            .map_err(Into::<CodeGenError>::into)
+        };
+        //#endregion
+
+        let builder = state.builder();
+        match expr_type {
+            Type::Bool => codegen_bool(builder),
+            Type::Unit | Type::Str | Type::Tuple(_) | Type::Ref { .. } => {
+                unreachable!(
+                    "Unit, string, tuple and reference types should have been handled earlier."
+                )
+            }
+            ty if ty.is_float() => codegen_float(builder),
+            _ => codegen_int(builder),
+        }
+    }
+}
+
+impl ExprCodeGen for ArithExprASTNode {}
+
+impl<'ctx> CodeGenState<'ctx> {
+    /// Generates the checks of the integer division (or remainder) of `lhs` by `rhs`,
+    /// and returns the divisor that should be used instead of `rhs`.
+    ///
+    /// The division rounds towards zero (i.e. the remainder has the sign of `lhs`), as in Rust.
+    /// With the [overflow checks](crate::codegen::CodeGenOptions::overflow_checks), the division traps
+    /// if `rhs` is zero or if the `signed` minimum (e.g. `i32::MIN`) is divided by `-1`.
+    /// Otherwise, the latter wraps around, which is achieved by dividing by `1` instead.
+    pub(crate) fn build_guarded_divisor(
+        &mut self,
+        lhs: IntValue<'ctx>,
+        rhs: IntValue<'ctx>,
+        signed: bool,
+    ) -> codegen::Result<IntValue<'ctx>> {
+        let ty = lhs.get_type();
+        // The unsigned division cannot overflow
+        let overflow = if signed {
+            let min = ty.const_int(1 << (ty.get_bit_width() - 1), false);
+            let minus_one = ty.const_all_ones();
+
+            let builder = self.builder();
+            let is_min = builder.build_int_compare(IntPredicate::EQ, lhs, min, "is_min")?;
+            let is_minus_one =
+                builder.build_int_compare(IntPredicate::EQ, rhs, minus_one, "is_m1")?;
+            Some(builder.build_and(is_min, is_minus_one, "div_overflow")?)
+        } else {
+            None
+        };
+
+        if !self.options().overflow_checks() {
+            let Some(overflow) = overflow else {
+                return Ok(rhs);
+            };
+            let one = ty.const_int(1, false);
+            let divisor = self.builder().build_select(overflow, one, rhs, "divisor")?;
+            return Ok(divisor.into_int_value());
+        }
+
+        let is_zero =
+            self.builder()
+                .build_int_compare(IntPredicate::EQ, rhs, ty.const_zero(), "is_zero")?;
+        let invalid = match overflow {
+            Some(overflow) => self.builder().build_or(overflow, is_zero, "div_invalid")?,
+            None => is_zero,
+        };
+
+        let parent_fn = self
+            .get_current_function()
+            .unwrap_or_else(|| panic!("Expression outside of function"));
+        let trap_bb = self.context().append_basic_block(parent_fn, "div_trap");
+        let ok_bb = self.context().append_basic_block(parent_fn, "div_ok");
+        self.builder()
+            .build_conditional_branch(invalid, trap_bb, ok_bb)?;
+
+        self.builder().position_at_end(trap_bb);
+        self.build_trap()?;
+
+        self.builder().position_at_end(ok_bb);
+        Ok(rhs)
+    }
+
+    /// Generates a call to the `llvm.trap` intrinsic, which aborts the execution,
+    /// followed by an `unreachable` terminator.
+    pub(crate) fn build_trap(&mut self) -> codegen::Result<()> {
+        let trap = Intrinsic::find("llvm.trap")
+            .and_then(|i| i.get_declaration(self.module(), &[]))
+            .expect("The `llvm.trap` intrinsic is always available");
+        self.builder().build_call(trap, &[], "")?;
+        self.builder().build_unreachable()?;
+        Ok(())
+    }
+}
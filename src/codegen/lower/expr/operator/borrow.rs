@@ -0,0 +1,137 @@
+//! The LLVM IR generation for the [`BorrowExprASTNode`].
+
+use std::rc::Rc;
+
+use inkwell::values::{AnyValue, AnyValueEnum, BasicValueEnum, PointerValue};
+
+use crate::ast::{
+    BlockASTNode, BorrowExprASTNode, DerefExprASTNode, ElseASTNode, ElseExpr, ExprASTNode,
+    GroupedExprASTNode, IfASTNode, LetASTNode, PathASTNode, TupleIndexASTNode, Type,
+    UnsafeBlockASTNode,
+};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::{ExprCodeGen, PlaceCodeGen};
+use crate::codegen::{CodeGen, CodeGenState};
+
+/// A [place expression](crate::ast::PlaceExprASTNode) is borrowed in place, while the value of any other
+/// expression is stored in a temporary first (e.g. `&5`), like in Rust.
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for BorrowExprASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let (ptr, ty) = match self.expr().try_as_place() {
+            Some(place) => place.code_gen_place(state, self.is_mutable())?,
+            None => {
+                // The referenced type is expected from the value, e.g. in `let r: &u8 = &1;`
+                let expected = state.expected_type().and_then(|ty| ty.referenced_type());
+                let value = match &expected {
+                    Some(ty) => state.build_expecting(self.expr(), ty.clone())?,
+                    None => state.without_expected_type(|state| {
+                        CodeGen::<AnyValueEnum>::code_gen(self.expr(), state)
+                    })?,
+                };
+                let ty = state.type_of(self.expr(), value, expected)?;
+                let value = BasicValueEnum::try_from(value).map_err(|_| {
+                    CodeGenError::InvalidLLVMValueType {
+                        message: "The borrowed value must be a basic value".into(),
+                        span: self.expr().span(),
+                    }
+                })?;
+                (state.build_temporary(value)?, ty)
+            }
+        };
+
+        state.record_reference_type(self, Type::reference(ty, self.is_mutable()));
+        Ok(ptr.as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for BorrowExprASTNode {}
+
+impl<'ctx> CodeGenState<'ctx> {
+    /// Stores the given value in a new temporary (on the stack of the current function)
+    /// and returns the pointer to it, e.g. to borrow a value which is not stored in memory.
+    pub(crate) fn build_temporary(
+        &mut self,
+        value: BasicValueEnum<'ctx>,
+    ) -> codegen::Result<PointerValue<'ctx>> {
+        let ptr = self.emit(|b| b.build_alloca(value.get_type(), "tmp"))?;
+        self.emit(|b| b.build_store(ptr, value))?;
+        Ok(ptr)
+    }
+
+    /// Returns the borrow expression whose reference would be the value of `expr`, if it refers
+    /// to a local variable or a temporary of the current function (which are stored on its stack,
+    /// so they don't outlive it), looking through the grouped expressions and the values
+    /// of the blocks. The `locals` are the variables declared in the enclosing blocks of `expr`,
+    /// which are not in the symbol table yet.
+    pub(crate) fn find_local_borrow<'a>(
+        &mut self,
+        expr: &'a dyn ExprASTNode,
+        locals: &mut Vec<Rc<str>>,
+    ) -> Option<&'a BorrowExprASTNode> {
+        let node = expr.as_any();
+        if let Some(borrow) = node.downcast_ref::<BorrowExprASTNode>() {
+            return self.is_local_place(borrow.expr(), locals).then_some(borrow);
+        }
+        if let Some(grouped) = node.downcast_ref::<GroupedExprASTNode>() {
+            return self.find_local_borrow(grouped.expr(), locals);
+        }
+        if let Some(block) = node.downcast_ref::<BlockASTNode>() {
+            return self.find_local_borrow_in_block(block, locals);
+        }
+        if let Some(unsafe_block) = node.downcast_ref::<UnsafeBlockASTNode>() {
+            return self.find_local_borrow_in_block(unsafe_block.block(), locals);
+        }
+        if let Some(if_node) = node.downcast_ref::<IfASTNode>() {
+            return self
+                .find_local_borrow_in_block(if_node.then_block(), locals)
+                .or_else(|| match if_node.else_node().map(ElseASTNode::branch) {
+                    None => None,
+                    Some(ElseExpr::Else(block)) => self.find_local_borrow_in_block(block, locals),
+                    Some(ElseExpr::ElseIf(else_if)) => {
+                        self.find_local_borrow(else_if.as_ref(), locals)
+                    }
+                });
+        }
+        None
+    }
+
+    /// Returns the borrow expression of a local value whose reference would be the value
+    /// of the given block (see [`find_local_borrow`](CodeGenState::find_local_borrow)).
+    fn find_local_borrow_in_block<'a>(
+        &mut self,
+        block: &'a BlockASTNode,
+        locals: &mut Vec<Rc<str>>,
+    ) -> Option<&'a BorrowExprASTNode> {
+        let expr = block.return_expr()?;
+        let len = locals.len();
+        let names = block
+            .statements()
+            .iter()
+            .filter_map(|statement| statement.as_any().downcast_ref::<LetASTNode>())
+            .filter_map(|stmt| stmt.decl().try_as_assignee()?.pattern());
+        locals.extend(names);
+        let borrow = self.find_local_borrow(expr, locals);
+        locals.truncate(len);
+        borrow
+    }
+
+    /// Returns whether the given borrowed expression is stored on the stack of the current
+    /// function, i.e. it is not a static or a dereferenced reference.
+    fn is_local_place(&mut self, expr: &dyn ExprASTNode, locals: &[Rc<str>]) -> bool {
+        let node = expr.as_any();
+        if let Some(path) = node.downcast_ref::<PathASTNode>() {
+            let name = path.path();
+            return locals.iter().any(|local| **local == *name)
+                || self.symbol_table().is_local(name);
+        }
+        if let Some(grouped) = node.downcast_ref::<GroupedExprASTNode>() {
+            return self.is_local_place(grouped.expr(), locals);
+        }
+        if let Some(tuple_index) = node.downcast_ref::<TupleIndexASTNode>() {
+            return self.is_local_place(tuple_index.tuple(), locals);
+        }
+        // Any other value than a dereference is borrowed from a temporary
+        !node.is::<DerefExprASTNode>()
+    }
+}
@@ -0,0 +1,168 @@
+//! The LLVM IR generation for the [`CompExprASTNode`].
+
+use inkwell::builder::Builder;
+use inkwell::values::{AnyValue, AnyValueEnum};
+use inkwell::{FloatPredicate, IntPredicate};
+
+use crate::ast::error::SemanticWarning;
+use crate::ast::{ASTNode, CompExprASTNode, CompOperator, ConstValue, Precedence, Type};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+use crate::pretty::SourcePrinter;
+
+impl CompExprASTNode {
+    /// Returns the [`SemanticWarning::RedundantBoolComparison`] for this expression,
+    /// if it is an (in)equality of a non-constant boolean expression and a boolean literal.
+    ///
+    /// The operands are expected to be of the boolean type.
+    fn redundant_bool_comparison(&self) -> Option<SemanticWarning> {
+        let negate = match self.operator() {
+            CompOperator::Eq => false,
+            CompOperator::Ne => true,
+            _ => return None,
+        };
+        let (operand, literal) = match (self.lhs().const_eval(), self.rhs().const_eval()) {
+            (None, Some(ConstValue::Bool(b))) => (self.lhs(), b),
+            (Some(ConstValue::Bool(b)), None) => (self.rhs(), b),
+            _ => return None,
+        };
+
+        let mut printer = SourcePrinter::new();
+        if negate == literal {
+            printer.write("!");
+            printer.print_operand(operand, operand.precedence() < Precedence::Unary);
+        } else {
+            printer.print_operand(operand, false);
+        }
+        Some(SemanticWarning::RedundantBoolComparison {
+            suggestion: printer.finish().into_boxed_str(),
+            span: self.span(),
+        })
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for CompExprASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        //TODO Refactor to use traits
+        // The operands are evaluated from left to right (see the `ast::expr` module),
+        // and they are compared, so the expected type (a boolean) doesn't apply to them
+        let lhs = state
+            .without_expected_type(|state| CodeGen::<AnyValueEnum>::code_gen(self.lhs(), state))?;
+        let lhs_ty = state.type_of(self.lhs(), lhs, None)?;
+        // An unsuffixed literal on the right adopts the type of the left operand (e.g. `x < 1`)
+        let rhs = state.build_expecting(self.rhs(), lhs_ty.clone())?;
+
+        //TODO Split into HIR & MIR and run type checking on HIR->MIR
+        //#region Type checking
+        let expr_ty = {
+            let rhs_ty = state.type_of(self.rhs(), rhs, Some(lhs_ty.clone()))?;
+
+            if lhs_ty != rhs_ty {
+                return Err(CodeGenError::TypeMismatch {
+                    expected: lhs_ty,
+                    actual: rhs_ty,
+                    span: self.rhs().span(),
+                });
+            }
+            // The strings would be compared by their addresses
+            if lhs_ty == Type::Str {
+                return Err(CodeGenError::UnsupportedType {
+                    message: "Cannot compare strings".into(),
+                    span: self.span(),
+                });
+            }
+            //TODO Compare the tuples element-wise (lexicographically)
+            if let Type::Tuple(_) = &lhs_ty {
+                return Err(CodeGenError::UnsupportedType {
+                    message: "Cannot compare tuples".into(),
+                    span: self.span(),
+                });
+            }
+            if let Type::Ref { .. } = &lhs_ty {
+                return Err(CodeGenError::UnsupportedType {
+                    message: "Cannot compare references (dereference them first)".into(),
+                    span: self.span(),
+                });
+            }
+
+            lhs_ty
+        };
+        if expr_ty == Type::Bool {
+            if let Some(warning) = self.redundant_bool_comparison() {
+                state.push_warning(warning);
+            }
+        }
+        //#endregion
+
+        //#region Type-specific code generation
+        let codegen_int = |builder: &mut Builder<'ctx>| {
+            let lhs = lhs.into_int_value();
+            let rhs = rhs.into_int_value();
+            let pred = match (self.operator(), expr_ty.is_signed()) {
+                (CompOperator::Eq, _) => IntPredicate::EQ,
+                (CompOperator::Ne, _) => IntPredicate::NE,
+                (CompOperator::Gt, true) => IntPredicate::SGT,
+                (CompOperator::Lt, true) => IntPredicate::SLT,
+                (CompOperator::Ge, true) => IntPredicate::SGE,
+                (CompOperator::Le, true) => IntPredicate::SLE,
+                (CompOperator::Gt, false) => IntPredicate::UGT,
+                (CompOperator::Lt, false) => IntPredicate::ULT,
+                (CompOperator::Ge, false) => IntPredicate::UGE,
+                (CompOperator::Le, false) => IntPredicate::ULE,
+            };
+
+            builder.build_int_compare(pred, lhs, rhs, "cmp")
+        };
+        let codegen_float = |builder: &mut Builder<'ctx>| {
+            let lhs = lhs.into_float_value();
+            let rhs = rhs.into_float_value();
+            // The ordered predicates are false if any operand is NaN, the unordered ones are true
+            let pred = match self.operator() {
+                CompOperator::Eq => FloatPredicate::OEQ,
+                CompOperator::Ne => FloatPredicate::UNE,
+                CompOperator::Gt => FloatPredicate::OGT,
+                CompOperator::Lt => FloatPredicate::OLT,
+                CompOperator::Ge => FloatPredicate::OGE,
+                CompOperator::Le => FloatPredicate::OLE,
+            };
+
+            builder.build_float_compare(pred, lhs, rhs, "cmp")
+        };
+        let codegen_bool = |builder: &mut Builder<'ctx>| {
+            let lhs = lhs.into_int_value();
+            let rhs = rhs.into_int_value();
+            let pred = match self.operator() {
+                CompOperator::Eq => IntPredicate::EQ,
+                CompOperator::Ne => IntPredicate::NE,
+                CompOperator::Gt => IntPredicate::UGT,
+                CompOperator::Lt => IntPredicate::ULT,
+                CompOperator::Ge => IntPredicate::UGE,
+                CompOperator::Le => IntPredicate::ULE,
+            };
+
+            builder.build_int_compare(pred, lhs, rhs, "cmp")
+        };
+        //#endregion
+
+        match expr_ty {
+            Type::Bool => codegen_bool(state.builder()),
+            Type::Unit => {
+                // The unit values are always equal (the operands are still evaluated above)
+                let result = matches!(
+                    self.operator(),
+                    CompOperator::Eq | CompOperator::Ge | CompOperator::Le
+                );
+                let value = state.context().bool_type().const_int(result as u64, false);
+                Ok(value)
+            }
+            ty if ty.is_float() => codegen_float(state.builder()),
+            _ => codegen_int(state.builder()),
+        }
+        .map(|v| v.as_any_value_enum())
+        .map_err(Into::<CodeGenError>::into)
+    }
+}
+
+impl ExprCodeGen for CompExprASTNode {}
@@ -0,0 +1,74 @@
+//! The LLVM IR generation for the [`DerefExprASTNode`].
+
+use inkwell::values::{AnyValue, AnyValueEnum, PointerValue};
+
+use crate::ast::error::SemanticError;
+use crate::ast::{ASTNode, DerefExprASTNode, Type};
+use crate::codegen;
+use crate::codegen::lower::{ExprCodeGen, PlaceCodeGen};
+use crate::codegen::{CodeGen, CodeGenState};
+
+/// The referenced value is read from the memory, with the [sanitizer](crate::codegen::sanitizer)
+/// checks if they are enabled.
+impl DerefExprASTNode {
+    /// Generates the dereferenced reference, whose referenced value is expected to be
+    /// of the `expected` type, and returns it together with the referenced type.
+    /// The reference has to be mutable if the referenced value is going to be mutated.
+    fn build_reference<'ctx>(
+        &self,
+        state: &mut CodeGenState<'ctx>,
+        expected: Option<Type>,
+        mutable: bool,
+    ) -> codegen::Result<(PointerValue<'ctx>, Type)> {
+        let expected = expected.map(|ty| Type::reference(ty, mutable));
+        let reference = match &expected {
+            Some(ty) => state.build_expecting(self.expr(), ty.clone())?,
+            None => state.without_expected_type(|state| {
+                CodeGen::<AnyValueEnum>::code_gen(self.expr(), state)
+            })?,
+        };
+        let ty = state.type_of(self.expr(), reference, expected)?;
+        match ty {
+            Type::Ref {
+                target,
+                mutable: true,
+            } => Ok((reference.into_pointer_value(), *target)),
+            Type::Ref { target, .. } if !mutable => Ok((reference.into_pointer_value(), *target)),
+            Type::Ref { .. } => Err(SemanticError::MutationBehindSharedReference {
+                ty,
+                span: self.span(),
+            }
+            .into()),
+            ty => Err(SemanticError::NotDereferenceable {
+                ty,
+                span: self.span(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for DerefExprASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        // The type of the referenced value determines the type of the reference, e.g. in `*&1`
+        let expected = state.expected_type();
+        let (ptr, ty) = self.build_reference(state, expected, false)?;
+        let llvm_type = ty.llvm_type(state);
+        let value = state.build_checked_load(llvm_type, ptr, self.span())?;
+        Ok(value.as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for DerefExprASTNode {}
+
+impl PlaceCodeGen for DerefExprASTNode {
+    /// The location is the value of the reference, which has to be mutable
+    /// if the location is going to be mutated.
+    fn code_gen_place<'ctx>(
+        &self,
+        state: &mut CodeGenState<'ctx>,
+        mutable: bool,
+    ) -> codegen::Result<(PointerValue<'ctx>, Type)> {
+        self.build_reference(state, None, mutable)
+    }
+}
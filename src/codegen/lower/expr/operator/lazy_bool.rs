@@ -0,0 +1,74 @@
+//! The LLVM IR generation for the [`LazyBoolExprASTNode`].
+
+use inkwell::values::{AnyValue, AnyValueEnum};
+
+use crate::ast::{LazyBoolExprASTNode, LazyBoolOperator};
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for LazyBoolExprASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let no_bb_panic = || panic!("Builder not in a block");
+
+        let parent_fn = state
+            .get_current_function()
+            .unwrap_or_else(|| panic!("Expression outside of function"));
+
+        //#region Labels
+        let lhs_bb = state.context().append_basic_block(parent_fn, "lhs");
+        let rhs_bb = state.context().append_basic_block(parent_fn, "rhs");
+        let merge_bb = state.context().append_basic_block(parent_fn, "merge");
+        state.emit(|builder| builder.build_unconditional_branch(lhs_bb))?;
+        //#endregion
+
+        //#region LHS
+        state.builder().position_at_end(lhs_bb);
+
+        let lhs = state.build_condition(
+            self.lhs(),
+            match self.operator() {
+                LazyBoolOperator::And => true,
+                LazyBoolOperator::Or => false,
+            },
+        )?;
+        state.emit(|builder| builder.build_conditional_branch(lhs, rhs_bb, merge_bb))?;
+
+        // The block may have changed because LHS could have added its own blocks
+        let lhs_bb = state
+            .builder()
+            .get_insert_block()
+            .unwrap_or_else(no_bb_panic);
+        //#endregion
+
+        //#region RHS
+        state.builder().position_at_end(rhs_bb);
+
+        let rhs = state.build_bool(self.rhs())?;
+        state.emit(|builder| builder.build_unconditional_branch(merge_bb))?;
+
+        // The block may have changed because RHS could have added its own blocks
+        let rhs_bb = state
+            .builder()
+            .get_insert_block()
+            .unwrap_or_else(no_bb_panic);
+        //#endregion
+
+        //#region Merge
+        state.builder().position_at_end(merge_bb);
+
+        let ty = state.context().bool_type();
+        // Skipping the RHS means that LHS alone decides the result (`false` for `&&`, `true` for `||`)
+        let short_circuit = ty.const_int(
+            matches!(self.operator(), LazyBoolOperator::Or) as u64,
+            false,
+        );
+        let phi = state.emit(|builder| builder.build_phi(ty, "lazybool"))?;
+        phi.add_incoming(&[(&short_circuit, lhs_bb), (&rhs, rhs_bb)]);
+        //#endregion
+
+        Ok(phi.as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for LazyBoolExprASTNode {}
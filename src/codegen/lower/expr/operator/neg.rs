@@ -0,0 +1,70 @@
+//! The LLVM IR generation for the [`NegExprASTNode`].
+
+use inkwell::values::{AnyValue, AnyValueEnum};
+
+use crate::ast::{ASTNode, NegExprASTNode, NegOperator, Type};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for NegExprASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        //TODO Refactor to use traits
+        let expected = state.expected_type();
+        // The range of a negated literal includes the minimum of its type (e.g. `-128i8`)
+        if let Some(literal) = self.negated_int_literal() {
+            let ty = literal.infer_type(expected.clone());
+            if ty.is_signed() {
+                let value = literal.check_range(&ty, true)?;
+                let int_type = ty.llvm_type(state).into_int_type();
+                return Ok(AnyValueEnum::IntValue(
+                    int_type.const_int(value as u64, true),
+                ));
+            }
+        }
+        let expr = CodeGen::<AnyValueEnum>::code_gen(self.expr(), state)?;
+
+        //TODO Split into HIR & MIR and run type checking on HIR->MIR
+        let expr_ty = state.type_of(self.expr(), expr, expected)?;
+
+        let builder = state.builder();
+        match self.operator() {
+            NegOperator::Neg => match expr_ty {
+                ty if ty.is_signed() => builder
+                    .build_int_neg(expr.into_int_value(), "neg")
+                    .map(|v| v.as_any_value_enum()),
+                ty if ty.is_float() => builder
+                    .build_float_neg(expr.into_float_value(), "neg")
+                    .map(|v| v.as_any_value_enum()),
+                ty if ty.is_unsigned() => {
+                    return Err(CodeGenError::UnsupportedType {
+                        message: format!("Cannot perform numerical negation on `{}`", ty)
+                            .into_boxed_str(),
+                        span: self.span(),
+                    });
+                }
+                _ => {
+                    return Err(CodeGenError::UnsupportedType {
+                        message: "Cannot perform numerical negation on non-numeric type".into(),
+                        span: self.span(),
+                    });
+                }
+            },
+            NegOperator::Not => match expr_ty {
+                Type::Bool => builder
+                    .build_not(expr.into_int_value(), "not")
+                    .map(|v| v.as_any_value_enum()),
+                _ => {
+                    return Err(CodeGenError::UnsupportedType {
+                        message: "Cannot perform logical negation on non-boolean type".into(),
+                        span: self.span(),
+                    });
+                }
+            },
+        }
+        .map_err(Into::<CodeGenError>::into)
+    }
+}
+
+impl ExprCodeGen for NegExprASTNode {}
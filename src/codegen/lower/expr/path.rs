@@ -0,0 +1,81 @@
+//! The LLVM IR generation for the [`PathASTNode`].
+
+use inkwell::values::{AnyValue, AnyValueEnum, BasicValueEnum, PointerValue};
+
+use crate::ast::{ASTNode, PathASTNode, Type};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::{ExprCodeGen, PlaceCodeGen};
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for PathASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let symbol =
+            state
+                .symbol_table()
+                .get(self.path())
+                .ok_or_else(|| CodeGenError::MissingSymbol {
+                    symbol: self.path().into(),
+                    span: self.span(),
+                })?;
+        let (value, stored_type) = (symbol.value(), symbol.stored_type());
+
+        // The variables (i.e. the local variables and the statics) are read from their memory,
+        // while the parameters and the functions are bound directly to their values
+        match (value, stored_type) {
+            (AnyValueEnum::PointerValue(ptr), Some(ty)) => state
+                .build_checked_load(ty, ptr, self.span())
+                .map(|value| value.as_any_value_enum()),
+            (_, Some(_)) => Err(CodeGenError::InvalidLLVMValueType {
+                message: "Expected a pointer value".into(),
+                span: self.span(),
+            }),
+            (value, None) => Ok(value),
+        }
+    }
+}
+
+impl ExprCodeGen for PathASTNode {}
+
+impl PlaceCodeGen for PathASTNode {
+    /// The variables are already stored in memory, while the (immutable) parameters are copied
+    /// into a temporary. The mutability of the local variables is checked by the [`InitChecker`](crate::init::InitChecker).
+    fn code_gen_place<'ctx>(
+        &self,
+        state: &mut CodeGenState<'ctx>,
+        _mutable: bool,
+    ) -> codegen::Result<(PointerValue<'ctx>, Type)> {
+        let symbol =
+            state
+                .symbol_table()
+                .get(self.path())
+                .ok_or_else(|| CodeGenError::MissingSymbol {
+                    symbol: self.path().into(),
+                    span: self.span(),
+                })?;
+        let (value, stored_type) = (symbol.value(), symbol.stored_type());
+        let Some(ty) = symbol.ty().cloned() else {
+            return Err(CodeGenError::UnsupportedType {
+                message: format!("Cannot take a reference to `{}`", self.path()).into_boxed_str(),
+                span: self.span(),
+            });
+        };
+
+        match (value, stored_type) {
+            (AnyValueEnum::PointerValue(ptr), Some(_)) => Ok((ptr, ty)),
+            (_, Some(_)) => Err(CodeGenError::InvalidLLVMValueType {
+                message: "Expected a pointer value".into(),
+                span: self.span(),
+            }),
+            (value, None) => {
+                let value = BasicValueEnum::try_from(value).map_err(|_| {
+                    CodeGenError::InvalidLLVMValueType {
+                        message: "A parameter must be a basic value".into(),
+                        span: self.span(),
+                    }
+                })?;
+                Ok((state.build_temporary(value)?, ty))
+            }
+        }
+    }
+}
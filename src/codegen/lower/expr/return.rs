@@ -0,0 +1,49 @@
+//! The LLVM IR generation for the [`ReturnASTNode`].
+
+use inkwell::values::{AnyValue, AnyValueEnum, BasicValue, BasicValueEnum};
+
+use crate::ast::error::SemanticError;
+use crate::ast::{ASTNode, ReturnASTNode};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for ReturnASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        if let Some(borrow) = self
+            .value()
+            .and_then(|v| state.find_local_borrow(v, &mut Vec::new()))
+        {
+            return Err(SemanticError::ReturnedLocalReference {
+                span: borrow.span(),
+            }
+            .into());
+        }
+
+        let value = self
+            .value()
+            .map(|v| {
+                let v = CodeGen::<AnyValueEnum>::code_gen(v, state)?;
+                BasicValueEnum::try_from(v).map_err(|_| CodeGenError::InvalidLLVMValueType {
+                    message: "Return value must be a basic value".into(),
+                    span: self.span(),
+                })
+            })
+            .transpose()?;
+
+        // A unit value is returned as `void` from the functions returning unit
+        let returns_void = state
+            .get_current_function()
+            .is_some_and(|f| f.get_type().get_return_type().is_none());
+        let value = value.filter(|v| !(returns_void && state.is_unit_value(v.as_any_value_enum())));
+
+        //TODO Type checking (probably on HIR->MIR conversion)
+        let value = value.as_ref().map(|v| v as &dyn BasicValue);
+        state
+            .emit(|builder| builder.build_return(value))
+            .map(|v| v.as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for ReturnASTNode {}
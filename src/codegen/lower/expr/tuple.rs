@@ -0,0 +1,52 @@
+//! The LLVM IR generation for the [`TupleASTNode`].
+
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::{AggregateValue, AnyValue, AnyValueEnum, BasicValueEnum};
+
+use crate::ast::{TupleASTNode, Type};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+/// The elements are evaluated from left to right and inserted into an LLVM struct.
+/// If a tuple type of the same length is expected, each element expects the type at its position.
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for TupleASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        let expected = state.expected_type();
+        let expected = expected
+            .as_ref()
+            .and_then(Type::tuple_elements)
+            .filter(|types| types.len() == self.elements().len());
+
+        let mut values = Vec::with_capacity(self.elements().len());
+        for (i, element) in self.elements().iter().enumerate() {
+            let value = match expected {
+                Some(types) => state.build_expecting(element.as_ref(), types[i].clone())?,
+                None => state.without_expected_type(|state| {
+                    CodeGen::<AnyValueEnum>::code_gen(element.as_ref(), state)
+                })?,
+            };
+            let value = BasicValueEnum::try_from(value).map_err(|_| {
+                CodeGenError::InvalidLLVMValueType {
+                    message: "The elements of a tuple must be basic values".into(),
+                    span: element.span(),
+                }
+            })?;
+            values.push(value);
+        }
+
+        let field_types: Vec<BasicTypeEnum> = values.iter().map(|value| value.get_type()).collect();
+        let mut tuple = state
+            .context()
+            .struct_type(&field_types, false)
+            .get_undef()
+            .as_aggregate_value_enum();
+        for (i, value) in values.into_iter().enumerate() {
+            tuple = state.emit(|b| b.build_insert_value(tuple, value, i as u32, "tuple"))?;
+        }
+        Ok(tuple.as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for TupleASTNode {}
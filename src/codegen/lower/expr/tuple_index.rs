@@ -0,0 +1,36 @@
+//! The LLVM IR generation for the [`TupleIndexASTNode`].
+
+use inkwell::values::{AnyValue, AnyValueEnum};
+
+use crate::ast::error::SemanticError;
+use crate::ast::{ASTNode, TupleIndexASTNode};
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for TupleIndexASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        // The type of the element doesn't determine the type of the tuple
+        let tuple = state.without_expected_type(|state| {
+            CodeGen::<AnyValueEnum>::code_gen(self.tuple(), state)
+        })?;
+        let ty = state.type_of(self.tuple(), tuple, None)?;
+        let in_bounds = ty
+            .tuple_elements()
+            .is_some_and(|elements| (self.index() as usize) < elements.len());
+        if !in_bounds {
+            return Err(SemanticError::NoTupleField {
+                ty,
+                index: self.index(),
+                span: self.span(),
+            }
+            .into());
+        }
+
+        let tuple = tuple.into_struct_value();
+        let element = state.emit(|b| b.build_extract_value(tuple, self.index(), "element"))?;
+        Ok(element.as_any_value_enum())
+    }
+}
+
+impl ExprCodeGen for TupleIndexASTNode {}
@@ -0,0 +1,18 @@
+//! The LLVM IR generation for the [`UnderscoreASTNode`].
+
+use inkwell::values::AnyValueEnum;
+
+use crate::ast::{ASTNode, UnderscoreASTNode};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for UnderscoreASTNode {
+    fn code_gen(&self, _: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        // Underscore cannot be used as a value; it should only be used as a pattern.
+        Err(CodeGenError::UnderscoreUsedAsValue { span: self.span() })
+    }
+}
+
+impl ExprCodeGen for UnderscoreASTNode {}
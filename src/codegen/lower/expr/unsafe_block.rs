@@ -0,0 +1,20 @@
+//! The LLVM IR generation for the [`UnsafeBlockASTNode`].
+
+use inkwell::values::AnyValueEnum;
+
+use crate::ast::UnsafeBlockASTNode;
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, AnyValueEnum<'ctx>> for UnsafeBlockASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyValueEnum<'ctx>> {
+        CodeGen::<AnyValueEnum>::code_gen(self.block(), state)
+    }
+}
+
+impl ExprCodeGen for UnsafeBlockASTNode {
+    fn code_gen_stmt<'ctx>(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        self.block().code_gen_stmt(state)
+    }
+}
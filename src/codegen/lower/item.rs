@@ -0,0 +1,21 @@
+//! The LLVM IR generation for the [`ItemASTNode`]s.
+
+use crate::ast::ItemASTNode;
+use crate::codegen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+mod r#extern;
+mod func;
+mod import;
+mod proto;
+mod r#static;
+
+impl<'ctx> CodeGen<'ctx, ()> for ItemASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        match self {
+            ItemASTNode::Func(func) => func.code_gen(state),
+            ItemASTNode::Extern(ext) => ext.code_gen(state),
+            ItemASTNode::Static(stat) => stat.code_gen(state),
+        }
+    }
+}
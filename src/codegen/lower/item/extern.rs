@@ -0,0 +1,12 @@
+//! The LLVM IR generation for the [`ExternASTNode`].
+
+use crate::ast::ExternASTNode;
+use crate::codegen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, ()> for ExternASTNode {
+    fn code_gen(&self, _state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        // The items are only declarations, which are added in the Collection Phase
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! The LLVM IR generation for the [`FuncASTNode`].
+
+use inkwell::values::{AnyValue, AnyValueEnum};
+
+use crate::ast::error::SemanticWarning;
+use crate::ast::{ASTNode, Attribute, AttributeArg, FuncASTNode};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::{CodeGen, CodeGenState};
+use crate::trace;
+
+impl<'ctx> CodeGen<'ctx, ()> for FuncASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        trace::span!(DEBUG, "function", name = %self.proto().name());
+        let fn_value = state.symbol_table().get(self.proto().name()).map_or_else(
+            || {
+                // The symbol is added during the collection phase
+                Err(CodeGenError::MissingSymbol {
+                    symbol: self.proto().name().into(),
+                    span: self.proto().name_span(),
+                })
+            },
+            |s| match s.value() {
+                AnyValueEnum::FunctionValue(v) => Ok(v),
+                _ => Err(CodeGenError::InvalidLLVMValueType {
+                    message: format!("`{}` is not a function", self.proto().name())
+                        .into_boxed_str(),
+                    span: self.proto().name_span(),
+                }),
+            },
+        )?;
+
+        state.clear_reference_types();
+        state.with_scope(|state| {
+            let body = state.context().append_basic_block(fn_value, "start");
+            state.builder().position_at_end(body);
+
+            //#region Parameters
+            let param_iter = fn_value.get_param_iter().zip(self.proto().get_param_iter());
+            for (llvm_param, param) in param_iter {
+                let ty = param.ty().ty();
+                let is_mutable = param.is_mutable();
+                let param = param.assignee()?;
+
+                let Some(name) = param.pattern() else {
+                    continue;
+                };
+                llvm_param.set_name(&name);
+                // The mutable parameters are stored like the local variables,
+                // so that they can be assigned to and borrowed mutably
+                if is_mutable {
+                    let stored_type = llvm_param.get_type();
+                    let ptr = state.emit(|builder| builder.build_alloca(stored_type, &name))?;
+                    state.emit(|builder| builder.build_store(ptr, llvm_param))?;
+                    state
+                        .symbol_table()
+                        .insert_variable(name, ptr, stored_type, ty);
+                } else {
+                    state
+                        .symbol_table()
+                        .insert_typed(name, llvm_param.as_any_value_enum(), ty);
+                }
+            }
+            //#endregion
+
+            //#region Body
+            let unroll = self
+                .attribute(Attribute::UNROLL)
+                .filter(|attr| match attr.args() {
+                    [AttributeArg::Int(count)] if u32::try_from(*count).is_ok_and(|c| c > 0) => {
+                        state.begin_unroll_hint(*count as u32);
+                        true
+                    }
+                    _ => {
+                        state.push_warning(SemanticWarning::IgnoredUnrollHint {
+                            reason: "expected a positive loop count",
+                            span: attr.span(),
+                        });
+                        false
+                    }
+                });
+
+            state.begin_debug_function(fn_value, !self.is_exported(), self.span().start());
+            let result = self.body().code_gen(state);
+            let loops = state.end_unroll_hint();
+            result?;
+
+            if let (Some(attr), 0) = (unroll, loops) {
+                state.push_warning(SemanticWarning::IgnoredUnrollHint {
+                    reason: "the function contains no loops",
+                    span: attr.span(),
+                });
+            }
+
+            // A function returning unit may omit the final `return`, so it is returned implicitly
+            let returns_void = fn_value.get_type().get_return_type().is_none();
+            if returns_void && !state.is_terminated() {
+                state.emit(|builder| builder.build_return(None))?;
+            }
+            state.end_debug_function();
+            //#endregion
+
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,12 @@
+//! The LLVM IR generation for the [`ImportASTNode`].
+
+use crate::ast::ImportASTNode;
+use crate::codegen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, ()> for ImportASTNode {
+    fn code_gen(&self, _state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        // The imported functions are declared in the Collection Phase
+        Ok(())
+    }
+}
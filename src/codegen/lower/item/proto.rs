@@ -0,0 +1,43 @@
+//! The LLVM IR generation for the [`FuncProtoASTNode`].
+
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType};
+
+use crate::ast::{ASTNode, FuncProtoASTNode, Type};
+use crate::codegen;
+use crate::codegen::registry::FunctionSignature;
+use crate::codegen::{CodeGen, CodeGenState};
+
+mod param;
+
+impl FuncProtoASTNode {
+    /// Returns the μRust-level signature of the function.
+    pub fn signature(&self) -> FunctionSignature {
+        FunctionSignature::new(
+            self.name_owned(),
+            self.param_types().collect(),
+            self.return_type().ty(),
+            self.span(),
+        )
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, ()> for FuncProtoASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        CodeGen::<FunctionType>::code_gen(self, state).map(|_| ())
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, FunctionType<'ctx>> for FuncProtoASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<FunctionType<'ctx>> {
+        let params = self
+            .get_param_iter()
+            .map(|p| CodeGen::<BasicMetadataTypeEnum>::code_gen(p, state))
+            .collect::<codegen::Result<Vec<_>>>()?;
+
+        Ok(match self.return_type().ty() {
+            Type::Unit => state.context().void_type().fn_type(&params, false),
+            _ => CodeGen::<BasicTypeEnum>::code_gen(self.return_type(), state)?
+                .fn_type(&params, false),
+        })
+    }
+}
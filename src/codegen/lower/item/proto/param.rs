@@ -0,0 +1,22 @@
+//! The LLVM IR generation for the [`ParamASTNode`].
+
+use inkwell::types::BasicMetadataTypeEnum;
+
+use crate::ast::ParamASTNode;
+use crate::codegen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, ()> for ParamASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        CodeGen::<BasicMetadataTypeEnum>::code_gen(self, state).map(|_| ())
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, BasicMetadataTypeEnum<'ctx>> for ParamASTNode {
+    fn code_gen(
+        &self,
+        state: &mut CodeGenState<'ctx>,
+    ) -> codegen::Result<BasicMetadataTypeEnum<'ctx>> {
+        CodeGen::<BasicMetadataTypeEnum>::code_gen(self.ty(), state)
+    }
+}
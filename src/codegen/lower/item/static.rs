@@ -0,0 +1,62 @@
+//! The LLVM IR generation for the [`StaticASTNode`].
+
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::BasicValueEnum;
+
+use crate::ast::error::SemanticError;
+use crate::ast::{ConstValue, StaticASTNode};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, ()> for StaticASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        // Here we only generate the initializer, the declaration is done during Collection Phase.
+        let global =
+            state
+                .module()
+                .get_global(self.name())
+                .ok_or_else(|| CodeGenError::MissingSymbol {
+                    symbol: self.name().into(),
+                    span: self.name_span(),
+                })?;
+        let ty = CodeGen::<BasicTypeEnum>::code_gen(self.ty(), state)?;
+
+        // A static without an initializer (which the parser doesn't allow) is zero-initialized,
+        // see the sanitizer for the checks of its reads
+        let Some(value) = self.value() else {
+            global.set_initializer(&ty.const_zero());
+            return Ok(());
+        };
+        //TODO Add support for more complicated initializers (evaluated at compile time)
+        let constant = value
+            .const_eval_in(state.static_values())
+            .ok_or(CodeGenError::NonConstantInitializer { span: value.span() })?;
+        if !constant.is_of_type(&self.ty().ty()) {
+            return Err(CodeGenError::TypeMismatch {
+                expected: self.ty().ty(),
+                actual: constant.ty(),
+                span: value.span(),
+            });
+        }
+        if let ConstValue::Int(i) = constant {
+            let ty = self.ty().ty();
+            if ty.int_range().is_some_and(|range| !range.contains(&i)) {
+                return Err(SemanticError::IntOutOfRange {
+                    value: i,
+                    ty,
+                    span: value.span(),
+                }
+                .into());
+            }
+        }
+        let initializer: BasicValueEnum = match constant {
+            ConstValue::Int(i) => ty.into_int_type().const_int(i as u64, true).into(),
+            ConstValue::Float(x) => ty.into_float_type().const_float(x).into(),
+            ConstValue::Bool(b) => ty.into_int_type().const_int(b as u64, false).into(),
+            ConstValue::Unit => ty.const_zero(),
+        };
+        global.set_initializer(&initializer);
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+//! The LLVM IR generation for the statement AST nodes.
+
+use crate::ast::{CoreStmt, StatementASTNode};
+use crate::codegen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+mod expr;
+mod r#let;
+
+impl<'ctx> CodeGen<'ctx, ()> for dyn StatementASTNode + '_ {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        match self.lower() {
+            CoreStmt::Let(stmt) => stmt.code_gen(state),
+            CoreStmt::Expr(stmt) => stmt.code_gen(state),
+        }
+    }
+}
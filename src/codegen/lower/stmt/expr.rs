@@ -0,0 +1,12 @@
+//! The LLVM IR generation for the [`ExprStmtASTNode`].
+
+use crate::ast::ExprStmtASTNode;
+use crate::codegen;
+use crate::codegen::lower::ExprCodeGen;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, ()> for ExprStmtASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        self.expr().code_gen_stmt(state)
+    }
+}
@@ -0,0 +1,79 @@
+//! The LLVM IR generation for the [`LetASTNode`].
+
+use inkwell::values::{AnyValueEnum, BasicValueEnum};
+
+use crate::ast::error::SemanticError;
+use crate::ast::{ASTNode, LetASTNode};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::{CodeGen, CodeGenState};
+
+impl<'ctx> CodeGen<'ctx, ()> for LetASTNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<()> {
+        // The value is generated before the variable is declared,
+        // so that it can refer to the variable it shadows
+        let value = self
+            .value()
+            .map(|v| match self.ty() {
+                Some(ty) => state.build_expecting(v, ty),
+                None => CodeGen::<AnyValueEnum>::code_gen(v, state),
+            })
+            .transpose()?;
+        // The value diverges (e.g. it is a `return`), so the variable is never declared
+        if state.is_terminated() {
+            return Ok(());
+        }
+
+        let pat = self
+            .decl()
+            .try_as_assignee()
+            .ok_or(SemanticError::WrongExpressionKind {
+                message: "Expected an assignee expression",
+                span: self.span(),
+            })?
+            .pattern();
+
+        //TODO Type checking (probably on HIR->MIR conversion)
+        let declared = match (self.ty(), value) {
+            (Some(expected), Some(value)) => {
+                let span = self.value().unwrap().span();
+                let actual = state.type_of(self.value().unwrap(), value, Some(expected.clone()))?;
+                if actual != expected {
+                    return Err(CodeGenError::TypeMismatch {
+                        expected,
+                        actual,
+                        span,
+                    });
+                }
+                expected
+            }
+            (Some(ty), None) => ty,
+            (None, Some(value)) => state.type_of(self.value().unwrap(), value, None)?,
+            (None, None) => unreachable!("A let with an inferred type has a value"),
+        };
+
+        if let Some(pat) = pat.as_ref() {
+            let ty = declared.llvm_type(state);
+            let ptr = state.emit(|builder| builder.build_alloca(ty, pat.as_ref()))?;
+
+            //TODO Variable mutability?
+            state
+                .symbol_table()
+                .insert_variable(pat.clone(), ptr, ty, declared);
+
+            if let Some(value) = value {
+                let value = BasicValueEnum::try_from(value).map_err(|_| {
+                    CodeGenError::InvalidLLVMValueType {
+                        message: "Return value must be a basic value".into(),
+                        span: self.value().unwrap().span(),
+                    }
+                })?;
+                state.builder().build_store(ptr, value)?;
+            }
+        }
+
+        //TODO Unused value warning
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+//! The LLVM IR generation for the [types](TypeASTMetaNode) and the conversions
+//! between the [`Type`]s and the LLVM types.
+
+use inkwell::context::Context;
+use inkwell::types::{AnyType, AnyTypeEnum, BasicMetadataTypeEnum, BasicType, BasicTypeEnum};
+use inkwell::values::AnyValueEnum;
+use inkwell::AddressSpace;
+
+use crate::ast::{Type, TypeASTMetaNode};
+use crate::codegen;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::{CodeGen, CodeGenState};
+use crate::token::Span;
+
+impl<'ctx> CodeGen<'ctx, AnyTypeEnum<'ctx>> for TypeASTMetaNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<AnyTypeEnum<'ctx>> {
+        CodeGen::<BasicTypeEnum>::code_gen(self, state).map(|bt| bt.as_any_type_enum())
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, BasicTypeEnum<'ctx>> for TypeASTMetaNode {
+    fn code_gen(&self, state: &mut CodeGenState<'ctx>) -> codegen::Result<BasicTypeEnum<'ctx>> {
+        Ok(self.ty().llvm_type(state))
+    }
+}
+
+impl Type {
+    /// Attempts to get the [`Type`] of an [`AnyValueEnum`].
+    ///
+    /// The integer values are always of the signed types, since LLVM doesn't distinguish
+    /// the unsigned ones, and the pointers are always string slices, since the pointers
+    /// don't carry the referenced type (see [`CodeGenState::type_of`] for the exact type
+    /// of an expression).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodeGenError::UnsupportedType`] if the type is not supported.
+    pub fn try_from_llvm_value<'ctx>(
+        context: &'ctx Context,
+        value: AnyValueEnum<'ctx>,
+        span: Span,
+    ) -> codegen::Result<Type> {
+        Type::try_from_llvm_type(context, value.get_type(), span)
+    }
+
+    /// Attempts to get the [`Type`] of the values of an [`AnyTypeEnum`]
+    /// (with the integer types signed, see [`try_from_llvm_value`](Type::try_from_llvm_value)).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodeGenError::UnsupportedType`] if the type is not supported.
+    pub fn try_from_llvm_type<'ctx>(
+        context: &'ctx Context,
+        ty: AnyTypeEnum<'ctx>,
+        span: Span,
+    ) -> codegen::Result<Type> {
+        match ty {
+            // Bool
+            AnyTypeEnum::IntType(i) if i.get_bit_width() == 1 => Ok(Type::Bool),
+            // Integers
+            AnyTypeEnum::IntType(i) if i.get_bit_width() == 8 => Ok(Type::I8),
+            AnyTypeEnum::IntType(i) if i.get_bit_width() == 16 => Ok(Type::I16),
+            AnyTypeEnum::IntType(i) if i.get_bit_width() == 32 => Ok(Type::I32),
+            AnyTypeEnum::IntType(i) if i.get_bit_width() == 64 => Ok(Type::I64),
+            // Floats
+            AnyTypeEnum::FloatType(f) if f == context.f32_type() => Ok(Type::F32),
+            AnyTypeEnum::FloatType(f) if f == context.f64_type() => Ok(Type::F64),
+            // Unit
+            AnyTypeEnum::StructType(s) if s.count_fields() == 0 => Ok(Type::Unit),
+            // Tuples
+            AnyTypeEnum::StructType(s) => {
+                let elements = s
+                    .get_field_types()
+                    .into_iter()
+                    .map(|field| Type::try_from_llvm_type(context, field.as_any_type_enum(), span))
+                    .collect::<codegen::Result<Vec<_>>>()?;
+                Ok(Type::tuple(elements))
+            }
+            // Strings (the pointee types of the references are not known from their LLVM types)
+            AnyTypeEnum::PointerType(_) => Ok(Type::Str),
+            // Unsupported ints
+            AnyTypeEnum::IntType(_) => Err(CodeGenError::UnsupportedType {
+                message: "Unsupported int type (only 8, 16, 32 and 64 bits are supported)".into(),
+                span,
+            }),
+            // Unsupported floats
+            AnyTypeEnum::FloatType(_) => Err(CodeGenError::UnsupportedType {
+                message: "Unsupported float type (only `f32` and `f64` are supported)".into(),
+                span,
+            }),
+            ty => Err(CodeGenError::UnsupportedType {
+                message: format!("Unsupported type: {}", ty).into_boxed_str(),
+                span,
+            }),
+        }
+    }
+
+    /// Returns the LLVM type representing the values of the type.
+    pub(crate) fn llvm_type<'ctx>(&self, state: &CodeGenState<'ctx>) -> BasicTypeEnum<'ctx> {
+        let context = state.context();
+        match self {
+            Type::I8 | Type::U8 => context.i8_type().as_basic_type_enum(),
+            Type::I16 | Type::U16 => context.i16_type().as_basic_type_enum(),
+            Type::I32 | Type::U32 => context.i32_type().as_basic_type_enum(),
+            Type::I64 | Type::U64 => context.i64_type().as_basic_type_enum(),
+            Type::F32 => context.f32_type().as_basic_type_enum(),
+            Type::F64 => context.f64_type().as_basic_type_enum(),
+            Type::Bool => context.bool_type().as_basic_type_enum(),
+            Type::Unit => state.unit_type().as_basic_type_enum(),
+            Type::Str => context
+                .i8_type()
+                .ptr_type(AddressSpace::default())
+                .as_basic_type_enum(),
+            Type::Tuple(elements) => {
+                let fields: Vec<_> = elements.iter().map(|ty| ty.llvm_type(state)).collect();
+                context.struct_type(&fields, false).as_basic_type_enum()
+            }
+            Type::Ref { target, .. } => target
+                .llvm_type(state)
+                .ptr_type(AddressSpace::default())
+                .as_basic_type_enum(),
+        }
+    }
+}
+
+impl<'ctx> CodeGen<'ctx, BasicMetadataTypeEnum<'ctx>> for TypeASTMetaNode {
+    fn code_gen(
+        &self,
+        state: &mut CodeGenState<'ctx>,
+    ) -> codegen::Result<BasicMetadataTypeEnum<'ctx>> {
+        CodeGen::<BasicTypeEnum>::code_gen(self, state).map(|bt| bt.into())
+    }
+}
@@ -0,0 +1,38 @@
+//! A module containing the builders of the LLVM metadata not exposed by the safe API of inkwell.
+
+use inkwell::context::{AsContextRef, Context};
+use inkwell::llvm_sys::core::{
+    LLVMMDNodeInContext2, LLVMMDStringInContext2, LLVMMetadataAsValue, LLVMValueAsMetadata,
+};
+use inkwell::llvm_sys::debuginfo::{LLVMMetadataReplaceAllUsesWith, LLVMTemporaryMDNode};
+use inkwell::values::{AsValueRef, MetadataValue};
+
+/// The kind of the metadata attached to the back edge of a loop.
+pub(crate) const LOOP_KIND: &str = "llvm.loop";
+
+/// The name of the loop property requesting the loop to be unrolled the given number of times.
+const UNROLL_COUNT: &str = "llvm.loop.unroll.count";
+
+/// Creates a loop ID node (i.e. `!0 = distinct !{!0, !1}`) with the property
+/// `!1 = !{!"llvm.loop.unroll.count", i32 count}`.
+///
+/// The first operand of a loop ID has to refer to the node itself, so every loop gets a distinct
+/// node and the loop passes know which loop the properties belong to.
+pub(crate) fn loop_unroll_count(context: &Context, count: u32) -> MetadataValue<'_> {
+    let count = context.i32_type().const_int(count as u64, false);
+    // SAFETY: All the references belong to the same context, which outlives the returned value.
+    // The temporary node is replaced with the loop ID (and so destroyed) before it is returned.
+    unsafe {
+        let ctx = context.as_ctx_ref();
+        let name = LLVMMDStringInContext2(ctx, UNROLL_COUNT.as_ptr().cast(), UNROLL_COUNT.len());
+        let mut property = [name, LLVMValueAsMetadata(count.as_value_ref())];
+        let property = LLVMMDNodeInContext2(ctx, property.as_mut_ptr(), property.len());
+
+        let placeholder = LLVMTemporaryMDNode(ctx, std::ptr::null_mut(), 0);
+        let mut operands = [placeholder, property];
+        let loop_id = LLVMMDNodeInContext2(ctx, operands.as_mut_ptr(), operands.len());
+        LLVMMetadataReplaceAllUsesWith(placeholder, loop_id);
+
+        MetadataValue::new(LLVMMetadataAsValue(ctx, loop_id))
+    }
+}
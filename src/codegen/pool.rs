@@ -0,0 +1,212 @@
+//! A module containing the pool of the constant data of a crate, e.g. the contents
+//! of the string and array literals.
+//!
+//! Every distinct constant is emitted only once, as a private global named [`POOL_PREFIX`]
+//! followed by its index in the pool, and the identical constants share it.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use inkwell::module::Linkage;
+use inkwell::values::GlobalValue;
+
+use crate::codegen::CodeGenState;
+
+/// The prefix of the names of the globals holding the pooled constants.
+pub const POOL_PREFIX: &str = "__mrs_const.";
+
+/// The data of a pooled constant (an array of its elements).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PoolConstant {
+    /// An array of bytes, e.g. the UTF-8 encoding of a string.
+    Bytes(Box<[u8]>),
+    /// An array of `i32` values.
+    Ints(Box<[i32]>),
+    /// An array of `f64` values, represented by their bits
+    /// (so that e.g. `0.0` and `-0.0` are different constants).
+    Floats(Box<[u64]>),
+}
+
+impl PoolConstant {
+    /// Creates the constant with the UTF-8 encoding of the given string.
+    pub fn string(s: &str) -> PoolConstant {
+        PoolConstant::Bytes(s.as_bytes().into())
+    }
+
+    /// Creates the constant with the null-terminated UTF-8 encoding of the given string,
+    /// i.e. a C string.
+    pub fn c_string(s: &str) -> PoolConstant {
+        let mut bytes = Vec::with_capacity(s.len() + 1);
+        bytes.extend_from_slice(s.as_bytes());
+        bytes.push(0);
+        PoolConstant::Bytes(bytes.into())
+    }
+
+    /// Creates the constant with the given `f64` values.
+    pub fn from_floats(values: &[f64]) -> PoolConstant {
+        PoolConstant::Floats(values.iter().map(|x| x.to_bits()).collect())
+    }
+
+    /// Returns the number of the elements of the constant.
+    pub fn len(&self) -> usize {
+        match self {
+            PoolConstant::Bytes(bytes) => bytes.len(),
+            PoolConstant::Ints(ints) => ints.len(),
+            PoolConstant::Floats(floats) => floats.len(),
+        }
+    }
+
+    /// Returns whether the constant has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Display for PoolConstant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolConstant::Bytes(bytes) => write!(f, "b\"{}\"", bytes.escape_ascii()),
+            PoolConstant::Ints(ints) => write!(f, "{:?}", ints),
+            PoolConstant::Floats(floats) => {
+                let floats: Vec<_> = floats.iter().map(|bits| f64::from_bits(*bits)).collect();
+                write!(f, "{:?}", floats)
+            }
+        }
+    }
+}
+
+/// The pool of the constants emitted into the module, in the order they were first used.
+#[derive(Debug, Default)]
+pub struct ConstantPool<'ctx> {
+    entries: Vec<(PoolConstant, GlobalValue<'ctx>)>,
+    indices: HashMap<PoolConstant, usize>,
+}
+
+impl<'ctx> ConstantPool<'ctx> {
+    /// Creates a new empty `ConstantPool`.
+    pub fn new() -> ConstantPool<'ctx> {
+        ConstantPool {
+            entries: Vec::new(),
+            indices: HashMap::new(),
+        }
+    }
+
+    /// Returns the global holding the given constant, if it is in the pool.
+    pub fn get(&self, constant: &PoolConstant) -> Option<GlobalValue<'ctx>> {
+        self.indices
+            .get(constant)
+            .map(|index| self.entries[*index].1)
+    }
+
+    /// Returns an iterator over the constants in the pool together with their globals.
+    pub fn entries(&self) -> impl Iterator<Item = (&PoolConstant, GlobalValue<'ctx>)> {
+        self.entries
+            .iter()
+            .map(|(constant, global)| (constant, *global))
+    }
+
+    /// Returns the number of the distinct constants in the pool.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<'ctx> CodeGenState<'ctx> {
+    /// Returns the global holding the given constant, emitting it into the module
+    /// if it hasn't been used before.
+    pub fn intern_constant(&mut self, constant: PoolConstant) -> GlobalValue<'ctx> {
+        if let Some(global) = self.constant_pool.get(&constant) {
+            return global;
+        }
+
+        let context = self.context();
+        let value = match &constant {
+            PoolConstant::Bytes(bytes) => context.const_string(bytes, false),
+            PoolConstant::Ints(ints) => {
+                let ty = context.i32_type();
+                let values: Vec<_> = ints.iter().map(|i| ty.const_int(*i as u64, true)).collect();
+                ty.const_array(&values)
+            }
+            PoolConstant::Floats(floats) => {
+                let ty = context.f64_type();
+                let values: Vec<_> = floats
+                    .iter()
+                    .map(|bits| ty.const_float(f64::from_bits(*bits)))
+                    .collect();
+                ty.const_array(&values)
+            }
+        };
+
+        let pool = &mut self.constant_pool;
+        let name = format!("{}{}", POOL_PREFIX, pool.len());
+        let global = self.module.add_global(value.get_type(), None, &name);
+        global.set_initializer(&value);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+        global.set_unnamed_addr(true);
+
+        pool.indices.insert(constant.clone(), pool.entries.len());
+        pool.entries.push((constant, global));
+        global
+    }
+
+    /// Returns the pool of the constants emitted so far.
+    pub fn constant_pool(&self) -> &ConstantPool<'ctx> {
+        &self.constant_pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::context::Context;
+
+    use super::*;
+
+    #[test]
+    fn test_constant_pool() {
+        let context = Context::create();
+        let mut state = CodeGenState::new(&context, "test");
+        assert!(state.constant_pool().is_empty());
+
+        let hello = state.intern_constant(PoolConstant::string("hello"));
+        let ints = state.intern_constant(PoolConstant::Ints([1, -2, 3].into()));
+        let zero = state.intern_constant(PoolConstant::from_floats(&[0.0]));
+        let neg_zero = state.intern_constant(PoolConstant::from_floats(&[-0.0]));
+        assert_eq!(state.intern_constant(PoolConstant::string("hello")), hello);
+        assert_eq!(
+            state.intern_constant(PoolConstant::Ints([1, -2, 3].into())),
+            ints
+        );
+        assert_ne!(zero, neg_zero);
+        assert_ne!(state.intern_constant(PoolConstant::string("hell")), hello);
+
+        let pool = state.constant_pool();
+        assert_eq!(pool.len(), 5);
+        assert_eq!(pool.get(&PoolConstant::string("hello")), Some(hello));
+        let entries: Vec<_> = pool
+            .entries()
+            .map(|(constant, global)| (constant.to_string(), global.get_name().to_owned()))
+            .collect();
+        assert_eq!(
+            entries[..3],
+            [
+                ("b\"hello\"".to_owned(), c"__mrs_const.0".to_owned()),
+                ("[1, -2, 3]".to_owned(), c"__mrs_const.1".to_owned()),
+                ("[0.0]".to_owned(), c"__mrs_const.2".to_owned()),
+            ]
+        );
+
+        let module = state.take_module();
+        module.verify().unwrap();
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("@__mrs_const.0 = private unnamed_addr constant [5 x i8] c\"hello\""));
+        assert!(ir.contains(
+            "@__mrs_const.1 = private unnamed_addr constant [3 x i32] [i32 1, i32 -2, i32 3]"
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+//! A module containing the profiling instrumentation of the generated LLVM IR.
+//!
+//! When the [profiling](crate::codegen::CodeGenOptions::profiling) is enabled, every function
+//! with a body calls the [`ENTER_HOOK`] when it is entered and the [`EXIT_HOOK`] before it
+//! returns, passing its index among the defined functions of the module. The hooks are provided
+//! by a [`Profiler`] attached to the execution engine, which measures the number of calls
+//! and the wall time spent in each function.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use inkwell::execution_engine::ExecutionEngine;
+use inkwell::module::{Linkage, Module};
+use inkwell::values::{FunctionValue, InstructionOpcode};
+
+/// The name of the function called when a profiled function is entered.
+pub const ENTER_HOOK: &str = "__mrs_profile_enter";
+
+/// The name of the function called when a profiled function returns.
+pub const EXIT_HOOK: &str = "__mrs_profile_exit";
+
+/// Instruments all the functions of the given module (which mustn't be changed afterwards)
+/// with the calls of the profiling hooks.
+pub(crate) fn instrument(module: &Module) {
+    let context = module.get_context();
+    let id_type = context.i32_type();
+    let hook_type = context.void_type().fn_type(&[id_type.into()], false);
+    let functions = profiled_functions(module);
+    let enter = module.add_function(ENTER_HOOK, hook_type, Some(Linkage::External));
+    let exit = module.add_function(EXIT_HOOK, hook_type, Some(Linkage::External));
+
+    let builder = context.create_builder();
+    for (id, function) in functions.into_iter().enumerate() {
+        let id = id_type.const_int(id as u64, false);
+        let mut returns = Vec::new();
+        for block in function.get_basic_blocks() {
+            if let Some(terminator) = block.get_terminator() {
+                if terminator.get_opcode() == InstructionOpcode::Return {
+                    returns.push(terminator);
+                }
+            }
+        }
+
+        // The entry block has no predecessors, so it cannot start with a phi node
+        let entry = function.get_first_basic_block();
+        let first = entry.and_then(|entry| entry.get_first_instruction());
+        let positions = first.into_iter().map(|first| (first, enter));
+        let positions = positions.chain(returns.into_iter().map(|ret| (ret, exit)));
+        for (instruction, hook) in positions {
+            builder.position_before(&instruction);
+            let result = builder.build_call(hook, &[id.into()], "");
+            // The builder is always positioned, so the call can always be built
+            debug_assert!(result.is_ok());
+        }
+    }
+}
+
+/// Returns the functions with a body in the order of their profiling indices.
+fn profiled_functions<'ctx>(module: &Module<'ctx>) -> Vec<FunctionValue<'ctx>> {
+    module
+        .get_functions()
+        .filter(|function| function.count_basic_blocks() > 0)
+        .collect()
+}
+
+/// The number of calls and the wall time spent in a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionProfile {
+    name: Box<str>,
+    calls: u64,
+    time: Duration,
+}
+
+impl FunctionProfile {
+    /// Returns the name of the function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of times the function was called.
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    /// Returns the total wall time spent in the function,
+    /// including the functions it called (but counting the recursive calls only once).
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+}
+
+/// The profile of all the instrumented functions of a module.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileReport {
+    functions: Vec<FunctionProfile>,
+}
+
+impl ProfileReport {
+    /// Returns the profiles of the individual functions, in the order of their definitions.
+    pub fn functions(&self) -> &[FunctionProfile] {
+        &self.functions
+    }
+
+    /// Returns the profile of the function with the given name, if it was instrumented.
+    pub fn function(&self, name: &str) -> Option<&FunctionProfile> {
+        self.functions.iter().find(|f| f.name() == name)
+    }
+
+    /// Returns the called functions sorted from the one with the most time spent in it.
+    pub fn hottest(&self) -> Vec<&FunctionProfile> {
+        let mut functions: Vec<_> = self.functions.iter().filter(|f| f.calls > 0).collect();
+        functions.sort_by(|a, b| b.time.cmp(&a.time).then(b.calls.cmp(&a.calls)));
+        functions
+    }
+}
+
+impl fmt::Display for ProfileReport {
+    /// Formats the [hottest](ProfileReport::hottest) functions, one per line.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines = self
+            .hottest()
+            .into_iter()
+            .map(|function| {
+                format!(
+                    "fn {}: {} call(s), {:?}",
+                    function.name, function.calls, function.time
+                )
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// The measurements of the functions running on the current thread.
+#[derive(Debug, Default)]
+struct Measurements {
+    calls: Vec<u64>,
+    depths: Vec<u32>,
+    starts: Vec<Option<Instant>>,
+    times: Vec<Duration>,
+}
+
+thread_local! {
+    /// The measurements of the profiled program running on the current thread.
+    static MEASUREMENTS: RefCell<Measurements> = RefCell::new(Measurements::default());
+}
+
+/// The profiler of a module instrumented with the profiling hooks.
+///
+/// The measurements are kept per thread, so the program has to run on the thread
+/// that [attached](Profiler::attach) the profiler, and only one profiled program
+/// can run on a thread at the same time.
+#[derive(Debug)]
+pub struct Profiler {
+    names: Vec<Box<str>>,
+}
+
+impl Profiler {
+    /// Provides the profiling hooks of the given instrumented module to the execution engine,
+    /// and resets the measurements of the current thread.
+    pub fn attach(module: &Module, engine: &ExecutionEngine) -> Profiler {
+        for (name, address) in [
+            (ENTER_HOOK, enter as extern "C" fn(u32) as usize),
+            (EXIT_HOOK, exit as extern "C" fn(u32) as usize),
+        ] {
+            if let Some(function) = module.get_function(name) {
+                engine.add_global_mapping(&function, address);
+            }
+        }
+
+        let names: Vec<Box<str>> = profiled_functions(module)
+            .into_iter()
+            .map(|function| function.get_name().to_string_lossy().into())
+            .collect();
+        let count = names.len();
+        MEASUREMENTS.with(|measurements| {
+            *measurements.borrow_mut() = Measurements {
+                calls: vec![0; count],
+                depths: vec![0; count],
+                starts: vec![None; count],
+                times: vec![Duration::ZERO; count],
+            };
+        });
+        Profiler { names }
+    }
+
+    /// Returns the measurements of the program that has run on the current thread.
+    pub fn report(&self) -> ProfileReport {
+        MEASUREMENTS.with(|measurements| {
+            let measurements = measurements.borrow();
+            let functions = self
+                .names
+                .iter()
+                .enumerate()
+                .map(|(id, name)| FunctionProfile {
+                    name: name.clone(),
+                    calls: measurements.calls.get(id).copied().unwrap_or_default(),
+                    time: measurements.times.get(id).copied().unwrap_or_default(),
+                })
+                .collect();
+            ProfileReport { functions }
+        })
+    }
+}
+
+extern "C" fn enter(id: u32) {
+    let id = id as usize;
+    MEASUREMENTS.with(|measurements| {
+        let mut m = measurements.borrow_mut();
+        if id >= m.calls.len() {
+            return;
+        }
+        m.calls[id] += 1;
+        if m.depths[id] == 0 {
+            m.starts[id] = Some(Instant::now());
+        }
+        m.depths[id] += 1;
+    });
+}
+
+extern "C" fn exit(id: u32) {
+    let id = id as usize;
+    MEASUREMENTS.with(|measurements| {
+        let mut m = measurements.borrow_mut();
+        if id >= m.depths.len() || m.depths[id] == 0 {
+            return;
+        }
+        m.depths[id] -= 1;
+        if m.depths[id] == 0 {
+            if let Some(start) = m.starts[id].take() {
+                m.times[id] += start.elapsed();
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::OptimizationLevel;
+
+    use crate::codegen::session::CompilationSession;
+    use crate::codegen::CodeGenOptions;
+    use crate::test_util::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_profiler() {
+        let crt = parse(
+            "fn fact(n: i32) -> i32 { if n <= 1 { return 1; }; return n * fact(n - 1); }\n\
+            fn unused() {}\n\
+            pub fn main() -> i32 { return fact(5) + fact(3); }\n",
+        );
+
+        let options = CodeGenOptions::new().with_profiling(true);
+        let report = CompilationSession::with_options(options)
+            .with_module(&crt, |module, _| {
+                let engine = module
+                    .create_jit_execution_engine(OptimizationLevel::None)
+                    .unwrap();
+                let profiler = Profiler::attach(module, &engine);
+                // SAFETY: The signature of `main` is known
+                let result = unsafe {
+                    engine
+                        .get_function::<unsafe extern "C" fn() -> i32>("main")
+                        .unwrap()
+                        .call()
+                };
+                assert_eq!(result, 126);
+                profiler.report()
+            })
+            .unwrap();
+
+        let calls: Vec<_> = report
+            .functions()
+            .iter()
+            .map(|f| (f.name(), f.calls()))
+            .collect();
+        assert_eq!(calls, [("fact", 8), ("unused", 0), ("main", 1)]);
+        let hottest: Vec<_> = report.hottest().iter().map(|f| f.name()).collect();
+        assert_eq!(hottest, ["main", "fact"]);
+        assert!(report.function("main").unwrap().time() >= report.function("fact").unwrap().time());
+        assert_eq!(report.to_string().lines().count(), 2);
+    }
+}
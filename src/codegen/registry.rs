@@ -0,0 +1,93 @@
+//! A module containing the registry of the μRust-level function signatures.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::Type;
+use crate::token::Span;
+
+/// The μRust-level signature of a function declared in the crate
+/// (including the functions declared in extern blocks).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSignature {
+    name: Rc<str>,
+    param_types: Vec<Type>,
+    return_type: Type,
+    span: Span,
+}
+
+impl FunctionSignature {
+    /// Creates a new `FunctionSignature` with the given name, parameter types, return type
+    /// and the span of the function prototype.
+    pub fn new(
+        name: Rc<str>,
+        param_types: Vec<Type>,
+        return_type: Type,
+        span: Span,
+    ) -> FunctionSignature {
+        FunctionSignature {
+            name,
+            param_types,
+            return_type,
+            span,
+        }
+    }
+
+    /// Returns the name of the function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the types of the parameters.
+    pub fn param_types(&self) -> &[Type] {
+        &self.param_types
+    }
+
+    /// Returns the number of the parameters.
+    pub fn param_count(&self) -> usize {
+        self.param_types.len()
+    }
+
+    /// Returns the return type.
+    pub fn return_type(&self) -> &Type {
+        &self.return_type
+    }
+
+    /// Returns the span of the function prototype.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// A registry of the signatures of all the functions declared in the crate.
+///
+/// Unlike the [symbol table](super::CodeGenState::symbol_table), which maps the names
+/// to the LLVM values, it is populated only once -- during the Collection Phase.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionRegistry {
+    functions: HashMap<Rc<str>, FunctionSignature>,
+}
+
+impl FunctionRegistry {
+    /// Creates a new empty `FunctionRegistry`.
+    pub fn new() -> FunctionRegistry {
+        FunctionRegistry {
+            functions: HashMap::new(),
+        }
+    }
+
+    /// Registers the given signature and returns the previous one with the same name, if any.
+    pub fn insert(&mut self, signature: FunctionSignature) -> Option<FunctionSignature> {
+        self.functions.insert(signature.name.clone(), signature)
+    }
+
+    /// Returns the signature of the function with the given name, if any.
+    pub fn get(&self, name: &str) -> Option<&FunctionSignature> {
+        self.functions.get(name)
+    }
+
+    /// Returns an iterator over all the registered signatures (in an arbitrary order).
+    pub fn iter(&self) -> impl Iterator<Item = &FunctionSignature> {
+        self.functions.values()
+    }
+}
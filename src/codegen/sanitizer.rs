@@ -0,0 +1,291 @@
+//! A module containing the runtime checks of the memory accesses in the generated LLVM IR.
+//!
+//! When the [sanitizer](crate::codegen::CodeGenOptions::sanitizer) is enabled, the loads
+//! and stores built with [`CodeGenState::build_checked_load`] and
+//! [`CodeGenState::build_checked_store`] are checked at runtime: an access through a pointer
+//! that is neither a local variable nor a static (i.e. a future reference) is checked for null,
+//! and the execution is aborted if it is. The statics always have an initializer,
+//! so their accesses are not checked.
+//!
+//! Every fault calls the [`REPORT_HOOK`] with the [kind](Fault) of the fault and the span
+//! of the faulting access. The hook is provided by a [`Sanitizer`] attached to the execution
+//! engine, which prints the fault to the standard error and collects it.
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+
+use inkwell::execution_engine::ExecutionEngine;
+use inkwell::module::{Linkage, Module};
+use inkwell::types::BasicType;
+use inkwell::values::{BasicValue, BasicValueEnum, FunctionValue, InstructionOpcode, PointerValue};
+
+use crate::codegen;
+use crate::codegen::CodeGenState;
+use crate::token::{Position, Span};
+
+/// The name of the function called when a fault is detected.
+pub const REPORT_HOOK: &str = "__mrs_sanitizer_report";
+
+/// The kind of invalid memory access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fault {
+    /// A null pointer was dereferenced.
+    NullPointer,
+}
+
+impl Fault {
+    /// The faults in the order of their codes.
+    const ALL: [Fault; 1] = [Fault::NullPointer];
+
+    /// Returns the code passed to the [`REPORT_HOOK`].
+    fn code(self) -> u64 {
+        self as u64
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::NullPointer => write!(f, "Null pointer dereference"),
+        }
+    }
+}
+
+/// An invalid memory access detected at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SanitizerError {
+    fault: Fault,
+    span: Span,
+}
+
+impl SanitizerError {
+    /// Returns the kind of the access.
+    pub fn fault(&self) -> Fault {
+        self.fault
+    }
+
+    /// Returns the span of the faulting access.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl fmt::Display for SanitizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.fault, self.span)
+    }
+}
+
+impl Error for SanitizerError {}
+
+impl<'ctx> CodeGenState<'ctx> {
+    /// Builds a load of a value of the given type from the pointer,
+    /// checked by the sanitizer if it is enabled.
+    ///
+    /// The `span` is the span of the expression accessing the memory.
+    pub fn build_checked_load<T: BasicType<'ctx>>(
+        &mut self,
+        ty: T,
+        ptr: PointerValue<'ctx>,
+        span: Span,
+    ) -> codegen::Result<BasicValueEnum<'ctx>> {
+        if self.needs_null_check(ptr) {
+            self.build_null_check(ptr, span)?;
+        }
+        Ok(self.builder().build_load(ty, ptr, "")?)
+    }
+
+    /// Builds a store of the value to the pointer, checked by the sanitizer if it is enabled.
+    ///
+    /// The `span` is the span of the expression accessing the memory.
+    pub fn build_checked_store<V: BasicValue<'ctx>>(
+        &mut self,
+        ptr: PointerValue<'ctx>,
+        value: V,
+        span: Span,
+    ) -> codegen::Result<()> {
+        if self.needs_null_check(ptr) {
+            self.build_null_check(ptr, span)?;
+        }
+        self.builder().build_store(ptr, value)?;
+        Ok(())
+    }
+
+    /// Returns whether the sanitizer is enabled and the pointer is neither
+    /// a local variable nor a static.
+    fn needs_null_check(&self, ptr: PointerValue<'ctx>) -> bool {
+        if !self.options().sanitizer() {
+            return false;
+        }
+        if ptr
+            .as_instruction()
+            .is_some_and(|i| i.get_opcode() == InstructionOpcode::Alloca)
+        {
+            return false;
+        }
+
+        let name = ptr.get_name().to_string_lossy();
+        !matches!(self.module().get_global(&name), Some(global) if global.as_pointer_value() == ptr)
+    }
+
+    /// Builds a check aborting the execution if the pointer is null.
+    fn build_null_check(&mut self, ptr: PointerValue<'ctx>, span: Span) -> codegen::Result<()> {
+        let is_null = self.builder().build_is_null(ptr, "is_null")?;
+        let function = self.current_function();
+        let fault_bb = self.context().append_basic_block(function, "null");
+        let ok_bb = self.context().append_basic_block(function, "non_null");
+        self.builder()
+            .build_conditional_branch(is_null, fault_bb, ok_bb)?;
+
+        self.builder().position_at_end(fault_bb);
+        self.build_fault_report(Fault::NullPointer, span)?;
+        self.build_trap()?;
+        self.builder().position_at_end(ok_bb);
+        Ok(())
+    }
+
+    /// Builds a call of the [`REPORT_HOOK`] with the given fault and span.
+    fn build_fault_report(&mut self, fault: Fault, span: Span) -> codegen::Result<()> {
+        let hook = self.report_hook();
+        let arg_type = self.context().i64_type();
+        let args = [
+            fault.code(),
+            span.start().line() as u64,
+            span.start().column() as u64,
+            span.end().line() as u64,
+            span.end().column() as u64,
+        ]
+        .map(|arg| arg_type.const_int(arg, false).into());
+        self.builder().build_call(hook, &args, "")?;
+        Ok(())
+    }
+
+    /// Returns the [`REPORT_HOOK`], declaring it if needed.
+    fn report_hook(&self) -> FunctionValue<'ctx> {
+        self.module().get_function(REPORT_HOOK).unwrap_or_else(|| {
+            let arg_type = self.context().i64_type().into();
+            let hook_type = self.context().void_type().fn_type(&[arg_type; 5], false);
+            let linkage = Some(Linkage::External);
+            self.module().add_function(REPORT_HOOK, hook_type, linkage)
+        })
+    }
+
+    /// Returns the function whose body is being generated.
+    fn current_function(&mut self) -> FunctionValue<'ctx> {
+        self.get_current_function()
+            .unwrap_or_else(|| panic!("Memory access outside of function"))
+    }
+}
+
+thread_local! {
+    /// The faults of the program running on the current thread.
+    static FAULTS: RefCell<Vec<SanitizerError>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The runtime of the sanitizer, collecting the faults of a module instrumented with the checks.
+///
+/// The faults are kept per thread, so the program has to run on the thread
+/// that [attached](Sanitizer::attach) the sanitizer.
+#[derive(Debug)]
+pub struct Sanitizer {
+    _private: (),
+}
+
+impl Sanitizer {
+    /// Provides the [`REPORT_HOOK`] of the given module to the execution engine,
+    /// and clears the faults of the current thread.
+    pub fn attach(module: &Module, engine: &ExecutionEngine) -> Sanitizer {
+        if let Some(function) = module.get_function(REPORT_HOOK) {
+            engine.add_global_mapping(&function, report as extern "C" fn(_, _, _, _, _) as usize);
+        }
+        FAULTS.with(|faults| faults.borrow_mut().clear());
+        Sanitizer { _private: () }
+    }
+
+    /// Returns the faults of the program that has run on the current thread,
+    /// in the order they were detected.
+    pub fn faults(&self) -> Vec<SanitizerError> {
+        FAULTS.with(|faults| faults.borrow().clone())
+    }
+}
+
+extern "C" fn report(fault: u64, start_line: u64, start_col: u64, end_line: u64, end_col: u64) {
+    let Some(fault) = Fault::ALL.get(fault as usize).copied() else {
+        return;
+    };
+    let start = Position::new_at(start_line as usize, start_col as usize);
+    let end = Position::new_at(end_line as usize, end_col as usize);
+    let error = SanitizerError {
+        fault,
+        span: Span::new(start, end),
+    };
+    // A null pointer dereference aborts the process, so the fault is printed right away
+    eprintln!("sanitizer: {}", error);
+    FAULTS.with(|faults| faults.borrow_mut().push(error));
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::context::Context;
+    use inkwell::OptimizationLevel;
+
+    use crate::codegen::{CodeGenOptions, CodeGenRequest};
+    use crate::test_util::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_checked_accesses() {
+        let crt = parse(
+            "static LIMIT: i32 = 2 * 5;\n\
+            static mut COUNT: i32 = 0;\n\
+            pub fn reset() { unsafe { COUNT = 1; }; }\n",
+        );
+
+        // The statics are always initialized, so their accesses are not checked
+        let context = Context::create();
+        let options = CodeGenOptions::new().with_sanitizer(true);
+        let (module, _) = crt
+            .code_gen_with(&context, CodeGenRequest::new().with_options(options))
+            .unwrap();
+        let ir = module.print_to_string().to_string();
+        assert!(ir.contains("@LIMIT = internal global i32 10"));
+        assert!(ir.contains("@COUNT = internal global i32 0"));
+        assert!(!ir.contains(REPORT_HOOK));
+
+        // A pointer that isn't a variable is checked for null
+        let mut state = CodeGenState::with_options(&context, "test", options);
+        let i32_type = context.i32_type();
+        let ptr_type = i32_type.ptr_type(Default::default());
+        let deref =
+            state
+                .module()
+                .add_function("deref", i32_type.fn_type(&[ptr_type.into()], false), None);
+        state
+            .builder()
+            .position_at_end(context.append_basic_block(deref, "start"));
+        let ptr = deref.get_first_param().unwrap().into_pointer_value();
+        let span = Span::new(Position::new_at(3, 1), Position::new_at(3, 3));
+        let value = state.build_checked_load(i32_type, ptr, span).unwrap();
+        state.builder().build_return(Some(&value)).unwrap();
+
+        let module = state.take_module();
+        module.verify().unwrap();
+        assert_eq!(deref.count_basic_blocks(), 3);
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        let sanitizer = Sanitizer::attach(&module, &engine);
+        let mut value = 42;
+        // SAFETY: The signature of the function is known, and the pointer is valid
+        let result = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn(*mut i32) -> i32>("deref")
+                .unwrap()
+                .call(&mut value)
+        };
+        assert_eq!(result, 42);
+        assert!(sanitizer.faults().is_empty());
+    }
+}
@@ -0,0 +1,524 @@
+//! A module containing the compilation sessions, which own the LLVM context of a compilation.
+//!
+//! Every LLVM value generated by [`Crate::code_gen`] borrows the [`Context`] it was created in,
+//! so the `'ctx` lifetime would otherwise leak into every type that holds a compiled module.
+//! A [`CompilationSession`] creates a fresh context for each compilation and either lends
+//! the module to a callback, or converts the results into owned [`CompilationOutput`].
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use inkwell::context::Context;
+use inkwell::module::Module;
+use inkwell::support::LLVMString;
+use inkwell::targets::{
+    CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine,
+};
+use inkwell::OptimizationLevel;
+
+use crate::ast::error::SemanticWarning;
+use crate::ast::Crate;
+use crate::backend::{Backend, BackendError};
+use crate::cancel::CancellationToken;
+use crate::codegen::asm;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::stats::IrStats;
+use crate::codegen::{CodeGenOptions, CodeGenRequest, Result};
+use crate::observer::SharedObserver;
+
+/// A compilation session with the given [options](CodeGenOptions).
+///
+/// The session doesn't hold any LLVM state -- a new [`Context`] is created (and dropped)
+/// for every compilation -- so it can be freely shared between threads,
+/// and so can the [`CompilationOutput`] it produces.
+///
+/// If the session has a [cancellation token](CompilationSession::with_cancellation),
+/// every compilation aborts with [`CodeGenError::Interrupted`] once the token
+/// is cancelled or times out.
+#[derive(Clone, Default)]
+pub struct CompilationSession {
+    options: CodeGenOptions,
+    cancellation: Option<CancellationToken>,
+    observer: Option<SharedObserver>,
+    module_name: Option<Arc<str>>,
+}
+
+impl CompilationSession {
+    /// Creates a new `CompilationSession` with the default options.
+    pub fn new() -> CompilationSession {
+        CompilationSession::with_options(CodeGenOptions::default())
+    }
+
+    /// Creates a new `CompilationSession` with the given options.
+    pub fn with_options(options: CodeGenOptions) -> CompilationSession {
+        CompilationSession {
+            options,
+            cancellation: None,
+            observer: None,
+            module_name: None,
+        }
+    }
+
+    /// Sets the token that aborts the compilations of the session.
+    pub fn with_cancellation(self, cancellation: CancellationToken) -> CompilationSession {
+        CompilationSession {
+            cancellation: Some(cancellation),
+            ..self
+        }
+    }
+
+    /// Registers the observer notified about the progress of the compilations of the session
+    /// (see [`PipelineObserver`](crate::observer::PipelineObserver)).
+    pub fn with_observer(self, observer: SharedObserver) -> CompilationSession {
+        CompilationSession {
+            observer: Some(observer),
+            ..self
+        }
+    }
+
+    /// Sets the identifier of the modules generated by the session,
+    /// overriding the [module naming](CodeGenOptions::module_naming) of the options.
+    pub fn with_module_name(self, name: impl Into<Arc<str>>) -> CompilationSession {
+        CompilationSession {
+            module_name: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Returns the options of the code generation.
+    pub fn options(&self) -> CodeGenOptions {
+        self.options
+    }
+
+    /// Returns the token that aborts the compilations of the session, if any.
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    /// Returns the request of a compilation of the session.
+    fn request(&self) -> CodeGenRequest<'_> {
+        let mut request = CodeGenRequest::new().with_options(self.options);
+        if let Some(cancellation) = &self.cancellation {
+            request = request.with_cancellation(cancellation);
+        }
+        if let Some(observer) = &self.observer {
+            request = request.with_observer(observer);
+        }
+        if let Some(name) = &self.module_name {
+            request = request.with_module_name(name);
+        }
+        request
+    }
+
+    /// Generates the LLVM IR for the given crate in a fresh context, and passes the module
+    /// together with the warnings to the given callback.
+    ///
+    /// The module (and the context) is dropped after the callback returns,
+    /// so the callback's result cannot borrow from it.
+    pub fn with_module<R>(
+        &self,
+        crt: &Crate,
+        f: impl for<'ctx> FnOnce(&Module<'ctx>, Vec<SemanticWarning>) -> R,
+    ) -> Result<R> {
+        let context = Context::create();
+        let (module, warnings) = crt.code_gen_with(&context, self.request())?;
+        Ok(f(&module, warnings))
+    }
+
+    /// Compiles the given crate and returns the owned results of the compilation.
+    ///
+    /// The object code is emitted for the host machine.
+    pub fn compile(&self, crt: &Crate) -> Result<CompilationOutput> {
+        self.with_module(crt, CompilationOutput::new)?
+    }
+
+    /// Compiles only the function or static item with the given `name` from the given crate
+    /// (see [`CodeGenRequest::with_item`]) and returns the owned results of the compilation.
+    pub fn compile_item(&self, crt: &Crate, name: &str) -> Result<CompilationOutput> {
+        let context = Context::create();
+        let (module, warnings) = crt.code_gen_with(&context, self.request().with_item(name))?;
+        CompilationOutput::new(&module, warnings)
+    }
+
+    /// Compiles the given crate with the [debug info](CodeGenOptions::debug_info), and returns
+    /// the assembly for the host machine annotated with the lines of its source file
+    /// (see [`asm::annotate`]).
+    ///
+    /// If the source file cannot be read, the assembly is annotated only with the line numbers.
+    pub fn emit_assembly(&self, crt: &Crate) -> Result<String> {
+        let session = CompilationSession {
+            options: self.options.with_debug_info(true),
+            ..self.clone()
+        };
+        let (asm, triple) = session.with_module(crt, |module, _| -> Result<_> {
+            let machine = native_target_machine()?;
+            module.set_triple(&machine.get_triple());
+            module.set_data_layout(&machine.get_target_data().get_data_layout());
+            let buffer = machine
+                .write_to_memory_buffer(module, FileType::Assembly)
+                .map_err(target_error)?;
+            let asm = String::from_utf8_lossy(buffer.as_slice()).into_owned();
+            Ok((
+                asm,
+                machine.get_triple().as_str().to_string_lossy().into_owned(),
+            ))
+        })??;
+        let source = crt
+            .source_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        Ok(asm::annotate(&asm, &source, asm::comment_token(&triple)))
+    }
+}
+
+/// A session is the LLVM [backend](Backend), emitting the object code with its options.
+impl Backend for CompilationSession {
+    fn name(&self) -> &'static str {
+        "llvm"
+    }
+
+    fn emit_object(&self, crt: &Crate) -> std::result::Result<Vec<u8>, BackendError> {
+        Ok(self.compile(crt)?.into_object())
+    }
+}
+
+impl fmt::Debug for CompilationSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompilationSession")
+            .field("options", &self.options)
+            .field("cancellation", &self.cancellation)
+            .field("observer", &self.observer.as_ref().map(|_| ".."))
+            .field("module_name", &self.module_name)
+            .finish()
+    }
+}
+
+/// The owned results of a [compilation](CompilationSession::compile).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompilationOutput {
+    ir: String,
+    bitcode: Vec<u8>,
+    object: Vec<u8>,
+    stats: IrStats,
+    warnings: Vec<SemanticWarning>,
+}
+
+impl CompilationOutput {
+    /// Converts the given module and warnings into owned results, emitting the object code.
+    fn new(module: &Module, warnings: Vec<SemanticWarning>) -> Result<CompilationOutput> {
+        let object = emit_object(module)?;
+        Ok(CompilationOutput {
+            ir: module.print_to_string().to_string(),
+            // The bitcode is written after emitting the object code, so that it has the target triple
+            bitcode: module.write_bitcode_to_memory().as_slice().to_vec(),
+            object,
+            stats: IrStats::of(module),
+            warnings,
+        })
+    }
+
+    /// Returns the textual representation of the generated LLVM IR.
+    pub fn ir(&self) -> &str {
+        &self.ir
+    }
+
+    /// Returns the generated LLVM IR encoded as LLVM bitcode.
+    pub fn bitcode(&self) -> &[u8] {
+        &self.bitcode
+    }
+
+    /// Writes the [bitcode](CompilationOutput::bitcode) into the `.bc` file at the given path,
+    /// so that it can be consumed by the other LLVM-based tools (e.g. `llvm-dis` or `clang`).
+    pub fn emit_bitcode(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, &self.bitcode)
+    }
+
+    /// Returns the contents of the object file for the host machine.
+    pub fn object(&self) -> &[u8] {
+        &self.object
+    }
+
+    /// Converts the results into the contents of the object file, dropping the rest.
+    pub fn into_object(self) -> Vec<u8> {
+        self.object
+    }
+
+    /// Returns the statistics of the generated LLVM IR.
+    pub fn stats(&self) -> IrStats {
+        self.stats
+    }
+
+    /// Returns the warnings encountered during code generation.
+    pub fn warnings(&self) -> &[SemanticWarning] {
+        &self.warnings
+    }
+}
+
+/// Creates a target machine for the host, using the position-independent relocation model.
+///
+/// # Errors
+///
+/// If the native target cannot be initialized, [`CodeGenError::TargetError`] is returned.
+pub fn native_target_machine() -> Result<TargetMachine> {
+    Target::initialize_native(&InitializationConfig::default())
+        .map_err(|message| CodeGenError::TargetError(message.into()))?;
+
+    let triple = TargetMachine::get_default_triple();
+    let target = Target::from_triple(&triple).map_err(target_error)?;
+    target
+        .create_target_machine(
+            &triple,
+            &TargetMachine::get_host_cpu_name().to_string(),
+            &TargetMachine::get_host_cpu_features().to_string(),
+            OptimizationLevel::None,
+            RelocMode::PIC,
+            CodeModel::Default,
+        )
+        .ok_or_else(|| CodeGenError::TargetError("Cannot create the target machine".into()))
+}
+
+/// Emits the object code of the given module for the host machine.
+fn emit_object(module: &Module) -> Result<Vec<u8>> {
+    let machine = native_target_machine()?;
+    module.set_triple(&machine.get_triple());
+    module.set_data_layout(&machine.get_target_data().get_data_layout());
+    let buffer = machine
+        .write_to_memory_buffer(module, FileType::Object)
+        .map_err(target_error)?;
+    Ok(buffer.as_slice().to_vec())
+}
+
+fn target_error(message: LLVMString) -> CodeGenError {
+    CodeGenError::TargetError(message.to_string().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::cancel::Interrupted;
+    use crate::codegen::ModuleNaming;
+    use crate::parser::Parser;
+    use crate::test_util::{parse_file, TempDir};
+
+    use super::*;
+
+    fn parse(source: &str, file_name: &str) -> Crate {
+        parse_file(file_name, source).unwrap()
+    }
+
+    #[test]
+    fn test_compile_across_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<CompilationSession>();
+        assert_send_sync::<CompilationOutput>();
+
+        let session = &CompilationSession::new();
+        let outputs: Vec<CompilationOutput> = thread::scope(|s| {
+            let handles: Vec<_> = (1..=2)
+                .map(|i| {
+                    s.spawn(move || {
+                        let source =
+                            format!("fn f(x: i32) -> i32 {{ unsafe {{ return x + {i}; }}; }}");
+                        let crt = parse(&source, &format!("mrs_test_session_{i}.mrs"));
+                        session.compile(&crt).unwrap()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        for (i, output) in (1..=2).zip(outputs.iter()) {
+            assert!(output.ir().contains(&format!("add i32 %x, {i}")));
+            assert!(!output.object().is_empty());
+            assert_eq!(output.stats().functions(), 1);
+            assert_eq!(output.warnings().len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_with_module() {
+        let session = CompilationSession::with_options(CodeGenOptions::new().with_mem2reg(false));
+        let crt = parse(
+            "fn f() -> i32 { let x: i32 = 1; return 1; }",
+            "mrs_test_session.mrs",
+        );
+        let ir = session
+            .with_module(&crt, |module, _| module.print_to_string().to_string())
+            .unwrap();
+        assert!(ir.contains("alloca i32"));
+
+        let crt = parse(
+            "fn f() -> i32 { let x: i32; return x; }",
+            "mrs_test_session.mrs",
+        );
+        assert!(session.compile(&crt).is_err());
+    }
+
+    #[test]
+    fn test_bitcode_round_trip() {
+        let crt = parse(
+            "pub fn f() -> i32 { return 2 * 3; }\nstatic X: f64 = 0.5;\n",
+            "mrs_test_session_bitcode.mrs",
+        );
+        let output = CompilationSession::new().compile(&crt).unwrap();
+        assert!(output.bitcode().starts_with(b"BC\xc0\xde"));
+
+        let dir = TempDir::new("session_bitcode");
+        let path = dir.join("main.bc");
+        output.emit_bitcode(&path).unwrap();
+        let context = Context::create();
+        let module = Module::parse_bitcode_from_path(&path, &context).unwrap();
+
+        module.verify().unwrap();
+        assert!(module.get_function("f").is_some());
+        assert!(module.get_global("X").is_some());
+        assert_eq!(
+            module.get_triple(),
+            native_target_machine().unwrap().get_triple()
+        );
+        // Only the module identifier (the first line) differs
+        let ir = module.print_to_string().to_string();
+        assert_eq!(
+            ir.split_once('\n').unwrap().1,
+            output.ir().split_once('\n').unwrap().1
+        );
+    }
+
+    #[test]
+    fn test_module_names() {
+        let dir = TempDir::new("session_names");
+        let path = dir.write("mrs_test_session_names.mrs", "fn f() -> i32 { return 1; }");
+        let crt = Parser::new(&path).unwrap().parse().unwrap();
+        let name_of = |session: CompilationSession| {
+            session
+                .with_module(&crt, |module, _| {
+                    let name = module.get_name().to_str().unwrap().to_owned();
+                    let source = module.get_source_file_name().to_str().unwrap().to_owned();
+                    (name, source)
+                })
+                .unwrap()
+        };
+        let with_naming = |naming| {
+            name_of(CompilationSession::with_options(
+                CodeGenOptions::new().with_module_naming(naming),
+            ))
+        };
+        let path = path.display().to_string();
+
+        assert_eq!(
+            with_naming(ModuleNaming::FileName),
+            ("mrs_test_session_names.mrs".to_owned(), path.clone())
+        );
+        assert_eq!(
+            with_naming(ModuleNaming::Stem),
+            ("mrs_test_session_names".to_owned(), path.clone())
+        );
+        assert_eq!(
+            with_naming(ModuleNaming::Path),
+            (path.clone(), path.clone())
+        );
+        assert_eq!(
+            name_of(CompilationSession::new().with_module_name("custom")),
+            ("custom".to_owned(), path)
+        );
+    }
+
+    #[test]
+    fn test_compile_item() {
+        let crt = parse(
+            "fn a() -> i32 { return 1; }\n\
+            fn b() -> i32 { return a(); }\n\
+            pub fn c() -> i32 { unsafe { return b(); }; }\n\
+            fn d() -> i32 { return c(); }\n",
+            "mrs_test_session_item.mrs",
+        );
+        let session = CompilationSession::new();
+
+        let output = session.compile_item(&crt, "b").unwrap();
+        assert!(output.ir().contains("define internal i32 @b()"));
+        assert!(output
+            .ir()
+            .lines()
+            .any(|line| line.starts_with("declare") && line.ends_with("i32 @a()")));
+        assert!(!output.ir().contains("@c("));
+        assert!(!output.ir().contains("@d("));
+        assert!(output.warnings().is_empty());
+        assert_eq!(output.stats().functions(), 1);
+
+        let output = session.compile_item(&crt, "c").unwrap();
+        assert_eq!(output.warnings().len(), 1);
+
+        assert!(matches!(
+            session.compile_item(&crt, "e"),
+            Err(CodeGenError::MissingSymbol { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let crt = parse(
+            "fn a() -> i32 { return 1; }\nfn b() -> i32 { return 2; }\n",
+            "mrs_test_session_cancellation.mrs",
+        );
+
+        let token = CancellationToken::new();
+        let session = CompilationSession::new().with_cancellation(token.clone());
+        assert!(session.compile(&crt).is_ok());
+        token.cancel();
+        assert_eq!(
+            session.compile(&crt).unwrap_err(),
+            CodeGenError::Interrupted(Interrupted::Cancelled)
+        );
+        assert_eq!(
+            session.compile_item(&crt, "a").unwrap_err(),
+            CodeGenError::Interrupted(Interrupted::Cancelled)
+        );
+
+        let token = CancellationToken::with_timeout(Duration::ZERO);
+        let session = CompilationSession::new().with_cancellation(token);
+        assert_eq!(
+            session.compile(&crt).unwrap_err(),
+            CodeGenError::Interrupted(Interrupted::TimedOut)
+        );
+    }
+
+    #[test]
+    fn test_emit_assembly() {
+        let source = "fn add(a: i32, b: i32) -> i32 {\n    return a + b;\n}\n\
+            fn main() -> i32 {\n    let x: i32 = add(1, 2);\n    return x;\n}\n";
+        let dir = TempDir::new("session_asm");
+        let path = dir.write("main.mrs", source);
+        let crt = Parser::new(&path).unwrap().parse().unwrap();
+        let asm = CompilationSession::new().emit_assembly(&crt).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(asm.contains(".loc"));
+        assert!(asm.contains(" 2: return a + b;"));
+        assert!(asm.contains(" 5: let x: i32 = add(1, 2);"));
+        assert!(asm.contains(" 6: return x;"));
+        // The options of the session itself are not changed
+        let output = CompilationSession::new().compile(&crt).unwrap();
+        assert!(!output.ir().contains("!dbg"));
+        // Without the source file, only the line numbers are known
+        let asm = CompilationSession::new().emit_assembly(&crt).unwrap();
+        assert!(!asm.contains("return a + b;"));
+    }
+
+    #[test]
+    fn test_empty_crate() {
+        let crt = parse("// Nothing to see here\n\n", "main.mrs");
+
+        let session = CompilationSession::new();
+        let output = session.compile(&crt).unwrap();
+        assert_eq!(output.stats().functions(), 0);
+        assert!(output.warnings().is_empty());
+        assert!(!output.object().is_empty());
+        assert!(session.emit_assembly(&crt).is_ok());
+    }
+}
@@ -0,0 +1,62 @@
+//! A module containing statistics about the generated LLVM IR.
+
+use std::fmt;
+
+use inkwell::module::Module;
+
+/// The size of the LLVM IR of a module, used to compare the quality of the generated code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IrStats {
+    functions: usize,
+    basic_blocks: usize,
+    instructions: usize,
+}
+
+impl IrStats {
+    /// Collects the statistics of the given module.
+    ///
+    /// Only the functions with a body are counted.
+    pub fn of(module: &Module) -> IrStats {
+        let mut stats = IrStats::default();
+        for function in module.get_functions() {
+            if function.count_basic_blocks() == 0 {
+                continue;
+            }
+            stats.functions += 1;
+            for block in function.get_basic_blocks() {
+                stats.basic_blocks += 1;
+                let mut instruction = block.get_first_instruction();
+                while let Some(i) = instruction {
+                    stats.instructions += 1;
+                    instruction = i.get_next_instruction();
+                }
+            }
+        }
+        stats
+    }
+
+    /// Returns the number of functions with a body.
+    pub fn functions(&self) -> usize {
+        self.functions
+    }
+
+    /// Returns the number of basic blocks.
+    pub fn basic_blocks(&self) -> usize {
+        self.basic_blocks
+    }
+
+    /// Returns the number of instructions.
+    pub fn instructions(&self) -> usize {
+        self.instructions
+    }
+}
+
+impl fmt::Display for IrStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} functions, {} basic blocks, {} instructions",
+            self.functions, self.basic_blocks, self.instructions
+        )
+    }
+}
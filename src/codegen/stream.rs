@@ -0,0 +1,235 @@
+//! A module containing the streaming code generation of large files.
+//!
+//! Generating a [`Crate`](crate::ast::Crate) requires its whole AST, which for a very large
+//! (e.g. generated) source file can take much more memory than the generated module itself.
+//! In the streaming mode, the file is [parsed item by item](Parser::items) twice instead:
+//! 1. the items are only declared (like by [`CrateASTNode::collect_symbols`]), so that every
+//!    item can refer to the ones following it, and only the statics are kept to evaluate
+//!    their initializers,
+//! 2. the code of every item is generated right after it is parsed, and the item is dropped.
+//!
+//! So at most one item is held in memory at a time (apart from the statics). The diagnostics
+//! which need the whole crate are not reported, i.e. the
+//! [unused `unsafe` blocks](crate::ast::Crate::check_unsafety), and the imports are not
+//! supported (the files with imports should be compiled as a [`Project`](crate::driver::Project)).
+//!
+//! # Example
+//! ```no_run
+//! # use inkwell::context::Context;
+//! # use mini_rust_compiler_components::codegen::stream;
+//! # use mini_rust_compiler_components::codegen::CodeGenOptions;
+//! let context = Context::create();
+//! let options = CodeGenOptions::default();
+//! let (module, warnings) = stream::code_gen_file(&context, "path/to/file.mrs", options).unwrap();
+//! ```
+//!
+//! [`CrateASTNode::collect_symbols`]: crate::ast::CrateASTNode::collect_symbols
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use fallible_iterator::FallibleIterator;
+use inkwell::context::Context;
+use inkwell::module::Module;
+
+use crate::ast::error::{SemanticError, SemanticWarning};
+use crate::ast::{ASTNode, ItemASTNode, StaticASTNode};
+use crate::codegen::error::CodeGenError;
+use crate::codegen::{CodeGen, CodeGenOptions, CodeGenState, ModuleNaming};
+use crate::init::InitChecker;
+use crate::parser::error::ParserError;
+use crate::parser::Parser;
+use crate::statics;
+use crate::trace;
+
+/// A result of a streaming code generation.
+pub type Result<T> = std::result::Result<T, StreamError>;
+
+/// The type of error that can occur during a streaming code generation.
+#[derive(Debug)]
+pub enum StreamError {
+    /// The source file cannot be read.
+    Io(io::Error),
+    /// The source file cannot be parsed.
+    Parser(ParserError),
+    /// The code cannot be generated.
+    CodeGen(CodeGenError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(err) => write!(f, "Cannot read the file: {}", err),
+            StreamError::Parser(err) => write!(f, "Cannot parse the file: {}", err),
+            StreamError::CodeGen(err) => write!(f, "Cannot generate the code: {:?}", err),
+        }
+    }
+}
+
+impl Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(err: io::Error) -> Self {
+        StreamError::Io(err)
+    }
+}
+
+impl From<ParserError> for StreamError {
+    fn from(err: ParserError) -> Self {
+        StreamError::Parser(err)
+    }
+}
+
+impl From<CodeGenError> for StreamError {
+    fn from(err: CodeGenError) -> Self {
+        StreamError::CodeGen(err)
+    }
+}
+
+impl From<SemanticError> for StreamError {
+    fn from(err: SemanticError) -> Self {
+        StreamError::CodeGen(err.into())
+    }
+}
+
+/// Generates the LLVM IR for the source file at the given path in the streaming mode
+/// (see the [module documentation](self)), together with the warnings encountered
+/// during code generation.
+///
+/// The module is the same as the one generated from the whole crate
+/// (e.g. by [`Crate::code_gen_with`](crate::ast::Crate::code_gen_with)).
+pub fn code_gen_file<'ctx, P: AsRef<Path>>(
+    context: &'ctx Context,
+    path: P,
+    options: CodeGenOptions,
+) -> Result<(Module<'ctx>, Vec<SemanticWarning>)> {
+    let path = path.as_ref();
+    let items = Parser::new(path)?.items();
+    let module_name = options
+        .module_naming()
+        .identifier(items.filename(), Some(path));
+    trace::span!(INFO, "codegen_stream", module = %module_name);
+    let mut state = CodeGenState::with_options(context, &module_name, options);
+    let source_file_name = ModuleNaming::Path.identifier(items.filename(), Some(path));
+    state.module().set_source_file_name(&source_file_name);
+    if options.debug_info() {
+        state.enable_debug_info(&source_file_name);
+    }
+
+    //#region Declarations
+    let statics = declare_items(items, &mut state)?;
+    let statics: Vec<&StaticASTNode> = statics.iter().collect();
+    state.static_values = statics::evaluate_statics(&statics, options.target_layout())?;
+    //#endregion
+
+    //#region Definitions
+    let mut items = Parser::new(path)?.items();
+    while let Some(item) = items.next()? {
+        let mut checker = InitChecker::new();
+        item.check_init(&mut checker);
+        if let Some(err) = checker.finish().into_iter().next() {
+            return Err(err.into());
+        }
+
+        state.check_cancellation()?;
+        state.notify_item_codegen(&item);
+        item.code_gen(&mut state)?;
+    }
+    //#endregion
+
+    let warnings = state.take_warnings();
+    let module = state.take_module();
+    module
+        .verify()
+        .map_err(CodeGenError::ModuleVerificationFailed)?;
+    trace::event!(DEBUG, warnings = warnings.len(), "generated the module");
+    options.run_passes(&module);
+
+    Ok((module, warnings))
+}
+
+/// Declares all the items of the stream in the symbol table, and returns the statics.
+fn declare_items(
+    mut items: impl FallibleIterator<Item = ItemASTNode, Error = ParserError>,
+    state: &mut CodeGenState,
+) -> Result<Vec<StaticASTNode>> {
+    let mut statics = Vec::new();
+    while let Some(item) = items.next()? {
+        item.add_to_symbol_table(state)?;
+        if let ItemASTNode::Static(stat) = item {
+            statics.push(*stat);
+        }
+    }
+    Ok(statics)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use inkwell::OptimizationLevel;
+
+    use crate::codegen::CodeGenRequest;
+    use crate::parser::Parser;
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_code_gen_file() {
+        let dir = TempDir::new("stream");
+        let path = dir.join("main.mrs");
+        fs::write(
+            &path,
+            "pub fn main() -> i32 { return twice(HALF) + unsafe { COUNT }; }\n\
+            static HALF: i32 = LIMIT / 2;\n\
+            fn twice(x: i32) -> i32 { return 2 * x; }\n\
+            static mut COUNT: i32 = 3;\n\
+            static LIMIT: i32 = 20;\n",
+        )
+        .unwrap();
+
+        let context = Context::create();
+        let options = CodeGenOptions::default();
+        let (module, warnings) = code_gen_file(&context, &path, options).unwrap();
+        assert!(warnings.is_empty());
+
+        // The module is the same as the one generated from the whole crate
+        let crt = Parser::new(&path).unwrap().parse().unwrap();
+        let (expected, _) = crt
+            .code_gen_with(&context, CodeGenRequest::new().with_options(options))
+            .unwrap();
+        assert_eq!(
+            module.print_to_string().to_string(),
+            expected.print_to_string().to_string()
+        );
+
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        let result = unsafe {
+            engine
+                .get_function::<unsafe extern "C" fn() -> i32>("main")
+                .unwrap()
+                .call()
+        };
+        assert_eq!(result, 23);
+
+        // The whole file is parsed before any code is generated
+        fs::write(&path, "pub fn f() -> i32 { let x: i32; return x; }\n+\n").unwrap();
+        let result = code_gen_file(&context, &path, options);
+        assert!(matches!(
+            result,
+            Err(StreamError::Parser(ParserError::Aggregated(_)))
+        ));
+
+        fs::write(&path, "pub fn f() -> i32 { let x: i32; return x; }\n").unwrap();
+        let result = code_gen_file(&context, &path, options);
+        assert!(matches!(
+            result,
+            Err(StreamError::CodeGen(CodeGenError::SemanticError(_)))
+        ));
+    }
+}
@@ -3,7 +3,10 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use inkwell::values::AnyValueEnum;
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::{AnyValue, AnyValueEnum, PointerValue};
+
+use crate::ast::Type;
 
 /// A symbol table used during code generation to store symbols.
 pub struct SymbolTable<'ctx> {
@@ -40,9 +43,57 @@ impl<'ctx> SymbolTable<'ctx> {
 
     /// Inserts a symbol with the given name and value into the current scope
     /// and returns the previous value associated with the name, if any.
+    ///
+    /// If the name is already declared in the current scope, the new symbol shadows it
+    /// for the rest of the scope, e.g. `let x: i32 = 1; let x: i32 = x + 1;`. This means that
+    /// the code referring to the shadowed symbol (like the initializer of a `let` statement)
+    /// has to be generated __before__ the new symbol is inserted.
     pub fn insert(&mut self, name: Rc<str>, value: AnyValueEnum<'ctx>) -> Option<Symbol<'ctx>> {
-        // The stack is guaranteed to have at least one element (see `pop_scope`)
         let symbol = Symbol::new(name.clone(), value);
+        self.insert_symbol(name, symbol)
+    }
+
+    /// Inserts a symbol with the given name and value of type `ty` (like a parameter)
+    /// into the current scope, and returns the previous value associated with the name, if any.
+    ///
+    /// See [`insert`](SymbolTable::insert) for the details about shadowing.
+    pub fn insert_typed(
+        &mut self,
+        name: Rc<str>,
+        value: AnyValueEnum<'ctx>,
+        ty: Type,
+    ) -> Option<Symbol<'ctx>> {
+        let symbol = Symbol {
+            ty: Some(ty),
+            ..Symbol::new(name.clone(), value)
+        };
+        self.insert_symbol(name, symbol)
+    }
+
+    /// Inserts a variable (i.e. a local variable or a static) of type `ty` stored at the given
+    /// pointer as a value of type `stored_type` into the current scope, and returns
+    /// the previous value associated with the name, if any.
+    ///
+    /// See [`insert`](SymbolTable::insert) for the details about shadowing.
+    pub fn insert_variable(
+        &mut self,
+        name: Rc<str>,
+        ptr: PointerValue<'ctx>,
+        stored_type: BasicTypeEnum<'ctx>,
+        ty: Type,
+    ) -> Option<Symbol<'ctx>> {
+        let symbol = Symbol {
+            name: name.clone(),
+            value: ptr.as_any_value_enum(),
+            stored_type: Some(stored_type),
+            ty: Some(ty),
+        };
+        self.insert_symbol(name, symbol)
+    }
+
+    /// Inserts the given symbol into the current scope.
+    fn insert_symbol(&mut self, name: Rc<str>, symbol: Symbol<'ctx>) -> Option<Symbol<'ctx>> {
+        // The stack is guaranteed to have at least one element (see `pop_scope`)
         self.symbol_stack.last_mut().unwrap().insert(name, symbol)
     }
 
@@ -69,6 +120,15 @@ impl<'ctx> SymbolTable<'ctx> {
             .rev()
             .any(|scope| scope.contains_key(name))
     }
+
+    /// Returns `true` if the given name refers to a symbol declared in a local scope
+    /// (like a local variable or a parameter), as opposed to the global scope.
+    pub fn is_local(&self, name: &str) -> bool {
+        self.symbol_stack
+            .iter()
+            .rposition(|scope| scope.contains_key(name))
+            .is_some_and(|index| index > 0)
+    }
 }
 
 impl Default for SymbolTable<'_> {
@@ -84,12 +144,19 @@ type Scope<'ctx> = HashMap<Rc<str>, Symbol<'ctx>>;
 pub struct Symbol<'ctx> {
     name: Rc<str>,
     value: AnyValueEnum<'ctx>,
+    stored_type: Option<BasicTypeEnum<'ctx>>,
+    ty: Option<Type>,
 }
 
 impl<'ctx> Symbol<'ctx> {
     /// Creates a new symbol with the given name and value.
     pub fn new(name: Rc<str>, value: AnyValueEnum<'ctx>) -> Symbol<'ctx> {
-        Symbol { name, value }
+        Symbol {
+            name,
+            value,
+            stored_type: None,
+            ty: None,
+        }
     }
 
     /// Returns the name of the symbol.
@@ -101,4 +168,77 @@ impl<'ctx> Symbol<'ctx> {
     pub fn value(&self) -> AnyValueEnum<'ctx> {
         self.value
     }
+
+    /// Returns the type of the value stored at the pointer of the symbol if it is
+    /// a [variable](SymbolTable::insert_variable), or `None` if the symbol is bound
+    /// directly to its value (like a function or a parameter).
+    pub fn stored_type(&self) -> Option<BasicTypeEnum<'ctx>> {
+        self.stored_type
+    }
+
+    /// Returns the μRust type of the value of the symbol if it is a variable or a parameter,
+    /// or `None` if it is not known (like for a function).
+    pub fn ty(&self) -> Option<&Type> {
+        self.ty.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use inkwell::context::Context;
+
+    use super::*;
+
+    #[test]
+    fn test_shadowing() {
+        let context = Context::create();
+        let value = |v: u64| context.i32_type().const_int(v, false).as_any_value_enum();
+
+        let mut table = SymbolTable::new();
+        table.open_scope();
+        assert!(table.insert("x".into(), value(1)).is_none());
+        let shadowed = table.insert("x".into(), value(2)).unwrap();
+        assert_eq!(shadowed.value(), value(1));
+        assert_eq!(table.get("x").unwrap().value(), value(2));
+
+        table.open_scope();
+        table.insert("x".into(), value(3));
+        assert_eq!(table.get("x").unwrap().value(), value(3));
+        table.close_scope();
+        assert_eq!(table.get("x").unwrap().value(), value(2));
+
+        table.close_scope();
+        assert!(!table.contains("x"));
+    }
+
+    #[test]
+    fn test_variables() {
+        let context = Context::create();
+        let module = context.create_module("variables");
+        let ty = context.f64_type();
+        let global = module.add_global(ty, None, "x");
+
+        let mut table = SymbolTable::new();
+        table.insert(
+            "f".into(),
+            context.i32_type().const_zero().as_any_value_enum(),
+        );
+        assert!(table.get("f").unwrap().stored_type().is_none());
+        assert!(table.get("f").unwrap().ty().is_none());
+
+        table.insert_variable("x".into(), global.as_pointer_value(), ty.into(), Type::F64);
+        let symbol = table.get("x").unwrap();
+        assert_eq!(
+            symbol.value(),
+            global.as_pointer_value().as_any_value_enum()
+        );
+        assert_eq!(symbol.stored_type(), Some(ty.into()));
+        assert_eq!(symbol.ty(), Some(&Type::F64));
+
+        let param = context.i8_type().const_zero().as_any_value_enum();
+        table.insert_typed("p".into(), param, Type::U8);
+        let symbol = table.get("p").unwrap();
+        assert!(symbol.stored_type().is_none());
+        assert_eq!(symbol.ty(), Some(&Type::U8));
+    }
 }
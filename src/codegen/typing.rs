@@ -0,0 +1,351 @@
+//! A module containing the resolution of the μRust types of the generated values.
+//!
+//! The LLVM value of an expression only determines the kind and the width of its type
+//! (see [`Type::try_from_llvm_value`]), since the signed and unsigned integer types
+//! of the same width are the same LLVM type. The signedness is resolved from the types
+//! declared in the source code instead, i.e. from the types of the variables, the parameters,
+//! the return types of the functions, the targets of the casts and the literal suffixes.
+//! The elements of the tuples are resolved the same way, one by one.
+//!
+//! Similarly, all the pointers are read as string slices, since they don't carry
+//! the referenced types. The types of the references are resolved from the declared types
+//! and from the types [recorded](CodeGenState::record_reference_type) by the borrow expressions.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use inkwell::values::AnyValueEnum;
+
+use crate::ast::{
+    ArithExprASTNode, BlockASTNode, BorrowExprASTNode, DerefExprASTNode, ElseASTNode, ElseExpr,
+    ExprASTNode, FunCallASTNode, GroupedExprASTNode, IfASTNode, LayoutOfASTNode, LetASTNode,
+    LiteralASTNode, NegExprASTNode, PathASTNode, TupleASTNode, TupleIndexASTNode, Type,
+    TypeCastASTNode, UnsafeBlockASTNode,
+};
+use crate::builtin::Builtin;
+use crate::codegen::symbol_table::Symbol;
+use crate::codegen::{CodeGenState, Result};
+
+/// The types of the local variables declared in the blocks whose values are being resolved
+/// (which are no longer in the symbol table once the blocks are generated).
+type Scopes = Vec<HashMap<Rc<str>, Type>>;
+
+impl<'ctx> CodeGenState<'ctx> {
+    /// Returns the μRust type of the `value` generated from `expr`, in a context
+    /// which expected the `expected` type (see [`build_expecting`](CodeGenState::build_expecting)).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`CodeGenError::UnsupportedType`](crate::codegen::error::CodeGenError::UnsupportedType)
+    /// if the type of the value is not supported.
+    pub(crate) fn type_of(
+        &self,
+        expr: &dyn ExprASTNode,
+        value: AnyValueEnum<'ctx>,
+        expected: Option<Type>,
+    ) -> Result<Type> {
+        let ty = Type::try_from_llvm_value(self.context(), value, expr.span())?;
+        Ok(self.resolve_type(expr, ty, expected, &mut Vec::new()))
+    }
+
+    /// Records the type of the reference generated by the given borrow expression,
+    /// so that it can be [resolved](CodeGenState::type_of) afterwards.
+    pub(crate) fn record_reference_type(&mut self, borrow: &BorrowExprASTNode, ty: Type) {
+        self.reference_types.insert(borrow, ty);
+    }
+
+    /// Forgets the types of the references recorded in the previous functions, whose borrow
+    /// expressions may be already dropped (e.g. when [streaming](crate::codegen::stream)),
+    /// so their addresses can be reused by other expressions.
+    pub(crate) fn clear_reference_types(&mut self) {
+        self.reference_types.clear();
+    }
+
+    /// Returns the type of the value stored at the given place expression (e.g. a variable
+    /// or a dereferenced reference), if it is known without generating the expression.
+    pub(crate) fn place_type(&self, place: &dyn ExprASTNode) -> Option<Type> {
+        self.declared_type(place, &Vec::new())
+    }
+
+    /// Returns the type `ty` of the value of `expr` (with all the integer types signed, as read
+    /// from the LLVM value) with the signedness of the integers resolved.
+    fn resolve_type(
+        &self,
+        expr: &dyn ExprASTNode,
+        ty: Type,
+        expected: Option<Type>,
+        scopes: &mut Scopes,
+    ) -> Type {
+        match ty {
+            ty if ty.is_integer() && self.is_unsigned(expr, expected.as_ref(), scopes) => {
+                ty.to_unsigned()
+            }
+            Type::Tuple(elements) => self.resolve_tuple_type(expr, &elements, expected, scopes),
+            Type::Str => self.resolve_pointer_type(expr, expected, scopes),
+            ty => ty,
+        }
+    }
+
+    /// Returns the type of the value of the pointer expression `expr`,
+    /// i.e. either a string slice or a reference.
+    fn resolve_pointer_type(
+        &self,
+        expr: &dyn ExprASTNode,
+        expected: Option<Type>,
+        scopes: &mut Scopes,
+    ) -> Type {
+        self.resolve_nested_type(expr, Type::Str, expected, scopes)
+            .or_else(|| self.declared_type(expr, scopes))
+            .unwrap_or(Type::Str)
+    }
+
+    /// Returns the type of the value of the tuple expression `expr`,
+    /// whose elements are of the given types (with all the integer types signed).
+    fn resolve_tuple_type(
+        &self,
+        expr: &dyn ExprASTNode,
+        elements: &[Type],
+        expected: Option<Type>,
+        scopes: &mut Scopes,
+    ) -> Type {
+        let ty = Type::Tuple(elements.into());
+        let node = expr.as_any();
+        if let Some(tuple) = node.downcast_ref::<TupleASTNode>() {
+            let expected = expected
+                .as_ref()
+                .and_then(Type::tuple_elements)
+                .filter(|types| types.len() == elements.len());
+            let elements = tuple
+                .elements()
+                .iter()
+                .zip(elements)
+                .enumerate()
+                .map(|(i, (element, ty))| {
+                    let expected = expected.map(|types| types[i].clone());
+                    self.resolve_type(element.as_ref(), ty.clone(), expected, scopes)
+                })
+                .collect();
+            return Type::tuple(elements);
+        }
+        // The declared tuple types are complete
+        self.resolve_nested_type(expr, ty.clone(), expected, scopes)
+            .or_else(|| self.declared_type(expr, scopes))
+            .unwrap_or(ty)
+    }
+
+    /// Returns the type of the value of `expr` (of the type `ty`, with all the integer types
+    /// signed) if its value is the value of a nested expression, e.g. of a grouped expression
+    /// or of a block, or `None` otherwise.
+    fn resolve_nested_type(
+        &self,
+        expr: &dyn ExprASTNode,
+        ty: Type,
+        expected: Option<Type>,
+        scopes: &mut Scopes,
+    ) -> Option<Type> {
+        let node = expr.as_any();
+        if let Some(grouped) = node.downcast_ref::<GroupedExprASTNode>() {
+            return Some(self.resolve_type(grouped.expr(), ty, expected, scopes));
+        }
+        if let Some(block) = node.downcast_ref::<BlockASTNode>() {
+            return Some(self.resolve_block_type(block, ty, expected, scopes));
+        }
+        if let Some(unsafe_block) = node.downcast_ref::<UnsafeBlockASTNode>() {
+            return Some(self.resolve_block_type(unsafe_block.block(), ty, expected, scopes));
+        }
+        if let Some(if_node) = node.downcast_ref::<IfASTNode>() {
+            if if_node.then_block().return_expr().is_some() {
+                return Some(self.resolve_block_type(if_node.then_block(), ty, expected, scopes));
+            }
+            return Some(match if_node.else_node().map(ElseASTNode::branch) {
+                None => ty,
+                Some(ElseExpr::Else(block)) => self.resolve_block_type(block, ty, expected, scopes),
+                Some(ElseExpr::ElseIf(else_if)) => {
+                    self.resolve_type(else_if.as_ref(), ty, expected, scopes)
+                }
+            });
+        }
+        None
+    }
+
+    /// Returns the type of the value of the given block, which is of the type `ty`
+    /// (with all the integer types signed).
+    fn resolve_block_type(
+        &self,
+        block: &BlockASTNode,
+        ty: Type,
+        expected: Option<Type>,
+        scopes: &mut Scopes,
+    ) -> Type {
+        let Some(expr) = block.return_expr() else {
+            return ty;
+        };
+        scopes.push(block_scope(block));
+        let ty = self.resolve_type(expr, ty, expected, scopes);
+        scopes.pop();
+        ty
+    }
+
+    /// Returns the type of `expr` declared in the source code, if it is known
+    /// without generating the expression (e.g. the type of a variable).
+    fn declared_type(&self, expr: &dyn ExprASTNode, scopes: &Scopes) -> Option<Type> {
+        let node = expr.as_any();
+        if let Some(path) = node.downcast_ref::<PathASTNode>() {
+            return self.variable_type(path.path(), scopes);
+        }
+        if let Some(call) = node.downcast_ref::<FunCallASTNode>() {
+            return self
+                .functions
+                .get(call.path())
+                .map(|signature| signature.return_type().clone());
+        }
+        if let Some(cast) = node.downcast_ref::<TypeCastASTNode>() {
+            return Some(cast.ty());
+        }
+        if let Some(grouped) = node.downcast_ref::<GroupedExprASTNode>() {
+            return self.declared_type(grouped.expr(), scopes);
+        }
+        if let Some(tuple_index) = node.downcast_ref::<TupleIndexASTNode>() {
+            let ty = self.declared_type(tuple_index.tuple(), scopes)?;
+            return ty
+                .tuple_elements()?
+                .get(tuple_index.index() as usize)
+                .cloned();
+        }
+        if let Some(borrow) = node.downcast_ref::<BorrowExprASTNode>() {
+            return self.reference_types.get(&(borrow as *const _)).cloned();
+        }
+        if let Some(deref) = node.downcast_ref::<DerefExprASTNode>() {
+            return self.declared_type(deref.expr(), scopes)?.referenced_type();
+        }
+        None
+    }
+
+    /// Returns the type of the variable with the given name, looking into the scopes
+    /// of the blocks being resolved first.
+    fn variable_type(&self, name: &str, scopes: &Scopes) -> Option<Type> {
+        scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+            .or_else(|| self.symbol_table.get(name).and_then(Symbol::ty).cloned())
+    }
+
+    /// Returns whether the value of the integer expression `expr` is of an unsigned type.
+    fn is_unsigned(
+        &self,
+        expr: &dyn ExprASTNode,
+        expected: Option<&Type>,
+        scopes: &mut Scopes,
+    ) -> bool {
+        let node = expr.as_any();
+        if let Some(literal) = node.downcast_ref::<LiteralASTNode<i128>>() {
+            return literal.infer_type(expected.cloned()).is_unsigned();
+        }
+        if let Some(layout_of) = node.downcast_ref::<LayoutOfASTNode>() {
+            return layout_of.infer_type(expected.cloned()).is_unsigned();
+        }
+        if let Some(path) = node.downcast_ref::<PathASTNode>() {
+            return self
+                .variable_type(path.path(), scopes)
+                .is_some_and(|ty| ty.is_unsigned());
+        }
+        if let Some(call) = node.downcast_ref::<FunCallASTNode>() {
+            if let Some(signature) = self.functions.get(call.path()) {
+                return signature.return_type().is_unsigned();
+            }
+            // A built-in function returns the type of its first argument
+            return Builtin::from_name(call.path()).is_some()
+                && call
+                    .args()
+                    .first()
+                    .is_some_and(|arg| self.is_unsigned(arg.as_ref(), expected, scopes));
+        }
+        if let Some(cast) = node.downcast_ref::<TypeCastASTNode>() {
+            return cast.ty().is_unsigned();
+        }
+        if let Some(grouped) = node.downcast_ref::<GroupedExprASTNode>() {
+            return self.is_unsigned(grouped.expr(), expected, scopes);
+        }
+        if let Some(tuple_index) = node.downcast_ref::<TupleIndexASTNode>() {
+            let index = tuple_index.index() as usize;
+            let mut tuple = tuple_index.tuple();
+            while let Some(grouped) = tuple.as_any().downcast_ref::<GroupedExprASTNode>() {
+                tuple = grouped.expr();
+            }
+            // The elements of a tuple expression are not expected to be of any type
+            if let Some(tuple) = tuple.as_any().downcast_ref::<TupleASTNode>() {
+                return tuple
+                    .elements()
+                    .get(index)
+                    .is_some_and(|element| self.is_unsigned(element.as_ref(), None, scopes));
+            }
+            return self
+                .declared_type(tuple, scopes)
+                .and_then(|ty| ty.tuple_elements()?.get(index).cloned())
+                .is_some_and(|ty| ty.is_unsigned());
+        }
+        // Both operands are of the same type
+        if let Some(arith) = node.downcast_ref::<ArithExprASTNode>() {
+            return self.is_unsigned(arith.lhs(), expected, scopes);
+        }
+        if let Some(neg) = node.downcast_ref::<NegExprASTNode>() {
+            return self.is_unsigned(neg.expr(), expected, scopes);
+        }
+        if let Some(deref) = node.downcast_ref::<DerefExprASTNode>() {
+            return self
+                .declared_type(deref, scopes)
+                .is_some_and(|ty| ty.is_unsigned());
+        }
+        if let Some(block) = node.downcast_ref::<BlockASTNode>() {
+            return self.is_block_unsigned(block, expected, scopes);
+        }
+        if let Some(unsafe_block) = node.downcast_ref::<UnsafeBlockASTNode>() {
+            return self.is_block_unsigned(unsafe_block.block(), expected, scopes);
+        }
+        if let Some(if_node) = node.downcast_ref::<IfASTNode>() {
+            // If the `then` branch diverges (e.g. it returns), the `else` branch determines the type
+            if if_node.then_block().return_expr().is_some() {
+                return self.is_block_unsigned(if_node.then_block(), expected, scopes);
+            }
+            return match if_node.else_node().map(ElseASTNode::branch) {
+                None => false,
+                Some(ElseExpr::Else(block)) => self.is_block_unsigned(block, expected, scopes),
+                Some(ElseExpr::ElseIf(else_if)) => {
+                    self.is_unsigned(else_if.as_ref(), expected, scopes)
+                }
+            };
+        }
+        false
+    }
+
+    /// Returns whether the value of the given block is of an unsigned integer type.
+    fn is_block_unsigned(
+        &self,
+        block: &BlockASTNode,
+        expected: Option<&Type>,
+        scopes: &mut Scopes,
+    ) -> bool {
+        let Some(expr) = block.return_expr() else {
+            return false;
+        };
+        scopes.push(block_scope(block));
+        let unsigned = self.is_unsigned(expr, expected, scopes);
+        scopes.pop();
+        unsigned
+    }
+}
+
+/// Returns the types of the variables declared in the given block,
+/// which the value of the block can refer to.
+fn block_scope(block: &BlockASTNode) -> HashMap<Rc<str>, Type> {
+    block
+        .statements()
+        .iter()
+        .filter_map(|statement| statement.as_any().downcast_ref::<LetASTNode>())
+        .filter_map(|stmt| {
+            let name = stmt.decl().try_as_assignee()?.pattern()?;
+            Some((name, stmt.ty()?))
+        })
+        .collect()
+}
@@ -0,0 +1,1503 @@
+//! A module containing the Cranelift code generator, an alternative to the LLVM one
+//! (see the [`codegen`](crate::codegen) module).
+//!
+//! Cranelift compiles much faster than LLVM, and it is a plain Rust dependency, so it doesn't
+//! require LLVM to be installed. On the other hand, the generated code is not optimized.
+//!
+//! The code generator builds the [core constructs](CoreExpr) the AST is lowered to,
+//! like the LLVM one (so e.g. the `for` and do-while loops are built in their desugared form,
+//! see [`ForASTNode::desugared`]), with the same semantics (e.g. the [evaluation order](crate::ast::ExprASTNode), or the integer division
+//! trapping on the division by zero and on the overflow). It supports a smaller subset
+//! of μRust than the LLVM one, as it rejects:
+//! - the value of an `if` with an `else` branch (it can still be used as a statement),
+//! - the type casts (`as`),
+//! - the tuples and the references (`&` and `*`, the string slices `&str` are supported),
+//! - the imports from other crates, whose functions cannot be declared.
+//!
+//! The other differences are:
+//! - the booleans are represented as `i8` (`0` or `1`) instead of `i1`,
+//! - the unsuffixed number literals only adopt the type expected from them directly by a let
+//!   statement, an assignment or the left operand of a binary operator (e.g. in `let x: u8 = 1;`
+//!   or `x + 1`), but not e.g. through a block or by a parameter, and the layout queries
+//!   are always `i32`,
+//! - the unit values (including the unit parameters) are not represented at all,
+//! - the options of the LLVM code generation (e.g. the coverage or the sanitizer)
+//!   and the `#[unroll]` hints are not supported,
+//! - no warnings are reported (they are the same as the ones of the LLVM code generation).
+//!
+//! The object code is emitted for the host machine, so it can be [linked](crate::link::Linker)
+//! in the same way as the one generated by LLVM (see [`CraneliftBackend`]).
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
+use cranelift_codegen::ir::{self, types, AbiParam, Block, InstBuilder, MemFlags, TrapCode, Value};
+use cranelift_codegen::isa::OwnedTargetIsa;
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_codegen::Context;
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use cranelift_module::{DataDescription, DataId, FuncId, Linkage, Module, ModuleError};
+use cranelift_object::{ObjectBuilder, ObjectModule};
+
+use crate::ast::error::SemanticError;
+use crate::ast::*;
+use crate::backend::{Backend, BackendError};
+use crate::builtin::Builtin;
+use crate::statics;
+use crate::target::TargetLayout;
+use crate::token::Span;
+
+/// A result of the Cranelift code generation.
+pub type Result<T> = std::result::Result<T, CraneliftError>;
+
+/// The type of error that can occur during the Cranelift code generation.
+#[derive(Debug)]
+pub enum CraneliftError {
+    /// A [`SemanticError`] encountered during code generation.
+    SemanticError(SemanticError),
+    /// An error when a symbol that is not declared is encountered.
+    MissingSymbol {
+        /// The name of the missing symbol.
+        symbol: Box<str>,
+        /// The span where the error occurred.
+        span: Span,
+    },
+    /// An error when an underscore is used as a value.
+    UnderscoreUsedAsValue {
+        /// The span where the error occurred.
+        span: Span,
+    },
+    /// An error when an operation doesn't support the type of its operands.
+    UnsupportedType {
+        /// The description of the error.
+        message: Box<str>,
+        /// The span where the error occurred.
+        span: Span,
+    },
+    /// An error when an expression is not supported by the code generation (yet).
+    UnsupportedExpression {
+        /// The description of the error.
+        message: Box<str>,
+        /// The span of the expression.
+        span: Span,
+    },
+    /// An error when an operation requires a different type than the one provided.
+    TypeMismatch {
+        /// The expected type.
+        expected: Type,
+        /// The actual type.
+        actual: Type,
+        /// The span where the error occurred.
+        span: Span,
+    },
+    /// An error when the initializer of a static item cannot be evaluated at compile time.
+    NonConstantInitializer {
+        /// The span of the initializer.
+        span: Span,
+    },
+    /// An error when the end of a function that doesn't return unit can be reached
+    /// without a `return`.
+    MissingReturn {
+        /// The span of the body of the function.
+        span: Span,
+    },
+    /// An error returned by the Cranelift module (e.g. a symbol declared twice).
+    ///
+    /// It is boxed, as it is much larger than the other variants.
+    Module(Box<ModuleError>),
+    /// An error when the host target cannot be initialized or the object code cannot be emitted.
+    TargetError(Box<str>),
+}
+
+impl fmt::Display for CraneliftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CraneliftError::SemanticError(err) => write!(f, "{}", err),
+            CraneliftError::MissingSymbol { symbol, span } => {
+                write!(f, "Cannot find `{}` at {}", symbol, span)
+            }
+            CraneliftError::UnderscoreUsedAsValue { span } => {
+                write!(f, "`_` cannot be used as a value at {}", span)
+            }
+            CraneliftError::UnsupportedType { message, span }
+            | CraneliftError::UnsupportedExpression { message, span } => {
+                write!(f, "{} at {}", message, span)
+            }
+            CraneliftError::TypeMismatch {
+                expected,
+                actual,
+                span,
+            } => write!(
+                f,
+                "Expected a value of type `{}`, found `{}` at {}",
+                expected, actual, span
+            ),
+            CraneliftError::NonConstantInitializer { span } => write!(
+                f,
+                "The initializer of a static has to be a constant at {}",
+                span
+            ),
+            CraneliftError::MissingReturn { span } => {
+                write!(f, "The function has to return a value at {}", span)
+            }
+            CraneliftError::Module(err) => write!(f, "{}", err),
+            CraneliftError::TargetError(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for CraneliftError {}
+
+impl From<SemanticError> for CraneliftError {
+    fn from(err: SemanticError) -> CraneliftError {
+        CraneliftError::SemanticError(err)
+    }
+}
+
+impl From<ModuleError> for CraneliftError {
+    fn from(err: ModuleError) -> CraneliftError {
+        CraneliftError::Module(Box::new(err))
+    }
+}
+
+/// Compiles the given crate with Cranelift, and returns the contents of the object file
+/// for the host machine.
+///
+/// # Errors
+///
+/// If the crate cannot be compiled, or the object code cannot be emitted,
+/// a [`CraneliftError`] is returned.
+pub fn compile(crt: &Crate) -> Result<Vec<u8>> {
+    let mut generator = Generator::new(crt.root().name())?;
+    generator.declare_items(crt.root())?;
+    for func in crt.functions() {
+        generator.define_function(func)?;
+    }
+    generator.finish()
+}
+
+/// The Cranelift [backend](Backend), emitting the object code with [`compile`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CraneliftBackend;
+
+impl Backend for CraneliftBackend {
+    fn name(&self) -> &'static str {
+        "cranelift"
+    }
+
+    fn emit_object(&self, crt: &Crate) -> std::result::Result<Vec<u8>, BackendError> {
+        Ok(compile(crt)?)
+    }
+}
+
+/// Creates the target ISA of the host, using the position-independent code.
+fn host_isa() -> Result<OwnedTargetIsa> {
+    let target_error = |message: String| CraneliftError::TargetError(message.into());
+
+    let mut flags = settings::builder();
+    flags
+        .set("is_pic", "true")
+        .map_err(|err| target_error(err.to_string()))?;
+    cranelift_native::builder()
+        .map_err(|message| target_error(message.to_owned()))?
+        .finish(settings::Flags::new(flags))
+        .map_err(|err| target_error(err.to_string()))
+}
+
+/// Returns the Cranelift type representing the values of the given type,
+/// or `None` for the unit type, whose values are not represented.
+fn clif_type(ty: &Type) -> Option<ir::Type> {
+    match ty {
+        Type::I8 | Type::U8 => Some(types::I8),
+        Type::I16 | Type::U16 => Some(types::I16),
+        Type::I32 | Type::U32 => Some(types::I32),
+        Type::I64 | Type::U64 => Some(types::I64),
+        Type::F32 => Some(types::F32),
+        Type::F64 => Some(types::F64),
+        Type::Bool => Some(types::I8),
+        Type::Unit => None,
+        // The code is only generated for the host
+        Type::Str if cfg!(target_pointer_width = "32") => Some(types::I32),
+        Type::Str => Some(types::I64),
+        Type::Tuple(_) => unreachable!("The tuples are rejected before the code generation"),
+        Type::Ref { .. } => {
+            unreachable!("The references are rejected before the code generation")
+        }
+    }
+}
+
+/// Returns the immediate operand representing the given integer of the given type,
+/// i.e. the integer zero-extended from the width of the type.
+fn int_immediate(ty: ir::Type, value: i64) -> i64 {
+    match ty.bits() {
+        64 => value,
+        bits => value & ((1 << bits) - 1),
+    }
+}
+
+/// Returns the bytes of the given constant of type `ty`, as it is stored in memory on the host.
+fn const_bytes(value: ConstValue, ty: &Type) -> Vec<u8> {
+    match value {
+        ConstValue::Int(i) => match ty.bit_width() {
+            Some(8) => (i as i8).to_ne_bytes().to_vec(),
+            Some(16) => (i as i16).to_ne_bytes().to_vec(),
+            Some(32) => (i as i32).to_ne_bytes().to_vec(),
+            _ => (i as i64).to_ne_bytes().to_vec(),
+        },
+        ConstValue::Float(x) if *ty == Type::F32 => (x as f32).to_ne_bytes().to_vec(),
+        ConstValue::Float(x) => x.to_ne_bytes().to_vec(),
+        ConstValue::Bool(b) => vec![b as u8],
+        ConstValue::Unit => Vec::new(),
+    }
+}
+
+/// A declared function.
+#[derive(Debug, Clone)]
+struct FunctionSymbol {
+    id: FuncId,
+    params: Vec<Type>,
+    return_type: Type,
+}
+
+/// A declared static item.
+#[derive(Debug, Clone)]
+struct StaticSymbol {
+    id: DataId,
+    ty: Type,
+}
+
+/// The state of the code generation of a crate.
+struct Generator {
+    module: ObjectModule,
+    functions: HashMap<Rc<str>, FunctionSymbol>,
+    statics: HashMap<Rc<str>, StaticSymbol>,
+    fmod: Option<FuncId>,
+    context: Context,
+    builder_context: FunctionBuilderContext,
+}
+
+impl Generator {
+    /// Creates the code generator of the object file with the given name.
+    fn new(name: &str) -> Result<Generator> {
+        let builder =
+            ObjectBuilder::new(host_isa()?, name, cranelift_module::default_libcall_names())?;
+        let module = ObjectModule::new(builder);
+        let context = module.make_context();
+        Ok(Generator {
+            module,
+            functions: HashMap::new(),
+            statics: HashMap::new(),
+            fmod: None,
+            context,
+            builder_context: FunctionBuilderContext::new(),
+        })
+    }
+
+    /// Declares all the items of the crate, and defines the statics.
+    fn declare_items(&mut self, root: &CrateASTNode) -> Result<()> {
+        // The other crates are not available, so the imported functions cannot be declared
+        if let Some(import) = root.imports().first() {
+            return Err(CraneliftError::MissingSymbol {
+                symbol: import.name().into(),
+                span: import.span(),
+            });
+        }
+        if let Some(span) = find_tuple(root) {
+            return Err(CraneliftError::UnsupportedType {
+                message: "Tuples are not supported by the Cranelift backend".into(),
+                span,
+            });
+        }
+        if let Some(span) = find_reference(root) {
+            return Err(CraneliftError::UnsupportedType {
+                message: "References are not supported by the Cranelift backend".into(),
+                span,
+            });
+        }
+
+        for item in root.items() {
+            match item {
+                ItemASTNode::Func(func) => {
+                    let linkage = if func.is_exported() {
+                        Linkage::Export
+                    } else {
+                        Linkage::Local
+                    };
+                    self.declare_function(func.proto(), linkage)?;
+                }
+                ItemASTNode::Static(stat) => {
+                    let linkage = if stat.visibility().is_public() {
+                        Linkage::Export
+                    } else {
+                        Linkage::Local
+                    };
+                    self.declare_static(stat, linkage)?;
+                }
+                ItemASTNode::Extern(ext) => {
+                    for item in ext.items() {
+                        match item {
+                            ExternItem::Func(proto) => {
+                                self.declare_function(proto, Linkage::Import)?
+                            }
+                            ExternItem::Static(stat) => {
+                                self.declare_static(stat, Linkage::Import)?
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let statics: Vec<_> = root.statics().collect();
+        let values = statics::evaluate_statics(&statics, TargetLayout::host())?;
+        for stat in statics {
+            self.define_static(stat, &values)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the signature of the given function prototype.
+    fn signature(&self, proto: &FuncProtoASTNode) -> ir::Signature {
+        let mut signature = self.module.make_signature();
+        let param = |ty: ir::Type| match ty {
+            // The booleans are zero-extended, like `bool` in C
+            types::I8 => AbiParam::new(ty).uext(),
+            _ => AbiParam::new(ty),
+        };
+        signature.params.extend(
+            proto
+                .param_types()
+                .filter_map(|ty| clif_type(&ty))
+                .map(param),
+        );
+        signature
+            .returns
+            .extend(clif_type(&proto.return_type().ty()).map(param));
+        signature
+    }
+
+    /// Declares the function with the given prototype and linkage.
+    fn declare_function(&mut self, proto: &FuncProtoASTNode, linkage: Linkage) -> Result<()> {
+        let signature = self.signature(proto);
+        let id = self
+            .module
+            .declare_function(proto.name(), linkage, &signature)?;
+        let symbol = FunctionSymbol {
+            id,
+            params: proto.param_types().collect(),
+            return_type: proto.return_type().ty(),
+        };
+        self.functions.insert(proto.name_owned(), symbol);
+        Ok(())
+    }
+
+    /// Declares the given static item with the given linkage.
+    fn declare_static(&mut self, stat: &StaticASTNode, linkage: Linkage) -> Result<()> {
+        // The statics are writable, like the LLVM globals generated for them
+        let id = self
+            .module
+            .declare_data(stat.name(), linkage, true, false)?;
+        let symbol = StaticSymbol {
+            id,
+            ty: stat.ty().ty(),
+        };
+        self.statics.insert(stat.name_owned(), symbol);
+        Ok(())
+    }
+
+    /// Defines the initial value of the given static item, which can refer
+    /// to the `values` of the other statics.
+    fn define_static(&mut self, stat: &StaticASTNode, values: &ConstEnv) -> Result<()> {
+        let ty = stat.ty().ty();
+        let mut description = DataDescription::new();
+        match stat.value() {
+            // A static without an initializer (which the parser doesn't allow) is zero-initialized
+            None => {
+                let size = clif_type(&ty).map_or(0, |ty| ty.bytes() as usize);
+                description.define_zeroinit(size);
+            }
+            Some(value) => {
+                let constant = value
+                    .const_eval_in(values)
+                    .ok_or(CraneliftError::NonConstantInitializer { span: value.span() })?;
+                if !constant.is_of_type(&ty) {
+                    return Err(CraneliftError::TypeMismatch {
+                        expected: ty,
+                        actual: constant.ty(),
+                        span: value.span(),
+                    });
+                }
+                if let ConstValue::Int(i) = constant {
+                    if ty.int_range().is_some_and(|range| !range.contains(&i)) {
+                        return Err(SemanticError::IntOutOfRange {
+                            value: i,
+                            ty,
+                            span: value.span(),
+                        }
+                        .into());
+                    }
+                }
+                description.define(const_bytes(constant, &ty).into_boxed_slice());
+            }
+        }
+        self.module
+            .define_data(self.statics[stat.name()].id, &description)?;
+        Ok(())
+    }
+
+    /// Generates the body of the given function.
+    fn define_function(&mut self, func: &FuncASTNode) -> Result<()> {
+        let symbol = self.functions[func.proto().name()].clone();
+        self.context.func.signature = self.signature(func.proto());
+
+        let builder = FunctionBuilder::new(&mut self.context.func, &mut self.builder_context);
+        let mut state = FunctionState {
+            builder,
+            module: &mut self.module,
+            functions: &self.functions,
+            statics: &self.statics,
+            fmod: &mut self.fmod,
+            scopes: Vec::new(),
+            variables: 0,
+            return_type: symbol.return_type,
+            unreachable: false,
+            expected_type: None,
+        };
+        state.build_function(func)?;
+        state.builder.finalize();
+
+        self.module.define_function(symbol.id, &mut self.context)?;
+        self.module.clear_context(&mut self.context);
+        Ok(())
+    }
+
+    /// Emits the object code of the generated module.
+    fn finish(self) -> Result<Vec<u8>> {
+        self.module
+            .finish()
+            .emit()
+            .map_err(|err| CraneliftError::TargetError(err.to_string().into()))
+    }
+}
+
+/// A value of an expression, together with its type.
+#[derive(Debug, Clone)]
+struct TypedValue {
+    /// The value, which is `None` for the unit values.
+    value: Option<Value>,
+    ty: Type,
+}
+
+impl TypedValue {
+    fn new(value: Value, ty: Type) -> TypedValue {
+        TypedValue {
+            value: Some(value),
+            ty,
+        }
+    }
+
+    fn unit() -> TypedValue {
+        TypedValue {
+            value: None,
+            ty: Type::Unit,
+        }
+    }
+}
+
+/// A local variable (or a parameter).
+#[derive(Debug, Clone)]
+struct Local {
+    /// The variable, which is `None` for the unit variables.
+    variable: Option<Variable>,
+    ty: Type,
+}
+
+/// The state of the code generation of a single function.
+struct FunctionState<'a> {
+    builder: FunctionBuilder<'a>,
+    module: &'a mut ObjectModule,
+    functions: &'a HashMap<Rc<str>, FunctionSymbol>,
+    statics: &'a HashMap<Rc<str>, StaticSymbol>,
+    fmod: &'a mut Option<FuncId>,
+    scopes: Vec<HashMap<Rc<str>, Local>>,
+    variables: u32,
+    return_type: Type,
+    /// Whether the current block can't be reached (e.g. it follows a `return`).
+    unreachable: bool,
+    /// The type expected from the expression being generated by
+    /// [`build_expecting`](FunctionState::build_expecting), if there is one.
+    expected_type: Option<Type>,
+}
+
+impl FunctionState<'_> {
+    //#region Control flow
+    fn build_function(&mut self, func: &FuncASTNode) -> Result<()> {
+        let entry = self.builder.create_block();
+        self.builder.append_block_params_for_function_params(entry);
+        self.builder.switch_to_block(entry);
+        self.builder.seal_block(entry);
+
+        self.scopes.push(HashMap::new());
+        let mut params = self.builder.block_params(entry).to_vec().into_iter();
+        for param in func.proto().get_param_iter() {
+            let ty = param.ty().ty();
+            let value = clif_type(&ty).and_then(|_| params.next());
+            if let Some(name) = param.assignee()?.pattern() {
+                let local = self.declare_local(name, ty);
+                if let (Some(variable), Some(value)) = (local.variable, value) {
+                    self.builder.def_var(variable, value);
+                }
+            }
+        }
+
+        self.build_block(func.body(), true)?;
+        if self.unreachable {
+            // The dead block after the last `return` still has to be terminated
+            self.builder.ins().trap(TrapCode::UnreachableCodeReached);
+        } else if self.return_type == Type::Unit {
+            // A function returning unit may omit the final `return`, so it is returned implicitly
+            self.builder.ins().return_(&[]);
+        } else {
+            return Err(CraneliftError::MissingReturn {
+                span: func.body().span(),
+            });
+        }
+        self.scopes.pop();
+        Ok(())
+    }
+
+    /// Continues the generation in the given block.
+    ///
+    /// If the current block is unreachable and not terminated, it is terminated with a trap.
+    fn switch_to(&mut self, block: Block) {
+        if self.unreachable && !self.is_terminated() {
+            self.builder.ins().trap(TrapCode::UnreachableCodeReached);
+        }
+        self.builder.switch_to_block(block);
+        self.unreachable = false;
+    }
+
+    /// Returns `true` if the current block already ends with a terminator (e.g. a `return`).
+    fn is_terminated(&self) -> bool {
+        let func = &self.builder.func;
+        self.builder
+            .current_block()
+            .and_then(|block| func.layout.last_inst(block))
+            .is_some_and(|inst| func.dfg.insts[inst].opcode().is_terminator())
+    }
+
+    /// Continues the generation in a new unreachable block,
+    /// after the current one has been terminated (e.g. by a `return`).
+    fn switch_to_unreachable(&mut self) {
+        let block = self.builder.create_block();
+        self.builder.seal_block(block);
+        self.switch_to(block);
+        self.unreachable = true;
+    }
+
+    /// Jumps to the given block, unless the current block is unreachable.
+    fn jump_if_reachable(&mut self, block: Block) {
+        if !self.unreachable {
+            self.builder.ins().jump(block, &[]);
+        }
+    }
+    //#endregion
+
+    //#region Variables
+    fn declare_local(&mut self, name: Rc<str>, ty: Type) -> Local {
+        let variable = clif_type(&ty).map(|clif_ty| {
+            let variable = Variable::from_u32(self.variables);
+            self.variables += 1;
+            self.builder.declare_var(variable, clif_ty);
+            variable
+        });
+        let local = Local { variable, ty };
+        self.scopes
+            .last_mut()
+            .expect("A local has to be declared in a scope")
+            .insert(name, local.clone());
+        local
+    }
+
+    fn find_local(&self, name: &str) -> Option<Local> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+    }
+
+    /// Returns the address of the static item with the given id.
+    fn static_address(&mut self, id: DataId) -> Value {
+        let global = self.module.declare_data_in_func(id, self.builder.func);
+        let pointer = self.module.target_config().pointer_type();
+        self.builder.ins().symbol_value(pointer, global)
+    }
+
+    /// Returns the given integer constant (already range-checked) of the integer type `ty`.
+    fn build_int_const(&mut self, value: i128, ty: Type) -> TypedValue {
+        let clif = clif_type(&ty).expect("Integers are represented");
+        let value = self
+            .builder
+            .ins()
+            .iconst(clif, int_immediate(clif, value as i64));
+        TypedValue::new(value, ty)
+    }
+
+    /// Returns the address of a new data object holding the given string
+    /// (null-terminated, so that it can be passed to the C functions).
+    fn build_str(&mut self, value: &str) -> Result<TypedValue> {
+        let mut bytes = Vec::with_capacity(value.len() + 1);
+        bytes.extend_from_slice(value.as_bytes());
+        bytes.push(0);
+        let mut description = DataDescription::new();
+        description.define(bytes.into_boxed_slice());
+        let id = self.module.declare_anonymous_data(false, false)?;
+        self.module.define_data(id, &description)?;
+
+        let global = self.module.declare_data_in_func(id, self.builder.func);
+        let pointer = self.module.target_config().pointer_type();
+        let address = self.builder.ins().symbol_value(pointer, global);
+        Ok(TypedValue::new(address, Type::Str))
+    }
+
+    fn missing_symbol(name: &str, span: Span) -> CraneliftError {
+        CraneliftError::MissingSymbol {
+            symbol: name.into(),
+            span,
+        }
+    }
+    //#endregion
+
+    //#region Statements
+    fn build_block(&mut self, block: &BlockASTNode, is_stmt: bool) -> Result<TypedValue> {
+        self.scopes.push(HashMap::new());
+        let result = self.build_block_contents(block, is_stmt);
+        self.scopes.pop();
+        result
+    }
+
+    fn build_block_contents(&mut self, block: &BlockASTNode, is_stmt: bool) -> Result<TypedValue> {
+        for statement in block.statements() {
+            // The unreachable statements are not generated (like in the LLVM code generation)
+            if self.unreachable {
+                return Ok(TypedValue::unit());
+            }
+            self.build_statement(statement.as_ref())?;
+        }
+        match block.return_expr() {
+            Some(_) if self.unreachable => Ok(TypedValue::unit()),
+            Some(expr) if is_stmt => self.build_expr_stmt(expr).map(|_| TypedValue::unit()),
+            Some(expr) => self.build_expr(expr),
+            None => Ok(TypedValue::unit()),
+        }
+    }
+
+    fn build_statement(&mut self, statement: &dyn StatementASTNode) -> Result<()> {
+        match statement.lower() {
+            CoreStmt::Expr(stmt) => self.build_expr_stmt(stmt.expr()),
+            CoreStmt::Let(stmt) => self.build_let(stmt),
+        }
+    }
+
+    fn build_let(&mut self, stmt: &LetASTNode) -> Result<()> {
+        // The value is generated before the variable is declared,
+        // so that it can refer to the variable it shadows
+        let value = stmt
+            .value()
+            .map(|v| self.build_expecting(v, stmt.ty()))
+            .transpose()?;
+        // The value diverges (e.g. it is a `return`), so the variable is never declared
+        if self.unreachable {
+            return Ok(());
+        }
+
+        let pattern = stmt
+            .decl()
+            .try_as_assignee()
+            .ok_or(SemanticError::WrongExpressionKind {
+                message: "Expected an assignee expression",
+                span: stmt.span(),
+            })?
+            .pattern();
+        let ty = match (stmt.ty(), &value) {
+            (Some(ty), Some(value)) if value.ty != ty => {
+                return Err(CraneliftError::TypeMismatch {
+                    expected: ty,
+                    actual: value.ty.clone(),
+                    span: stmt.value().expect("The value was generated").span(),
+                });
+            }
+            (Some(ty), _) => ty,
+            (None, Some(value)) => value.ty.clone(),
+            (None, None) => unreachable!("A let with an inferred type has a value"),
+        };
+
+        if let Some(name) = pattern {
+            let local = self.declare_local(name, ty);
+            if let (Some(variable), Some(value)) = (local.variable, value.and_then(|v| v.value)) {
+                self.builder.def_var(variable, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generates the given expression in a statement position, i.e. discarding its value.
+    ///
+    /// The expressions with a block don't produce a value in a statement position,
+    /// so e.g. the branches of an `if` don't have to be of the same type.
+    fn build_expr_stmt(&mut self, expr: &dyn ExprASTNode) -> Result<()> {
+        match expr.lower() {
+            CoreExpr::Block(block) => self.build_block(block, true).map(|_| ()),
+            CoreExpr::UnsafeBlock(unsafe_block) => {
+                self.build_block(unsafe_block.block(), true).map(|_| ())
+            }
+            CoreExpr::If(if_node) => self.build_if_stmt(if_node),
+            _ => self.build_expr(expr).map(|_| ()),
+        }
+    }
+    //#endregion
+
+    //#region Expressions
+    /// Generates the given expression whose type is expected (e.g. by a type annotation)
+    /// to be `ty`, which the unsuffixed number literals adopt (see [`LiteralASTNode::infer_type`]).
+    ///
+    /// Unlike in the LLVM code generation, the expected type only applies to the expression
+    /// itself (or the grouped or negated one), not e.g. to the value of a block.
+    fn build_expecting(&mut self, expr: &dyn ExprASTNode, ty: Option<Type>) -> Result<TypedValue> {
+        self.expected_type = ty;
+        self.build_expr(expr)
+    }
+
+    fn build_expr(&mut self, expr: &dyn ExprASTNode) -> Result<TypedValue> {
+        let expected = self.expected_type.take();
+        match expr.lower() {
+            CoreExpr::Int(literal) => {
+                let ty = literal.infer_type(expected);
+                let value = literal.check_range(&ty, false)?;
+                Ok(self.build_int_const(value, ty))
+            }
+            CoreExpr::LayoutOf(layout_of) => {
+                // The code is only generated for the host
+                let Some(ConstValue::Int(value)) = layout_of.const_eval() else {
+                    unreachable!("Layout queries evaluate to integers");
+                };
+                let value = self.builder.ins().iconst(types::I32, value as i64);
+                Ok(TypedValue::new(value, Type::I32))
+            }
+            CoreExpr::Float(literal) => {
+                let ty = literal.infer_type(expected);
+                let value = match ty {
+                    Type::F32 => self.builder.ins().f32const(*literal.value() as f32),
+                    _ => self.builder.ins().f64const(*literal.value()),
+                };
+                Ok(TypedValue::new(value, ty))
+            }
+            CoreExpr::Bool(literal) => {
+                let value = self
+                    .builder
+                    .ins()
+                    .iconst(types::I8, *literal.value() as i64);
+                Ok(TypedValue::new(value, Type::Bool))
+            }
+            CoreExpr::Unit(_) => Ok(TypedValue::unit()),
+            CoreExpr::Str(literal) => self.build_str(literal.value()),
+            CoreExpr::Path(path) => self.build_path(path),
+            CoreExpr::Block(block) => self.build_block(block, false),
+            CoreExpr::UnsafeBlock(unsafe_block) => self.build_block(unsafe_block.block(), false),
+            CoreExpr::Grouped(grouped) => self.build_expecting(grouped.expr(), expected),
+            CoreExpr::FunCall(call) => self.build_call(call),
+            CoreExpr::Assign(assign) => self.build_assign(assign),
+            CoreExpr::Arith(arith) => self.build_arith(arith),
+            CoreExpr::Comp(comp) => self.build_comp(comp),
+            CoreExpr::LazyBool(lazy_bool) => self.build_lazy_bool(lazy_bool),
+            CoreExpr::Neg(neg) => self.build_neg(neg, expected),
+            CoreExpr::If(if_node) => self.build_if(if_node),
+            CoreExpr::While(while_node) => self.build_while(while_node),
+            CoreExpr::InfLoop(inf_loop) => self.build_inf_loop(inf_loop.body()),
+            CoreExpr::Return(ret) => self.build_return(ret),
+            // Underscore cannot be used as a value; it should only be used as a pattern.
+            CoreExpr::Underscore(_) => {
+                Err(CraneliftError::UnderscoreUsedAsValue { span: expr.span() })
+            }
+            CoreExpr::Cast(_) => Err(CraneliftError::UnsupportedExpression {
+                message: "Type casts are not supported".into(),
+                span: expr.span(),
+            }),
+            // They are rejected before the code generation (see `declare_items`)
+            CoreExpr::Tuple(_) | CoreExpr::TupleIndex(_) => {
+                unreachable!("Tuples are not supported")
+            }
+            CoreExpr::Borrow(_) | CoreExpr::Deref(_) => {
+                unreachable!("References are not supported")
+            }
+        }
+    }
+
+    /// Generates the given expression, and checks that it is a boolean value.
+    fn build_bool(&mut self, expr: &dyn ExprASTNode) -> Result<Value> {
+        let value = self.build_expr(expr)?;
+        match value.value {
+            Some(v) if value.ty == Type::Bool => Ok(v),
+            _ => Err(CraneliftError::TypeMismatch {
+                expected: Type::Bool,
+                actual: value.ty,
+                span: expr.span(),
+            }),
+        }
+    }
+
+    fn build_path(&mut self, path: &PathASTNode) -> Result<TypedValue> {
+        if let Some(local) = self.find_local(path.path()) {
+            return Ok(match local.variable {
+                Some(variable) => TypedValue::new(self.builder.use_var(variable), local.ty),
+                None => TypedValue::unit(),
+            });
+        }
+        let statics = self.statics;
+        let symbol = statics
+            .get(path.path())
+            .ok_or_else(|| Self::missing_symbol(path.path(), path.span()))?;
+        let Some(ty) = clif_type(&symbol.ty) else {
+            return Ok(TypedValue::unit());
+        };
+        let address = self.static_address(symbol.id);
+        let value = self.builder.ins().load(ty, MemFlags::new(), address, 0);
+        Ok(TypedValue::new(value, symbol.ty.clone()))
+    }
+
+    fn build_call(&mut self, call: &FunCallASTNode) -> Result<TypedValue> {
+        let functions = self.functions;
+        // The functions declared in the crate shadow the built-in ones
+        if !functions.contains_key(call.path()) {
+            if let Some(builtin) = Builtin::from_name(call.path()) {
+                return self.build_builtin_call(builtin, call);
+            }
+        }
+        let symbol = functions
+            .get(call.path())
+            .ok_or_else(|| Self::missing_symbol(call.path(), call.span()))?;
+        if symbol.params.len() != call.args().len() {
+            return Err(SemanticError::ArgumentCountMismatch {
+                name: call.path().into(),
+                expected: symbol.params.len(),
+                actual: call.args().len(),
+                span: call.span(),
+            }
+            .into());
+        }
+
+        // The arguments are evaluated from left to right (see the `ast::expr` module)
+        let mut args = Vec::with_capacity(call.args().len());
+        for (arg, expected) in call.args().iter().zip(&symbol.params) {
+            let value = self.build_expr(arg.as_ref())?;
+            if value.ty != *expected {
+                return Err(CraneliftError::TypeMismatch {
+                    expected: expected.clone(),
+                    actual: value.ty,
+                    span: arg.span(),
+                });
+            }
+            args.extend(value.value);
+        }
+
+        let func = self
+            .module
+            .declare_func_in_func(symbol.id, self.builder.func);
+        let inst = self.builder.ins().call(func, &args);
+        // A call to a function returning unit evaluates to the unit value
+        Ok(match self.builder.inst_results(inst).first() {
+            Some(value) => TypedValue::new(*value, symbol.return_type.clone()),
+            None => TypedValue::unit(),
+        })
+    }
+
+    /// Generates a call to the given [built-in function](crate::builtin),
+    /// whose arguments are all of the type of the first one.
+    fn build_builtin_call(
+        &mut self,
+        builtin: Builtin,
+        call: &FunCallASTNode,
+    ) -> Result<TypedValue> {
+        if builtin.param_count() != call.args().len() {
+            return Err(SemanticError::ArgumentCountMismatch {
+                name: call.path().into(),
+                expected: builtin.param_count(),
+                actual: call.args().len(),
+                span: call.span(),
+            }
+            .into());
+        }
+
+        // The arguments are evaluated from left to right (see the `ast::expr` module)
+        let mut args = Vec::with_capacity(call.args().len());
+        let mut ty = None;
+        for arg in call.args() {
+            let value = self.build_expr(arg.as_ref())?;
+            let expected = ty.get_or_insert_with(|| value.ty.clone()).clone();
+            if value.ty != expected {
+                return Err(CraneliftError::TypeMismatch {
+                    expected,
+                    actual: value.ty,
+                    span: arg.span(),
+                });
+            }
+            args.extend(value.value);
+        }
+        let ty = ty.expect("The built-in functions have parameters");
+        if !builtin.supports(&ty) {
+            return Err(CraneliftError::UnsupportedType {
+                message: format!("Function `{}` cannot be called with `{}`", builtin, ty).into(),
+                span: call.args()[0].span(),
+            });
+        }
+
+        let ins = self.builder.ins();
+        let value = match (builtin, &ty) {
+            (Builtin::Min | Builtin::Max, ty) if ty.is_float() => {
+                // If one of the operands is `NaN`, the other one is the result
+                let (a, b) = (args[0], args[1]);
+                let extremum = match builtin {
+                    Builtin::Min => ins.fmin(a, b),
+                    _ => ins.fmax(a, b),
+                };
+                let a_nan = self.builder.ins().fcmp(FloatCC::Unordered, a, a);
+                let b_nan = self.builder.ins().fcmp(FloatCC::Unordered, b, b);
+                let value = self.builder.ins().select(b_nan, a, extremum);
+                self.builder.ins().select(a_nan, b, value)
+            }
+            (Builtin::Min, ty) if ty.is_signed() => ins.smin(args[0], args[1]),
+            (Builtin::Max, ty) if ty.is_signed() => ins.smax(args[0], args[1]),
+            (Builtin::Min, _) => ins.umin(args[0], args[1]),
+            (Builtin::Max, _) => ins.umax(args[0], args[1]),
+            (Builtin::Abs, ty) if ty.is_float() => ins.fabs(args[0]),
+            (Builtin::Abs, _) => ins.iabs(args[0]),
+            (Builtin::Sqrt, _) => ins.sqrt(args[0]),
+        };
+        Ok(TypedValue::new(value, ty))
+    }
+
+    fn build_assign(&mut self, assign: &AssignASTNode) -> Result<TypedValue> {
+        let pattern = assign
+            .assignee()
+            .try_as_assignee()
+            .ok_or(SemanticError::WrongExpressionKind {
+                message: "Expected an assignee expression",
+                span: assign.span(),
+            })?
+            .pattern();
+        // The value is expected to be of the type of the assigned variable
+        let expected = pattern
+            .as_deref()
+            .and_then(|name| match self.find_local(name) {
+                Some(local) => Some(local.ty),
+                None => self.statics.get(name).map(|symbol| symbol.ty.clone()),
+            });
+        let value = self.build_expecting(assign.value(), expected)?;
+        let Some(name) = pattern else {
+            return Ok(TypedValue::unit());
+        };
+
+        let span = assign.assignee().span();
+        let (ty, local) = match self.find_local(&name) {
+            Some(local) => (local.ty.clone(), Some(local)),
+            None => {
+                let symbol = self
+                    .statics
+                    .get(&name)
+                    .ok_or_else(|| Self::missing_symbol(&name, span))?;
+                (symbol.ty.clone(), None)
+            }
+        };
+        if value.ty != ty {
+            return Err(CraneliftError::TypeMismatch {
+                expected: ty,
+                actual: value.ty,
+                span: assign.value().span(),
+            });
+        }
+
+        // The unit values are not stored at all
+        let Some(value) = value.value else {
+            return Ok(TypedValue::unit());
+        };
+        match local.and_then(|local| local.variable) {
+            Some(variable) => self.builder.def_var(variable, value),
+            None => {
+                let address = self.static_address(self.statics[&name].id);
+                self.builder.ins().store(MemFlags::new(), value, address, 0);
+            }
+        }
+        Ok(TypedValue::unit())
+    }
+
+    fn build_arith(&mut self, arith: &ArithExprASTNode) -> Result<TypedValue> {
+        // The operands are evaluated from left to right (see the `ast::expr` module)
+        let lhs = self.build_expr(arith.lhs())?;
+        let rhs = self.build_expecting(arith.rhs(), Some(lhs.ty.clone()))?;
+        let ty = self.check_operands(&lhs, &rhs, arith.rhs().span())?;
+        if ty == Type::Str {
+            return Err(CraneliftError::UnsupportedType {
+                message: "Cannot perform arithmetic operations on strings".into(),
+                span: arith.span(),
+            });
+        }
+        let (Some(lhs), Some(rhs)) = (lhs.value, rhs.value) else {
+            return Err(CraneliftError::UnsupportedType {
+                message: "Cannot perform arithmetic operations on unit type".into(),
+                span: arith.span(),
+            });
+        };
+
+        let ins = self.builder.ins();
+        let value = match (&ty, arith.operator()) {
+            (ty, ArithOperator::Add) if ty.is_integer() => ins.iadd(lhs, rhs),
+            (ty, ArithOperator::Sub) if ty.is_integer() => ins.isub(lhs, rhs),
+            (ty, ArithOperator::Mul) if ty.is_integer() => ins.imul(lhs, rhs),
+            (ty, op @ (ArithOperator::Div | ArithOperator::Rem)) if ty.is_integer() => {
+                self.build_division(op, ty, lhs, rhs)
+            }
+            (ty, ArithOperator::Add) if ty.is_float() => ins.fadd(lhs, rhs),
+            (ty, ArithOperator::Sub) if ty.is_float() => ins.fsub(lhs, rhs),
+            (ty, ArithOperator::Mul) if ty.is_float() => ins.fmul(lhs, rhs),
+            (ty, ArithOperator::Div) if ty.is_float() => ins.fdiv(lhs, rhs),
+            (ty, ArithOperator::Rem) if ty.is_float() => self.build_float_rem(ty, lhs, rhs)?,
+            (ty, _) if ty.is_float() => {
+                return Err(CraneliftError::UnsupportedType {
+                    message: "Bitwise operations are not supported on floating point operands"
+                        .into(),
+                    span: arith.span(),
+                });
+            }
+            // The remaining operands are integers or booleans
+            (_, ArithOperator::BitAnd) => ins.band(lhs, rhs),
+            (_, ArithOperator::BitOr) => ins.bor(lhs, rhs),
+            (_, ArithOperator::BitXor) => ins.bxor(lhs, rhs),
+            (_, _) => {
+                return Err(CraneliftError::UnsupportedType {
+                    message: "Only bitwise operations are supported on boolean operands".into(),
+                    span: arith.span(),
+                });
+            }
+        };
+        Ok(TypedValue::new(value, ty))
+    }
+
+    /// Checks that both operands of a binary operator are of the same type, and returns it.
+    fn check_operands(&self, lhs: &TypedValue, rhs: &TypedValue, rhs_span: Span) -> Result<Type> {
+        if lhs.ty != rhs.ty {
+            return Err(CraneliftError::TypeMismatch {
+                expected: lhs.ty.clone(),
+                actual: rhs.ty.clone(),
+                span: rhs_span,
+            });
+        }
+        Ok(lhs.ty.clone())
+    }
+
+    /// Generates the integer division (or remainder) of `lhs` by `rhs`.
+    ///
+    /// The division rounds towards zero (i.e. the remainder has the sign of `lhs`), as in Rust,
+    /// and it traps if `rhs` is zero or if the minimum of a signed type (e.g. `i32::MIN`)
+    /// is divided by `-1`.
+    fn build_division(&mut self, op: ArithOperator, ty: &Type, lhs: Value, rhs: Value) -> Value {
+        let signed = ty.is_signed();
+        if signed {
+            // The immediates of the narrow types are zero-extended
+            let clif = clif_type(ty).expect("Integers are represented");
+            let min = int_immediate(clif, 1 << (clif.bits() - 1));
+            let min = self.builder.ins().iconst(clif, min);
+            let minus_one = self.builder.ins().iconst(clif, int_immediate(clif, -1));
+            let is_min = self.builder.ins().icmp(IntCC::Equal, lhs, min);
+            let is_minus_one = self.builder.ins().icmp(IntCC::Equal, rhs, minus_one);
+            let overflow = self.builder.ins().band(is_min, is_minus_one);
+            self.builder
+                .ins()
+                .trapnz(overflow, TrapCode::IntegerOverflow);
+        }
+        self.builder
+            .ins()
+            .trapz(rhs, TrapCode::IntegerDivisionByZero);
+        match (op, signed) {
+            (ArithOperator::Div, true) => self.builder.ins().sdiv(lhs, rhs),
+            (ArithOperator::Div, false) => self.builder.ins().udiv(lhs, rhs),
+            (_, true) => self.builder.ins().srem(lhs, rhs),
+            (_, false) => self.builder.ins().urem(lhs, rhs),
+        }
+    }
+
+    /// Generates the floating-point remainder of type `ty`, i.e. a call to `fmod` from
+    /// the C library, which is what LLVM generates for its `frem` instruction.
+    fn build_float_rem(&mut self, ty: &Type, lhs: Value, rhs: Value) -> Result<Value> {
+        // The remainder is exact, so it is the same when computed for the promoted operands
+        if *ty == Type::F32 {
+            let lhs = self.builder.ins().fpromote(types::F64, lhs);
+            let rhs = self.builder.ins().fpromote(types::F64, rhs);
+            let rem = self.build_float_rem(&Type::F64, lhs, rhs)?;
+            return Ok(self.builder.ins().fdemote(types::F32, rem));
+        }
+
+        let id = match *self.fmod {
+            Some(id) => id,
+            None => {
+                let mut signature = self.module.make_signature();
+                signature.params.push(AbiParam::new(types::F64));
+                signature.params.push(AbiParam::new(types::F64));
+                signature.returns.push(AbiParam::new(types::F64));
+                let id = self
+                    .module
+                    .declare_function("fmod", Linkage::Import, &signature)?;
+                *self.fmod = Some(id);
+                id
+            }
+        };
+        let fmod = self.module.declare_func_in_func(id, self.builder.func);
+        let inst = self.builder.ins().call(fmod, &[lhs, rhs]);
+        Ok(self.builder.inst_results(inst)[0])
+    }
+
+    fn build_comp(&mut self, comp: &CompExprASTNode) -> Result<TypedValue> {
+        // The operands are evaluated from left to right (see the `ast::expr` module)
+        let lhs = self.build_expr(comp.lhs())?;
+        let rhs = self.build_expecting(comp.rhs(), Some(lhs.ty.clone()))?;
+        let ty = self.check_operands(&lhs, &rhs, comp.rhs().span())?;
+        if ty == Type::Str {
+            return Err(CraneliftError::UnsupportedType {
+                message: "Cannot compare strings".into(),
+                span: comp.span(),
+            });
+        }
+        let op = comp.operator();
+        let (Some(lhs), Some(rhs)) = (lhs.value, rhs.value) else {
+            // The unit values are always equal (the operands are still evaluated above)
+            let result = matches!(op, CompOperator::Eq | CompOperator::Ge | CompOperator::Le);
+            let value = self.builder.ins().iconst(types::I8, result as i64);
+            return Ok(TypedValue::new(value, Type::Bool));
+        };
+
+        let value = match &ty {
+            ty if ty.is_float() => {
+                // The comparisons are ordered (i.e. false if any operand is NaN), except for `!=`
+                let cond = match op {
+                    CompOperator::Eq => FloatCC::Equal,
+                    CompOperator::Ne => FloatCC::NotEqual,
+                    CompOperator::Gt => FloatCC::GreaterThan,
+                    CompOperator::Lt => FloatCC::LessThan,
+                    CompOperator::Ge => FloatCC::GreaterThanOrEqual,
+                    CompOperator::Le => FloatCC::LessThanOrEqual,
+                };
+                self.builder.ins().fcmp(cond, lhs, rhs)
+            }
+            _ => {
+                let signed = ty.is_signed();
+                let cond = match (op, signed) {
+                    (CompOperator::Eq, _) => IntCC::Equal,
+                    (CompOperator::Ne, _) => IntCC::NotEqual,
+                    (CompOperator::Gt, true) => IntCC::SignedGreaterThan,
+                    (CompOperator::Lt, true) => IntCC::SignedLessThan,
+                    (CompOperator::Ge, true) => IntCC::SignedGreaterThanOrEqual,
+                    (CompOperator::Le, true) => IntCC::SignedLessThanOrEqual,
+                    (CompOperator::Gt, false) => IntCC::UnsignedGreaterThan,
+                    (CompOperator::Lt, false) => IntCC::UnsignedLessThan,
+                    (CompOperator::Ge, false) => IntCC::UnsignedGreaterThanOrEqual,
+                    (CompOperator::Le, false) => IntCC::UnsignedLessThanOrEqual,
+                };
+                self.builder.ins().icmp(cond, lhs, rhs)
+            }
+        };
+        Ok(TypedValue::new(value, Type::Bool))
+    }
+
+    fn build_lazy_bool(&mut self, lazy_bool: &LazyBoolExprASTNode) -> Result<TypedValue> {
+        let lhs = self.build_bool(lazy_bool.lhs())?;
+
+        let rhs_block = self.builder.create_block();
+        let merge_block = self.builder.create_block();
+        self.builder.append_block_param(merge_block, types::I8);
+        // The right-hand side is only evaluated if the left-hand side doesn't determine the result
+        match lazy_bool.operator() {
+            LazyBoolOperator::And => {
+                self.builder
+                    .ins()
+                    .brif(lhs, rhs_block, &[], merge_block, &[lhs])
+            }
+            LazyBoolOperator::Or => {
+                self.builder
+                    .ins()
+                    .brif(lhs, merge_block, &[lhs], rhs_block, &[])
+            }
+        };
+        self.builder.seal_block(rhs_block);
+
+        self.switch_to(rhs_block);
+        let rhs = self.build_bool(lazy_bool.rhs())?;
+        self.builder.ins().jump(merge_block, &[rhs]);
+        self.builder.seal_block(merge_block);
+
+        self.switch_to(merge_block);
+        let value = self.builder.block_params(merge_block)[0];
+        Ok(TypedValue::new(value, Type::Bool))
+    }
+
+    fn build_neg(&mut self, neg: &NegExprASTNode, expected: Option<Type>) -> Result<TypedValue> {
+        // The range of a negated literal includes the minimum of its type (e.g. `-128i8`)
+        if let Some(literal) = neg.negated_int_literal() {
+            let ty = literal.infer_type(expected.clone());
+            if ty.is_signed() {
+                let value = literal.check_range(&ty, true)?;
+                return Ok(self.build_int_const(value, ty));
+            }
+        }
+        let expr = self.build_expecting(neg.expr(), expected)?;
+        let value = match (neg.operator(), &expr.ty, expr.value) {
+            (NegOperator::Neg, ty, Some(v)) if ty.is_signed() => self.builder.ins().ineg(v),
+            (NegOperator::Neg, ty, Some(v)) if ty.is_float() => self.builder.ins().fneg(v),
+            (NegOperator::Not, Type::Bool, Some(v)) => self.builder.ins().bxor_imm(v, 1),
+            (NegOperator::Neg, _, _) => {
+                return Err(CraneliftError::UnsupportedType {
+                    message: "Cannot perform numerical negation on non-numeric type".into(),
+                    span: neg.span(),
+                });
+            }
+            (NegOperator::Not, _, _) => {
+                return Err(CraneliftError::UnsupportedType {
+                    message: "Cannot perform logical negation on non-boolean type".into(),
+                    span: neg.span(),
+                });
+            }
+        };
+        Ok(TypedValue::new(value, expr.ty))
+    }
+
+    fn build_if_stmt(&mut self, if_node: &IfASTNode) -> Result<()> {
+        // A constant condition selects the branch at compile time
+        if let Some(condition) = if_node.condition().const_eval().and_then(|c| c.as_bool()) {
+            return match (condition, if_node.else_node().map(ElseASTNode::branch)) {
+                (true, _) => self.build_block(if_node.then_block(), true).map(|_| ()),
+                (false, None) => Ok(()),
+                (false, Some(ElseExpr::Else(block))) => self.build_block(block, true).map(|_| ()),
+                (false, Some(ElseExpr::ElseIf(else_if))) => self.build_if_stmt(else_if),
+            };
+        }
+
+        let condition = self.build_bool(if_node.condition())?;
+        let then_block = self.builder.create_block();
+        let else_block = if_node
+            .else_node()
+            .map(|else_node| (self.builder.create_block(), else_node.branch()));
+        let end_block = self.builder.create_block();
+        self.builder.ins().brif(
+            condition,
+            then_block,
+            &[],
+            else_block.map_or(end_block, |(block, _)| block),
+            &[],
+        );
+
+        // The values of the branches are discarded, so their types don't have to match
+        self.builder.seal_block(then_block);
+        self.switch_to(then_block);
+        self.build_block(if_node.then_block(), true)?;
+        self.jump_if_reachable(end_block);
+
+        if let Some((else_block, branch)) = else_block {
+            self.builder.seal_block(else_block);
+            self.switch_to(else_block);
+            match branch {
+                ElseExpr::Else(block) => self.build_block(block, true).map(|_| ())?,
+                ElseExpr::ElseIf(else_if) => self.build_if_stmt(else_if)?,
+            }
+            self.jump_if_reachable(end_block);
+        }
+
+        self.builder.seal_block(end_block);
+        self.switch_to(end_block);
+        Ok(())
+    }
+
+    fn build_if(&mut self, if_node: &IfASTNode) -> Result<TypedValue> {
+        if let Some(condition) = if_node.condition().const_eval().and_then(|c| c.as_bool()) {
+            return match (condition, if_node.else_node().map(ElseASTNode::branch)) {
+                (true, _) => self.build_block(if_node.then_block(), false),
+                (false, None) => Ok(TypedValue::unit()),
+                (false, Some(ElseExpr::Else(block))) => self.build_block(block, false),
+                (false, Some(ElseExpr::ElseIf(else_if))) => self.build_if(else_if),
+            };
+        }
+
+        // Without an `else` branch, the value is always unit (like in a statement position)
+        if if_node.else_node().is_none() {
+            self.build_if_stmt(if_node)?;
+            return Ok(TypedValue::unit());
+        }
+
+        Err(CraneliftError::UnsupportedExpression {
+            message: "The value of an `if` with an `else` branch is not supported".into(),
+            span: if_node.span(),
+        })
+    }
+
+    fn build_while(&mut self, while_node: &WhileASTNode) -> Result<TypedValue> {
+        match while_node
+            .condition()
+            .const_eval()
+            .and_then(|c| c.as_bool())
+        {
+            // The body is never executed, so the loop is not generated at all
+            Some(false) => return Ok(TypedValue::unit()),
+            // The condition does not need to be checked, so this is just a `loop`
+            Some(true) => return self.build_inf_loop(while_node.body()),
+            None => {}
+        }
+
+        let header_block = self.builder.create_block();
+        self.builder.ins().jump(header_block, &[]);
+
+        self.switch_to(header_block);
+        let condition = self.build_expr(while_node.condition())?;
+        // The condition diverges (e.g. a desugared do-while loop whose body returns),
+        // so the loop is never entered and the code after it is unreachable
+        if self.unreachable {
+            self.builder.seal_block(header_block);
+            return Ok(TypedValue::unit());
+        }
+        let condition = match condition.value {
+            Some(v) if condition.ty == Type::Bool => v,
+            _ => {
+                return Err(CraneliftError::TypeMismatch {
+                    expected: Type::Bool,
+                    actual: condition.ty,
+                    span: while_node.condition().span(),
+                })
+            }
+        };
+        let body_block = self.builder.create_block();
+        let end_block = self.builder.create_block();
+        self.builder
+            .ins()
+            .brif(condition, body_block, &[], end_block, &[]);
+
+        self.builder.seal_block(body_block);
+        self.switch_to(body_block);
+        self.build_block(while_node.body(), true)?;
+        self.jump_if_reachable(header_block);
+        self.builder.seal_block(header_block);
+
+        self.builder.seal_block(end_block);
+        self.switch_to(end_block);
+        Ok(TypedValue::unit())
+    }
+
+    fn build_inf_loop(&mut self, body: &BlockASTNode) -> Result<TypedValue> {
+        let header_block = self.builder.create_block();
+        self.builder.ins().jump(header_block, &[]);
+
+        self.switch_to(header_block);
+        self.build_block(body, true)?;
+        self.jump_if_reachable(header_block);
+        self.builder.seal_block(header_block);
+
+        // The loop can only be exited with a `return`, so the code after it is unreachable
+        self.switch_to_unreachable();
+        Ok(TypedValue::unit())
+    }
+
+    fn build_return(&mut self, ret: &ReturnASTNode) -> Result<TypedValue> {
+        let value = match ret.value() {
+            Some(value) => self.build_expr(value)?,
+            None => TypedValue::unit(),
+        };
+        // The value diverges itself, so the `return` is never reached
+        if self.unreachable {
+            return Ok(TypedValue::unit());
+        }
+        if value.ty != self.return_type {
+            return Err(CraneliftError::TypeMismatch {
+                expected: self.return_type.clone(),
+                actual: value.ty,
+                span: ret.value().map_or(ret.span(), |v| v.span()),
+            });
+        }
+
+        let values: Vec<Value> = value.value.into_iter().collect();
+        self.builder.ins().return_(&values);
+        self.switch_to_unreachable();
+        Ok(TypedValue::unit())
+    }
+    //#endregion
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_compile() {
+        let crt = parse(
+            "extern \"C\" { fn abs(x: i32) -> i32; }\n\
+            static mut COUNTER: i32 = 0;\n\
+            pub static LIMIT: i32 = 10 * 2;\n\
+            fn fib(n: i32) -> i32 {\n\
+                if n < 2 { return n; };\n\
+                return fib(n - 1) + fib(n - 2);\n\
+            }\n\
+            pub fn run(x: f64, flag: bool) -> i32 {\n\
+                let mut i: i32 = 0;\n\
+                while i < LIMIT && flag || x % 2.0 == 1.0 { i = i + 1; unsafe { COUNTER = i; }; };\n\
+                loop { unsafe { return abs(fib(i) / (i - 3)); }; }\n\
+            }\n\
+            fn main() { let _: () = (); return; }\n",
+        );
+        let object = compile(&crt).unwrap();
+        assert!(!object.is_empty());
+        if cfg!(target_os = "linux") {
+            assert!(object.starts_with(b"\x7fELF"));
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| compile(&parse(source)).unwrap_err();
+
+        assert!(matches!(
+            error("fn f() -> i32 { let x: bool = 1; return 0; }\n"),
+            CraneliftError::TypeMismatch {
+                expected: Type::Bool,
+                actual: Type::I32,
+                ..
+            }
+        ));
+        assert!(matches!(
+            error("fn f() -> i32 {}\n"),
+            CraneliftError::MissingReturn { .. }
+        ));
+        assert!(matches!(
+            error("fn f() { g(); }\n"),
+            CraneliftError::MissingSymbol { .. }
+        ));
+        assert!(matches!(
+            error("fn f(x: i32) { f(); }\n"),
+            CraneliftError::SemanticError(SemanticError::ArgumentCountMismatch { .. })
+        ));
+        assert!(matches!(
+            error("fn f() -> f64 { return 1.0 & 2.0; }\n"),
+            CraneliftError::UnsupportedType { .. }
+        ));
+        assert!(matches!(
+            error("fn f() -> i32 { return 1 as i32; }\n"),
+            CraneliftError::UnsupportedExpression { .. }
+        ));
+        assert!(matches!(
+            error("fn f(t: (i32, bool)) -> i32 { return 1; }\n"),
+            CraneliftError::UnsupportedType { .. }
+        ));
+        assert!(matches!(
+            error("fn f(x: i32) -> i32 { let r: &i32 = &x; return *r; }\n"),
+            CraneliftError::UnsupportedType { .. }
+        ));
+    }
+}
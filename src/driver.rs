@@ -0,0 +1,382 @@
+//! A module containing the driver that loads a μRust project consisting of multiple files.
+//!
+//! A file can import the `pub` functions of another file in the same directory with
+//! `use other_file::func;`. The imported file is parsed into its own [`Crate`] and compiled
+//! into its own LLVM module, in which the function is exported; the importing module
+//! only declares it, so the modules have to be linked together.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+#[cfg(feature = "codegen")]
+use inkwell::context::Context;
+#[cfg(feature = "codegen")]
+use inkwell::module::Module;
+
+#[cfg(feature = "codegen")]
+use crate::ast::error::SemanticWarning;
+use crate::ast::{ASTNode, Crate, FuncProtoASTNode, ItemASTNode};
+#[cfg(feature = "codegen")]
+use crate::codegen;
+#[cfg(feature = "codegen")]
+use crate::codegen::{CodeGenOptions, CodeGenRequest};
+use crate::parser::error::ParserError;
+use crate::parser::Parser;
+use crate::token::Span;
+
+/// The file extension of the μRust source files.
+const EXTENSION: &str = "mrs";
+
+/// A result of loading a project.
+pub type Result<T> = std::result::Result<T, DriverError>;
+
+/// The type of error that can occur while loading a project.
+#[derive(Debug)]
+pub enum DriverError {
+    /// A file of the project cannot be read.
+    Io {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying error.
+        error: io::Error,
+    },
+    /// A file of the project cannot be parsed.
+    Parser {
+        /// The path of the file.
+        path: PathBuf,
+        /// The underlying error.
+        error: ParserError,
+    },
+    /// An imported module doesn't exist.
+    MissingModule {
+        /// The name of the module.
+        module: Box<str>,
+        /// The span of the import.
+        span: Span,
+    },
+    /// An imported function is not exported from its module.
+    MissingExport {
+        /// The name of the module.
+        module: Box<str>,
+        /// The name of the function.
+        name: Box<str>,
+        /// The span of the import.
+        span: Span,
+    },
+}
+
+impl fmt::Display for DriverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DriverError::Io { path, error } => {
+                write!(f, "Cannot read {}: {}", path.display(), error)
+            }
+            DriverError::Parser { path, error } => {
+                write!(f, "Cannot parse {}: {}", path.display(), error)
+            }
+            DriverError::MissingModule { module, span } => {
+                write!(f, "Module `{}` not found at {}", module, span)
+            }
+            DriverError::MissingExport { module, name, span } => {
+                write!(
+                    f,
+                    "Module `{}` doesn't export a function `{}` at {}",
+                    module, name, span
+                )
+            }
+        }
+    }
+}
+
+impl Error for DriverError {}
+
+/// A μRust project: the root crate and all the crates it (transitively) imports.
+#[derive(Debug)]
+pub struct Project {
+    root_name: Rc<str>,
+    root: Crate,
+    /// The imported crates together with their module names, in the order they were loaded.
+    modules: Vec<(Rc<str>, Crate)>,
+}
+
+impl Project {
+    /// Loads the project with the root file at the given path.
+    ///
+    /// The imported modules are looked up in the directory of the root file,
+    /// and every module is loaded only once (so the imports can be cyclic).
+    ///
+    /// # Errors
+    ///
+    /// If any of the files cannot be read or parsed, or an import cannot be resolved,
+    /// the corresponding [`DriverError`] is returned.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Project> {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let mut project = Project {
+            root_name: module_name(path).into(),
+            root: parse_file(path)?,
+            modules: Vec::new(),
+        };
+
+        let mut next = 0;
+        while next <= project.modules.len() {
+            let imports: Vec<(Rc<str>, Span)> = project
+                .crate_at(next)
+                .root()
+                .imports()
+                .iter()
+                .map(|import| (import.module_owned(), import.span()))
+                .collect();
+            for (module, span) in imports {
+                if project.lookup(&module).is_some() {
+                    continue;
+                }
+                let path = dir.join(&*module).with_extension(EXTENSION);
+                if !path.is_file() {
+                    return Err(DriverError::MissingModule {
+                        module: module.as_ref().into(),
+                        span,
+                    });
+                }
+                let crt = parse_file(&path)?;
+                project.modules.push((module, crt));
+            }
+            next += 1;
+        }
+
+        project.check_imports()?;
+        Ok(project)
+    }
+
+    /// Returns the root crate of the project.
+    pub fn root(&self) -> &Crate {
+        &self.root
+    }
+
+    /// Returns the crate of the imported module with the given name.
+    pub fn module(&self, name: &str) -> Option<&Crate> {
+        self.modules
+            .iter()
+            .find(|(module, _)| **module == *name)
+            .map(|(_, crt)| crt)
+    }
+
+    /// Returns an iterator over the imported modules, in the order they were loaded.
+    pub fn modules(&self) -> impl Iterator<Item = (&str, &Crate)> {
+        self.modules.iter().map(|(name, crt)| (name.as_ref(), crt))
+    }
+
+    /// Generates the LLVM IR for every crate of the project given the context and options,
+    /// together with the warnings encountered during code generation.
+    ///
+    /// The root module comes first, followed by the imported ones in the order they were loaded.
+    #[cfg(feature = "codegen")]
+    pub fn code_gen<'ctx>(
+        &self,
+        context: &'ctx Context,
+        options: CodeGenOptions,
+    ) -> codegen::Result<Vec<(Module<'ctx>, Vec<SemanticWarning>)>> {
+        (0..=self.modules.len())
+            .map(|i| self.crate_at(i))
+            .map(|crt| {
+                let imports = self.imported_prototypes(crt);
+                let request = CodeGenRequest::new()
+                    .with_options(options)
+                    .with_imports(&imports);
+                crt.code_gen_with(context, request)
+            })
+            .collect()
+    }
+
+    fn crate_at(&self, index: usize) -> &Crate {
+        match index {
+            0 => &self.root,
+            i => &self.modules[i - 1].1,
+        }
+    }
+
+    /// Returns the crate of the module with the given name, including the root one.
+    fn lookup(&self, name: &str) -> Option<&Crate> {
+        if *self.root_name == *name {
+            Some(&self.root)
+        } else {
+            self.module(name)
+        }
+    }
+
+    /// Checks that every import refers to a `pub` function of its module.
+    fn check_imports(&self) -> Result<()> {
+        for i in 0..=self.modules.len() {
+            for import in self.crate_at(i).root().imports() {
+                let exported = self
+                    .lookup(import.module())
+                    .and_then(|module| exported_prototype(module, import.name()));
+                if exported.is_none() {
+                    return Err(DriverError::MissingExport {
+                        module: import.module().into(),
+                        name: import.name().into(),
+                        span: import.span(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the prototypes of the functions imported by the given crate,
+    /// paired with the names of their modules.
+    #[cfg(feature = "codegen")]
+    fn imported_prototypes<'a>(&'a self, crt: &'a Crate) -> Vec<(&'a str, &'a FuncProtoASTNode)> {
+        crt.root()
+            .imports()
+            .iter()
+            .filter_map(|import| {
+                let proto = exported_prototype(self.lookup(import.module())?, import.name())?;
+                Some((import.module(), proto))
+            })
+            .collect()
+    }
+}
+
+/// Returns the prototype of the `pub` function with the given name from the given crate.
+fn exported_prototype<'a>(crt: &'a Crate, name: &str) -> Option<&'a FuncProtoASTNode> {
+    crt.root().items().iter().find_map(|item| match item {
+        ItemASTNode::Func(func) if func.visibility().is_public() && func.proto().name() == name => {
+            Some(func.proto())
+        }
+        _ => None,
+    })
+}
+
+/// Returns the name of the module in the file at the given path.
+fn module_name(path: &Path) -> &str {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("")
+}
+
+fn parse_file(path: &Path) -> Result<Crate> {
+    let parser = Parser::new(path).map_err(|error| DriverError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
+    parser.parse().map_err(|error| DriverError::Parser {
+        path: path.to_path_buf(),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    #[cfg(feature = "codegen")]
+    use inkwell::OptimizationLevel;
+
+    #[cfg(feature = "codegen")]
+    use crate::link::{self, LinkError};
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "codegen")]
+    fn test_import_function() {
+        let dir = TempDir::new("driver_import");
+        fs::write(
+            dir.join("main.mrs"),
+            "use math::square;\n\
+            use math::twice;\n\
+            pub fn run(x: i32) -> i32 { return twice(square(x)) + 1; }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("math.mrs"),
+            "use main::run;\n\
+            pub fn square(x: i32) -> i32 { return x * x; }\n\
+            pub fn twice(x: i32) -> i32 { return x + x; }\n\
+            fn hidden() -> i32 { return 0; }\n",
+        )
+        .unwrap();
+
+        let project = Project::load(dir.join("main.mrs")).unwrap();
+        assert_eq!(project.modules().count(), 1);
+        assert!(project.module("math").is_some());
+
+        let context = Context::create();
+        let modules = project
+            .code_gen(&context, CodeGenOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|(module, _)| module);
+        let main = link::link_modules(modules).unwrap();
+
+        let engine = main
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .unwrap();
+        unsafe {
+            let run = engine
+                .get_function::<unsafe extern "C" fn(i32) -> i32>("run")
+                .unwrap();
+            assert_eq!(run.call(3), 19);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "codegen")]
+    fn test_duplicate_definitions() {
+        let dir = TempDir::new("driver_duplicate");
+        fs::write(
+            dir.join("main.mrs"),
+            "use other::f;\npub fn g() -> i32 { return 1; }\nfn h() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("other.mrs"),
+            "pub fn f() {}\npub fn g() -> i32 { return 2; }\nfn h() {}\n",
+        )
+        .unwrap();
+
+        let project = Project::load(dir.join("main.mrs")).unwrap();
+        let context = Context::create();
+        let modules = project
+            .code_gen(&context, CodeGenOptions::default())
+            .unwrap()
+            .into_iter()
+            .map(|(module, _)| module);
+        let err = link::link_modules(modules).unwrap_err();
+        // The private functions with the same name don't collide
+        assert!(matches!(
+            &err,
+            LinkError::DuplicateDefinition { symbol, .. } if &**symbol == "g"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Symbol `g` is defined in both `main.mrs` and `other.mrs`"
+        );
+        assert!(matches!(
+            link::link_modules(Vec::new()),
+            Err(LinkError::NoModules)
+        ));
+    }
+
+    #[test]
+    fn test_unresolved_imports() {
+        let dir = TempDir::new("driver_unresolved");
+        fs::write(dir.join("main.mrs"), "use missing::f;\n").unwrap();
+        assert!(matches!(
+            Project::load(dir.join("main.mrs")),
+            Err(DriverError::MissingModule { module, .. }) if &*module == "missing"
+        ));
+
+        fs::write(dir.join("main.mrs"), "use other::hidden;\n").unwrap();
+        fs::write(dir.join("other.mrs"), "fn hidden() {}\n").unwrap();
+        assert!(matches!(
+            Project::load(dir.join("main.mrs")),
+            Err(DriverError::MissingExport { name, .. }) if &*name == "hidden"
+        ));
+    }
+}
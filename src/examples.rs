@@ -0,0 +1,167 @@
+//! A module containing the corpus of example μRust programs shipped with the compiler.
+//!
+//! The sources are embedded in the crate, so the documentation, the tests and the playgrounds
+//! can all use the same programs, which are checked to compile by the tests of this module.
+//!
+//! # Example
+//! ```
+//! # use mini_rust_compiler_components::examples;
+//! let factorial = examples::get("factorial").unwrap();
+//! assert!(factorial.source().contains("fn factorial(n: i32) -> i32"));
+//! assert_eq!(factorial.main_result(), Some(240));
+//! assert!(examples::all().iter().any(|e| e.name() == "fizzbuzz"));
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An example μRust program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Example {
+    name: &'static str,
+    description: &'static str,
+    source: &'static str,
+    main_result: Option<i32>,
+}
+
+impl Example {
+    /// Returns the name of the example (e.g. `factorial`).
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns a one-line description of the example.
+    pub fn description(&self) -> &'static str {
+        self.description
+    }
+
+    /// Returns the source code of the example.
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+
+    /// Returns the name of the source file of the example (e.g. `factorial.mrs`).
+    pub fn file_name(&self) -> String {
+        format!("{}.mrs", self.name)
+    }
+
+    /// Returns the value returned by the `main` function of the example, if it returns
+    /// an `i32` and doesn't depend on any extern items (so it can be run on its own).
+    pub fn main_result(&self) -> Option<i32> {
+        self.main_result
+    }
+
+    /// Writes the source code of the example into the [file](Example::file_name)
+    /// in the given directory (e.g. to [parse](crate::parser::Parser::new) it),
+    /// and returns the path of the file.
+    pub fn write_to<P: AsRef<Path>>(&self, dir: P) -> io::Result<PathBuf> {
+        let path = dir.as_ref().join(self.file_name());
+        fs::write(&path, self.source)?;
+        Ok(path)
+    }
+}
+
+/// All the examples, ordered by their name.
+const EXAMPLES: &[Example] = &[
+    Example {
+        name: "factorial",
+        description: "Recursive and iterative factorial",
+        source: include_str!("examples/factorial.mrs"),
+        main_result: Some(240),
+    },
+    Example {
+        name: "fizzbuzz",
+        description: "FizzBuzz printing the numbers with an extern C function",
+        source: include_str!("examples/fizzbuzz.mrs"),
+        main_result: None,
+    },
+    Example {
+        name: "loops",
+        description: "Nested `while` loops, a `for` loop, a `do`-`while` loop and a `loop` left with a `return`",
+        source: include_str!("examples/loops.mrs"),
+        main_result: Some(255),
+    },
+    Example {
+        name: "statics",
+        description: "Immutable statics as constants and mutable statics in `unsafe` blocks",
+        source: include_str!("examples/statics.mrs"),
+        main_result: Some(70),
+    },
+];
+
+/// Returns all the examples, ordered by their name.
+pub fn all() -> &'static [Example] {
+    EXAMPLES
+}
+
+/// Returns the example with the given name, if any.
+pub fn get(name: &str) -> Option<&'static Example> {
+    EXAMPLES.iter().find(|example| example.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::Crate;
+    use crate::parser::Parser;
+    use crate::test_util::TempDir;
+    use crate::transpile;
+
+    use super::*;
+
+    fn parse(example: &Example) -> Crate {
+        let dir = TempDir::new("examples");
+        let path = example.write_to(dir.path()).unwrap();
+        let crt = Parser::new(path).unwrap().parse();
+        crt.unwrap_or_else(|err| panic!("{}: {}", example.name(), err))
+    }
+
+    #[test]
+    fn test_examples() {
+        let names: Vec<&str> = all().iter().map(Example::name).collect();
+        assert_eq!(names, ["factorial", "fizzbuzz", "loops", "statics"]);
+        assert_eq!(
+            get("loops").map(Example::file_name).as_deref(),
+            Some("loops.mrs")
+        );
+        assert_eq!(get("hello"), None);
+
+        for example in all() {
+            let crt = parse(example);
+            assert!(crt.check_initialization().is_empty(), "{}", example.name());
+            assert!(
+                crt.check_unsafety().iter().all(|block| block.is_used()),
+                "{}",
+                example.name()
+            );
+            assert!(transpile::to_c(&crt).is_ok(), "{}", example.name());
+        }
+    }
+
+    #[cfg(feature = "codegen")]
+    #[test]
+    fn test_example_results() {
+        use inkwell::context::Context;
+        use inkwell::OptimizationLevel;
+
+        for example in all() {
+            let crt = parse(example);
+            let context = Context::create();
+            let module = crt.code_gen(&context).unwrap();
+            let Some(expected) = example.main_result() else {
+                continue;
+            };
+
+            let engine = module
+                .create_jit_execution_engine(OptimizationLevel::None)
+                .unwrap();
+            let result = unsafe {
+                engine
+                    .get_function::<unsafe extern "C" fn() -> i32>("main")
+                    .unwrap()
+                    .call()
+            };
+            assert_eq!(result, expected, "{}", example.name());
+        }
+    }
+}
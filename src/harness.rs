@@ -0,0 +1,368 @@
+//! A module containing the built-in test runner for μRust programs.
+//!
+//! The functions annotated with `#[test]` (either `fn name()` or `fn name() -> i32`) are compiled
+//! together with the rest of the crate, and each of them is run with the JIT. A test fails if it
+//! returns a non-zero value, or if it calls the [assertion function](ASSERT_FUNCTION) with `false`.
+//! The assertion function is provided by the runner, and the program has to declare it
+//! in an extern block:
+//! ```text
+//! extern "C" {
+//!     fn assert(condition: bool);
+//! }
+//!
+//! #[test]
+//! fn addition() {
+//!     unsafe { assert(1 + 1 == 2); };
+//! }
+//! ```
+//! A failed assertion doesn't stop the test, so all the failed assertions of a test are counted.
+//!
+//! # Caveats
+//!
+//! Just like in the [sandbox](crate::sandbox), the tests run in the current process, so a trap
+//! (e.g. an integer overflow when the [overflow checks](CodeGenOptions::overflow_checks)
+//! are enabled) aborts the whole process.
+
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+use inkwell::execution_engine::ExecutionEngine;
+use inkwell::module::Linkage;
+use inkwell::OptimizationLevel;
+
+use crate::ast::{ASTNode, Attribute, Crate, ExternItem, FuncASTNode, Type};
+use crate::codegen::error::CodeGenError;
+use crate::codegen::session::CompilationSession;
+use crate::codegen::CodeGenOptions;
+use crate::token::Span;
+
+/// The name of the assertion function provided by the test runner.
+///
+/// It takes a single `bool` parameter and returns unit.
+pub const ASSERT_FUNCTION: &str = "assert";
+
+/// A result of running the tests.
+pub type Result<T> = std::result::Result<T, HarnessError>;
+
+/// The type of error that prevents the tests from running.
+#[derive(Debug, PartialEq, Eq)]
+pub enum HarnessError {
+    /// A test function has a signature other than `fn()` or `fn() -> i32`.
+    InvalidTestSignature {
+        /// The name of the test.
+        name: Box<str>,
+        /// The span of the prototype of the test.
+        span: Span,
+    },
+    /// The [assertion function](ASSERT_FUNCTION) is declared with a signature other than
+    /// `fn(bool)`.
+    InvalidAssertSignature {
+        /// The span of the declaration.
+        span: Span,
+    },
+    /// The crate couldn't be compiled.
+    CodeGen(CodeGenError),
+    /// The JIT failed to load the compiled crate.
+    Jit(Box<str>),
+}
+
+impl fmt::Display for HarnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HarnessError::InvalidTestSignature { name, span } => {
+                write!(
+                    f,
+                    "The test `{}` has to be `fn {}()` or `fn {}() -> i32` at {}",
+                    name, name, name, span
+                )
+            }
+            HarnessError::InvalidAssertSignature { span } => {
+                write!(
+                    f,
+                    "The function `{}` has to be declared as `fn {}(condition: bool)` at {}",
+                    ASSERT_FUNCTION, ASSERT_FUNCTION, span
+                )
+            }
+            HarnessError::CodeGen(err) => write!(f, "Cannot compile the tests: {:?}", err),
+            HarnessError::Jit(message) => write!(f, "Cannot run the tests: {}", message),
+        }
+    }
+}
+
+impl Error for HarnessError {}
+
+impl From<CodeGenError> for HarnessError {
+    fn from(error: CodeGenError) -> Self {
+        HarnessError::CodeGen(error)
+    }
+}
+
+/// The outcome of a single test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    /// The test returned unit or `0`, and all its assertions held.
+    Passed,
+    /// The test returned the given non-zero value.
+    Returned(i32),
+    /// The given number of assertions of the test failed.
+    AssertionsFailed(usize),
+}
+
+/// The result of a single test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestResult {
+    name: Rc<str>,
+    span: Span,
+    status: TestStatus,
+}
+
+impl TestResult {
+    /// Returns the name of the test function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the span of the test function.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the outcome of the test.
+    pub fn status(&self) -> TestStatus {
+        self.status
+    }
+
+    /// Returns whether the test passed.
+    pub fn is_passed(&self) -> bool {
+        self.status == TestStatus::Passed
+    }
+}
+
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "test {} ... ", self.name)?;
+        match self.status {
+            TestStatus::Passed => write!(f, "ok"),
+            TestStatus::Returned(value) => write!(f, "FAILED (returned {})", value),
+            TestStatus::AssertionsFailed(count) => {
+                write!(f, "FAILED ({} assertion(s) failed)", count)
+            }
+        }
+    }
+}
+
+/// The results of all the tests of a crate, in the order of their declarations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestReport {
+    results: Vec<TestResult>,
+}
+
+impl TestReport {
+    /// Returns the results of the individual tests.
+    pub fn results(&self) -> &[TestResult] {
+        &self.results
+    }
+
+    /// Returns the number of the passed tests.
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.is_passed()).count()
+    }
+
+    /// Returns the number of the failed tests.
+    pub fn failed(&self) -> usize {
+        self.results.len() - self.passed()
+    }
+
+    /// Returns whether all the tests passed.
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0
+    }
+}
+
+impl fmt::Display for TestReport {
+    /// Formats the report similarly to `cargo test`, i.e. one line per test
+    /// followed by the summary.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "running {} test(s)", self.results.len())?;
+        for result in &self.results {
+            writeln!(f, "{}", result)?;
+        }
+        let outcome = if self.is_success() { "ok" } else { "FAILED" };
+        write!(
+            f,
+            "\ntest result: {}. {} passed; {} failed",
+            outcome,
+            self.passed(),
+            self.failed()
+        )
+    }
+}
+
+/// Returns the functions of the crate annotated with `#[test]`.
+pub fn collect_tests(crt: &Crate) -> impl Iterator<Item = &FuncASTNode> {
+    crt.functions()
+        .filter(|func| func.attribute(Attribute::TEST).is_some())
+}
+
+/// Compiles the given crate with the given options and runs all its tests.
+pub fn run_tests(crt: &Crate, options: CodeGenOptions) -> Result<TestReport> {
+    check_program(crt)?;
+    let tests: Vec<_> = collect_tests(crt).collect();
+    let session = CompilationSession::with_options(options);
+    session.with_module(crt, |module, _| {
+        // The tests are usually private, so they have to be exported for the JIT to find them
+        for test in &tests {
+            if let Some(function) = module.get_function(test.proto().name()) {
+                function.set_linkage(Linkage::External);
+            }
+        }
+        let engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .map_err(|err| HarnessError::Jit(err.to_string().into()))?;
+        if let Some(function) = module.get_function(ASSERT_FUNCTION) {
+            engine.add_global_mapping(&function, assert as extern "C" fn(u8) as usize);
+        }
+
+        let results = tests
+            .iter()
+            .map(|test| run_test(&engine, test))
+            .collect::<Result<_>>()?;
+        Ok(TestReport { results })
+    })?
+}
+
+/// Checks the signatures of the tests and of the assertion function.
+fn check_program(crt: &Crate) -> Result<()> {
+    for test in collect_tests(crt) {
+        let signature = test.proto().signature();
+        if signature.param_count() != 0
+            || !matches!(signature.return_type(), Type::Unit | Type::I32)
+        {
+            return Err(HarnessError::InvalidTestSignature {
+                name: test.proto().name().into(),
+                span: test.proto().span(),
+            });
+        }
+    }
+
+    let assert = crt
+        .extern_blocks()
+        .flat_map(|block| block.items())
+        .find_map(|item| match item {
+            ExternItem::Func(proto) if proto.name() == ASSERT_FUNCTION => Some(proto),
+            _ => None,
+        });
+    if let Some(assert) = assert {
+        let signature = assert.signature();
+        if signature.param_types() != [Type::Bool] || *signature.return_type() != Type::Unit {
+            return Err(HarnessError::InvalidAssertSignature {
+                span: assert.span(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Runs the given test with the given execution engine.
+fn run_test(engine: &ExecutionEngine, test: &FuncASTNode) -> Result<TestResult> {
+    let name = test.proto().name();
+    FAILED_ASSERTIONS.with(|failed| failed.set(0));
+    // SAFETY: The signature of the test has been checked
+    let returned = unsafe {
+        if test.proto().return_type().ty() == Type::Unit {
+            let test = engine
+                .get_function::<unsafe extern "C" fn()>(name)
+                .map_err(|err| HarnessError::Jit(err.to_string().into()))?;
+            test.call();
+            0
+        } else {
+            let test = engine
+                .get_function::<unsafe extern "C" fn() -> i32>(name)
+                .map_err(|err| HarnessError::Jit(err.to_string().into()))?;
+            test.call()
+        }
+    };
+
+    let failed = FAILED_ASSERTIONS.with(Cell::get);
+    let status = match (returned, failed) {
+        (0, 0) => TestStatus::Passed,
+        (_, 0) => TestStatus::Returned(returned),
+        (_, failed) => TestStatus::AssertionsFailed(failed),
+    };
+    Ok(TestResult {
+        name: test.proto().name_owned(),
+        span: test.span(),
+        status,
+    })
+}
+
+thread_local! {
+    /// The number of the failed assertions of the test running on the current thread.
+    static FAILED_ASSERTIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The μRust `bool` is passed as an `i1`, so only its lowest bit is defined.
+extern "C" fn assert(condition: u8) {
+    if condition & 1 == 0 {
+        FAILED_ASSERTIONS.with(|failed| failed.set(failed.get() + 1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_run_tests() {
+        let crt = parse(
+            "extern \"C\" { fn assert(condition: bool); }\n\
+            fn square(x: i32) -> i32 { return x * x; }\n\
+            #[test]\n\
+            fn passing() { unsafe { assert(square(3) == 9); }; }\n\
+            #[test]\n\
+            fn returning() -> i32 { return square(2) - 4 + 1; }\n\
+            #[test]\n\
+            fn asserting() { unsafe { assert(square(2) == 5); assert(true); assert(1 > 2); }; }\n\
+            fn not_a_test() -> i32 { return 1; }\n",
+        );
+        let report = run_tests(&crt, CodeGenOptions::new()).unwrap();
+        let statuses: Vec<_> = report
+            .results()
+            .iter()
+            .map(|r| (r.name(), r.status()))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("passing", TestStatus::Passed),
+                ("returning", TestStatus::Returned(1)),
+                ("asserting", TestStatus::AssertionsFailed(2)),
+            ]
+        );
+        assert!(!report.is_success());
+        assert_eq!(
+            report.to_string(),
+            "running 3 test(s)\n\
+            test passing ... ok\n\
+            test returning ... FAILED (returned 1)\n\
+            test asserting ... FAILED (2 assertion(s) failed)\n\
+            \n\
+            test result: FAILED. 1 passed; 2 failed"
+        );
+
+        let crt = parse("#[test]\nfn t(x: i32) {}\n");
+        assert!(matches!(
+            run_tests(&crt, CodeGenOptions::new()),
+            Err(HarnessError::InvalidTestSignature { name, .. }) if &*name == "t"
+        ));
+        let crt = parse("extern \"C\" { fn assert(x: i32); }\n#[test]\nfn t() {}\n");
+        assert!(matches!(
+            run_tests(&crt, CodeGenOptions::new()),
+            Err(HarnessError::InvalidAssertSignature { .. })
+        ));
+    }
+}
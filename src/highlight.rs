@@ -0,0 +1,206 @@
+//! A module for classifying source ranges of a μRust program, e.g. for syntax highlighting
+//! or LSP semantic tokens.
+
+use std::collections::HashSet;
+use std::iter;
+
+use crate::ast::{Crate, Type};
+use crate::resolve::DeclKind;
+use crate::token::{Span, Token, TokenType};
+
+/// The kind of a [highlighted range](HighlightRange).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightKind {
+    /// A keyword (including the ones reserved for future use).
+    Keyword,
+    /// A type annotation.
+    Type,
+    /// A function name, both in declarations and calls.
+    Function,
+    /// A function parameter, both in declarations and usages.
+    Parameter,
+    /// A literal (including ABI strings).
+    Literal,
+}
+
+/// A classified range of the source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighlightRange {
+    kind: HighlightKind,
+    span: Span,
+}
+
+impl HighlightRange {
+    /// Creates a new `HighlightRange` of the given kind at the given span.
+    pub fn new(kind: HighlightKind, span: Span) -> HighlightRange {
+        HighlightRange { kind, span }
+    }
+
+    /// Returns the kind of the range.
+    pub fn kind(&self) -> HighlightKind {
+        self.kind
+    }
+
+    /// Returns the span of the range.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// Classifies the `tokens` of a source file parsed into `crt`.
+///
+/// The lexical classes (keywords, literals) come from the tokens themselves,
+/// while the parameters and their usages are taken from the [resolved names](Crate::resolve_names),
+/// so a local variable shadowing a parameter is not highlighted as one.
+///
+/// The returned ranges are in the order of the `tokens` and do not overlap.
+pub fn highlight(crt: &Crate, tokens: &[Token]) -> Vec<HighlightRange> {
+    let params = param_spans(crt);
+
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        let prev = i.checked_sub(1).map(|i| tokens[i].ty());
+        let next = tokens.get(i + 1).map(|t| t.ty());
+
+        let kind = match token.ty() {
+            TokenType::IntLit(..)
+            | TokenType::FloatLit(..)
+            | TokenType::BoolLit(_)
+            | TokenType::StrLit(_) => Some(HighlightKind::Literal),
+            TokenType::Ident(ident) => match (prev, next) {
+                (Some(TokenType::Fn), _) | (_, Some(TokenType::LPar)) => {
+                    Some(HighlightKind::Function)
+                }
+                (Some(TokenType::Colon | TokenType::Arrow | TokenType::As), _)
+                    if ident.parse::<Type>().is_ok() =>
+                {
+                    Some(HighlightKind::Type)
+                }
+                _ if params.contains(&token.span()) => Some(HighlightKind::Parameter),
+                _ => None,
+            },
+            // Unit type
+            TokenType::LPar
+                if matches!(
+                    prev,
+                    Some(TokenType::Colon | TokenType::Arrow | TokenType::As)
+                ) && matches!(next, Some(TokenType::RPar)) =>
+            {
+                let span = Span::new(token.span().start(), tokens[i + 1].span().end());
+                result.push(HighlightRange::new(HighlightKind::Type, span));
+                i += 2;
+                continue;
+            }
+            TokenType::EOF => None,
+            ty if is_keyword(ty) => Some(HighlightKind::Keyword),
+            _ => None,
+        };
+
+        if let Some(kind) = kind {
+            result.push(HighlightRange::new(kind, token.span()));
+        }
+        i += 1;
+    }
+
+    result
+}
+
+/// Collects the spans of the declarations of all the parameters in the crate
+/// and of the references to them.
+fn param_spans(crt: &Crate) -> HashSet<Span> {
+    let map = crt.resolve_names();
+    map.declarations()
+        .iter()
+        .filter(|decl| decl.kind() == DeclKind::Param)
+        .flat_map(|decl| iter::once(decl.span()).chain(map.references(decl.span())))
+        .collect()
+}
+
+/// Checks whether the token type is a keyword.
+fn is_keyword(ty: &TokenType) -> bool {
+    use TokenType::*;
+
+    matches!(
+        ty,
+        Fn | Static
+            | Extern
+            | Let
+            | Mut
+            | As
+            | Loop
+            | While
+            | Do
+            | For
+            | In
+            | If
+            | Else
+            | Unsafe
+            | Return
+            | Pub
+            | Use
+    ) || ty.reserved_keyword().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use fallible_iterator::FallibleIterator;
+
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    /// Returns the kinds of the ranges highlighted in the given source.
+    fn highlight_kinds(name: &str, source: &str) -> Vec<HighlightKind> {
+        let dir = TempDir::new(name);
+        let path = dir.join("main.mrs");
+        fs::write(&path, source).unwrap();
+
+        let crt = Parser::new(&path).unwrap().parse().unwrap();
+        let tokens: Vec<Token> = Lexer::new(&path).unwrap().collect().unwrap();
+        highlight(&crt, &tokens)
+            .iter()
+            .map(HighlightRange::kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_highlight() {
+        let kinds = highlight_kinds(
+            "highlight",
+            "fn add(a: i32, b: i32) -> i32 {\n    return add(a, 1);\n}\n",
+        );
+
+        use HighlightKind::*;
+        assert_eq!(
+            kinds,
+            vec![
+                Keyword, Function, Parameter, Type, Parameter, Type, Type, Keyword, Function,
+                Parameter, Literal
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_shadowed_param() {
+        let kinds = highlight_kinds(
+            "highlight_shadowed",
+            "extern \"C\" { fn g(x: i32); }\nfn f(x: i32) -> i32 {\n    let x: i32 = x;\n    x\n}\n",
+        );
+
+        // Only the initializer of the local variable refers to the parameter
+        use HighlightKind::*;
+        assert_eq!(
+            kinds,
+            vec![
+                Keyword, Literal, Keyword, Function, Parameter, Type, Keyword, Function, Parameter,
+                Type, Type, Keyword, Type, Parameter
+            ]
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! A module containing the definite initialization analysis of local variables.
+//!
+//! Besides checking that the variables are initialized before they are read,
+//! it also checks that the immutable variables are assigned at most once.
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::error::SemanticError;
+use crate::token::Span;
+
+/// The state of the definite initialization analysis.
+///
+/// The analysis is run by calling [`ASTNode::check_init`](crate::ast::ASTNode::check_init)
+/// on the root of the AST. The nodes [declare](InitChecker::declare), [read](InitChecker::read)
+/// and [write](InitChecker::write) the local variables, and [split](InitChecker::snapshot) and
+/// [merge](InitChecker::merge) the control flow.
+pub struct InitChecker {
+    locals: Vec<Local>,
+    scopes: Vec<HashMap<Rc<str>, usize>>,
+    flow: FlowState,
+    errors: Vec<SemanticError>,
+}
+
+/// A local variable tracked by the analysis.
+struct Local {
+    name: Rc<str>,
+    span: Span,
+    mut_span: Option<Span>,
+}
+
+/// The initialization state of the local variables at some point of the control flow.
+#[derive(Debug, Clone, Default)]
+pub struct FlowState {
+    /// Whether the local variable (indexed by its id) is definitely initialized.
+    initialized: Vec<bool>,
+    /// Whether the local variable (indexed by its id) is possibly initialized.
+    maybe_initialized: Vec<bool>,
+    /// Whether this point of the control flow is unreachable (e.g. after a `return`).
+    diverged: bool,
+}
+
+impl FlowState {
+    /// Returns whether the local variable with the given id is definitely initialized.
+    fn is_initialized(&self, id: usize) -> bool {
+        self.diverged || self.initialized.get(id).copied().unwrap_or(false)
+    }
+
+    /// Returns whether the local variable with the given id is possibly initialized.
+    fn is_maybe_initialized(&self, id: usize) -> bool {
+        !self.diverged && self.maybe_initialized.get(id).copied().unwrap_or(false)
+    }
+
+    /// Sets whether the local variable with the given id is (definitely) initialized.
+    fn set_initialized(&mut self, id: usize, initialized: bool) {
+        if self.initialized.len() <= id {
+            self.initialized.resize(id + 1, false);
+            self.maybe_initialized.resize(id + 1, false);
+        }
+        self.initialized[id] = initialized;
+        self.maybe_initialized[id] = initialized;
+    }
+
+    /// Joins two control flow paths -- a variable is definitely initialized
+    /// only if it is initialized on both paths, and possibly initialized if it is
+    /// possibly initialized on any of the paths (unless one of the paths is unreachable).
+    fn join(self, other: FlowState) -> FlowState {
+        if self.diverged {
+            return other;
+        }
+        if other.diverged {
+            return self;
+        }
+
+        let initialized = self
+            .initialized
+            .iter()
+            .zip(other.initialized.iter())
+            .map(|(a, b)| *a && *b)
+            .collect();
+        let maybe_initialized = self
+            .maybe_initialized
+            .iter()
+            .zip(other.maybe_initialized.iter())
+            .map(|(a, b)| *a || *b)
+            .collect();
+        FlowState {
+            initialized,
+            maybe_initialized,
+            diverged: false,
+        }
+    }
+}
+
+impl InitChecker {
+    /// Creates a new `InitChecker` without any local variables.
+    pub fn new() -> InitChecker {
+        InitChecker {
+            locals: Vec::new(),
+            scopes: Vec::new(),
+            flow: FlowState::default(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Opens a new scope for local variables.
+    pub fn open_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// Closes the current scope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no open scope.
+    pub fn close_scope(&mut self) {
+        self.scopes.pop().expect("There is no scope to close");
+    }
+
+    /// Declares a local variable with the given name, span and the span of its `mut` keyword
+    /// (if it is mutable) in the current scope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no open scope.
+    pub fn declare(
+        &mut self,
+        name: Rc<str>,
+        span: Span,
+        mut_span: Option<Span>,
+        initialized: bool,
+    ) {
+        let id = self.locals.len();
+        self.locals.push(Local {
+            name: name.clone(),
+            span,
+            mut_span,
+        });
+        self.flow.set_initialized(id, initialized);
+        self.scopes
+            .last_mut()
+            .expect("Local variables have to be declared in a scope")
+            .insert(name, id);
+    }
+
+    /// Records a read of the variable with the given name at the given span.
+    ///
+    /// If the variable is a local that is possibly uninitialized at this point,
+    /// [`SemanticError::UninitializedVariable`] is recorded.
+    pub fn read(&mut self, name: &str, span: Span) {
+        if let Some(id) = self.lookup(name) {
+            if !self.flow.is_initialized(id) {
+                let local = &self.locals[id];
+                self.push_error(SemanticError::UninitializedVariable {
+                    name: local.name.as_ref().into(),
+                    decl_span: local.span,
+                    span,
+                });
+            }
+        }
+    }
+
+    /// Records a write to the variable with the given name at the given span.
+    ///
+    /// If the variable is an immutable local that is possibly initialized at this point,
+    /// [`SemanticError::ImmutableAssignedTwice`] is recorded.
+    pub fn write(&mut self, name: &str, span: Span) {
+        if let Some(id) = self.lookup(name) {
+            let local = &self.locals[id];
+            if local.mut_span.is_none() && self.flow.is_maybe_initialized(id) {
+                self.push_error(SemanticError::ImmutableAssignedTwice {
+                    name: local.name.as_ref().into(),
+                    decl_span: local.span,
+                    span,
+                });
+            }
+            self.flow.set_initialized(id, true);
+        }
+    }
+
+    /// Records a mutable borrow (`&mut`) of the variable with the given name at the given span,
+    /// which reads the variable as well (see [`read`](InitChecker::read)).
+    ///
+    /// If the variable is an immutable local, [`SemanticError::ImmutableBorrowedAsMutable`]
+    /// is recorded.
+    pub fn borrow_mut(&mut self, name: &str, span: Span) {
+        self.read(name, span);
+        if let Some(id) = self.lookup(name) {
+            let local = &self.locals[id];
+            if local.mut_span.is_none() {
+                self.push_error(SemanticError::ImmutableBorrowedAsMutable {
+                    name: local.name.as_ref().into(),
+                    decl_span: local.span,
+                    span,
+                });
+            }
+        }
+    }
+
+    /// Marks the current point of the control flow as unreachable.
+    pub fn diverge(&mut self) {
+        self.flow.diverged = true;
+    }
+
+    /// Returns the current state of the control flow, e.g. before entering a conditional branch.
+    pub fn snapshot(&self) -> FlowState {
+        self.flow.clone()
+    }
+
+    /// Restores the state of the control flow, e.g. after a branch that may not be executed.
+    pub fn restore(&mut self, state: FlowState) {
+        self.flow = state;
+    }
+
+    /// Merges the given state (of another control flow path) into the current one.
+    pub fn merge(&mut self, state: FlowState) {
+        let current = std::mem::take(&mut self.flow);
+        self.flow = current.join(state);
+    }
+
+    /// Runs the given check of a loop body, which may be executed repeatedly.
+    ///
+    /// The body is checked twice -- the second time with the variables that were possibly
+    /// initialized in the first iteration, so that a repeated assignment is detected.
+    /// The state after the check is the state after the first iteration.
+    pub fn check_loop_body(&mut self, mut check: impl FnMut(&mut InitChecker)) {
+        let before = self.snapshot();
+        check(self);
+        let after = self.snapshot();
+
+        self.restore(before);
+        self.merge(after.clone());
+        check(self);
+        self.restore(after);
+    }
+
+    /// Finishes the analysis and returns the errors it found.
+    pub fn finish(self) -> Vec<SemanticError> {
+        self.errors
+    }
+
+    /// Records the error, unless it has already been recorded (e.g. in a loop body).
+    fn push_error(&mut self, error: SemanticError) {
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    /// Returns the id of the innermost local variable with the given name, if any.
+    fn lookup(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .copied()
+    }
+}
+
+impl Default for InitChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::parse;
+
+    use super::*;
+
+    fn check(source: &str) -> Vec<SemanticError> {
+        parse(source).check_initialization()
+    }
+
+    #[test]
+    fn test_check_init() {
+        assert_eq!(check("fn f() -> i32 { let x: i32; return x; }").len(), 1);
+        assert!(check("fn f() -> i32 { let mut x: i32; x = 1; return x; }").is_empty());
+        assert!(check(
+            "fn f(c: bool) -> i32 { let mut x: i32; if c { x = 1; } else { x = 2; }; return x; }"
+        )
+        .is_empty());
+        assert!(check(
+            "fn f(c: bool) -> i32 { let mut x: i32; if c { x = 1; } else { return 0; }; return x; }"
+        )
+        .is_empty());
+        assert_eq!(
+            check("fn f(c: bool) -> i32 { let mut x: i32; if c { x = 1; }; return x; }").len(),
+            1
+        );
+        assert_eq!(
+            check("fn f(c: bool) -> i32 { let mut x: i32; while c { x = 1; }; return x; }").len(),
+            1
+        );
+        assert!(check(
+            "fn f(c: bool) -> i32 { let mut x: i32; do { x = 1; } while c && x > 0; return x; }"
+        )
+        .is_empty());
+        assert_eq!(
+            check("fn f(n: i32) -> i32 { let mut x: i32; for i in 0..n { x = i; }; return x; }")
+                .len(),
+            1
+        );
+        // The loop variable is initialized by the loop
+        assert!(check(
+            "fn f(n: i32) -> i32 { let mut s: i32 = 0; for i in 0..n { s = s + i; }; return s; }"
+        )
+        .is_empty());
+        assert_eq!(
+            check("fn f(c: bool) -> bool { let b: bool; return c && b; }").len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_check_assign_once() {
+        assert!(check("fn f() -> i32 { let x: i32; x = 5; return x; }").is_empty());
+        assert!(check(
+            "fn f(c: bool) -> i32 { let x: i32; if c { x = 1; } else { x = 2; }; return x; }"
+        )
+        .is_empty());
+
+        let errors = check("fn f() -> i32 { let x: i32 = 1; x = 2; return x; }");
+        assert!(matches!(
+            errors.as_slice(),
+            [SemanticError::ImmutableAssignedTwice { .. }]
+        ));
+        assert!(errors[0]
+            .to_string()
+            .ends_with("-- consider adding `mut` at 1:21"));
+        assert_eq!(
+            check("fn f(c: bool) { let x: i32; if c { x = 1; }; x = 2; }").len(),
+            1
+        );
+        assert_eq!(check("fn f(x: i32) { x = 2; }").len(), 1);
+        assert_eq!(
+            check("fn f(c: bool) { let x: i32; while c { x = 1; }; }").len(),
+            1
+        );
+        assert!(check("fn f(c: bool) { let mut x: i32; while c { x = 1; }; }").is_empty());
+        assert_eq!(
+            check("fn f(c: bool) { let x: i32; do { x = 1; } while c; }").len(),
+            1
+        );
+        assert!(matches!(
+            check("fn f(n: i32) { for i in 0..n { i = 1; }; }").as_slice(),
+            [SemanticError::ImmutableAssignedTwice { .. }]
+        ));
+    }
+
+    #[test]
+    fn test_check_borrow_mut() {
+        assert!(check("fn f() -> i32 { let mut x: i32 = 1; *&mut x = 2; return x; }").is_empty());
+        assert!(
+            check("fn f(mut x: i32) -> i32 { let r: &mut i32 = &mut x; *r = 2; return x; }")
+                .is_empty()
+        );
+        assert!(check("fn f() -> i32 { let x: i32 = 1; let r: &i32 = &x; return *r; }").is_empty());
+
+        let errors = check("fn f() { let x: i32 = 1; let r: &mut i32 = &mut x; }");
+        assert!(matches!(
+            errors.as_slice(),
+            [SemanticError::ImmutableBorrowedAsMutable { .. }]
+        ));
+        assert!(errors[0]
+            .to_string()
+            .ends_with("-- consider adding `mut` at 1:14"));
+        assert_eq!(check("fn f(x: i32) { *&mut x = 2; }").len(), 1);
+        // The borrowed variable is read as well
+        assert!(matches!(
+            check("fn f() { let mut x: i32; let r: &mut i32 = &mut x; }").as_slice(),
+            [SemanticError::UninitializedVariable { .. }]
+        ));
+        // Borrowing a value stored in a temporary doesn't need a mutable variable
+        assert!(check("fn f(x: i32) { let r: &mut i32 = &mut (x + 1); }").is_empty());
+    }
+}
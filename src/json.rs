@@ -0,0 +1,38 @@
+//! A module containing the helpers for writing the JSON outputs
+//! (e.g. the [token dump](crate::lexer::dump::TokenDump::to_json)).
+
+use std::fmt::Write;
+
+use crate::token::{Position, Span};
+
+/// Returns the given value as a JSON string literal.
+pub(crate) fn string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(result, "\\u{:04x}", c as u32);
+            }
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Returns the given span as a JSON object with the `start` and `end` positions
+/// (each with its `line` and `column`).
+pub(crate) fn span(span: Span) -> String {
+    let position =
+        |pos: Position| format!(r#"{{"line":{},"column":{}}}"#, pos.line(), pos.column());
+    format!(
+        r#"{{"start":{},"end":{}}}"#,
+        position(span.start()),
+        position(span.end())
+    )
+}
@@ -1,18 +1,20 @@
 //! The module containing the lexer for the μRust compiler.
 
+use std::collections::VecDeque;
 use std::io;
-use std::iter::Peekable;
 use std::path::Path;
 use std::rc::Rc;
 
 use fallible_iterator::FallibleIterator;
 use unicode_ident::{is_xid_continue, is_xid_start};
 
-use crate::token::{Position, Span, Token, TokenType};
+use crate::cancel::CancellationToken;
+use crate::token::{NumericSuffix, Position, Span, Token, TokenType};
 
 use self::error::*;
 use self::file_reader::*;
 
+pub mod dump;
 pub mod error;
 mod file_reader;
 mod helper;
@@ -25,6 +27,19 @@ pub type Result<T> = std::result::Result<T, LexerError>;
 /// The `Lexer` is a [FallibleIterator] with [Token] as items and [LexerError] as error type.
 /// It reads a file character by character and produces a stream of tokens.
 /// The last token produced will always be [TokenType::EOF].
+///
+/// By default, the lexer is fused after an error, i.e. every following call to
+/// [next](FallibleIterator::next) returns `Ok(None)`. In the [recovering](Lexer::recovering) mode,
+/// the offending characters (i.e. the span of the error) are skipped instead,
+/// and the lexing continues after them.
+///
+/// The invalid UTF-8 sequences in the file are reported as [errors](LexerErrorKind::InvalidUtf8)
+/// before the token containing them, in which they are replaced with
+/// [`char::REPLACEMENT_CHARACTER`]. In the [lossy](Lexer::lossy) mode, they are only replaced.
+///
+/// If the lexer has a [cancellation token](Lexer::with_cancellation), the token is checked
+/// before every lexed token, and the lexer is fused after an [interruption](LexerErrorKind::Interrupted)
+/// in either mode.
 /// # Examples
 ///
 /// The Lexer would typically be used in a parser by manually calling [next](FallibleIterator::next) on it.
@@ -70,9 +85,16 @@ pub type Result<T> = std::result::Result<T, LexerError>;
 /// ```
 pub struct Lexer {
     filename: Rc<str>,
+    path: Rc<Path>,
     position: Position,
-    iter: Peekable<FileReaderIter>,
+    iter: FileReaderIter,
+    /// The results to return before lexing the next token,
+    /// i.e. the errors about invalid UTF-8 followed by the token containing them.
+    pending: VecDeque<Result<Token>>,
     finished: bool,
+    recovering: bool,
+    lossy: bool,
+    cancellation: Option<CancellationToken>,
 }
 
 impl Lexer {
@@ -80,15 +102,73 @@ impl Lexer {
     /// Creates a new `Lexer` that will lex the file at the given path.
     pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Lexer> {
         let filename = helper::filename_from_path(&path)?;
-        let iter = FileReader::new(path).try_iter()?.peekable();
+        let path_owned = path.as_ref().into();
+        let iter = FileReader::new(path).try_iter()?;
         Ok(Lexer {
             filename: filename.into(),
+            path: path_owned,
             position: Position::new(),
             iter,
+            pending: VecDeque::new(),
             finished: false,
+            recovering: false,
+            lossy: false,
+            cancellation: None,
         })
     }
 
+    /// Switches the lexer to the recovering mode, in which the lexing continues after an error.
+    pub fn recovering(self) -> Lexer {
+        Lexer {
+            recovering: true,
+            ..self
+        }
+    }
+
+    /// Switches the lexer to the lossy mode, in which the invalid UTF-8 sequences are replaced
+    /// with [`char::REPLACEMENT_CHARACTER`] without reporting any errors.
+    pub fn lossy(self) -> Lexer {
+        Lexer {
+            lossy: true,
+            ..self
+        }
+    }
+
+    /// Sets the token that aborts the lexing when it is cancelled or times out.
+    pub fn with_cancellation(self, cancellation: CancellationToken) -> Lexer {
+        Lexer {
+            cancellation: Some(cancellation),
+            ..self
+        }
+    }
+
+    /// Returns whether the lexer is in the [recovering](Lexer::recovering) mode.
+    pub fn is_recovering(&self) -> bool {
+        self.recovering
+    }
+
+    /// Returns whether the lexer is in the [lossy](Lexer::lossy) mode.
+    pub fn is_lossy(&self) -> bool {
+        self.lossy
+    }
+
+    /// Lexes the whole file in the [recovering](Lexer::recovering) mode, consuming the `Lexer`.
+    ///
+    /// Returns all the tokens (ending with [TokenType::EOF]) and all the errors encountered.
+    pub fn tokenize(self) -> (Vec<Token>, Vec<LexerError>) {
+        let mut lexer = self.recovering();
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        loop {
+            match lexer.next() {
+                Ok(Some(token)) => tokens.push(token),
+                Ok(None) => break,
+                Err(err) => errors.push(err),
+            }
+        }
+        (tokens, errors)
+    }
+
     /// Returns the name of the file being lexed.
     pub fn get_filename(&self) -> &str {
         &self.filename
@@ -99,6 +179,11 @@ impl Lexer {
         self.filename.clone()
     }
 
+    /// Returns the path of the file being lexed, as it was given to the lexer.
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
     //TODO Add tests
     /// Returns the next token from the source file.
     fn next_token(&mut self) -> Result<Token> {
@@ -106,25 +191,32 @@ impl Lexer {
         self.collect_while(helper::is_whitespace, &mut String::new());
 
         let start_pos = self.position;
-        let c = match self.iter.next() {
+        let c = match self.next_char() {
             Some(c) => c,
             None => {
                 self.finished = true;
                 return Ok(Token::eof(self.position));
             }
         };
-        self.position.col_inc();
+
+        // Range operators
+        if c == '.' && self.next_char_if(|n| n == '.').is_some() {
+            let tt = match self.next_char_if(|n| n == '=') {
+                Some(_) => TokenType::DotDotEq,
+                None => TokenType::DotDot,
+            };
+            return Ok(Token::new(tt, start_pos, self.position));
+        }
 
         // Two-character delimiters or operators
         if let Some(n) = self.iter.peek() {
             let n = *n;
             let tt = TokenType::extract_keyword_or_symbol(match (c, n) {
                 ('-', '>') => "->",
+                (':', ':') => "::",
                 ('/', '/') => {
                     // Skip comment
-                    while self.iter.next_if(|&c| !helper::is_new_line(c)).is_some() {
-                        self.position.col_inc();
-                    }
+                    while self.next_char_if(|c| !helper::is_new_line(c)).is_some() {}
                     return self.next_token();
                 }
                 ('&', '&') => "&&",
@@ -136,8 +228,7 @@ impl Lexer {
                 _ => "",
             });
             if let Some(tt) = tt {
-                self.iter.next();
-                self.position.col_inc();
+                self.next_char();
                 let token = Token::new(tt, start_pos, self.position);
                 return Ok(token);
             }
@@ -153,52 +244,63 @@ impl Lexer {
         // Number literals
         if helper::is_digit(c) {
             let mut num_str = c.to_string();
-            self.collect_while(|c| is_xid_continue(c) || c == '.', &mut num_str);
+            self.collect_while(is_xid_continue, &mut num_str);
+            // A `.` followed by another one is a range operator (e.g. in `0..n`)
+            while self.iter.peek() == Some(&'.') && self.iter.peek_second() != Some(&'.') {
+                self.next_char();
+                num_str.push('.');
+                self.collect_while(is_xid_continue, &mut num_str);
+            }
+            let span = Span::new(start_pos, self.position);
+
+            let suffix = match helper::suffix_start(&num_str) {
+                Some(index) => {
+                    let suffix_str = num_str.split_off(index);
+                    match NumericSuffix::from_name(&suffix_str) {
+                        Some(suffix) if !suffix.is_float() && num_str.contains('.') => None,
+                        suffix => suffix,
+                    }
+                    .map(Some)
+                    .ok_or_else(|| {
+                        let err_kind = LexerErrorKind::InvalidSuffix(suffix_str.into_boxed_str());
+                        LexerError::new(err_kind, span)
+                    })?
+                }
+                None => None,
+            };
 
-            let tt = if num_str.contains('.') {
+            let tt = if num_str.contains('.') || suffix.is_some_and(NumericSuffix::is_float) {
                 // Floating point literal
-                let float_val = num_str.parse::<f64>().map_err(|_| {
-                    let err_kind = LexerErrorKind::InvalidFloatLiteral(num_str.into_boxed_str());
-                    LexerError::new(err_kind, Span::new(start_pos, self.position))
-                })?;
-                TokenType::FloatLit(float_val)
+                let float_val = match num_str.parse::<f64>() {
+                    Ok(val) => val,
+                    Err(_) => {
+                        let err_kind =
+                            LexerErrorKind::InvalidFloatLiteral(num_str.into_boxed_str());
+                        return Err(LexerError::new(
+                            err_kind,
+                            Span::new(start_pos, self.position),
+                        ));
+                    }
+                };
+                TokenType::FloatLit(float_val, num_str.into(), suffix)
             } else {
                 // Integer literal
-                let int_val = num_str.parse::<i32>().map_err(|_| {
+                let int_val = num_str.parse::<i128>().map_err(|_| {
                     let err_kind = LexerErrorKind::InvalidIntLiteral(num_str.into_boxed_str());
                     LexerError::new(err_kind, Span::new(start_pos, self.position))
                 })?;
-                TokenType::IntLit(int_val)
+                TokenType::IntLit(int_val, suffix)
             };
 
             return Ok(Token::new(tt, start_pos, self.position));
         }
 
-        // String literals (not supported; can only appear as an ABI) //TODO Add support for string literals
+        // String literals
         if c == '"' {
-            let mut str_lit = String::new();
-            let mut escaped = false;
-            while let Some(c) = self.iter.next_if(|&c| c != '"' || escaped) {
-                if c == '\\' {
-                    escaped = !escaped;
-                }
-                str_lit.push(c);
-                self.position.col_inc();
-            }
-            if self.iter.next().is_none() {
-                let err_kind = LexerErrorKind::UnterminatedStringLiteral;
-                return Err(LexerError::new(
-                    err_kind,
-                    Span::new(start_pos, self.position),
-                ));
-            }
-            self.position.col_inc();
-
-            return Ok(Token::new(
-                TokenType::Abi(str_lit.into()),
-                start_pos,
-                self.position,
-            ));
+            return self.lex_string(start_pos);
+        }
+        if c == 'r' && matches!(self.iter.peek(), Some('"' | '#')) {
+            return self.lex_raw_string(start_pos);
         }
 
         // Identifier or keyword
@@ -220,14 +322,178 @@ impl Lexer {
         ))
     }
 
+    /// Lexes the rest of a string literal (after the opening quote), processing the escape sequences.
+    ///
+    /// The whole literal is consumed even if it contains an invalid escape sequence,
+    /// in which case the error spans only the first such sequence.
+    fn lex_string(&mut self, start_pos: Position) -> Result<Token> {
+        let mut value = String::new();
+        let mut error = None;
+        loop {
+            let escape_pos = self.position;
+            match self.next_char() {
+                None => {
+                    let err_kind = LexerErrorKind::UnterminatedStringLiteral;
+                    return Err(LexerError::new(
+                        err_kind,
+                        Span::new(start_pos, self.position),
+                    ));
+                }
+                Some('"') => break,
+                Some('\\') => match self.lex_escape() {
+                    Ok(c) => value.push(c),
+                    Err(err_kind) => {
+                        let span = Span::new(escape_pos, self.position);
+                        error.get_or_insert(LexerError::new(err_kind, span));
+                    }
+                },
+                Some(c) => value.push(c),
+            }
+        }
+
+        match error {
+            Some(err) => Err(err),
+            None => Ok(Token::new(
+                TokenType::StrLit(value.into()),
+                start_pos,
+                self.position,
+            )),
+        }
+    }
+
+    /// Lexes the rest of an escape sequence (after the backslash), returning the escaped character.
+    ///
+    /// The supported escape sequences are `\"`, `\'`, `\\`, `\n`, `\r`, `\t`, `\0`
+    /// and `\u{...}` (with 1 to 6 hexadecimal digits of a Unicode scalar value).
+    fn lex_escape(&mut self) -> std::result::Result<char, LexerErrorKind> {
+        let c = match self.next_char() {
+            Some(c) => c,
+            None => return Err(LexerErrorKind::InvalidEscape("\\".into())),
+        };
+        match c {
+            '"' => Ok('"'),
+            '\'' => Ok('\''),
+            '\\' => Ok('\\'),
+            'n' => Ok('\n'),
+            'r' => Ok('\r'),
+            't' => Ok('\t'),
+            '0' => Ok('\0'),
+            'u' => {
+                let mut escape = String::from("\\u");
+                let value = self.lex_unicode_escape(&mut escape);
+                value.ok_or_else(|| LexerErrorKind::InvalidEscape(escape.into_boxed_str()))
+            }
+            c => Err(LexerErrorKind::InvalidEscape(format!("\\{}", c).into())),
+        }
+    }
+
+    /// Lexes the rest of a Unicode escape sequence (after `\u`), appending the consumed
+    /// characters to `escape`. Returns `None` if the sequence is malformed.
+    fn lex_unicode_escape(&mut self, escape: &mut String) -> Option<char> {
+        self.next_char_if(|c| c == '{')?;
+        escape.push('{');
+        let mut digits = String::new();
+        self.collect_while(|c| c.is_ascii_hexdigit(), &mut digits);
+        escape.push_str(&digits);
+        self.next_char_if(|c| c == '}')?;
+        escape.push('}');
+
+        if digits.is_empty() || digits.len() > 6 {
+            return None;
+        }
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .and_then(char::from_u32)
+    }
+
+    /// Lexes the rest of a raw string literal (after the `r`), i.e. `r"..."` or `r#"..."#`
+    /// with any number of `#`. The escape sequences are not processed in raw strings.
+    fn lex_raw_string(&mut self, start_pos: Position) -> Result<Token> {
+        let mut hashes = 0;
+        while self.next_char_if(|c| c == '#').is_some() {
+            hashes += 1;
+        }
+        if self.next_char_if(|c| c == '"').is_none() {
+            let err_kind = LexerErrorKind::InvalidRawStringLiteral;
+            return Err(LexerError::new(
+                err_kind,
+                Span::new(start_pos, self.position),
+            ));
+        }
+
+        let mut value = String::new();
+        loop {
+            match self.next_char() {
+                None => {
+                    let err_kind = LexerErrorKind::UnterminatedStringLiteral;
+                    return Err(LexerError::new(
+                        err_kind,
+                        Span::new(start_pos, self.position),
+                    ));
+                }
+                Some('"') => {
+                    let mut closing = 0;
+                    while closing < hashes && self.next_char_if(|c| c == '#').is_some() {
+                        closing += 1;
+                    }
+                    if closing == hashes {
+                        break;
+                    }
+                    value.push('"');
+                    value.extend(std::iter::repeat('#').take(closing));
+                }
+                Some(c) => value.push(c),
+            }
+        }
+
+        Ok(Token::new(
+            TokenType::StrLit(value.into()),
+            start_pos,
+            self.position,
+        ))
+    }
+
     /// Collects characters from `self.iter` to `s` while the predicate `pred` is true,
     /// updating `self.position` accordingly.
     fn collect_while(&mut self, pred: fn(char) -> bool, s: &mut String) {
-        while let Some(c) = self.iter.next_if(|&c| pred(c)) {
+        while let Some(c) = self.next_char_if(pred) {
             s.push(c);
-            self.position.col_inc();
-            if helper::is_new_line(c) {
-                self.position.line_inc();
+        }
+    }
+
+    /// Consumes the next character from `self.iter`, [advancing](Position::advance)
+    /// `self.position` past it.
+    ///
+    /// All the characters should be consumed through this method (or [Lexer::next_char_if]),
+    /// so the positions stay correct for the tokens spanning multiple lines.
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.iter.next()?;
+        self.advance(c);
+        Some(c)
+    }
+
+    /// Consumes the next character from `self.iter` if the predicate `pred` is true for it,
+    /// [advancing](Position::advance) `self.position` past it.
+    fn next_char_if(&mut self, pred: impl FnOnce(char) -> bool) -> Option<char> {
+        let c = self.iter.next_if(|&c| pred(c))?;
+        self.advance(c);
+        Some(c)
+    }
+
+    /// Advances `self.position` past the consumed character `c`,
+    /// queueing an error if it replaced an invalid UTF-8 sequence.
+    fn advance(&mut self, c: char) {
+        let start_pos = self.position;
+        self.position.advance(c);
+        if let Some(seq) = self.iter.take_invalid() {
+            if !self.lossy {
+                let offset = seq.offset();
+                let err_kind = LexerErrorKind::InvalidUtf8 {
+                    bytes: seq.into_bytes(),
+                    offset,
+                };
+                let span = Span::new(start_pos, self.position);
+                self.pending.push_back(Err(LexerError::new(err_kind, span)));
             }
         }
     }
@@ -238,10 +504,341 @@ impl FallibleIterator for Lexer {
     type Error = LexerError;
 
     fn next(&mut self) -> std::result::Result<Option<Self::Item>, Self::Error> {
-        if self.finished {
-            return Ok(None);
+        let result = match self.pending.pop_front() {
+            Some(result) => result,
+            None => {
+                if self.finished {
+                    return Ok(None);
+                }
+
+                if let Some(Err(reason)) = self.cancellation.as_ref().map(CancellationToken::check)
+                {
+                    self.finished = true;
+                    let err_kind = LexerErrorKind::Interrupted(reason);
+                    return Err(LexerError::new(
+                        err_kind,
+                        Span::new(self.position, self.position),
+                    ));
+                }
+
+                // The errors queued while lexing the token are returned first
+                let result = self.next_token();
+                match self.pending.pop_front() {
+                    Some(err) => {
+                        // The replacement character of an invalid sequence isn't reported
+                        // as an unknown token as well
+                        let unknown = LexerErrorKind::UnknownToken(char::REPLACEMENT_CHARACTER);
+                        if !matches!(&result, Err(e) if *e.kind() == unknown) {
+                            self.pending.push_back(result);
+                        }
+                        err
+                    }
+                    None => result,
+                }
+            }
+        };
+
+        #[cfg(feature = "tracing")]
+        if let Ok(token) = &result {
+            crate::trace::event!(TRACE, %token, "lexed a token");
+        }
+        if result.is_err() && !self.recovering {
+            self.finished = true;
+            self.pending.clear();
         }
+        result.map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    fn lexer(source: impl AsRef<[u8]>, file_name: &str) -> Lexer {
+        let dir = TempDir::new("lexer");
+        let path = dir.write(file_name, source);
+        // The file has already been opened, so the directory can be removed
+        Lexer::new(path).unwrap()
+    }
+
+    #[test]
+    fn test_fused_after_error() {
+        let mut lexer = lexer("let $ x", "mrs_test_lexer_fused.mrs");
+        assert!(matches!(lexer.next(), Ok(Some(t)) if *t.ty() == TokenType::Let));
+        assert!(lexer.next().is_err());
+        assert_eq!(lexer.next(), Ok(None));
+        assert_eq!(lexer.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_recovering() {
+        let lexer = lexer(
+            "let $ x = 999999999999999999999999999999999999999999 + 1.2.3;\n@ \"abc",
+            "mrs_test_lexer_recovering.mrs",
+        );
+        let (tokens, errors) = lexer.tokenize();
+
+        let kinds: Vec<&LexerErrorKind> = errors.iter().map(LexerError::kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &LexerErrorKind::UnknownToken('$'),
+                &LexerErrorKind::InvalidIntLiteral(
+                    "999999999999999999999999999999999999999999".into()
+                ),
+                &LexerErrorKind::InvalidFloatLiteral("1.2.3".into()),
+                &LexerErrorKind::UnknownToken('@'),
+                &LexerErrorKind::UnterminatedStringLiteral,
+            ]
+        );
+        assert_eq!(
+            errors[0].span(),
+            Span::new(Position::new_at(1, 5), Position::new_at(1, 6))
+        );
+
+        let types: Vec<&TokenType> = tokens.iter().map(Token::ty).collect();
+        assert_eq!(
+            types,
+            vec![
+                &TokenType::Let,
+                &TokenType::Ident("x".into()),
+                &TokenType::Assign,
+                &TokenType::Plus,
+                &TokenType::Semi,
+                &TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_literal_suffixes() {
+        let lexer = lexer(
+            "1i32 2f64 2.50f64 3.0 1.5i32 7u8 1.5u16 4f32 9u128",
+            "mrs_test_lexer_suffixes.mrs",
+        );
+        let (tokens, errors) = lexer.tokenize();
+
+        let kinds: Vec<&LexerErrorKind> = errors.iter().map(LexerError::kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                &LexerErrorKind::InvalidSuffix("i32".into()),
+                &LexerErrorKind::InvalidSuffix("u16".into()),
+                &LexerErrorKind::InvalidSuffix("u128".into()),
+            ]
+        );
+        let types: Vec<&TokenType> = tokens.iter().map(Token::ty).collect();
+        assert_eq!(
+            types,
+            vec![
+                &TokenType::IntLit(1, Some(NumericSuffix::I32)),
+                &TokenType::FloatLit(2.0, "2".into(), Some(NumericSuffix::F64)),
+                &TokenType::FloatLit(2.5, "2.50".into(), Some(NumericSuffix::F64)),
+                &TokenType::FloatLit(3.0, "3.0".into(), None),
+                &TokenType::IntLit(7, Some(NumericSuffix::U8)),
+                &TokenType::FloatLit(4.0, "4".into(), Some(NumericSuffix::F32)),
+                &TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_range_operators() {
+        let lexer = lexer("0..n 1..=5 1.5..2 a..=b", "mrs_test_lexer_ranges.mrs");
+        let (tokens, errors) = lexer.tokenize();
+        assert!(errors.is_empty());
+
+        let types: Vec<&TokenType> = tokens.iter().map(Token::ty).collect();
+        assert_eq!(
+            types,
+            vec![
+                &TokenType::IntLit(0, None),
+                &TokenType::DotDot,
+                &TokenType::Ident("n".into()),
+                &TokenType::IntLit(1, None),
+                &TokenType::DotDotEq,
+                &TokenType::IntLit(5, None),
+                // A dot followed by another one ends a number
+                &TokenType::FloatLit(1.5, "1.5".into(), None),
+                &TokenType::DotDot,
+                &TokenType::IntLit(2, None),
+                &TokenType::Ident("a".into()),
+                &TokenType::DotDotEq,
+                &TokenType::Ident("b".into()),
+                &TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tuple_indices() {
+        let lexer = lexer("t.0 t.0.1", "mrs_test_lexer_tuple_indices.mrs");
+        let (tokens, errors) = lexer.tokenize();
+        assert!(errors.is_empty());
+
+        let types: Vec<&TokenType> = tokens.iter().map(Token::ty).collect();
+        assert_eq!(
+            types,
+            vec![
+                &TokenType::Ident("t".into()),
+                &TokenType::Dot,
+                &TokenType::IntLit(0, None),
+                &TokenType::Ident("t".into()),
+                &TokenType::Dot,
+                // The nested indices are split by the parser
+                &TokenType::FloatLit(0.1, "0.1".into(), None),
+                &TokenType::EOF,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multi_line_tokens() {
+        let lexer = lexer(
+            "extern \"C\nD\" // comment\n\t{\r\n}\n\"a\nb",
+            "mrs_test_lexer_multi_line.mrs",
+        );
+        let (tokens, errors) = lexer.tokenize();
+
+        let spans: Vec<Span> = tokens.iter().map(Token::span).collect();
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(Position::new_at(1, 1), Position::new_at(1, 7)),
+                Span::new(Position::new_at(1, 8), Position::new_at(2, 3)),
+                Span::new(Position::new_at(3, 2), Position::new_at(3, 3)),
+                Span::new(Position::new_at(4, 1), Position::new_at(4, 2)),
+                Span::new(Position::new_at(6, 2), Position::new_at(6, 2)),
+            ]
+        );
+        assert_eq!(tokens[1].ty(), &TokenType::StrLit("C\nD".into()));
+        assert_eq!(
+            errors[0].span(),
+            Span::new(Position::new_at(5, 1), Position::new_at(6, 2))
+        );
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let lexer = lexer(
+            r####""a\"b\\c\n\u{1F600}" r"\n" r##"x"#y"## "\q\u{110000}" r#x "\u{41" "####,
+            "mrs_test_lexer_strings.mrs",
+        );
+        let (tokens, errors) = lexer.tokenize();
+
+        let types: Vec<&TokenType> = tokens.iter().map(Token::ty).collect();
+        assert_eq!(
+            types,
+            vec![
+                &TokenType::StrLit("a\"b\\c\n\u{1F600}".into()),
+                &TokenType::StrLit("\\n".into()),
+                &TokenType::StrLit("x\"#y".into()),
+                &TokenType::Ident("x".into()),
+                &TokenType::EOF,
+            ]
+        );
+        assert_eq!(
+            tokens[2].span(),
+            Span::new(Position::new_at(1, 28), Position::new_at(1, 39))
+        );
+
+        let errors: Vec<(&LexerErrorKind, Span)> =
+            errors.iter().map(|err| (err.kind(), err.span())).collect();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    &LexerErrorKind::InvalidEscape("\\q".into()),
+                    Span::new(Position::new_at(1, 41), Position::new_at(1, 43))
+                ),
+                (
+                    &LexerErrorKind::InvalidRawStringLiteral,
+                    Span::new(Position::new_at(1, 55), Position::new_at(1, 57))
+                ),
+                (
+                    &LexerErrorKind::InvalidEscape("\\u{41".into()),
+                    Span::new(Position::new_at(1, 60), Position::new_at(1, 65))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let source = b"let x\xFF = \"a\xC0b\";";
+        let file_name = "mrs_test_lexer_utf8.mrs";
+
+        let (tokens, errors) = lexer(source, file_name).tokenize();
+        let errors: Vec<(&LexerErrorKind, Span)> =
+            errors.iter().map(|err| (err.kind(), err.span())).collect();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    &LexerErrorKind::InvalidUtf8 {
+                        bytes: b"\xFF"[..].into(),
+                        offset: 5
+                    },
+                    Span::new(Position::new_at(1, 6), Position::new_at(1, 7))
+                ),
+                (
+                    &LexerErrorKind::InvalidUtf8 {
+                        bytes: b"\xC0"[..].into(),
+                        offset: 11
+                    },
+                    Span::new(Position::new_at(1, 12), Position::new_at(1, 13))
+                ),
+            ]
+        );
+        let types: Vec<&TokenType> = tokens.iter().map(Token::ty).collect();
+        assert_eq!(
+            types,
+            vec![
+                &TokenType::Let,
+                &TokenType::Ident("x".into()),
+                &TokenType::Assign,
+                &TokenType::StrLit("a\u{FFFD}b".into()),
+                &TokenType::Semi,
+                &TokenType::EOF,
+            ]
+        );
+
+        // The replacement characters are lexed like any other characters
+        let (tokens, errors) = lexer(source, file_name).lossy().tokenize();
+        let kinds: Vec<&LexerErrorKind> = errors.iter().map(LexerError::kind).collect();
+        assert_eq!(
+            kinds,
+            vec![&LexerErrorKind::UnknownToken(char::REPLACEMENT_CHARACTER)]
+        );
+        assert_eq!(tokens[3].ty(), &TokenType::StrLit("a\u{FFFD}b".into()));
+
+        // The lexer is fused after the first error
+        let mut lexer = lexer(source, file_name);
+        assert_eq!(lexer.by_ref().take(2).count().unwrap(), 2);
+        assert!(matches!(
+            lexer.next(),
+            Err(err) if matches!(err.kind(), LexerErrorKind::InvalidUtf8 { offset: 5, .. })
+        ));
+        assert_eq!(lexer.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let token = CancellationToken::new();
+        let mut lexer = lexer("let x;", "mrs_test_lexer_cancellation.mrs")
+            .recovering()
+            .with_cancellation(token.clone());
+        assert!(matches!(lexer.next(), Ok(Some(t)) if *t.ty() == TokenType::Let));
 
-        self.next_token().map(Some)
+        token.cancel();
+        let err = lexer.next().unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &LexerErrorKind::Interrupted(crate::cancel::Interrupted::Cancelled)
+        );
+        assert_eq!(err.span().start(), Position::new_at(1, 4));
+        assert_eq!(lexer.next(), Ok(None));
     }
 }
@@ -0,0 +1,159 @@
+//! A module containing the dump of the token stream of a file, independent of the parser.
+//!
+//! It is useful for learning how the [lexer](crate::lexer::Lexer) works
+//! and for debugging the tokenization issues.
+
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+use crate::json;
+use crate::lexer::error::LexerError;
+use crate::lexer::Lexer;
+use crate::token::Token;
+
+/// The format of a [`TokenDump`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DumpFormat {
+    /// One token per line, preceded by its span (the default).
+    #[default]
+    Text,
+    /// A JSON object with the list of the tokens and the list of the errors.
+    Json,
+}
+
+impl DumpFormat {
+    /// Returns the format with the given name (`text` or `json`), if any.
+    pub fn from_name(name: &str) -> Option<DumpFormat> {
+        match name {
+            "text" => Some(DumpFormat::Text),
+            "json" => Some(DumpFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// All the tokens of a file together with the errors encountered while lexing it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenDump {
+    tokens: Vec<Token>,
+    errors: Vec<LexerError>,
+}
+
+impl TokenDump {
+    /// Lexes the file at the given path in the [recovering](Lexer::recovering) mode.
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<TokenDump> {
+        Ok(TokenDump::from_lexer(Lexer::new(path)?))
+    }
+
+    /// Lexes the rest of the file using the given lexer (see [`Lexer::tokenize`]).
+    pub fn from_lexer(lexer: Lexer) -> TokenDump {
+        let (tokens, errors) = lexer.tokenize();
+        TokenDump { tokens, errors }
+    }
+
+    /// Returns the tokens of the file (ending with the EOF token).
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Returns the errors encountered while lexing the file.
+    pub fn errors(&self) -> &[LexerError] {
+        &self.errors
+    }
+
+    /// Formats the dump in the given format.
+    pub fn format(&self, format: DumpFormat) -> String {
+        match format {
+            DumpFormat::Text => self.to_text(),
+            DumpFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Formats the dump as text, i.e. every token in its own line (followed by the errors).
+    ///
+    /// # Examples
+    ///
+    /// ```text
+    /// <1:1>-<1:4> 'let'
+    /// <1:5>-<1:6> identifier 'x'
+    /// error: Unknown token "36" at <1:7>-<1:8>
+    /// ```
+    pub fn to_text(&self) -> String {
+        let mut result = String::new();
+        for token in &self.tokens {
+            let _ = writeln!(result, "{} {}", token.span(), token.ty());
+        }
+        for error in &self.errors {
+            let _ = writeln!(result, "error: {}", error);
+        }
+        result
+    }
+
+    /// Formats the dump as a JSON object with the fields `tokens`
+    /// (with the `kind`, `token` and `span` of every token) and `errors`
+    /// (with the `message` and `span` of every error).
+    pub fn to_json(&self) -> String {
+        let tokens = self.tokens.iter().map(|token| {
+            let kind = format!("{:?}", token.ty());
+            let kind = kind.split('(').next().unwrap_or_default();
+            format!(
+                r#"{{"kind":{},"token":{},"span":{}}}"#,
+                json::string(kind),
+                json::string(&token.ty().to_string()),
+                json::span(token.span())
+            )
+        });
+        let errors = self.errors.iter().map(|error| {
+            format!(
+                r#"{{"message":{},"span":{}}}"#,
+                json::string(&error.to_string()),
+                json::span(error.span())
+            )
+        });
+        format!(
+            r#"{{"tokens":[{}],"errors":[{}]}}"#,
+            tokens.collect::<Vec<_>>().join(","),
+            errors.collect::<Vec<_>>().join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_token_dump() {
+        let dir = TempDir::new("token_dump");
+        let path = dir.join("main.mrs");
+        fs::write(&path, "let x = \"C\";\n$").unwrap();
+        let dump = TokenDump::new(&path).unwrap();
+
+        assert_eq!(dump.tokens().len(), 6);
+        assert_eq!(
+            dump.format(DumpFormat::Text),
+            "<1:1>-<1:4> 'let'\n\
+            <1:5>-<1:6> identifier 'x'\n\
+            <1:7>-<1:8> '='\n\
+            <1:9>-<1:12> string literal '\"C\"'\n\
+            <1:12>-<1:13> ';'\n\
+            <2:2>-<2:2> end of file\n\
+            error: Unknown token \"36\" at <2:1>-<2:2>\n"
+        );
+
+        let json = dump.format(DumpFormat::Json);
+        assert!(json.starts_with(
+            r#"{"tokens":[{"kind":"Let","token":"'let'","span":{"start":{"line":1,"column":1},"end":{"line":1,"column":4}}},"#
+        ));
+        assert!(json.contains(r#"{"kind":"StrLit","token":"string literal '\"C\"'","#));
+        assert!(json.ends_with(
+            r#""errors":[{"message":"Unknown token \"36\" at <2:1>-<2:2>","span":{"start":{"line":2,"column":1},"end":{"line":2,"column":2}}}]}"#
+        ));
+        assert_eq!(DumpFormat::from_name("json"), Some(DumpFormat::Json));
+        assert_eq!(DumpFormat::from_name("xml"), None);
+    }
+}
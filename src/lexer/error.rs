@@ -3,6 +3,7 @@
 use std::error::Error;
 use std::fmt;
 
+use crate::cancel::Interrupted;
 use crate::token::Span;
 
 /// The type of error that can occur during lexing.
@@ -12,10 +13,26 @@ pub enum LexerErrorKind {
     InvalidIntLiteral(Box<str>),
     /// The float literal has an invalid format.
     InvalidFloatLiteral(Box<str>),
+    /// The suffix of a number literal is unknown or doesn't match the literal
+    /// (e.g. `1.5i32`).
+    InvalidSuffix(Box<str>),
     /// The string literal is not terminated (i.e. it is missing a closing quote).
     UnterminatedStringLiteral,
+    /// The escape sequence in a string literal is unknown or malformed.
+    InvalidEscape(Box<str>),
+    /// The raw string literal prefix (`r` followed by any number of `#`) is not followed by a quote.
+    InvalidRawStringLiteral,
     /// An unknown token was encountered.
     UnknownToken(char),
+    /// The file contains an invalid UTF-8 sequence.
+    InvalidUtf8 {
+        /// The bytes of the sequence.
+        bytes: Box<[u8]>,
+        /// The offset of the first byte of the sequence from the start of the file.
+        offset: usize,
+    },
+    /// The lexing was aborted by a [cancellation token](crate::cancel::CancellationToken).
+    Interrupted(Interrupted),
 }
 
 /// An error that can occur during lexing.
@@ -30,6 +47,16 @@ impl LexerError {
     pub fn new(kind: LexerErrorKind, span: Span) -> LexerError {
         LexerError { kind, span }
     }
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> &LexerErrorKind {
+        &self.kind
+    }
+
+    /// Returns the span of the offending characters.
+    pub fn span(&self) -> Span {
+        self.span
+    }
 }
 
 impl fmt::Display for LexerError {
@@ -41,6 +68,13 @@ impl fmt::Display for LexerError {
             LexerErrorKind::InvalidFloatLiteral(s) => {
                 write!(f, r#"Invalid float literal "{}" at {}"#, s, self.span)
             }
+            LexerErrorKind::InvalidSuffix(s) => {
+                write!(
+                    f,
+                    r#"Invalid number literal suffix "{}" at {}"#,
+                    s, self.span
+                )
+            }
             LexerErrorKind::UnterminatedStringLiteral => {
                 write!(
                     f,
@@ -48,9 +82,32 @@ impl fmt::Display for LexerError {
                     self.span.start()
                 )
             }
+            LexerErrorKind::InvalidEscape(s) => {
+                write!(f, r#"Invalid escape sequence "{}" at {}"#, s, self.span)
+            }
+            LexerErrorKind::InvalidRawStringLiteral => {
+                write!(
+                    f,
+                    "Expected '\"' after the raw string prefix at {}",
+                    self.span
+                )
+            }
             LexerErrorKind::UnknownToken(c) => {
                 write!(f, r#"Unknown token "{}" at {}"#, *c as u32, self.span)
             }
+            LexerErrorKind::InvalidUtf8 { bytes, offset } => {
+                let bytes: Vec<String> = bytes.iter().map(|b| format!("{:#04X}", b)).collect();
+                write!(
+                    f,
+                    "Invalid UTF-8 sequence [{}] at byte {} ({})",
+                    bytes.join(", "),
+                    offset,
+                    self.span.start()
+                )
+            }
+            LexerErrorKind::Interrupted(reason) => {
+                write!(f, "{} at {}", reason, self.span.start())
+            }
         }
     }
 }
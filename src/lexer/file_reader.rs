@@ -1,7 +1,9 @@
+use std::collections::VecDeque;
 use std::fs::File;
 use std::io;
 use std::io::{BufRead, BufReader, ErrorKind};
 use std::path::Path;
+use std::str;
 
 /// A buffered reader that reads from a file.
 pub struct FileReader<P> {
@@ -25,17 +27,50 @@ impl<P: AsRef<Path>> FileReader<P> {
     }
 }
 
+/// An invalid UTF-8 sequence in a file, which is read as [`char::REPLACEMENT_CHARACTER`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidSequence {
+    /// The index of the replacement character in the buffer of its line.
+    index: usize,
+    /// The offset of the first byte of the sequence from the start of the file.
+    offset: usize,
+    /// The bytes of the sequence.
+    bytes: Box<[u8]>,
+}
+
+impl InvalidSequence {
+    /// Returns the offset of the first byte of the sequence from the start of the file.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Consumes the sequence, returning its bytes.
+    pub fn into_bytes(self) -> Box<[u8]> {
+        self.bytes
+    }
+}
+
 /// An iterator over the characters of a file.
+///
+/// Every invalid UTF-8 sequence (as defined by [`String::from_utf8_lossy`]) is read
+/// as a single [`char::REPLACEMENT_CHARACTER`], and can be [taken](FileReaderIter::take_invalid)
+/// right after the character is returned.
 pub struct FileReaderIter {
     /// A buffered reader that reads from a file.
     reader: BufReader<File>,
-    /// A buffer that contains the last line read from the file
+    /// A buffer that contains the bytes of the last line read from the file
     /// (stored here only as an optimization to prevent unnecessary allocations).
-    buffer_str: String,
+    buffer_bytes: Vec<u8>,
     /// A buffer that contains the characters of the last line read from the file.
     buffer: Vec<char>,
     /// The index of the next character to be returned by the iterator.
     buffer_index: usize,
+    /// The offset of the first byte of the next line from the start of the file.
+    line_offset: usize,
+    /// The invalid sequences of the last line that haven't been returned yet, in order.
+    invalid: VecDeque<InvalidSequence>,
+    /// The invalid sequence replaced by the last character returned by the iterator.
+    last_invalid: Option<InvalidSequence>,
     /// The number of times the iterator has retried reading from the file.
     retries: u8,
 }
@@ -46,54 +81,184 @@ impl FileReaderIter {
     pub fn new(reader: BufReader<File>) -> FileReaderIter {
         FileReaderIter {
             reader,
-            buffer_str: String::new(),
+            buffer_bytes: Vec::new(),
             buffer: Vec::new(),
             buffer_index: 0,
+            line_offset: 0,
+            invalid: VecDeque::new(),
+            last_invalid: None,
             retries: 0,
         }
     }
-}
 
-impl Iterator for FileReaderIter {
-    type Item = char;
+    /// Returns a reference to the next character without consuming it
+    /// (like [`Peekable::peek`](std::iter::Peekable::peek)).
+    pub fn peek(&mut self) -> Option<&char> {
+        if !self.fill_buffer() {
+            return None;
+        }
+        self.buffer.get(self.buffer_index)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        const RETRY_LIMIT: u8 = 10;
+    /// Returns a reference to the character after the next one without consuming any,
+    /// if they are both in the same line (i.e. `None` if the next character is a new line).
+    pub fn peek_second(&mut self) -> Option<&char> {
+        if !self.fill_buffer() {
+            return None;
+        }
+        self.buffer.get(self.buffer_index + 1)
+    }
 
-        // If there are still characters in the buffer, return the next one.
-        match self.buffer.get(self.buffer_index) {
-            Some(&c) => {
-                self.buffer_index += 1;
-                return Some(c);
-            }
-            None => {
-                self.buffer_str.clear();
-                self.buffer.clear();
-                self.buffer_index = 1;
-            }
+    /// Consumes and returns the next character if the predicate `func` is true for it
+    /// (like [`Peekable::next_if`](std::iter::Peekable::next_if)).
+    pub fn next_if(&mut self, func: impl FnOnce(&char) -> bool) -> Option<char> {
+        match self.peek() {
+            Some(c) if func(c) => self.next(),
+            _ => None,
         }
+    }
+
+    /// Takes the invalid sequence replaced by the last character returned by the iterator,
+    /// if it was a replacement character.
+    pub fn take_invalid(&mut self) -> Option<InvalidSequence> {
+        self.last_invalid.take()
+    }
 
-        // Otherwise, read the next line from the file.
-        match self.reader.read_line(&mut self.buffer_str) {
-            Ok(read_count) => {
-                self.retries = 0;
-                if read_count == 0 {
-                    None
-                } else {
-                    self.buffer = self.buffer_str.chars().collect();
-                    Some(self.buffer[0])
+    /// Reads the next line from the file if all the characters of the buffer have been returned.
+    ///
+    /// Returns `false` if there are no more characters to return.
+    fn fill_buffer(&mut self) -> bool {
+        const RETRY_LIMIT: u8 = 10;
+
+        while self.buffer_index >= self.buffer.len() {
+            self.buffer_bytes.clear();
+            self.buffer.clear();
+            self.buffer_index = 0;
+
+            match self.reader.read_until(b'\n', &mut self.buffer_bytes) {
+                Ok(0) => return false,
+                Ok(read_count) => {
+                    self.retries = 0;
+                    self.decode_line();
+                    self.line_offset += read_count;
                 }
-            }
-            Err(e) => match e.kind() {
                 // Retry reading from the file if error is recoverable.
-                ErrorKind::Interrupted if self.retries < RETRY_LIMIT => {
+                Err(e) if e.kind() == ErrorKind::Interrupted && self.retries < RETRY_LIMIT => {
                     self.retries += 1;
-                    self.next()
                 }
-                _ => None,
-            },
+                Err(_) => return false,
+            }
         }
+        true
+    }
+
+    /// Decodes the bytes of the last line into the buffer of characters,
+    /// replacing the invalid sequences.
+    fn decode_line(&mut self) {
+        let mut bytes = &self.buffer_bytes[..];
+        let mut offset = self.line_offset;
+        loop {
+            match str::from_utf8(bytes) {
+                Ok(valid) => {
+                    self.buffer.extend(valid.chars());
+                    return;
+                }
+                Err(e) => {
+                    let (valid, rest) = bytes.split_at(e.valid_up_to());
+                    let valid = str::from_utf8(valid).expect("The prefix should be valid UTF-8");
+                    self.buffer.extend(valid.chars());
+                    offset += valid.len();
+
+                    // An incomplete sequence can only be cut off by the end of the file
+                    let len = e.error_len().unwrap_or(rest.len());
+                    self.invalid.push_back(InvalidSequence {
+                        index: self.buffer.len(),
+                        offset,
+                        bytes: rest[..len].into(),
+                    });
+                    self.buffer.push(char::REPLACEMENT_CHARACTER);
+                    offset += len;
+                    bytes = &rest[len..];
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for FileReaderIter {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.last_invalid = None;
+        if !self.fill_buffer() {
+            return None;
+        }
+
+        let c = self.buffer[self.buffer_index];
+        if self
+            .invalid
+            .front()
+            .is_some_and(|seq| seq.index == self.buffer_index)
+        {
+            self.last_invalid = self.invalid.pop_front();
+        }
+        self.buffer_index += 1;
+        Some(c)
     }
 }
 
-//TODO Add tests
+#[cfg(test)]
+mod tests {
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_invalid_utf8() {
+        let dir = TempDir::new("file_reader_utf8");
+        let path = dir.join("main.mrs");
+        fs::write(&path, b"a\xFFb\n\xE2\x82c \xF0\x9F\xA6\x80\n\xC5").unwrap();
+        let mut iter = FileReader::new(&path).try_iter().unwrap();
+
+        let mut chars = String::new();
+        let mut invalid = Vec::new();
+        while let Some(c) = iter.next() {
+            chars.push(c);
+            if let Some(seq) = iter.take_invalid() {
+                invalid.push((seq.offset(), seq.into_bytes()));
+            }
+        }
+        assert_eq!(chars, "a\u{FFFD}b\n\u{FFFD}c 🦀\n\u{FFFD}");
+        assert_eq!(
+            invalid,
+            vec![
+                (1, b"\xFF"[..].into()),
+                (4, b"\xE2\x82"[..].into()),
+                (13, b"\xC5"[..].into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_peek() {
+        let dir = TempDir::new("file_reader_peek");
+        let path = dir.join("main.mrs");
+        fs::write(&path, "ab\n\nc").unwrap();
+        let mut iter = FileReader::new(&path).try_iter().unwrap();
+
+        assert_eq!(iter.peek(), Some(&'a'));
+        assert_eq!(iter.peek_second(), Some(&'b'));
+        assert_eq!(iter.next_if(|&c| c == 'b'), None);
+        assert_eq!(iter.next_if(|&c| c == 'a'), Some('a'));
+        assert_eq!(iter.next(), Some('b'));
+        // The second character is in the next line
+        assert_eq!(iter.peek_second(), None);
+        assert_eq!(iter.next(), Some('\n'));
+        assert_eq!(iter.peek(), Some(&'\n'));
+        assert_eq!(iter.next(), Some('\n'));
+        assert_eq!(iter.next(), Some('c'));
+        assert_eq!(iter.peek(), None);
+        assert_eq!(iter.next(), None);
+    }
+}
@@ -61,6 +61,22 @@ pub fn is_digit(c: char) -> bool {
     c.is_ascii_digit()
 }
 
+/// Returns the byte index at which the type suffix of the number literal `num` starts
+/// (e.g. `3` in `1.5f64`), if it has one.
+///
+/// The suffix starts with the first letter which isn't the `e` (or `E`) of an exponent,
+/// i.e. isn't followed by a digit.
+pub fn suffix_start(num: &str) -> Option<usize> {
+    let mut chars = num.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let is_exponent = matches!(c, 'e' | 'E') && chars.peek().is_some_and(|(_, c)| is_digit(*c));
+        if c.is_alphabetic() && !is_exponent {
+            return Some(i);
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -105,4 +121,13 @@ mod tests {
         assert!(!is_digit('a'));
         assert!(!is_digit('.'));
     }
+
+    #[test]
+    fn test_suffix_start() {
+        assert_eq!(suffix_start("1"), None);
+        assert_eq!(suffix_start("1.5e3"), None);
+        assert_eq!(suffix_start("1i32"), Some(1));
+        assert_eq!(suffix_start("1.5E3f64"), Some(5));
+        assert_eq!(suffix_start("2e"), Some(1));
+    }
 }
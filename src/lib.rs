@@ -2,8 +2,43 @@
 #![warn(missing_docs)]
 // #![warn(clippy::missing_docs_in_private_items)] //TODO Enable this
 
+pub mod abi;
 pub mod ast;
+pub mod backend;
+pub mod builtin;
+pub mod cancel;
+#[cfg(feature = "codegen")]
 pub mod codegen;
+#[cfg(feature = "cranelift")]
+pub mod cranelift;
+pub mod driver;
+pub mod examples;
+#[cfg(feature = "codegen")]
+pub mod harness;
+pub mod highlight;
+pub mod init;
+mod json;
 pub mod lexer;
+pub mod limits;
+#[cfg(feature = "codegen")]
+pub mod link;
+#[cfg(feature = "lsp")]
+pub mod lsp;
+pub mod metrics;
+pub mod observer;
 pub mod parser;
+pub mod pretty;
+pub mod refactor;
+pub mod resolve;
+#[cfg(feature = "codegen")]
+pub mod sandbox;
+pub mod serialize;
+pub mod statics;
+pub mod suggestion;
+pub mod target;
+#[cfg(test)]
+mod test_util;
 pub mod token;
+mod trace;
+pub mod transpile;
+pub mod unsafety;
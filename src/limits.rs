@@ -0,0 +1,139 @@
+//! A module containing the caps on the memory used by a compilation.
+//!
+//! The [parser](crate::parser::Parser) accounts the tokens it consumes and the AST nodes
+//! it produces (see [`MemoryUsage`]). When a [limit](MemoryLimits) is exceeded, the parsing
+//! aborts with [`LimitExceeded`], so an adversarial input cannot exhaust the memory
+//! of the process embedding the compiler.
+//!
+//! The tokens are checked as they are consumed, and the AST nodes as they are created
+//! (so that the parsing is aborted early) and once more when the whole file is parsed.
+//! Every AST node consumes at least one token, except for the expression statements without
+//! a trailing semicolon, so the number of nodes is at most twice the number of tokens.
+
+use std::error::Error;
+use std::fmt;
+
+/// The kind of resource accounted during a compilation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resource {
+    /// The tokens of the input.
+    Tokens,
+    /// The nodes of the AST.
+    Nodes,
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resource::Tokens => write!(f, "tokens"),
+            Resource::Nodes => write!(f, "AST nodes"),
+        }
+    }
+}
+
+/// The caps on the resources used by a compilation. By default, there are no caps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryLimits {
+    max_tokens: Option<usize>,
+    max_nodes: Option<usize>,
+}
+
+impl MemoryLimits {
+    /// Creates new `MemoryLimits` without any caps.
+    pub fn new() -> MemoryLimits {
+        MemoryLimits::default()
+    }
+
+    /// Sets the maximum number of tokens of the input.
+    pub fn with_max_tokens(self, max_tokens: usize) -> MemoryLimits {
+        MemoryLimits {
+            max_tokens: Some(max_tokens),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of nodes of the AST.
+    pub fn with_max_nodes(self, max_nodes: usize) -> MemoryLimits {
+        MemoryLimits {
+            max_nodes: Some(max_nodes),
+            ..self
+        }
+    }
+
+    /// Returns the maximum number of tokens of the input, if any.
+    pub fn max_tokens(&self) -> Option<usize> {
+        self.max_tokens
+    }
+
+    /// Returns the maximum number of nodes of the AST, if any.
+    pub fn max_nodes(&self) -> Option<usize> {
+        self.max_nodes
+    }
+
+    /// Checks that the given amount of the resource doesn't exceed its cap.
+    pub(crate) fn check(&self, resource: Resource, amount: usize) -> Result<(), LimitExceeded> {
+        let limit = match resource {
+            Resource::Tokens => self.max_tokens,
+            Resource::Nodes => self.max_nodes,
+        };
+        match limit {
+            Some(limit) if amount > limit => Err(LimitExceeded { resource, limit }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The amount of resources used by a compilation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    tokens: usize,
+    nodes: usize,
+}
+
+impl MemoryUsage {
+    /// Creates a new `MemoryUsage` with the given number of tokens and AST nodes.
+    pub fn new(tokens: usize, nodes: usize) -> MemoryUsage {
+        MemoryUsage { tokens, nodes }
+    }
+
+    /// Returns the number of tokens of the input, including the EOF token.
+    pub fn tokens(&self) -> usize {
+        self.tokens
+    }
+
+    /// Returns the number of nodes of the AST.
+    pub fn nodes(&self) -> usize {
+        self.nodes
+    }
+}
+
+/// An error when a compilation exceeds one of its [limits](MemoryLimits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LimitExceeded {
+    resource: Resource,
+    limit: usize,
+}
+
+impl LimitExceeded {
+    /// Returns the resource whose cap was exceeded.
+    pub fn resource(&self) -> Resource {
+        self.resource
+    }
+
+    /// Returns the cap that was exceeded.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The input exceeds the limit of {} {}",
+            self.limit, self.resource
+        )
+    }
+}
+
+impl Error for LimitExceeded {}
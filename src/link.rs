@@ -0,0 +1,523 @@
+//! A module containing the linker driver, which turns a compiled crate into an executable
+//! or a library that can be consumed from C (or Rust).
+//!
+//! The object code is emitted for the host machine and passed to the system tools:
+//! the C compiler (`cc`, or the one in the `CC` environment variable) links the executables
+//! and the shared libraries, and the archiver (`ar`, or the one in `AR`) creates
+//! the static libraries.
+//!
+//! Only an [executable](CrateType::Executable) needs the `main` function; a library crate
+//! (like a Rust crate with `#![no_main]`) exports just its `pub` functions and statics.
+//!
+//! The object code is generated by the [backend](Backend) of the linker, which is LLVM
+//! by default (see also [`BackendKind`]).
+//!
+//! The modules generated for the files of a [project](crate::driver::Project) can also be
+//! combined into one with [`link_modules`], without the external tools.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use inkwell::module::{Linkage, Module};
+
+use crate::ast::{ASTNode, Crate, FuncASTNode, Type};
+use crate::backend::{Backend, BackendError};
+use crate::codegen::session::CompilationSession;
+#[cfg(feature = "cranelift")]
+use crate::cranelift::CraneliftBackend;
+use crate::token::Span;
+
+/// The kind of the artifact produced from a crate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum CrateType {
+    /// An executable, whose entry point is the `main` function.
+    #[default]
+    Executable,
+    /// A static library (`.a`).
+    StaticLibrary,
+    /// A shared library (`.so`) with the C ABI.
+    SharedLibrary,
+}
+
+impl CrateType {
+    /// Returns the crate type with the given name (`bin`, `staticlib` or `cdylib`, as in Rust),
+    /// if there is one.
+    pub fn from_name(name: &str) -> Option<CrateType> {
+        match name {
+            "bin" => Some(CrateType::Executable),
+            "staticlib" => Some(CrateType::StaticLibrary),
+            "cdylib" => Some(CrateType::SharedLibrary),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the crate type.
+    pub fn name(self) -> &'static str {
+        match self {
+            CrateType::Executable => "bin",
+            CrateType::StaticLibrary => "staticlib",
+            CrateType::SharedLibrary => "cdylib",
+        }
+    }
+
+    /// Returns whether the crate has to define the `main` function.
+    pub fn requires_main(self) -> bool {
+        self == CrateType::Executable
+    }
+}
+
+impl fmt::Display for CrateType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The kind of the [backend](Backend) producing the object code of the linked crates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BackendKind {
+    /// The LLVM code generator (see the [`codegen`](crate::codegen) module).
+    #[default]
+    Llvm,
+    /// The Cranelift code generator (see the [`cranelift`](crate::cranelift) module),
+    /// which compiles faster, but doesn't optimize the code.
+    #[cfg(feature = "cranelift")]
+    Cranelift,
+}
+
+impl BackendKind {
+    /// Returns the backend kind with the given name (`llvm` or `cranelift`), if there is one
+    /// (and it is enabled).
+    pub fn from_name(name: &str) -> Option<BackendKind> {
+        match name {
+            "llvm" => Some(BackendKind::Llvm),
+            #[cfg(feature = "cranelift")]
+            "cranelift" => Some(BackendKind::Cranelift),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the backend kind, which is also the [name](Backend::name)
+    /// of its backends.
+    pub fn name(self) -> &'static str {
+        match self {
+            BackendKind::Llvm => "llvm",
+            #[cfg(feature = "cranelift")]
+            BackendKind::Cranelift => "cranelift",
+        }
+    }
+
+    /// Creates a backend of this kind with the default options.
+    pub fn create(self) -> Arc<dyn Backend> {
+        match self {
+            BackendKind::Llvm => Arc::new(CompilationSession::new()),
+            #[cfg(feature = "cranelift")]
+            BackendKind::Cranelift => Arc::new(CraneliftBackend),
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A result of linking a crate.
+pub type Result<T> = std::result::Result<T, LinkError>;
+
+/// The type of error that can occur while linking a crate.
+#[derive(Debug)]
+pub enum LinkError {
+    /// The crate of an executable doesn't define the `main` function.
+    MissingEntryPoint,
+    /// The `main` function has a signature other than `fn main()` or `fn main() -> i32`.
+    InvalidEntryPoint {
+        /// The span of the prototype of `main`.
+        span: Span,
+    },
+    /// The crate cannot be compiled by the backend.
+    Backend(BackendError),
+    /// The object file cannot be written.
+    Io(io::Error),
+    /// The linker or the archiver cannot be run, or it has failed.
+    Tool {
+        /// The name of the tool.
+        tool: Box<str>,
+        /// The reason of the failure (e.g. the error output of the tool).
+        message: Box<str>,
+    },
+    /// There are no modules to link.
+    NoModules,
+    /// A symbol is defined (and exported) by more than one of the linked modules.
+    DuplicateDefinition {
+        /// The name of the symbol.
+        symbol: Box<str>,
+        /// The name of the module with the first definition.
+        first_module: Box<str>,
+        /// The name of the module with the duplicate definition.
+        module: Box<str>,
+    },
+    /// LLVM has failed to link the modules.
+    Llvm(Box<str>),
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::MissingEntryPoint => write!(f, "The `main` function is missing"),
+            LinkError::InvalidEntryPoint { span } => write!(
+                f,
+                "The `main` function has to be `fn main()` or `fn main() -> i32` at {}",
+                span
+            ),
+            LinkError::Backend(err) => write!(f, "Cannot compile the crate: {}", err),
+            LinkError::Io(err) => write!(f, "Cannot write the object file: {}", err),
+            LinkError::Tool { tool, message } => write!(f, "`{}` failed: {}", tool, message),
+            LinkError::NoModules => write!(f, "There are no modules to link"),
+            LinkError::DuplicateDefinition {
+                symbol,
+                first_module,
+                module,
+            } => write!(
+                f,
+                "Symbol `{}` is defined in both `{}` and `{}`",
+                symbol, first_module, module
+            ),
+            LinkError::Llvm(message) => write!(f, "Cannot link the modules: {}", message),
+        }
+    }
+}
+
+impl Error for LinkError {}
+
+impl From<BackendError> for LinkError {
+    fn from(err: BackendError) -> LinkError {
+        LinkError::Backend(err)
+    }
+}
+
+impl From<io::Error> for LinkError {
+    fn from(err: io::Error) -> LinkError {
+        LinkError::Io(err)
+    }
+}
+
+/// Checks that the crate defines the `main` function, and that it is either `fn main()`
+/// or `fn main() -> i32`.
+pub fn check_entry_point(crt: &Crate) -> Result<()> {
+    let main = crt
+        .functions()
+        .map(FuncASTNode::proto)
+        .find(|proto| proto.name() == FuncASTNode::ENTRY_POINT)
+        .ok_or(LinkError::MissingEntryPoint)?;
+    let signature = main.signature();
+    if signature.param_count() != 0 || !matches!(signature.return_type(), Type::Unit | Type::I32) {
+        return Err(LinkError::InvalidEntryPoint { span: main.span() });
+    }
+    Ok(())
+}
+
+/// The linker driver producing the artifacts of the given [type](CrateType).
+#[derive(Debug, Clone)]
+pub struct Linker {
+    crate_type: CrateType,
+    backend: Arc<dyn Backend>,
+    cc: Box<str>,
+    ar: Box<str>,
+}
+
+impl Linker {
+    /// Creates a new `Linker` for the given crate type, compiling the crates
+    /// with the default [session](CompilationSession).
+    ///
+    /// The tools are taken from the `CC` and `AR` environment variables, defaulting to `cc` and `ar`.
+    pub fn new(crate_type: CrateType) -> Linker {
+        let tool = |var: &str, default: &str| env::var(var).unwrap_or(default.to_owned()).into();
+        Linker {
+            crate_type,
+            backend: BackendKind::default().create(),
+            cc: tool("CC", "cc"),
+            ar: tool("AR", "ar"),
+        }
+    }
+
+    /// Sets the session the crates are compiled with, i.e. compiles them with LLVM.
+    pub fn with_session(self, session: CompilationSession) -> Linker {
+        self.with_backend(Arc::new(session))
+    }
+
+    /// Sets the backend generating the object code of the crates.
+    pub fn with_backend(self, backend: Arc<dyn Backend>) -> Linker {
+        Linker { backend, ..self }
+    }
+
+    /// Returns the backend generating the object code of the crates.
+    pub fn backend(&self) -> &dyn Backend {
+        &*self.backend
+    }
+
+    /// Returns the type of the produced artifacts.
+    pub fn crate_type(&self) -> CrateType {
+        self.crate_type
+    }
+
+    /// Compiles the given crate and links it into the artifact at `output`.
+    ///
+    /// # Errors
+    ///
+    /// If the crate of an executable has no valid [entry point](check_entry_point),
+    /// the crate cannot be compiled, or the tools fail, a [`LinkError`] is returned.
+    pub fn link(&self, crt: &Crate, output: &Path) -> Result<()> {
+        if self.crate_type.requires_main() {
+            check_entry_point(crt)?;
+        }
+        let compiled = self.backend.emit_object(crt)?;
+
+        let object = object_path(output);
+        fs::write(&object, compiled)?;
+        let result = match self.crate_type {
+            CrateType::Executable => {
+                run(Command::new(&*self.cc).arg("-o").arg(output).arg(&object))
+            }
+            CrateType::SharedLibrary => run(Command::new(&*self.cc)
+                .arg("-shared")
+                .arg("-o")
+                .arg(output)
+                .arg(&object)),
+            CrateType::StaticLibrary => {
+                // `ar` appends to an existing archive, so the old one has to be removed first
+                match fs::remove_file(output) {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+                    _ => {}
+                }
+                run(Command::new(&*self.ar).arg("crs").arg(output).arg(&object))
+            }
+        };
+        // The object file is only an intermediate artifact
+        let _ = fs::remove_file(&object);
+        result
+    }
+}
+
+/// Links the given modules (e.g. the ones generated by [`Project::code_gen`]) into the first one,
+/// so that the declarations of the symbols imported from the other modules are resolved
+/// to their definitions.
+///
+/// [`Project::code_gen`]: crate::driver::Project::code_gen
+///
+/// # Errors
+///
+/// If there are no modules, [`LinkError::NoModules`] is returned. If an exported symbol
+/// is defined in more than one module, [`LinkError::DuplicateDefinition`] is returned.
+pub fn link_modules<'ctx>(modules: impl IntoIterator<Item = Module<'ctx>>) -> Result<Module<'ctx>> {
+    let mut modules = modules.into_iter();
+    let linked = modules.next().ok_or(LinkError::NoModules)?;
+
+    // The symbols defined so far, with the names of the modules defining them
+    let mut definitions: HashMap<Box<str>, Box<str>> = HashMap::new();
+    let mut define = |module: &Module| {
+        let module_name: Box<str> = module.get_name().to_string_lossy().into();
+        for symbol in exported_definitions(module) {
+            match definitions.entry(symbol) {
+                Entry::Occupied(first) => {
+                    return Err(LinkError::DuplicateDefinition {
+                        symbol: first.key().clone(),
+                        first_module: first.get().clone(),
+                        module: module_name,
+                    });
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(module_name.clone());
+                }
+            }
+        }
+        Ok(())
+    };
+    define(&linked)?;
+    for module in modules {
+        define(&module)?;
+        linked
+            .link_in_module(module)
+            .map_err(|message| LinkError::Llvm(message.to_string().into()))?;
+    }
+    Ok(linked)
+}
+
+/// Returns the names of the functions and globals defined with the external linkage
+/// in the given module.
+fn exported_definitions(module: &Module) -> Vec<Box<str>> {
+    let functions = module
+        .get_functions()
+        .filter(|function| function.count_basic_blocks() > 0)
+        .filter(|function| function.get_linkage() == Linkage::External)
+        .map(|function| function.get_name().to_string_lossy().into());
+    let globals = module
+        .get_globals()
+        .filter(|global| global.get_initializer().is_some())
+        .filter(|global| global.get_linkage() == Linkage::External)
+        .map(|global| global.get_name().to_string_lossy().into());
+    functions.chain(globals).collect()
+}
+
+/// Returns the path of the intermediate object file of the given artifact.
+fn object_path(output: &Path) -> PathBuf {
+    let mut name = output.file_name().unwrap_or_default().to_os_string();
+    name.push(".o");
+    output.with_file_name(name)
+}
+
+/// Runs the given tool, turning its failure into a [`LinkError::Tool`].
+fn run(command: &mut Command) -> Result<()> {
+    let tool: Box<str> = command.get_program().to_string_lossy().into();
+    let output = command.output().map_err(|err| LinkError::Tool {
+        tool: tool.clone(),
+        message: err.to_string().into(),
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(LinkError::Tool {
+            tool,
+            message: format!("{} ({})", stderr.trim(), output.status).into(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::sync::Arc;
+
+    use crate::test_util::{parse, TempDir};
+
+    use super::*;
+
+    #[test]
+    fn test_libraries() {
+        let crt = parse("pub fn answer() -> i32 { return 42; }\n");
+        let dir = TempDir::new("link_libraries");
+
+        // A library doesn't need `main`
+        assert!(matches!(
+            Linker::new(CrateType::Executable).link(&crt, &dir.join("mrs_test_link_bin")),
+            Err(LinkError::MissingEntryPoint)
+        ));
+
+        let archive = dir.join("libmrs_test_link.a");
+        Linker::new(CrateType::StaticLibrary)
+            .link(&crt, &archive)
+            .unwrap();
+        assert!(fs::read(&archive).unwrap().starts_with(b"!<arch>\n"));
+        assert!(!object_path(&archive).exists());
+
+        // A C program can call the function from the archive
+        let c_source = dir.write(
+            "mrs_test_link_main.c",
+            "int answer(void);\nint main(void) { return answer(); }\n",
+        );
+        let program = dir.join("mrs_test_link_main");
+        let status = Command::new("cc")
+            .arg("-o")
+            .arg(&program)
+            .arg(&c_source)
+            .arg(&archive)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(Command::new(&program).status().unwrap().code(), Some(42));
+
+        let shared = dir.join("libmrs_test_link.so");
+        Linker::new(CrateType::SharedLibrary)
+            .link(&crt, &shared)
+            .unwrap();
+        assert!(fs::read(&shared).unwrap().starts_with(b"\x7fELF"));
+    }
+
+    #[test]
+    fn test_executable() {
+        let dir = TempDir::new("link_executable");
+        let program = dir.join("mrs_test_link_exe");
+        let crt = parse("fn main() -> i32 { return 3; }\n");
+        Linker::new(CrateType::Executable)
+            .link(&crt, &program)
+            .unwrap();
+        assert_eq!(Command::new(&program).status().unwrap().code(), Some(3));
+
+        let crt = parse("fn main(x: i32) {}\n");
+        let err = Linker::new(CrateType::Executable)
+            .link(&crt, &program)
+            .unwrap_err();
+        assert!(matches!(err, LinkError::InvalidEntryPoint { .. }));
+
+        assert_eq!(
+            CrateType::from_name("cdylib"),
+            Some(CrateType::SharedLibrary)
+        );
+        assert_eq!(CrateType::from_name("lib"), None);
+        assert!(!CrateType::StaticLibrary.requires_main());
+    }
+
+    #[test]
+    #[cfg(feature = "cranelift")]
+    fn test_cranelift_backend() {
+        let dir = TempDir::new("link_cranelift");
+        let program = dir.join("mrs_test_link_cranelift");
+        let crt = parse(
+            "fn square(x: i32) -> i32 { return x * x; }\n\
+            fn main() -> i32 { let mut i: i32 = 0; while square(i) < 50 { i = i + 1; }; return i; }\n",
+        );
+        let backend = BackendKind::from_name("cranelift").unwrap().create();
+        let linker = Linker::new(CrateType::Executable).with_backend(backend);
+        assert_eq!(linker.backend().name(), "cranelift");
+        linker.link(&crt, &program).unwrap();
+        assert_eq!(Command::new(&program).status().unwrap().code(), Some(8));
+    }
+
+    /// A backend that fails without generating any code.
+    #[derive(Debug)]
+    struct FailingBackend;
+
+    impl Backend for FailingBackend {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn emit_object(&self, _crt: &Crate) -> std::result::Result<Vec<u8>, BackendError> {
+            Err(BackendError::Custom {
+                backend: self.name(),
+                message: "Not implemented".into(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_backend() {
+        let dir = TempDir::new("link_backend");
+        let program = dir.join("mrs_test_link_backend");
+        let crt = parse("fn main() {}\n");
+        let linker = Linker::new(CrateType::Executable);
+        assert_eq!(linker.backend().name(), BackendKind::Llvm.name());
+
+        let err = linker
+            .with_backend(Arc::new(FailingBackend))
+            .link(&crt, &program)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            LinkError::Backend(BackendError::Custom {
+                backend: "failing",
+                ..
+            })
+        ));
+        assert!(!object_path(&program).exists());
+        assert_eq!(BackendKind::from_name("gcc"), None);
+    }
+}
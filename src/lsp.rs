@@ -0,0 +1,95 @@
+//! A module containing the conversions of the positions in the source code to the types of
+//! the [Language Server Protocol](https://microsoft.github.io/language-server-protocol/)
+//! (from the [`lsp_types`] crate).
+//!
+//! The [positions](Position) count both the lines and the columns (i.e. the characters) from 1,
+//! while LSP counts them from 0 and measures the columns in UTF-16 code units.
+//! The [`From`] conversions assume that all the characters preceding the position on its line
+//! are a single UTF-16 code unit (i.e. they are not e.g. emojis), while a [`LineIndex`]
+//! computes the exact columns from the source code.
+
+use crate::token::{Position, Span};
+
+impl From<Position> for lsp_types::Position {
+    fn from(pos: Position) -> Self {
+        lsp_types::Position::new(
+            pos.line().saturating_sub(1) as u32,
+            pos.column().saturating_sub(1) as u32,
+        )
+    }
+}
+
+impl From<Span> for lsp_types::Range {
+    fn from(span: Span) -> Self {
+        lsp_types::Range::new(span.start().into(), span.end().into())
+    }
+}
+
+/// The lines of a source file, used to convert the positions in it to the LSP types
+/// with the exact [UTF-16 columns](Position::utf16_column).
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    lines: Vec<&'a str>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Creates a new `LineIndex` of the given source code.
+    pub fn new(source: &'a str) -> LineIndex<'a> {
+        LineIndex {
+            lines: source.split('\n').collect(),
+        }
+    }
+
+    /// Converts the given position to an LSP position.
+    ///
+    /// A position past the last line is converted as if all the characters before it
+    /// were a single UTF-16 code unit.
+    pub fn position(&self, pos: Position) -> lsp_types::Position {
+        let line = pos.line().saturating_sub(1);
+        let character = match self.lines.get(line) {
+            Some(text) => pos.utf16_column(text),
+            None => pos.column().saturating_sub(1),
+        };
+        lsp_types::Position::new(line as u32, character as u32)
+    }
+
+    /// Converts the given span to an LSP range.
+    pub fn range(&self, span: Span) -> lsp_types::Range {
+        lsp_types::Range::new(self.position(span.start()), self.position(span.end()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        let span = Span::new(Position::new_at(1, 5), Position::new_at(2, 1));
+        assert_eq!(
+            lsp_types::Range::from(span),
+            lsp_types::Range::new(
+                lsp_types::Position::new(0, 4),
+                lsp_types::Position::new(1, 0)
+            )
+        );
+
+        let index = LineIndex::new("fn main() {\n    let 🦀: i32 = 1;\n}\n");
+        let span = Span::new(Position::new_at(2, 9), Position::new_at(2, 10));
+        assert_eq!(
+            index.range(span),
+            lsp_types::Range::new(
+                lsp_types::Position::new(1, 8),
+                lsp_types::Position::new(1, 10)
+            )
+        );
+        assert_eq!(
+            index.position(Position::new_at(2, 12)),
+            lsp_types::Position::new(1, 12)
+        );
+        assert_eq!(
+            index.position(Position::new_at(7, 3)),
+            lsp_types::Position::new(6, 2)
+        );
+    }
+}
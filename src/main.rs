@@ -1,9 +1,147 @@
+use std::env;
+use std::process::ExitCode;
+
+use mini_rust_compiler_components::codegen::session::CompilationSession;
+use mini_rust_compiler_components::codegen::CodeGenOptions;
+use mini_rust_compiler_components::harness;
+use mini_rust_compiler_components::lexer::dump::{DumpFormat, TokenDump};
+use mini_rust_compiler_components::link::{BackendKind, CrateType, Linker};
+use mini_rust_compiler_components::observer::Diagnostic;
 use mini_rust_compiler_components::parser::Parser;
+use mini_rust_compiler_components::transpile;
+
+/// The file compiled when no path is given.
+const DEFAULT_PATH: &str = "tests/test.mrs";
+
+/// The usage of the command-line interface.
+const USAGE: &str = "Usage: mini-rust-compiler [--emit=tokens [--format=text|json] | \
+    --check [--format=text|json] | --emit=c | --emit=asm | --test | --stats | --out=<file> [--crate-type=bin|staticlib|cdylib] \
+    [--backend=llvm|cranelift]] [path]";
+
+fn main() -> ExitCode {
+    let mut path = DEFAULT_PATH.to_owned();
+    let mut emit_tokens = false;
+    let mut emit_c = false;
+    let mut emit_asm = false;
+    let mut check = false;
+    let mut test = false;
+    let mut stats = false;
+    let mut format = DumpFormat::default();
+    let mut out = None;
+    let mut crate_type = CrateType::default();
+    let mut backend = BackendKind::default();
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "--emit=tokens" => emit_tokens = true,
+            "--emit=c" => emit_c = true,
+            "--emit=asm" => emit_asm = true,
+            "--check" => check = true,
+            "--test" => test = true,
+            "--stats" => stats = true,
+            arg if arg.starts_with("--out=") => out = Some(arg["--out=".len()..].to_owned()),
+            arg if arg.starts_with("--crate-type=") => {
+                match CrateType::from_name(&arg["--crate-type=".len()..]) {
+                    Some(ty) => crate_type = ty,
+                    None => {
+                        eprintln!("Unknown crate type: {}\n{}", arg, USAGE);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            arg if arg.starts_with("--backend=") => {
+                match BackendKind::from_name(&arg["--backend=".len()..]) {
+                    Some(b) => backend = b,
+                    None => {
+                        eprintln!("Unknown backend: {}\n{}", arg, USAGE);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            arg if arg.starts_with("--format=") => {
+                match DumpFormat::from_name(&arg["--format=".len()..]) {
+                    Some(f) => format = f,
+                    None => {
+                        eprintln!("Unknown format: {}\n{}", arg, USAGE);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+            arg if arg.starts_with("--") => {
+                eprintln!("Unknown option: {}\n{}", arg, USAGE);
+                return ExitCode::FAILURE;
+            }
+            arg => path = arg.to_owned(),
+        }
+    }
+
+    if emit_tokens {
+        return match TokenDump::new(&path) {
+            Ok(dump) => {
+                print!("{}", dump.format(format));
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("Error while reading the file: {}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
 
-fn main() {
-    let parser = Parser::new("tests/test.mrs").unwrap();
+    let parser = Parser::new(&path).unwrap();
     let crt = parser.parse();
+    if check {
+        let errors = match &crt {
+            Ok(crt) => crt.check_initialization(),
+            Err(_) => Vec::new(),
+        };
+        let diagnostics = match &crt {
+            Ok(_) => errors.iter().map(Diagnostic::Error).collect(),
+            Err(err) => err.diagnostics(),
+        };
+        print_diagnostics(&diagnostics, format);
+        return if diagnostics.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    }
+
     match crt {
+        Ok(crt) if test => match harness::run_tests(&crt, CodeGenOptions::new()) {
+            Ok(report) => {
+                println!("{}", report);
+                if !report.is_success() {
+                    return ExitCode::FAILURE;
+                }
+            }
+            Err(err) => {
+                eprintln!("Error while running the tests: {}", err);
+                return ExitCode::FAILURE;
+            }
+        },
+        Ok(crt) if emit_c => match transpile::to_c(&crt) {
+            Ok(source) => print!("{}", source),
+            Err(err) => {
+                eprintln!("Error while transpiling the crate to C: {}", err);
+                return ExitCode::FAILURE;
+            }
+        },
+        Ok(crt) if emit_asm => match CompilationSession::new().emit_assembly(&crt) {
+            Ok(asm) => print!("{}", asm),
+            Err(err) => {
+                eprintln!("Error while compiling the crate to assembly: {:?}", err);
+                return ExitCode::FAILURE;
+            }
+        },
+        Ok(crt) if stats => print!("{}", crt.stats()),
+        Ok(crt) if out.is_some() => {
+            let out = out.unwrap_or_default();
+            let linker = Linker::new(crate_type).with_backend(backend.create());
+            if let Err(err) = linker.link(&crt, out.as_ref()) {
+                eprintln!("Error while linking the {} crate: {}", crate_type, err);
+                return ExitCode::FAILURE;
+            }
+        }
         Ok(crt) => {
             println!("{}", crt);
         }
@@ -11,4 +149,23 @@ fn main() {
             println!("Error while parsing the file: {}", err);
         }
     }
+    ExitCode::SUCCESS
+}
+
+/// Prints the diagnostics (with their suggestions) in the given format.
+fn print_diagnostics(diagnostics: &[Diagnostic], format: DumpFormat) {
+    match format {
+        DumpFormat::Text => {
+            for diagnostic in diagnostics {
+                println!("{}: {}", diagnostic.level(), diagnostic);
+                if let Some(suggestion) = diagnostic.suggestion() {
+                    println!("  help: {}", suggestion);
+                }
+            }
+        }
+        DumpFormat::Json => {
+            let diagnostics: Vec<String> = diagnostics.iter().map(|d| d.to_json()).collect();
+            println!(r#"{{"diagnostics":[{}]}}"#, diagnostics.join(","));
+        }
+    }
 }
@@ -0,0 +1,387 @@
+//! A module containing statistics about the AST of a crate.
+//!
+//! The statistics are the inputs of the compiler rather than its outputs
+//! (compare [`IrStats`](crate::codegen::stats::IrStats)), e.g. to grade the programs
+//! or to track what the performance of the compiler depends on.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::*;
+
+/// The kind of an AST node, as counted by [`AstStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NodeKind {
+    /// The root of the crate.
+    Crate,
+    /// An import of an item from another crate.
+    Import,
+    /// A function definition.
+    Func,
+    /// A function prototype.
+    FuncProto,
+    /// A function parameter.
+    Param,
+    /// A static item.
+    Static,
+    /// An extern block.
+    Extern,
+    /// A let statement.
+    Let,
+    /// An expression statement.
+    ExprStmt,
+    /// A block expression.
+    Block,
+    /// A literal of any type.
+    Literal,
+    /// A path expression.
+    Path,
+    /// An underscore expression.
+    Underscore,
+    /// A grouped (parenthesized) expression.
+    Grouped,
+    /// A tuple expression.
+    Tuple,
+    /// A tuple index expression.
+    TupleIndex,
+    /// A function call.
+    FunCall,
+    /// An assignment.
+    Assign,
+    /// An arithmetic or logical operator expression.
+    Arith,
+    /// A comparison operator expression.
+    Comp,
+    /// A lazy boolean operator expression.
+    LazyBool,
+    /// A negation operator expression.
+    Neg,
+    /// A borrow operator expression.
+    Borrow,
+    /// A dereference operator expression.
+    Deref,
+    /// A type cast.
+    TypeCast,
+    /// A layout query (`size_of` or `align_of`).
+    LayoutOf,
+    /// An infinite loop.
+    InfLoop,
+    /// A while loop.
+    While,
+    /// A do-while loop.
+    DoWhile,
+    /// A for loop.
+    For,
+    /// An if expression.
+    If,
+    /// The `else` branch of an if expression.
+    Else,
+    /// An unsafe block.
+    UnsafeBlock,
+    /// A return expression.
+    Return,
+}
+
+impl NodeKind {
+    /// Returns the kind of the given AST node.
+    pub fn of(node: &dyn ASTNode) -> NodeKind {
+        macro_rules! kind_of {
+            ($($ty:ty => $kind:ident),+ $(,)?) => {
+                $(
+                    if node.as_any().is::<$ty>() {
+                        return NodeKind::$kind;
+                    }
+                )+
+            };
+        }
+
+        kind_of! {
+            CrateASTNode => Crate,
+            ImportASTNode => Import,
+            FuncASTNode => Func,
+            FuncProtoASTNode => FuncProto,
+            ParamASTNode => Param,
+            StaticASTNode => Static,
+            ExternASTNode => Extern,
+            LetASTNode => Let,
+            ExprStmtASTNode => ExprStmt,
+            BlockASTNode => Block,
+            LiteralASTNode<i128> => Literal,
+            LiteralASTNode<f64> => Literal,
+            LiteralASTNode<bool> => Literal,
+            LiteralASTNode<Rc<str>> => Literal,
+            LiteralASTNode<()> => Literal,
+            PathASTNode => Path,
+            UnderscoreASTNode => Underscore,
+            GroupedExprASTNode => Grouped,
+            TupleASTNode => Tuple,
+            TupleIndexASTNode => TupleIndex,
+            FunCallASTNode => FunCall,
+            AssignASTNode => Assign,
+            ArithExprASTNode => Arith,
+            CompExprASTNode => Comp,
+            LazyBoolExprASTNode => LazyBool,
+            NegExprASTNode => Neg,
+            BorrowExprASTNode => Borrow,
+            DerefExprASTNode => Deref,
+            TypeCastASTNode => TypeCast,
+            LayoutOfASTNode => LayoutOf,
+            InfLoopASTNode => InfLoop,
+            WhileASTNode => While,
+            DoWhileASTNode => DoWhile,
+            ForASTNode => For,
+            IfASTNode => If,
+            ElseASTNode => Else,
+            UnsafeBlockASTNode => UnsafeBlock,
+            ReturnASTNode => Return,
+        }
+        match node.as_any().downcast_ref::<ItemASTNode>() {
+            Some(item) => NodeKind::of(item.inner_ast()),
+            None => unreachable!("Unknown AST node: {}", node),
+        }
+    }
+
+    /// Returns whether the nodes of this kind branch the control flow,
+    /// i.e. whether they add to the [cyclomatic complexity](FunctionStats::complexity).
+    pub fn is_decision_point(&self) -> bool {
+        matches!(
+            self,
+            NodeKind::If
+                | NodeKind::While
+                | NodeKind::DoWhile
+                | NodeKind::For
+                | NodeKind::InfLoop
+                | NodeKind::LazyBool
+        )
+    }
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NodeKind::Crate => "crate",
+            NodeKind::Import => "import",
+            NodeKind::Func => "function",
+            NodeKind::FuncProto => "function prototype",
+            NodeKind::Param => "parameter",
+            NodeKind::Static => "static",
+            NodeKind::Extern => "extern block",
+            NodeKind::Let => "let statement",
+            NodeKind::ExprStmt => "expression statement",
+            NodeKind::Block => "block",
+            NodeKind::Literal => "literal",
+            NodeKind::Path => "path",
+            NodeKind::Underscore => "underscore",
+            NodeKind::Grouped => "grouped expression",
+            NodeKind::Tuple => "tuple",
+            NodeKind::TupleIndex => "tuple index",
+            NodeKind::FunCall => "function call",
+            NodeKind::Assign => "assignment",
+            NodeKind::Arith => "arithmetic or logical operator",
+            NodeKind::Comp => "comparison operator",
+            NodeKind::LazyBool => "lazy boolean operator",
+            NodeKind::Neg => "negation operator",
+            NodeKind::Borrow => "borrow operator",
+            NodeKind::Deref => "dereference operator",
+            NodeKind::TypeCast => "type cast",
+            NodeKind::LayoutOf => "layout query",
+            NodeKind::InfLoop => "loop",
+            NodeKind::While => "while loop",
+            NodeKind::DoWhile => "do-while loop",
+            NodeKind::For => "for loop",
+            NodeKind::If => "if",
+            NodeKind::Else => "else branch",
+            NodeKind::UnsafeBlock => "unsafe block",
+            NodeKind::Return => "return",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The statistics of the AST of a crate (see [`Crate::stats`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AstStats {
+    nodes: BTreeMap<NodeKind, usize>,
+    depth: usize,
+    functions: Vec<FunctionStats>,
+}
+
+/// The statistics of a single function definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionStats {
+    name: Rc<str>,
+    nodes: usize,
+    depth: usize,
+    complexity: usize,
+}
+
+impl AstStats {
+    /// Collects the statistics of the AST with the given root.
+    pub fn of(root: &CrateASTNode) -> AstStats {
+        let mut stats = AstStats::default();
+        stats.depth = stats.visit(root, 1);
+        stats.functions = root.functions().map(FunctionStats::of).collect();
+        stats
+    }
+
+    /// Counts the given node and its descendants, and returns the depth of the subtree.
+    fn visit(&mut self, node: &dyn ASTNode, depth: usize) -> usize {
+        *self.nodes.entry(NodeKind::of(node)).or_default() += 1;
+        node.children().map_or(depth, |children| {
+            children
+                .map(|child| self.visit(child, depth + 1))
+                .fold(depth, usize::max)
+        })
+    }
+
+    /// Returns the number of all the nodes, including the root.
+    ///
+    /// It is the same as [`Crate::node_count`].
+    pub fn node_count(&self) -> usize {
+        self.nodes.values().sum()
+    }
+
+    /// Returns the number of the nodes of the given kind.
+    pub fn count(&self, kind: NodeKind) -> usize {
+        self.nodes.get(&kind).copied().unwrap_or_default()
+    }
+
+    /// Returns the numbers of the nodes by their kind, ordered by the kind.
+    ///
+    /// The kinds without any nodes are omitted.
+    pub fn counts(&self) -> impl Iterator<Item = (NodeKind, usize)> + '_ {
+        self.nodes.iter().map(|(kind, count)| (*kind, *count))
+    }
+
+    /// Returns the maximum nesting depth of the AST, where the root has depth 1.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of function definitions.
+    pub fn function_count(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// Returns the statistics of the function definitions, in the order of their definitions.
+    pub fn functions(&self) -> &[FunctionStats] {
+        &self.functions
+    }
+}
+
+impl FunctionStats {
+    /// Collects the statistics of the given function.
+    pub fn of(func: &FuncASTNode) -> FunctionStats {
+        fn visit(node: &dyn ASTNode, depth: usize, stats: &mut FunctionStats) {
+            stats.nodes += 1;
+            stats.depth = stats.depth.max(depth);
+            if NodeKind::of(node).is_decision_point() {
+                stats.complexity += 1;
+            }
+            if let Some(children) = node.children() {
+                for child in children {
+                    visit(child, depth + 1, stats);
+                }
+            }
+        }
+
+        let mut stats = FunctionStats {
+            name: func.proto().name_owned(),
+            nodes: 0,
+            depth: 0,
+            complexity: 1,
+        };
+        visit(func, 1, &mut stats);
+        stats
+    }
+
+    /// Returns the name of the function.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of nodes of the function, including the function node itself.
+    pub fn node_count(&self) -> usize {
+        self.nodes
+    }
+
+    /// Returns the maximum nesting depth of the function, where the function node has depth 1.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the cyclomatic complexity of the function approximated from its AST,
+    /// i.e. one more than the number of its [decision points](NodeKind::is_decision_point).
+    pub fn complexity(&self) -> usize {
+        self.complexity
+    }
+}
+
+impl fmt::Display for AstStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} nodes, depth {}, {} functions",
+            self.node_count(),
+            self.depth,
+            self.functions.len()
+        )?;
+        for (kind, count) in self.counts() {
+            writeln!(f, "  {}: {}", kind, count)?;
+        }
+        for func in &self.functions {
+            writeln!(f, "{}", func)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FunctionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "fn {}: {} nodes, depth {}, complexity {}",
+            self.name, self.nodes, self.depth, self.complexity
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let crt = parse(
+            "static X: i32 = 1;\n\
+            fn f() -> i32 { return 1; }\n\
+            fn g(c: bool, d: bool) -> i32 {\n\
+                if c && d { return 1; } else if c { return 2; };\n\
+                while c { };\n\
+                return 3;\n\
+            }\n",
+        );
+        let stats = crt.stats();
+        assert_eq!(stats.node_count(), crt.node_count());
+        assert_eq!(stats.count(NodeKind::Crate), 1);
+        assert_eq!(stats.count(NodeKind::Func), 2);
+        assert_eq!(stats.count(NodeKind::Static), 1);
+        assert_eq!(stats.count(NodeKind::If), 2);
+        assert_eq!(stats.count(NodeKind::Return), 4);
+        assert_eq!(stats.count(NodeKind::Extern), 0);
+        assert_eq!(stats.function_count(), 2);
+
+        let [f, g] = stats.functions() else {
+            panic!("Expected two functions");
+        };
+        assert_eq!(f.name(), "f");
+        assert_eq!(f.complexity(), 1);
+        assert_eq!(g.name(), "g");
+        assert_eq!(g.complexity(), 5);
+        assert!(g.depth() > f.depth());
+        assert_eq!(stats.depth(), g.depth() + 1);
+    }
+}
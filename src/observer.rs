@@ -0,0 +1,226 @@
+//! A module containing the hooks into the compilation pipeline.
+//!
+//! A [`PipelineObserver`] registered with the [parser](crate::parser::Parser::with_observer)
+//! or the [compilation session](crate::codegen::session::CompilationSession::with_observer)
+//! is notified about the progress of the compilation, e.g. to show a progress bar,
+//! a live view of the AST or to collect metrics.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::ast::error::{SemanticError, SemanticWarning};
+use crate::ast::ItemASTNode;
+#[cfg(feature = "codegen")]
+use crate::codegen::error::CodeGenError;
+use crate::json;
+use crate::parser::error::{ParserError, RecoverableParserError};
+use crate::suggestion::Suggestion;
+use crate::token::Token;
+
+/// An observer shared between the user and the stages of the pipeline.
+///
+/// The observer is locked for the duration of every callback, so it mustn't be locked
+/// by the user while the compilation is running on the same thread.
+pub type SharedObserver = Arc<Mutex<dyn PipelineObserver + Send>>;
+
+/// A diagnostic reported during the compilation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Diagnostic<'a> {
+    /// An error that didn't prevent the parser from continuing.
+    ParserError(&'a RecoverableParserError),
+    /// An error that aborted the parsing (see [`ParserError::diagnostics`]).
+    Fatal(&'a ParserError),
+    /// An error found by the analysis of a parsed crate
+    /// (e.g. [`Crate::check_initialization`](crate::ast::Crate::check_initialization)).
+    Error(&'a SemanticError),
+    /// A warning encountered during code generation.
+    Warning(&'a SemanticWarning),
+    /// An error that aborted the code generation
+    /// (other than a [`SemanticError`], which is reported as [`Diagnostic::Error`]).
+    #[cfg(feature = "codegen")]
+    CodeGen(&'a CodeGenError),
+}
+
+impl Diagnostic<'_> {
+    /// Returns the severity of the diagnostic, i.e. `error` or `warning`.
+    pub fn level(&self) -> &'static str {
+        match self {
+            Diagnostic::ParserError(_) | Diagnostic::Fatal(_) | Diagnostic::Error(_) => "error",
+            #[cfg(feature = "codegen")]
+            Diagnostic::CodeGen(_) => "error",
+            Diagnostic::Warning(_) => "warning",
+        }
+    }
+
+    /// Returns the [`Suggestion`] fixing the diagnostic, if there is one.
+    pub fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            Diagnostic::ParserError(err) => err.suggestion(),
+            Diagnostic::Fatal(_) => None,
+            Diagnostic::Error(err) => err.suggestion(),
+            Diagnostic::Warning(warning) => warning.suggestion(),
+            #[cfg(feature = "codegen")]
+            Diagnostic::CodeGen(_) => None,
+        }
+    }
+
+    /// Formats the diagnostic as a JSON object with the fields `level`, `message`
+    /// and `suggestions` (with the [fields](Suggestion::to_json) of every suggestion).
+    pub fn to_json(&self) -> String {
+        let suggestions = self.suggestion().map(|s| s.to_json()).unwrap_or_default();
+        format!(
+            r#"{{"level":{},"message":{},"suggestions":[{}]}}"#,
+            json::string(self.level()),
+            json::string(&self.to_string()),
+            suggestions
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::ParserError(err) => fmt::Display::fmt(err, f),
+            Diagnostic::Fatal(err) => fmt::Display::fmt(err, f),
+            Diagnostic::Error(err) => fmt::Display::fmt(err, f),
+            Diagnostic::Warning(warning) => fmt::Display::fmt(warning, f),
+            #[cfg(feature = "codegen")]
+            Diagnostic::CodeGen(err) => fmt::Display::fmt(err, f),
+        }
+    }
+}
+
+/// A trait for the callbacks invoked during the compilation.
+///
+/// All the callbacks do nothing by default, so only the relevant ones have to be implemented.
+pub trait PipelineObserver {
+    /// Called when the parser consumes a token (including the EOF token).
+    fn on_token(&mut self, _token: &Token) {}
+
+    /// Called when the parser finishes parsing a top-level item.
+    fn on_item_parsed(&mut self, _item: &ItemASTNode) {}
+
+    /// Called before the code is generated for a top-level item.
+    fn on_item_codegen(&mut self, _item: &ItemASTNode) {}
+
+    /// Called when a diagnostic is reported.
+    fn on_diagnostic(&mut self, _diagnostic: Diagnostic) {}
+}
+
+/// Invokes the given callback on the observer, if any.
+///
+/// A poisoned observer (i.e. one whose callback has panicked before) is skipped.
+pub(crate) fn notify(
+    observer: &Option<SharedObserver>,
+    callback: impl FnOnce(&mut dyn PipelineObserver),
+) {
+    if let Some(observer) = observer {
+        if let Ok(mut observer) = observer.lock() {
+            callback(&mut *observer);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "codegen"))]
+mod tests {
+    use std::fs;
+
+    use crate::codegen::session::CompilationSession;
+    use crate::parser::Parser;
+    use crate::test_util::TempDir;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        tokens: usize,
+        events: Vec<String>,
+    }
+
+    impl PipelineObserver for Recorder {
+        fn on_token(&mut self, _token: &Token) {
+            self.tokens += 1;
+        }
+
+        fn on_item_parsed(&mut self, item: &ItemASTNode) {
+            self.events.push(format!("parsed {}", item.span()));
+        }
+
+        fn on_item_codegen(&mut self, item: &ItemASTNode) {
+            self.events.push(format!("codegen {}", item.span()));
+        }
+
+        fn on_diagnostic(&mut self, diagnostic: Diagnostic) {
+            self.events.push(diagnostic.level().to_owned());
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let dir = TempDir::new("observer");
+        let path = dir.join("main.mrs");
+        fs::write(
+            &path,
+            "fn a() -> i32 { return 1; }\nfn b() { unsafe { a(); }; }\n",
+        )
+        .unwrap();
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+
+        let crt = Parser::new(&path)
+            .unwrap()
+            .with_observer(recorder.clone())
+            .parse()
+            .unwrap();
+        CompilationSession::new()
+            .with_observer(recorder.clone())
+            .compile(&crt)
+            .unwrap();
+
+        let recorder = recorder.lock().unwrap();
+        assert_eq!(recorder.tokens, 26);
+        assert_eq!(
+            recorder.events,
+            [
+                "parsed <1:1>-<1:28>",
+                "parsed <2:1>-<2:28>",
+                "warning",
+                "codegen <1:1>-<1:28>",
+                "codegen <2:1>-<2:28>",
+            ]
+        );
+
+        fs::write(&path, "pub extern \"C\" {}\n").unwrap();
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let result = Parser::new(&path)
+            .unwrap()
+            .with_observer(recorder.clone())
+            .parse();
+        assert!(result.is_err());
+        assert_eq!(
+            recorder.lock().unwrap().events,
+            ["error", "parsed <1:5>-<1:18>"]
+        );
+
+        // The errors aborting the parsing or the code generation are reported as well
+        let diagnostics = |source: &str| {
+            fs::write(&path, source).unwrap();
+            let recorder = Arc::new(Mutex::new(Recorder::default()));
+            let parser = Parser::new(&path).unwrap().with_observer(recorder.clone());
+            if let Ok(crt) = parser.parse() {
+                let session = CompilationSession::new().with_observer(recorder.clone());
+                assert!(session.compile(&crt).is_err());
+            }
+            let events = recorder.lock().unwrap().events.clone();
+            events
+        };
+        assert_eq!(diagnostics("fn f( {}\n"), ["error"]);
+        assert_eq!(
+            diagnostics("fn f() -> i32 { let x: i32; let y: i32; return x + y; }\n"),
+            ["parsed <1:1>-<1:56>", "error", "error"]
+        );
+        assert_eq!(
+            diagnostics("fn f() -> i32 { let x: i32 = true; return x; }\n"),
+            ["parsed <1:1>-<1:47>", "codegen <1:1>-<1:47>", "error"]
+        );
+    }
+}
@@ -6,10 +6,16 @@ use std::rc::Rc;
 
 use fallible_iterator::{FallibleIterator, Peekable};
 
-use crate::ast::Crate;
+use crate::ast::{Crate, ImportASTNode, ItemASTNode};
+use crate::cancel::CancellationToken;
 use crate::lexer::Lexer;
+use crate::limits::{MemoryLimits, MemoryUsage, Resource};
+use crate::observer::SharedObserver;
+use crate::token::Token;
+use crate::trace;
 
 use self::error::*;
+use self::productions::ExprContext;
 
 pub mod error;
 mod productions;
@@ -22,20 +28,91 @@ pub type Result<T> = std::result::Result<T, ParserError>;
 pub struct Parser {
     lexer: Peekable<Lexer>,
     filename: Rc<str>,
+    path: Rc<Path>,
     /// Recoverable errors that occurred during parsing.
     errors: Vec<RecoverableParserError>,
+    /// The number of recoverable errors after which the parsing is aborted.
+    max_errors: usize,
+    limits: MemoryLimits,
+    /// The number of tokens consumed so far.
+    tokens: usize,
+    /// The number of AST nodes created so far (of the current item, when streaming).
+    nodes: usize,
+    observer: Option<SharedObserver>,
+    /// The context of the expression being parsed.
+    context: ExprContext,
+    /// The opening delimiters that haven't been closed yet, from the outermost.
+    delimiters: Vec<Token>,
+    /// The innermost opening delimiter if the last consumed token was a closing delimiter
+    /// that didn't match it.
+    mismatched: Option<Token>,
 }
 
 impl Parser {
+    /// The default number of recoverable errors after which the parsing is aborted
+    /// (see [`Parser::with_max_errors`]).
+    pub const DEFAULT_MAX_ERRORS: usize = 100;
+
     /// Creates a new `Parser` that will parse the file at the given path.
     pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Parser> {
-        let lexer = Lexer::new(path)?;
+        Ok(Parser::from_lexer(Lexer::new(path)?))
+    }
+
+    /// Creates a new `Parser` that will parse the file at the given path,
+    /// aborting with [`ParserError::Interrupted`] when the given token is cancelled or times out.
+    ///
+    /// The token is checked before every token of the input (see [`Lexer::with_cancellation`]).
+    pub fn with_cancellation<P: AsRef<Path>>(
+        path: P,
+        cancellation: CancellationToken,
+    ) -> io::Result<Parser> {
+        let lexer = Lexer::new(path)?.with_cancellation(cancellation);
+        Ok(Parser::from_lexer(lexer))
+    }
+
+    fn from_lexer(lexer: Lexer) -> Parser {
         let filename = lexer.get_filename_owned();
-        Ok(Parser {
+        let path = lexer.get_path().into();
+        Parser {
             lexer: lexer.peekable(),
             filename,
+            path,
             errors: Vec::new(),
-        })
+            max_errors: Parser::DEFAULT_MAX_ERRORS,
+            limits: MemoryLimits::default(),
+            tokens: 0,
+            nodes: 0,
+            observer: None,
+            context: ExprContext::Nested,
+            delimiters: Vec::new(),
+            mismatched: None,
+        }
+    }
+
+    /// Sets the memory limits of the parser.
+    ///
+    /// If the input exceeds any of them, the parsing aborts with [`ParserError::LimitExceeded`].
+    pub fn with_limits(self, limits: MemoryLimits) -> Parser {
+        Parser { limits, ..self }
+    }
+
+    /// Sets the number of recoverable errors after which the parsing is aborted
+    /// with [`ParserError::TooManyErrors`] (at least 1), so that a pathological input
+    /// cannot accumulate an unbounded list of errors.
+    pub fn with_max_errors(self, max_errors: usize) -> Parser {
+        Parser {
+            max_errors: max_errors.max(1),
+            ..self
+        }
+    }
+
+    /// Registers the observer notified about the consumed tokens, the parsed items
+    /// and the recoverable errors (see [`PipelineObserver`](crate::observer::PipelineObserver)).
+    pub fn with_observer(self, observer: SharedObserver) -> Parser {
+        Parser {
+            observer: Some(observer),
+            ..self
+        }
     }
 
     /// Returns the name of the file being parsed.
@@ -43,10 +120,940 @@ impl Parser {
         &self.filename
     }
 
+    /// Returns the path of the file being parsed.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     /// Parses the input file and returns `ast::Crate`
     pub fn parse(self) -> Result<Crate> {
-        let root = self.parse_crate()?;
-        let root = Box::new(root);
-        Ok(Crate::new(root))
+        self.parse_with_usage().map(|(crt, _)| crt)
+    }
+
+    /// Parses the input file and returns `ast::Crate` together with the resources used
+    /// to parse it.
+    pub fn parse_with_usage(self) -> Result<(Crate, MemoryUsage)> {
+        trace::span!(INFO, "parse", file = %self.filename);
+        let limits = self.limits;
+        let path = self.path.clone();
+        let (root, tokens) = self.parse_crate()?;
+        let crt = Crate::new(Box::new(root)).with_source_path(path);
+
+        let usage = MemoryUsage::new(tokens, crt.node_count());
+        trace::event!(DEBUG, tokens, nodes = usage.nodes(), "parsed the crate");
+        limits.check(Resource::Nodes, usage.nodes())?;
+        Ok((crt, usage))
+    }
+
+    /// Turns the parser into a stream of the items of the input file, which are parsed
+    /// one at a time, so that a large file can be processed without holding its whole AST
+    /// in memory (see [`codegen::stream`](crate::codegen::stream)).
+    ///
+    /// The [node limit](MemoryLimits::max_nodes) applies to every item separately.
+    pub fn items(self) -> ItemStream {
+        ItemStream {
+            parser: self,
+            imports: Vec::new(),
+            finished: false,
+        }
+    }
+}
+
+/// A stream of the items of a file, parsed one at a time (see [`Parser::items`]).
+///
+/// The `ItemStream` is a [FallibleIterator] with [`ItemASTNode`]s as items and [`ParserError`]
+/// as error type. The recoverable errors are collected like by [`Parser::parse`], and returned
+/// as [`ParserError::Aggregated`] at the end of the file instead of finishing the stream.
+/// The stream is fused after an error.
+pub struct ItemStream {
+    parser: Parser,
+    imports: Vec<ImportASTNode>,
+    finished: bool,
+}
+
+impl ItemStream {
+    /// Returns the name of the file being parsed.
+    pub fn filename(&self) -> &str {
+        self.parser.filename()
+    }
+
+    /// Returns the path of the file being parsed.
+    pub fn path(&self) -> &Path {
+        self.parser.path()
+    }
+
+    /// Returns the imports parsed so far (i.e. the ones preceding the last returned item,
+    /// or all of them at the end of the stream).
+    pub fn imports(&self) -> &[ImportASTNode] {
+        &self.imports
+    }
+
+    /// Returns the number of tokens consumed so far.
+    pub fn tokens(&self) -> usize {
+        self.parser.tokens
+    }
+}
+
+impl FallibleIterator for ItemStream {
+    type Item = ItemASTNode;
+    type Error = ParserError;
+
+    fn next(&mut self) -> Result<Option<ItemASTNode>> {
+        if self.finished {
+            return Ok(None);
+        }
+        let result = self.parser.stream_next_item(&mut self.imports);
+        if !matches!(result, Ok(Some(_))) {
+            self.finished = true;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::abi::Abi;
+    use crate::ast::error::SemanticError;
+    use crate::ast::{
+        ASTNode, AsASTNode, Attribute, AttributeArg, BorrowExprASTNode, ElseASTNode, ItemASTNode,
+        ItemMeta, TupleIndexASTNode, Visibility,
+    };
+    use crate::cancel::Interrupted;
+    use crate::metrics::NodeKind;
+    use crate::observer::Diagnostic;
+    use crate::test_util::{try_parse as parse, TempDir};
+    use crate::token::{Position, Span, Token, TokenType};
+
+    use super::*;
+
+    #[test]
+    fn test_function_without_body() {
+        let errors = match parse("fn f(x: i32) -> i32;\nfn main() {}\n") {
+            Err(ParserError::Aggregated(errors)) => errors,
+            result => panic!("Expected aggregated errors, got {:?}", result),
+        };
+        assert!(matches!(
+            errors.as_slice(),
+            [RecoverableParserError::SemanticError(
+                SemanticError::FunctionWithoutBody { .. }
+            )]
+        ));
+        assert!(parse("extern \"C\" { fn f(x: i32) -> i32; }\n").is_ok());
+    }
+
+    #[test]
+    fn test_empty_files() {
+        let sources = [
+            ("", Position::new_at(1, 1)),
+            ("  \n\t\n\n", Position::new_at(4, 1)),
+            (
+                "// A comment\n    // and another one",
+                Position::new_at(2, 23),
+            ),
+        ];
+        for (source, end_pos) in sources {
+            let crt = parse(source).unwrap();
+            assert!(crt.root().items().is_empty(), "{source:?}");
+            assert_eq!(crt.root().span(), Span::new(Position::new(), end_pos));
+            assert_eq!(crt.to_source(), "");
+            assert_eq!(crt.stats().function_count(), 0);
+            assert!(crt.check_initialization().is_empty());
+            assert!(crt.check_unsafety().is_empty());
+            assert!(crate::transpile::to_c(&crt).is_ok());
+
+            let decoded = Crate::from_bytes(&crt.to_bytes()).unwrap();
+            assert_eq!(decoded.root().span(), crt.root().span());
+            assert!(decoded.root().items().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_duplicate_params() {
+        let errors = match parse("fn f(a: i32, _: i32, _: bool, a: i32) {}\n") {
+            Err(ParserError::Aggregated(errors)) => errors,
+            result => panic!("Expected aggregated errors, got {:?}", result),
+        };
+        let span = |col| Span::new(Position::new_at(1, col), Position::new_at(1, col + 1));
+        assert_eq!(
+            errors,
+            vec![RecoverableParserError::SemanticError(
+                SemanticError::DuplicateParameter {
+                    name: "a".into(),
+                    first_span: span(6),
+                    span: span(31),
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_param_spans() {
+        let crt = parse("fn f(a: i32, mut b: bool) {}\n").unwrap();
+        let func = match &crt.root().items()[0] {
+            crate::ast::ItemASTNode::Func(func) => func,
+            _ => unreachable!(),
+        };
+        let span = |start, end| Span::new(Position::new_at(1, start), Position::new_at(1, end));
+        let spans: Vec<(Span, Span, Span)> = func
+            .proto()
+            .get_param_iter()
+            .map(|p| (p.span(), p.name_span(), p.ty().span()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (span(6, 12), span(6, 7), span(9, 12)),
+                (span(14, 25), span(18, 19), span(21, 25)),
+            ]
+        );
+        let mut_spans: Vec<Option<Span>> = func
+            .proto()
+            .get_param_iter()
+            .map(|p| p.mut_span())
+            .collect();
+        assert_eq!(mut_spans, vec![None, Some(span(14, 17))]);
+
+        let crt = parse(
+            "static A: i32 = 1;
+pub static  mut B: i32 = 2;
+",
+        )
+        .unwrap();
+        let mut_spans: Vec<Option<Span>> = crt
+            .root()
+            .items()
+            .iter()
+            .map(|item| match item {
+                ItemASTNode::Static(stat) => stat.mut_span(),
+                _ => unreachable!(),
+            })
+            .collect();
+        let span = Span::new(Position::new_at(2, 13), Position::new_at(2, 16));
+        assert_eq!(mut_spans, vec![None, Some(span)]);
+    }
+
+    #[test]
+    fn test_visibility() {
+        let crt =
+            parse("pub fn f() {}\nfn g() {}\npub static mut X: i32 = 1;\nstatic Y: i32 = 2;\n")
+                .unwrap();
+        let items: Vec<(Visibility, String)> = crt
+            .items()
+            .iter()
+            .map(|item| (item.visibility(), item.to_string()))
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                (Visibility::Public, "Function Pub \"f\" <1:1>-<1:14>".into()),
+                (Visibility::Private, "Function \"g\" <2:1>-<2:10>".into()),
+                (
+                    Visibility::Public,
+                    "Static Pub Mut <3:1>-<3:27> \"X\"".into()
+                ),
+                (Visibility::Private, "Static <4:1>-<4:19> \"Y\"".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_inputs() {
+        let source = "pub fn f(mut a: i32) -> bool { let x: i32 = a * (2 + 1); return x < 3; }\n\
+            extern \"C\" { static mut S: f64; }\n";
+        // Every prefix of a valid input is parsed without panicking
+        for (end, _) in source.char_indices() {
+            let _ = parse(&source[..end]);
+        }
+        for source in [
+            "fn",
+            "pub",
+            "}}",
+            "fn f() {} )",
+            "extern \"C\" {",
+            "$",
+            "\"abc",
+        ] {
+            assert!(parse(source).is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let source = "fn f(x: i32) -> i32 {\n    let y: i32 = x\n    if x = 1 { return y; };\n    \
+            while x = 2 {};\n    return y;\n}\n";
+        let err = parse(source).unwrap_err();
+        let suggestions: Vec<String> = err
+            .diagnostics()
+            .iter()
+            .map(|d| d.suggestion().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            suggestions,
+            [
+                "add a semicolon: insert `;` at 2:19",
+                "compare for equality: replace <3:8>-<3:13> with `x == 1`",
+                "compare for equality: replace <4:11>-<4:16> with `x == 2`",
+            ]
+        );
+        assert!(err.diagnostics()[0].to_json().starts_with(
+            r#"{"level":"error","message":"Expected ';' at position 2:19","suggestions":[{"message":"add a semicolon","#
+        ));
+        assert_eq!(
+            err.diagnostics()[1].to_json(),
+            r#"{"level":"error","message":"Expected a boolean condition, found an assignment at <3:8>-<3:13> (use `x == 1` to compare for equality)","suggestions":[{"message":"compare for equality","span":{"start":{"line":3,"column":8},"end":{"line":3,"column":13}},"replacement":"x == 1"}]}"#
+        );
+
+        let crt = parse("fn f() -> i32 { let x: i32 = 1; x = 2; return x; }\n").unwrap();
+        let errors = crt.check_initialization();
+        let suggestion = Diagnostic::Error(&errors[0]).suggestion().unwrap();
+        assert_eq!(
+            suggestion.to_string(),
+            "make the variable mutable: insert `mut ` at 1:21"
+        );
+
+        let err = parse("fn f() {\n").unwrap_err();
+        assert_eq!(err.diagnostics(), [Diagnostic::Fatal(&err)]);
+        assert_eq!(err.diagnostics()[0].suggestion(), None);
+    }
+
+    #[test]
+    fn test_list_commas() {
+        assert!(parse("fn f(a: i32, b: i32,) -> i32 { return f(a, b,); }\n").is_ok());
+
+        let errors = match parse("fn f(a: i32 mut b: i32) { f(1 (2), 3 4); }\n") {
+            Err(ParserError::Aggregated(errors)) => errors,
+            result => panic!("Expected aggregated errors, got {:?}", result),
+        };
+        assert_eq!(
+            errors,
+            vec![
+                RecoverableParserError::MissingToken(TokenType::Comma, Position::new_at(1, 12)),
+                RecoverableParserError::MissingToken(TokenType::Comma, Position::new_at(1, 30)),
+                RecoverableParserError::MissingToken(TokenType::Comma, Position::new_at(1, 37)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_semicolon_recovery() {
+        let source = "fn f() -> i32 {\n    let x: i32 = 1\n    x = x + 1\n    return x\n}\n";
+        let errors = match parse(source) {
+            Err(ParserError::Aggregated(errors)) => errors,
+            result => panic!("Expected aggregated errors, got {:?}", result),
+        };
+        assert_eq!(
+            errors,
+            vec![
+                RecoverableParserError::MissingToken(TokenType::Semi, Position::new_at(2, 19)),
+                RecoverableParserError::ExprWithoutSemicolon(
+                    Position::new_at(3, 14),
+                    Token::new(
+                        TokenType::Return,
+                        Position::new_at(4, 5),
+                        Position::new_at(4, 11)
+                    )
+                ),
+            ]
+        );
+
+        // An expression without a semicolon at the end of a block is its return value
+        assert!(parse("fn f() -> i32 { let x: i32 = 1; x }\n").is_ok());
+
+        // Any statement can follow the expression, not only the ones starting with a keyword
+        let source = "fn f() -> i32 {\n    f()\n    (1 + 2) * 3\n}\n";
+        let errors = match parse(source) {
+            Err(ParserError::Aggregated(errors)) => errors,
+            result => panic!("Expected aggregated errors, got {:?}", result),
+        };
+        assert_eq!(
+            errors,
+            vec![RecoverableParserError::ExprWithoutSemicolon(
+                Position::new_at(2, 8),
+                Token::new(
+                    TokenType::LPar,
+                    Position::new_at(3, 5),
+                    Position::new_at(3, 6)
+                )
+            )]
+        );
+        assert!(errors[0]
+            .to_string()
+            .starts_with("Expected ';' at position 2:8"));
+        let suggestion = errors[0].suggestion().unwrap();
+        assert!(suggestion.is_insertion());
+        assert_eq!(suggestion.span().start(), Position::new_at(2, 8));
+        assert_eq!(suggestion.replacement(), ";");
+
+        // A token which cannot start a statement is still unexpected
+        let err = parse("fn f() -> i32 { 1 ) }\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ParserError::UnclosedDelimiter { error, .. }
+            if matches!(*error, ParserError::UnexpectedToken { expected: "'}'", .. })
+        ));
+    }
+
+    #[test]
+    fn test_do_while_loops() {
+        let crt = parse(
+            "fn f(mut n: i32) -> i32 { do { n = n - 1; } while n > 0; do { } while false }\n",
+        )
+        .unwrap();
+        assert!(crt.to_source().contains("} while n > 0;"));
+
+        // Like any expression without a block, a do-while loop needs a semicolon after it
+        let errors = match parse("fn f() { do { } while false let x: i32 = 1; }\n") {
+            Err(ParserError::Aggregated(errors)) => errors,
+            result => panic!("Expected aggregated errors, got {:?}", result),
+        };
+        assert_eq!(
+            errors,
+            vec![RecoverableParserError::ExprWithoutSemicolon(
+                Position::new_at(1, 28),
+                Token::new(
+                    TokenType::Let,
+                    Position::new_at(1, 29),
+                    Position::new_at(1, 32)
+                )
+            )]
+        );
+        assert!(parse("fn f() { do { }; }\n").is_err());
+    }
+
+    #[test]
+    fn test_for_loops() {
+        let crt =
+            parse("fn f(n: i32) { for i in 0..n + 1 { f(i); } for _ in 1..=n {} }\n").unwrap();
+        assert!(crt.to_source().contains("for i in 0..n + 1 {"));
+        assert!(crt.to_source().contains("for _ in 1..=n {}"));
+
+        let err = parse("fn f() { for i in 0 {} }\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ParserError::UnexpectedToken {
+                expected: "'..', '..='",
+                ..
+            }
+        ));
+        assert!(parse("fn f() { for 1 in 0..1 {} }\n").is_err());
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let crt = parse(
+            "extern \"C\" { fn puts(s: &str) -> i32; }\n\
+            fn f() { let s: &str = r\"a\\b\"; unsafe { puts(\"\\u{48}i\\n\"); puts(s); } }\n",
+        )
+        .unwrap();
+        let source = crt.to_source();
+        assert!(source.contains("fn puts(s: &str) -> i32;"));
+        assert!(source.contains("let s: &str = \"a\\\\b\";"));
+        assert!(source.contains("puts(\"Hi\\n\");"));
+
+        // The string slices are always shared
+        let err = parse("fn f(s: &mut str) {}\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ParserError::UnexpectedToken {
+                expected: "<type>",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_unclosed_delimiters() {
+        let opener = |err: ParserError| match err {
+            ParserError::UnclosedDelimiter { delimiter, .. } => delimiter.span().start(),
+            err => panic!("Expected an unclosed delimiter, got {:?}", err),
+        };
+        let err = parse("fn f() {\n    let x: i32 = 1;\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected <expr>, got end of file at <3:1>-<3:1>\nnote: unclosed '{' opened at <1:8>-<1:9>"
+        );
+        assert_eq!(
+            opener(parse("fn f() { (1 }\n").unwrap_err()),
+            Position::new_at(1, 10)
+        );
+
+        // The errors not caused by a delimiter don't point to one
+        let err = parse("fn f() { g(1 + ) }\n").unwrap_err();
+        assert!(matches!(err, ParserError::UnexpectedToken { .. }));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let crt = parse("#[unroll(4)]\npub fn f() {}\n").unwrap();
+        let func = match &crt.root().items()[0] {
+            ItemASTNode::Func(func) => func,
+            _ => unreachable!(),
+        };
+        let attr = func.attribute(Attribute::UNROLL).unwrap();
+        assert_eq!(attr.args(), [AttributeArg::Int(4)]);
+        assert_eq!(attr.to_string(), "#[unroll(4)]");
+        assert_eq!(attr.span().end(), Position::new_at(1, 13));
+
+        let err = parse("#[inline]\nfn f() {}\n#[unroll(2)]\nstatic X: i32 = 1;\n").unwrap_err();
+        assert_eq!(
+            err,
+            ParserError::Aggregated(vec![
+                RecoverableParserError::UnsupportedAttribute(
+                    "inline".into(),
+                    Span::new(Position::new_at(1, 1), Position::new_at(1, 10))
+                ),
+                RecoverableParserError::UnsupportedAttribute(
+                    "unroll".into(),
+                    Span::new(Position::new_at(3, 1), Position::new_at(3, 13))
+                ),
+            ])
+        );
+        let err = parse("#[unroll(4]\nfn f() {}\n").unwrap_err();
+        assert!(
+            matches!(err, ParserError::UnclosedDelimiter { delimiter, .. }
+            if delimiter.span().start() == Position::new_at(1, 9))
+        );
+    }
+
+    #[test]
+    fn test_builtin_constants() {
+        let source =
+            "fn f() -> f64 {\n    return f64::INFINITY - f64::NEG_INFINITY * f64::NAN;\n}\n";
+        let crt = parse(source).unwrap();
+        assert_eq!(crt.to_source(), source);
+
+        let err = parse("fn f() -> f64 { return f64::MAX; }\n").unwrap_err();
+        assert_eq!(
+            err,
+            ParserError::Aggregated(vec![SemanticError::UnknownAssociatedConstant {
+                path: "f64::MAX".into(),
+                span: Span::new(Position::new_at(1, 24), Position::new_at(1, 32)),
+            }
+            .into()])
+        );
+    }
+
+    #[test]
+    fn test_layout_queries() {
+        let source = "fn f() -> u64 {\n    return size_of::<i64>() * align_of::<&str>() + size_of::<()>();\n}\n";
+        let crt = parse(source).unwrap();
+        assert_eq!(crt.to_source(), source);
+
+        let err = parse("fn f() -> i32 { return offset_of::<i32>(); }\n").unwrap_err();
+        assert_eq!(
+            err,
+            ParserError::Aggregated(vec![SemanticError::UnknownGenericFunction {
+                name: "offset_of".into(),
+                span: Span::new(Position::new_at(1, 24), Position::new_at(1, 33)),
+            }
+            .into()])
+        );
+        // The parentheses of the call are required
+        assert!(parse("fn f() -> i32 { return size_of::<i32>; }\n").is_err());
+    }
+
+    #[test]
+    fn test_else_branches() {
+        fn else_spans(node: &dyn ASTNode, spans: &mut Vec<Span>) {
+            if let Some(else_node) = node.as_any().downcast_ref::<ElseASTNode>() {
+                spans.push(else_node.span());
+            }
+            for child in node.children().into_iter().flatten() {
+                else_spans(child, spans);
+            }
+        }
+
+        let source =
+            "fn f(x: i32) -> i32 {\n    if x < 0 { 1 } else if x > 0 { 2 } else { 3 }\n}\n";
+        let crt = parse(source).unwrap();
+        assert_eq!(
+            crt.to_source(),
+            "fn f(x: i32) -> i32 {\n    if x < 0 {\n        1\n    } else if x > 0 {\n        2\n    } else {\n        3\n    }\n}\n"
+        );
+
+        // The spans start at the `else` keyword, and the nested one is a child of the `else if`
+        let mut spans = Vec::new();
+        else_spans(crt.root().as_ast(), &mut spans);
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(Position::new_at(2, 20), Position::new_at(2, 50)),
+                Span::new(Position::new_at(2, 40), Position::new_at(2, 50)),
+            ]
+        );
+        assert_eq!(crt.stats().count(NodeKind::Else), 2);
+        assert_eq!(crt.to_string().matches("Else ").count(), 2);
+    }
+
+    #[test]
+    fn test_tuples() {
+        fn index_spans(node: &dyn ASTNode, spans: &mut Vec<(u32, Span)>) {
+            if let Some(index) = node.as_any().downcast_ref::<TupleIndexASTNode>() {
+                spans.push((index.index(), index.span()));
+            }
+            for child in node.children().into_iter().flatten() {
+                index_spans(child, spans);
+            }
+        }
+
+        let source = "fn f(t: ((i32, bool), (u8,))) -> (i32) {\n    \
+            let u: (f64, ()) = (1.5, (),);\n    \
+            -t.0.0 + (t.1).0 as i32 + (1).0\n}\n";
+        let crt = parse(source).unwrap();
+        assert_eq!(
+            crt.to_source(),
+            "fn f(t: ((i32, bool), (u8,))) -> i32 {\n    \
+            let u: (f64, ()) = (1.5, ());\n    \
+            -t.0.0 + (t.1).0 as i32 + (1).0\n}\n"
+        );
+        assert_eq!(crt.stats().count(NodeKind::Tuple), 1);
+        assert_eq!(crt.stats().count(NodeKind::TupleIndex), 5);
+
+        // The nested indices are lexed as a single float literal, which is split
+        let mut spans = Vec::new();
+        index_spans(crt.root().as_ast(), &mut spans);
+        assert_eq!(
+            spans[..2],
+            [
+                (
+                    0,
+                    Span::new(Position::new_at(3, 6), Position::new_at(3, 11))
+                ),
+                (0, Span::new(Position::new_at(3, 6), Position::new_at(3, 9))),
+            ]
+        );
+
+        let single = parse("fn f() { let t: (i32,) = (1,); }\n").unwrap();
+        assert!(single.to_source().contains("let t: (i32,) = (1,);"));
+
+        let err = parse("fn f(t: (i32, i32)) -> i32 { t.x }\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ParserError::UnexpectedToken {
+                expected: "<tuple index>",
+                ..
+            }
+        ));
+        assert!(parse("fn f(t: (i32, i32)) -> i32 { t.0u8 }\n").is_err());
+        assert!(parse("fn f(t: (i32, i32)) -> i32 { t.1e2 }\n").is_err());
+        assert!(parse("fn f() { let t: (i32 bool) = (1, true); }\n").is_err());
+        assert!(parse("fn f() { let t: (i32, bool) = (1 true); }\n").is_err());
+        assert!(parse("fn f() { let t: (i32, bool) = (, 1); }\n").is_err());
+    }
+
+    #[test]
+    fn test_references() {
+        fn borrow_spans(node: &dyn ASTNode, spans: &mut Vec<Span>) {
+            if let Some(borrow) = node.as_any().downcast_ref::<BorrowExprASTNode>() {
+                spans.push(borrow.span());
+            }
+            for child in node.children().into_iter().flatten() {
+                borrow_spans(child, spans);
+            }
+        }
+
+        let source = "fn f(mut x: i32, r: &&i32, t: &(u8, &str)) -> &mut (i32, bool) {\n    \
+            let y: &mut i32 = &mut x;\n    \
+            *y = **r * -*&x & *t.0;\n    \
+            f(x, &&x, (*t).1)\n}\n";
+        let crt = parse(source).unwrap();
+        assert_eq!(crt.to_source(), source);
+        assert_eq!(crt.stats().count(NodeKind::Borrow), 4);
+        assert_eq!(crt.stats().count(NodeKind::Deref), 6);
+
+        // `&&` is lexed as a single token, which is split into two borrows
+        let mut spans = Vec::new();
+        borrow_spans(crt.root().as_ast(), &mut spans);
+        assert_eq!(
+            spans[2..],
+            [
+                Span::new(Position::new_at(4, 10), Position::new_at(4, 13)),
+                Span::new(Position::new_at(4, 11), Position::new_at(4, 13)),
+            ]
+        );
+
+        let err = parse("fn f() { let r: &mut i32 = &mut; }\n").unwrap_err();
+        assert!(matches!(
+            err,
+            ParserError::UnexpectedToken {
+                expected: "'!', '-', '&', '*', <expr>",
+                ..
+            }
+        ));
+        assert!(parse("fn f() { let r: & = 1; }\n").is_err());
+        assert!(parse("fn f() { let r: &mut mut i32 = 1; }\n").is_err());
+    }
+
+    #[test]
+    fn test_return_expressions() {
+        let source = "fn f(c: bool) -> i32 {\n    \
+            if c {\n        \
+                return if c {\n            \
+                    1\n        \
+                } else {\n            \
+                    2\n        \
+                };\n    \
+            };\n    \
+            return loop {}\n}\n";
+        assert_eq!(parse(source).unwrap().to_source(), source);
+    }
+
+    #[test]
+    fn test_qualified_extern_functions() {
+        let source = "extern \"C\" fn f(n: i32);\n\npub extern \"system\" fn g() {}\n";
+        let crt = parse(source).unwrap();
+        assert_eq!(crt.to_source(), source);
+        match crt.root().items() {
+            [ItemASTNode::Extern(ext), ItemASTNode::Func(func)] => {
+                assert!(ext.is_qualified());
+                assert_eq!(ext.abi(), Abi::C);
+                assert_eq!(
+                    ext.span(),
+                    Span::new(Position::new_at(1, 1), Position::new_at(1, 25))
+                );
+                assert_eq!(func.proto().abi(), Some(Abi::System));
+                assert_eq!(func.span().start(), Position::new_at(3, 1));
+            }
+            items => panic!("Unexpected items: {:?}", items),
+        }
+
+        let err = parse("pub extern \"C\" fn f();\nextern \"C\" fn g() -> i32 5").unwrap_err();
+        assert!(matches!(err, ParserError::UnexpectedToken { .. }));
+        let err = parse("pub extern \"C\" fn f();\n").unwrap_err();
+        assert_eq!(
+            err,
+            ParserError::Aggregated(vec![SemanticError::VisibilityNotPermitted {
+                span: Span::new(Position::new_at(1, 1), Position::new_at(1, 4)),
+            }
+            .into()])
+        );
+    }
+
+    #[test]
+    fn test_unsupported_abi() {
+        let err = parse("extern \"stdcall\" { fn f(); }\n").unwrap_err();
+        assert_eq!(
+            err,
+            ParserError::Aggregated(vec![RecoverableParserError::UnsupportedAbi(
+                "stdcall".into()
+            )])
+        );
+        assert_eq!(
+            err.to_string().lines().last(),
+            Some("Unsupported ABI: stdcall (expected one of \"C\", \"system\")")
+        );
+    }
+
+    #[test]
+    fn test_error_limit() {
+        let source = format!("fn f() {{\n{}}}\n", "    let x: i32 = 1\n".repeat(150));
+        let dir = TempDir::new("parser_error_limit");
+        let path = dir.join("main.mrs");
+        fs::write(&path, &source).unwrap();
+        let errors = |parser: Parser| match parser.parse() {
+            Err(ParserError::TooManyErrors(errors)) => errors,
+            result => panic!("Expected too many errors, got {:?}", result),
+        };
+        let default = errors(Parser::new(&path).unwrap());
+        let limited = errors(Parser::new(&path).unwrap().with_max_errors(3));
+        let unlimited = Parser::new(&path)
+            .unwrap()
+            .with_max_errors(usize::MAX)
+            .parse();
+
+        assert_eq!(default.len(), Parser::DEFAULT_MAX_ERRORS);
+        assert_eq!(limited, default[..3]);
+        assert!(matches!(unlimited, Err(ParserError::Aggregated(errors)) if errors.len() == 150));
+        let message = ParserError::TooManyErrors(limited).to_string();
+        assert!(message.starts_with("Too many errors occurred during parsing:\n"));
+        assert!(message.ends_with("\nAborting after 3 errors\n"));
+
+        // The errors differing only in their locations are grouped
+        let message = ParserError::TooManyErrors(default).to_string();
+        assert_eq!(message.lines().count(), 3);
+        assert!(message.contains("\nExpected ';' (occurred 100 times, first at 2:19)\n"));
+
+        // The repeated messages are printed only once
+        let err = parse("extern \"cdecl\" fn f();\nextern \"cdecl\" fn g();\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Multiple errors occurred during parsing:\n\
+            Unsupported ABI: cdecl (expected one of \"C\", \"system\") (occurred 2 times)\n"
+        );
+    }
+
+    #[test]
+    fn test_unexpected_token_message() {
+        let err = parse("fn 5() {}\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected <ident>, got integer literal '5' at <1:4>-<1:5>"
+        );
+        let err = parse("fn f() {}\n{").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Multiple errors occurred during parsing:\n\
+            Expected <item>, got '{' at <2:1>-<2:2> (skipped <2:1>-<2:2> up to the next item)\n"
+        );
+    }
+
+    #[test]
+    fn test_item_recovery() {
+        let source = "fn f() {}\n\
+            struct S { fn x() }\n\
+            fn g(a: i32 b: i32) {}\n\
+            let y: i32 = 2; = 1;\n\
+            pub fn h() {}\n";
+        let errors = match parse(source) {
+            Err(ParserError::Aggregated(errors)) => errors,
+            result => panic!("Expected aggregated errors, got {:?}", result),
+        };
+        let skipped = |error: &RecoverableParserError| match error {
+            RecoverableParserError::SkippedInput(error, span) => (error.to_string(), *span),
+            error => panic!("Expected skipped input, got {:?}", error),
+        };
+        let span = |start: (usize, usize), end: (usize, usize)| {
+            Span::new(
+                Position::new_at(start.0, start.1),
+                Position::new_at(end.0, end.1),
+            )
+        };
+        assert_eq!(errors.len(), 3);
+        // The `fn` inside the braces doesn't start an item
+        assert_eq!(
+            skipped(&errors[0]),
+            (
+                "Keyword `struct` is not yet supported in μRust at <2:1>-<2:7>".to_owned(),
+                span((2, 1), (2, 20))
+            )
+        );
+        // The errors inside the items following the skipped input are still reported
+        assert_eq!(
+            errors[1],
+            RecoverableParserError::MissingToken(TokenType::Comma, Position::new_at(3, 12))
+        );
+        assert_eq!(
+            skipped(&errors[2]),
+            (
+                "Expected <item>, got 'let' at <4:1>-<4:4>".to_owned(),
+                span((4, 1), (4, 21))
+            )
+        );
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let dir = TempDir::new("parser_cancellation");
+        let path = dir.join("main.mrs");
+        fs::write(
+            &path,
+            "fn f() {}
+"
+            .repeat(100),
+        )
+        .unwrap();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let result = Parser::with_cancellation(&path, token).unwrap().parse();
+        assert!(matches!(
+            result,
+            Err(ParserError::Interrupted(Interrupted::Cancelled))
+        ));
+
+        let token = CancellationToken::with_timeout(std::time::Duration::ZERO);
+        let result = Parser::with_cancellation(&path, token).unwrap().parse();
+        assert!(matches!(
+            result,
+            Err(ParserError::Interrupted(Interrupted::TimedOut))
+        ));
+
+        let token = CancellationToken::with_timeout(std::time::Duration::from_secs(3600));
+        assert!(Parser::with_cancellation(&path, token)
+            .unwrap()
+            .parse()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_memory_limits() {
+        let dir = TempDir::new("parser_limits");
+        let path = dir.join("main.mrs");
+        fs::write(&path, "fn f(x: i32) -> i32 { return x + 1; }\n").unwrap();
+        let parse = |limits| {
+            Parser::new(&path)
+                .unwrap()
+                .with_limits(limits)
+                .parse_with_usage()
+        };
+
+        let (_, usage) = parse(MemoryLimits::new()).unwrap();
+        assert_eq!(usage.tokens(), 17);
+        assert!(usage.nodes() > 0 && usage.nodes() <= 2 * usage.tokens());
+
+        let limits = MemoryLimits::new()
+            .with_max_tokens(usage.tokens())
+            .with_max_nodes(usage.nodes());
+        assert_eq!(parse(limits).unwrap().1, usage);
+
+        let exceeded = |result: Result<(Crate, MemoryUsage)>| match result {
+            Err(ParserError::LimitExceeded(err)) => (err.resource(), err.limit()),
+            result => panic!("Expected an exceeded limit, got {:?}", result),
+        };
+        let limits = MemoryLimits::new().with_max_tokens(10);
+        assert_eq!(exceeded(parse(limits)), (Resource::Tokens, 10));
+        let limits = MemoryLimits::new().with_max_nodes(usage.nodes() - 1);
+        assert_eq!(
+            exceeded(parse(limits)),
+            (Resource::Nodes, usage.nodes() - 1)
+        );
+
+        // The nodes are checked as they are created, without parsing the rest of the input
+        let terms = vec!["1"; 1000].join(" + ");
+        fs::write(&path, format!("fn f() -> i32 {{ return {terms}; }}\n")).unwrap();
+        let limits = MemoryLimits::new().with_max_nodes(10);
+        let mut items = Parser::new(&path).unwrap().with_limits(limits).items();
+        assert!(matches!(items.next(), Err(ParserError::LimitExceeded(_))));
+        assert!(items.tokens() < 30);
+    }
+
+    #[test]
+    fn test_item_stream() {
+        let dir = TempDir::new("parser_stream");
+        let path = dir.join("main.mrs");
+        let source = "use a::f;\nfn g() {}\nuse b::h;\nstatic X: i32 = 1;\n";
+        fs::write(&path, source).unwrap();
+
+        let mut items = Parser::new(&path).unwrap().items();
+        assert!(matches!(items.next(), Ok(Some(ItemASTNode::Func(_)))));
+        assert_eq!(items.imports().len(), 1);
+        assert!(matches!(items.next(), Ok(Some(ItemASTNode::Static(_)))));
+        assert!(matches!(items.next(), Ok(None)));
+        assert_eq!(items.imports().len(), 2);
+        assert_eq!(items.tokens(), 24);
+        assert!(matches!(items.next(), Ok(None)));
+
+        // The recoverable errors are returned at the end
+        fs::write(&path, "fn f() {}\n+ fn g() {}\n").unwrap();
+        let items = Parser::new(&path).unwrap().items();
+        let results: Vec<_> = items.iterator().collect();
+        assert!(matches!(
+            results.as_slice(),
+            [
+                Ok(ItemASTNode::Func(_)),
+                Ok(ItemASTNode::Func(_)),
+                Err(ParserError::Aggregated(_))
+            ]
+        ));
+
+        // The node limit applies to every item separately
+        fs::write(&path, "fn f() {}\nfn g() {}\nfn h() { return; }\n").unwrap();
+        let limits = MemoryLimits::new().with_max_nodes(3);
+        let mut items = Parser::new(&path).unwrap().with_limits(limits).items();
+        assert_eq!(items.by_ref().take(2).count(), Ok(2));
+        assert!(matches!(items.next(), Err(ParserError::LimitExceeded(_))));
+        assert!(matches!(items.next(), Ok(None)));
     }
 }
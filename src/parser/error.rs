@@ -1,12 +1,19 @@
 //! Error types for the parser.
 
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
+use std::mem;
 use std::rc::Rc;
 
+use crate::abi::Abi;
 use crate::ast::error::SemanticError;
-use crate::lexer::error::LexerError;
-use crate::token::{Position, Token, TokenType};
+use crate::cancel::Interrupted;
+use crate::lexer::error::{LexerError, LexerErrorKind};
+use crate::limits::LimitExceeded;
+use crate::observer::Diagnostic;
+use crate::suggestion::Suggestion;
+use crate::token::{Position, Span, Token, TokenType};
 
 /// The type of error that can occur during parsing.
 #[derive(Debug, Clone, PartialEq)]
@@ -14,6 +21,9 @@ pub enum ParserError {
     /// A list of errors that occurred during parsing,
     /// but did not prevent the parser from continuing.
     Aggregated(Vec<RecoverableParserError>),
+    /// A list of recoverable errors that reached the [limit](crate::parser::Parser::with_max_errors),
+    /// so the parsing was aborted.
+    TooManyErrors(Vec<RecoverableParserError>),
     /// An error that occurred during lexing.
     LexicalError(LexerError),
     /// Unexpected end of file.
@@ -25,6 +35,24 @@ pub enum ParserError {
         /// The expected token type.
         expected: &'static str,
     },
+    /// A keyword that is reserved for future use was encountered.
+    ReservedKeyword {
+        /// The spelling of the keyword.
+        keyword: &'static str,
+        /// The span of the keyword.
+        span: Span,
+    },
+    /// The parsing was aborted by a [cancellation token](crate::cancel::CancellationToken).
+    Interrupted(Interrupted),
+    /// The input exceeds one of the [memory limits](crate::limits::MemoryLimits) of the parser.
+    LimitExceeded(LimitExceeded),
+    /// An error caused by a delimiter (`(`, `{` or `[`) that wasn't closed.
+    UnclosedDelimiter {
+        /// The error at the point where the delimiter should have been closed.
+        error: Box<ParserError>,
+        /// The unclosed opening delimiter.
+        delimiter: Token,
+    },
 }
 
 impl fmt::Display for ParserError {
@@ -32,25 +60,123 @@ impl fmt::Display for ParserError {
         match self {
             ParserError::Aggregated(errs) => {
                 writeln!(f, "Multiple errors occurred during parsing:")?;
-                for err in errs {
-                    writeln!(f, "{}", err)?;
-                }
-                Ok(())
+                fmt_errors(f, errs)
+            }
+            ParserError::TooManyErrors(errs) => {
+                writeln!(f, "Too many errors occurred during parsing:")?;
+                fmt_errors(f, errs)?;
+                writeln!(f, "Aborting after {} errors", errs.len())
             }
             ParserError::LexicalError(err) => fmt::Display::fmt(err, f),
             ParserError::UnexpectedEOF => write!(f, "Unexpected end of file"),
             ParserError::UnexpectedToken { actual, expected } => {
                 write!(f, "Expected {}, got {}", expected, actual)
             }
+            ParserError::ReservedKeyword { keyword, span } => {
+                write!(
+                    f,
+                    "Keyword `{}` is not yet supported in μRust at {}",
+                    keyword, span
+                )
+            }
+            ParserError::Interrupted(reason) => fmt::Display::fmt(reason, f),
+            ParserError::LimitExceeded(err) => fmt::Display::fmt(err, f),
+            ParserError::UnclosedDelimiter { error, delimiter } => {
+                write!(
+                    f,
+                    "{}\nnote: unclosed {} opened at {}",
+                    error,
+                    delimiter.ty(),
+                    delimiter.span()
+                )
+            }
+        }
+    }
+}
+
+impl ParserError {
+    /// Returns the diagnostics of this error, i.e. the [recoverable errors](RecoverableParserError)
+    /// if the parser collected them, or this error as a [fatal](Diagnostic::Fatal) one.
+    pub fn diagnostics(&self) -> Vec<Diagnostic<'_>> {
+        match self {
+            ParserError::Aggregated(errs) | ParserError::TooManyErrors(errs) => {
+                errs.iter().map(Diagnostic::ParserError).collect()
+            }
+            _ => vec![Diagnostic::Fatal(self)],
         }
     }
 }
 
 impl Error for ParserError {}
 
+/// Writes every distinct error on a separate line, in the order of their first occurrence.
+///
+/// The errors with the same message (apart from their location) and the same kind of location
+/// are written once, noting how many times they occurred and where the first one did.
+fn fmt_errors(f: &mut fmt::Formatter<'_>, errs: &[RecoverableParserError]) -> fmt::Result {
+    type Group<'a> = (&'a RecoverableParserError, String, Option<Location>, usize);
+    let mut groups: Vec<Group> = Vec::new();
+    let mut indices: HashMap<(String, Option<mem::Discriminant<Location>>), usize> = HashMap::new();
+    for err in errs {
+        let (message, location) = err.located_message();
+        let key = (message, location.as_ref().map(mem::discriminant));
+        match indices.get(&key) {
+            Some(&index) => groups[index].3 += 1,
+            None => {
+                let (message, _) = key.clone();
+                indices.insert(key, groups.len());
+                groups.push((err, message, location, 1));
+            }
+        }
+    }
+    for (err, message, location, count) in groups {
+        match (count, location) {
+            (1, _) => writeln!(f, "{}", err)?,
+            (_, Some(first)) => writeln!(
+                f,
+                "{} (occurred {} times, first at {})",
+                message, count, first
+            )?,
+            (_, None) => writeln!(f, "{} (occurred {} times)", message, count)?,
+        }
+    }
+    Ok(())
+}
+
+/// The location of a [recoverable error](RecoverableParserError).
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Location {
+    Position(Position),
+    Span(Span),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Position(pos) => fmt::Display::fmt(pos, f),
+            Location::Span(span) => fmt::Display::fmt(span, f),
+        }
+    }
+}
+
 impl From<LexerError> for ParserError {
     fn from(error: LexerError) -> Self {
-        ParserError::LexicalError(error)
+        match error.kind() {
+            LexerErrorKind::Interrupted(reason) => ParserError::Interrupted(*reason),
+            _ => ParserError::LexicalError(error),
+        }
+    }
+}
+
+impl From<Interrupted> for ParserError {
+    fn from(reason: Interrupted) -> Self {
+        ParserError::Interrupted(reason)
+    }
+}
+
+impl From<LimitExceeded> for ParserError {
+    fn from(error: LimitExceeded) -> Self {
+        ParserError::LimitExceeded(error)
     }
 }
 
@@ -62,8 +188,17 @@ pub enum RecoverableParserError {
     SemanticError(SemanticError),
     /// Missing a required token.
     MissingToken(TokenType, Position),
+    /// An expression without a semicolon (at the position where it is needed),
+    /// which is followed by another statement (starting with the token),
+    /// so it cannot be the return value of its block.
+    ExprWithoutSemicolon(Position, Token),
     /// An unsupported ABI was encountered.
     UnsupportedAbi(Rc<str>),
+    /// An attribute that is unknown or not permitted on the item was encountered.
+    UnsupportedAttribute(Rc<str>, Span),
+    /// An unexpected token was encountered instead of an item. It was skipped together with
+    /// the following input (the span) up to the next item.
+    SkippedInput(Box<ParserError>, Span),
 }
 
 impl fmt::Display for RecoverableParserError {
@@ -71,11 +206,76 @@ impl fmt::Display for RecoverableParserError {
         match self {
             RecoverableParserError::SemanticError(err) => fmt::Display::fmt(err, f),
             RecoverableParserError::MissingToken(expected, pos) => {
-                write!(f, "Expected token {:?} at position {}", expected, pos)
+                write!(f, "Expected {} at position {}", expected, pos)
+            }
+            RecoverableParserError::ExprWithoutSemicolon(pos, next) => {
+                write!(
+                    f,
+                    "Expected ';' at position {} -- only the last expression of a block \
+                    can omit it, but the expression is followed by {} at {}",
+                    pos,
+                    next.ty(),
+                    next.span()
+                )
             }
             RecoverableParserError::UnsupportedAbi(abi) => {
-                write!(f, "Unsupported ABI: {}", abi)
+                let supported = Abi::SUPPORTED.map(|abi| format!("\"{}\"", abi));
+                write!(
+                    f,
+                    "Unsupported ABI: {} (expected one of {})",
+                    abi,
+                    supported.join(", ")
+                )
+            }
+            RecoverableParserError::UnsupportedAttribute(name, span) => {
+                write!(f, "Unsupported attribute `{}` at {}", name, span)
             }
+            RecoverableParserError::SkippedInput(error, span) => {
+                write!(f, "{} (skipped {} up to the next item)", error, span)
+            }
+        }
+    }
+}
+
+impl RecoverableParserError {
+    /// Returns the [`Suggestion`] fixing the error, if there is one.
+    pub fn suggestion(&self) -> Option<Suggestion> {
+        match self {
+            RecoverableParserError::SemanticError(err) => err.suggestion(),
+            RecoverableParserError::MissingToken(TokenType::Semi, pos)
+            | RecoverableParserError::ExprWithoutSemicolon(pos, _) => {
+                Some(Suggestion::insert("add a semicolon", *pos, ";"))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the message of the error without its location, together with the location
+    /// (if the message has one).
+    fn located_message(&self) -> (String, Option<Location>) {
+        match self {
+            RecoverableParserError::MissingToken(expected, pos) => (
+                format!("Expected {}", expected),
+                Some(Location::Position(*pos)),
+            ),
+            RecoverableParserError::ExprWithoutSemicolon(pos, next) => (
+                format!(
+                    "Expected ';' -- only the last expression of a block can omit it, \
+                    but the expression is followed by {}",
+                    next.ty()
+                ),
+                Some(Location::Position(*pos)),
+            ),
+            RecoverableParserError::UnsupportedAttribute(name, span) => (
+                format!("Unsupported attribute `{}`", name),
+                Some(Location::Span(*span)),
+            ),
+            RecoverableParserError::SkippedInput(error, span) => (
+                format!("{} (skipped up to the next item)", error),
+                Some(Location::Span(*span)),
+            ),
+            RecoverableParserError::SemanticError(_)
+            | RecoverableParserError::UnsupportedAbi(_) => (self.to_string(), None),
         }
     }
 }
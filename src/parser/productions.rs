@@ -1,13 +1,20 @@
 //! A module containing all production rules for the parser.
 
-use either::Either;
+use std::collections::HashMap;
+use std::rc::Rc;
+
 use fallible_iterator::FallibleIterator;
 
+use crate::abi;
 use crate::ast::error::SemanticError;
 use crate::ast::*;
+use crate::limits::Resource;
+use crate::observer::{self, Diagnostic};
 use crate::parser::error::{ParserError, RecoverableParserError};
 use crate::parser::{Parser, Result};
-use crate::token::{Position, Span, Token, TokenType::*};
+use crate::pretty::SourcePrinter;
+use crate::token::{Position, Span, Token, TokenType, TokenType::*};
+use crate::trace;
 
 use self::macros::*;
 
@@ -18,14 +25,34 @@ mod ops;
 
 impl Parser {
     /// Consumes the next token from the lexer.
+    ///
+    /// The token is accounted against the [memory limits](Parser::with_limits) of the parser.
     fn consume(&mut self) -> Result<Token> {
         match self.lexer.next() {
             Ok(None) => Err(ParserError::UnexpectedEOF),
-            Ok(Some(t)) => Ok(t),
+            Ok(Some(t)) => {
+                self.tokens += 1;
+                self.limits.check(Resource::Tokens, self.tokens)?;
+                self.track_delimiter(&t);
+                observer::notify(&self.observer, |o| o.on_token(&t));
+                Ok(t)
+            }
             Err(e) => Err(e.into()),
         }
     }
 
+    /// Accounts a newly created AST node against the [memory limits](Parser::with_limits)
+    /// of the parser.
+    ///
+    /// Only the nodes certain to stay in the AST (e.g. the statements, operators and operands)
+    /// are accounted, so the count never exceeds the size of the final AST, which is checked
+    /// exactly once it is parsed.
+    fn add_node(&mut self) -> Result<()> {
+        self.nodes += 1;
+        self.limits.check(Resource::Nodes, self.nodes)?;
+        Ok(())
+    }
+
     /// Peeks at the next token from the lexer without consuming it.
     fn peek(&mut self) -> Result<&Token> {
         match self.lexer.peek() {
@@ -35,71 +62,343 @@ impl Parser {
         }
     }
 
+    /// Updates the stack of the unclosed delimiters with the consumed token.
+    fn track_delimiter(&mut self, token: &Token) {
+        self.mismatched = None;
+        match token.ty() {
+            LPar | LBra | LSqu => self.delimiters.push(token.clone()),
+            RPar | RBra | RSqu => match self.delimiters.last() {
+                Some(open)
+                    if matches!(
+                        (open.ty(), token.ty()),
+                        (LPar, RPar) | (LBra, RBra) | (LSqu, RSqu)
+                    ) =>
+                {
+                    self.delimiters.pop();
+                }
+                open => self.mismatched = open.cloned(),
+            },
+            _ => {}
+        }
+    }
+
+    /// Wraps the error in [`ParserError::UnclosedDelimiter`] if it was caused
+    /// by an unclosed delimiter, i.e. it occurred at the end of the file
+    /// or at a closing delimiter that doesn't match the innermost opening one.
+    fn with_unclosed_delimiter(&mut self, error: ParserError) -> ParserError {
+        let delimiter = match &error {
+            ParserError::UnexpectedEOF => self.delimiters.pop(),
+            ParserError::UnexpectedToken { actual, .. } if actual.is_eof() => self.delimiters.pop(),
+            ParserError::UnexpectedToken { actual, .. }
+                if matches!(actual.ty(), RPar | RBra | RSqu) =>
+            {
+                self.mismatched.take()
+            }
+            _ => None,
+        };
+        match delimiter {
+            Some(delimiter) => ParserError::UnclosedDelimiter {
+                error: Box::new(error),
+                delimiter,
+            },
+            None => error,
+        }
+    }
+
     /// Pushes a recoverable error into the parser's error list.
-    fn push_rcv_error(&mut self, error: RecoverableParserError) {
+    ///
+    /// # Errors
+    ///
+    /// If the list reaches the [limit](Parser::with_max_errors), the parsing is aborted
+    /// with [`ParserError::TooManyErrors`] containing the collected errors.
+    fn push_rcv_error(&mut self, error: RecoverableParserError) -> Result<()> {
+        observer::notify(&self.observer, |o| {
+            o.on_diagnostic(Diagnostic::ParserError(&error))
+        });
         self.errors.push(error);
+        if self.errors.len() >= self.max_errors {
+            return Err(ParserError::TooManyErrors(std::mem::take(&mut self.errors)));
+        }
+        Ok(())
     }
 
     //TODO Improve documentation
     /// Parses the input file into a [`CrateASTNode`], consuming the `Parser`.
-    pub(super) fn parse_crate(mut self) -> Result<CrateASTNode> {
-        let items = self.parse_items()?;
+    ///
+    /// Returns the root node together with the number of consumed tokens.
+    pub(super) fn parse_crate(mut self) -> Result<(CrateASTNode, usize)> {
+        match self.parse_root() {
+            Ok(root) => Ok((root, self.tokens)),
+            Err(err) => Err(self.report_fatal(err)),
+        }
+    }
+
+    /// Wraps the error that aborted the parsing like [`Parser::with_unclosed_delimiter`],
+    /// and reports it to the observer, unless it consists of the recoverable errors
+    /// (which were reported as they occurred).
+    fn report_fatal(&mut self, error: ParserError) -> ParserError {
+        let error = self.with_unclosed_delimiter(error);
+        if !matches!(
+            error,
+            ParserError::Aggregated(_) | ParserError::TooManyErrors(_)
+        ) {
+            observer::notify(&self.observer, |o| {
+                o.on_diagnostic(Diagnostic::Fatal(&error))
+            });
+        }
+        error
+    }
+
+    fn parse_root(&mut self) -> Result<CrateASTNode> {
+        let (imports, items) = self.parse_items()?;
 
         if !self.errors.is_empty() {
-            return Err(ParserError::Aggregated(self.errors));
+            return Err(ParserError::Aggregated(std::mem::take(&mut self.errors)));
         }
 
-        let end_pos = match self.consume() {
-            Ok(t) if t.is_eof() => t.span().end(),
-            Ok(t) => panic!("Unexpected token: {:?} - expected EOF.", t.ty()),
-            Err(e) => panic!("No finishing EOF token found.\nError: {}", e),
-        };
+        // The crate spans the whole file, so an empty file (whose EOF token is at its start)
+        // is an empty crate with a zero-length span
+        let end_pos = assert_token!(self, EOF, "EOF").end();
         let span = Span::new(Position::new(), end_pos);
         let name = self.filename.clone();
 
-        Ok(CrateASTNode::new(name, items, span))
+        Ok(CrateASTNode::new(name, imports, items, span))
     }
 
-    fn parse_items(&mut self) -> Result<Vec<ItemASTNode>> {
+    fn parse_items(&mut self) -> Result<(Vec<ImportASTNode>, Vec<ItemASTNode>)> {
+        let mut imports = Vec::new();
         let mut result = Vec::new();
+        while let Some(item) = self.parse_next_item(&mut imports)? {
+            result.push(item);
+        }
+        Ok((imports, result))
+    }
+
+    /// Parses the next item, pushing the imports preceding it to `imports`.
+    ///
+    /// Returns `None` at the end of the file, without consuming the EOF token.
+    fn parse_next_item(&mut self, imports: &mut Vec<ImportASTNode>) -> Result<Option<ItemASTNode>> {
         loop {
             let next = self.peek()?;
             match next.ty() {
-                Fn | Static | Extern => {
+                Use => {
+                    let import = self.parse_import()?;
+                    imports.push(import);
+                }
+                Pound | Pub | Fn | Static | Extern => {
+                    let attributes = self.parse_attributes()?;
                     let item = self.parse_item()?;
-                    result.push(item);
+                    let item = self.with_attributes(item, attributes)?;
+                    observer::notify(&self.observer, |o| o.on_item_parsed(&item));
+                    return Ok(Some(item));
+                }
+                EOF => return Ok(None),
+                _ => self.skip_to_item()?,
+            }
+        }
+    }
+
+    /// Parses the next item of the [stream](crate::parser::ItemStream), returning `None`
+    /// at the end of the file (after which the stream is finished).
+    ///
+    /// The nodes of every item are checked against the
+    /// [limit](crate::limits::MemoryLimits::max_nodes) separately.
+    pub(super) fn stream_next_item(
+        &mut self,
+        imports: &mut Vec<ImportASTNode>,
+    ) -> Result<Option<ItemASTNode>> {
+        self.nodes = 0;
+        let result = match self.parse_next_item(imports) {
+            Ok(Some(item)) => {
+                self.limits.check(Resource::Nodes, node_count(&item))?;
+                return Ok(Some(item));
+            }
+            Ok(None) if !self.errors.is_empty() => {
+                Err(ParserError::Aggregated(std::mem::take(&mut self.errors)))
+            }
+            Ok(None) => self.consume().map(|_| None),
+            Err(err) => Err(err),
+        };
+        result.map_err(|err| self.report_fatal(err))
+    }
+
+    /// Reports the unexpected token where an item should start, and skips it together with
+    /// the following tokens up to the next plausible start of an item (or the end of the file),
+    /// so that the rest of the file still produces an AST and diagnostics.
+    ///
+    /// The delimited groups (e.g. the body of an unsupported construct) are skipped as a whole.
+    fn skip_to_item(&mut self) -> Result<()> {
+        let token = self.consume()?;
+        let start_pos = token.span().start();
+        let mut end_pos = token.span().end();
+        // The number of the delimited groups the last skipped token is in
+        fn nested(depth: usize, token: &Token) -> usize {
+            match token.ty() {
+                LPar | LBra | LSqu => depth + 1,
+                RPar | RBra | RSqu => depth.saturating_sub(1),
+                _ => depth,
+            }
+        }
+        let mut depth = nested(0, &token);
+        let error: Result<()> = unknown_token!(self, token, "<item>");
+        let error = error.expect_err("An unexpected token should always be an error");
+
+        loop {
+            let next = self.peek()?;
+            match next.ty() {
+                EOF => break,
+                Use | Pound | Pub | Fn | Static | Extern if depth == 0 => break,
+                _ => {
+                    let token = self.consume()?;
+                    end_pos = token.span().end();
+                    depth = nested(depth, &token);
+                }
+            }
+        }
+
+        let span = Span::new(start_pos, end_pos);
+        self.push_rcv_error(RecoverableParserError::SkippedInput(Box::new(error), span))?;
+        Ok(())
+    }
+
+    fn parse_import(&mut self) -> Result<ImportASTNode> {
+        trace::span!(TRACE, "parse_import");
+        // UseDeclaration rule
+        let start_pos = assert_token!(self, Use, "'use'").start();
+        let module = assert_ident!(self, "<ident>");
+        assert_token!(self, PathSep, "'::'");
+
+        let token = self.consume()?;
+        let name_span = token.span();
+        let name = assert_ident!(self, token, "<ident>");
+
+        let end_pos = assert_token!(self, Semi, "';'").end();
+        let span = Span::new(start_pos, end_pos);
+        Ok(ImportASTNode::new(module, name, name_span, span))
+    }
+
+    fn parse_attributes(&mut self) -> Result<Vec<Attribute>> {
+        trace::span!(TRACE, "parse_attributes");
+        let mut result = Vec::new();
+        // OuterAttribute rule
+        while let Some(pound_span) = expect_token!(self, Pound) {
+            assert_token!(self, LSqu, "'['");
+            let name = assert_ident!(self, "<ident>");
+
+            let mut args = Vec::new();
+            if expect_token!(self, LPar).is_some() {
+                loop {
+                    // AttrArgs rule
+                    let token = self.consume()?;
+                    let arg = match token.ty() {
+                        IntLit(value, _) => AttributeArg::Int(*value),
+                        Ident(ident) => AttributeArg::Ident(ident.clone()),
+                        RPar => break,
+                        _ => return unknown_token!(self, token, "<attribute argument>, ')'"),
+                    };
+                    args.push(arg);
+
+                    // AttrArgs' rule
+                    let token = self.consume()?;
+                    match token.ty() {
+                        Comma => {}
+                        RPar => break,
+                        _ => return unknown_token!(self, token, "',', ')'"),
+                    }
                 }
-                EOF => return Ok(result),
-                _ => return unknown_token!(self, "<item>"),
             }
+
+            let end_pos = assert_token!(self, RSqu, "']'").end();
+            let span = Span::new(pound_span.start(), end_pos);
+            result.push(Attribute::new(name, args, span));
+        }
+        Ok(result)
+    }
+
+    /// Attaches the attributes to the item, reporting the ones that are not supported on it.
+    fn with_attributes(
+        &mut self,
+        item: ItemASTNode,
+        attributes: Vec<Attribute>,
+    ) -> Result<ItemASTNode> {
+        let (supported, unsupported): (Vec<_>, Vec<_>) = attributes
+            .into_iter()
+            .partition(|attr| attr.is_supported() && matches!(item, ItemASTNode::Func(_)));
+        for attr in unsupported {
+            let error =
+                RecoverableParserError::UnsupportedAttribute(attr.name().into(), attr.span());
+            self.push_rcv_error(error)?;
         }
+        Ok(match item {
+            ItemASTNode::Func(func) if !supported.is_empty() => {
+                ItemASTNode::Func(Box::new(func.with_attributes(supported)))
+            }
+            item => item,
+        })
     }
 
     fn parse_item(&mut self) -> Result<ItemASTNode> {
+        trace::span!(TRACE, "parse_item");
+        // Visibility rule
+        let pub_span = expect_token!(self, Pub);
+
         let next = self.peek()?;
         Ok(match next.ty() {
-            Fn => ItemASTNode::Func(Box::new(self.parse_func()?)),
-            Static => ItemASTNode::Static(Box::new(self.parse_static(false)?)),
-            Extern => ItemASTNode::Extern(Box::new(self.parse_extern()?)),
+            Fn => ItemASTNode::Func(Box::new(self.parse_func(pub_span)?)),
+            Static => ItemASTNode::Static(Box::new(self.parse_static(pub_span, false)?)),
+            Extern => self.parse_extern(pub_span)?,
             _ => return unknown_token!(self, "<item>"),
         })
     }
 
-    fn parse_func(&mut self) -> Result<FuncASTNode> {
+    /// Returns the visibility of an item based on the span of its `pub` keyword (if any).
+    fn visibility(pub_span: Option<Span>) -> Visibility {
+        match pub_span {
+            Some(_) => Visibility::Public,
+            None => Visibility::Private,
+        }
+    }
+
+    fn parse_func(&mut self, pub_span: Option<Span>) -> Result<FuncASTNode> {
+        trace::span!(TRACE, "parse_func");
         let proto = self.parse_func_proto()?;
-        let body = self.parse_block_expr()?;
-        let span = Span::new(proto.span().start(), body.span().end());
-        Ok(FuncASTNode::new(proto, body, span))
+
+        let next = self.peek()?;
+        let body = match next.ty() {
+            LBra => self.parse_block_expr()?,
+            Semi => {
+                // Forward declarations are not supported outside extern blocks
+                let semi_span = assert_token!(self, Semi, "';'");
+                self.push_rcv_error(
+                    SemanticError::FunctionWithoutBody { span: proto.span() }.into(),
+                )?;
+                BlockASTNode::new(Vec::new(), semi_span)
+            }
+            _ => return unknown_token!(self, "'{', ';'"),
+        };
+
+        let start_pos = pub_span.map_or(proto.span().start(), |s| s.start());
+        let span = Span::new(start_pos, body.span().end());
+        Ok(FuncASTNode::new(
+            proto,
+            body,
+            Parser::visibility(pub_span),
+            span,
+        ))
     }
 
     fn parse_func_proto(&mut self) -> Result<FuncProtoASTNode> {
+        trace::span!(TRACE, "parse_func_proto");
         let start_pos = assert_token!(self, Fn, "'fn'").start();
 
-        let ident = assert_ident!(self, "<ident>");
+        let token = self.consume()?;
+        let ident_span = token.span();
+        let ident = assert_ident!(self, token, "<ident>");
 
         assert_token!(self, LPar, "'('");
 
         let params = self.parse_func_params()?;
+        self.check_duplicate_params(&params)?;
 
         // If there is no return type, the prototype ends with the closing parenthesis.
         let mut end_pos = assert_token!(self, RPar, "')'").end();
@@ -115,7 +414,9 @@ impl Parser {
         };
 
         let span = Span::new(start_pos, end_pos);
-        Ok(FuncProtoASTNode::new(ident, params, ret_ty, span))
+        Ok(FuncProtoASTNode::new(
+            ident, ident_span, params, ret_ty, span,
+        ))
     }
 
     fn parse_func_params(&mut self) -> Result<Vec<ParamASTNode>> {
@@ -135,16 +436,55 @@ impl Parser {
             // FunctionParameters' rule
             let next = self.peek()?;
             match next.ty() {
-                Comma => assert_token!(self, Comma, "','"),
+                Comma => {
+                    assert_token!(self, Comma, "','");
+                }
                 RPar => return Ok(result),
+                // A missing comma between two parameters
+                Mut | Underscore | Ident(_) => {
+                    let end_pos = result
+                        .last()
+                        .map_or(next.span().start(), |p| p.span().end());
+                    self.push_rcv_error(RecoverableParserError::MissingToken(Comma, end_pos))?;
+                }
                 _ => return unknown_token!(self, "',', ')'"),
             };
         }
     }
 
+    /// Pushes a recoverable error for every parameter whose name is already used
+    /// by a previous parameter.
+    fn check_duplicate_params(&mut self, params: &[ParamASTNode]) -> Result<()> {
+        let mut names: HashMap<Rc<str>, Span> = HashMap::new();
+        for param in params {
+            let Ok(Some(name)) = param.assignee().map(|a| a.pattern()) else {
+                continue;
+            };
+            // `_` does not bind anything, so it can be repeated
+            if name.as_ref() == "_" {
+                continue;
+            }
+            match names.get(&name) {
+                Some(first_span) => {
+                    let error = SemanticError::DuplicateParameter {
+                        name: name.as_ref().into(),
+                        first_span: *first_span,
+                        span: param.name_span(),
+                    };
+                    self.push_rcv_error(error.into())?;
+                }
+                None => {
+                    names.insert(name, param.name_span());
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn parse_param(&mut self) -> Result<ParamASTNode> {
         // FunctionParam + FunctionParam' rules
-        let mutability = self.parse_mut()?;
+        let start_pos = self.peek()?.span().start();
+        let mut_span = self.parse_mut()?;
         let token = self.consume()?;
         let ident_span = token.span();
         let ident = assert_ident_or_underscore!(self, token);
@@ -157,7 +497,8 @@ impl Parser {
             None => Box::new(UnderscoreASTNode::new(ident_span)),
             Some(ident) => Box::new(PathASTNode::new(ident, ident_span)),
         };
-        Ok(ParamASTNode::new(assignee, ty, mutability, ident_span))
+        let span = Span::new(start_pos, ty.span().end());
+        Ok(ParamASTNode::new(assignee, ty, mut_span, span))
     }
 
     fn parse_func_ret_ty(&mut self) -> Result<Option<TypeASTMetaNode>> {
@@ -172,13 +513,13 @@ impl Parser {
         }
     }
 
-    fn parse_mut(&mut self) -> Result<bool> {
+    fn parse_mut(&mut self) -> Result<Option<Span>> {
         let token = self.peek()?;
         if let Mut = token.ty() {
-            self.consume().expect("Mut token should be present.");
-            Ok(true)
+            let token = self.consume().expect("Mut token should be present.");
+            Ok(Some(token.span()))
         } else {
-            Ok(false)
+            Ok(None)
         }
     }
 
@@ -195,11 +536,16 @@ impl Parser {
         }
     }
 
-    fn parse_static(&mut self, is_extern: bool) -> Result<StaticASTNode> {
+    fn parse_static(&mut self, pub_span: Option<Span>, is_extern: bool) -> Result<StaticASTNode> {
+        trace::span!(TRACE, "parse_static");
         let start_pos = assert_token!(self, Static, "'static'").start();
+        let start_pos = pub_span.map_or(start_pos, |s| s.start());
+        let visibility = Parser::visibility(pub_span);
 
-        let mutability = self.parse_mut()?;
-        let ident = assert_ident!(self, "'_', 'mut', <ident>");
+        let mut_span = self.parse_mut()?;
+        let token = self.consume()?;
+        let ident_span = token.span();
+        let ident = assert_ident!(self, token, "'_', 'mut', <ident>");
 
         assert_token!(self, Colon, "':'");
         let ty = self.parse_type()?;
@@ -213,41 +559,89 @@ impl Parser {
                 if is_extern {
                     self.push_rcv_error(
                         SemanticError::ExternStaticWithInitializer { span: value.span() }.into(),
-                    );
+                    )?;
                 }
-                StaticASTNode::new_with_assignment(ident, value, ty, mutability, span)
+                StaticASTNode::new_with_assignment(
+                    ident, ident_span, value, ty, mut_span, visibility, span,
+                )
             }
             None => {
                 if !is_extern {
-                    self.push_rcv_error(SemanticError::StaticWithoutInitializer { span }.into());
+                    self.push_rcv_error(SemanticError::StaticWithoutInitializer { span }.into())?;
                 }
-                StaticASTNode::new(ident, ty, mutability, span)
+                StaticASTNode::new(ident, ident_span, ty, mut_span, visibility, span)
             }
         };
         Ok(item)
     }
 
-    fn parse_extern(&mut self) -> Result<ExternASTNode> {
+    fn parse_extern(&mut self, pub_span: Option<Span>) -> Result<ItemASTNode> {
+        trace::span!(TRACE, "parse_extern");
         let start_pos = assert_token!(self, Extern, "'extern'").start();
         let next = self.consume()?;
         let abi = match next.ty() {
-            //TODO Add support for other ABIs
-            Abi(abi) => match abi.as_ref() {
-                "C" => abi.clone(),
-                _ => {
-                    self.push_rcv_error(RecoverableParserError::UnsupportedAbi(abi.clone()));
-                    abi.clone()
+            StrLit(abi) => match abi::Abi::from_name(abi) {
+                Some(abi) => abi,
+                None => {
+                    self.push_rcv_error(RecoverableParserError::UnsupportedAbi(abi.clone()))?;
+                    abi::Abi::default()
                 }
             },
             _ => return unknown_token!(self, next, "<ABI>"),
         };
 
+        if let Fn = self.peek()?.ty() {
+            return self.parse_qualified_func(pub_span, start_pos, abi);
+        }
+        if let Some(span) = pub_span {
+            self.push_rcv_error(SemanticError::VisibilityNotPermitted { span }.into())?;
+        }
+
         assert_token!(self, LBra, "'{'");
         let items = self.parse_extern_items()?;
         let end_pos = assert_token!(self, RBra, "'}'").end();
 
         let span = Span::new(start_pos, end_pos);
-        Ok(ExternASTNode::new(abi, items, span))
+        Ok(ItemASTNode::Extern(Box::new(ExternASTNode::new(
+            abi, items, span,
+        ))))
+    }
+
+    // ExternBlock'' rule
+    fn parse_qualified_func(
+        &mut self,
+        pub_span: Option<Span>,
+        start_pos: Position,
+        abi: abi::Abi,
+    ) -> Result<ItemASTNode> {
+        let proto = self.parse_func_proto()?;
+
+        let next = self.peek()?;
+        Ok(match next.ty() {
+            LBra => {
+                // A function definition with the given ABI
+                let body = self.parse_block_expr()?;
+                let start_pos = pub_span.map_or(start_pos, |s| s.start());
+                let span = Span::new(start_pos, body.span().end());
+                let func = FuncASTNode::new(
+                    proto.with_abi(abi),
+                    body,
+                    Parser::visibility(pub_span),
+                    span,
+                );
+                ItemASTNode::Func(Box::new(func))
+            }
+            Semi => {
+                // A declaration equivalent to an extern block with a single function
+                let end_pos = assert_token!(self, Semi, "';'").end();
+                if let Some(span) = pub_span {
+                    self.push_rcv_error(SemanticError::VisibilityNotPermitted { span }.into())?;
+                }
+                let span = Span::new(start_pos, end_pos);
+                ItemASTNode::Extern(Box::new(ExternASTNode::new_qualified(proto, abi, span)))
+            }
+            _ => return unknown_token!(self, "'{', ';'"),
+        })
     }
 
     fn parse_extern_items(&mut self) -> Result<Vec<ExternItem>> {
@@ -260,7 +654,7 @@ impl Parser {
                     result.push(ExternItem::Func(Box::new(item)));
                 }
                 Static => {
-                    let item = self.parse_static(true)?;
+                    let item = self.parse_static(None, true)?;
                     result.push(ExternItem::Static(Box::new(item)));
                 }
                 RBra => return Ok(result),
@@ -281,7 +675,7 @@ impl Parser {
                 let body_span = self.parse_block_expr()?.span();
                 self.push_rcv_error(
                     SemanticError::ExternFunctionWithBody { span: body_span }.into(),
-                );
+                )?;
             }
             _ => return unknown_token!(self, "';', '{'"),
         }
@@ -290,11 +684,14 @@ impl Parser {
     }
 
     fn parse_let_stmt(&mut self) -> Result<LetASTNode> {
+        trace::span!(TRACE, "parse_let_stmt");
         let start_pos = assert_token!(self, Let, "'let'").start();
 
         //TODO Add support for destructuring
-        let mutability = self.parse_mut()?;
-        let ident = assert_ident!(self, "<pattern>");
+        let mut_span = self.parse_mut()?;
+        let token = self.consume()?;
+        let ident_span = token.span();
+        let ident = assert_ident!(self, token, "<pattern>");
 
         //TODO Add support for type inference
         assert_token!(self, Colon, "':'");
@@ -308,16 +705,16 @@ impl Parser {
         let end_pos = match expect_token!(self, Semi) {
             Some(span) => span.end(),
             None => {
-                self.push_rcv_error(RecoverableParserError::MissingToken(Semi, end_pos));
+                self.push_rcv_error(RecoverableParserError::MissingToken(Semi, end_pos))?;
                 end_pos
             }
         };
         let span = Span::new(start_pos, end_pos);
 
-        let assignee = Box::new(PathASTNode::new(ident, span));
+        let assignee = Box::new(PathASTNode::new(ident, ident_span));
         let let_stmt = match val {
-            Some(val) => LetASTNode::new_with_assignment(assignee, ty, val, mutability, span),
-            None => LetASTNode::new(assignee, ty, mutability, span),
+            Some(val) => LetASTNode::new_with_assignment(assignee, ty, val, mut_span, span),
+            None => LetASTNode::new(assignee, ty, mut_span, span),
         };
 
         Ok(let_stmt)
@@ -327,13 +724,23 @@ impl Parser {
     // because (I believe) it cannot be represented as production rule in a context-free grammar.
     /// Returns (expr_stmt, had_trailing_semi)
     fn parse_expr_stmt(&mut self) -> Result<(ExprStmtASTNode, bool)> {
-        let expr = self.parse_expr_wo_block()?;
-
-        let semi = expect_token!(self, Semi);
-        let end_pos = match semi {
-            Some(span) => span.end(),
-            None => expr.span().end(),
+        trace::span!(TRACE, "parse_expr_stmt");
+        // An expression with a block (e.g. `if`) at the start of a statement is a statement
+        // on its own, so it is not an operand and doesn't need a semicolon
+        let with_block = matches!(self.peek()?.ty(), LBra | If | Unsafe | Loop | While | For);
+        let expr = if with_block {
+            self.parse_expr_w_block()?
+        } else {
+            self.parse_expr_wo_block()?
         };
+
+        // An expression without a block followed by another statement is missing
+        // its semicolon, which is reported by `parse_stmts`
+        let mut semi = expect_token!(self, Semi).map(|span| span.end());
+        if semi.is_none() && with_block && !matches!(self.peek()?.ty(), RBra) {
+            semi = Some(expr.span().end());
+        }
+        let end_pos = semi.unwrap_or(expr.span().end());
         let span = Span::new(expr.span().start(), end_pos);
 
         let expr_stmt = ExprStmtASTNode::new(expr, span);
@@ -341,14 +748,16 @@ impl Parser {
     }
 
     fn parse_expr(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        trace::span!(TRACE, "parse_expr");
         self.parse_expr_wo_block()
     }
 
     fn parse_expr_wo_block(&mut self) -> Result<Box<dyn ExprASTNode>> {
         let next = self.peek()?;
         match next.ty() {
-            Minus | Not | IntLit(_) | FloatLit(_) | BoolLit(_) | LPar | Underscore | LBra | If
-            | Unsafe | Ident(_) | Loop | While => self.parse_operator_expr(),
+            Minus | Not | BitAnd | And | Asterisk | IntLit(..) | FloatLit(..) | BoolLit(_)
+            | StrLit(_) | LPar | Underscore | LBra | If | Unsafe | Ident(_) | Loop | While | Do
+            | For => self.parse_operator_expr(),
             Return => {
                 let return_expr = self.parse_return()?;
                 Ok(Box::new(return_expr))
@@ -361,19 +770,17 @@ impl Parser {
     fn parse_expr_wo_block_(&mut self) -> Result<Box<dyn ExprASTNode>> {
         let next = self.peek()?;
         match next.ty() {
-            IntLit(_) | FloatLit(_) | BoolLit(_) => self.parse_literal_expr(),
+            IntLit(..) | FloatLit(..) | BoolLit(_) | StrLit(_) => self.parse_literal_expr(),
             Ident(_) => self.parse_path_or_call_expr(),
-            LPar => {
-                let expr = self.parse_grouped_expr_or_unit_lit()?;
-                Ok(match expr {
-                    Either::Left(expr) => Box::new(expr),
-                    Either::Right(lit) => Box::new(lit),
-                })
-            }
+            LPar => self.parse_paren_expr(),
             Underscore => {
                 let expr = self.parse_underscore_expr()?;
                 Ok(Box::new(expr))
             }
+            Do => {
+                let expr = self.parse_post_loop_expr()?;
+                Ok(Box::new(expr))
+            }
             _ => unknown_token!(self, "<expr>"),
         }
     }
@@ -382,7 +789,7 @@ impl Parser {
         let next = self.peek()?;
         Ok(match next.ty() {
             LBra => Box::new(self.parse_block_expr()?),
-            Loop | While => self.parse_loop_expr()?,
+            Loop | While | For => self.parse_loop_expr()?,
             If => Box::new(self.parse_if_expr()?),
             Unsafe => Box::new(self.parse_unsafe_expr()?),
             _ => return unknown_token!(self, "<expr>"),
@@ -390,6 +797,7 @@ impl Parser {
     }
 
     fn parse_literal_expr(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        trace::span!(TRACE, "parse_literal_expr");
         /// A macro to create a boxed literal node.
         macro_rules! box_literal {
             ($ty:ty, $val:expr, $span:expr) => {{
@@ -399,19 +807,44 @@ impl Parser {
         }
 
         let token = self.consume()?;
+        if matches!(
+            token.ty(),
+            IntLit(..) | FloatLit(..) | BoolLit(_) | StrLit(_)
+        ) {
+            self.add_node()?;
+        }
         match token.ty() {
             //TODO Add support for different sizes of ints and floats
-            IntLit(val) => box_literal!(i32, *val, token.span()),
-            FloatLit(val) => box_literal!(f64, *val, token.span()),
+            IntLit(val, None) => box_literal!(i128, *val, token.span()),
+            IntLit(val, Some(suffix)) => {
+                let literal = LiteralASTNode::<i128>::new(*val, token.span());
+                Ok(Box::new(literal.with_suffix(Type::from(*suffix))))
+            }
+            FloatLit(val, text, suffix) => {
+                let literal = LiteralASTNode::<f64>::new(*val, token.span());
+                let literal = literal.with_text(text.clone());
+                match suffix {
+                    Some(suffix) => Ok(Box::new(literal.with_suffix(Type::from(*suffix)))),
+                    None => Ok(Box::new(literal)),
+                }
+            }
             BoolLit(val) => box_literal!(bool, *val, token.span()),
+            StrLit(val) => box_literal!(Rc<str>, val.clone(), token.span()),
             _ => unknown_token!(self, token, "<literal>"),
         }
     }
 
     // Ident' rule
     fn parse_path_or_call_expr(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        trace::span!(TRACE, "parse_path_or_call_expr");
         let path = Box::new(self.parse_path_expr()?);
         let next = self.peek()?;
+        if let PathSep = next.ty() {
+            return self.parse_qualified_path_expr(*path);
+        }
+        // Either the call or the path itself
+        self.add_node()?;
+        let next = self.peek()?;
         Ok(match next.ty() {
             LPar => {
                 // CallExpression' rule
@@ -437,7 +870,51 @@ impl Parser {
         }
     }
 
+    fn parse_qualified_path_expr(&mut self, prefix: PathASTNode) -> Result<Box<dyn ExprASTNode>> {
+        assert_token!(self, PathSep, "'::'");
+        if let Lt = self.peek()?.ty() {
+            return self.parse_layout_of_expr(prefix);
+        }
+        let name = self.parse_path_expr()?;
+        let path = format!("{}::{}", prefix.path(), name.path());
+        let span = Span::new(prefix.span().start(), name.span().end());
+
+        //TODO Add support for paths to items other than the builtin constants
+        if let Some(constant) = LiteralASTNode::<f64>::builtin_constant(&path, span) {
+            return Ok(Box::new(constant));
+        }
+        let error = SemanticError::UnknownAssociatedConstant {
+            path: path.clone().into_boxed_str(),
+            span,
+        };
+        self.push_rcv_error(error.into())?;
+        Ok(Box::new(PathASTNode::new(path.into(), span)))
+    }
+
+    fn parse_layout_of_expr(&mut self, function: PathASTNode) -> Result<Box<dyn ExprASTNode>> {
+        trace::span!(TRACE, "parse_layout_of_expr");
+        // The generic arguments of a function call (the "turbofish")
+        assert_token!(self, Lt, "'<'");
+        let ty = self.parse_type()?;
+        assert_token!(self, Gt, "'>'");
+        assert_token!(self, LPar, "'('");
+        let end_pos = assert_token!(self, RPar, "')'").end();
+        let span = Span::new(function.span().start(), end_pos);
+
+        //TODO Add support for generic functions other than the layout queries
+        if let Some(query) = LayoutQuery::from_name(function.path()) {
+            return Ok(Box::new(LayoutOfASTNode::new(query, ty, span)));
+        }
+        let error = SemanticError::UnknownGenericFunction {
+            name: function.path().into(),
+            span: function.span(),
+        };
+        self.push_rcv_error(error.into())?;
+        Ok(Box::new(PathASTNode::new(function.path().into(), span)))
+    }
+
     fn parse_block_expr(&mut self) -> Result<BlockASTNode> {
+        trace::span!(TRACE, "parse_block_expr");
         let start_pos = assert_token!(self, LBra, "'{'").start();
 
         let (stmts, return_expr) = self.parse_stmts()?;
@@ -457,7 +934,8 @@ impl Parser {
             let next = self.peek()?;
             match next.ty() {
                 Let => {
-                    let stmt = self.parse_let_stmt()?;
+                    let stmt = self.with_context(ExprContext::Stmt, Parser::parse_let_stmt)?;
+                    self.add_node()?;
                     statements.push(Box::new(stmt));
                 }
                 Semi => {
@@ -466,55 +944,97 @@ impl Parser {
                 }
                 RBra => return Ok((statements, None)),
                 _ => {
-                    let (expr_stmt, is_last) = self.parse_expr_stmt()?;
+                    let (expr_stmt, is_last) =
+                        self.with_context(ExprContext::Stmt, Parser::parse_expr_stmt)?;
                     if is_last {
-                        let return_expr = Some(expr_stmt.into_expr());
-                        return Ok((statements, return_expr));
-                    } else {
-                        statements.push(Box::new(expr_stmt));
+                        // The expression is not the last one if another statement follows it
+                        // (a block is not an operand, so it ends the expression as well)
+                        let next = self.peek()?;
+                        if !matches!(next.ty(), LBra) && !ExprContext::Stmt.ends_expr(next.ty()) {
+                            let return_expr = Some(expr_stmt.into_expr());
+                            return Ok((statements, return_expr));
+                        }
+                        let next = next.clone();
+                        let end_pos = expr_stmt.span().end();
+                        let error = RecoverableParserError::ExprWithoutSemicolon(end_pos, next);
+                        self.push_rcv_error(error)?;
                     }
+                    self.add_node()?;
+                    statements.push(Box::new(expr_stmt));
                 }
             }
         }
     }
 
+    /// Parses a production in the given expression context, restoring the previous one afterwards.
+    fn with_context<T>(
+        &mut self,
+        context: ExprContext,
+        parse: impl FnOnce(&mut Parser) -> Result<T>,
+    ) -> Result<T> {
+        let outer = std::mem::replace(&mut self.context, context);
+        let result = parse(self);
+        self.context = outer;
+        result
+    }
+
     fn parse_operator_expr(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        trace::span!(TRACE, "parse_operator_expr");
         ops::parse_ops(self)
     }
 
-    fn parse_grouped_expr_or_unit_lit(
-        &mut self,
-    ) -> Result<Either<GroupedExprASTNode, LiteralASTNode<()>>> {
+    /// Parses an expression in parentheses, i.e. the unit literal `()`, a grouped expression `(e)`
+    /// or a tuple expression (`(e,)`, `(e1, e2)`, etc.).
+    fn parse_paren_expr(&mut self) -> Result<Box<dyn ExprASTNode>> {
         let start_pos = assert_token!(self, LPar, "'('").start();
 
         // GroupedOrUnit rule
+        if let Some(end) = expect_token!(self, RPar) {
+            let span = Span::new(start_pos, end.end());
+            return Ok(Box::new(LiteralASTNode::<()>::new(span)));
+        }
+        let expr = self.with_context(ExprContext::Nested, Parser::parse_expr)?;
+
+        // GroupedOrTuple rule
         let next = self.peek()?;
-        Ok(match next.ty() {
+        match next.ty() {
             RPar => {
                 let end_pos = assert_token!(self, RPar, "')'").end();
                 let span = Span::new(start_pos, end_pos);
-                Either::Right(LiteralASTNode::<()>::new(span))
+                return Ok(Box::new(GroupedExprASTNode::new(expr, span)));
             }
-            _ => {
-                let expr = self.parse_expr()?;
-                let end_pos = assert_token!(self, RPar, "')'").end();
-                let span = Span::new(start_pos, end_pos);
+            Comma => {
+                assert_token!(self, Comma, "','");
+            }
+            _ => return unknown_token!(self, "',', ')'"),
+        }
 
-                let expr = GroupedExprASTNode::new(expr, span);
-                Either::Left(expr)
+        // TupleElements rule (with an optional trailing comma)
+        let mut elements = vec![expr];
+        let end_pos = loop {
+            if let Some(end) = expect_token!(self, RPar) {
+                break end.end();
             }
-        })
+            elements.push(self.with_context(ExprContext::Nested, Parser::parse_expr)?);
+            if expect_token!(self, Comma).is_none() {
+                break assert_token!(self, RPar, "',', ')'").end();
+            }
+        };
+        let span = Span::new(start_pos, end_pos);
+        Ok(Box::new(TupleASTNode::new(elements, span)))
     }
 
     fn parse_call_params(&mut self) -> Result<Vec<Box<dyn ExprASTNode>>> {
+        trace::span!(TRACE, "parse_call_params");
         let mut result = Vec::new();
         loop {
             // CallParams rule
             let next = self.peek()?;
             match next.ty() {
-                Return | Minus | Not | IntLit(_) | FloatLit(_) | BoolLit(_) | LPar | Underscore
-                | LBra | If | Unsafe | Ident(_) | Loop | While => {
-                    let expr = self.parse_expr()?;
+                Return | Minus | Not | BitAnd | And | Asterisk | IntLit(..) | FloatLit(..)
+                | BoolLit(_) | StrLit(_) | LPar | Underscore | LBra | If | Unsafe | Ident(_)
+                | Loop | While | Do | For => {
+                    let expr = self.with_context(ExprContext::CallParam, Parser::parse_expr)?;
                     result.push(expr);
                 }
                 RPar => return Ok(result),
@@ -524,18 +1044,29 @@ impl Parser {
             // CallParams' rule
             let next = self.peek()?;
             match next.ty() {
-                Comma => assert_token!(self, Comma, "','"),
+                Comma => {
+                    assert_token!(self, Comma, "','");
+                }
                 RPar => return Ok(result),
+                // A missing comma between two arguments
+                ty if ExprContext::CallParam.ends_expr(ty) => {
+                    let end_pos = result
+                        .last()
+                        .map_or(next.span().start(), |e| e.span().end());
+                    self.push_rcv_error(RecoverableParserError::MissingToken(Comma, end_pos))?;
+                }
                 _ => return unknown_token!(self, "',', ')'"),
             };
         }
     }
 
     fn parse_loop_expr(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        trace::span!(TRACE, "parse_loop_expr");
         let next = self.peek()?;
         Ok(match next.ty() {
             Loop => Box::new(self.parse_inf_loop_expr()?),
             While => Box::new(self.parse_pred_loop_expr()?),
+            For => Box::new(self.parse_iter_loop_expr()?),
             _ => return unknown_token!(self, "<loop expr>"),
         })
     }
@@ -553,7 +1084,7 @@ impl Parser {
     fn parse_pred_loop_expr(&mut self) -> Result<WhileASTNode> {
         let start_pos = assert_token!(self, While, "'while'").start();
 
-        let condition = self.parse_expr()?;
+        let condition = self.parse_condition()?;
         let body = self.parse_block_expr()?;
 
         let end_pos = body.span().end();
@@ -563,39 +1094,106 @@ impl Parser {
         Ok(while_expr)
     }
 
+    fn parse_iter_loop_expr(&mut self) -> Result<ForASTNode> {
+        trace::span!(TRACE, "parse_iter_loop_expr");
+        let start_pos = assert_token!(self, For, "'for'").start();
+
+        let token = self.consume()?;
+        let ident_span = token.span();
+        let ident = assert_ident_or_underscore!(self, token);
+        let pattern: Box<dyn ExprASTNode> = match ident {
+            None => Box::new(UnderscoreASTNode::new(ident_span)),
+            Some(ident) => Box::new(PathASTNode::new(ident, ident_span)),
+        };
+        assert_token!(self, In, "'in'");
+
+        let start = self.parse_expr()?;
+        let token = self.consume()?;
+        let inclusive = match token.ty() {
+            DotDot => false,
+            DotDotEq => true,
+            _ => return unknown_token!(self, token, "'..', '..='"),
+        };
+        let end = self.parse_expr()?;
+        let body = self.parse_block_expr()?;
+
+        let end_pos = body.span().end();
+        let span = Span::new(start_pos, end_pos);
+
+        let for_expr = ForASTNode::new(pattern, start, end, inclusive, Box::new(body), span);
+        Ok(for_expr)
+    }
+
+    fn parse_post_loop_expr(&mut self) -> Result<DoWhileASTNode> {
+        trace::span!(TRACE, "parse_post_loop_expr");
+        let start_pos = assert_token!(self, Do, "'do'").start();
+
+        let body = self.parse_block_expr()?;
+        assert_token!(self, While, "'while'");
+        let condition = self.parse_condition()?;
+
+        let end_pos = condition.span().end();
+        let span = Span::new(start_pos, end_pos);
+
+        let do_while_expr = DoWhileASTNode::new(Box::new(body), condition, span);
+        Ok(do_while_expr)
+    }
+
+    /// Parses the condition of an `if` or `while`, reporting an assignment
+    /// (most likely a mistyped `==`) as a recoverable error.
+    fn parse_condition(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let condition = self.parse_expr()?;
+        if let Some(assign) = condition.as_any().downcast_ref::<AssignASTNode>() {
+            let mut printer = SourcePrinter::new();
+            printer.print_bin_op(
+                assign.assignee(),
+                "==",
+                assign.value(),
+                Precedence::Comparison,
+            );
+            let error = SemanticError::AssignmentInCondition {
+                suggestion: printer.finish().into_boxed_str(),
+                span: condition.span(),
+            };
+            self.push_rcv_error(error.into())?;
+        }
+        Ok(condition)
+    }
+
     fn parse_if_expr(&mut self) -> Result<IfASTNode> {
+        trace::span!(TRACE, "parse_if_expr");
         let start_pos = assert_token!(self, If, "'if'").start();
 
-        let condition = self.parse_expr()?;
+        let condition = self.parse_condition()?;
         let then_block = self.parse_block_expr()?;
-        let else_expr = self.parse_else_expr()?;
+        let else_node = self.parse_else_expr()?;
 
-        let end_pos = match &else_expr {
-            ElseExpr::None => then_block.span().end(),
-            ElseExpr::Else(block) => block.span().end(),
-            ElseExpr::ElseIf(if_node) => if_node.span().end(),
+        let end_pos = match &else_node {
+            None => then_block.span().end(),
+            Some(else_node) => else_node.span().end(),
         };
         let span = Span::new(start_pos, end_pos);
 
         Ok(IfASTNode::new(
             condition,
             Box::new(then_block),
-            else_expr,
+            else_node,
             span,
         ))
     }
 
     // IfExpressionTail' & ElseExpression' rules
-    fn parse_else_expr(&mut self) -> Result<ElseExpr> {
+    fn parse_else_expr(&mut self) -> Result<Option<ElseASTNode>> {
         // IfExpressionTail' rule
+        let context = self.context;
         let next = self.peek()?;
         Ok(match next.ty() {
             Else => {
-                assert_token!(self, Else, "'else'");
+                let start_pos = assert_token!(self, Else, "'else'").start();
 
                 // ElseExpression' rule
                 let next = self.peek()?;
-                match next.ty() {
+                let branch = match next.ty() {
                     If => {
                         let if_node = self.parse_if_expr()?;
                         ElseExpr::ElseIf(Box::new(if_node))
@@ -605,22 +1203,28 @@ impl Parser {
                         ElseExpr::Else(Box::new(block))
                     }
                     _ => return unknown_token!(self, "'if', '{'"),
-                }
+                };
+                let end_pos = match &branch {
+                    ElseExpr::Else(block) => block.span().end(),
+                    ElseExpr::ElseIf(if_node) => if_node.span().end(),
+                };
+                Some(ElseASTNode::new(branch, Span::new(start_pos, end_pos)))
             }
             RPar | Comma | LBra | As | Asterisk | Div | Mod | Plus | Minus | BitAnd | BitXor
-            | BitOr | Eq | Ne | Lt | Gt | Le | Ge | And | Or | Assign | RBra | Semi => {
-                ElseExpr::None
-            }
+            | BitOr | Eq | Ne | Lt | Gt | Le | Ge | And | Or | Assign | RBra | Semi | DotDot
+            | DotDotEq | Dot => None,
+            ty if context.ends_expr(ty) => None,
             _ => {
                 return unknown_token!(
                     self,
-                    "'else', ')', ',', '{', 'as', <operator>, '=', '}', ';'"
+                    "'else', ')', ',', '{', 'as', <operator>, '=', '}', ';', '.'"
                 )
             }
         })
     }
 
     fn parse_unsafe_expr(&mut self) -> Result<UnsafeBlockASTNode> {
+        trace::span!(TRACE, "parse_unsafe_expr");
         let start_pos = assert_token!(self, Unsafe, "'unsafe'").start();
         let block = self.parse_block_expr()?;
         let end_pos = block.span().end();
@@ -631,29 +1235,58 @@ impl Parser {
     }
 
     fn parse_return(&mut self) -> Result<ReturnASTNode> {
+        trace::span!(TRACE, "parse_return");
         let span = assert_token!(self, Return, "'return'");
 
         // ReturnExpressionTail' rule
+        let context = self.context;
         let next = self.peek()?;
         Ok(match next.ty() {
-            Return | Minus | Not | IntLit(_) | FloatLit(_) | BoolLit(_) | LPar | Underscore
-            | LBra | If | Unsafe | Ident(_) | Loop | While => {
+            Return | Minus | Not | BitAnd | And | Asterisk | IntLit(..) | FloatLit(..)
+            | BoolLit(_) | StrLit(_) | LPar | Underscore | LBra | If | Unsafe | Ident(_) | Loop
+            | While | Do | For => {
                 let expr = self.parse_expr()?;
                 let span = Span::new(span.start(), expr.span().end());
 
                 ReturnASTNode::new(expr, span)
             }
             RPar | Comma | RBra | Semi => ReturnASTNode::empty(span),
+            ty if context.ends_expr(ty) => ReturnASTNode::empty(span),
             _ => return unknown_token!(self, "<expr>, ';'"),
         })
     }
 
+    /// Parses the rest of a reference type starting with `&` at the given position,
+    /// i.e. the string slice type `&str` or a reference type `&T` or `&mut T`.
+    fn parse_reference_type(&mut self, start_pos: Position) -> Result<TypeASTMetaNode> {
+        let mut_span = self.parse_mut()?;
+        let next = self.peek()?;
+        if let Ident(ident) = next.ty() {
+            if ident.as_ref() == "str" {
+                if mut_span.is_some() {
+                    return unknown_token!(self, "<type>");
+                }
+                let end_pos = self.consume()?.span().end();
+                return Ok(TypeASTMetaNode::new(
+                    Type::Str,
+                    Span::new(start_pos, end_pos),
+                ));
+            }
+        }
+
+        let target = self.parse_type()?;
+        let span = Span::new(start_pos, target.span().end());
+        let ty = Type::reference(target.ty(), mut_span.is_some());
+        Ok(TypeASTMetaNode::new(ty, span))
+    }
+
     fn parse_underscore_expr(&mut self) -> Result<UnderscoreASTNode> {
         let span = assert_token!(self, Underscore, "'_'");
         Ok(UnderscoreASTNode::new(span))
     }
 
     fn parse_type(&mut self) -> Result<TypeASTMetaNode> {
+        trace::span!(TRACE, "parse_type");
         let token = self.consume()?;
         match token.ty() {
             Ident(ident) => match ident.parse::<Type>() {
@@ -661,15 +1294,89 @@ impl Parser {
                 Err(_) => unknown_token!(self, token, "<type>"),
             },
             LPar => {
-                //TODO Add support for tuples
-                let end_pos = assert_token!(self, RPar, "')'").end();
+                // `()` is the unit type, `(T)` is a parenthesized type
+                // and `(T,)` or `(T, U)` is a tuple type
+                let mut elements = Vec::new();
+                let mut trailing_comma = false;
+                let end_pos = loop {
+                    if let Some(span) = expect_token!(self, RPar) {
+                        break span.end();
+                    }
+                    elements.push(self.parse_type()?.ty());
+                    trailing_comma = expect_token!(self, Comma).is_some();
+                    if !trailing_comma {
+                        break assert_token!(self, RPar, "',', ')'").end();
+                    }
+                };
                 let span = Span::new(token.span().start(), end_pos);
-                Ok(TypeASTMetaNode::new(Type::Unit, span))
+                let ty = match elements.as_slice() {
+                    [ty] if !trailing_comma => ty.clone(),
+                    _ => Type::tuple(elements),
+                };
+                Ok(TypeASTMetaNode::new(ty, span))
+            }
+            BitAnd => self.parse_reference_type(token.span().start()),
+            // `&&T` is lexed as a single token, but it is a reference to a reference
+            And => {
+                let start_pos = token.span().start();
+                let inner_pos = Position::new_at(start_pos.line(), start_pos.column() + 1);
+                let target = self.parse_reference_type(inner_pos)?;
+                let span = Span::new(start_pos, target.span().end());
+                Ok(TypeASTMetaNode::new(
+                    Type::reference(target.ty(), false),
+                    span,
+                ))
             }
             _ => {
-                //TODO Add support for other symbol-based types (e.g. references, slices, etc.)
+                //TODO Add support for other symbol-based types (e.g. slices, arrays, etc.)
                 unknown_token!(self, token, "<type>")
             }
         }
     }
 }
+
+/// The context of an expression, which determines the tokens that end it
+/// when the separator after it is missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ExprContext {
+    /// An expression nested in another one, e.g. in parentheses, which is never ended early.
+    Nested,
+    /// An argument of a call, ended by a token that starts the next argument.
+    CallParam,
+    /// An expression statement or the initializer of a `let` statement,
+    /// ended by a token that starts the next statement.
+    Stmt,
+}
+
+impl ExprContext {
+    /// Returns whether the given token ends an expression in this context
+    /// after a missing separator.
+    ///
+    /// `-`, `*`, `&` and `&&` never end an expression, as they continue it
+    /// as a binary operation.
+    pub(super) fn ends_expr(self, ty: &TokenType) -> bool {
+        match self {
+            ExprContext::Nested => false,
+            ExprContext::CallParam => matches!(
+                ty,
+                Return
+                    | Not
+                    | IntLit(..)
+                    | FloatLit(..)
+                    | BoolLit(_)
+                    | StrLit(_)
+                    | LPar
+                    | Underscore
+                    | If
+                    | Unsafe
+                    | Ident(_)
+                    | Loop
+                    | While
+                    | Do
+                    | For
+            ),
+            // Any token starting an argument can start a statement as well
+            ExprContext::Stmt => matches!(ty, Let) || ExprContext::CallParam.ends_expr(ty),
+        }
+    }
+}
@@ -5,6 +5,10 @@
 /// A macro that consumes the next token and returns
 /// [`ParserError::UnexpectedToken`](crate::parser::ParserError::UnexpectedToken)
 /// with the consumed token.
+///
+/// If the token is a [reserved keyword](crate::token::TokenType::reserved_keyword),
+/// [`ParserError::ReservedKeyword`](crate::parser::ParserError::ReservedKeyword)
+/// is returned instead.
 macro_rules! unknown_token {
     ($self:expr, $expected:expr) => {{
         let token = $self
@@ -12,12 +16,19 @@ macro_rules! unknown_token {
             .expect("Unsuccessful consuming after successful peeking should be impossible.");
         unknown_token!($self, token, $expected)
     }};
-    ($self:expr, $token:expr, $expected:expr) => {
-        Err(ParserError::UnexpectedToken {
-            actual: $token,
-            expected: $expected,
-        })
-    };
+    ($self:expr, $token:expr, $expected:expr) => {{
+        let token = $token;
+        match token.ty().reserved_keyword() {
+            Some(keyword) => Err(ParserError::ReservedKeyword {
+                keyword,
+                span: token.span(),
+            }),
+            None => Err(ParserError::UnexpectedToken {
+                actual: token,
+                expected: $expected,
+            }),
+        }
+    }};
 }
 
 /// Consumes the next token or uses the provided one, checks if it matches
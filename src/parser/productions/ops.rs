@@ -72,6 +72,7 @@ macro_rules! parse_op {
             }
 
             fn parse_tail(parser: &mut Parser, lhs: Box<dyn ExprASTNode>) -> Result<Box<dyn ExprASTNode>> {
+                let context = parser.context;
                 let next = parser.peek()?;
                 match next.ty() {
                     $(
@@ -81,10 +82,13 @@ macro_rules! parse_op {
                             let span = Span::new(lhs.span().start(), rhs.span().end());
 
                             let lhs = Box::new($ctr(lhs, rhs, span));
+                            parser.add_node()?;
                             parse_tail(parser, lhs)
                         },
                     )+
                     $empty => Ok(lhs),
+                    // The next argument or statement after a missing separator
+                    ty if context.ends_expr(ty) => Ok(lhs),
                     _ => unknown_token!(parser, $expected_display),
                 }
             }
@@ -125,7 +129,7 @@ parse_op!(
     op1,
     op2,
     [(Assign, "'='", ctr::assign)],
-    LBra | RPar | Comma | RBra | Semi,
+    LBra | DotDot | DotDotEq | RPar | Comma | RBra | Semi,
     "'=', '{', '(', ',', '}', ';'",
 );
 
@@ -134,7 +138,7 @@ parse_op!(
     op2,
     op3,
     [(Or, "'||'", ctr::lazy_or)],
-    LBra | RPar | Comma | RBra | Semi | Assign,
+    LBra | DotDot | DotDotEq | RPar | Comma | RBra | Semi | Assign,
     "'||', '=', '{', '(', ',', '}', ';'",
 );
 
@@ -143,7 +147,7 @@ parse_op!(
     op3,
     op4,
     [(And, "'&&'", ctr::lazy_and)],
-    LBra | RPar | Comma | RBra | Semi | Assign | Or,
+    LBra | DotDot | DotDotEq | RPar | Comma | RBra | Semi | Assign | Or,
     "<operator>, '=', '{', '(', ',', '}', ';'",
 );
 
@@ -159,7 +163,7 @@ parse_op!(
         (Ge, "'>='", ctr::ge),
         (Le, "'<='", ctr::le)
     ],
-    LBra | RPar | Comma | RBra | Semi | Assign | Or | And,
+    LBra | DotDot | DotDotEq | RPar | Comma | RBra | Semi | Assign | Or | And,
     "<operator>, '=', '{', '(', ',', '}', ';'",
 );
 
@@ -168,7 +172,21 @@ parse_op!(
     op5,
     op6,
     [(BitOr, "'|'", ctr::bit_or)],
-    LBra | RPar | Comma | RBra | Semi | Assign | Or | And | Eq | Ne | Gt | Lt | Ge | Le,
+    LBra | DotDot
+        | DotDotEq
+        | RPar
+        | Comma
+        | RBra
+        | Semi
+        | Assign
+        | Or
+        | And
+        | Eq
+        | Ne
+        | Gt
+        | Lt
+        | Ge
+        | Le,
     "<operator>, '=', '{', '(', ',', '}', ';'",
 );
 
@@ -177,7 +195,22 @@ parse_op!(
     op6,
     op7,
     [(BitXor, "'^'", ctr::bit_xor)],
-    LBra | RPar | Comma | RBra | Semi | Assign | Or | And | Eq | Ne | Gt | Lt | Ge | Le | BitOr,
+    LBra | DotDot
+        | DotDotEq
+        | RPar
+        | Comma
+        | RBra
+        | Semi
+        | Assign
+        | Or
+        | And
+        | Eq
+        | Ne
+        | Gt
+        | Lt
+        | Ge
+        | Le
+        | BitOr,
     "<operator>, '=', '{', '(', ',', '}', ';'",
 );
 
@@ -186,7 +219,9 @@ parse_op!(
     op7,
     op8,
     [(BitAnd, "'&'", ctr::bit_and)],
-    LBra | RPar
+    LBra | DotDot
+        | DotDotEq
+        | RPar
         | Comma
         | RBra
         | Semi
@@ -209,7 +244,9 @@ parse_op!(
     op8,
     op9,
     [(Plus, "'+'", ctr::add), (Minus, "'-'", ctr::sub)],
-    LBra | RPar
+    LBra | DotDot
+        | DotDotEq
+        | RPar
         | Comma
         | RBra
         | Semi
@@ -237,7 +274,9 @@ parse_op!(
         (Div, "'/'", ctr::div),
         (Mod, "'%'", ctr::rem)
     ],
-    LBra | RPar
+    LBra | DotDot
+        | DotDotEq
+        | RPar
         | Comma
         | RBra
         | Semi
@@ -272,6 +311,7 @@ mod op10 {
 
     // `Expr10'`
     fn parse_tail(parser: &mut Parser, lhs: Box<dyn ExprASTNode>) -> Result<Box<dyn ExprASTNode>> {
+        let context = parser.context;
         let next = parser.peek()?;
         match next.ty() {
             As => {
@@ -282,41 +322,119 @@ mod op10 {
                 let lhs = Box::new(TypeCastASTNode::new(lhs, ty, span));
                 parse_tail(parser, lhs)
             }
-            LBra | RPar | Comma | RBra | Semi | Assign | Or | And | Eq | Ne | Gt | Lt | Ge | Le
-            | BitOr | BitXor | BitAnd | Plus | Minus | Asterisk | Div | Mod => Ok(lhs),
+            LBra | DotDot | DotDotEq | RPar | Comma | RBra | Semi | Assign | Or | And | Eq | Ne
+            | Gt | Lt | Ge | Le | BitOr | BitXor | BitAnd | Plus | Minus | Asterisk | Div | Mod => {
+                Ok(lhs)
+            }
+            ty if context.ends_expr(ty) => Ok(lhs),
             _ => unknown_token!(parser, "<operator>, '=', '{', '(', ',', '}', ';', 'as'"),
         }
     }
 }
 
-// `Expr11` and `NegationExpression`
+// `Expr11`, `NegationExpression`, `BorrowExpression`, `DereferenceExpression`
+// and `TupleIndexExpression`
 mod op11 {
-    use crate::ast::{NegExprASTNode, NegOperator};
+    use crate::ast::{
+        BorrowExprASTNode, DerefExprASTNode, NegExprASTNode, NegOperator, TupleIndexASTNode,
+    };
 
     use super::*;
 
     // `Expr11`
     pub fn parse(parser: &mut Parser) -> Result<Box<dyn ExprASTNode>> {
         let next = parser.peek()?;
-        match next.ty() {
+        let expr = match next.ty() {
             Minus => {
                 let start_pos = assert_token!(parser, Minus, "'-'").start();
-                parse_negation(parser, NegOperator::Neg, start_pos)
+                return parse_negation(parser, NegOperator::Neg, start_pos);
             }
             Not => {
                 let start_pos = assert_token!(parser, Not, "'!'").start();
-                parse_negation(parser, NegOperator::Not, start_pos)
+                return parse_negation(parser, NegOperator::Not, start_pos);
+            }
+            BitAnd => {
+                let start_pos = assert_token!(parser, BitAnd, "'&'").start();
+                return parse_borrow(parser, start_pos);
             }
-            IntLit(_) | FloatLit(_) | BoolLit(_) | RPar | Underscore | Ident(_) => {
-                Parser::parse_expr_wo_block_(parser)
+            // `&&expr` is lexed as a single token, but it is a borrow of a borrow
+            And => {
+                let start_pos = assert_token!(parser, And, "'&&'").start();
+                let inner_pos = Position::new_at(start_pos.line(), start_pos.column() + 1);
+                let expr = parse_borrow(parser, inner_pos)?;
+                let span = Span::new(start_pos, expr.span().end());
+                return Ok(Box::new(BorrowExprASTNode::new(false, expr, span)));
             }
-            LPar => Ok(match Parser::parse_grouped_expr_or_unit_lit(parser)? {
-                Either::Left(group) => Box::new(group),
-                Either::Right(lit) => Box::new(lit),
-            }),
-            LBra | If | Unsafe | Loop | While => Parser::parse_expr_w_block(parser),
-            _ => unknown_token!(parser, "'!', '-', <expr>"),
+            Asterisk => {
+                let start_pos = assert_token!(parser, Asterisk, "'*'").start();
+                let expr = parse(parser)?;
+                let span = Span::new(start_pos, expr.span().end());
+                return Ok(Box::new(DerefExprASTNode::new(expr, span)));
+            }
+            IntLit(..) | FloatLit(..) | BoolLit(_) | StrLit(_) | RPar | Underscore | Ident(_)
+            | Do => Parser::parse_expr_wo_block_(parser)?,
+            LPar => Parser::parse_paren_expr(parser)?,
+            LBra | If | Unsafe | Loop | While | For => Parser::parse_expr_w_block(parser)?,
+            _ => return unknown_token!(parser, "'!', '-', '&', '*', <expr>"),
+        };
+        parse_tuple_index(parser, expr)
+    }
+
+    // `TupleIndexExpression`
+    fn parse_tuple_index(
+        parser: &mut Parser,
+        tuple: Box<dyn ExprASTNode>,
+    ) -> Result<Box<dyn ExprASTNode>> {
+        if expect_token!(parser, Dot).is_none() {
+            return Ok(tuple);
         }
+        let token = parser.consume()?;
+        let start_pos = tuple.span().start();
+        let expr = match token.ty() {
+            IntLit(index, None) => {
+                let Ok(index) = u32::try_from(*index) else {
+                    return unknown_token!(parser, token, "<tuple index>");
+                };
+                let span = Span::new(start_pos, token.span().end());
+                TupleIndexASTNode::new(tuple, index, span)
+            }
+            // The indices of a nested tuple (e.g. `t.0.1`) are lexed as a float literal
+            FloatLit(_, text, None) => {
+                let indices = text.split_once('.').and_then(|(outer, inner)| {
+                    let is_index = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+                    if !is_index(outer) || !is_index(inner) {
+                        return None;
+                    }
+                    Some((
+                        outer.parse::<u32>().ok()?,
+                        inner.parse::<u32>().ok()?,
+                        outer.len(),
+                    ))
+                });
+                let Some((outer, inner, outer_len)) = indices else {
+                    return unknown_token!(parser, token, "<tuple index>");
+                };
+                let literal_start = token.span().start();
+                let outer_end =
+                    Position::new_at(literal_start.line(), literal_start.column() + outer_len);
+                let tuple = TupleIndexASTNode::new(tuple, outer, Span::new(start_pos, outer_end));
+                let span = Span::new(start_pos, token.span().end());
+                TupleIndexASTNode::new(Box::new(tuple), inner, span)
+            }
+            _ => return unknown_token!(parser, token, "<tuple index>"),
+        };
+        parse_tuple_index(parser, Box::new(expr))
+    }
+
+    // `BorrowExpression` (after the `&`)
+    fn parse_borrow(parser: &mut Parser, start_pos: Position) -> Result<Box<dyn ExprASTNode>> {
+        let mutable = expect_token!(parser, Mut).is_some();
+        let expr = parse(parser)?;
+        let span = Span::new(start_pos, expr.span().end());
+
+        let expr = BorrowExprASTNode::new(mutable, expr, span);
+        parser.add_node()?;
+        Ok(Box::new(expr))
     }
 
     // `NegationExpression`
@@ -329,6 +447,7 @@ mod op11 {
         let span = Span::new(start_pos, expr.span().end());
 
         let expr = NegExprASTNode::new(op, expr, span);
+        parser.add_node()?;
         Ok(Box::new(expr))
     }
 }
@@ -0,0 +1,225 @@
+//! A module containing the pretty printer that turns the AST back into μRust source code.
+
+use std::fmt;
+use std::fmt::Write;
+
+use crate::ast::{ASTNode, ExprASTNode, Precedence};
+
+/// The number of spaces used for one level of indentation.
+const INDENT_WIDTH: usize = 4;
+
+/// A printer that builds the μRust source code of an AST.
+///
+/// The nodes print themselves using [`ASTNode::print_source`]. Parentheses around
+/// the operands of operators are printed only where the [precedence](Precedence)
+/// of the operand requires them.
+pub struct SourcePrinter {
+    output: String,
+    indent: usize,
+}
+
+impl SourcePrinter {
+    /// Creates a new empty `SourcePrinter`.
+    pub fn new() -> SourcePrinter {
+        SourcePrinter {
+            output: String::new(),
+            indent: 0,
+        }
+    }
+
+    /// Appends the given text to the output.
+    pub fn write(&mut self, text: impl fmt::Display) {
+        // Writing to a `String` never fails
+        write!(self.output, "{text}").unwrap();
+    }
+
+    /// Starts a new line at the current indentation level.
+    pub fn new_line(&mut self) {
+        self.output.push('\n');
+        self.output
+            .push_str(&" ".repeat(self.indent * INDENT_WIDTH));
+    }
+
+    /// Increases the indentation level (starting from the next line).
+    pub fn indent(&mut self) {
+        self.indent += 1;
+    }
+
+    /// Decreases the indentation level (starting from the next line).
+    pub fn dedent(&mut self) {
+        self.indent = self.indent.saturating_sub(1);
+    }
+
+    /// Prints the given node.
+    pub fn print(&mut self, node: &dyn ASTNode) {
+        node.print_source(self);
+    }
+
+    /// Prints the given expression, wrapped in parentheses if `parenthesize` is `true`.
+    pub fn print_operand(&mut self, expr: &dyn ExprASTNode, parenthesize: bool) {
+        if parenthesize {
+            self.write("(");
+            expr.print_source(self);
+            self.write(")");
+        } else {
+            expr.print_source(self);
+        }
+    }
+
+    /// Prints a left-associative binary operator with the given precedence.
+    pub fn print_bin_op(
+        &mut self,
+        lhs: &dyn ExprASTNode,
+        operator: &str,
+        rhs: &dyn ExprASTNode,
+        precedence: Precedence,
+    ) {
+        self.print_operand(lhs, lhs.precedence() < precedence);
+        self.write(format_args!(" {operator} "));
+        self.print_operand(rhs, rhs.precedence() <= precedence);
+    }
+
+    /// Prints a list of nodes separated by commas.
+    pub fn print_list<'a>(&mut self, nodes: impl IntoIterator<Item = &'a dyn ASTNode>) {
+        for (i, node) in nodes.into_iter().enumerate() {
+            if i > 0 {
+                self.write(", ");
+            }
+            node.print_source(self);
+        }
+    }
+
+    /// Returns the printed source code.
+    pub fn finish(self) -> String {
+        self.output
+    }
+}
+
+impl Default for SourcePrinter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{
+        ArithExprASTNode, ArithOperator, CompExprASTNode, CompOperator, LiteralASTNode,
+        NegExprASTNode, NegOperator,
+    };
+    use crate::test_util::parse;
+    use crate::token::{Position, Span};
+
+    use super::*;
+
+    fn span() -> Span {
+        Span::new(Position::new(), Position::new())
+    }
+
+    fn int(value: i128) -> Box<dyn ExprASTNode> {
+        Box::new(LiteralASTNode::<i128>::new(value, span()))
+    }
+
+    fn arith(
+        op: ArithOperator,
+        lhs: Box<dyn ExprASTNode>,
+        rhs: Box<dyn ExprASTNode>,
+    ) -> Box<dyn ExprASTNode> {
+        Box::new(ArithExprASTNode::new(op, lhs, rhs, span()))
+    }
+
+    #[test]
+    fn test_minimal_parentheses() {
+        use ArithOperator::*;
+
+        let cases: Vec<(Box<dyn ExprASTNode>, &str)> = vec![
+            (
+                arith(Sub, arith(Sub, int(10), int(4)), int(3)),
+                "10 - 4 - 3",
+            ),
+            (
+                arith(Sub, int(10), arith(Sub, int(4), int(3))),
+                "10 - (4 - 3)",
+            ),
+            (
+                arith(Mul, arith(Add, int(1), int(2)), int(3)),
+                "(1 + 2) * 3",
+            ),
+            (arith(Add, int(1), arith(Mul, int(2), int(3))), "1 + 2 * 3"),
+            (
+                Box::new(NegExprASTNode::new(
+                    NegOperator::Neg,
+                    arith(Add, int(1), int(2)),
+                    span(),
+                )),
+                "-(1 + 2)",
+            ),
+            (
+                Box::new(CompExprASTNode::new(
+                    CompOperator::Lt,
+                    arith(BitAnd, int(6), int(3)),
+                    int(5),
+                    span(),
+                )),
+                "6 & 3 < 5",
+            ),
+        ];
+
+        for (expr, expected) in cases {
+            let mut printer = SourcePrinter::new();
+            printer.print(expr.as_ast());
+            let printed = printer.finish();
+            assert_eq!(printed, expected);
+
+            // Reparsing the printed expression has to preserve its structure (and thus its value)
+            let crt = parse(&format!("static X: i32 = {printed};"));
+            let stat = match &crt.root().items()[0] {
+                crate::ast::ItemASTNode::Static(stat) => stat,
+                _ => unreachable!(),
+            };
+            assert_eq!(stat.value().unwrap().const_eval(), expr.const_eval());
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source = r#"extern "C" {
+    fn print(n: i32);
+    static mut X: i32;
+}
+
+pub static Y: f64 = 0.5;
+
+pub static mut Z: i32 = 1;
+
+pub fn main() {
+    let mut n: i32 = 0i32;
+    while n < (9 + 1) {
+        n = n + 1;
+        if n % 2 == 0 {
+            unsafe {
+                print(-n as f64 as i32);
+            };
+        } else if !(n > 3 && true) {
+            return;
+        } else {
+            loop {
+                let k: i32 = n;
+                return k;
+            };
+        };
+    };
+    do {
+        n = n - 1;
+    } while n > 5;
+    for i in 0..=n {
+        print(i);
+    };
+}
+"#;
+
+        let printed = parse(source).to_source();
+        assert_eq!(printed, source);
+        assert_eq!(parse(&printed).to_source(), printed);
+    }
+}
@@ -0,0 +1,170 @@
+//! A module containing source-level refactorings built on top of the [name resolution](crate::resolve).
+
+use std::rc::Rc;
+
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+use crate::ast::{ASTNode, Crate};
+use crate::resolve::{DeclKind, Declaration, ResolutionMap, Resolver};
+use crate::token::{Span, TokenType};
+
+use self::error::RenameError;
+
+pub mod error;
+
+/// A replacement of the text at the given span of the source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    span: Span,
+    replacement: Rc<str>,
+}
+
+impl TextEdit {
+    /// Creates a new `TextEdit` replacing the text at `span` with `replacement`.
+    pub fn new(span: Span, replacement: Rc<str>) -> TextEdit {
+        TextEdit { span, replacement }
+    }
+
+    /// Returns the span of the replaced text.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the replacement text.
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+}
+
+/// Renames the symbol declared or referenced at `span` to `new_name`.
+///
+/// Returns the edits for the declaration and all the references of the symbol,
+/// ordered by their position in the source file.
+///
+/// # Errors
+///
+/// - [`RenameError::NoSymbol`] if there is no occurrence of a symbol at `span`.
+/// - [`RenameError::InvalidName`] if `new_name` is not an identifier.
+/// - [`RenameError::Collision`] if the rename would change what any occurrence refers to,
+///   or `new_name` is already used by another item.
+pub fn rename(crt: &Crate, span: Span, new_name: &str) -> Result<Vec<TextEdit>, RenameError> {
+    if !is_ident(new_name) {
+        return Err(RenameError::InvalidName {
+            name: new_name.into(),
+        });
+    }
+
+    let map = crt.resolve_names();
+    let decl = find_symbol(&map, span).ok_or(RenameError::NoSymbol { span })?;
+    let mut occurrences = map.references(decl.span());
+    occurrences.push(decl.span());
+    occurrences.sort_by_key(|s| s.start());
+
+    let collision = |span| RenameError::Collision {
+        name: new_name.into(),
+        span,
+    };
+
+    // Items share a single namespace, so they cannot be shadowed
+    if matches!(decl.kind(), DeclKind::Function | DeclKind::Static) {
+        let item = map.declarations().iter().find(|d| {
+            d.name() == new_name
+                && d.span() != decl.span()
+                && matches!(d.kind(), DeclKind::Function | DeclKind::Static)
+        });
+        if let Some(item) = item {
+            return Err(collision(item.span()));
+        }
+    }
+
+    // Every occurrence has to resolve to the same declaration as before the rename
+    let new_name: Rc<str> = new_name.into();
+    let mut resolver = Resolver::with_rename(occurrences.iter().copied(), new_name.clone());
+    crt.root().resolve_names(&mut resolver);
+    let renamed = resolver.finish();
+    for decl in map.declarations() {
+        let before = map.references(decl.span());
+        let after = renamed.references(decl.span());
+        if let Some(span) = before.iter().find(|s| !after.contains(s)) {
+            return Err(collision(*span));
+        }
+        if let Some(span) = after.iter().find(|s| !before.contains(s)) {
+            return Err(collision(*span));
+        }
+    }
+
+    let edits = occurrences
+        .into_iter()
+        .map(|span| TextEdit::new(span, new_name.clone()))
+        .collect();
+    Ok(edits)
+}
+
+/// Finds the declaration of the symbol declared or referenced at `span`.
+fn find_symbol(map: &ResolutionMap, span: Span) -> Option<&Declaration> {
+    map.declarations()
+        .iter()
+        .find(|d| d.span().contains(span))
+        .or_else(|| {
+            map.declarations()
+                .iter()
+                .find(|d| map.references(d.span()).iter().any(|r| r.contains(span)))
+        })
+}
+
+/// Checks whether `name` is a valid identifier (and not a keyword or `_`).
+fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    let valid = match chars.next() {
+        Some(c) => (is_xid_start(c) || c == '_') && chars.all(is_xid_continue),
+        None => false,
+    };
+    valid && name != "_" && TokenType::extract_keyword_or_symbol(name).is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::parse_file;
+    use crate::token::Position;
+
+    use super::*;
+
+    fn parse(name: &str, source: &str) -> Crate {
+        parse_file(name, source).unwrap()
+    }
+
+    #[test]
+    fn test_rename() {
+        let crt = parse(
+            "mrs_test_rename.mrs",
+            "fn f(a: i32, b: i32) -> i32 {\n    let c: i32 = a;\n    c + b\n}\n",
+        );
+        let span =
+            |line, col| Span::new(Position::new_at(line, col), Position::new_at(line, col + 1));
+
+        let edits = rename(&crt, span(3, 5), "d").unwrap();
+        let spans: Vec<Span> = edits.iter().map(TextEdit::span).collect();
+        assert_eq!(spans, vec![span(2, 9), span(3, 5)]);
+        assert!(edits.iter().all(|e| e.replacement() == "d"));
+
+        // The reference to `b` would be captured by `c`
+        assert_eq!(
+            rename(&crt, span(1, 14), "c"),
+            Err(RenameError::Collision {
+                name: "c".into(),
+                span: span(3, 9)
+            })
+        );
+        assert!(rename(&crt, span(2, 9), "b").is_err());
+        // The initializer is resolved before the declaration, so this is fine
+        assert!(rename(&crt, span(1, 6), "c").is_ok());
+        assert!(matches!(
+            rename(&crt, span(1, 6), "loop"),
+            Err(RenameError::InvalidName { .. })
+        ));
+        assert!(matches!(
+            rename(&crt, span(1, 1), "x"),
+            Err(RenameError::NoSymbol { .. })
+        ));
+    }
+}
@@ -0,0 +1,50 @@
+//! Error types for refactorings.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::token::Span;
+
+/// An error that can occur when renaming a symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// An error when there is no declaration or reference of a symbol at the given span.
+    NoSymbol {
+        /// The span given for the rename.
+        span: Span,
+    },
+    /// An error when the new name is not a valid identifier (e.g. it is a keyword).
+    InvalidName {
+        /// The new name.
+        name: Box<str>,
+    },
+    /// An error when the new name would change what an occurrence of some symbol refers to.
+    Collision {
+        /// The new name.
+        name: Box<str>,
+        /// The span of the occurrence whose meaning would change.
+        span: Span,
+    },
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::NoSymbol { span } => {
+                write!(f, "No symbol to rename at {}", span)
+            }
+            RenameError::InvalidName { name } => {
+                write!(f, "`{}` is not a valid identifier", name)
+            }
+            RenameError::Collision { name, span } => {
+                write!(
+                    f,
+                    "Renaming to `{}` collides with the symbol at {}",
+                    name, span
+                )
+            }
+        }
+    }
+}
+
+impl Error for RenameError {}
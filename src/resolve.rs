@@ -0,0 +1,275 @@
+//! A module containing the name resolution pass and the index of symbol occurrences it produces.
+
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use crate::ast::{ExternItem, ItemASTNode};
+use crate::token::Span;
+
+/// The state of the name resolution process.
+///
+/// The names are resolved by calling [`ASTNode::resolve_names`](crate::ast::ASTNode::resolve_names)
+/// on the root of the AST, which [declares](Resolver::declare) and [references](Resolver::reference)
+/// the names in the scopes opened by the nodes.
+pub struct Resolver {
+    scopes: Vec<HashMap<Rc<str>, Span>>,
+    map: ResolutionMap,
+    /// The occurrences (by their spans) that should be resolved as if they had a different name.
+    renamed: Option<(HashSet<Span>, Rc<str>)>,
+}
+
+impl Resolver {
+    /// Creates a new `Resolver` with a single scope, the global scope.
+    pub fn new() -> Resolver {
+        Resolver {
+            scopes: vec![HashMap::new()],
+            map: ResolutionMap::default(),
+            renamed: None,
+        }
+    }
+
+    /// Creates a new `Resolver` that treats all the occurrences at the given spans
+    /// as if they were named `new_name`.
+    ///
+    /// It is used to check whether renaming a symbol changes the meaning of the program.
+    pub(crate) fn with_rename(
+        occurrences: impl IntoIterator<Item = Span>,
+        new_name: Rc<str>,
+    ) -> Resolver {
+        let mut resolver = Resolver::new();
+        resolver.renamed = Some((occurrences.into_iter().collect(), new_name));
+        resolver
+    }
+
+    /// Returns the name of the occurrence at the given span, taking the rename into account.
+    fn name_at(&self, name: Rc<str>, span: Span) -> Rc<str> {
+        match &self.renamed {
+            Some((spans, new_name)) if spans.contains(&span) => new_name.clone(),
+            _ => name,
+        }
+    }
+
+    /// Opens a new scope and sets it as the current scope.
+    pub fn open_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// Closes the current scope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if you try to pop the global scope.
+    pub fn close_scope(&mut self) {
+        if self.scopes.len() == 1 {
+            panic!("Cannot pop the global scope");
+        }
+
+        self.scopes.pop();
+    }
+
+    /// Declares a symbol with the given name, kind and span (of its identifier) in the current scope.
+    ///
+    /// If a symbol with the same name has already been declared in the current scope,
+    /// it gets shadowed by the new one.
+    pub fn declare(&mut self, name: Rc<str>, kind: DeclKind, span: Span) {
+        let name = self.name_at(name, span);
+        // The stack is guaranteed to have at least one element (see `close_scope`)
+        self.scopes.last_mut().unwrap().insert(name.clone(), span);
+        self.map.declarations.push(Declaration { name, kind, span });
+        self.map.references.entry(span).or_default();
+    }
+
+    /// Declares all the given items in the current scope.
+    ///
+    /// This should be done before resolving the names inside the items,
+    /// so that the items can be referenced before they are defined.
+    pub fn declare_items(&mut self, items: &[ItemASTNode]) {
+        for item in items {
+            match item {
+                ItemASTNode::Func(func) => {
+                    let proto = func.proto();
+                    self.declare(proto.name_owned(), DeclKind::Function, proto.name_span());
+                }
+                ItemASTNode::Static(stat) => {
+                    self.declare(stat.name_owned(), DeclKind::Static, stat.name_span());
+                }
+                ItemASTNode::Extern(ext) => {
+                    for item in ext.items() {
+                        match item {
+                            ExternItem::Func(proto) => {
+                                let (name, span) = (proto.name_owned(), proto.name_span());
+                                self.declare(name, DeclKind::Function, span);
+                            }
+                            ExternItem::Static(stat) => {
+                                let (name, span) = (stat.name_owned(), stat.name_span());
+                                self.declare(name, DeclKind::Static, span);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Records a reference to the symbol with the given name at the given span.
+    ///
+    /// The reference is resolved to the innermost declaration with the same name
+    /// that is visible from the current scope.
+    pub fn reference(&mut self, name: &str, span: Span) {
+        let name = self.name_at(name.into(), span);
+        let decl = self.scopes.iter().rev().find_map(|scope| scope.get(&name));
+        match decl {
+            Some(decl) => self.map.references.entry(*decl).or_default().push(span),
+            None => self.map.unresolved.push((name, span)),
+        }
+    }
+
+    /// Finishes the name resolution and returns the produced [`ResolutionMap`].
+    pub fn finish(self) -> ResolutionMap {
+        self.map
+    }
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An index of all the declarations in a crate and the references to them.
+#[derive(Debug, Clone, Default)]
+pub struct ResolutionMap {
+    declarations: Vec<Declaration>,
+    references: HashMap<Span, Vec<Span>>,
+    unresolved: Vec<(Rc<str>, Span)>,
+}
+
+impl ResolutionMap {
+    /// Returns all the declarations in the order they were encountered.
+    pub fn declarations(&self) -> &[Declaration] {
+        &self.declarations
+    }
+
+    /// Returns the declaration whose identifier is at the given span, if any.
+    pub fn declaration(&self, span: Span) -> Option<&Declaration> {
+        self.declarations.iter().find(|d| d.span == span)
+    }
+
+    /// Returns the spans of all the references to the queried symbol.
+    ///
+    /// The symbol can be queried either by the span of its declaration,
+    /// or by its name -- in which case the references to all the declarations
+    /// with that name are returned.
+    pub fn references<'a>(&self, symbol: impl Into<SymbolQuery<'a>>) -> Vec<Span> {
+        match symbol.into() {
+            SymbolQuery::Decl(span) => self.references.get(&span).cloned().unwrap_or_default(),
+            SymbolQuery::Name(name) => self
+                .declarations
+                .iter()
+                .filter(|d| d.name.as_ref() == name)
+                .flat_map(|d| self.references[&d.span].iter().copied())
+                .collect(),
+        }
+    }
+
+    /// Returns the declaration that the reference at the given span resolves to, if any.
+    pub fn resolve(&self, reference: Span) -> Option<&Declaration> {
+        self.references
+            .iter()
+            .find(|(_, refs)| refs.contains(&reference))
+            .and_then(|(decl, _)| self.declaration(*decl))
+    }
+
+    /// Returns the names (and their spans) that could not be resolved to any declaration.
+    pub fn unresolved(&self) -> &[(Rc<str>, Span)] {
+        &self.unresolved
+    }
+}
+
+/// A way of querying a symbol in the [`ResolutionMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolQuery<'a> {
+    /// Queries the symbol by its name.
+    Name(&'a str),
+    /// Queries the symbol by the span of its declaration.
+    Decl(Span),
+}
+
+impl<'a> From<&'a str> for SymbolQuery<'a> {
+    fn from(name: &'a str) -> Self {
+        SymbolQuery::Name(name)
+    }
+}
+
+impl From<Span> for SymbolQuery<'_> {
+    fn from(span: Span) -> Self {
+        SymbolQuery::Decl(span)
+    }
+}
+
+/// A declaration of a symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Declaration {
+    name: Rc<str>,
+    kind: DeclKind,
+    span: Span,
+}
+
+impl Declaration {
+    /// Returns the name of the declared symbol.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the kind of the declared symbol.
+    pub fn kind(&self) -> DeclKind {
+        self.kind
+    }
+
+    /// Returns the span of the identifier of the declared symbol.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+}
+
+/// The kind of declared symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeclKind {
+    /// A function.
+    Function,
+    /// A static item.
+    Static,
+    /// A function parameter.
+    Param,
+    /// A local variable.
+    Local,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::parse;
+    use crate::token::Position;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve_references() {
+        let crt = parse(
+            "fn main() -> i32 {\n    let x: i32 = id(1);\n    let x: i32 = x;\n    x\n}\nfn id(x: i32) -> i32 { x }\n",
+        );
+        let map = crt.resolve_names();
+
+        let span = |line, start, end| {
+            Span::new(Position::new_at(line, start), Position::new_at(line, end))
+        };
+        assert_eq!(map.references("id"), vec![span(2, 18, 20)]);
+        assert_eq!(map.references(span(2, 9, 10)), vec![span(3, 18, 19)]);
+        assert_eq!(map.references(span(3, 9, 10)), vec![span(4, 5, 6)]);
+        assert_eq!(map.references("x").len(), 3);
+        assert_eq!(
+            map.resolve(span(6, 24, 25)).map(Declaration::kind),
+            Some(DeclKind::Param)
+        );
+        assert!(map.unresolved().is_empty());
+    }
+}
@@ -0,0 +1,544 @@
+//! A module containing the sandbox that compiles and runs a μRust program, e.g. in a playground.
+//!
+//! The program is compiled within the given [limits](SandboxLimits), and its `main` function
+//! (either `fn main()` or `fn main() -> i32`) is run with the JIT. The program can print values
+//! by calling the [runtime functions](RUNTIME_FUNCTIONS), which it has to declare in an extern block:
+//! ```text
+//! extern "C" {
+//!     fn print_i32(x: i32);
+//!     fn print_f64(x: f64);
+//!     fn print_bool(x: bool);
+//! }
+//! ```
+//! Every call prints the value followed by a new line into the captured output.
+//! No other extern items are allowed, so the program cannot call into the host process.
+//!
+//! # Caveats
+//!
+//! The JIT-compiled code runs in the host process and cannot be interrupted. If the program
+//! doesn't finish before the timeout, [`RunStatus::TimedOut`] is returned, but the thread running
+//! it is left detached. Similarly, a trap (e.g. a division by zero when the
+//! [overflow checks](crate::codegen::CodeGenOptions::overflow_checks) are enabled) aborts
+//! the whole process. A playground should therefore run the sandbox in a separate process.
+//!
+//! # Profiling
+//!
+//! A program run with [`run_profiled`] is instrumented with the [profiling](crate::codegen::profile)
+//! hooks, and the number of calls and the wall time of every function is available
+//! in its [`RunResult::profile`]. The measured times include the overhead of the hooks,
+//! so they are only meaningful relative to each other.
+
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use inkwell::module::Module;
+use inkwell::OptimizationLevel;
+
+use crate::ast::{ASTNode, Crate, ExternItem, FuncASTNode, Type};
+use crate::cancel::CancellationToken;
+use crate::codegen::error::CodeGenError;
+use crate::codegen::profile::{ProfileReport, Profiler};
+use crate::codegen::session::CompilationSession;
+use crate::codegen::CodeGenOptions;
+use crate::limits::MemoryLimits;
+use crate::link;
+use crate::parser::error::ParserError;
+use crate::parser::Parser;
+
+/// The names of the functions provided by the sandbox to the programs.
+///
+/// Each of them takes a single parameter (of type `i32`, `f64` and `bool` respectively)
+/// and returns unit.
+pub const RUNTIME_FUNCTIONS: [&str; 3] = ["print_i32", "print_f64", "print_bool"];
+
+/// The limits of a [run](run) in the sandbox. By default, there are no limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SandboxLimits {
+    memory: MemoryLimits,
+    timeout: Option<Duration>,
+    max_output: Option<usize>,
+}
+
+impl SandboxLimits {
+    /// Creates new `SandboxLimits` without any limits.
+    pub fn new() -> SandboxLimits {
+        SandboxLimits::default()
+    }
+
+    /// Sets the memory limits of the parsing.
+    pub fn with_memory(self, memory: MemoryLimits) -> SandboxLimits {
+        SandboxLimits { memory, ..self }
+    }
+
+    /// Sets the maximum wall-clock time of the compilation and execution together.
+    pub fn with_timeout(self, timeout: Duration) -> SandboxLimits {
+        SandboxLimits {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Sets the maximum length (in bytes) of the captured output.
+    ///
+    /// The output exceeding the limit is discarded, but the program keeps running.
+    pub fn with_max_output(self, max_output: usize) -> SandboxLimits {
+        SandboxLimits {
+            max_output: Some(max_output),
+            ..self
+        }
+    }
+
+    /// Returns the memory limits of the parsing.
+    pub fn memory(&self) -> MemoryLimits {
+        self.memory
+    }
+
+    /// Returns the maximum wall-clock time of the compilation and execution, if any.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the maximum length (in bytes) of the captured output, if any.
+    pub fn max_output(&self) -> Option<usize> {
+        self.max_output
+    }
+}
+
+/// The status of a finished [run](run).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    /// The program ran to completion, and `main` returned the given exit code
+    /// (or `0` if it returns unit).
+    Exited(i32),
+    /// The program couldn't be compiled (see [`RunResult::diagnostics`]).
+    CompileError,
+    /// The compilation or the execution exceeded the timeout.
+    TimedOut,
+    /// The compiler failed unexpectedly (e.g. it doesn't support a construct yet).
+    InternalError,
+}
+
+/// The result of a [run](run) in the sandbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunResult {
+    output: String,
+    truncated: bool,
+    status: RunStatus,
+    diagnostics: Vec<String>,
+    profile: Option<ProfileReport>,
+}
+
+impl RunResult {
+    /// Returns the captured output of the program.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// Returns whether the output was truncated because it exceeded its
+    /// [limit](SandboxLimits::with_max_output).
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the status of the run.
+    pub fn status(&self) -> RunStatus {
+        self.status
+    }
+
+    /// Returns the errors and warnings encountered during the compilation.
+    pub fn diagnostics(&self) -> &[String] {
+        &self.diagnostics
+    }
+
+    /// Returns the profile of the program, if it was [run with the profiler](run_profiled)
+    /// and it ran to completion.
+    pub fn profile(&self) -> Option<&ProfileReport> {
+        self.profile.as_ref()
+    }
+}
+
+/// Compiles the given μRust source code and runs its `main` function within the given limits.
+pub fn run(source: &str, limits: SandboxLimits) -> RunResult {
+    run_with(source, limits, false)
+}
+
+/// Like [`run`], but also measures the number of calls and the wall time of every function
+/// of the program (see [`RunResult::profile`]).
+pub fn run_profiled(source: &str, limits: SandboxLimits) -> RunResult {
+    run_with(source, limits, true)
+}
+
+/// Runs the program, with the profiler if `profiling` is set.
+fn run_with(source: &str, limits: SandboxLimits, profiling: bool) -> RunResult {
+    let cancellation = limits
+        .timeout
+        .map_or_else(CancellationToken::new, CancellationToken::with_timeout);
+    let output = Arc::new(Mutex::new(Output::new(limits.max_output)));
+
+    let (sender, receiver) = mpsc::channel();
+    {
+        let source = source.to_owned();
+        let cancellation = cancellation.clone();
+        let output = output.clone();
+        thread::spawn(move || {
+            OUTPUT.with(|current| *current.borrow_mut() = Some(output));
+            let result = compile_and_run(&source, limits.memory, cancellation, profiling);
+            let _ = sender.send(result);
+        });
+    }
+
+    let result = match cancellation.deadline() {
+        Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+        None => receiver.recv().map_err(RecvTimeoutError::from),
+    };
+    let (status, diagnostics, profile) = match result {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => {
+            // Stops the compilation, if it is still in progress
+            cancellation.cancel();
+            (RunStatus::TimedOut, Vec::new(), None)
+        }
+        Err(RecvTimeoutError::Disconnected) => (
+            RunStatus::InternalError,
+            vec!["error: The compiler panicked".to_owned()],
+            None,
+        ),
+    };
+
+    let output = output.lock().map(|o| o.clone()).unwrap_or_default();
+    RunResult {
+        output: output.text,
+        truncated: output.truncated,
+        status,
+        diagnostics,
+        profile,
+    }
+}
+
+/// The status of a run, its diagnostics and the profile of the program (if it was profiled).
+type Outcome = (RunStatus, Vec<String>, Option<ProfileReport>);
+
+/// Compiles and runs the program on the current thread (with the profiler if `profiling` is set),
+/// returning the status of the run, the diagnostics and the profile.
+fn compile_and_run(
+    source: &str,
+    memory: MemoryLimits,
+    cancellation: CancellationToken,
+    profiling: bool,
+) -> Outcome {
+    let crt = match parse(source, memory, &cancellation) {
+        Ok(Ok(crt)) => crt,
+        Ok(Err(err)) => return parser_failure(err),
+        Err(err) => {
+            return (
+                RunStatus::InternalError,
+                vec![format!("error: {err}")],
+                None,
+            )
+        }
+    };
+
+    let errors = check_program(&crt);
+    if !errors.is_empty() {
+        let diagnostics = errors.iter().map(|e| format!("error: {e}")).collect();
+        return (RunStatus::CompileError, diagnostics, None);
+    }
+
+    let options = CodeGenOptions::new().with_profiling(profiling);
+    let session = CompilationSession::with_options(options).with_cancellation(cancellation);
+    let mut diagnostics = Vec::new();
+    let result = session.with_module(&crt, |module, warnings| {
+        diagnostics.extend(warnings.iter().map(|w| format!("warning: {w}")));
+        execute(module, profiling)
+    });
+    match result {
+        Ok(Ok((code, profile))) => (RunStatus::Exited(code), diagnostics, profile),
+        Ok(Err(message)) => {
+            diagnostics.push(format!("error: {message}"));
+            (RunStatus::InternalError, diagnostics, None)
+        }
+        Err(CodeGenError::Interrupted(_)) => (RunStatus::TimedOut, diagnostics, None),
+        Err(err) => {
+            diagnostics.push(format!("error: {err:?}"));
+            (RunStatus::CompileError, diagnostics, None)
+        }
+    }
+}
+
+/// Returns the outcome of a program that couldn't be parsed.
+fn parser_failure(err: ParserError) -> Outcome {
+    match err {
+        ParserError::Interrupted(_) => (RunStatus::TimedOut, Vec::new(), None),
+        ParserError::Aggregated(errors) => {
+            let diagnostics = errors.iter().map(|e| format!("error: {e}")).collect();
+            (RunStatus::CompileError, diagnostics, None)
+        }
+        ParserError::TooManyErrors(errors) => {
+            let mut diagnostics: Vec<String> =
+                errors.iter().map(|e| format!("error: {e}")).collect();
+            diagnostics.push(format!("error: aborting after {} errors", errors.len()));
+            (RunStatus::CompileError, diagnostics, None)
+        }
+        err => (RunStatus::CompileError, vec![format!("error: {err}")], None),
+    }
+}
+
+/// Parses the source code from a temporary file.
+fn parse(
+    source: &str,
+    memory: MemoryLimits,
+    cancellation: &CancellationToken,
+) -> io::Result<Result<Crate, ParserError>> {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+    // The name of the file determines the name of the module
+    let dir = std::env::temp_dir().join(format!(
+        "mrs_sandbox_{}_{}",
+        process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    let path = dir.join("main.mrs");
+    fs::create_dir_all(&dir)?;
+    let result = fs::write(&path, source)
+        .and_then(|_| Parser::with_cancellation(&path, cancellation.clone()))
+        .map(|parser| parser.with_limits(memory).parse());
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+/// Returns the errors that prevent the program from running in the sandbox.
+fn check_program(crt: &Crate) -> Vec<String> {
+    let mut errors = Vec::new();
+    for block in crt.extern_blocks() {
+        for item in block.items() {
+            match item {
+                ExternItem::Func(proto) => {
+                    let expected = runtime_param_type(proto.name());
+                    let signature = proto.signature();
+                    match expected {
+                        Some(ty)
+                            if signature.param_types() == [ty.clone()]
+                                && *signature.return_type() == Type::Unit => {}
+                        Some(ty) => errors.push(format!(
+                            "The runtime function `{}` has the signature `fn({})` at {}",
+                            proto.name(),
+                            ty,
+                            proto.span()
+                        )),
+                        None => errors.push(format!(
+                            "Unknown runtime function `{}` at {}",
+                            proto.name(),
+                            proto.span()
+                        )),
+                    }
+                }
+                ExternItem::Static(stat) => errors.push(format!(
+                    "Extern statics are not available in the sandbox at {}",
+                    stat.span()
+                )),
+            }
+        }
+    }
+
+    if let Err(err) = link::check_entry_point(crt) {
+        errors.push(err.to_string());
+    }
+    errors
+}
+
+/// Runs the `main` function of the given module, returning its exit code
+/// and its profile if `profiling` is set.
+fn execute(module: &Module, profiling: bool) -> Result<(i32, Option<ProfileReport>), String> {
+    let engine = module
+        .create_jit_execution_engine(OptimizationLevel::None)
+        .map_err(|err| err.to_string())?;
+    for (name, address) in [
+        ("print_i32", print_i32 as extern "C" fn(i32) as usize),
+        ("print_f64", print_f64 as extern "C" fn(f64) as usize),
+        ("print_bool", print_bool as extern "C" fn(u8) as usize),
+    ] {
+        if let Some(function) = module.get_function(name) {
+            engine.add_global_mapping(&function, address);
+        }
+    }
+    let profiler = profiling.then(|| Profiler::attach(module, &engine));
+
+    let returns_unit = module
+        .get_function(FuncASTNode::ENTRY_POINT)
+        .is_some_and(|main| main.get_type().get_return_type().is_none());
+    // SAFETY: The signature of `main` has been checked, and the program can only call
+    // the runtime functions
+    let code = unsafe {
+        if returns_unit {
+            let main = engine
+                .get_function::<unsafe extern "C" fn()>(FuncASTNode::ENTRY_POINT)
+                .map_err(|err| err.to_string())?;
+            main.call();
+            0
+        } else {
+            let main = engine
+                .get_function::<unsafe extern "C" fn() -> i32>(FuncASTNode::ENTRY_POINT)
+                .map_err(|err| err.to_string())?;
+            main.call()
+        }
+    };
+    Ok((code, profiler.map(|profiler| profiler.report())))
+}
+
+/// Returns the type of the parameter of the runtime function with the given name, if any.
+fn runtime_param_type(name: &str) -> Option<Type> {
+    match name {
+        "print_i32" => Some(Type::I32),
+        "print_f64" => Some(Type::F64),
+        "print_bool" => Some(Type::Bool),
+        _ => None,
+    }
+}
+
+/// The captured output of a program.
+#[derive(Debug, Clone, Default)]
+struct Output {
+    text: String,
+    limit: Option<usize>,
+    truncated: bool,
+}
+
+impl Output {
+    fn new(limit: Option<usize>) -> Output {
+        Output {
+            limit,
+            ..Output::default()
+        }
+    }
+
+    fn write(&mut self, s: &str) {
+        let remaining = self
+            .limit
+            .map_or(usize::MAX, |limit| limit - self.text.len());
+        if s.len() <= remaining {
+            self.text.push_str(s);
+        } else {
+            // The printed values are ASCII, so any index is a char boundary
+            self.text.push_str(&s[..remaining]);
+            self.truncated = true;
+        }
+    }
+}
+
+thread_local! {
+    /// The output of the program running on the current thread.
+    static OUTPUT: RefCell<Option<Arc<Mutex<Output>>>> = const { RefCell::new(None) };
+}
+
+fn print(s: String) {
+    OUTPUT.with(|current| {
+        if let Some(output) = &*current.borrow() {
+            if let Ok(mut output) = output.lock() {
+                output.write(&s);
+            }
+        }
+    });
+}
+
+extern "C" fn print_i32(x: i32) {
+    print(format!("{x}\n"));
+}
+
+extern "C" fn print_f64(x: f64) {
+    print(format!("{x}\n"));
+}
+
+/// The μRust `bool` is passed as an `i1`, so only its lowest bit is defined.
+extern "C" fn print_bool(x: u8) {
+    print(format!("{}\n", x & 1 != 0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRINTS: &str = "extern \"C\" {\n\
+        fn print_i32(x: i32);\n\
+        fn print_f64(x: f64);\n\
+        fn print_bool(x: bool);\n\
+    }\n";
+
+    #[test]
+    fn test_run() {
+        let source = format!(
+            "{PRINTS}fn main() -> i32 {{\n\
+                unsafe {{ print_i32(6 * 7); print_f64(0.5); print_bool(1 < 2); }};\n\
+                return 3;\n\
+            }}\n"
+        );
+        let result = run(&source, SandboxLimits::new());
+        assert_eq!(result.status(), RunStatus::Exited(3));
+        assert_eq!(result.output(), "42\n0.5\ntrue\n");
+        assert!(!result.is_truncated());
+        assert!(result.diagnostics().is_empty());
+
+        let source =
+            format!("{PRINTS}fn main() {{ unsafe {{ print_i32(1); print_i32(23); }}; }}\n");
+        let result = run(&source, SandboxLimits::new().with_max_output(3));
+        assert_eq!(result.status(), RunStatus::Exited(0));
+        assert_eq!(result.output(), "1\n2");
+        assert!(result.is_truncated());
+        assert!(result.profile().is_none());
+    }
+
+    #[test]
+    fn test_run_profiled() {
+        let source = format!(
+            "{PRINTS}fn fib(n: i32) -> i32 {{
+                if n < 2 {{ return n; }};
+                return fib(n - 1) + fib(n - 2);
+            }}
+            fn main() {{ unsafe {{ print_i32(fib(10)); }}; }}
+"
+        );
+        let result = run_profiled(&source, SandboxLimits::new());
+        assert_eq!(result.status(), RunStatus::Exited(0));
+        assert_eq!(result.output(), "55\n");
+        let profile = result.profile().unwrap();
+        assert_eq!(profile.function("fib").unwrap().calls(), 177);
+        assert_eq!(profile.function("main").unwrap().calls(), 1);
+        assert_eq!(profile.hottest()[0].name(), "main");
+
+        let result = run_profiled("fn main() -> i32 { return 1 + ; }", SandboxLimits::new());
+        assert_eq!(result.status(), RunStatus::CompileError);
+        assert!(result.profile().is_none());
+    }
+
+    #[test]
+    fn test_rejected_programs() {
+        let result = run("fn main() -> i32 { return 1 + ; }", SandboxLimits::new());
+        assert_eq!(result.status(), RunStatus::CompileError);
+        assert_eq!(result.diagnostics().len(), 1);
+
+        let result = run(
+            "extern \"C\" { fn abort(); fn print_i32(x: f64); }\nfn start() {}\n",
+            SandboxLimits::new(),
+        );
+        assert_eq!(result.status(), RunStatus::CompileError);
+        assert_eq!(result.diagnostics().len(), 3);
+        assert!(result.diagnostics()[0].contains("`abort`"));
+
+        let limits = SandboxLimits::new().with_memory(MemoryLimits::new().with_max_tokens(5));
+        let result = run("fn main() { let x: i32 = 1; }", limits);
+        assert_eq!(result.status(), RunStatus::CompileError);
+        assert!(result.diagnostics()[0].contains("limit of 5 tokens"));
+
+        let result = run(
+            "fn main() {}",
+            SandboxLimits::new().with_timeout(Duration::ZERO),
+        );
+        assert_eq!(result.status(), RunStatus::TimedOut);
+    }
+}
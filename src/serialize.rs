@@ -0,0 +1,1163 @@
+//! A module containing the serialization of the AST into a compact binary format.
+//!
+//! The nodes encode themselves using [`ASTNode::encode`] -- each node writes its [tag](NodeTag)
+//! followed by its fields (the children are encoded recursively) -- and the [`Decoder`] rebuilds
+//! them using their constructors. The serialized AST can be used to cache the results of parsing.
+//!
+//! # Format
+//!
+//! The encoded data starts with a header:
+//! - the magic bytes `MRSA`,
+//! - the [format version](FORMAT_VERSION) (a little-endian `u16`),
+//! - the checksum of the payload (a little-endian `u64`, the 64-bit FNV-1a hash).
+//!
+//! The header is followed by the payload, in which the lengths, line and column numbers are
+//! LEB128-encoded, the `i128` and `f64` values are little-endian and the strings are UTF-8
+//! prefixed with their length. Decoding never panics on malformed input -- a corrupted
+//! or incompatible payload is reported as a [`DecodeError`].
+
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::abi::Abi;
+use crate::ast::{
+    ASTNode, ArithExprASTNode, ArithOperator, AssignASTNode, Attribute, AttributeArg, BlockASTNode,
+    BorrowExprASTNode, CompExprASTNode, CompOperator, ConstValue, Crate, CrateASTNode,
+    DerefExprASTNode, DoWhileASTNode, ElseASTNode, ElseExpr, ExprASTNode, ExprStmtASTNode,
+    ExternASTNode, ExternItem, ForASTNode, FunCallASTNode, FuncASTNode, FuncProtoASTNode,
+    GroupedExprASTNode, IfASTNode, ImportASTNode, InfLoopASTNode, ItemASTNode, LayoutOfASTNode,
+    LayoutQuery, LazyBoolExprASTNode, LazyBoolOperator, LetASTNode, LiteralASTNode, NegExprASTNode,
+    NegOperator, ParamASTNode, PathASTNode, ReturnASTNode, StatementASTNode, StaticASTNode,
+    TupleASTNode, TupleIndexASTNode, Type, TypeASTMetaNode, TypeCastASTNode, UnderscoreASTNode,
+    UnsafeBlockASTNode, Visibility, WhileASTNode,
+};
+use crate::token::{Position, Span};
+
+/// The version of the binary format.
+///
+/// It has to be incremented whenever the encoding of any node changes.
+pub const FORMAT_VERSION: u16 = 16;
+
+/// The magic bytes at the start of the encoded data.
+const MAGIC: &[u8; 4] = b"MRSA";
+
+/// The length of the header (magic bytes, version and checksum).
+const HEADER_LENGTH: usize = MAGIC.len() + 2 + 8;
+
+/// The maximum nesting depth of the decoded nodes.
+///
+/// It protects the decoder from overflowing the stack on corrupted (or malicious) input.
+const MAX_DEPTH: usize = 256;
+
+/// A result of decoding.
+pub type Result<T> = std::result::Result<T, DecodeError>;
+
+/// The type of error that can occur while decoding the AST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data doesn't start with the expected header.
+    InvalidHeader,
+    /// The data was encoded in a different version of the format.
+    UnsupportedVersion {
+        /// The version of the data.
+        version: u16,
+    },
+    /// The checksum of the payload doesn't match the one in the header.
+    ChecksumMismatch,
+    /// The data ended unexpectedly.
+    UnexpectedEnd,
+    /// An invalid value was encountered.
+    InvalidValue {
+        /// The description of the expected value.
+        expected: &'static str,
+        /// The offset of the value in the data.
+        offset: usize,
+    },
+    /// The nodes are nested too deeply (see [`MAX_DEPTH`]).
+    TooDeep {
+        /// The offset of the node that exceeds the maximum depth.
+        offset: usize,
+    },
+    /// There are unexpected bytes after the end of the AST.
+    TrailingBytes {
+        /// The offset of the first unexpected byte.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidHeader => write!(f, "The data is not a serialized μRust AST"),
+            DecodeError::UnsupportedVersion { version } => {
+                write!(
+                    f,
+                    "Unsupported format version {} (expected {})",
+                    version, FORMAT_VERSION
+                )
+            }
+            DecodeError::ChecksumMismatch => write!(f, "The data is corrupted (checksum mismatch)"),
+            DecodeError::UnexpectedEnd => write!(f, "Unexpected end of data"),
+            DecodeError::InvalidValue { expected, offset } => {
+                write!(f, "Expected {} at offset {}", expected, offset)
+            }
+            DecodeError::TooDeep { offset } => {
+                write!(f, "The nodes are nested too deeply at offset {}", offset)
+            }
+            DecodeError::TrailingBytes { offset } => {
+                write!(
+                    f,
+                    "Unexpected data after the end of the AST at offset {}",
+                    offset
+                )
+            }
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// The tag identifying the kind of an encoded node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum NodeTag {
+    Crate = 0,
+    Import,
+    Func,
+    Proto,
+    Param,
+    Static,
+    Extern,
+    Let,
+    ExprStmt,
+    Literal,
+    Path,
+    Underscore,
+    Grouped,
+    Neg,
+    Arith,
+    Comp,
+    LazyBool,
+    Cast,
+    Assign,
+    Block,
+    UnsafeBlock,
+    If,
+    InfLoop,
+    While,
+    Return,
+    FunCall,
+    DoWhile,
+    For,
+    LayoutOf,
+    Else,
+    Tuple,
+    TupleIndex,
+    Borrow,
+    Deref,
+}
+
+impl NodeTag {
+    const ALL: [NodeTag; 34] = [
+        NodeTag::Crate,
+        NodeTag::Import,
+        NodeTag::Func,
+        NodeTag::Proto,
+        NodeTag::Param,
+        NodeTag::Static,
+        NodeTag::Extern,
+        NodeTag::Let,
+        NodeTag::ExprStmt,
+        NodeTag::Literal,
+        NodeTag::Path,
+        NodeTag::Underscore,
+        NodeTag::Grouped,
+        NodeTag::Neg,
+        NodeTag::Arith,
+        NodeTag::Comp,
+        NodeTag::LazyBool,
+        NodeTag::Cast,
+        NodeTag::Assign,
+        NodeTag::Block,
+        NodeTag::UnsafeBlock,
+        NodeTag::If,
+        NodeTag::InfLoop,
+        NodeTag::While,
+        NodeTag::Return,
+        NodeTag::FunCall,
+        NodeTag::DoWhile,
+        NodeTag::For,
+        NodeTag::LayoutOf,
+        NodeTag::Else,
+        NodeTag::Tuple,
+        NodeTag::TupleIndex,
+        NodeTag::Borrow,
+        NodeTag::Deref,
+    ];
+}
+
+/// An encoder that builds the binary representation of an AST.
+///
+/// The nodes are encoded using [`ASTNode::encode`].
+pub struct Encoder {
+    payload: Vec<u8>,
+}
+
+impl Encoder {
+    /// Creates a new empty `Encoder`.
+    pub fn new() -> Encoder {
+        Encoder {
+            payload: Vec::new(),
+        }
+    }
+
+    /// Writes the tag of a node.
+    pub fn write_tag(&mut self, tag: NodeTag) {
+        self.write_u8(tag as u8);
+    }
+
+    /// Writes a byte.
+    pub fn write_u8(&mut self, value: u8) {
+        self.payload.push(value);
+    }
+
+    /// Writes a boolean.
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    /// Writes an unsigned integer as a LEB128 varint.
+    pub fn write_usize(&mut self, mut value: usize) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.write_u8(byte);
+                return;
+            }
+            self.write_u8(byte | 0x80);
+        }
+    }
+
+    /// Writes a string prefixed with its length.
+    pub fn write_str(&mut self, value: &str) {
+        self.write_usize(value.len());
+        self.payload.extend_from_slice(value.as_bytes());
+    }
+
+    /// Writes a span.
+    pub fn write_span(&mut self, span: Span) {
+        for position in [span.start(), span.end()] {
+            self.write_usize(position.line());
+            self.write_usize(position.column());
+        }
+    }
+
+    /// Writes a span, if any.
+    pub fn write_optional_span(&mut self, span: Option<Span>) {
+        self.write_bool(span.is_some());
+        if let Some(span) = span {
+            self.write_span(span);
+        }
+    }
+
+    /// Writes a type annotation.
+    pub fn write_type(&mut self, ty: &TypeASTMetaNode) {
+        self.write_type_name(&ty.ty());
+        self.write_span(ty.span());
+    }
+
+    /// Writes the type given by the suffix of a literal, if it has one.
+    pub fn write_suffix(&mut self, suffix: Option<Type>) {
+        self.write_bool(suffix.is_some());
+        if let Some(ty) = suffix {
+            self.write_type_name(&ty);
+        }
+    }
+
+    /// Writes a type without a span.
+    fn write_type_name(&mut self, ty: &Type) {
+        let tag = match ty {
+            Type::I32 => 0,
+            Type::F64 => 1,
+            Type::Bool => 2,
+            Type::Unit => 3,
+            Type::I8 => 4,
+            Type::I16 => 5,
+            Type::I64 => 6,
+            Type::U8 => 7,
+            Type::U16 => 8,
+            Type::U32 => 9,
+            Type::U64 => 10,
+            Type::F32 => 11,
+            Type::Str => 12,
+            Type::Tuple(elements) => {
+                self.write_u8(13);
+                self.write_usize(elements.len());
+                for element in elements.iter() {
+                    self.write_type_name(element);
+                }
+                return;
+            }
+            Type::Ref { target, mutable } => {
+                self.write_u8(14);
+                self.write_bool(*mutable);
+                self.write_type_name(target);
+                return;
+            }
+        };
+        self.write_u8(tag);
+    }
+
+    /// Writes a visibility.
+    pub fn write_visibility(&mut self, visibility: Visibility) {
+        self.write_bool(visibility.is_public());
+    }
+
+    /// Writes the attributes of an item prefixed with their count.
+    pub fn write_attributes(&mut self, attributes: &[Attribute]) {
+        self.write_usize(attributes.len());
+        for attr in attributes {
+            self.write_str(attr.name());
+            self.write_usize(attr.args().len());
+            for arg in attr.args() {
+                match arg {
+                    AttributeArg::Int(i) => {
+                        self.write_u8(0);
+                        self.payload.extend_from_slice(&i.to_le_bytes());
+                    }
+                    AttributeArg::Ident(ident) => {
+                        self.write_u8(1);
+                        self.write_str(ident);
+                    }
+                }
+            }
+            self.write_span(attr.span());
+        }
+    }
+
+    /// Writes the value of a literal.
+    pub fn write_literal(&mut self, value: ConstValue) {
+        match value {
+            ConstValue::Int(i) => {
+                self.write_u8(0);
+                self.payload.extend_from_slice(&i.to_le_bytes());
+            }
+            ConstValue::Float(x) => {
+                self.write_u8(1);
+                self.payload.extend_from_slice(&x.to_le_bytes());
+            }
+            ConstValue::Bool(b) => {
+                self.write_u8(2);
+                self.write_bool(b);
+            }
+            ConstValue::Unit => self.write_u8(3),
+        }
+    }
+
+    /// Writes the value of a string literal.
+    pub fn write_str_literal(&mut self, value: &str) {
+        self.write_u8(4);
+        self.write_str(value);
+    }
+
+    /// Encodes the given node.
+    pub fn write_node(&mut self, node: &dyn ASTNode) {
+        node.encode(self);
+    }
+
+    /// Encodes the given node, if any.
+    pub fn write_optional(&mut self, node: Option<&dyn ASTNode>) {
+        self.write_bool(node.is_some());
+        if let Some(node) = node {
+            node.encode(self);
+        }
+    }
+
+    /// Encodes the given nodes prefixed with their count.
+    pub fn write_list<'a>(&mut self, nodes: impl ExactSizeIterator<Item = &'a dyn ASTNode>) {
+        self.write_usize(nodes.len());
+        for node in nodes {
+            node.encode(self);
+        }
+    }
+
+    /// Returns the encoded data, including the header.
+    pub fn finish(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH + self.payload.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&checksum(&self.payload).to_le_bytes());
+        bytes.extend_from_slice(&self.payload);
+        bytes
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A decoder that rebuilds an AST from its binary representation.
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Creates a new `Decoder` for the given data, validating its header.
+    ///
+    /// # Errors
+    ///
+    /// If the header is invalid, the version is not [supported](FORMAT_VERSION),
+    /// or the checksum doesn't match the payload, the corresponding [`DecodeError`] is returned.
+    pub fn new(bytes: &'a [u8]) -> Result<Decoder<'a>> {
+        if bytes.len() < HEADER_LENGTH || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(DecodeError::InvalidHeader);
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != FORMAT_VERSION {
+            return Err(DecodeError::UnsupportedVersion { version });
+        }
+        let mut expected = [0; 8];
+        expected.copy_from_slice(&bytes[6..HEADER_LENGTH]);
+        if u64::from_le_bytes(expected) != checksum(&bytes[HEADER_LENGTH..]) {
+            return Err(DecodeError::ChecksumMismatch);
+        }
+
+        Ok(Decoder {
+            bytes,
+            offset: HEADER_LENGTH,
+            depth: 0,
+        })
+    }
+
+    /// Decodes a whole crate and checks that there is no data left.
+    pub fn decode_crate(mut self) -> Result<Crate> {
+        let root = self.crate_node()?;
+        if self.offset != self.bytes.len() {
+            return Err(DecodeError::TrailingBytes {
+                offset: self.offset,
+            });
+        }
+        Ok(Crate::new(Box::new(root)))
+    }
+
+    //#region Primitives
+    fn invalid<T>(&self, expected: &'static str, offset: usize) -> Result<T> {
+        Err(DecodeError::InvalidValue { expected, offset })
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(count)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        let bytes = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool> {
+        let offset = self.offset;
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => self.invalid("a boolean", offset),
+        }
+    }
+
+    fn usize(&mut self) -> Result<usize> {
+        let offset = self.offset;
+        let mut value: usize = 0;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.u8()?;
+            let bits = ((byte & 0x7f) as usize)
+                .checked_shl(shift)
+                .filter(|bits| bits >> shift == (byte & 0x7f) as usize);
+            value |= match bits {
+                Some(bits) => bits,
+                None => return self.invalid("an integer", offset),
+            };
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        self.invalid("an integer", offset)
+    }
+
+    fn str(&mut self) -> Result<Rc<str>> {
+        let offset = self.offset;
+        let len = self.usize()?;
+        match std::str::from_utf8(self.take(len)?) {
+            Ok(s) => Ok(s.into()),
+            Err(_) => self.invalid("a UTF-8 string", offset),
+        }
+    }
+
+    fn position(&mut self) -> Result<Position> {
+        let line = self.usize()?;
+        let column = self.usize()?;
+        Ok(Position::new_at(line, column))
+    }
+
+    fn span(&mut self) -> Result<Span> {
+        let start = self.position()?;
+        let end = self.position()?;
+        Ok(Span::new(start, end))
+    }
+
+    fn ty(&mut self) -> Result<TypeASTMetaNode> {
+        let ty = self.type_name()?;
+        Ok(TypeASTMetaNode::new(ty, self.span()?))
+    }
+
+    fn type_name(&mut self) -> Result<Type> {
+        let offset = self.offset;
+        let ty = match self.u8()? {
+            0 => Type::I32,
+            1 => Type::F64,
+            2 => Type::Bool,
+            3 => Type::Unit,
+            4 => Type::I8,
+            5 => Type::I16,
+            6 => Type::I64,
+            7 => Type::U8,
+            8 => Type::U16,
+            9 => Type::U32,
+            10 => Type::U64,
+            11 => Type::F32,
+            12 => Type::Str,
+            13 => Type::tuple(self.nested(|decoder| decoder.list(Self::type_name))?),
+            14 => {
+                let mutable = self.bool()?;
+                Type::reference(self.nested(Self::type_name)?, mutable)
+            }
+            _ => return self.invalid("a type", offset),
+        };
+        Ok(ty)
+    }
+
+    fn visibility(&mut self) -> Result<Visibility> {
+        Ok(match self.bool()? {
+            true => Visibility::Public,
+            false => Visibility::Private,
+        })
+    }
+
+    fn attribute(&mut self) -> Result<Attribute> {
+        let name = self.str()?;
+        let args = self.list(|decoder| {
+            let offset = decoder.offset;
+            match decoder.u8()? {
+                0 => Ok(AttributeArg::Int(i128::from_le_bytes(decoder.array()?))),
+                1 => Ok(AttributeArg::Ident(decoder.str()?)),
+                _ => decoder.invalid("an attribute argument", offset),
+            }
+        })?;
+        Ok(Attribute::new(name, args, self.span()?))
+    }
+
+    /// Reads an operator encoded as its index in the given list (i.e. the order of declaration).
+    fn operator<T: Copy>(&mut self, operators: &[T]) -> Result<T> {
+        let offset = self.offset;
+        match operators.get(self.u8()? as usize) {
+            Some(operator) => Ok(*operator),
+            None => self.invalid("an operator", offset),
+        }
+    }
+
+    fn tag(&mut self) -> Result<(NodeTag, usize)> {
+        let offset = self.offset;
+        match NodeTag::ALL.get(self.u8()? as usize) {
+            Some(tag) => Ok((*tag, offset)),
+            None => self.invalid("a node tag", offset),
+        }
+    }
+
+    fn expect(&mut self, expected: NodeTag, description: &'static str) -> Result<()> {
+        match self.tag()? {
+            (tag, _) if tag == expected => Ok(()),
+            (_, offset) => self.invalid(description, offset),
+        }
+    }
+
+    fn list<T>(&mut self, mut decode: impl FnMut(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        let count = self.usize()?;
+        // The count is not trusted, so the vector is not preallocated
+        let mut nodes = Vec::new();
+        for _ in 0..count {
+            nodes.push(decode(self)?);
+        }
+        Ok(nodes)
+    }
+
+    fn optional<T>(&mut self, decode: impl FnOnce(&mut Self) -> Result<T>) -> Result<Option<T>> {
+        match self.bool()? {
+            true => decode(self).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    fn nested<T>(&mut self, decode: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth == MAX_DEPTH {
+            return Err(DecodeError::TooDeep {
+                offset: self.offset,
+            });
+        }
+        self.depth += 1;
+        let result = decode(self);
+        self.depth -= 1;
+        result
+    }
+    //#endregion
+
+    //#region Items
+    fn crate_node(&mut self) -> Result<CrateASTNode> {
+        self.expect(NodeTag::Crate, "a crate")?;
+        let name = self.str()?;
+        let imports = self.list(Self::import)?;
+        let items = self.list(Self::item)?;
+        Ok(CrateASTNode::new(name, imports, items, self.span()?))
+    }
+
+    fn import(&mut self) -> Result<ImportASTNode> {
+        self.expect(NodeTag::Import, "an import")?;
+        let module = self.str()?;
+        let name = self.str()?;
+        let name_span = self.span()?;
+        Ok(ImportASTNode::new(module, name, name_span, self.span()?))
+    }
+
+    fn item(&mut self) -> Result<ItemASTNode> {
+        Ok(match self.tag()? {
+            (NodeTag::Func, _) => ItemASTNode::Func(Box::new(self.func()?)),
+            (NodeTag::Static, _) => ItemASTNode::Static(Box::new(self.static_item()?)),
+            (NodeTag::Extern, _) => ItemASTNode::Extern(Box::new(self.extern_block()?)),
+            (_, offset) => return self.invalid("an item", offset),
+        })
+    }
+
+    fn func(&mut self) -> Result<FuncASTNode> {
+        self.expect(NodeTag::Proto, "a function prototype")?;
+        let proto = self.proto()?;
+        let body = self.block()?;
+        let visibility = self.visibility()?;
+        let attributes = self.list(Self::attribute)?;
+        Ok(FuncASTNode::new(proto, body, visibility, self.span()?).with_attributes(attributes))
+    }
+
+    fn proto(&mut self) -> Result<FuncProtoASTNode> {
+        let name = self.str()?;
+        let name_span = self.span()?;
+        let params = self.list(Self::param)?;
+        let return_type = self.ty()?;
+        let abi = self.optional(Self::abi)?;
+        let proto = FuncProtoASTNode::new(name, name_span, params, return_type, self.span()?);
+        Ok(match abi {
+            Some(abi) => proto.with_abi(abi),
+            None => proto,
+        })
+    }
+
+    fn param(&mut self) -> Result<ParamASTNode> {
+        self.expect(NodeTag::Param, "a parameter")?;
+        let assignee = self.expr()?;
+        let ty = self.ty()?;
+        let mut_span = self.optional(Self::span)?;
+        Ok(ParamASTNode::new(assignee, ty, mut_span, self.span()?))
+    }
+
+    fn static_item(&mut self) -> Result<StaticASTNode> {
+        let name = self.str()?;
+        let name_span = self.span()?;
+        let value = self.optional(Self::expr)?;
+        let ty = self.ty()?;
+        let mut_span = self.optional(Self::span)?;
+        let visibility = self.visibility()?;
+        let span = self.span()?;
+        Ok(match value {
+            Some(value) => StaticASTNode::new_with_assignment(
+                name, name_span, value, ty, mut_span, visibility, span,
+            ),
+            None => StaticASTNode::new(name, name_span, ty, mut_span, visibility, span),
+        })
+    }
+
+    fn abi(&mut self) -> Result<Abi> {
+        let offset = self.offset;
+        match Abi::from_name(&self.str()?) {
+            Some(abi) => Ok(abi),
+            None => self.invalid("a supported ABI", offset),
+        }
+    }
+
+    fn extern_block(&mut self) -> Result<ExternASTNode> {
+        let abi = self.abi()?;
+        let items = self.list(|d| match d.tag()? {
+            (NodeTag::Proto, _) => Ok(ExternItem::Func(Box::new(d.proto()?))),
+            (NodeTag::Static, _) => Ok(ExternItem::Static(Box::new(d.static_item()?))),
+            (_, offset) => d.invalid("an extern item", offset),
+        })?;
+        Ok(ExternASTNode::new(abi, items, self.span()?))
+    }
+    //#endregion
+
+    //#region Statements
+    fn statement(&mut self) -> Result<Box<dyn StatementASTNode>> {
+        self.nested(|d| {
+            Ok::<Box<dyn StatementASTNode>, _>(match d.tag()? {
+                (NodeTag::Let, _) => {
+                    let decl = d.expr()?;
+                    let ty = d.ty()?;
+                    let value = d.optional(Self::expr)?;
+                    let mut_span = d.optional(Self::span)?;
+                    let span = d.span()?;
+                    Box::new(match value {
+                        Some(value) => {
+                            LetASTNode::new_with_assignment(decl, ty, value, mut_span, span)
+                        }
+                        None => LetASTNode::new(decl, ty, mut_span, span),
+                    })
+                }
+                (NodeTag::ExprStmt, _) => {
+                    let expr = d.expr()?;
+                    Box::new(ExprStmtASTNode::new(expr, d.span()?))
+                }
+                (_, offset) => return d.invalid("a statement", offset),
+            })
+        })
+    }
+
+    fn block(&mut self) -> Result<BlockASTNode> {
+        self.expect(NodeTag::Block, "a block")?;
+        self.nested(Self::block_body)
+    }
+
+    fn block_body(&mut self) -> Result<BlockASTNode> {
+        let statements = self.list(Self::statement)?;
+        let return_expr = self.optional(Self::expr)?;
+        let span = self.span()?;
+        Ok(match return_expr {
+            Some(expr) => BlockASTNode::new_with_return(statements, expr, span),
+            None => BlockASTNode::new(statements, span),
+        })
+    }
+
+    fn if_body(&mut self) -> Result<IfASTNode> {
+        let condition = self.expr()?;
+        let then_block = Box::new(self.block()?);
+        let else_node = self.optional(Self::else_node)?;
+        Ok(IfASTNode::new(
+            condition,
+            then_block,
+            else_node,
+            self.span()?,
+        ))
+    }
+
+    fn else_node(&mut self) -> Result<ElseASTNode> {
+        self.expect(NodeTag::Else, "an else branch")?;
+        self.nested(Self::else_body)
+    }
+
+    fn else_body(&mut self) -> Result<ElseASTNode> {
+        let branch = match self.tag()? {
+            (NodeTag::Block, _) => ElseExpr::Else(Box::new(self.nested(Self::block_body)?)),
+            (NodeTag::If, _) => ElseExpr::ElseIf(Box::new(self.nested(Self::if_body)?)),
+            (_, offset) => return self.invalid("an else expression", offset),
+        };
+        Ok(ElseASTNode::new(branch, self.span()?))
+    }
+    //#endregion
+
+    //#region Expressions
+    fn expr(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        self.nested(Self::expr_body)
+    }
+
+    fn expr_body(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        // Every kind of expression is decoded in a separate method,
+        // which keeps the stack frames of the recursion small
+        let (tag, offset) = self.tag()?;
+        match tag {
+            NodeTag::Literal => self.literal(),
+            NodeTag::Path => Ok(Box::new(self.path()?)),
+            NodeTag::Underscore => Ok(Box::new(UnderscoreASTNode::new(self.span()?))),
+            NodeTag::Grouped => self.grouped(),
+            NodeTag::Tuple => self.tuple(),
+            NodeTag::TupleIndex => self.tuple_index(),
+            NodeTag::Neg => self.neg(),
+            NodeTag::Borrow => self.borrow(),
+            NodeTag::Deref => self.deref(),
+            NodeTag::Arith => self.arith(),
+            NodeTag::Comp => self.comp(),
+            NodeTag::LazyBool => self.lazy_bool(),
+            NodeTag::Cast => self.cast(),
+            NodeTag::LayoutOf => self.layout_of(),
+            NodeTag::Assign => self.assign(),
+            NodeTag::Block => Ok(Box::new(self.block_body()?)),
+            NodeTag::UnsafeBlock => self.unsafe_block(),
+            NodeTag::If => Ok(Box::new(self.if_body()?)),
+            NodeTag::InfLoop => self.inf_loop(),
+            NodeTag::While => self.while_loop(),
+            NodeTag::DoWhile => self.do_while_loop(),
+            NodeTag::For => self.for_loop(),
+            NodeTag::Return => self.return_expr(),
+            NodeTag::FunCall => self.fun_call(),
+            _ => self.invalid("an expression", offset),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        /// The value of a literal, which is either a constant or a string.
+        enum Value {
+            Const(ConstValue),
+            Str(Rc<str>),
+        }
+
+        let offset = self.offset;
+        let value = match self.u8()? {
+            0 => Value::Const(ConstValue::Int(i128::from_le_bytes(self.array()?))),
+            1 => Value::Const(ConstValue::Float(f64::from_le_bytes(self.array()?))),
+            2 => Value::Const(ConstValue::Bool(self.bool()?)),
+            3 => Value::Const(ConstValue::Unit),
+            4 => Value::Str(self.str()?),
+            _ => return self.invalid("a literal", offset),
+        };
+        let text = self.optional(Self::str)?;
+        let suffix = self.optional(Self::type_name)?;
+        let span = self.span()?;
+
+        /// Sets the text and the suffix of the decoded literal, if they were encoded.
+        fn boxed<T>(
+            literal: LiteralASTNode<T>,
+            text: Option<Rc<str>>,
+            suffix: Option<Type>,
+        ) -> Box<dyn ExprASTNode>
+        where
+            LiteralASTNode<T>: ExprASTNode + 'static,
+        {
+            let literal = match text {
+                Some(text) => literal.with_text(text),
+                None => literal,
+            };
+            match suffix {
+                Some(ty) => Box::new(literal.with_suffix(ty)),
+                None => Box::new(literal),
+            }
+        }
+        Ok(match value {
+            Value::Const(ConstValue::Int(i)) => {
+                boxed(LiteralASTNode::<i128>::new(i, span), text, suffix)
+            }
+            Value::Const(ConstValue::Float(x)) => {
+                boxed(LiteralASTNode::<f64>::new(x, span), text, suffix)
+            }
+            Value::Const(ConstValue::Bool(b)) => {
+                boxed(LiteralASTNode::<bool>::new(b, span), text, suffix)
+            }
+            Value::Const(ConstValue::Unit) => boxed(LiteralASTNode::<()>::new(span), text, suffix),
+            Value::Str(s) => boxed(LiteralASTNode::<Rc<str>>::new(s, span), text, suffix),
+        })
+    }
+
+    fn grouped(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let expr = self.expr()?;
+        Ok(Box::new(GroupedExprASTNode::new(expr, self.span()?)))
+    }
+
+    fn tuple(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let elements = self.list(Self::expr)?;
+        Ok(Box::new(TupleASTNode::new(elements, self.span()?)))
+    }
+
+    fn tuple_index(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let tuple = self.expr()?;
+        let offset = self.offset;
+        let Ok(index) = u32::try_from(self.usize()?) else {
+            return self.invalid("a tuple index", offset);
+        };
+        Ok(Box::new(TupleIndexASTNode::new(tuple, index, self.span()?)))
+    }
+
+    fn borrow(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let mutable = self.bool()?;
+        let expr = self.expr()?;
+        Ok(Box::new(BorrowExprASTNode::new(
+            mutable,
+            expr,
+            self.span()?,
+        )))
+    }
+
+    fn deref(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let expr = self.expr()?;
+        Ok(Box::new(DerefExprASTNode::new(expr, self.span()?)))
+    }
+
+    fn neg(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        use NegOperator::*;
+
+        let operator = self.operator(&[Neg, Not])?;
+        let expr = self.expr()?;
+        Ok(Box::new(NegExprASTNode::new(operator, expr, self.span()?)))
+    }
+
+    fn arith(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        use ArithOperator::*;
+
+        let operator = self.operator(&[Add, Sub, Mul, Div, Rem, BitAnd, BitOr, BitXor])?;
+        let (lhs, rhs) = (self.expr()?, self.expr()?);
+        let span = self.span()?;
+        Ok(Box::new(ArithExprASTNode::new(operator, lhs, rhs, span)))
+    }
+
+    fn comp(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        use CompOperator::*;
+
+        let operator = self.operator(&[Eq, Ne, Gt, Lt, Ge, Le])?;
+        let (lhs, rhs) = (self.expr()?, self.expr()?);
+        let span = self.span()?;
+        Ok(Box::new(CompExprASTNode::new(operator, lhs, rhs, span)))
+    }
+
+    fn lazy_bool(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        use LazyBoolOperator::*;
+
+        let operator = self.operator(&[And, Or])?;
+        let (lhs, rhs) = (self.expr()?, self.expr()?);
+        let span = self.span()?;
+        Ok(Box::new(LazyBoolExprASTNode::new(operator, lhs, rhs, span)))
+    }
+
+    fn cast(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let value = self.expr()?;
+        let ty = self.ty()?;
+        Ok(Box::new(TypeCastASTNode::new(value, ty, self.span()?)))
+    }
+
+    fn layout_of(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        use LayoutQuery::*;
+
+        let query = self.operator(&[SizeOf, AlignOf])?;
+        let ty = self.ty()?;
+        Ok(Box::new(LayoutOfASTNode::new(query, ty, self.span()?)))
+    }
+
+    fn assign(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let (assignee, value) = (self.expr()?, self.expr()?);
+        Ok(Box::new(AssignASTNode::new(assignee, value, self.span()?)))
+    }
+
+    fn unsafe_block(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let block = Box::new(self.block()?);
+        Ok(Box::new(UnsafeBlockASTNode::new(block, self.span()?)))
+    }
+
+    fn inf_loop(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let block = Box::new(self.block()?);
+        Ok(Box::new(InfLoopASTNode::new(block, self.span()?)))
+    }
+
+    fn while_loop(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let condition = self.expr()?;
+        let body = Box::new(self.block()?);
+        Ok(Box::new(WhileASTNode::new(condition, body, self.span()?)))
+    }
+
+    fn do_while_loop(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let body = Box::new(self.block()?);
+        let condition = self.expr()?;
+        Ok(Box::new(DoWhileASTNode::new(body, condition, self.span()?)))
+    }
+
+    fn for_loop(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        let (pattern, start, end) = (self.expr()?, self.expr()?, self.expr()?);
+        let inclusive = self.bool()?;
+        let body = Box::new(self.block()?);
+        let span = self.span()?;
+        Ok(Box::new(ForASTNode::new(
+            pattern, start, end, inclusive, body, span,
+        )))
+    }
+
+    fn return_expr(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        Ok(match self.optional(Self::expr)? {
+            Some(value) => Box::new(ReturnASTNode::new(value, self.span()?)),
+            None => Box::new(ReturnASTNode::empty(self.span()?)),
+        })
+    }
+
+    fn fun_call(&mut self) -> Result<Box<dyn ExprASTNode>> {
+        self.expect(NodeTag::Path, "a path")?;
+        let path = Box::new(self.path()?);
+        let args = self.list(Self::expr)?;
+        Ok(Box::new(FunCallASTNode::new(path, args, self.span()?)))
+    }
+
+    fn path(&mut self) -> Result<PathASTNode> {
+        let path = self.str()?;
+        Ok(PathASTNode::new(path, self.span()?))
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+    //#endregion
+}
+
+impl Crate {
+    /// Serializes this crate into the binary format (see the [module](crate::serialize) docs).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut encoder = Encoder::new();
+        encoder.write_node(self.root());
+        encoder.finish()
+    }
+
+    /// Deserializes a crate from the binary format (see the [module](crate::serialize) docs).
+    ///
+    /// # Errors
+    ///
+    /// If the data is corrupted or was encoded in a different version of the format,
+    /// the corresponding [`DecodeError`] is returned.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Crate> {
+        Decoder::new(bytes)?.decode_crate()
+    }
+}
+
+/// Returns the 64-bit FNV-1a hash of the given bytes.
+pub(crate) fn checksum(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let crt = parse(
+            "use other::f;\n\
+            extern \"C\" { fn print(n: i32); static mut X: i32; }\n\
+            extern \"system\" fn flush();\n\
+            pub extern \"C\" fn callback(n: i32) -> i32 { return n; }\n\
+            pub static Y: f64 = -0.5 * 2f64;\n\
+            static Z: bool = !(1 < 2) || true && 3 != 4;\n\
+            #[unroll(2)]\n\
+            fn g(mut a: i32, _: ()) -> i32 {\n\
+                let mut n: i32 = (a + 1) % 3 ^ 7;\n\
+                let u: ();\n\
+                while n >= 0 { n = n - 1; };\n\
+                do { n = n + 1; } while n < 5;\n\
+                let b: u8 = 7u8 + 250;\n\
+                for i in 0..n { a = a + i; }\n\
+                for _ in 1..=2u8 {}\n\
+                let s: &str = \"\\\"\u{1F980}\\\"\\n\";\n\
+                let z: u8 = size_of::<i64>() * align_of::<&str>();\n\
+                let t: ((i32, bool), (u8,)) = ((n, true), (z,));\n\
+                let w: i32 = (t.0.0 + t.0.0, ()).0;\n\
+                let r: &mut (i32, &&u8) = &mut (w, &&z);\n\
+                *r = (-*&w, &&*&7);\n\
+                if n == 1 { return 1; } else if n <= 2 { loop { return 2; }; } else { n = 0; };\n\
+                unsafe { print(n as f64 as i32); };\n\
+                { 1i32 }\n\
+            }\n",
+        );
+
+        let bytes = crt.to_bytes();
+        let decoded = Crate::from_bytes(&bytes).unwrap();
+        // The tree representation contains the spans of all the nodes
+        assert_eq!(decoded.to_string(), crt.to_string());
+        assert_eq!(decoded.to_source(), crt.to_source());
+        assert_eq!(decoded.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_corrupted_data() {
+        let bytes = parse("fn main() -> i32 { return 1 + 2; }\n").to_bytes();
+
+        assert_eq!(
+            Crate::from_bytes(b"not an AST").unwrap_err(),
+            DecodeError::InvalidHeader
+        );
+
+        let mut outdated = bytes.clone();
+        outdated[4] = outdated[4].wrapping_add(1);
+        assert_eq!(
+            Crate::from_bytes(&outdated).unwrap_err(),
+            DecodeError::UnsupportedVersion {
+                version: FORMAT_VERSION + 1
+            }
+        );
+
+        let mut flipped = bytes.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            Crate::from_bytes(&flipped).unwrap_err(),
+            DecodeError::ChecksumMismatch
+        );
+        assert_eq!(
+            Crate::from_bytes(&bytes[..bytes.len() - 1]).unwrap_err(),
+            DecodeError::ChecksumMismatch
+        );
+
+        // A payload with a valid checksum can still be malformed
+        let mut encoder = Encoder::new();
+        encoder.write_tag(NodeTag::Crate);
+        encoder.write_str("main.mrs");
+        encoder.write_usize(0);
+        encoder.write_usize(1);
+        encoder.write_u8(u8::MAX);
+        assert!(matches!(
+            Crate::from_bytes(&encoder.finish()),
+            Err(DecodeError::InvalidValue {
+                expected: "a node tag",
+                ..
+            })
+        ));
+
+        let mut encoder = Encoder::new();
+        encoder.write_tag(NodeTag::Crate);
+        encoder.write_str("main.mrs");
+        encoder.write_usize(0);
+        encoder.write_usize(usize::MAX);
+        assert_eq!(
+            Crate::from_bytes(&encoder.finish()).unwrap_err(),
+            DecodeError::UnexpectedEnd
+        );
+
+        let mut encoder = Encoder::new();
+        encoder.write_tag(NodeTag::Crate);
+        encoder.write_str("main.mrs");
+        encoder.write_usize(0);
+        encoder.write_usize(1);
+        encoder.write_tag(NodeTag::Static);
+        encoder.write_str("X");
+        encoder.write_span(Span::new(Position::new(), Position::new()));
+        encoder.write_bool(true);
+        for _ in 0..=MAX_DEPTH {
+            encoder.write_tag(NodeTag::Grouped);
+        }
+        assert!(matches!(
+            Crate::from_bytes(&encoder.finish()),
+            Err(DecodeError::TooDeep { .. })
+        ));
+    }
+}
@@ -0,0 +1,257 @@
+//! A module containing the evaluation of the static initializers in their dependency order.
+//!
+//! The initializer of a static can refer to other statics of the crate, so the statics it
+//! depends on have to be evaluated first. The crate is rejected if the dependencies
+//! form a cycle (e.g. `static A: i32 = B; static B: i32 = A;`).
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::error::SemanticError;
+use crate::ast::{ConstEnv, StaticASTNode};
+use crate::resolve::Resolver;
+use crate::target::TargetLayout;
+use crate::token::Span;
+
+/// Returns the names (and the spans of the references) of the statics
+/// which the initializer of the given static refers to.
+///
+/// The references are found by the name resolution of the initializer on its own,
+/// so the locals declared inside it are not included.
+fn dependencies(stat: &StaticASTNode) -> Vec<(Rc<str>, Span)> {
+    let Some(value) = stat.value() else {
+        return Vec::new();
+    };
+    let mut resolver = Resolver::new();
+    value.resolve_names(&mut resolver);
+    resolver.finish().unresolved().to_vec()
+}
+
+/// The state of a static during the depth-first search of the dependencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+/// Returns the given statics sorted so that every static comes after the statics
+/// its initializer refers to, keeping the source order of the independent ones.
+///
+/// # Errors
+///
+/// If the initializers refer to each other in a cycle, [`SemanticError::CyclicStaticInitializers`]
+/// listing the statics of the first found cycle is returned.
+pub fn initialization_order<'a>(
+    statics: &[&'a StaticASTNode],
+) -> Result<Vec<&'a StaticASTNode>, SemanticError> {
+    let indices: HashMap<&str, usize> = statics
+        .iter()
+        .enumerate()
+        .map(|(i, stat)| (stat.name(), i))
+        .collect();
+    let graph: Vec<Vec<(usize, Span)>> = statics
+        .iter()
+        .map(|stat| {
+            dependencies(stat)
+                .into_iter()
+                .filter_map(|(name, span)| indices.get(name.as_ref()).map(|i| (*i, span)))
+                .collect()
+        })
+        .collect();
+
+    let mut visits = vec![None; statics.len()];
+    let mut order = Vec::with_capacity(statics.len());
+    for root in 0..statics.len() {
+        if visits[root].is_some() {
+            continue;
+        }
+        // The path from the root to the current static, with the index of its next dependency
+        let mut path = vec![(root, 0)];
+        visits[root] = Some(Visit::InProgress);
+        while let Some((current, next)) = path.last_mut() {
+            let current = *current;
+            let Some((dependency, span)) = graph[current].get(*next).copied() else {
+                visits[current] = Some(Visit::Done);
+                order.push(statics[current]);
+                path.pop();
+                continue;
+            };
+            *next += 1;
+            match visits[dependency] {
+                None => {
+                    visits[dependency] = Some(Visit::InProgress);
+                    path.push((dependency, 0));
+                }
+                Some(Visit::InProgress) => {
+                    let start = path.iter().position(|(i, _)| *i == dependency).unwrap_or(0);
+                    let cycle = path[start..]
+                        .iter()
+                        .map(|(i, _)| statics[*i].name())
+                        .chain([statics[dependency].name()])
+                        .map(Box::from)
+                        .collect();
+                    return Err(SemanticError::CyclicStaticInitializers { cycle, span });
+                }
+                Some(Visit::Done) => {}
+            }
+        }
+    }
+    Ok(order)
+}
+
+/// Evaluates the initializers of the given statics in their [initialization order]
+/// for a target machine with the given layout, returning the values of the immutable ones.
+///
+/// The initializers which cannot be evaluated at compile time are skipped (they are reported
+/// when the static is generated), as well as the mutable statics, whose values cannot be
+/// read by the other initializers.
+///
+/// [initialization order]: initialization_order
+pub fn evaluate_statics(
+    statics: &[&StaticASTNode],
+    layout: TargetLayout,
+) -> Result<ConstEnv, SemanticError> {
+    let mut env = ConstEnv::with_layout(layout);
+    for stat in initialization_order(statics)? {
+        if stat.is_mutable() {
+            continue;
+        }
+        let value = stat.value().and_then(|value| value.const_eval_in(&env));
+        if let Some(value) = value {
+            if value.is_of_type(&stat.ty().ty()) {
+                env.insert(stat.name_owned(), value);
+            }
+        }
+    }
+    Ok(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{ConstValue, Type};
+    use crate::test_util::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_initialization_order() {
+        let crt = parse(
+            "static A: i32 = B * C;\n\
+            static B: i32 = C + 1;\n\
+            static C: i32 = { 2 };\n\
+            static mut D: i32 = A;\n\
+            pub static E: bool = A > (B + C);\n\
+            pub fn main() -> i32 { return 0; }\n",
+        );
+        let statics: Vec<_> = crt.statics().collect();
+        let order: Vec<_> = initialization_order(&statics)
+            .unwrap()
+            .into_iter()
+            .map(StaticASTNode::name)
+            .collect();
+        assert_eq!(order, ["C", "B", "A", "D", "E"]);
+
+        let env = evaluate_statics(&statics, TargetLayout::host()).unwrap();
+        assert_eq!(env.get("A"), Some(ConstValue::Int(6)));
+        assert_eq!(env.get("E"), Some(ConstValue::Bool(true)));
+        // The mutable statics cannot be read by the initializers
+        assert_eq!(env.get("D"), None);
+
+        #[cfg(feature = "codegen")]
+        {
+            use inkwell::context::Context;
+            use inkwell::values::AnyValue;
+
+            let context = Context::create();
+            let module = crt.code_gen(&context).unwrap();
+            let initializer = |name: &str| {
+                let global = module.get_global(name).unwrap();
+                global
+                    .get_initializer()
+                    .unwrap()
+                    .print_to_string()
+                    .to_string()
+            };
+            assert_eq!(initializer("A"), "i32 6");
+            assert_eq!(initializer("D"), "i32 6");
+            assert_eq!(initializer("E"), "i1 true");
+        }
+    }
+
+    #[test]
+    fn test_cyclic_initializers() {
+        let crt = parse(
+            "static A: i32 = 1;\n\
+            static B: i32 = C + A;\n\
+            static C: i32 = D;\n\
+            static D: i32 = B * 2;\n",
+        );
+        let statics: Vec<_> = crt.statics().collect();
+        let err = initialization_order(&statics).unwrap_err();
+        let SemanticError::CyclicStaticInitializers { cycle, span } = &err else {
+            panic!("Unexpected error: {err}");
+        };
+        assert_eq!(cycle.as_ref(), ["B", "C", "D", "B"].map(Box::from));
+        assert_eq!(span.start().line(), 4);
+        assert!(err.to_string().contains("`B` -> `C` -> `D` -> `B`"));
+
+        #[cfg(feature = "codegen")]
+        {
+            use inkwell::context::Context;
+
+            use crate::codegen::error::CodeGenError;
+
+            let context = Context::create();
+            let result = crt.code_gen(&context);
+            assert!(matches!(
+                result,
+                Err(CodeGenError::SemanticError(
+                    SemanticError::CyclicStaticInitializers { .. }
+                ))
+            ));
+        }
+
+        let crt = parse("static A: i32 = A;\n");
+        let statics: Vec<_> = crt.statics().collect();
+        let err = initialization_order(&statics).unwrap_err();
+        assert!(err.to_string().contains("`A` -> `A`"));
+    }
+
+    #[test]
+    fn test_target_layout() {
+        let crt = parse(
+            "static P: u32 = size_of::<&str>() + W;\n\
+            static W: u32 = align_of::<i64>();\n",
+        );
+        let statics: Vec<_> = crt.statics().collect();
+        // i386 Linux
+        let i386 = "e-m:e-p:32:32-p270:32:32-p271:32:32-p272:64:64-f64:32:64-f80:32-n8:16:32-S128";
+        let layout = TargetLayout::from_data_layout(i386).unwrap();
+        let env = evaluate_statics(&statics, layout).unwrap();
+        assert_eq!(env.layout(), layout);
+        assert_eq!(env.get("W"), Some(ConstValue::Int(4)));
+        assert_eq!(env.get("P"), Some(ConstValue::Int(8)));
+
+        let host = TargetLayout::host();
+        let env = evaluate_statics(&statics, host).unwrap();
+        let expected = host.size_of(&Type::Str) + host.align_of(&Type::I64);
+        assert_eq!(env.get("P"), Some(ConstValue::Int(expected as i128)));
+
+        #[cfg(feature = "codegen")]
+        {
+            use inkwell::context::Context;
+            use inkwell::values::AnyValue;
+
+            use crate::codegen::{CodeGenOptions, CodeGenRequest};
+
+            let context = Context::create();
+            let options = CodeGenOptions::new().with_target_layout(layout);
+            let (module, _) = crt
+                .code_gen_with(&context, CodeGenRequest::new().with_options(options))
+                .unwrap();
+            let global = module.get_global("P").unwrap();
+            let initializer = global.get_initializer().unwrap().print_to_string();
+            assert_eq!(initializer.to_string(), "i32 8");
+        }
+    }
+}
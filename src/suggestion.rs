@@ -0,0 +1,119 @@
+//! A module containing the suggestions (fix-its) attached to the diagnostics.
+//!
+//! A [`Suggestion`] is machine-applicable: replacing the source code in its span with
+//! its replacement fixes the reported problem, so editors can offer it as a quick fix
+//! (see [`Diagnostic::to_json`](crate::observer::Diagnostic::to_json)).
+
+use std::fmt;
+
+use crate::json;
+use crate::token::{Position, Span};
+
+/// A replacement of a part of the source code that fixes a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    message: &'static str,
+    span: Span,
+    replacement: Box<str>,
+}
+
+impl Suggestion {
+    /// Creates a new `Suggestion` with the given message, which replaces
+    /// the source code in the given span with the replacement.
+    pub fn replace(
+        message: &'static str,
+        span: Span,
+        replacement: impl Into<Box<str>>,
+    ) -> Suggestion {
+        Suggestion {
+            message,
+            span,
+            replacement: replacement.into(),
+        }
+    }
+
+    /// Creates a new `Suggestion` with the given message, which inserts the text
+    /// at the given position (i.e. replaces an empty span).
+    pub fn insert(message: &'static str, pos: Position, text: impl Into<Box<str>>) -> Suggestion {
+        Suggestion::replace(message, Span::new(pos, pos), text)
+    }
+
+    /// Returns the message describing the suggestion (e.g. `add a semicolon`).
+    pub fn message(&self) -> &'static str {
+        self.message
+    }
+
+    /// Returns the span of the replaced source code (empty for an insertion).
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the replacement of the source code in the span.
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
+    /// Returns whether the suggestion only inserts text, i.e. its span is empty.
+    pub fn is_insertion(&self) -> bool {
+        self.span.start() == self.span.end()
+    }
+
+    /// Formats the suggestion as a JSON object with the fields `message`, `span`
+    /// and `replacement`.
+    pub fn to_json(&self) -> String {
+        format!(
+            r#"{{"message":{},"span":{},"replacement":{}}}"#,
+            json::string(self.message),
+            json::span(self.span),
+            json::string(&self.replacement)
+        )
+    }
+}
+
+impl fmt::Display for Suggestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_insertion() {
+            write!(
+                f,
+                "{}: insert `{}` at {}",
+                self.message,
+                self.replacement,
+                self.span.start()
+            )
+        } else {
+            write!(
+                f,
+                "{}: replace {} with `{}`",
+                self.message, self.span, self.replacement
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggestion() {
+        let pos = Position::new_at(2, 14);
+        let insertion = Suggestion::insert("add a semicolon", pos, ";");
+        assert!(insertion.is_insertion());
+        assert_eq!(insertion.to_string(), "add a semicolon: insert `;` at 2:14");
+        assert_eq!(
+            insertion.to_json(),
+            r#"{"message":"add a semicolon","span":{"start":{"line":2,"column":14},"end":{"line":2,"column":14}},"replacement":";"}"#
+        );
+
+        let span = Span::new(Position::new_at(1, 4), Position::new_at(1, 9));
+        let replacement = Suggestion::replace("compare for equality", span, "x == 1");
+        assert!(!replacement.is_insertion());
+        assert_eq!(
+            replacement.to_string(),
+            "compare for equality: replace <1:4>-<1:9> with `x == 1`"
+        );
+        assert!(replacement
+            .to_json()
+            .ends_with(r#""replacement":"x == 1"}"#));
+    }
+}
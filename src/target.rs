@@ -0,0 +1,241 @@
+//! A module containing the description of the target machine which the semantic analysis
+//! depends on, i.e. the sizes and the alignments of the types.
+//!
+//! The layout is used by the [const evaluator](crate::ast::ConstEnv) to resolve
+//! the [`size_of`](crate::ast::LayoutOfASTNode) expressions at compile time,
+//! so that their values match the code generated for the target.
+
+use crate::ast::Type;
+
+/// The sizes and the ABI alignments (in bytes) of the types on a target machine.
+///
+/// It can be read from an [LLVM data layout string](TargetLayout::from_data_layout),
+/// e.g. the one of the target machine the code is generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TargetLayout {
+    /// The size of a pointer.
+    pointer_size: u32,
+    /// The alignment of a pointer.
+    pointer_align: u32,
+    /// The alignments of the 8, 16, 32 and 64-bit integers.
+    int_align: [u32; 4],
+    /// The alignments of the 32 and 64-bit floats.
+    float_align: [u32; 2],
+}
+
+impl TargetLayout {
+    /// Returns the layout of the machine the compiler is running on.
+    pub fn host() -> TargetLayout {
+        use std::mem::align_of;
+
+        TargetLayout {
+            pointer_size: usize::BITS / 8,
+            pointer_align: align_of::<*const u8>() as u32,
+            int_align: [
+                align_of::<i8>() as u32,
+                align_of::<i16>() as u32,
+                align_of::<i32>() as u32,
+                align_of::<i64>() as u32,
+            ],
+            float_align: [align_of::<f32>() as u32, align_of::<f64>() as u32],
+        }
+    }
+
+    /// Parses the given LLVM data layout string (e.g. `e-m:e-p:32:32-i64:64-n32-S128`),
+    /// returning `None` if it is malformed.
+    ///
+    /// Only the specifications of the pointers in the default address space
+    /// and of the supported integer and float types are read. The other types
+    /// have LLVM's default layout (e.g. 64-bit pointers and `i64` aligned to 4 bytes).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::ast::Type;
+    /// # use mini_rust_compiler_components::target::TargetLayout;
+    ///
+    /// let layout = TargetLayout::from_data_layout("e-m:e-p:32:32-Fi8-i64:64-v128:64:128-n32-S64")
+    ///     .unwrap();
+    /// assert_eq!(layout.size_of(&Type::Str), 4);
+    /// assert_eq!(layout.align_of(&Type::I64), 8);
+    /// assert_eq!(TargetLayout::from_data_layout("p:sixty-four"), None);
+    /// ```
+    pub fn from_data_layout(data_layout: &str) -> Option<TargetLayout> {
+        let mut layout = TargetLayout {
+            pointer_size: 8,
+            pointer_align: 8,
+            int_align: [1, 2, 4, 4],
+            float_align: [4, 8],
+        };
+        let bytes = |bits: &str| -> Option<u32> {
+            let bits: u32 = bits.parse().ok()?;
+            let bytes = bits / 8;
+            (bytes > 0 && bytes * 8 == bits).then_some(bytes)
+        };
+        let align = |bits: &str| bytes(bits).filter(|bytes| bytes.is_power_of_two());
+
+        for spec in data_layout.split('-').filter(|spec| !spec.is_empty()) {
+            let mut fields = spec.split(':');
+            let name = fields.next()?;
+            let Some(kind) = name.chars().next() else {
+                continue;
+            };
+            let (kind, width) = name.split_at(kind.len_utf8());
+            match kind {
+                // The pointers of the other address spaces are not used
+                "p" if width.is_empty() || width == "0" => {
+                    layout.pointer_size = bytes(fields.next()?)?;
+                    layout.pointer_align = align(fields.next()?)?;
+                }
+                "i" | "f" => {
+                    let abi_align = align(fields.next()?)?;
+                    let slot = match (kind, width) {
+                        ("i", "8") => &mut layout.int_align[0],
+                        ("i", "16") => &mut layout.int_align[1],
+                        ("i", "32") => &mut layout.int_align[2],
+                        ("i", "64") => &mut layout.int_align[3],
+                        ("f", "32") => &mut layout.float_align[0],
+                        ("f", "64") => &mut layout.float_align[1],
+                        _ => continue,
+                    };
+                    *slot = abi_align;
+                }
+                _ => {}
+            }
+        }
+        Some(layout)
+    }
+
+    /// Returns the number of bytes occupied by a value of the given type
+    /// (including the padding up to its alignment, like [`std::mem::size_of`]).
+    ///
+    /// The elements of a tuple are laid out in order, each at the first offset
+    /// aligned for its type (like the fields of an LLVM struct or a C struct).
+    pub fn size_of(&self, ty: &Type) -> u32 {
+        let size = match ty {
+            Type::Unit => return 0,
+            Type::Bool => 1,
+            Type::Str | Type::Ref { .. } => self.pointer_size,
+            Type::Tuple(elements) => elements.iter().fold(0, |offset, element| {
+                align_to(offset, self.align_of(element)) + self.size_of(element)
+            }),
+            ty => ty.bit_width().expect("Numeric types have a width") / 8,
+        };
+        align_to(size, self.align_of(ty))
+    }
+
+    /// Returns the ABI alignment (in bytes) of the values of the given type
+    /// (like [`std::mem::align_of`]).
+    pub fn align_of(&self, ty: &Type) -> u32 {
+        match ty {
+            Type::Unit => 1,
+            Type::Bool | Type::I8 | Type::U8 => self.int_align[0],
+            Type::I16 | Type::U16 => self.int_align[1],
+            Type::I32 | Type::U32 => self.int_align[2],
+            Type::I64 | Type::U64 => self.int_align[3],
+            Type::F32 => self.float_align[0],
+            Type::F64 => self.float_align[1],
+            Type::Str | Type::Ref { .. } => self.pointer_align,
+            Type::Tuple(elements) => elements
+                .iter()
+                .map(|element| self.align_of(element))
+                .max()
+                .unwrap_or(1),
+        }
+    }
+
+    /// Returns the width of the pointers in bits.
+    pub fn pointer_width(&self) -> u32 {
+        self.pointer_size * 8
+    }
+}
+
+/// Rounds the given offset up to a multiple of the given alignment (a power of two).
+fn align_to(offset: u32, align: u32) -> u32 {
+    (offset + align - 1) & !(align - 1)
+}
+
+impl Default for TargetLayout {
+    fn default() -> Self {
+        Self::host()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_layout() {
+        let layout = TargetLayout::host();
+        assert_eq!(
+            layout.size_of(&Type::I64),
+            std::mem::size_of::<i64>() as u32
+        );
+        assert_eq!(
+            layout.align_of(&Type::F64),
+            std::mem::align_of::<f64>() as u32
+        );
+        assert_eq!(
+            layout.size_of(&Type::Str),
+            std::mem::size_of::<&u8>() as u32
+        );
+        assert_eq!(layout.size_of(&Type::Bool), 1);
+        assert_eq!(layout.size_of(&Type::Unit), 0);
+        assert_eq!(layout.align_of(&Type::Unit), 1);
+
+        // Unlike Rust, the elements of the tuples are not reordered
+        #[repr(C)]
+        struct Tuple(u8, i64, bool);
+        #[repr(C)]
+        struct Nested(i16, Tuple, ());
+
+        let tuple = Type::tuple(vec![Type::U8, Type::I64, Type::Bool]);
+        assert_eq!(layout.size_of(&tuple), std::mem::size_of::<Tuple>() as u32);
+        assert_eq!(
+            layout.align_of(&tuple),
+            std::mem::align_of::<Tuple>() as u32
+        );
+        let nested = Type::tuple(vec![Type::I16, tuple, Type::Unit]);
+        assert_eq!(
+            layout.size_of(&nested),
+            std::mem::size_of::<Nested>() as u32
+        );
+        assert_eq!(TargetLayout::default(), layout);
+    }
+
+    #[test]
+    fn test_data_layout() {
+        // x86_64 Linux
+        let x86_64 =
+            "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-i128:128-f80:128-n8:16:32:64-S128";
+        let layout = TargetLayout::from_data_layout(x86_64).unwrap();
+        assert_eq!(layout.pointer_width(), 64);
+        assert_eq!(layout.size_of(&Type::U64), 8);
+        assert_eq!(layout.align_of(&Type::I64), 8);
+
+        // i386 Linux, where `i64` and `f64` are aligned to 4 bytes
+        let i386 = "e-m:e-p:32:32-p270:32:32-p271:32:32-p272:64:64-f64:32:64-f80:32-n8:16:32-S128";
+        let layout = TargetLayout::from_data_layout(i386).unwrap();
+        assert_eq!(layout.size_of(&Type::Str), 4);
+        assert_eq!(layout.align_of(&Type::Str), 4);
+        assert_eq!(layout.size_of(&Type::reference(Type::F64, true)), 4);
+        assert_eq!(layout.align_of(&Type::I64), 4);
+        assert_eq!(layout.align_of(&Type::F64), 4);
+        assert_eq!(layout.size_of(&Type::F64), 8);
+
+        // The empty string is LLVM's default layout
+        let layout = TargetLayout::from_data_layout("").unwrap();
+        assert_eq!(layout.size_of(&Type::Str), 8);
+        assert_eq!(layout.align_of(&Type::I64), 4);
+
+        // The `i64` is only padded to the alignment of 4 bytes
+        let tuple = Type::tuple(vec![Type::U8, Type::I64, Type::Bool]);
+        assert_eq!(layout.size_of(&tuple), 16);
+        assert_eq!(layout.align_of(&tuple), 4);
+
+        assert_eq!(TargetLayout::from_data_layout("p:64"), None);
+        assert_eq!(TargetLayout::from_data_layout("i32:3"), None);
+        assert_eq!(TargetLayout::from_data_layout("i64:48"), None);
+    }
+}
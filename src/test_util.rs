@@ -0,0 +1,72 @@
+//! Helpers shared by the tests of the crate.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::ast::Crate;
+use crate::parser::{self, Parser};
+
+/// A temporary directory with a path unique to the test using it (the tests run in parallel,
+/// so they would overwrite each other's files otherwise), which is removed when dropped.
+pub(crate) struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Creates a new empty `TempDir`, with the given name as a part of its path.
+    pub(crate) fn new(name: &str) -> TempDir {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "mrs_test_{}_{}_{}",
+            name,
+            process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    /// Returns the path of the directory.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path of the file with the given name inside the directory.
+    pub(crate) fn join(&self, file_name: &str) -> PathBuf {
+        self.path.join(file_name)
+    }
+
+    /// Writes the given contents to the file with the given name inside the directory
+    /// and returns its path.
+    pub(crate) fn write(&self, file_name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.join(file_name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// Parses the given source code as the file `main.mrs`.
+pub(crate) fn try_parse(source: &str) -> parser::Result<Crate> {
+    parse_file("main.mrs", source)
+}
+
+/// Parses the given source code as the file `main.mrs`, panicking on errors.
+pub(crate) fn parse(source: &str) -> Crate {
+    try_parse(source).unwrap()
+}
+
+/// Parses the given source code as a file with the given name.
+pub(crate) fn parse_file(file_name: &str, source: &str) -> parser::Result<Crate> {
+    let dir = TempDir::new("parse");
+    let path = dir.write(file_name, source);
+    Parser::new(path).unwrap().parse()
+}
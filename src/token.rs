@@ -17,8 +17,6 @@ pub enum TokenType {
     Ident(Rc<str>),
     /// `_`
     Underscore,
-    /// Only `"C"` ABI is valid at this point
-    Abi(Rc<str>),
     //#endregion
 
     //#region Keywords
@@ -38,6 +36,12 @@ pub enum TokenType {
     Loop,
     /// `while`
     While,
+    /// `do`
+    Do,
+    /// `for`
+    For,
+    /// `in`
+    In,
     /// `if`
     If,
     /// `else`
@@ -46,6 +50,25 @@ pub enum TokenType {
     Unsafe,
     /// `return`
     Return,
+    /// `pub`
+    Pub,
+    /// `use`
+    Use,
+    //#endregion
+
+    //#region Reserved keywords
+    /// `struct` (reserved for future use)
+    Struct,
+    /// `enum` (reserved for future use)
+    Enum,
+    /// `match` (reserved for future use)
+    Match,
+    /// `const` (reserved for future use)
+    Const,
+    /// `break` (reserved for future use)
+    Break,
+    /// `continue` (reserved for future use)
+    Continue,
     //#endregion
 
     //#region Delimiters
@@ -65,15 +88,29 @@ pub enum TokenType {
     Colon,
     /// `->`
     Arrow,
+    /// `::`
+    PathSep,
+    /// `#`
+    Pound,
+    /// `[`
+    LSqu,
+    /// `]`
+    RSqu,
+    /// `.`
+    Dot,
     //#endregion
 
     //#region Literals
-    /// Matches `[0-9]+`
-    IntLit(i32),
-    /// Matches `[0-9]+.[0-9]+`
-    FloatLit(f64),
+    /// Matches `[0-9]+`, optionally followed by a [numeric suffix](NumericSuffix)
+    IntLit(i128, Option<NumericSuffix>),
+    /// Matches `[0-9]+.[0-9]+` or `[0-9]+(.[0-9]+)?(f32|f64)`, together with its text
+    /// in the source code (without the suffix) and its suffix
+    FloatLit(f64, Rc<str>, Option<NumericSuffix>),
     /// Matches `true` or `false`
     BoolLit(bool),
+    /// Matches `"..."` (with escape sequences) or `r"..."` (a raw string, optionally with `#`s),
+    /// together with its processed value. It is also used as the ABI name of `extern` items
+    StrLit(Rc<str>),
     //#endregion
 
     //#region Operators
@@ -123,6 +160,13 @@ pub enum TokenType {
     Le,
     //#endregion
 
+    //#region Range operators
+    /// `..`
+    DotDot,
+    /// `..=`
+    DotDotEq,
+    //#endregion
+
     //#endregion
     
     /// Represents the end of the file
@@ -159,10 +203,21 @@ impl TokenType {
             "as" => TokenType::As,
             "loop" => TokenType::Loop,
             "while" => TokenType::While,
+            "do" => TokenType::Do,
+            "for" => TokenType::For,
+            "in" => TokenType::In,
             "if" => TokenType::If,
             "else" => TokenType::Else,
             "unsafe" => TokenType::Unsafe,
             "return" => TokenType::Return,
+            "struct" => TokenType::Struct,
+            "enum" => TokenType::Enum,
+            "match" => TokenType::Match,
+            "const" => TokenType::Const,
+            "pub" => TokenType::Pub,
+            "use" => TokenType::Use,
+            "break" => TokenType::Break,
+            "continue" => TokenType::Continue,
             "(" => TokenType::LPar,
             ")" => TokenType::RPar,
             "{" => TokenType::LBra,
@@ -171,6 +226,11 @@ impl TokenType {
             "," => TokenType::Comma,
             ":" => TokenType::Colon,
             "->" => TokenType::Arrow,
+            "::" => TokenType::PathSep,
+            "#" => TokenType::Pound,
+            "[" => TokenType::LSqu,
+            "]" => TokenType::RSqu,
+            "." => TokenType::Dot,
             "true" => TokenType::BoolLit(true),
             "false" => TokenType::BoolLit(false),
             "=" => TokenType::Assign,
@@ -191,9 +251,205 @@ impl TokenType {
             "<" => TokenType::Lt,
             ">=" => TokenType::Ge,
             "<=" => TokenType::Le,
+            ".." => TokenType::DotDot,
+            "..=" => TokenType::DotDotEq,
             _ => return None,
         })
     }
+
+    /// Returns the spelling of the keyword if this [TokenType] is a keyword
+    /// that is reserved for future use (i.e. it is not yet supported in μRust).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::token::TokenType;
+    ///
+    /// assert_eq!(TokenType::Struct.reserved_keyword(), Some("struct"));
+    /// assert_eq!(TokenType::Fn.reserved_keyword(), None);
+    /// ```
+    pub fn reserved_keyword(&self) -> Option<&'static str> {
+        Some(match self {
+            TokenType::Struct => "struct",
+            TokenType::Enum => "enum",
+            TokenType::Match => "match",
+            TokenType::Const => "const",
+            TokenType::Break => "break",
+            TokenType::Continue => "continue",
+            _ => return None,
+        })
+    }
+}
+
+/// The type suffix of a number literal, e.g. `i32` in `1i32`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NumericSuffix {
+    /// `i8`
+    I8,
+    /// `i16`
+    I16,
+    /// `i32`
+    I32,
+    /// `i64`
+    I64,
+    /// `u8`
+    U8,
+    /// `u16`
+    U16,
+    /// `u32`
+    U32,
+    /// `u64`
+    U64,
+    /// `f32`
+    F32,
+    /// `f64`
+    F64,
+}
+
+impl NumericSuffix {
+    /// Returns the suffix with the given spelling, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::token::NumericSuffix;
+    ///
+    /// assert_eq!(NumericSuffix::from_name("f64"), Some(NumericSuffix::F64));
+    /// assert_eq!(NumericSuffix::from_name("u8"), Some(NumericSuffix::U8));
+    /// assert_eq!(NumericSuffix::from_name("u128"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<NumericSuffix> {
+        match name {
+            "i8" => Some(NumericSuffix::I8),
+            "i16" => Some(NumericSuffix::I16),
+            "i32" => Some(NumericSuffix::I32),
+            "i64" => Some(NumericSuffix::I64),
+            "u8" => Some(NumericSuffix::U8),
+            "u16" => Some(NumericSuffix::U16),
+            "u32" => Some(NumericSuffix::U32),
+            "u64" => Some(NumericSuffix::U64),
+            "f32" => Some(NumericSuffix::F32),
+            "f64" => Some(NumericSuffix::F64),
+            _ => None,
+        }
+    }
+
+    /// Returns the spelling of the suffix.
+    pub fn name(self) -> &'static str {
+        match self {
+            NumericSuffix::I8 => "i8",
+            NumericSuffix::I16 => "i16",
+            NumericSuffix::I32 => "i32",
+            NumericSuffix::I64 => "i64",
+            NumericSuffix::U8 => "u8",
+            NumericSuffix::U16 => "u16",
+            NumericSuffix::U32 => "u32",
+            NumericSuffix::U64 => "u64",
+            NumericSuffix::F32 => "f32",
+            NumericSuffix::F64 => "f64",
+        }
+    }
+
+    /// Returns whether the suffix is of a floating-point type.
+    pub fn is_float(self) -> bool {
+        matches!(self, NumericSuffix::F32 | NumericSuffix::F64)
+    }
+}
+
+impl fmt::Display for NumericSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl fmt::Display for TokenType {
+    /// Formats the token type as it would be shown to the user in a diagnostic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::token::TokenType;
+    ///
+    /// assert_eq!(TokenType::LBra.to_string(), "'{'");
+    /// assert_eq!(TokenType::IntLit(5, None).to_string(), "integer literal '5'");
+    /// assert_eq!(TokenType::Ident("x".into()).to_string(), "identifier 'x'");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            TokenType::Ident(ident) => return write!(f, "identifier '{}'", ident),
+            TokenType::StrLit(value) => {
+                return write!(f, "string literal '\"{}\"'", value.escape_default())
+            }
+            TokenType::IntLit(val, suffix) => {
+                let suffix = suffix.map_or("", NumericSuffix::name);
+                return write!(f, "integer literal '{}{}'", val, suffix);
+            }
+            TokenType::FloatLit(_, text, suffix) => {
+                let suffix = suffix.map_or("", NumericSuffix::name);
+                return write!(f, "float literal '{}{}'", text, suffix);
+            }
+            TokenType::BoolLit(val) => return write!(f, "boolean literal '{}'", val),
+            TokenType::EOF => return write!(f, "end of file"),
+            TokenType::Underscore => "_",
+            TokenType::Fn => "fn",
+            TokenType::Static => "static",
+            TokenType::Extern => "extern",
+            TokenType::Let => "let",
+            TokenType::Mut => "mut",
+            TokenType::As => "as",
+            TokenType::Loop => "loop",
+            TokenType::While => "while",
+            TokenType::Do => "do",
+            TokenType::For => "for",
+            TokenType::In => "in",
+            TokenType::If => "if",
+            TokenType::Else => "else",
+            TokenType::Unsafe => "unsafe",
+            TokenType::Return => "return",
+            TokenType::Pub => "pub",
+            TokenType::Use => "use",
+            TokenType::Struct => "struct",
+            TokenType::Enum => "enum",
+            TokenType::Match => "match",
+            TokenType::Const => "const",
+            TokenType::Break => "break",
+            TokenType::Continue => "continue",
+            TokenType::LPar => "(",
+            TokenType::RPar => ")",
+            TokenType::LBra => "{",
+            TokenType::RBra => "}",
+            TokenType::Semi => ";",
+            TokenType::Comma => ",",
+            TokenType::Colon => ":",
+            TokenType::Arrow => "->",
+            TokenType::PathSep => "::",
+            TokenType::Pound => "#",
+            TokenType::LSqu => "[",
+            TokenType::RSqu => "]",
+            TokenType::Dot => ".",
+            TokenType::Assign => "=",
+            TokenType::Plus => "+",
+            TokenType::Minus => "-",
+            TokenType::Asterisk => "*",
+            TokenType::Div => "/",
+            TokenType::Mod => "%",
+            TokenType::BitAnd => "&",
+            TokenType::BitOr => "|",
+            TokenType::BitXor => "^",
+            TokenType::Not => "!",
+            TokenType::And => "&&",
+            TokenType::Or => "||",
+            TokenType::Eq => "==",
+            TokenType::Ne => "!=",
+            TokenType::Gt => ">",
+            TokenType::Lt => "<",
+            TokenType::Ge => ">=",
+            TokenType::Le => "<=",
+            TokenType::DotDot => "..",
+            TokenType::DotDotEq => "..=",
+        };
+        write!(f, "'{}'", symbol)
+    }
 }
 
 /// A primitive production in μRust's grammar defined by regular languages.
@@ -253,7 +509,7 @@ impl From<Token> for TokenType {
 
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?} at {}", self.token_type, self.span)
+        write!(f, "{} at {}", self.token_type, self.span)
     }
 }
 
@@ -261,6 +517,35 @@ impl fmt::Display for Token {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_token_type_display() {
+        // Every keyword and symbol is displayed as its spelling
+        for spelling in ["fn", "continue", "::", "(", "}", "&&", "<=", "true"] {
+            let ty = TokenType::extract_keyword_or_symbol(spelling).unwrap();
+            let expected = match ty {
+                TokenType::BoolLit(_) => format!("boolean literal '{spelling}'"),
+                _ => format!("'{spelling}'"),
+            };
+            assert_eq!(ty.to_string(), expected);
+        }
+        let float = TokenType::FloatLit(2.0, "2.00".into(), None);
+        assert_eq!(float.to_string(), "float literal '2.00'");
+        let float = TokenType::FloatLit(2.0, "2".into(), Some(NumericSuffix::F64));
+        assert_eq!(float.to_string(), "float literal '2f64'");
+        assert_eq!(
+            TokenType::StrLit("C".into()).to_string(),
+            "string literal '\"C\"'"
+        );
+        assert_eq!(TokenType::EOF.to_string(), "end of file");
+
+        let token = Token::new(
+            TokenType::Semi,
+            Position::new_at(1, 5),
+            Position::new_at(1, 6),
+        );
+        assert_eq!(token.to_string(), "';' at <1:5>-<1:6>");
+    }
+
     #[test]
     fn test_token_type_extract_keyword_or_symbol() {
         assert_eq!(
@@ -295,6 +580,18 @@ mod tests {
             TokenType::extract_keyword_or_symbol("while"),
             Some(TokenType::While)
         );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("do"),
+            Some(TokenType::Do)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("for"),
+            Some(TokenType::For)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("in"),
+            Some(TokenType::In)
+        );
         assert_eq!(
             TokenType::extract_keyword_or_symbol("if"),
             Some(TokenType::If)
@@ -311,6 +608,38 @@ mod tests {
             TokenType::extract_keyword_or_symbol("return"),
             Some(TokenType::Return)
         );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("struct"),
+            Some(TokenType::Struct)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("enum"),
+            Some(TokenType::Enum)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("match"),
+            Some(TokenType::Match)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("const"),
+            Some(TokenType::Const)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("pub"),
+            Some(TokenType::Pub)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("use"),
+            Some(TokenType::Use)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("break"),
+            Some(TokenType::Break)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("continue"),
+            Some(TokenType::Continue)
+        );
         assert_eq!(
             TokenType::extract_keyword_or_symbol("("),
             Some(TokenType::LPar)
@@ -423,6 +752,18 @@ mod tests {
             TokenType::extract_keyword_or_symbol("<="),
             Some(TokenType::Le)
         );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("."),
+            Some(TokenType::Dot)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol(".."),
+            Some(TokenType::DotDot)
+        );
+        assert_eq!(
+            TokenType::extract_keyword_or_symbol("..="),
+            Some(TokenType::DotDotEq)
+        );
         assert_eq!(TokenType::extract_keyword_or_symbol(""), None);
         assert_eq!(TokenType::extract_keyword_or_symbol("invalid"), None);
         assert_eq!(TokenType::extract_keyword_or_symbol("== "), None);
@@ -433,4 +774,18 @@ mod tests {
         assert_eq!(TokenType::extract_keyword_or_symbol("0123"), None);
         assert_eq!(TokenType::extract_keyword_or_symbol(r#""C""#), None);
     }
+
+    #[test]
+    fn test_token_type_reserved_keyword() {
+        assert_eq!(TokenType::Struct.reserved_keyword(), Some("struct"));
+        assert_eq!(TokenType::Enum.reserved_keyword(), Some("enum"));
+        assert_eq!(TokenType::Match.reserved_keyword(), Some("match"));
+        assert_eq!(TokenType::Const.reserved_keyword(), Some("const"));
+        assert_eq!(TokenType::Break.reserved_keyword(), Some("break"));
+        assert_eq!(TokenType::Continue.reserved_keyword(), Some("continue"));
+        assert_eq!(TokenType::Fn.reserved_keyword(), None);
+        assert_eq!(TokenType::Return.reserved_keyword(), None);
+        assert_eq!(TokenType::Pub.reserved_keyword(), None);
+        assert_eq!(TokenType::Ident("struct".into()).reserved_keyword(), None);
+    }
 }
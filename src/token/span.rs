@@ -1,7 +1,7 @@
 use std::fmt;
 
 /// A span between two positions in a file.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Span {
     start: Position,
     end: Position,
@@ -34,6 +34,22 @@ impl Span {
     pub fn end(&self) -> Position {
         self.end
     }
+
+    /// Checks whether the `other` span is fully contained within this `Span`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::token::{Position, Span};
+    ///
+    /// let outer = Span::new(Position::new_at(1, 1), Position::new_at(3, 2));
+    /// let inner = Span::new(Position::new_at(2, 5), Position::new_at(2, 8));
+    /// assert!(outer.contains(inner));
+    /// assert!(!inner.contains(outer));
+    /// ```
+    pub fn contains(&self, other: Span) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
 }
 
 impl fmt::Display for Span {
@@ -43,7 +59,9 @@ impl fmt::Display for Span {
 }
 
 /// A position in a file.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Positions are ordered by line first, then by column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Position {
     line: usize,
     column: usize,
@@ -122,6 +140,50 @@ impl Position {
     pub fn col_inc(&mut self) {
         self.column += 1;
     }
+
+    /// Moves this `Position` past the given character, i.e. [increments the line](Position::line_inc)
+    /// if the character is a new line or [increments the column](Position::col_inc) otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::token::Position;
+    ///
+    /// let mut pos = Position::new_at(2, 3);
+    /// pos.advance('a');
+    /// assert_eq!(pos, Position::new_at(2, 4));
+    /// pos.advance('\n');
+    /// assert_eq!(pos, Position::new_at(3, 1));
+    /// ```
+    pub fn advance(&mut self, c: char) {
+        if c == '\n' {
+            self.line_inc();
+        } else {
+            self.col_inc();
+        }
+    }
+
+    /// Returns the 0-based column of this `Position` in UTF-16 code units
+    /// (as used e.g. by the Language Server Protocol), given the text of its line.
+    ///
+    /// The columns past the end of the line are counted as single code units.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mini_rust_compiler_components::token::Position;
+    ///
+    /// let line = "let ń: f64 = 1; // 🦀 x";
+    /// assert_eq!(Position::new_at(1, 6).utf16_column(line), 5);
+    /// assert_eq!(Position::new_at(1, 22).utf16_column(line), 22);
+    /// assert_eq!(Position::new_at(1, 26).utf16_column(line), 26);
+    /// ```
+    pub fn utf16_column(&self, line: &str) -> usize {
+        let mut chars = line.chars();
+        (1..self.column)
+            .map(|_| chars.next().map_or(1, char::len_utf16))
+            .sum()
+    }
 }
 
 impl Default for Position {
@@ -0,0 +1,42 @@
+//! A module containing the structured logging of the compilation pipeline.
+//!
+//! With the `tracing` feature enabled, the lexer, the parser and the code generation emit
+//! [`tracing`](https://docs.rs/tracing) spans and events, which can be collected by any subscriber
+//! installed by the embedder:
+//! - the whole parsing and code generation of a crate are spans at the `INFO` level,
+//! - the code generation of every function is a span at the `DEBUG` level,
+//! - the summaries (e.g. the numbers of tokens and nodes) are events at the `DEBUG` level,
+//! - every production of the parser is a span, and every token an event, at the `TRACE` level.
+//!
+//! Without the feature, the macros of this module expand to nothing.
+
+/// Enters a new [span](https://docs.rs/tracing/latest/tracing/macro.span.html)
+/// at the given level until the end of the enclosing block.
+///
+/// # Example
+/// ```ignore
+/// trace::span!(DEBUG, "function", name = %self.proto.name());
+/// ```
+macro_rules! span {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::span!(::tracing::Level::$level, $($arg)+).entered();
+    };
+}
+
+/// Emits a new [event](https://docs.rs/tracing/latest/tracing/macro.event.html)
+/// at the given level.
+///
+/// # Example
+/// ```ignore
+/// trace::event!(DEBUG, tokens, nodes, "parsed the crate");
+/// ```
+macro_rules! event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        ::tracing::event!(::tracing::Level::$level, $($arg)+);
+    };
+}
+
+pub(crate) use event;
+pub(crate) use span;
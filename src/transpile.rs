@@ -0,0 +1,1443 @@
+//! A module containing the C backend, which transpiles a crate into readable C source code
+//! (see [`to_c`]).
+//!
+//! It is meant for teaching, i.e. to compare the μRust code with the equivalent C code
+//! (and with the LLVM IR generated for it), so the generated code follows the structure
+//! of the source code as closely as possible:
+//! - the integer types are mapped to the fixed-width ones (e.g. `int32_t` or `uint8_t`),
+//!   the floating-point types to `float` and `double`, and the unit values
+//!   are not represented at all (so `()` is `void` as a return type),
+//! - the private items are `static`, and the immutable statics and variables are `const`,
+//! - the shadowed variables (and the ones named like C keywords) are renamed.
+//!
+//! C has no expressions with blocks, so a block, an `if` or a loop whose value is used
+//! is turned into statements assigning the value to its destination (a variable,
+//! a temporary or the return value). The operands evaluated before such an expression
+//! are stored in temporaries as well, so that the [evaluation order](crate::ast::ExprASTNode)
+//! is preserved.
+//!
+//! Unlike the compiled code, the generated code doesn't trap on the integer overflow
+//! or on the division by zero, which are undefined behaviour in C (and so is the cast
+//! of a float that doesn't fit into the integer type, which saturates in μRust).
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write;
+use std::rc::Rc;
+
+use crate::ast::error::SemanticError;
+use crate::ast::*;
+use crate::builtin::Builtin;
+use crate::metrics::NodeKind;
+use crate::statics;
+use crate::target::TargetLayout;
+use crate::token::Span;
+
+/// The number of spaces used for one level of indentation.
+const INDENT_WIDTH: usize = 4;
+
+/// The C keywords (and the names defined by the included headers) which cannot be used
+/// as identifiers, so they get an underscore appended.
+const RESERVED_NAMES: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+    "else", "enum", "extern", "fabs", "fabsf", "false", "float", "fmax", "fmaxf", "fmin", "fminf",
+    "fmod", "for", "goto", "if", "inline", "int", "int16_t", "int32_t", "int64_t", "int8_t",
+    "long", "register", "restrict", "return", "short", "signed", "sizeof", "sqrt", "sqrtf",
+    "static", "struct", "switch", "true", "typedef", "uint16_t", "uint32_t", "uint64_t", "uint8_t",
+    "union", "unsigned", "void", "volatile", "while",
+];
+
+/// A result of the transpilation.
+pub type Result<T> = std::result::Result<T, TranspileError>;
+
+/// The type of error that can occur during the transpilation to C.
+#[derive(Debug)]
+pub enum TranspileError {
+    /// A [`SemanticError`] encountered during the transpilation.
+    SemanticError(SemanticError),
+    /// An error when a symbol that is not declared is encountered.
+    MissingSymbol {
+        /// The name of the missing symbol.
+        symbol: Box<str>,
+        /// The span where the error occurred.
+        span: Span,
+    },
+    /// An error when an underscore is used as a value.
+    UnderscoreUsedAsValue {
+        /// The span where the error occurred.
+        span: Span,
+    },
+    /// An error when the initializer of a static item cannot be evaluated at compile time.
+    NonConstantInitializer {
+        /// The span of the initializer.
+        span: Span,
+    },
+    /// An error when a construct cannot be expressed in C (e.g. an import from another crate,
+    /// whose signature is not known).
+    Unsupported {
+        /// The description of the error.
+        message: Box<str>,
+        /// The span of the construct.
+        span: Span,
+    },
+}
+
+impl fmt::Display for TranspileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranspileError::SemanticError(err) => write!(f, "{}", err),
+            TranspileError::MissingSymbol { symbol, span } => {
+                write!(f, "Cannot find `{}` at {}", symbol, span)
+            }
+            TranspileError::UnderscoreUsedAsValue { span } => {
+                write!(f, "`_` cannot be used as a value at {}", span)
+            }
+            TranspileError::NonConstantInitializer { span } => write!(
+                f,
+                "The initializer of a static has to be a constant at {}",
+                span
+            ),
+            TranspileError::Unsupported { message, span } => write!(f, "{} at {}", message, span),
+        }
+    }
+}
+
+impl Error for TranspileError {}
+
+impl From<SemanticError> for TranspileError {
+    fn from(err: SemanticError) -> TranspileError {
+        TranspileError::SemanticError(err)
+    }
+}
+
+/// Transpiles the given crate into C source code.
+///
+/// # Errors
+///
+/// If the crate cannot be expressed in C (e.g. it imports items from another crate),
+/// or a symbol is missing, a [`TranspileError`] is returned.
+pub fn to_c(crt: &Crate) -> Result<String> {
+    let mut transpiler = Transpiler::new(crt.root())?;
+    for func in crt.functions() {
+        transpiler.function(func)?;
+    }
+    transpiler.finish(crt.root())
+}
+
+/// Returns the C type representing the given type.
+fn c_type(ty: &Type) -> &'static str {
+    match ty {
+        Type::I8 => "int8_t",
+        Type::I16 => "int16_t",
+        Type::I32 => "int32_t",
+        Type::I64 => "int64_t",
+        Type::U8 => "uint8_t",
+        Type::U16 => "uint16_t",
+        Type::U32 => "uint32_t",
+        Type::U64 => "uint64_t",
+        Type::F32 => "float",
+        Type::F64 => "double",
+        Type::Bool => "bool",
+        Type::Unit => "void",
+        Type::Str => "const char *",
+        Type::Tuple(_) => unreachable!("The tuples are rejected before the transpilation"),
+        Type::Ref { .. } => unreachable!("The references are rejected before the transpilation"),
+    }
+}
+
+/// Returns the C identifier for the given μRust identifier.
+fn c_name(name: &str) -> String {
+    if RESERVED_NAMES.contains(&name) {
+        format!("{name}_")
+    } else {
+        name.to_owned()
+    }
+}
+
+/// Returns the C literal of the given constant.
+fn c_const(value: ConstValue) -> String {
+    match value {
+        ConstValue::Int(i) => i.to_string(),
+        ConstValue::Float(x) => c_float(x),
+        ConstValue::Bool(b) => b.to_string(),
+        ConstValue::Unit => String::new(),
+    }
+}
+
+/// Returns the C literal of the given float.
+fn c_float(x: f64) -> String {
+    if x.is_nan() {
+        "(0.0 / 0.0)".to_owned()
+    } else if x.is_infinite() {
+        format!("({}1.0 / 0.0)", if x < 0.0 { "-" } else { "" })
+    } else {
+        // The debug format always has a decimal point or an exponent
+        format!("{x:?}")
+    }
+}
+
+/// Returns the C string literal with the given contents.
+///
+/// The characters other than printable ASCII are written as octal escapes of their UTF-8 bytes,
+/// which (unlike the hexadecimal ones) cannot be continued by the following characters.
+fn c_string(s: &str) -> String {
+    let mut literal = String::from("\"");
+    for byte in s.bytes() {
+        match byte {
+            b'"' => literal.push_str("\\\""),
+            b'\\' => literal.push_str("\\\\"),
+            b'\n' => literal.push_str("\\n"),
+            b'\t' => literal.push_str("\\t"),
+            b' '..=b'~' => literal.push(byte as char),
+            _ => literal.push_str(&format!("\\{byte:03o}")),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Returns the C operator of the given arithmetic or logical operator.
+fn arith_operator(operator: ArithOperator) -> &'static str {
+    match operator {
+        ArithOperator::Add => "+",
+        ArithOperator::Sub => "-",
+        ArithOperator::Mul => "*",
+        ArithOperator::Div => "/",
+        ArithOperator::Rem => "%",
+        ArithOperator::BitAnd => "&",
+        ArithOperator::BitOr => "|",
+        ArithOperator::BitXor => "^",
+    }
+}
+
+/// Returns the C operator of the given comparison operator.
+fn comp_operator(operator: CompOperator) -> &'static str {
+    match operator {
+        CompOperator::Eq => "==",
+        CompOperator::Ne => "!=",
+        CompOperator::Gt => ">",
+        CompOperator::Lt => "<",
+        CompOperator::Ge => ">=",
+        CompOperator::Le => "<=",
+    }
+}
+
+/// Returns whether the given C expression doesn't need parentheses as an operand,
+/// i.e. it is an identifier, a number or a function call.
+fn is_atomic(expr: &str) -> bool {
+    let is_name_char = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    let Some(open) = expr.find('(') else {
+        return expr.chars().all(is_name_char);
+    };
+    if open == 0 || !expr[..open].chars().all(is_name_char) {
+        return false;
+    }
+    // The parentheses of the call have to enclose the rest of the expression
+    let mut depth = 0;
+    for (i, c) in expr[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return open + i == expr.len() - 1;
+        }
+    }
+    false
+}
+
+/// Returns whether the given expression can be written as a C expression, i.e. it doesn't
+/// contain any expression that has to be turned into statements.
+fn is_simple(node: &dyn ASTNode) -> bool {
+    let kind = NodeKind::of(node);
+    !matches!(
+        kind,
+        NodeKind::Block
+            | NodeKind::UnsafeBlock
+            | NodeKind::If
+            | NodeKind::While
+            | NodeKind::DoWhile
+            | NodeKind::For
+            | NodeKind::InfLoop
+            | NodeKind::Return
+            | NodeKind::Assign
+    ) && node.children().into_iter().flatten().all(is_simple)
+}
+
+/// Returns whether the given expression is a literal, i.e. it doesn't have to be stored
+/// in a temporary to preserve the evaluation order.
+fn is_literal(expr: &dyn ExprASTNode) -> bool {
+    matches!(
+        NodeKind::of(expr.as_ast()),
+        NodeKind::Literal | NodeKind::LayoutOf
+    )
+}
+
+/// A declared function.
+#[derive(Debug, Clone)]
+struct FunctionSymbol {
+    name: String,
+    params: Vec<Type>,
+    return_type: Type,
+}
+
+/// A variable (a local variable, a parameter or a static item).
+#[derive(Debug, Clone)]
+struct Variable {
+    /// The C identifier of the variable (empty for the unit variables, which are not declared).
+    name: String,
+    ty: Type,
+}
+
+/// The destination of the value of an expression turned into statements.
+#[derive(Debug, Clone)]
+enum Dest {
+    /// The value is discarded.
+    Discard,
+    /// The value is assigned to the variable with the given C identifier.
+    Var(String),
+    /// The value is returned from the function.
+    Return,
+}
+
+/// The state of the transpilation of a crate.
+struct Transpiler {
+    functions: HashMap<Rc<str>, FunctionSymbol>,
+    statics: HashMap<Rc<str>, Variable>,
+    /// The C identifiers of the items, which the local variables cannot shadow.
+    global_names: HashSet<String>,
+    /// The generated function definitions.
+    output: String,
+    indent: usize,
+    uses_math: bool,
+    // The state of the current function
+    scopes: Vec<HashMap<Rc<str>, Variable>>,
+    local_names: HashSet<String>,
+    /// The statement returning the unit value from the current function.
+    unit_return: &'static str,
+}
+
+impl Transpiler {
+    /// Creates a new transpiler, declaring the items of the crate.
+    fn new(root: &CrateASTNode) -> Result<Transpiler> {
+        if let Some(import) = root.imports().first() {
+            return Err(TranspileError::Unsupported {
+                message: "The signatures of the imported functions are not known".into(),
+                span: import.span(),
+            });
+        }
+        if let Some(span) = find_tuple(root) {
+            return Err(TranspileError::Unsupported {
+                message: "Tuples cannot be expressed in C".into(),
+                span,
+            });
+        }
+        if let Some(span) = find_reference(root) {
+            return Err(TranspileError::Unsupported {
+                message: "References cannot be expressed in C".into(),
+                span,
+            });
+        }
+
+        let mut transpiler = Transpiler {
+            functions: HashMap::new(),
+            statics: HashMap::new(),
+            global_names: HashSet::new(),
+            output: String::new(),
+            indent: 0,
+            uses_math: false,
+            scopes: Vec::new(),
+            local_names: HashSet::new(),
+            unit_return: "return;",
+        };
+        let protos = root.items().iter().flat_map(|item| match item {
+            ItemASTNode::Func(func) => vec![func.proto()],
+            ItemASTNode::Extern(ext) => ext
+                .items()
+                .iter()
+                .filter_map(|item| match item {
+                    ExternItem::Func(proto) => Some(proto.as_ref()),
+                    ExternItem::Static(_) => None,
+                })
+                .collect(),
+            ItemASTNode::Static(_) => vec![],
+        });
+        for proto in protos {
+            let symbol = FunctionSymbol {
+                name: c_name(proto.name()),
+                params: proto.param_types().collect(),
+                return_type: proto.return_type().ty(),
+            };
+            transpiler.global_names.insert(symbol.name.clone());
+            transpiler.functions.insert(proto.name_owned(), symbol);
+        }
+        let extern_statics = root.extern_blocks().flat_map(|ext| {
+            ext.items().iter().filter_map(|item| match item {
+                ExternItem::Static(stat) => Some(stat.as_ref()),
+                ExternItem::Func(_) => None,
+            })
+        });
+        for stat in root.statics().chain(extern_statics) {
+            let variable = Variable {
+                name: c_name(stat.name()),
+                ty: stat.ty().ty(),
+            };
+            transpiler.global_names.insert(variable.name.clone());
+            transpiler.statics.insert(stat.name_owned(), variable);
+        }
+        Ok(transpiler)
+    }
+
+    /// Assembles the C source code from the declarations of the items
+    /// and the generated function definitions.
+    fn finish(self, root: &CrateASTNode) -> Result<String> {
+        let mut source = String::new();
+        let mut line = |text: &str| {
+            source.push_str(text);
+            source.push('\n');
+        };
+
+        line(&format!(
+            "// Transpiled from the μRust crate `{}`",
+            root.name()
+        ));
+        line("#include <stdbool.h>");
+        line("#include <stdint.h>");
+        if self.uses_math {
+            line("#include <math.h>");
+        }
+
+        let externs: Vec<_> = root.extern_blocks().flat_map(|ext| ext.items()).collect();
+        if !externs.is_empty() {
+            line("");
+        }
+        for item in externs {
+            match item {
+                ExternItem::Func(proto) => line(&format!("{};", self.prototype(proto, None))),
+                ExternItem::Static(stat) if stat.ty().ty() != Type::Unit => {
+                    let constness = if stat.is_mutable() { "" } else { "const " };
+                    let variable = &self.statics[stat.name()];
+                    let ty = c_type(&variable.ty);
+                    line(&format!("extern {constness}{ty} {};", variable.name));
+                }
+                ExternItem::Static(_) => {}
+            }
+        }
+
+        let statics: Vec<_> = root.statics().collect();
+        // The C code is assumed to be compiled for the host
+        let values = statics::evaluate_statics(&statics, TargetLayout::host())?;
+        if !statics.is_empty() {
+            line("");
+        }
+        for stat in statics {
+            let variable = &self.statics[stat.name()];
+            // The unit statics are not represented at all
+            if variable.ty == Type::Unit {
+                continue;
+            }
+            let value = stat
+                .value()
+                .map(|value| {
+                    value
+                        .const_eval_in(&values)
+                        .ok_or(TranspileError::NonConstantInitializer { span: value.span() })
+                })
+                .transpose()?;
+            let linkage = if stat.visibility().is_public() {
+                ""
+            } else {
+                "static "
+            };
+            let constness = if stat.is_mutable() { "" } else { "const " };
+            let ty = c_type(&variable.ty);
+            let initializer = value.map(|v| format!(" = {}", c_const(v)));
+            line(&format!(
+                "{linkage}{constness}{ty} {}{};",
+                variable.name,
+                initializer.unwrap_or_default()
+            ));
+        }
+
+        let functions: Vec<_> = root.functions().collect();
+        if !functions.is_empty() {
+            line("");
+        }
+        for func in functions {
+            line(&format!("{};", self.definition_header(func, None)));
+        }
+
+        source.push_str(&self.output);
+        Ok(source)
+    }
+
+    //#region Output
+    /// Writes the given text as a new line at the current indentation level.
+    fn line(&mut self, text: impl fmt::Display) {
+        let indent = " ".repeat(self.indent * INDENT_WIDTH);
+        // Writing to a `String` never fails
+        writeln!(self.output, "{indent}{text}").unwrap();
+    }
+
+    /// Writes the body of a compound statement, i.e. the given block contents,
+    /// one level deeper than the current indentation level.
+    fn indented(&mut self, f: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        self.indent += 1;
+        let result = f(self);
+        self.indent -= 1;
+        result
+    }
+
+    /// Returns the C prototype of the given function, with the given names of the parameters
+    /// (or without them).
+    fn prototype(&self, proto: &FuncProtoASTNode, param_names: Option<&[String]>) -> String {
+        let symbol = &self.functions[proto.name()];
+        let mut params: Vec<_> = symbol
+            .params
+            .iter()
+            .filter(|ty| **ty != Type::Unit)
+            .map(|ty| c_type(ty).to_owned())
+            .collect();
+        if let Some(names) = param_names {
+            for (param, name) in params.iter_mut().zip(names) {
+                param.push(' ');
+                param.push_str(name);
+            }
+        }
+        let params = if params.is_empty() {
+            "void".to_owned()
+        } else {
+            params.join(", ")
+        };
+        let return_type = match &symbol.return_type {
+            // The entry point has to return `int` in C
+            Type::Unit | Type::I32 if proto.name() == FuncASTNode::ENTRY_POINT => "int",
+            ty => c_type(ty),
+        };
+        format!("{return_type} {}({params})", symbol.name)
+    }
+
+    /// Returns the header of the definition of the given function, i.e. its prototype
+    /// (which is `static` if the function is private).
+    fn definition_header(&self, func: &FuncASTNode, param_names: Option<&[String]>) -> String {
+        let linkage = if func.is_exported() { "" } else { "static " };
+        format!("{linkage}{}", self.prototype(func.proto(), param_names))
+    }
+    //#endregion
+
+    //#region Variables
+    /// Declares a local variable with the given name and type, and returns its C identifier,
+    /// which is unique in the function.
+    fn declare_local(&mut self, name: Rc<str>, ty: Type) -> String {
+        let c_name = match ty {
+            // The unit variables are not represented at all
+            Type::Unit => String::new(),
+            _ => self.fresh_name(&name),
+        };
+        self.scopes
+            .last_mut()
+            .expect("A local has to be declared in a scope")
+            .insert(
+                name,
+                Variable {
+                    name: c_name.clone(),
+                    ty,
+                },
+            );
+        c_name
+    }
+
+    /// Returns a new C identifier based on the given name, which is unique in the function.
+    fn fresh_name(&mut self, name: &str) -> String {
+        let base = c_name(name);
+        let mut c_name = base.clone();
+        let mut i = 0;
+        while self.local_names.contains(&c_name) || self.global_names.contains(&c_name) {
+            i += 1;
+            c_name = format!("{base}_{i}");
+        }
+        self.local_names.insert(c_name.clone());
+        c_name
+    }
+
+    /// Stores the given C expression in a new temporary of the given type,
+    /// and returns the identifier of the temporary.
+    fn temporary(&mut self, expr: String, ty: &Type) -> String {
+        let name = self.fresh_name("tmp");
+        self.line(format_args!("{} {name} = {expr};", c_type(ty)));
+        name
+    }
+
+    /// Returns the variable (a local or a static) with the given name.
+    fn variable(&self, name: &str, span: Span) -> Result<Variable> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .or_else(|| self.statics.get(name))
+            .cloned()
+            .ok_or_else(|| TranspileError::MissingSymbol {
+                symbol: name.into(),
+                span,
+            })
+    }
+
+    /// Returns the type of the given expression.
+    ///
+    /// The expressions that don't produce a value (e.g. a `return`) are of the unit type.
+    fn type_of(&mut self, expr: &dyn ExprASTNode) -> Result<Type> {
+        let node = expr.as_any();
+        if let Some(path) = node.downcast_ref::<PathASTNode>() {
+            return Ok(self.variable(path.path(), path.span())?.ty);
+        }
+        if let Some(grouped) = node.downcast_ref::<GroupedExprASTNode>() {
+            return self.type_of(grouped.expr());
+        }
+        if let Some(call) = node.downcast_ref::<FunCallASTNode>() {
+            // A built-in function returns the type of its first argument
+            if let (None, Some(_), Some(arg)) = (
+                self.functions.get(call.path()),
+                Builtin::from_name(call.path()),
+                call.args().first(),
+            ) {
+                return self.type_of(arg.as_ref());
+            }
+            return Ok(self
+                .function_symbol(call.path(), call.span())?
+                .return_type
+                .clone());
+        }
+        if let Some(arith) = node.downcast_ref::<ArithExprASTNode>() {
+            return self.type_of(arith.lhs());
+        }
+        if let Some(neg) = node.downcast_ref::<NegExprASTNode>() {
+            return self.type_of(neg.expr());
+        }
+        if let Some(cast) = node.downcast_ref::<TypeCastASTNode>() {
+            return Ok(cast.ty());
+        }
+        if let Some(block) = node.downcast_ref::<BlockASTNode>() {
+            return self.block_type(block);
+        }
+        if let Some(unsafe_block) = node.downcast_ref::<UnsafeBlockASTNode>() {
+            return self.block_type(unsafe_block.block());
+        }
+        if let Some(if_node) = node.downcast_ref::<IfASTNode>() {
+            // If the `then` branch diverges (e.g. it returns), the `else` branch determines the type
+            return match (self.block_type(if_node.then_block())?, if_node.else_node()) {
+                (Type::Unit, Some(else_node)) => self.type_of(else_node.expr()),
+                (ty, _) => Ok(ty),
+            };
+        }
+        let ty = match NodeKind::of(expr.as_ast()) {
+            NodeKind::Literal => {
+                let literal_type = |node: &dyn std::any::Any| {
+                    node.downcast_ref::<LiteralASTNode<i128>>()
+                        .map(|l| l.ty())
+                        .or_else(|| node.downcast_ref::<LiteralASTNode<f64>>().map(|l| l.ty()))
+                        .or_else(|| node.downcast_ref::<LiteralASTNode<bool>>().map(|l| l.ty()))
+                        .or_else(|| {
+                            node.downcast_ref::<LiteralASTNode<Rc<str>>>()
+                                .map(|l| l.ty())
+                        })
+                };
+                literal_type(node).unwrap_or(Type::Unit)
+            }
+            // Like the unsuffixed literals, the layout queries are `int32_t`
+            NodeKind::LayoutOf => Type::I32,
+            NodeKind::Comp | NodeKind::LazyBool => Type::Bool,
+            _ => Type::Unit,
+        };
+        Ok(ty)
+    }
+
+    /// Returns the type of the value of the given block.
+    fn block_type(&mut self, block: &BlockASTNode) -> Result<Type> {
+        let Some(expr) = block.return_expr() else {
+            return Ok(Type::Unit);
+        };
+        // The value can refer to the variables declared in the block
+        let mut scope = HashMap::new();
+        for statement in block.statements() {
+            if let Some(stmt) = statement.as_any().downcast_ref::<LetASTNode>() {
+                if let Some(name) = stmt.decl().try_as_assignee().and_then(|a| a.pattern()) {
+                    let variable = Variable {
+                        name: String::new(),
+                        ty: self.let_type(stmt)?,
+                    };
+                    scope.insert(name, variable);
+                }
+            }
+        }
+        self.scopes.push(scope);
+        let ty = self.type_of(expr);
+        self.scopes.pop();
+        ty
+    }
+
+    /// Returns the type of the variable declared by the given let statement,
+    /// inferring it from the value if it is not annotated.
+    fn let_type(&mut self, stmt: &LetASTNode) -> Result<Type> {
+        match (stmt.ty(), stmt.value()) {
+            (Some(ty), _) => Ok(ty),
+            (None, Some(value)) => self.type_of(value),
+            (None, None) => unreachable!("A let with an inferred type has a value"),
+        }
+    }
+
+    /// Returns the function with the given name.
+    fn function_symbol(&self, name: &str, span: Span) -> Result<&FunctionSymbol> {
+        self.functions
+            .get(name)
+            .ok_or_else(|| TranspileError::MissingSymbol {
+                symbol: name.into(),
+                span,
+            })
+    }
+    //#endregion
+
+    //#region Items
+    /// Writes the definition of the given function.
+    fn function(&mut self, func: &FuncASTNode) -> Result<()> {
+        self.local_names.clear();
+        self.scopes.push(HashMap::new());
+        let is_main = func.proto().name() == FuncASTNode::ENTRY_POINT;
+        self.unit_return = if is_main { "return 0;" } else { "return;" };
+
+        let mut param_names = Vec::new();
+        for param in func.proto().get_param_iter() {
+            let ty = param.ty().ty();
+            let name = param.assignee()?.pattern();
+            match (name, &ty) {
+                (Some(name), _) => {
+                    let unit = ty == Type::Unit;
+                    let c_name = self.declare_local(name, ty);
+                    if !unit {
+                        param_names.push(c_name);
+                    }
+                }
+                (None, Type::Unit) => {}
+                // C (before C23) requires the parameters of a definition to be named
+                (None, _) => param_names.push(self.fresh_name("unused")),
+            }
+        }
+
+        self.output.push('\n');
+        let header = self.definition_header(func, Some(&param_names));
+        self.line(format_args!("{header} {{"));
+        self.indented(|this| {
+            this.block_contents(func.body(), &Dest::Discard)?;
+            // The entry point returning unit has to return a status code in C
+            if is_main && func.proto().return_type().ty() == Type::Unit {
+                this.line(this.unit_return);
+            }
+            Ok(())
+        })?;
+        self.line("}");
+        self.scopes.pop();
+        Ok(())
+    }
+    //#endregion
+
+    //#region Statements
+    /// Writes the statements of the given block, and the statements putting its value
+    /// into the given destination (the surrounding braces are written by the caller).
+    fn block_contents(&mut self, block: &BlockASTNode, dest: &Dest) -> Result<()> {
+        self.scopes.push(HashMap::new());
+        let result = self.block_contents_in_scope(block, dest);
+        self.scopes.pop();
+        result
+    }
+
+    fn block_contents_in_scope(&mut self, block: &BlockASTNode, dest: &Dest) -> Result<()> {
+        for statement in block.statements() {
+            let node = statement.as_any();
+            if let Some(stmt) = node.downcast_ref::<ExprStmtASTNode>() {
+                self.expr_into(stmt.expr(), &Dest::Discard)?;
+            } else if let Some(stmt) = node.downcast_ref::<LetASTNode>() {
+                self.let_stmt(stmt)?;
+            } else {
+                unreachable!("Unknown statement: {}", statement);
+            }
+        }
+        match block.return_expr() {
+            Some(expr) => self.expr_into(expr, dest),
+            None => self.unit_into(dest),
+        }
+    }
+
+    fn let_stmt(&mut self, stmt: &LetASTNode) -> Result<()> {
+        let ty = self.let_type(stmt)?;
+        let name = stmt
+            .decl()
+            .try_as_assignee()
+            .ok_or(SemanticError::WrongExpressionKind {
+                message: "Expected an assignee expression",
+                span: stmt.span(),
+            })?
+            .pattern();
+        let Some(name) = name.clone().filter(|_| ty != Type::Unit) else {
+            if let Some(value) = stmt.value() {
+                self.expr_into(value, &Dest::Discard)?;
+            }
+            if let Some(name) = name {
+                self.declare_local(name, ty);
+            }
+            return Ok(());
+        };
+
+        // The value can refer to the variable shadowed by the declared one,
+        // so the declared variable is only visible after the value is generated
+        let c_name = self.fresh_name(&name);
+        match stmt.value() {
+            Some(value) if is_simple(value.as_ast()) => {
+                let value = self.value(value)?;
+                let constness = if stmt.is_mutable() { "" } else { "const " };
+                self.line(format_args!(
+                    "{constness}{} {c_name} = {value};",
+                    c_type(&ty)
+                ));
+            }
+            value => {
+                self.line(format_args!("{} {c_name};", c_type(&ty)));
+                if let Some(value) = value {
+                    self.expr_into(value, &Dest::Var(c_name.clone()))?;
+                }
+            }
+        }
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .insert(name, Variable { name: c_name, ty });
+        Ok(())
+    }
+
+    /// Writes the statements evaluating the given expression
+    /// and putting its value into the given destination.
+    fn expr_into(&mut self, expr: &dyn ExprASTNode, dest: &Dest) -> Result<()> {
+        let node = expr.as_any();
+        if let Some(grouped) = node.downcast_ref::<GroupedExprASTNode>() {
+            return self.expr_into(grouped.expr(), dest);
+        }
+        if let Some(block) = node.downcast_ref::<BlockASTNode>() {
+            return self.nested_block(block, dest);
+        }
+        if let Some(unsafe_block) = node.downcast_ref::<UnsafeBlockASTNode>() {
+            return self.nested_block(unsafe_block.block(), dest);
+        }
+        if let Some(if_node) = node.downcast_ref::<IfASTNode>() {
+            return self.if_stmt(if_node, dest);
+        }
+        if let Some(while_node) = node.downcast_ref::<WhileASTNode>() {
+            self.while_stmt(while_node)?;
+            return self.unit_into(dest);
+        }
+        if let Some(do_while) = node.downcast_ref::<DoWhileASTNode>() {
+            self.do_while_stmt(do_while)?;
+            return self.unit_into(dest);
+        }
+        if let Some(for_node) = node.downcast_ref::<ForASTNode>() {
+            self.for_stmt(for_node)?;
+            return self.unit_into(dest);
+        }
+        if let Some(inf_loop) = node.downcast_ref::<InfLoopASTNode>() {
+            // The loop can only be exited with a `return`, so it never produces a value
+            self.line("while (true) {");
+            self.indented(|this| this.block_contents(inf_loop.body(), &Dest::Discard))?;
+            self.line("}");
+            return Ok(());
+        }
+        if let Some(ret) = node.downcast_ref::<ReturnASTNode>() {
+            return match ret.value() {
+                Some(value) => self.expr_into(value, &Dest::Return),
+                None => self.unit_into(&Dest::Return),
+            };
+        }
+        if let Some(assign) = node.downcast_ref::<AssignASTNode>() {
+            self.assign(assign)?;
+            return self.unit_into(dest);
+        }
+
+        if self.type_of(expr)? == Type::Unit {
+            // The unit values are not represented, so the expression is only evaluated
+            if let Some(call) = node.downcast_ref::<FunCallASTNode>() {
+                let call = self.call(call)?;
+                self.line(format_args!("{call};"));
+            } else if !matches!(
+                NodeKind::of(expr.as_ast()),
+                NodeKind::Literal | NodeKind::Path
+            ) {
+                self.value(expr)?;
+            }
+            return self.unit_into(dest);
+        }
+
+        let value = self.value(expr)?;
+        match dest {
+            Dest::Discard if node.is::<FunCallASTNode>() => self.line(format_args!("{value};")),
+            Dest::Discard => self.line(format_args!("(void)({value});")),
+            Dest::Var(name) => self.line(format_args!("{name} = {value};")),
+            Dest::Return => self.line(format_args!("return {value};")),
+        }
+        Ok(())
+    }
+
+    /// Writes the statements putting the unit value into the given destination.
+    fn unit_into(&mut self, dest: &Dest) -> Result<()> {
+        if let Dest::Return = dest {
+            self.line(self.unit_return);
+        }
+        Ok(())
+    }
+
+    /// Writes a block nested in another one, i.e. in braces (unless it has no statements).
+    fn nested_block(&mut self, block: &BlockASTNode, dest: &Dest) -> Result<()> {
+        if block.statements().is_empty() {
+            return self.block_contents(block, dest);
+        }
+        self.line("{");
+        self.indented(|this| this.block_contents(block, dest))?;
+        self.line("}");
+        Ok(())
+    }
+
+    fn if_stmt(&mut self, if_node: &IfASTNode, dest: &Dest) -> Result<()> {
+        // Without the final `else`, the value is unit, so the branches' values are discarded
+        let mut last = if_node;
+        while let Some(ElseExpr::ElseIf(else_if)) = last.else_node().map(ElseASTNode::branch) {
+            last = else_if;
+        }
+        let has_value = last.else_node().is_some();
+        let branch_dest = if has_value { dest } else { &Dest::Discard };
+
+        let condition = self.value(if_node.condition())?;
+        self.line(format_args!("if ({condition}) {{"));
+        let mut current = if_node;
+        loop {
+            self.indented(|this| this.block_contents(current.then_block(), branch_dest))?;
+            match current.else_node().map(ElseASTNode::branch) {
+                None => break,
+                Some(ElseExpr::Else(block)) => {
+                    self.line("} else {");
+                    self.indented(|this| this.block_contents(block, branch_dest))?;
+                    break;
+                }
+                // The condition of an `else if` can only be chained if it is a C expression
+                Some(ElseExpr::ElseIf(else_if)) if is_simple(else_if.condition().as_ast()) => {
+                    let condition = self.value(else_if.condition())?;
+                    self.line(format_args!("}} else if ({condition}) {{"));
+                    current = else_if;
+                }
+                Some(ElseExpr::ElseIf(else_if)) => {
+                    self.line("} else {");
+                    self.indented(|this| this.if_stmt(else_if, branch_dest))?;
+                    break;
+                }
+            }
+        }
+        self.line("}");
+
+        if !has_value {
+            self.unit_into(dest)?;
+        }
+        Ok(())
+    }
+
+    fn while_stmt(&mut self, while_node: &WhileASTNode) -> Result<()> {
+        if is_simple(while_node.condition().as_ast()) {
+            let condition = self.value(while_node.condition())?;
+            self.line(format_args!("while ({condition}) {{"));
+            self.indented(|this| this.block_contents(while_node.body(), &Dest::Discard))?;
+            self.line("}");
+            return Ok(());
+        }
+
+        // The statements of the condition have to be evaluated in every iteration
+        self.line("while (true) {");
+        self.indented(|this| {
+            let condition = this.value(while_node.condition())?;
+            this.line(format_args!("if (!({condition})) {{"));
+            this.indented(|this| {
+                this.line("break;");
+                Ok(())
+            })?;
+            this.line("}");
+            this.block_contents(while_node.body(), &Dest::Discard)
+        })?;
+        self.line("}");
+        Ok(())
+    }
+
+    fn do_while_stmt(&mut self, do_while: &DoWhileASTNode) -> Result<()> {
+        if is_simple(do_while.condition().as_ast()) {
+            self.line("do {");
+            self.indented(|this| this.block_contents(do_while.body(), &Dest::Discard))?;
+            let condition = self.value(do_while.condition())?;
+            self.line(format_args!("}} while ({condition});"));
+            return Ok(());
+        }
+
+        // The statements of the condition have to be evaluated after every iteration
+        self.line("while (true) {");
+        self.indented(|this| {
+            this.block_contents(do_while.body(), &Dest::Discard)?;
+            let condition = this.value(do_while.condition())?;
+            this.line(format_args!("if (!({condition})) {{"));
+            this.indented(|this| {
+                this.line("break;");
+                Ok(())
+            })?;
+            this.line("}");
+            Ok(())
+        })?;
+        self.line("}");
+        Ok(())
+    }
+
+    fn for_stmt(&mut self, for_node: &ForASTNode) -> Result<()> {
+        // An unsuffixed literal at the start adopts the type of the end (e.g. `0..n`)
+        let ty = match for_node
+            .start()
+            .as_any()
+            .downcast_ref::<LiteralASTNode<i128>>()
+        {
+            Some(literal) if literal.suffix().is_none() => self.type_of(for_node.end())?,
+            _ => self.type_of(for_node.start())?,
+        };
+        if !ty.is_integer() {
+            return Err(TranspileError::Unsupported {
+                message: format!("Cannot iterate over a range of `{}`", ty).into(),
+                span: for_node.start().span(),
+            });
+        }
+
+        // The bounds are evaluated once and in order, and the end cannot change in the body
+        let start = self.value(for_node.start())?;
+        let start = match is_literal(for_node.start()) {
+            true => start,
+            false => self.temporary(start, &ty),
+        };
+        let end = self.value(for_node.end())?;
+        let end = match is_literal(for_node.end()) {
+            true => end,
+            false => self.temporary(end, &ty),
+        };
+
+        self.scopes.push(HashMap::new());
+        let counter = match for_node.variable() {
+            Some(name) => self.declare_local(name, ty.clone()),
+            None => self.fresh_name("i"),
+        };
+        let comparison = if for_node.is_inclusive() { "<=" } else { "<" };
+        self.line(format_args!(
+            "for ({} {counter} = {start}; {counter} {comparison} {end}; {counter}++) {{",
+            c_type(&ty)
+        ));
+        self.indented(|this| {
+            this.block_contents(for_node.body(), &Dest::Discard)?;
+            if for_node.is_inclusive() {
+                // The counter is not incremented past the end of the range, so it cannot overflow
+                this.line(format_args!("if ({counter} == {end}) {{"));
+                this.indented(|this| {
+                    this.line("break;");
+                    Ok(())
+                })?;
+                this.line("}");
+            }
+            Ok(())
+        })?;
+        self.line("}");
+        self.scopes.pop();
+        Ok(())
+    }
+
+    fn assign(&mut self, assign: &AssignASTNode) -> Result<()> {
+        let pattern = assign
+            .assignee()
+            .try_as_assignee()
+            .ok_or(SemanticError::WrongExpressionKind {
+                message: "Expected an assignee expression",
+                span: assign.span(),
+            })?
+            .pattern();
+        let variable = match pattern {
+            Some(name) => self.variable(&name, assign.assignee().span())?,
+            None => return self.expr_into(assign.value(), &Dest::Discard),
+        };
+        match variable.ty {
+            Type::Unit => self.expr_into(assign.value(), &Dest::Discard),
+            // The variable is only assigned once the value is evaluated
+            _ => self.expr_into(assign.value(), &Dest::Var(variable.name)),
+        }
+    }
+    //#endregion
+
+    //#region Expressions
+    /// Returns the C expression of the value of the given (non-unit) expression,
+    /// writing the statements that have to be evaluated before it.
+    fn value(&mut self, expr: &dyn ExprASTNode) -> Result<String> {
+        let node = expr.as_any();
+        if let Some(literal) = node.downcast_ref::<LiteralASTNode<i128>>() {
+            return Ok(literal.value().to_string());
+        }
+        if let Some(literal) = node.downcast_ref::<LiteralASTNode<f64>>() {
+            return Ok(c_float(*literal.value()));
+        }
+        if let Some(literal) = node.downcast_ref::<LiteralASTNode<bool>>() {
+            return Ok(literal.value().to_string());
+        }
+        if let Some(literal) = node.downcast_ref::<LiteralASTNode<Rc<str>>>() {
+            return Ok(c_string(literal.value()));
+        }
+        if let Some(layout_of) = node.downcast_ref::<LayoutOfASTNode>() {
+            let value = layout_of
+                .const_eval()
+                .expect("Layout queries are always evaluated at compile time");
+            return Ok(c_const(value));
+        }
+        if let Some(path) = node.downcast_ref::<PathASTNode>() {
+            return Ok(self.variable(path.path(), path.span())?.name);
+        }
+        if node.is::<UnderscoreASTNode>() {
+            return Err(TranspileError::UnderscoreUsedAsValue { span: expr.span() });
+        }
+        if let Some(grouped) = node.downcast_ref::<GroupedExprASTNode>() {
+            return self.value(grouped.expr());
+        }
+        if let Some(call) = node.downcast_ref::<FunCallASTNode>() {
+            return self.call(call);
+        }
+        if let Some(arith) = node.downcast_ref::<ArithExprASTNode>() {
+            return self.arith(arith);
+        }
+        if let Some(comp) = node.downcast_ref::<CompExprASTNode>() {
+            return self.comp(comp);
+        }
+        if let Some(lazy_bool) = node.downcast_ref::<LazyBoolExprASTNode>() {
+            return self.lazy_bool(lazy_bool);
+        }
+        if let Some(neg) = node.downcast_ref::<NegExprASTNode>() {
+            let ty = self.type_of(neg.expr())?;
+            let operand = self.operand(neg.expr())?;
+            return match (neg.operator(), &ty) {
+                (NegOperator::Neg, ty) if ty.is_signed() || ty.is_float() => {
+                    Ok(format!("-{operand}"))
+                }
+                (NegOperator::Not, Type::Bool) => Ok(format!("!{operand}")),
+                (NegOperator::Not, ty) if ty.is_integer() => Ok(format!("~{operand}")),
+                _ => Err(TranspileError::Unsupported {
+                    message: format!("Cannot negate a value of type `{}`", ty).into(),
+                    span: neg.span(),
+                }),
+            };
+        }
+        if let Some(cast) = node.downcast_ref::<TypeCastASTNode>() {
+            let operand = self.operand(cast.value())?;
+            return Ok(format!("({}){operand}", c_type(&cast.ty())));
+        }
+
+        // An expression with a block, whose value is put into a temporary
+        let ty = self.type_of(expr)?;
+        if ty == Type::Unit {
+            return Err(TranspileError::Unsupported {
+                message: "The unit value cannot be used as a value in C".into(),
+                span: expr.span(),
+            });
+        }
+        let name = self.fresh_name("tmp");
+        self.line(format_args!("{} {name};", c_type(&ty)));
+        self.expr_into(expr, &Dest::Var(name.clone()))?;
+        Ok(name)
+    }
+
+    /// Returns the C expression of the given operand, parenthesized if needed.
+    fn operand(&mut self, expr: &dyn ExprASTNode) -> Result<String> {
+        let value = self.value(expr)?;
+        Ok(if is_atomic(&value) {
+            value
+        } else {
+            format!("({value})")
+        })
+    }
+
+    /// Returns the C expressions of the operands of a binary operator, storing the left-hand
+    /// side in a temporary if the right-hand side has to be turned into statements.
+    fn operands(
+        &mut self,
+        lhs: &dyn ExprASTNode,
+        rhs: &dyn ExprASTNode,
+    ) -> Result<(String, String)> {
+        let mut lhs_value = self.operand(lhs)?;
+        if !is_simple(rhs.as_ast()) && !is_literal(lhs) {
+            let ty = self.type_of(lhs)?;
+            lhs_value = self.temporary(lhs_value, &ty);
+        }
+        Ok((lhs_value, self.operand(rhs)?))
+    }
+
+    fn call(&mut self, call: &FunCallASTNode) -> Result<String> {
+        // The functions declared in the crate shadow the built-in ones
+        if !self.functions.contains_key(call.path()) {
+            if let Some(builtin) = Builtin::from_name(call.path()) {
+                return self.builtin_call(builtin, call);
+            }
+        }
+        let function = self.function_symbol(call.path(), call.span())?.clone();
+        if function.params.len() != call.args().len() {
+            return Err(SemanticError::ArgumentCountMismatch {
+                name: call.path().into(),
+                expected: function.params.len(),
+                actual: call.args().len(),
+                span: call.span(),
+            }
+            .into());
+        }
+
+        // The arguments are evaluated from left to right (see the `ast::expr` module),
+        // so an argument evaluated before some statements has to be stored in a temporary
+        let has_statements = |arg: &dyn ExprASTNode, ty: &Type| {
+            !is_simple(arg.as_ast())
+                || (*ty == Type::Unit
+                    && !matches!(
+                        NodeKind::of(arg.as_ast()),
+                        NodeKind::Literal | NodeKind::Path
+                    ))
+        };
+        let args = call.args();
+        let mut values = Vec::with_capacity(args.len());
+        for (i, (arg, ty)) in args.iter().zip(&function.params).enumerate() {
+            if *ty == Type::Unit {
+                // The unit arguments are not passed, so they are only evaluated
+                if has_statements(arg.as_ref(), ty) {
+                    self.expr_into(arg.as_ref(), &Dest::Discard)?;
+                }
+                continue;
+            }
+            let mut value = self.value(arg.as_ref())?;
+            let later_statements = args[i + 1..]
+                .iter()
+                .zip(&function.params[i + 1..])
+                .any(|(arg, ty)| has_statements(arg.as_ref(), ty));
+            if later_statements && !is_literal(arg.as_ref()) {
+                value = self.temporary(value, ty);
+            }
+            values.push(value);
+        }
+        Ok(format!("{}({})", function.name, values.join(", ")))
+    }
+
+    /// Returns the C expression of the call to the given [built-in function](crate::builtin).
+    ///
+    /// The float functions are the ones from `math.h` (with the same handling of `NaN`),
+    /// and the integer ones are conditional expressions.
+    fn builtin_call(&mut self, builtin: Builtin, call: &FunCallASTNode) -> Result<String> {
+        let args = call.args();
+        if builtin.param_count() != args.len() {
+            return Err(SemanticError::ArgumentCountMismatch {
+                name: call.path().into(),
+                expected: builtin.param_count(),
+                actual: args.len(),
+                span: call.span(),
+            }
+            .into());
+        }
+        let ty = self.type_of(args[0].as_ref())?;
+        if !builtin.supports(&ty) {
+            return Err(TranspileError::Unsupported {
+                message: format!("Function `{}` cannot be called with `{}`", builtin, ty).into(),
+                span: args[0].span(),
+            });
+        }
+
+        // The operands of a conditional expression are used more than once,
+        // so they are stored in temporaries unless they are variables or literals
+        let mut values = Vec::with_capacity(args.len());
+        for (i, arg) in args.iter().enumerate() {
+            let mut value = self.value(arg.as_ref())?;
+            let later_statements = args[i + 1..].iter().any(|arg| !is_simple(arg.as_ast()));
+            let reused = ty.is_integer()
+                && !matches!(
+                    NodeKind::of(arg.as_ast()),
+                    NodeKind::Literal | NodeKind::Path
+                );
+            if (later_statements && !is_literal(arg.as_ref())) || reused {
+                value = self.temporary(value, &ty);
+            }
+            values.push(value);
+        }
+
+        if ty.is_float() {
+            self.uses_math = true;
+            let name = match builtin {
+                Builtin::Min => "fmin",
+                Builtin::Max => "fmax",
+                Builtin::Abs => "fabs",
+                Builtin::Sqrt => "sqrt",
+            };
+            let suffix = if ty == Type::F32 { "f" } else { "" };
+            return Ok(format!("{name}{suffix}({})", values.join(", ")));
+        }
+        let a = &values[0];
+        Ok(match builtin {
+            Builtin::Min => format!("{a} < {b} ? {a} : {b}", b = values[1]),
+            Builtin::Max => format!("{a} > {b} ? {a} : {b}", b = values[1]),
+            Builtin::Abs => format!("{a} < 0 ? -{a} : {a}"),
+            Builtin::Sqrt => unreachable!("Only the floats have a square root"),
+        })
+    }
+
+    fn arith(&mut self, arith: &ArithExprASTNode) -> Result<String> {
+        let ty = self.type_of(arith.lhs())?;
+        if ty == Type::Unit {
+            return Err(TranspileError::Unsupported {
+                message: "Cannot perform arithmetic operations on unit type".into(),
+                span: arith.span(),
+            });
+        }
+        let (lhs, rhs) = self.operands(arith.lhs(), arith.rhs())?;
+        let operator = arith.operator();
+        if ty.is_float() && operator == ArithOperator::Rem {
+            self.uses_math = true;
+            return Ok(format!("fmod({lhs}, {rhs})"));
+        }
+        Ok(format!("{lhs} {} {rhs}", arith_operator(operator)))
+    }
+
+    fn comp(&mut self, comp: &CompExprASTNode) -> Result<String> {
+        if self.type_of(comp.lhs())? == Type::Unit {
+            // The unit values are always equal (the operands are still evaluated)
+            self.expr_into(comp.lhs(), &Dest::Discard)?;
+            self.expr_into(comp.rhs(), &Dest::Discard)?;
+            let result = matches!(
+                comp.operator(),
+                CompOperator::Eq | CompOperator::Ge | CompOperator::Le
+            );
+            return Ok(result.to_string());
+        }
+        let (lhs, rhs) = self.operands(comp.lhs(), comp.rhs())?;
+        Ok(format!("{lhs} {} {rhs}", comp_operator(comp.operator())))
+    }
+
+    fn lazy_bool(&mut self, lazy_bool: &LazyBoolExprASTNode) -> Result<String> {
+        let operator = lazy_bool.operator();
+        if is_simple(lazy_bool.rhs().as_ast()) {
+            let lhs = self.operand(lazy_bool.lhs())?;
+            let rhs = self.operand(lazy_bool.rhs())?;
+            let operator = match operator {
+                LazyBoolOperator::And => "&&",
+                LazyBoolOperator::Or => "||",
+            };
+            return Ok(format!("{lhs} {operator} {rhs}"));
+        }
+
+        // The statements of the right-hand side are only evaluated if the left-hand side
+        // doesn't determine the result
+        let lhs = self.value(lazy_bool.lhs())?;
+        let result = self.temporary(lhs, &Type::Bool);
+        let condition = match operator {
+            LazyBoolOperator::And => result.clone(),
+            LazyBoolOperator::Or => format!("!{result}"),
+        };
+        self.line(format_args!("if ({condition}) {{"));
+        self.indented(|this| this.expr_into(lazy_bool.rhs(), &Dest::Var(result.clone())))?;
+        self.line("}");
+        Ok(result)
+    }
+    //#endregion
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use crate::test_util::{parse, TempDir};
+
+    use super::*;
+
+    #[test]
+    fn test_to_c() {
+        let crt = parse(
+            "static LIMIT: i32 = 10 * 2;\n\
+            pub static mut COUNTER: i32 = 0;\n\
+            fn double(x: i32) -> i32 { return x * 2; }\n\
+            fn main() -> i32 {\n\
+                let x: i32 = 1;\n\
+                let x: i32 = if x > 0 { x + 1 } else { 0 };\n\
+                let int: i32 = double(x) + { let y: i32 = x; y * 3 };\n\
+                while COUNTER < LIMIT && int > 0 { COUNTER = COUNTER + 1; };\n\
+                let mut sum: i32 = 0;\n\
+                for i in 1..=4 { sum = sum + i; }\n\
+                for _ in 0..x { sum = sum - 4; }\n\
+                let rem: f64 = 7.5 % 2.0;\n\
+                let m: i32 = max(double(align_of::<u8>()), min(-3, x)) + abs(-2);\n\
+                loop { return COUNTER + int + sum + ((rem + sqrt(2.25)) as i32) + m; }\n\
+            }\n",
+        );
+        let source = to_c(&crt).unwrap();
+        assert!(source.contains("#include <math.h>"));
+        assert!(source.contains("static const int32_t LIMIT = 20;"));
+        assert!(source.contains("\nint32_t COUNTER = 0;"));
+        assert!(source.contains("static int32_t double_(int32_t);"));
+        assert!(source.contains("\nint main(void) {"));
+        assert!(source.contains("    int32_t x_1;\n    if (x > 0) {"));
+        assert!(source.contains("while ((COUNTER < LIMIT) && (int_ > 0)) {"));
+        assert!(source.contains("for (int32_t i = 1; i <= 4; i++) {"));
+        assert!(source.contains("(rem + sqrt(2.25))"));
+
+        // The generated code is valid C, and it computes the same result
+        let dir = TempDir::new("transpile");
+        let c_source = dir.write("main.c", &source);
+        let program = dir.join("main");
+        let status = Command::new("cc")
+            .arg("-o")
+            .arg(&program)
+            .arg(&c_source)
+            .arg("-lm")
+            .status()
+            .unwrap();
+        assert!(status.success(), "{}", source);
+        // `x` is 2, `int` is 4 + 6, `COUNTER` stops at 20, `sum` is 10 - 8, `rem` is 1.5
+        // (plus the square root 1.5) and `m` is 2 + 2
+        assert_eq!(Command::new(&program).status().unwrap().code(), Some(39));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| to_c(&parse(source)).unwrap_err();
+
+        assert!(matches!(
+            error("fn f() { g(); }\n"),
+            TranspileError::MissingSymbol { .. }
+        ));
+        assert!(matches!(
+            error("fn f(x: i32) { f(); }\n"),
+            TranspileError::SemanticError(SemanticError::ArgumentCountMismatch { .. })
+        ));
+        assert!(matches!(
+            error("fn f() -> i32 { return sqrt(4); }\n"),
+            TranspileError::Unsupported { .. }
+        ));
+        assert!(matches!(
+            error("use other::f;\nfn main() { f(); }\n"),
+            TranspileError::Unsupported { .. }
+        ));
+        assert!(matches!(
+            error("fn f() -> i32 { let t: i32 = 1; return (t, 2).1; }\n"),
+            TranspileError::Unsupported { .. }
+        ));
+        assert!(matches!(
+            error("fn f(x: i32) -> i32 { return *&x; }\n"),
+            TranspileError::Unsupported { .. }
+        ));
+        assert!(matches!(
+            error("fn f(r: &mut i32) {}\n"),
+            TranspileError::Unsupported { .. }
+        ));
+        assert!(matches!(
+            error("fn f() -> i32 { return 1; }\nstatic X: i32 = f();\n"),
+            TranspileError::NonConstantInitializer { .. }
+        ));
+    }
+}
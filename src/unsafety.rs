@@ -0,0 +1,164 @@
+//! A module containing the unsafety checking of `unsafe` blocks.
+//!
+//! The operations that require an `unsafe` block are the uses of:
+//! - the functions and statics declared in an extern block,
+//! - the mutable statics.
+
+use std::collections::HashSet;
+
+use crate::ast::error::SemanticWarning;
+use crate::ast::{ExternItem, ItemASTNode};
+use crate::resolve::ResolutionMap;
+use crate::token::Span;
+
+/// The state of the unsafety checking.
+///
+/// The check is run by calling [`ASTNode::check_unsafety`](crate::ast::ASTNode::check_unsafety)
+/// on the root of the AST. The `unsafe` blocks are [entered](UnsafetyChecker::enter_unsafe) and
+/// [exited](UnsafetyChecker::exit_unsafe) by the nodes, and the paths [used](UnsafetyChecker::use_path)
+/// in between are attributed to the outermost one.
+pub struct UnsafetyChecker {
+    /// The spans of all the references to the symbols that require an `unsafe` block.
+    unsafe_refs: HashSet<Span>,
+    open: Vec<UnsafeBlock>,
+    blocks: Vec<UnsafeBlock>,
+}
+
+/// The metadata of an `unsafe` block gathered by the unsafety checking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsafeBlock {
+    span: Span,
+    operations: Vec<Span>,
+}
+
+impl UnsafeBlock {
+    /// Returns the span of the block.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the spans of the operations inside the block that require it.
+    ///
+    /// An operation inside nested `unsafe` blocks belongs only to the outermost one.
+    pub fn operations(&self) -> &[Span] {
+        &self.operations
+    }
+
+    /// Returns whether anything inside the block actually requires it.
+    pub fn is_used(&self) -> bool {
+        !self.operations.is_empty()
+    }
+
+    /// Returns the [`SemanticWarning::UnusedUnsafe`] for the block, if it is not used.
+    pub fn unused_warning(&self) -> Option<SemanticWarning> {
+        (!self.is_used()).then_some(SemanticWarning::UnusedUnsafe { span: self.span })
+    }
+}
+
+impl UnsafetyChecker {
+    /// Creates a new `UnsafetyChecker` for a crate with the given items
+    /// and the [resolved names](crate::ast::Crate::resolve_names).
+    pub fn new(items: &[ItemASTNode], map: &ResolutionMap) -> UnsafetyChecker {
+        let mut unsafe_decls = Vec::new();
+        for item in items {
+            match item {
+                ItemASTNode::Func(_) => {}
+                ItemASTNode::Static(stat) => {
+                    if stat.is_mutable() {
+                        unsafe_decls.push(stat.name_span());
+                    }
+                }
+                ItemASTNode::Extern(ext) => {
+                    unsafe_decls.extend(ext.items().iter().map(ExternItem::name_span));
+                }
+            }
+        }
+
+        let unsafe_refs = unsafe_decls
+            .into_iter()
+            .flat_map(|decl| map.references(decl))
+            .collect();
+        UnsafetyChecker {
+            unsafe_refs,
+            open: Vec::new(),
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Enters an `unsafe` block with the given span.
+    pub fn enter_unsafe(&mut self, span: Span) {
+        self.open.push(UnsafeBlock {
+            span,
+            operations: Vec::new(),
+        });
+    }
+
+    /// Exits the current `unsafe` block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no `unsafe` block to exit.
+    pub fn exit_unsafe(&mut self) {
+        let block = self.open.pop().expect("There is no unsafe block to exit");
+        self.blocks.push(block);
+    }
+
+    /// Records a use of the path at the given span.
+    ///
+    /// If the path refers to a symbol that requires an `unsafe` block,
+    /// the use is attributed to the outermost `unsafe` block.
+    pub fn use_path(&mut self, span: Span) {
+        if self.unsafe_refs.contains(&span) {
+            if let Some(block) = self.open.first_mut() {
+                block.operations.push(span);
+            }
+        }
+    }
+
+    /// Finishes the check and returns all the `unsafe` blocks, ordered by their position.
+    pub fn finish(self) -> Vec<UnsafeBlock> {
+        let mut blocks = self.blocks;
+        blocks.sort_by_key(|b| b.span.start());
+        blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::parse;
+
+    use super::*;
+
+    #[test]
+    fn test_unused_unsafe() {
+        let crt = parse(
+            "extern \"C\" { fn print(n: i32); }\n\
+            static mut COUNT: i32 = 0;\n\
+            static LIMIT: i32 = 10;\n\
+            fn main() {\n\
+                unsafe { print(LIMIT); };\n\
+                unsafe { COUNT = COUNT + 1; };\n\
+                unsafe { let x: i32 = LIMIT; };\n\
+                unsafe { unsafe { print(1); }; };\n\
+                unsafe { let print: i32 = 1; let y: i32 = print; };\n\
+            }\n",
+        );
+        let blocks = crt.check_unsafety();
+
+        let used: Vec<bool> = blocks.iter().map(UnsafeBlock::is_used).collect();
+        assert_eq!(used, vec![true, true, false, true, false, false]);
+        assert_eq!(blocks[1].operations().len(), 2);
+
+        let warnings: Vec<SemanticWarning> = blocks
+            .iter()
+            .filter_map(UnsafeBlock::unused_warning)
+            .collect();
+        assert_eq!(warnings.len(), 3);
+        assert_eq!(
+            warnings[1],
+            SemanticWarning::UnusedUnsafe {
+                span: blocks[4].span()
+            }
+        );
+    }
+}
@@ -0,0 +1,127 @@
+//! Integration tests of calling across the `extern "C"` boundary between μRust and C.
+//!
+//! The μRust crate is compiled to an object file, linked with an object file compiled
+//! from C (using the [`cc`] crate), and the resulting executable is run.
+#![cfg(all(feature = "codegen", target_os = "linux", target_env = "gnu"))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use mini_rust_compiler_components::codegen::session::CompilationSession;
+use mini_rust_compiler_components::parser::Parser;
+
+/// Returns a fresh temporary directory for the test with the given name.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mrs_test_extern_c_{name}"));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Compiles the μRust source to an object file in the given directory.
+fn compile_mrs(dir: &Path, source: &str) -> PathBuf {
+    let source_path = dir.join("lib.mrs");
+    fs::write(&source_path, source).unwrap();
+    let crt = Parser::new(&source_path).unwrap().parse().unwrap();
+    let output = CompilationSession::new().compile(&crt).unwrap();
+
+    let object_path = dir.join("lib_mrs.o");
+    fs::write(&object_path, output.object()).unwrap();
+    object_path
+}
+
+/// Compiles the C source, links it with the given objects, runs the executable
+/// and returns its standard output.
+fn link_and_run(dir: &Path, c_source: &str, objects: &[PathBuf]) -> String {
+    let c_path = dir.join("main.c");
+    fs::write(&c_path, c_source).unwrap();
+
+    let target = format!("{}-unknown-linux-gnu", std::env::consts::ARCH);
+    let mut build = cc::Build::new();
+    build
+        .file(&c_path)
+        .target(&target)
+        .host(&target)
+        .opt_level(0)
+        .out_dir(dir)
+        .cargo_metadata(false)
+        .cargo_warnings(false);
+    let mut c_objects = build.compile_intermediates();
+    c_objects.extend_from_slice(objects);
+
+    let exe_path = dir.join("main");
+    let status = build
+        .get_compiler()
+        .to_command()
+        .args(&c_objects)
+        .arg("-o")
+        .arg(&exe_path)
+        .status()
+        .unwrap();
+    assert!(status.success(), "Linking failed");
+
+    let output = Command::new(&exe_path).output().unwrap();
+    assert!(output.status.success(), "The executable failed");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_call_c_from_mrs() {
+    let dir = test_dir("call_c");
+    let object = compile_mrs(
+        &dir,
+        "extern \"C\" {\n\
+            fn c_scale(x: i32, factor: f64) -> f64;\n\
+            fn c_is_even(n: i32) -> bool;\n\
+        }\n\
+        pub fn mrs_scale(x: i32) -> f64 {\n\
+            unsafe { return c_scale(x, 2.5); };\n\
+        }\n\
+        pub fn mrs_is_even(n: i32) -> bool {\n\
+            unsafe { return c_is_even(n); };\n\
+        }\n",
+    );
+
+    let stdout = link_and_run(
+        &dir,
+        "#include <stdbool.h>\n\
+        #include <stdio.h>\n\
+        double c_scale(int x, double factor) { return x * factor; }\n\
+        bool c_is_even(int n) { return n % 2 == 0; }\n\
+        double mrs_scale(int x);\n\
+        bool mrs_is_even(int n);\n\
+        int main(void) {\n\
+            printf(\"%.1f %d %d\\n\", mrs_scale(4), mrs_is_even(6), mrs_is_even(7));\n\
+            return 0;\n\
+        }\n",
+        &[object],
+    );
+    assert_eq!(stdout, "10.0 1 0\n");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_call_mrs_from_c() {
+    let dir = test_dir("call_mrs");
+    let object = compile_mrs(
+        &dir,
+        "pub fn mrs_add(a: i32, b: i32) -> i32 { return a + b; }\n\
+        pub fn mrs_div(a: f64, b: f64) -> f64 { return a / b; }\n\
+        fn helper() -> i32 { return 1; }\n",
+    );
+
+    let stdout = link_and_run(
+        &dir,
+        "#include <stdio.h>\n\
+        int mrs_add(int a, int b);\n\
+        double mrs_div(double a, double b);\n\
+        int main(void) {\n\
+            printf(\"%d %.2f\\n\", mrs_add(-3, 10), mrs_div(1.0, 4.0));\n\
+            return 0;\n\
+        }\n",
+        &[object],
+    );
+    assert_eq!(stdout, "7 0.25\n");
+    fs::remove_dir_all(&dir).unwrap();
+}